@@ -0,0 +1,83 @@
+//! Serialization and deserialization of single-column blocks in the `Native` format, without a server.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use klickhouse::{block::Block, Type, Value};
+
+mod util;
+
+fn block(rows: u64, type_: Type, value: impl Fn(u64) -> Value) -> Block {
+    let mut block = Block::default();
+    block
+        .insert_column("column", type_, (0..rows).map(value).collect())
+        .unwrap();
+    block
+}
+
+fn bench_blocks(c: &mut Criterion) {
+    let rows = util::rows();
+    let columns: Vec<(&str, Block)> = vec![
+        ("UInt64", block(rows, Type::UInt64, Value::UInt64)),
+        (
+            "Float64",
+            block(rows, Type::Float64, |x| Value::Float64(x as f64 / 3.0)),
+        ),
+        (
+            "String",
+            block(rows, Type::String, |x| {
+                Value::String(format!("value number {x}").into_bytes())
+            }),
+        ),
+        (
+            "LowCardinality(String)",
+            block(rows, Type::LowCardinality(Box::new(Type::String)), |x| {
+                Value::String(format!("category {}", x % 64).into_bytes())
+            }),
+        ),
+        (
+            "Array(UInt32)",
+            block(rows, Type::Array(Box::new(Type::UInt32)), |x| {
+                Value::Array((0..x % 8).map(|y| Value::UInt32(y as u32)).collect())
+            }),
+        ),
+        (
+            "Map(String, UInt64)",
+            block(
+                rows,
+                Type::Map(Box::new(Type::String), Box::new(Type::UInt64)),
+                |x| {
+                    let keys = (0..x % 4)
+                        .map(|y| Value::String(format!("key{y}").into_bytes()))
+                        .collect();
+                    let values = (0..x % 4).map(Value::UInt64).collect();
+                    Value::Map(keys, values)
+                },
+            ),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("block");
+    group.throughput(Throughput::Elements(rows));
+    for (name, block) in &columns {
+        group.bench_function(format!("serialize {name}"), |b| {
+            b.iter_batched(
+                || block.clone(),
+                |block| {
+                    let mut out = vec![];
+                    block.write_native(&mut out).unwrap();
+                    out
+                },
+                BatchSize::LargeInput,
+            )
+        });
+
+        let mut encoded = vec![];
+        block.clone().write_native(&mut encoded).unwrap();
+        group.bench_function(format!("deserialize {name}"), |b| {
+            b.iter(|| Block::read_native(&mut &encoded[..]).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_blocks);
+criterion_main!(benches);
@@ -0,0 +1,111 @@
+//! End-to-end inserts and selects against a live server, configured like the integration tests (`KLICKHOUSE_TEST_*`).
+//! Skipped if no server is reachable.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use futures_util::StreamExt;
+use klickhouse::{test_util, Row, UnitValue};
+
+mod util;
+
+#[derive(Row, Debug, Clone)]
+struct BenchRow {
+    id: u64,
+    name: String,
+    value: f64,
+    tags: Vec<String>,
+}
+
+fn bench_client(c: &mut Criterion) {
+    let rows = util::rows();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let client = match runtime.block_on(test_util::get_client()) {
+        Ok(client) => client,
+        Err(e) => {
+            println!("skipping client benchmarks, no server available: {e}");
+            return;
+        }
+    };
+    runtime.block_on(async {
+        client
+            .execute("DROP TABLE IF EXISTS klickhouse_bench")
+            .await
+            .unwrap();
+        client
+            .execute(
+                "CREATE TABLE klickhouse_bench (id UInt64, name String, value Float64, tags Array(LowCardinality(String))) ENGINE = Memory",
+            )
+            .await
+            .unwrap();
+    });
+
+    let data = (0..rows)
+        .map(|id| BenchRow {
+            id,
+            name: format!("row {id}"),
+            value: id as f64 / 7.0,
+            tags: (0..id % 4).map(|x| format!("tag{x}")).collect(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("client");
+    group.sample_size(10).throughput(Throughput::Elements(rows));
+
+    group.bench_function("insert", |b| {
+        b.to_async(&runtime).iter_batched(
+            || data.clone(),
+            |rows| async {
+                client
+                    .execute("TRUNCATE TABLE klickhouse_bench")
+                    .await
+                    .unwrap();
+                client
+                    .insert_native_block("INSERT INTO klickhouse_bench FORMAT native", rows)
+                    .await
+                    .unwrap();
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("select rows", |b| {
+        b.to_async(&runtime).iter(|| async {
+            client
+                .query_collect::<BenchRow>("SELECT * FROM klickhouse_bench")
+                .await
+                .unwrap()
+        })
+    });
+
+    group.bench_function("select scalar column", |b| {
+        b.to_async(&runtime).iter(|| async {
+            client
+                .query_collect::<UnitValue<u64>>("SELECT id FROM klickhouse_bench")
+                .await
+                .unwrap()
+        })
+    });
+
+    group.bench_function("select raw blocks", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let mut blocks = client
+                .query_raw(format!(
+                    "SELECT number AS id, toString(number) AS name FROM system.numbers LIMIT {rows}"
+                ))
+                .await
+                .unwrap();
+            let mut rows = 0;
+            while let Some(block) = blocks.next().await {
+                rows += block.unwrap().rows;
+            }
+            rows
+        })
+    });
+    group.finish();
+
+    runtime.block_on(async {
+        client.execute("DROP TABLE klickhouse_bench").await.unwrap();
+    });
+}
+
+criterion_group!(benches, bench_client);
+criterion_main!(benches);
@@ -0,0 +1,142 @@
+//! Deserialization of derived rows from the columns of a block, without a server.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use klickhouse::{ColumnLookup, Row, RowAccessor, Type, Value};
+
+mod util;
+
+#[derive(Row)]
+#[allow(dead_code)]
+struct Event {
+    id: u64,
+    user_id: u64,
+    session_id: u64,
+    kind: String,
+    page: String,
+    referrer: String,
+    duration_ms: u32,
+    score: f64,
+}
+
+#[derive(Row)]
+#[allow(dead_code)]
+struct Location {
+    country: String,
+    city: String,
+}
+
+#[derive(Row)]
+#[allow(dead_code)]
+struct FlatEvent {
+    id: u64,
+    user_id: u64,
+    kind: String,
+    #[klickhouse(flatten)]
+    location: Location,
+}
+
+/// Rows of `columns` as the values of each row, in column order.
+fn row_values(rows: u64, columns: &[(&str, &Type)]) -> Vec<Vec<Value>> {
+    (0..rows)
+        .map(|x| {
+            columns
+                .iter()
+                .map(|(_, type_)| match type_ {
+                    Type::UInt64 => Value::UInt64(x),
+                    Type::UInt32 => Value::UInt32(x as u32),
+                    Type::Float64 => Value::Float64(x as f64 / 3.0),
+                    _ => Value::String(format!("value {}", x % 64).into_bytes()),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn bench<T: Row>(c: &mut Criterion, name: &str, columns: &[(&str, &Type)]) {
+    let rows = util::rows();
+    let data = row_values(rows, columns);
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(rows));
+    group.bench_function("deserialize_row", |b| {
+        b.iter_batched(
+            || data.clone(),
+            |rows| {
+                rows.into_iter()
+                    .map(|values| {
+                        let row = columns
+                            .iter()
+                            .zip(values)
+                            .map(|((name, type_), value)| (*name, *type_, value))
+                            .collect();
+                        T::deserialize_row(row).unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    // a lookup per row finds the columns of every row by name again
+    group.bench_function("accessor, lookup per row", |b| {
+        b.iter_batched(
+            || data.clone(),
+            |rows| {
+                rows.into_iter()
+                    .map(|mut values| {
+                        let lookup = ColumnLookup::default();
+                        T::deserialize_accessor(RowAccessor::new(columns, &mut values, &lookup))
+                            .unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("accessor, lookup per block", |b| {
+        b.iter_batched(
+            || data.clone(),
+            |rows| {
+                let lookup = ColumnLookup::default();
+                rows.into_iter()
+                    .map(|mut values| {
+                        T::deserialize_accessor(RowAccessor::new(columns, &mut values, &lookup))
+                            .unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_rows(c: &mut Criterion) {
+    // columns in a different order than the fields, so matching by position doesn't apply
+    bench::<Event>(
+        c,
+        "Event",
+        &[
+            ("score", &Type::Float64),
+            ("duration_ms", &Type::UInt32),
+            ("referrer", &Type::String),
+            ("page", &Type::String),
+            ("kind", &Type::String),
+            ("session_id", &Type::UInt64),
+            ("user_id", &Type::UInt64),
+            ("id", &Type::UInt64),
+        ],
+    );
+    bench::<FlatEvent>(
+        c,
+        "FlatEvent",
+        &[
+            ("city", &Type::String),
+            ("country", &Type::String),
+            ("kind", &Type::String),
+            ("user_id", &Type::UInt64),
+            ("id", &Type::UInt64),
+        ],
+    );
+}
+
+criterion_group!(benches, bench_rows);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+//! Settings shared by the benches.
+
+/// Rows per block or insert, from `KLICKHOUSE_BENCH_ROWS` (default `1000000`).
+pub fn rows() -> u64 {
+    std::env::var("KLICKHOUSE_BENCH_ROWS")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(1_000_000)
+}
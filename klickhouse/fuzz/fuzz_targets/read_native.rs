@@ -0,0 +1,15 @@
+//! Feeds arbitrary bytes to the `Native` block decoder, which runs every column deserializer
+//! against untrusted type names and data. Run with `cargo +nightly fuzz run read_native`.
+#![no_main]
+
+use klickhouse::block::Block;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut data = data;
+    while !data.is_empty() {
+        if Block::read_native(&mut data).is_err() {
+            break;
+        }
+    }
+});
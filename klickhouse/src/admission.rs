@@ -0,0 +1,158 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tokio::sync::oneshot;
+
+use crate::{KlickhouseError, QueryPriority, Result};
+
+#[derive(Default)]
+struct AdmissionState {
+    /// Queries holding a slot, from submission until they end
+    admitted: usize,
+    /// Submissions waiting for a slot, by priority from high to low, each in FIFO order
+    waiting: [VecDeque<oneshot::Sender<AdmissionPermit>>; 3],
+}
+
+/// Limits the queries submitted to a connection to [`ClientOptions::max_concurrent_queries`](crate::ClientOptions::max_concurrent_queries).
+/// When a query ends, its slot goes to the longest waiting submission of the highest priority.
+pub(crate) struct Admission {
+    limit: Option<usize>,
+    state: Mutex<AdmissionState>,
+    /// Admitted queries the connection hasn't dispatched yet, maintained by the connection task
+    pending: AtomicUsize,
+}
+
+fn priority_index(priority: QueryPriority) -> usize {
+    match priority {
+        QueryPriority::High => 0,
+        QueryPriority::Normal => 1,
+        QueryPriority::Low => 2,
+    }
+}
+
+impl Admission {
+    pub fn new(limit: Option<usize>) -> Arc<Self> {
+        Arc::new(Self {
+            limit: limit.map(|x| x.max(1)),
+            state: Mutex::new(AdmissionState::default()),
+            pending: AtomicUsize::new(0),
+        })
+    }
+
+    /// Waits for a slot, behind earlier submissions of the same or a higher priority.
+    pub async fn acquire(self: &Arc<Self>, priority: QueryPriority) -> Result<AdmissionPermit> {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if self.limit.map_or(true, |x| state.admitted < x) {
+                state.admitted += 1;
+                return Ok(AdmissionPermit {
+                    admission: Some(self.clone()),
+                });
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.waiting[priority_index(priority)].push_back(sender);
+            receiver
+        };
+        receiver.await.map_err(|e| {
+            KlickhouseError::ProtocolError(format!("failed to wait for query admission: {e}"))
+        })
+    }
+
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let Some(waiter) = state.waiting.iter_mut().find_map(VecDeque::pop_front) else {
+                state.admitted -= 1;
+                return;
+            };
+            // a waiter that gave up hands the slot back, which is passed on without releasing it
+            match waiter.send(AdmissionPermit {
+                admission: Some(self.clone()),
+            }) {
+                Ok(()) => return,
+                Err(mut permit) => permit.admission = None,
+            }
+        }
+    }
+
+    pub fn set_pending(&self, pending: usize) {
+        self.pending.store(pending, Ordering::Relaxed);
+    }
+
+    /// Queries submitted but not yet sent to the server, waiting for a slot or in the connection's queue.
+    pub fn queued(&self) -> usize {
+        let waiting = self
+            .state
+            .lock()
+            .unwrap()
+            .waiting
+            .iter()
+            .map(|x| x.iter().filter(|x| !x.is_closed()).count())
+            .sum::<usize>();
+        waiting + self.pending.load(Ordering::Relaxed)
+    }
+}
+
+/// A slot of [`Admission`], released when dropped.
+pub(crate) struct AdmissionPermit {
+    admission: Option<Arc<Admission>>,
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        if let Some(admission) = self.admission.take() {
+            admission.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_admission_order() {
+        let admission = Admission::new(Some(1));
+        let first = admission.acquire(QueryPriority::Normal).await.unwrap();
+
+        let (order, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let mut waiters = vec![];
+        for (name, priority) in [
+            ("low", QueryPriority::Low),
+            ("normal 1", QueryPriority::Normal),
+            ("abandoned", QueryPriority::High),
+            ("high", QueryPriority::High),
+            ("normal 2", QueryPriority::Normal),
+        ] {
+            let admission = admission.clone();
+            let order = order.clone();
+            waiters.push(tokio::spawn(async move {
+                let permit = admission.acquire(priority).await.unwrap();
+                order.send(name).unwrap();
+                drop(permit);
+            }));
+            tokio::task::yield_now().await;
+        }
+        waiters.remove(2).abort();
+        tokio::task::yield_now().await;
+        assert_eq!(admission.queued(), 4);
+
+        drop(first);
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+        drop(order);
+        let mut names = vec![];
+        while let Some(name) = received.recv().await {
+            names.push(name);
+        }
+        assert_eq!(names, ["high", "normal 1", "normal 2", "low"]);
+        assert_eq!(admission.queued(), 0);
+        assert_eq!(admission.state.lock().unwrap().admitted, 0);
+    }
+}
@@ -0,0 +1,140 @@
+use chrono::{Days, NaiveDate};
+
+use crate::{Client, Date, Progress, QueryBuilder, Result, ToSql};
+
+/// A chunk of a [`Backfill`] that completed.
+#[derive(Debug, Clone)]
+pub struct BackfillChunk<'a, K> {
+    /// Index of the chunk in the backfill's ranges, pass `index + 1` to [`Backfill::resume_from`] to continue after it.
+    pub index: usize,
+    /// Total number of chunks in the backfill
+    pub chunks: usize,
+    /// Lower (inclusive) bound of the chunk
+    pub start: &'a K,
+    /// Upper (exclusive) bound of the chunk
+    pub end: &'a K,
+    /// Progress reported by the server for this chunk
+    pub progress: Progress,
+    /// Sum of the progress of all chunks run so far, excluding any skipped by [`Backfill::resume_from`]
+    pub total: Progress,
+}
+
+/// Runs a large `INSERT INTO ... SELECT` in bounded chunks, one query at a time, i.e. to migrate a table without a single long-running query.
+///
+/// The query binds the lower (inclusive) bound of each chunk to `$1` and the upper (exclusive) bound to `$2`, i.e.
+/// `INSERT INTO events_v2 SELECT * FROM events WHERE id >= $1 AND id < $2`.
+/// To resume after a failure, record the last completed [`BackfillChunk::index`] and run the same backfill with [`Backfill::resume_from`].
+#[derive(Debug, Clone)]
+pub struct Backfill<K> {
+    query: String,
+    ranges: Vec<(K, K)>,
+    resume_from: usize,
+}
+
+impl<K: ToSql + Clone> Backfill<K> {
+    pub fn new(query: impl Into<String>, ranges: impl IntoIterator<Item = (K, K)>) -> Self {
+        Self {
+            query: query.into(),
+            ranges: ranges.into_iter().collect(),
+            resume_from: 0,
+        }
+    }
+
+    /// Skips the first `chunk` chunks, i.e. those that completed in an earlier run.
+    pub fn resume_from(mut self, chunk: usize) -> Self {
+        self.resume_from = chunk;
+        self
+    }
+
+    /// Number of chunks, including skipped ones.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Runs the remaining chunks in order, calling `on_chunk` after each one completes. Returns the progress summed over all chunks run.
+    /// Stops at the first failing chunk. Inserts are not atomic across chunks, so the failed chunk may have been partially inserted.
+    pub async fn run(
+        &self,
+        client: &Client,
+        mut on_chunk: impl FnMut(&BackfillChunk<'_, K>),
+    ) -> Result<Progress> {
+        let mut total = Progress::default();
+        for (index, (start, end)) in self.ranges.iter().enumerate().skip(self.resume_from) {
+            let query = QueryBuilder::new(&self.query)
+                .arg(start.clone())
+                .arg(end.clone());
+            let progress = client.execute_with_progress(query).await?;
+            total += progress;
+            on_chunk(&BackfillChunk {
+                index,
+                chunks: self.ranges.len(),
+                start,
+                end,
+                progress,
+                total,
+            });
+        }
+        Ok(total)
+    }
+}
+
+impl Backfill<u64> {
+    /// Chunks `start..end` into ranges of `step` (at least 1) ids.
+    pub fn by_id(query: impl Into<String>, start: u64, end: u64, step: u64) -> Self {
+        let step = step.max(1);
+        let ranges = (start..end)
+            .step_by(step.try_into().unwrap_or(usize::MAX))
+            .map(|x| (x, x.saturating_add(step).min(end)));
+        Self::new(query, ranges)
+    }
+}
+
+impl Backfill<Date> {
+    /// Chunks the dates `start..end` into ranges of `days` (at least 1) days.
+    /// Dates outside the range of the `Date` type (1970-01-01 to 2149-06-06) are clamped to it.
+    pub fn by_day(query: impl Into<String>, start: NaiveDate, end: NaiveDate, days: u64) -> Self {
+        let clamp = |date: NaiveDate| {
+            Date::from(date.clamp(NaiveDate::from(Date(0)), NaiveDate::from(Date(u16::MAX))))
+        };
+        let mut ranges = vec![];
+        let mut current = start;
+        while current < end {
+            let next = current
+                .checked_add_days(Days::new(days.max(1)))
+                .unwrap_or(NaiveDate::MAX)
+                .min(end);
+            ranges.push((clamp(current), clamp(next)));
+            current = next;
+        }
+        ranges.retain(|(start, end)| start != end);
+        Self::new(query, ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges() {
+        let backfill = Backfill::by_id("", 10, 35, 10).resume_from(1);
+        assert_eq!(backfill.ranges, vec![(10, 20), (20, 30), (30, 35)]);
+        assert_eq!(backfill.len(), 3);
+        assert!(Backfill::by_id("", 5, 5, 10).is_empty());
+
+        let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        let backfill = Backfill::by_day("", date(1), date(10), 4);
+        assert_eq!(
+            backfill.ranges,
+            vec![
+                (date(1).into(), date(5).into()),
+                (date(5).into(), date(9).into()),
+                (date(9).into(), date(10).into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Storage of blobs too large for a single `String` value (see [`MAX_STRING_SIZE`]), or for the blocks of an insert,
+//! as rows of chunks in a table like:
+//!
+//! ```sql
+//! CREATE TABLE blobs (blob_id String, seq UInt32, last Bool, data String)
+//! ENGINE = ReplacingMergeTree ORDER BY (blob_id, seq)
+//! ```
+//!
+//! [`Client::insert_blob`] streams a blob into such a table one chunk per block, and [`assemble_blobs`] joins the chunks
+//! of a query ordered by `blob_id, seq` back together.
+
+use std::borrow::Cow;
+
+use futures_util::{stream, Stream, StreamExt};
+use indexmap::IndexMap;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{
+    Bytes, Client, FromSql, InsertResult, KlickhouseError, ParsedQuery, QueryOptions, Result, Row,
+    ToSql, Type, Value, MAX_STRING_SIZE,
+};
+
+/// Size of chunks used by [`split_blob`] and [`Client::insert_blob`] unless told otherwise, 1 MiB.
+pub const DEFAULT_BLOB_CHUNK_SIZE: usize = 1 << 20;
+
+/// One row of a chunked blob. The chunks of a blob are numbered from 0 by `seq`, and only the final one is `last`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BlobChunk {
+    pub blob_id: String,
+    pub seq: u32,
+    pub last: bool,
+    pub data: Bytes,
+}
+
+impl Row for BlobChunk {
+    const COLUMN_COUNT: Option<usize> = Some(4);
+
+    fn column_names() -> Option<Vec<Cow<'static, str>>> {
+        Some(vec![
+            "blob_id".into(),
+            "seq".into(),
+            "last".into(),
+            "data".into(),
+        ])
+    }
+
+    fn deserialize_row(map: Vec<(&str, &Type, Value)>) -> Result<Self> {
+        let mut out = BlobChunk::default();
+        let mut seen = [false; 4];
+        for (name, type_, value) in map {
+            let index = match name {
+                "blob_id" => {
+                    out.blob_id = String::from_sql(type_, value)?;
+                    0
+                }
+                "seq" => {
+                    out.seq = u32::from_sql(type_, value)?;
+                    1
+                }
+                "last" => {
+                    out.last = bool::from_sql(type_, value)?;
+                    2
+                }
+                "data" => {
+                    out.data = Bytes::from_sql(type_, value)?;
+                    3
+                }
+                name => {
+                    return Err(KlickhouseError::DeserializeError(format!(
+                        "unexpected column {name} for blob chunk"
+                    )))
+                }
+            };
+            if std::mem::replace(&mut seen[index], true) {
+                return Err(KlickhouseError::DuplicateField(
+                    ["blob_id", "seq", "last", "data"][index],
+                ));
+            }
+        }
+        if let Some(index) = seen.iter().position(|x| !x) {
+            return Err(KlickhouseError::MissingField(
+                ["blob_id", "seq", "last", "data"][index],
+            ));
+        }
+        Ok(out)
+    }
+
+    fn serialize_row(
+        self,
+        type_hints: &IndexMap<String, Type>,
+    ) -> Result<Vec<(Cow<'static, str>, Value)>> {
+        Ok(vec![
+            (
+                "blob_id".into(),
+                self.blob_id.to_sql(type_hints.get("blob_id"))?,
+            ),
+            ("seq".into(), self.seq.to_sql(type_hints.get("seq"))?),
+            ("last".into(), self.last.to_sql(type_hints.get("last"))?),
+            ("data".into(), self.data.to_sql(type_hints.get("data"))?),
+        ])
+    }
+}
+
+fn check_chunk_size(chunk_size: usize) -> Result<()> {
+    if chunk_size == 0 || chunk_size > MAX_STRING_SIZE {
+        return Err(KlickhouseError::QueryArgumentError(format!(
+            "blob chunk size must be in 1..={MAX_STRING_SIZE}, got {chunk_size}"
+        )));
+    }
+    Ok(())
+}
+
+/// Splits `data` into chunks of up to `chunk_size` bytes. An empty blob is a single empty chunk.
+pub fn split_blob(
+    blob_id: impl Into<String>,
+    data: &[u8],
+    chunk_size: usize,
+) -> Result<Vec<BlobChunk>> {
+    check_chunk_size(chunk_size)?;
+    let blob_id = blob_id.into();
+    let count = data.len().div_ceil(chunk_size).max(1);
+    if count > u32::MAX as usize {
+        return Err(KlickhouseError::QueryArgumentError(format!(
+            "blob of {} bytes has too many chunks of {chunk_size} bytes",
+            data.len()
+        )));
+    }
+    Ok((0..count)
+        .map(|seq| BlobChunk {
+            blob_id: blob_id.clone(),
+            seq: seq as u32,
+            last: seq + 1 == count,
+            data: Bytes(data[seq * chunk_size..data.len().min((seq + 1) * chunk_size)].to_vec()),
+        })
+        .collect())
+}
+
+/// Reads up to `chunk_size` bytes, fewer only at the end of `reader`.
+async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R, chunk_size: usize) -> Result<Vec<u8>> {
+    // the chunk size may be up to MAX_STRING_SIZE, so only grow past the default as data arrives
+    let mut chunk = Vec::with_capacity(chunk_size.min(DEFAULT_BLOB_CHUNK_SIZE));
+    while chunk.len() < chunk_size {
+        let read = (&mut *reader)
+            .take((chunk_size - chunk.len()) as u64)
+            .read_to_end(&mut chunk)
+            .await?;
+        if read == 0 {
+            break;
+        }
+    }
+    Ok(chunk)
+}
+
+/// Joins the chunks of blobs back together, yielding each blob as `(blob_id, data)` once its last chunk arrived.
+/// Chunks must be ordered by `blob_id` and `seq`, and duplicates removed, i.e. with `SELECT ... FINAL ORDER BY blob_id, seq`.
+/// Fails if a blob is missing chunks, such as one whose insert didn't complete.
+pub fn assemble_blobs(
+    chunks: impl Stream<Item = Result<BlobChunk>> + Send + 'static,
+) -> impl Stream<Item = Result<(String, Vec<u8>)>> + Send {
+    let missing = |blob_id: &str, seq: u32| {
+        KlickhouseError::DeserializeError(format!("blob {blob_id} is missing chunk {seq}"))
+    };
+    stream::try_unfold(
+        (Box::pin(chunks), None::<(String, u32, Vec<u8>)>),
+        move |(mut chunks, mut current)| async move {
+            loop {
+                let Some(chunk) = chunks.next().await.transpose()? else {
+                    return match current {
+                        Some((blob_id, seq, _)) => Err(missing(&blob_id, seq)),
+                        None => Ok(None),
+                    };
+                };
+                let (blob_id, seq, data) =
+                    current.get_or_insert_with(|| (chunk.blob_id.clone(), 0, vec![]));
+                if *blob_id != chunk.blob_id || *seq != chunk.seq {
+                    return Err(missing(blob_id, *seq));
+                }
+                data.extend_from_slice(&chunk.data.0);
+                *seq += 1;
+                if chunk.last {
+                    let (blob_id, _, data) = current.take().unwrap();
+                    return Ok(Some(((blob_id, data), (chunks, current))));
+                }
+            }
+        },
+    )
+}
+
+impl Client {
+    /// Inserts the contents of `reader` as blob `blob_id`, in chunks of `chunk_size` bytes sent as one block each,
+    /// so only about two chunks are held in memory. `query` is an insert of [`BlobChunk`] rows, i.e. `INSERT INTO blobs FORMAT Native`.
+    ///
+    /// If reading fails, the insert still ends, with the chunks read until then, and the error is returned.
+    /// As the blob then has no last chunk, [`assemble_blobs`] fails to read it until it is inserted again.
+    pub async fn insert_blob(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        blob_id: impl Into<String>,
+        reader: impl AsyncRead + Unpin + Send + 'static,
+        chunk_size: usize,
+    ) -> Result<InsertResult> {
+        self.insert_blob_with_options(query, blob_id, reader, chunk_size, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `insert_blob`, with per-query [`QueryOptions`].
+    pub async fn insert_blob_with_options(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        blob_id: impl Into<String>,
+        mut reader: impl AsyncRead + Unpin + Send + 'static,
+        chunk_size: usize,
+        options: QueryOptions,
+    ) -> Result<InsertResult> {
+        check_chunk_size(chunk_size)?;
+        let blob_id = blob_id.into();
+        // the first chunk is read ahead, so a failing reader doesn't start an insert
+        let first = read_chunk(&mut reader, chunk_size).await?;
+        let (error_sender, mut error_receiver) = tokio::sync::oneshot::channel();
+        let chunks = stream::unfold(
+            (reader, Some(first), 0u32, Some(error_sender)),
+            move |(mut reader, chunk, seq, mut error_sender)| {
+                let blob_id = blob_id.clone();
+                async move {
+                    let data = chunk?;
+                    // a short chunk ends the blob without reading on
+                    let next = if data.len() < chunk_size {
+                        Ok(vec![])
+                    } else {
+                        read_chunk(&mut reader, chunk_size).await
+                    };
+                    let next = match next {
+                        Ok(next) if next.is_empty() => None,
+                        Ok(next) => Some(next),
+                        Err(e) => {
+                            if let Some(sender) = error_sender.take() {
+                                sender.send(e).ok();
+                            }
+                            None
+                        }
+                    };
+                    let last = next.is_none() && error_sender.is_some();
+                    let chunk = BlobChunk {
+                        blob_id,
+                        seq,
+                        last,
+                        data: Bytes(data),
+                    };
+                    Some((vec![chunk], (reader, next, seq + 1, error_sender)))
+                }
+            },
+        );
+        let result = self
+            .insert_native_with_options(query, Box::pin(chunks), options)
+            .await?;
+        match error_receiver.try_recv() {
+            Ok(e) => Err(e),
+            Err(_) => Ok(result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn assemble(chunks: Vec<BlobChunk>) -> Result<Vec<(String, Vec<u8>)>> {
+        assemble_blobs(stream::iter(chunks.into_iter().map(Ok)))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_split_and_assemble() {
+        let data = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+        let chunks = split_blob("a", &data, 300).unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[3].data.0.len(), 100);
+        assert!(chunks[3].last && !chunks[2].last);
+
+        let empty = split_blob("b", &[], 300).unwrap();
+        assert_eq!(empty.len(), 1);
+        assert!(empty[0].last);
+        assert!(split_blob("c", &data, 0).is_err());
+
+        let mut all = chunks.clone();
+        all.extend(empty);
+        assert_eq!(
+            assemble(all).await.unwrap(),
+            [("a".to_string(), data), ("b".to_string(), vec![])]
+        );
+
+        let mut gap = chunks.clone();
+        gap.remove(1);
+        assert!(assemble(gap).await.is_err());
+        assert!(assemble(chunks[..3].to_vec()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_chunk() {
+        let mut reader = tokio::io::BufReader::with_capacity(7, &[1u8; 20][..]);
+        assert_eq!(read_chunk(&mut reader, 16).await.unwrap().len(), 16);
+        assert_eq!(read_chunk(&mut reader, 16).await.unwrap().len(), 4);
+        assert!(read_chunk(&mut reader, 16).await.unwrap().is_empty());
+    }
+}
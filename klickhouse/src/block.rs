@@ -1,11 +1,13 @@
-use std::{collections::VecDeque, str::FromStr};
+use std::{borrow::Cow, collections::VecDeque, future::Future, str::FromStr};
 
 use crate::Result;
+use futures_util::FutureExt;
 use indexmap::IndexMap;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::{
     io::{ClickhouseRead, ClickhouseWrite},
+    protocol::DBMS_MIN_REVISION_WITH_CUSTOM_SERIALIZATION,
     types::{DeserializerState, SerializerState, Type},
     values::Value,
     KlickhouseError,
@@ -63,7 +65,7 @@ impl BlockInfo {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 /// A chunk of data in columnar form.
 pub struct Block {
     /// Metadata about the block
@@ -101,6 +103,8 @@ impl<'a> Iterator for BlockRowIter<'a> {
 // Iterator type for `take_iter_rows`
 pub struct BlockRowValueIter<'a> {
     column_data: Vec<(&'a str, &'a Type, std::vec::IntoIter<Value>)>,
+    /// Remaining rows of a block without columns, which are yielded as empty rows
+    empty_rows: u64,
 }
 
 impl<'a> Iterator for BlockRowValueIter<'a> {
@@ -108,7 +112,11 @@ impl<'a> Iterator for BlockRowValueIter<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.column_data.is_empty() {
-            return None;
+            if self.empty_rows == 0 {
+                return None;
+            }
+            self.empty_rows -= 1;
+            return Some(Vec::new());
         }
         let mut out = Vec::new();
         for (name, type_, pop) in self.column_data.iter_mut() {
@@ -139,6 +147,44 @@ impl Iterator for BlockRowIntoIter {
 }
 
 impl Block {
+    /// Iterate over all columns in order, with their name, type, and data.
+    pub fn columns(&self) -> impl Iterator<Item = (&str, &Type, &[Value])> {
+        self.column_types.iter().map(|(name, type_)| {
+            let data = self
+                .column_data
+                .get(name)
+                .map(|x| &x[..])
+                .unwrap_or_default();
+            (&**name, type_, data)
+        })
+    }
+
+    /// Appends a column to the block, or replaces an existing column of the same name in place.
+    /// The first column inserted into an empty block sets the row count. All other columns must match it.
+    pub fn insert_column(
+        &mut self,
+        name: impl Into<String>,
+        type_: Type,
+        data: Vec<Value>,
+    ) -> Result<()> {
+        let name = name.into();
+        let replaces_only_column =
+            self.column_types.len() == 1 && self.column_types.contains_key(&name);
+        if self.column_types.is_empty() || replaces_only_column {
+            self.rows = data.len() as u64;
+        } else if data.len() as u64 != self.rows {
+            return Err(KlickhouseError::SerializeError(format!(
+                "column '{}' has {} rows, but block has {} rows",
+                name,
+                data.len(),
+                self.rows
+            )));
+        }
+        self.column_types.insert(name.clone(), type_);
+        self.column_data.insert(name, data);
+        Ok(())
+    }
+
     /// Create a borrowing iterator for all rows
     pub fn iter_rows(&self) -> BlockRowIter<'_> {
         BlockRowIter {
@@ -148,7 +194,8 @@ impl Block {
     }
 
     /// Iterate over all rows with owned values.
-    pub fn take_iter_rows(&mut self) -> BlockRowValueIter {
+    /// A block with rows but no columns yields that many empty rows.
+    pub fn take_iter_rows(&mut self) -> BlockRowValueIter<'_> {
         let mut column_data = IndexMap::new();
         std::mem::swap(&mut self.column_data, &mut column_data);
         let mut out = Vec::with_capacity(self.rows as usize);
@@ -156,7 +203,10 @@ impl Block {
             let (name, type_) = self.column_types.get_key_value(&name).unwrap();
             out.push((&**name, type_.strip_low_cardinality(), values.into_iter()));
         }
-        BlockRowValueIter { column_data: out }
+        BlockRowValueIter {
+            empty_rows: if out.is_empty() { self.rows } else { 0 },
+            column_data: out,
+        }
     }
 
     /// Iterate over all rows with owned value, types, and names.
@@ -177,6 +227,37 @@ impl Block {
         }
     }
 
+    /// Decodes one block in Clickhouse's `Native` format (i.e. the body of a `FORMAT Native` HTTP response) from the front of `data`, advancing it.
+    /// Call repeatedly until `data` is empty to read every block. This needs no async runtime, so it is available without the `client` feature and on wasm32.
+    pub fn read_native(data: &mut &[u8]) -> Result<Self> {
+        Self::read(data, 0).now_or_never().unwrap_or_else(|| {
+            Err(KlickhouseError::ProtocolError(
+                "in-memory block read did not complete".to_string(),
+            ))
+        })
+    }
+
+    /// Encodes this block in Clickhouse's `Native` format, appending it to `out`. See [`Block::read_native`].
+    pub fn write_native(self, out: &mut Vec<u8>) -> Result<()> {
+        self.write_buffer(out, 0)
+    }
+
+    /// Encodes this block as sent to a server speaking `revision`, appending it to `out`.
+    pub(crate) fn write_buffer(self, out: &mut Vec<u8>, revision: u64) -> Result<()> {
+        complete_write(self.write(out, revision))
+    }
+
+    /// A 128-bit CityHash of the block's columns (names, types and data, in order) as encoded in the `Native` format,
+    /// which is stable across processes and versions of this crate. The row count is covered by the data, [`BlockInfo`] isn't hashed.
+    ///
+    /// Like the server's deduplication of inserts into replicated tables, this lets retried inserts of the same block be recognized,
+    /// i.e. by an ingestion layer that records the hashes of inserted blocks.
+    pub fn content_hash(&self) -> Result<u128> {
+        let mut data = vec![];
+        complete_write(self.write_ref(&mut data, 0))?;
+        Ok(cityhash_rs::cityhash_102_128(&data))
+    }
+
     pub(crate) async fn read<R: ClickhouseRead>(reader: &mut R, revision: u64) -> Result<Self> {
         let info = if revision > 0 {
             BlockInfo::read(reader).await?
@@ -195,8 +276,15 @@ impl Block {
             let name = reader.read_utf8_string().await?;
             let type_name = reader.read_utf8_string().await?;
             let type_ = Type::from_str(&type_name)?;
+            if revision >= DBMS_MIN_REVISION_WITH_CUSTOM_SERIALIZATION
+                && reader.read_u8().await? != 0
+            {
+                return Err(KlickhouseError::ProtocolError(format!(
+                    "column {name} uses a custom (i.e. sparse) serialization, which isn't supported"
+                )));
+            }
             block.column_types.insert(name.clone(), type_.clone());
-            let mut state = DeserializerState {};
+            let mut state = DeserializerState::default();
             let row_data = if rows > 0 {
                 type_.deserialize_prefix(reader, &mut state).await?;
                 type_
@@ -215,23 +303,46 @@ impl Block {
         mut self,
         writer: &mut W,
         revision: u64,
+    ) -> Result<()> {
+        // resolved in place, sparing `write_ref` a copy of the values
+        for (name, type_) in self.column_types.iter_mut() {
+            if let Some(data) = self.column_data.get_mut(name) {
+                *type_ = type_.resolve_dynamic(data.iter_mut().collect())?;
+            }
+        }
+        self.write_ref(writer, revision).await
+    }
+
+    /// Same as [`Block::write`], without consuming the block. Columns containing `Dynamic` are copied to be resolved.
+    pub(crate) async fn write_ref<W: ClickhouseWrite>(
+        &self,
+        writer: &mut W,
+        revision: u64,
     ) -> Result<()> {
         if revision > 0 {
             self.info.write(writer).await?;
         }
         let joined = self
             .column_types
-            .into_iter()
-            .flat_map(|(key, type_)| {
-                let values = self.column_data.swap_remove(&key)?;
-                Some((key, (type_, values)))
-            })
+            .iter()
+            .flat_map(|(key, type_)| Some((key, (type_, self.column_data.get(key)?))))
             .collect::<Vec<_>>();
         writer.write_var_uint(joined.len() as u64).await?;
         writer.write_var_uint(self.rows).await?;
         for (name, (type_, data)) in joined {
-            writer.write_string(&name).await?;
+            let mut resolved = None;
+            let type_ = if type_.contains_dynamic() {
+                let values = resolved.insert(data.clone());
+                Cow::Owned(type_.resolve_dynamic(values.iter_mut().collect())?)
+            } else {
+                Cow::Borrowed(type_)
+            };
+            let data = resolved.as_ref().unwrap_or(data);
+            writer.write_string(name).await?;
             writer.write_string(&type_.to_string()).await?;
+            if revision >= DBMS_MIN_REVISION_WITH_CUSTOM_SERIALIZATION {
+                writer.write_u8(0).await?;
+            }
             if data.len() != self.rows as usize {
                 return Err(KlickhouseError::ProtocolError(format!(
                     "row and column length mismatch. {} != {}",
@@ -242,9 +353,215 @@ impl Block {
             if self.rows > 0 {
                 let mut state = SerializerState {};
                 type_.serialize_prefix(writer, &mut state).await?;
-                type_.serialize_column(data, writer, &mut state).await?;
+                type_
+                    .serialize_column(data.iter().collect(), writer, &mut state)
+                    .await?;
             }
         }
         Ok(())
     }
 }
+
+/// Runs a write into memory, which never waits.
+fn complete_write(write: impl Future<Output = Result<()>>) -> Result<()> {
+    write.now_or_never().unwrap_or_else(|| {
+        Err(KlickhouseError::ProtocolError(
+            "in-memory block write did not complete".to_string(),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_column() {
+        let mut block = Block::default();
+        block
+            .insert_column("a", Type::UInt32, vec![Value::UInt32(1), Value::UInt32(2)])
+            .unwrap();
+        block
+            .insert_column(
+                "b",
+                Type::String,
+                vec![Value::string("x"), Value::string("y")],
+            )
+            .unwrap();
+        assert_eq!(block.rows, 2);
+        assert!(block
+            .insert_column("c", Type::UInt8, vec![Value::UInt8(1)])
+            .is_err());
+        block
+            .insert_column("a", Type::UInt8, vec![Value::UInt8(3), Value::UInt8(4)])
+            .unwrap();
+
+        let columns = block.columns().collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            vec![
+                ("a", &Type::UInt8, &[Value::UInt8(3), Value::UInt8(4)][..]),
+                (
+                    "b",
+                    &Type::String,
+                    &[Value::string("x"), Value::string("y")][..]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_take_iter_rows_without_columns() {
+        let mut block = Block {
+            rows: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            block.take_iter_rows().collect::<Vec<_>>(),
+            vec![vec![], vec![]]
+        );
+        assert_eq!(Block::default().take_iter_rows().count(), 0);
+    }
+
+    #[test]
+    fn test_native_roundtrip() {
+        let mut first = Block::default();
+        first
+            .insert_column(
+                "s",
+                Type::Nullable(Box::new(Type::String)),
+                vec![Value::string("a"), Value::Null],
+            )
+            .unwrap();
+        let mut second = Block::default();
+        second
+            .insert_column("i", Type::Int64, vec![Value::Int64(-1)])
+            .unwrap();
+
+        let mut data = vec![];
+        first.clone().write_native(&mut data).unwrap();
+        second.clone().write_native(&mut data).unwrap();
+
+        let mut reader = &data[..];
+        let read_first = Block::read_native(&mut reader).unwrap();
+        let read_second = Block::read_native(&mut reader).unwrap();
+        assert!(reader.is_empty());
+        assert_eq!(read_first.column_data, first.column_data);
+        assert_eq!(read_second.column_data, second.column_data);
+        assert!(Block::read_native(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let block = |values: Vec<Value>| {
+            let mut block = Block::default();
+            block.insert_column("a", Type::UInt32, values).unwrap();
+            block
+        };
+        let hash = block(vec![Value::UInt32(1), Value::UInt32(2)])
+            .content_hash()
+            .unwrap();
+        let mut same = block(vec![Value::UInt32(1), Value::UInt32(2)]);
+        same.info.bucket_num = 3;
+        assert_eq!(same.content_hash().unwrap(), hash);
+        assert_ne!(
+            block(vec![Value::UInt32(2), Value::UInt32(1)])
+                .content_hash()
+                .unwrap(),
+            hash
+        );
+        assert_ne!(block(vec![Value::UInt32(1)]).content_hash().unwrap(), hash);
+
+        let mut renamed = Block::default();
+        renamed
+            .insert_column("b", Type::UInt32, vec![Value::UInt32(1), Value::UInt32(2)])
+            .unwrap();
+        assert_ne!(renamed.content_hash().unwrap(), hash);
+        // pinned, so changes to the hash are noticed
+        assert_eq!(hash, 0x0ecd_21ef_425a_2f0f_38a3_5f53_9328_6ce3);
+
+        // `Dynamic` values are resolved the same way as when writing the block
+        let mut dynamic = Block::default();
+        dynamic
+            .insert_column(
+                "d",
+                Type::Dynamic,
+                vec![Value::UInt32(1), Value::Null, Value::string("x")],
+            )
+            .unwrap();
+        let mut native = vec![];
+        dynamic.clone().write_native(&mut native).unwrap();
+        assert_eq!(
+            dynamic.content_hash().unwrap(),
+            cityhash_rs::cityhash_102_128(&native)
+        );
+    }
+
+    #[test]
+    fn test_corrupt_native() {
+        let mut block = Block::default();
+        block
+            .insert_column(
+                "a",
+                Type::Array(Box::new(Type::Nullable(Box::new(Type::Int32)))),
+                vec![
+                    Value::Array(vec![Value::Int32(1), Value::Null]),
+                    Value::Array(vec![]),
+                ],
+            )
+            .unwrap();
+        block
+            .insert_column(
+                "m",
+                Type::Map(Box::new(Type::String), Box::new(Type::FixedString(3))),
+                vec![
+                    Value::Map(vec![Value::string("k")], vec![Value::string("v")]),
+                    Value::Map(vec![], vec![]),
+                ],
+            )
+            .unwrap();
+        block
+            .insert_column(
+                "t",
+                Type::Tuple(vec![
+                    Type::LowCardinality(Box::new(Type::String)),
+                    Type::Point,
+                ]),
+                vec![
+                    Value::Tuple(vec![Value::string("x"), Value::Point(Default::default())]),
+                    Value::Tuple(vec![Value::string("y"), Value::Point(Default::default())]),
+                ],
+            )
+            .unwrap();
+        let mut data = vec![];
+        block.write_native(&mut data).unwrap();
+
+        // truncated input must fail, and corrupted input must not panic
+        for len in 0..data.len() {
+            assert!(Block::read_native(&mut &data[..len]).is_err());
+        }
+        for i in 0..data.len() {
+            for byte in [0x00, 0x01, 0x7F, 0x80, 0xFF] {
+                let mut corrupted = data.clone();
+                corrupted[i] = byte;
+                let _ = Block::read_native(&mut &corrupted[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decreasing_offsets() {
+        let mut data = vec![];
+        data.write_var_uint(1).now_or_never().unwrap().unwrap();
+        data.write_var_uint(2).now_or_never().unwrap().unwrap();
+        data.write_string("a").now_or_never().unwrap().unwrap();
+        data.write_string("Array(UInt8)")
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        data.extend(2u64.to_le_bytes());
+        data.extend(1u64.to_le_bytes());
+        data.extend([1, 2]);
+        assert!(Block::read_native(&mut &data[..]).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, future, sync::Arc, time::Duration};
 
 use futures_util::{stream, Stream, StreamExt};
 use indexmap::IndexMap;
@@ -12,55 +12,177 @@ use tokio::{
         mpsc::{self, Receiver},
         oneshot,
     },
+    time::{sleep_until, Instant},
 };
 use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
 
 use crate::{
+    admission::{Admission, AdmissionPermit},
     block::{Block, BlockInfo},
     convert::Row,
+    events::ClientEvent,
     internal_client_in::InternalClientIn,
     internal_client_out::{
         ClientHello, ClientInfo, InternalClientOut, Query, QueryKind, QueryProcessingStage,
+        QuerySetting,
     },
     io::{ClickhouseRead, ClickhouseWrite},
     progress::Progress,
     protocol::{self, ServerPacket},
-    KlickhouseError, ParsedQuery, RawRow, Result,
+    query_audit::{AuditedQuery, QueryAuditHook},
+    query_sink::QuerySink,
+    schema_cache::SchemaCache,
+    slow_query::{self, SlowQuery, SlowQueryHook},
+    AsyncInsertWait, ColumnWriters, Identifier, KlickhouseError, ParsedQuery, QualifiedTable,
+    QueryOptions, QueryPriority, RawRow, Result, SlowConsumerPolicy, Type, Utf8Policy, Value,
 };
 use log::*;
 
 // Maximum number of progress statuses to keep in memory. New statuses evict old ones.
 const PROGRESS_CAPACITY: usize = 100;
+// Maximum number of client events to keep in memory. New events evict old ones.
+const EVENTS_CAPACITY: usize = 100;
 
 struct InnerClient<R: ClickhouseRead, W: ClickhouseWrite> {
     input: InternalClientIn<R>,
     output: InternalClientOut<W>,
     options: ClientOptions,
-    pending_queries: VecDeque<PendingQuery>,
-    executing_query: Option<(Uuid, mpsc::Sender<Result<Block>>)>,
+    pending_queries: VecDeque<PendingBatch>,
+    /// Rest of the batch of the executing query, dispatched before any pending query
+    running_batch: VecDeque<PendingQuery>,
+    executing_query: Option<ExecutingQuery>,
+    /// Database selected with a `USE` statement for queries with [`QueryOptions::database`], `None` while on the database selected at login
+    current_database: Option<String>,
+    /// Database selected at login, looked up before the first `USE` statement if [`ClientOptions::default_database`] is empty
+    login_database: Option<String>,
     progress: broadcast::Sender<(Uuid, Progress)>,
+    events: broadcast::Sender<ClientEvent>,
+    /// Pings requested while a query was executing, sent before the next query is dispatched
+    deferred_pings: Vec<oneshot::Sender<()>>,
+    /// Pings sent to the server, in order, answered by the next pong
+    awaiting_pongs: VecDeque<oneshot::Sender<()>>,
+    admission: Arc<Admission>,
 }
 
-struct PendingQuery {
+struct ExecutingQuery {
+    id: Uuid,
+    sink: QuerySink,
+    /// Loggable query text
     query: String,
-    response: oneshot::Sender<mpsc::Receiver<Result<Block>>>,
+    /// Redacted query whose SQL and bound values are scrubbed from server errors
+    scrub: Option<ParsedQuery>,
+    started: Instant,
+    progress: Progress,
+    reported_slow: bool,
+    /// For a statement sent ahead of a query to select its database, what it does and the query to dispatch once it succeeds
+    preamble: Option<Preamble>,
+    /// Columns of the first block with any, which all later blocks with columns must match
+    column_types: Option<IndexMap<String, Type>>,
+    _permit: Option<AdmissionPermit>,
+}
+
+impl ExecutingQuery {
+    /// When to report this query as slow, if it hasn't been yet.
+    fn slow_deadline(&self, threshold: Option<Duration>) -> Option<Instant> {
+        if self.reported_slow {
+            return None;
+        }
+        Some(self.started + threshold?)
+    }
+}
+
+/// Adds the progress received so far for query `id` to `progress`, skipping any lost to lag.
+fn collect_progress(
+    receiver: &mut broadcast::Receiver<(Uuid, Progress)>,
+    id: Uuid,
+    progress: &mut Progress,
+) {
+    loop {
+        match receiver.try_recv() {
+            Ok((progress_id, delta)) if progress_id == id => *progress += delta,
+            Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => (),
+            Err(_) => break,
+        }
+    }
+}
+
+/// Lists columns as `name Type, ...`, for [`KlickhouseError::SchemaChanged`].
+fn describe_columns(columns: &IndexMap<String, Type>) -> String {
+    columns
+        .iter()
+        .map(|(name, type_)| format!("{name} {type_}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Statement sent ahead of a query with [`QueryOptions::database`].
+enum Preamble {
+    /// `SELECT currentDatabase()`, to switch back to the database selected at login later
+    LoginDatabase(PendingQuery),
+    /// `USE` statement selecting the database
+    UseDatabase(String, PendingQuery),
+}
+
+impl Preamble {
+    fn into_query(self) -> PendingQuery {
+        match self {
+            Preamble::LoginDatabase(query) | Preamble::UseDatabase(_, query) => query,
+        }
+    }
+}
+
+/// Queries submitted together, dispatched back to back at the highest priority among them.
+struct PendingBatch {
+    priority: QueryPriority,
+    queries: VecDeque<PendingQuery>,
+}
+
+struct PendingQuery {
+    query: ParsedQuery,
+    options: QueryOptions,
+    response: oneshot::Sender<(Uuid, mpsc::Receiver<Result<Block>>)>,
+    /// Slot of `max_concurrent_queries`, held until the query ends
+    permit: Option<AdmissionPermit>,
 }
 
 impl<R: ClickhouseRead + 'static, W: ClickhouseWrite> InnerClient<R, W> {
     pub fn new(reader: R, writer: W, options: ClientOptions) -> Self {
         Self {
-            input: InternalClientIn::new(reader),
+            input: InternalClientIn::new(reader, options.compression),
             output: InternalClientOut::new(writer),
-            options,
             pending_queries: VecDeque::new(),
+            running_batch: VecDeque::new(),
             executing_query: None,
+            current_database: None,
+            login_database: (!options.default_database.is_empty())
+                .then(|| options.default_database.clone()),
             progress: broadcast::channel(PROGRESS_CAPACITY).0,
+            events: options
+                .event_sender
+                .clone()
+                .unwrap_or_else(|| broadcast::channel(EVENTS_CAPACITY).0),
+            deferred_pings: vec![],
+            awaiting_pongs: VecDeque::new(),
+            admission: Admission::new(options.max_concurrent_queries),
+            options,
         }
     }
 
-    async fn dispatch_query(&mut self, query: PendingQuery) -> Result<()> {
-        let id = Uuid::new_v4();
+    /// Sends a query packet followed by the empty data block that ends its (absent) external tables.
+    async fn send_query_packet(
+        &mut self,
+        id: Uuid,
+        sql: &str,
+        settings: &[QuerySetting<'_>],
+        parameters: &[(String, String)],
+    ) -> Result<()> {
+        if let Some(hook) = &self.options.query_audit_hook {
+            hook.call(&AuditedQuery {
+                id,
+                sql: sql.to_string(),
+            });
+        }
         self.output
             .send_query(Query {
                 id: &id.to_string(),
@@ -71,24 +193,22 @@ impl<R: ClickhouseRead + 'static, W: ClickhouseWrite> InnerClient<R, W> {
                     initial_address: "0.0.0.0:0",
                     os_user: "",
                     client_hostname: "localhost",
-                    client_name: "ClickHouseclient",
-                    client_version_major: crate::VERSION_MAJOR,
-                    client_version_minor: crate::VERSION_MINOR,
+                    client_name: &self.options.client_name,
+                    client_version_major: self.options.client_version.0,
+                    client_version_minor: self.options.client_version.1,
                     client_tcp_protocol_version: protocol::DBMS_TCP_PROTOCOL_VERSION,
                     quota_key: "",
                     distributed_depth: 1,
-                    client_version_patch: 1,
+                    client_version_patch: self.options.client_version.2,
                     open_telemetry: None,
                 },
+                settings,
                 stage: QueryProcessingStage::Complete,
-                compression: CompressionMethod::default(),
-                query: &query.query,
+                compression: self.options.compression,
+                query: sql,
+                parameters,
             })
             .await?;
-
-        let (sender, receiver) = mpsc::channel(32);
-        query.response.send(receiver).ok();
-        self.executing_query = Some((id, sender));
         self.output
             .send_data(
                 Block {
@@ -97,30 +217,199 @@ impl<R: ClickhouseRead + 'static, W: ClickhouseWrite> InnerClient<R, W> {
                     column_types: IndexMap::new(),
                     column_data: IndexMap::new(),
                 },
-                CompressionMethod::default(),
+                self.options.compression,
                 "",
                 false,
             )
+            .await
+    }
+
+    async fn dispatch_query(&mut self, query: PendingQuery) -> Result<()> {
+        let switch_to = match (&query.options.database, &self.current_database) {
+            (Some(database), Some(current)) if database == current => None,
+            (Some(database), None) if self.login_database.as_ref() == Some(database) => None,
+            (Some(database), _) => Some(database.clone()),
+            // the login database is always known once another one was selected
+            (None, Some(_)) => self.login_database.clone(),
+            (None, None) => None,
+        };
+        if let Some(database) = switch_to {
+            return self.switch_database(database, query).await;
+        }
+
+        let id = query.options.query_id.unwrap_or_else(Uuid::new_v4);
+        let settings = query.options.settings();
+        let settings = settings
+            .iter()
+            .map(|(name, value, important)| QuerySetting {
+                name,
+                value,
+                important: *important,
+            })
+            .collect::<Vec<_>>();
+        self.send_query_packet(id, &query.query.sql, &settings, &query.query.params)
             .await?;
+        debug!(
+            "dispatched clickhouse query {id}: {}",
+            query.query.loggable()
+        );
+        let _ = self.events.send(ClientEvent::QueryStarted { id });
+
+        let (sender, receiver) = mpsc::channel(self.options.query_buffer_size.max(1));
+        query.response.send((id, receiver)).ok();
+        self.executing_query = Some(ExecutingQuery {
+            id,
+            sink: QuerySink::new(sender, self.options.slow_consumer_policy.clone()),
+            scrub: (query.query.is_redacted() && !self.options.raw_sql_in_errors)
+                .then(|| query.query.clone()),
+            query: query.query.loggable().to_string(),
+            started: Instant::now(),
+            progress: Progress::default(),
+            reported_slow: false,
+            preamble: None,
+            column_types: None,
+            _permit: query.permit,
+        });
+        Ok(())
+    }
+
+    /// Sends deferred pings and dispatches the next pending query, once the previous query has ended.
+    async fn dispatch_next(&mut self) -> Result<()> {
+        for response in std::mem::take(&mut self.deferred_pings) {
+            self.output.send_ping().await?;
+            self.awaiting_pongs.push_back(response);
+        }
+        if self.running_batch.is_empty() {
+            if let Some(batch) = self.pending_queries.pop_front() {
+                self.running_batch = batch.queries;
+            }
+        }
+        if let Some(query) = self.running_batch.pop_front() {
+            self.admission.set_pending(self.pending_count());
+            self.dispatch_query(query).await?;
+        }
+        Ok(())
+    }
+
+    /// Number of queries waiting to be dispatched.
+    fn pending_count(&self) -> usize {
+        self.running_batch.len()
+            + self
+                .pending_queries
+                .iter()
+                .map(|x| x.queries.len())
+                .sum::<usize>()
+    }
+
+    /// Selects `database` on the connection, then dispatches `query`.
+    /// If the database selected at login isn't known yet, it is looked up first to be able to switch back.
+    async fn switch_database(&mut self, database: String, query: PendingQuery) -> Result<()> {
+        let id = Uuid::new_v4();
+        let (sql, preamble) = if self.login_database.is_none() {
+            (
+                "SELECT currentDatabase()".to_string(),
+                Preamble::LoginDatabase(query),
+            )
+        } else {
+            (
+                format!("USE {}", Identifier::new(&*database)),
+                Preamble::UseDatabase(database, query),
+            )
+        };
+        self.send_query_packet(id, &sql, &[], &[]).await?;
+        debug!("dispatched clickhouse query {id}: {sql}");
+
+        // rows of the preamble are never sent to the sink
+        let (sender, _) = mpsc::channel(1);
+        self.executing_query = Some(ExecutingQuery {
+            id,
+            sink: QuerySink::new(sender, SlowConsumerPolicy::Wait),
+            scrub: None,
+            query: sql,
+            started: Instant::now(),
+            progress: Progress::default(),
+            reported_slow: false,
+            preamble: Some(preamble),
+            column_types: None,
+            _permit: None,
+        });
+        Ok(())
+    }
+
+    /// Queues `queries` to run back to back, in order, with no other query of the connection in between.
+    async fn queue_batch(&mut self, queries: Vec<PendingQuery>) -> Result<()> {
+        let Some(priority) = queries.iter().map(|x| x.options.queue_priority).max() else {
+            return Ok(());
+        };
+        let mut queries = VecDeque::from(queries);
+        let queued = self.pending_count();
+        if queued == 0 && self.executing_query.is_none() {
+            let _ = self.events.send(ClientEvent::QueryQueued { pending: 0 });
+            let first = queries.pop_front().expect("batch is not empty");
+            self.running_batch = queries;
+            self.dispatch_query(first).await?;
+        } else {
+            // FIFO among batches of the same priority
+            let position = self
+                .pending_queries
+                .iter()
+                .position(|x| x.priority < priority)
+                .unwrap_or(self.pending_queries.len());
+            self.pending_queries
+                .insert(position, PendingBatch { priority, queries });
+        }
+        self.admission.set_pending(self.pending_count());
+        for pending in queued + 1..=self.pending_count() {
+            let _ = self.events.send(ClientEvent::QueryQueued { pending });
+        }
         Ok(())
     }
 
     async fn handle_request(&mut self, request: ClientRequest) -> Result<()> {
         match request.data {
-            ClientRequestData::Query { query, response } => {
-                let query = PendingQuery { query, response };
-                if self.pending_queries.is_empty() && self.executing_query.is_none() {
-                    self.dispatch_query(query).await?;
-                } else {
-                    self.pending_queries.push_back(query);
-                }
+            ClientRequestData::Query {
+                query,
+                options,
+                response,
+                permit,
+            } => {
+                self.queue_batch(vec![PendingQuery {
+                    query,
+                    options,
+                    response,
+                    permit,
+                }])
+                .await?;
+            }
+            ClientRequestData::Queries { queries } => {
+                self.queue_batch(queries).await?;
             }
             ClientRequestData::SendData { block, response } => {
                 self.output
-                    .send_data(block, CompressionMethod::default(), "", false)
+                    .send_data(block, self.options.compression, "", false)
                     .await?;
                 response.send(()).ok();
             }
+            #[cfg(feature = "compression")]
+            ClientRequestData::SendCompressedData { frame, response } => {
+                self.output.send_compressed_data(&frame, "").await?;
+                response.send(()).ok();
+            }
+            ClientRequestData::Ping { response } => {
+                // the server only reads packets other than data and cancellations between queries
+                if self.pending_count() == 0 && self.executing_query.is_none() {
+                    self.output.send_ping().await?;
+                    self.awaiting_pongs.push_back(response);
+                } else {
+                    self.deferred_pings.push(response);
+                }
+            }
+            ClientRequestData::Cancel { id } => {
+                if self.executing_query.as_ref().is_some_and(|x| x.id == id) {
+                    debug!("cancelling clickhouse query {id}");
+                    self.output.send_cancel().await?;
+                }
+            }
         }
         Ok(())
     }
@@ -133,8 +422,42 @@ impl<R: ClickhouseRead + 'static, W: ClickhouseWrite> InnerClient<R, W> {
                 ))
             }
             ServerPacket::Data(block) => {
-                if let Some((_, current)) = self.executing_query.as_ref() {
-                    current.send(Ok(block.block)).await.ok();
+                if let Some(current) = self.executing_query.as_mut() {
+                    let mut block = block.block;
+                    if let Some(preamble) = &current.preamble {
+                        if let Preamble::LoginDatabase(_) = preamble {
+                            if let Some(Value::String(name)) = block
+                                .column_data
+                                .get_index_mut(0)
+                                .and_then(|(_, values)| values.pop())
+                            {
+                                self.login_database =
+                                    Some(String::from_utf8_lossy(&name).into_owned());
+                            }
+                        }
+                        return Ok(());
+                    }
+                    match &current.column_types {
+                        _ if block.column_types.is_empty() => (),
+                        None => current.column_types = Some(block.column_types.clone()),
+                        Some(expected) if *expected != block.column_types => {
+                            warn!(
+                                "columns of clickhouse query {} changed mid-stream",
+                                current.id
+                            );
+                            let _ = self
+                                .events
+                                .send(ClientEvent::SchemaChanged { id: current.id });
+                            let error = KlickhouseError::SchemaChanged {
+                                expected: describe_columns(expected),
+                                received: describe_columns(&block.column_types),
+                            };
+                            current.sink.fail(error).await;
+                            return Ok(());
+                        }
+                        Some(_) => (),
+                    }
+                    current.sink.send(Ok(block)).await;
                 } else {
                     return Err(KlickhouseError::ProtocolError(
                         "received data block, but no pending queries".to_string(),
@@ -142,30 +465,74 @@ impl<R: ClickhouseRead + 'static, W: ClickhouseWrite> InnerClient<R, W> {
                 }
             }
             ServerPacket::Exception(e) => {
-                if let Some((_, current)) = self.executing_query.take() {
-                    current.send(Err(e.emit())).await.ok();
-                    if let Some(query) = self.pending_queries.pop_front() {
-                        self.dispatch_query(query).await?;
+                if let Some(mut current) = self.executing_query.take() {
+                    let mut error = e.emit().with_query_id(current.id);
+                    if let Some(preamble) = current.preamble {
+                        let query = preamble.into_query();
+                        // the query fails with the preamble's error
+                        let (sender, receiver) = mpsc::channel(1);
+                        sender.try_send(Err(error)).ok();
+                        query.response.send((current.id, receiver)).ok();
+                        return self.dispatch_next().await;
                     }
+                    if let Some(query) = &current.scrub {
+                        error = scrub_sql(error, query);
+                    }
+                    let _ = self.events.send(ClientEvent::QueryFinished {
+                        id: current.id,
+                        elapsed: current.started.elapsed(),
+                        error: Some(error.to_string()),
+                    });
+                    current.sink.send(Err(error)).await;
+                    self.dispatch_next().await?;
                 } else {
                     return Err(e.emit());
                 }
             }
             ServerPacket::Progress(progress) => {
-                if let Some((id, _)) = &self.executing_query {
-                    let _ = self.progress.send((*id, progress));
+                if let Some(current) = self.executing_query.as_mut() {
+                    current.progress += progress;
+                    let _ = self.progress.send((current.id, progress));
+                }
+            }
+            ServerPacket::Pong => {
+                if let Some(response) = self.awaiting_pongs.pop_front() {
+                    response.send(()).ok();
                 }
             }
-            ServerPacket::Pong => {}
             ServerPacket::EndOfStream => {
-                if self.executing_query.take().is_none() {
+                let Some(current) = self.executing_query.take() else {
                     return Err(KlickhouseError::ProtocolError(
                         "received end of stream, but no executing query".to_string(),
                     ));
+                };
+                match current.preamble {
+                    Some(Preamble::LoginDatabase(query)) => {
+                        if self.login_database.is_none() {
+                            let (sender, receiver) = mpsc::channel(1);
+                            sender
+                                .try_send(Err(KlickhouseError::ProtocolError(
+                                    "currentDatabase() returned no rows".to_string(),
+                                )))
+                                .ok();
+                            query.response.send((current.id, receiver)).ok();
+                            return self.dispatch_next().await;
+                        }
+                        return self.dispatch_query(query).await;
+                    }
+                    Some(Preamble::UseDatabase(database, query)) => {
+                        self.current_database =
+                            (self.login_database.as_ref() != Some(&database)).then_some(database);
+                        return self.dispatch_query(query).await;
+                    }
+                    None => (),
                 }
-                if let Some(query) = self.pending_queries.pop_front() {
-                    self.dispatch_query(query).await?;
-                }
+                let _ = self.events.send(ClientEvent::QueryFinished {
+                    id: current.id,
+                    elapsed: current.started.elapsed(),
+                    error: None,
+                });
+                self.dispatch_next().await?;
             }
             ServerPacket::ProfileInfo(_) => {}
             ServerPacket::Totals(_) => {}
@@ -173,25 +540,63 @@ impl<R: ClickhouseRead + 'static, W: ClickhouseWrite> InnerClient<R, W> {
             ServerPacket::TablesStatusResponse(_) => {}
             ServerPacket::Log(_) => {}
             ServerPacket::TableColumns(_) => {}
-            ServerPacket::PartUUIDs(_) => {}
-            ServerPacket::ReadTaskRequest => {}
+            ServerPacket::ProfileEvents(_) => {}
+            // only sent for query deduplication, which would require the client to track parts across replicas
+            ServerPacket::PartUUIDs(uuids) => {
+                debug!("ignoring {} part uuid(s) from server", uuids.len());
+            }
+            // the server waits for a response, so hand out no tasks rather than stalling the query
+            ServerPacket::ReadTaskRequest => {
+                debug!("answering read task request with no tasks");
+                self.output.send_read_task_response("").await?;
+            }
         }
         Ok(())
     }
 
-    async fn run_inner(mut self, mut input: Receiver<ClientRequest>) -> Result<()> {
+    async fn handshake(&mut self) -> Result<()> {
         self.output
             .send_hello(ClientHello {
                 default_database: &self.options.default_database,
                 username: &self.options.username,
                 password: &self.options.password,
+                client_name: &self.options.client_name,
+                client_version: self.options.client_version,
             })
             .await?;
-        let hello_response = self.input.receive_hello().await?;
+        let mut hello_response = self.input.receive_hello().await?;
+        // both ends speak the older of their revisions
+        hello_response.revision_version = hello_response
+            .revision_version
+            .min(protocol::DBMS_TCP_PROTOCOL_VERSION);
         self.input.server_hello = hello_response.clone();
-        self.output.server_hello = hello_response.clone();
+        self.output.server_hello = hello_response;
+        self.output.send_addendum("").await?;
+        Ok(())
+    }
 
+    fn report_slow_query(&mut self) {
+        let Some(current) = self.executing_query.as_mut() else {
+            return;
+        };
+        current.reported_slow = true;
+        slow_query::report(
+            self.options.slow_query_hook.as_ref(),
+            &SlowQuery {
+                id: current.id,
+                query: current.query.clone(),
+                elapsed: current.started.elapsed(),
+                progress: current.progress,
+            },
+        );
+    }
+
+    async fn run_inner(mut self, mut input: Receiver<ClientRequest>) -> Result<()> {
         loop {
+            let slow_deadline = self
+                .executing_query
+                .as_ref()
+                .and_then(|x| x.slow_deadline(self.options.slow_query_threshold));
             select! {
                 request = input.recv() => {
                     if request.is_none() {
@@ -203,26 +608,115 @@ impl<R: ClickhouseRead + 'static, W: ClickhouseWrite> InnerClient<R, W> {
                     let packet = packet?;
                     self.receive_packet(packet).await?;
                 },
+                _ = async {
+                    match slow_deadline {
+                        Some(deadline) => sleep_until(deadline).await,
+                        None => future::pending().await,
+                    }
+                } => {
+                    self.report_slow_query();
+                },
             }
         }
     }
 
     pub async fn run(self, input: Receiver<ClientRequest>) {
-        if let Err(e) = self.run_inner(input).await {
-            error!("clickhouse client failed: {:?}", e);
+        let events = self.events.clone();
+        let error = match self.run_inner(input).await {
+            Ok(()) => None,
+            Err(e) => {
+                error!("clickhouse client failed: {:?}", e);
+                Some(e.to_string())
+            }
+        };
+        let _ = events.send(ClientEvent::Disconnected { error });
+    }
+}
+
+/// Deserializes all rows of a result block, after applying `utf8` to its strings. Rows without columns are skipped unless `keep_empty_rows` is set.
+pub(crate) fn deserialize_block<T: Row>(
+    mut block: Block,
+    keep_empty_rows: bool,
+    utf8: Utf8Policy,
+) -> Vec<Result<T>> {
+    if let Err(e) = utf8.apply(&mut block) {
+        return vec![Err(e)];
+    }
+    if T::COLUMN_COUNT == Some(1) && block.column_types.len() == 1 {
+        let (name, type_) = block.column_types.pop().unwrap();
+        let type_ = type_.strip_low_cardinality();
+        return block
+            .column_data
+            .pop()
+            .map(|(_, values)| values)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|value| T::deserialize_unit(&name, type_, value))
+            .collect();
+    }
+    let (rows, skipped) = crate::convert::deserialize_rows(block, keep_empty_rows);
+    if skipped > 0 {
+        warn!("skipped {skipped} row(s) without columns in query result");
+    }
+    rows
+}
+
+/// Replaces occurrences of a redacted query's SQL in a server error with its loggable form,
+/// and any of its bound values quoted elsewhere in the error, i.e. in a parse error, with `<redacted>`.
+fn scrub_sql(mut error: KlickhouseError, query: &ParsedQuery) -> KlickhouseError {
+    let scrub = |text: &str| {
+        text.split(query.sql.as_str())
+            .map(|part| {
+                query.secrets.iter().fold(part.to_string(), |part, secret| {
+                    part.replace(secret, "<redacted>")
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(query.loggable())
+    };
+    if let KlickhouseError::ServerException {
+        message,
+        stack_trace,
+        nested,
+        ..
+    } = &mut error
+    {
+        *message = scrub(message);
+        *stack_trace = scrub(stack_trace);
+        if let Some(inner) = nested.take() {
+            *nested = Some(Box::new(scrub_sql(*inner, query)));
         }
     }
+    error
 }
 
 enum ClientRequestData {
     Query {
-        query: String,
-        response: oneshot::Sender<mpsc::Receiver<Result<Block>>>,
+        query: ParsedQuery,
+        options: QueryOptions,
+        response: oneshot::Sender<(Uuid, mpsc::Receiver<Result<Block>>)>,
+        permit: Option<AdmissionPermit>,
     },
     SendData {
         block: Block,
         response: oneshot::Sender<()>,
     },
+    #[cfg(feature = "compression")]
+    SendCompressedData {
+        frame: Vec<u8>,
+        response: oneshot::Sender<()>,
+    },
+    /// Queues several queries at once, so no other query is queued in between
+    Queries {
+        queries: Vec<PendingQuery>,
+    },
+    /// Cancels the query with this id, if it is still executing
+    Cancel {
+        id: Uuid,
+    },
+    Ping {
+        response: oneshot::Sender<()>,
+    },
 }
 
 struct ClientRequest {
@@ -230,10 +724,75 @@ struct ClientRequest {
 }
 
 /// Client handle for a Clickhouse connection, has internal reference to connection, and can be freely cloned and sent across threads.
+/// `Client` is `Send + Sync`, so it can be stored in shared state (i.e. axum state or an `Arc`) and used from spawned tasks.
+/// The futures returned by its methods and the streams they resolve to are `Send` whenever the row type is.
 #[derive(Clone)]
 pub struct Client {
     sender: mpsc::Sender<ClientRequest>,
     progress: broadcast::Sender<(Uuid, Progress)>,
+    events: broadcast::Sender<ClientEvent>,
+    pub(crate) schema_cache: Arc<SchemaCache>,
+    pub(crate) schema_cache_ttl: Option<Duration>,
+    #[cfg_attr(not(feature = "compression"), allow(dead_code))]
+    revision: u64,
+    #[cfg_attr(not(feature = "compression"), allow(dead_code))]
+    compression_threads: usize,
+    #[cfg_attr(not(feature = "compression"), allow(dead_code))]
+    compression: CompressionMethod,
+    fixed_string_overflow: FixedStringOverflow,
+    admission: Arc<Admission>,
+}
+
+/// What [`Client::insert_native`] does with values longer than their `FixedString(N)` column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FixedStringOverflow {
+    /// Fail the insert.
+    #[default]
+    Error,
+    /// Keep the first `N` bytes.
+    Truncate,
+}
+
+/// An insert query the server accepted, whose data is yet to be sent.
+struct StartedInsert {
+    id: Uuid,
+    receiver: mpsc::Receiver<Result<Block>>,
+    progress_receiver: broadcast::Receiver<(Uuid, Progress)>,
+    /// Whether to wait for the server to finish the insert after sending the data
+    wait: bool,
+}
+
+/// Limits of the blocks [`Client::insert_rows_stream`] sends. A block is sent as soon as any limit is reached.
+#[derive(Debug, Clone)]
+pub struct InsertBatching {
+    /// Maximum number of rows per block (at least 1). Default 65536.
+    pub max_rows: usize,
+    /// Maximum approximate size of a block in the native format. Default 16 MiB.
+    pub max_bytes: usize,
+    /// Maximum time between the first row of a block arriving and the block being sent. Default 1 second.
+    pub max_delay: Duration,
+}
+
+impl Default for InsertBatching {
+    fn default() -> Self {
+        InsertBatching {
+            max_rows: 65536,
+            max_bytes: 16 * 1024 * 1024,
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Outcome of an insert, i.e. of [`Client::insert_native`], once the server finished it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InsertResult {
+    /// Rows sent, not counting rows skipped because they failed to serialize.
+    pub rows: u64,
+    /// Bytes the server reported writing, as summed from its progress packets (`written_bytes` in `system.query_log`).
+    /// Best-effort like [`Client::execute_with_progress`], and 0 for servers that don't report it.
+    pub bytes: u64,
+    /// Data blocks sent, not counting the empty block that ends the insert.
+    pub blocks: u64,
 }
 
 /// Options set for a Clickhouse connection.
@@ -243,6 +802,40 @@ pub struct ClientOptions {
     pub password: String,
     pub default_database: String,
     pub tcp_nodelay: bool,
+    /// Number of result blocks buffered per query before `slow_consumer_policy` applies. Default 32.
+    pub query_buffer_size: usize,
+    /// What to do when a query's consumer doesn't keep up with incoming blocks. Defaults to waiting, which stalls the connection.
+    pub slow_consumer_policy: SlowConsumerPolicy,
+    /// Number of outgoing blocks compressed concurrently on blocking worker threads during inserts. Block order is preserved.
+    /// Default 1, which compresses blocks on the connection task.
+    pub compression_threads: usize,
+    /// Queries still executing after this long are reported once to `slow_query_hook`, e.g. `Some(Duration::from_secs(5))`. Default `None`, which disables reporting.
+    pub slow_query_threshold: Option<Duration>,
+    /// Receives queries exceeding `slow_query_threshold`. If `None`, they are logged as warnings.
+    pub slow_query_hook: Option<SlowQueryHook>,
+    /// Receives every query with its id and final SQL right before it is sent, i.e. for audit logging. Default `None`.
+    pub query_audit_hook: Option<QueryAuditHook>,
+    /// If `false` (the default), server errors for queries built with [`QueryBuilder::redact`](crate::QueryBuilder::redact) have the query's SQL replaced by its redacted form.
+    pub raw_sql_in_errors: bool,
+    /// Channel to publish [`ClientEvent`]s on, i.e. to share one channel across a pool of connections. If `None`, each connection creates its own.
+    pub event_sender: Option<broadcast::Sender<ClientEvent>>,
+    /// How long [`Client::describe_table`] results are cached, i.e. to avoid repeated metadata queries in high-rate insert services.
+    /// Default `None`, which disables caching.
+    pub schema_cache_ttl: Option<Duration>,
+    /// What to do with inserted values that are too long for their `FixedString` column. Defaults to an error.
+    pub fixed_string_overflow: FixedStringOverflow,
+    /// Name the client reports to the server, i.e. `client_name` in `system.query_log`. Defaults to `klickhouse`.
+    pub client_name: String,
+    /// Version (major, minor, patch) the client reports alongside `client_name`. Defaults to the version of this crate.
+    /// The protocol revision, which the server uses to negotiate features, is independent of it.
+    pub client_version: (u64, u64, u64),
+    /// Maximum number of queries submitted to the connection at once, executing or waiting in its queue. Further queries wait on the client
+    /// until one of them ends, and are admitted by [`QueryOptions::queue_priority`], then in submission order.
+    /// Default `None`, which queues every query on the connection right away.
+    pub max_concurrent_queries: Option<usize>,
+    /// Compression of data blocks in both directions, i.e. inserted rows and query results. Defaults to `LZ4` with the `compression` feature.
+    /// `CompressionMethod::None` trades bandwidth for CPU, i.e. on a local network.
+    pub compression: CompressionMethod,
 }
 
 impl Default for ClientOptions {
@@ -252,6 +845,24 @@ impl Default for ClientOptions {
             password: String::new(),
             default_database: String::new(),
             tcp_nodelay: true,
+            query_buffer_size: 32,
+            slow_consumer_policy: SlowConsumerPolicy::default(),
+            compression_threads: 1,
+            slow_query_threshold: None,
+            slow_query_hook: None,
+            query_audit_hook: None,
+            raw_sql_in_errors: false,
+            event_sender: None,
+            schema_cache_ttl: None,
+            fixed_string_overflow: FixedStringOverflow::default(),
+            client_name: env!("CARGO_PKG_NAME").to_string(),
+            client_version: (
+                env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap_or_default(),
+                env!("CARGO_PKG_VERSION_MINOR").parse().unwrap_or_default(),
+                env!("CARGO_PKG_VERSION_PATCH").parse().unwrap_or_default(),
+            ),
+            max_concurrent_queries: None,
+            compression: CompressionMethod::default(),
         }
     }
 }
@@ -271,6 +882,16 @@ impl Client {
         .await
     }
 
+    /// Connects over an already-buffered custom transport without adding another layer of buffering.
+    /// Any [`ClickhouseRead`]/[`ClickhouseWrite`] works, e.g. QUIC streams, [`tokio::io::duplex`] pipes for tests, or instrumented wrappers.
+    pub async fn connect_transport<R: ClickhouseRead + 'static, W: ClickhouseWrite>(
+        read: R,
+        writer: W,
+        options: ClientOptions,
+    ) -> Result<Self> {
+        Self::start(InnerClient::new(read, writer, options)).await
+    }
+
     /// Connects to a specific socket address over plaintext TCP for Clickhouse.
     pub async fn connect<A: ToSocketAddrs>(destination: A, options: ClientOptions) -> Result<Self> {
         let stream = TcpStream::connect(destination).await?;
@@ -295,40 +916,144 @@ impl Client {
     }
 
     async fn start<R: ClickhouseRead + 'static, W: ClickhouseWrite>(
-        inner: InnerClient<R, W>,
+        mut inner: InnerClient<R, W>,
     ) -> Result<Self> {
+        #[cfg(not(feature = "compression"))]
+        if inner.options.compression != CompressionMethod::None {
+            return Err(KlickhouseError::ProtocolError(
+                "LZ4 compression requires the `compression` feature of klickhouse".to_string(),
+            ));
+        }
+        inner.handshake().await?;
         let progress = inner.progress.clone();
+        let events = inner.events.clone();
+        let hello = &inner.output.server_hello;
+        let _ = events.send(ClientEvent::Connected {
+            server_name: hello.server_name.clone(),
+            version: format!(
+                "{}.{}.{}",
+                hello.major_version, hello.minor_version, hello.patch_version
+            ),
+            revision: hello.revision_version,
+        });
+        let revision = inner.output.server_hello.revision_version;
+        let compression_threads = inner.options.compression_threads;
+        let compression = inner.options.compression;
+        let admission = inner.admission.clone();
+        let schema_cache_ttl = inner.options.schema_cache_ttl;
+        let fixed_string_overflow = inner.options.fixed_string_overflow;
         let (sender, receiver) = mpsc::channel(1024);
 
         tokio::spawn(inner.run(receiver));
-        let client = Client { sender, progress };
+        let client = Client {
+            sender,
+            progress,
+            events,
+            schema_cache: Default::default(),
+            schema_cache_ttl,
+            revision,
+            compression_threads,
+            compression,
+            fixed_string_overflow,
+            admission,
+        };
         client
             .execute("SET date_time_input_format='best_effort'")
             .await?;
         Ok(client)
     }
 
-    /// Sends a query string and read column blocks over a stream.
-    /// You probably want [`Client::query()`]
-    pub async fn query_raw(
+    /// Queues a query on the connection, returning its ID and result blocks once it is dispatched.
+    async fn send_query(
         &self,
-        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
-    ) -> Result<impl Stream<Item = Result<Block>>> {
+        query: ParsedQuery,
+        options: QueryOptions,
+    ) -> Result<(Uuid, mpsc::Receiver<Result<Block>>)> {
+        let permit = self.admission.acquire(options.queue_priority).await?;
         let (sender, receiver) = oneshot::channel();
         self.sender
             .send(ClientRequest {
                 data: ClientRequestData::Query {
-                    query: query.try_into()?.0,
+                    query,
+                    options,
                     response: sender,
+                    permit: Some(permit),
                 },
             })
             .await
             .map_err(|e| KlickhouseError::ProtocolError(format!("failed to send query: {e}")))?;
-        let receiver = receiver.await.map_err(|e| {
+        receiver.await.map_err(|e| {
             KlickhouseError::ProtocolError(format!("failed to receive blocks from upstream: {e}"))
-        })?;
+        })
+    }
 
-        Ok(ReceiverStream::new(receiver))
+    /// Queues `queries` on the connection back to back, returning the receivers of their IDs and result blocks, which resolve as each is dispatched.
+    /// The batch is queued at the highest priority among its queries, and takes a single slot of `max_concurrent_queries`, held until its last query ends.
+    pub(crate) async fn send_queries(
+        &self,
+        queries: Vec<(ParsedQuery, QueryOptions)>,
+    ) -> Result<Vec<oneshot::Receiver<(Uuid, mpsc::Receiver<Result<Block>>)>>> {
+        let Some(priority) = queries
+            .iter()
+            .map(|(_, options)| options.queue_priority)
+            .max()
+        else {
+            return Ok(vec![]);
+        };
+        let mut permit = Some(self.admission.acquire(priority).await?);
+        let mut receivers = vec![];
+        let mut queries = queries
+            .into_iter()
+            .map(|(query, options)| {
+                let (sender, receiver) = oneshot::channel();
+                receivers.push(receiver);
+                PendingQuery {
+                    query,
+                    options,
+                    response: sender,
+                    permit: None,
+                }
+            })
+            .collect::<Vec<_>>();
+        queries.last_mut().unwrap().permit = permit.take();
+        self.sender
+            .send(ClientRequest {
+                data: ClientRequestData::Queries { queries },
+            })
+            .await
+            .map_err(|e| KlickhouseError::ProtocolError(format!("failed to send queries: {e}")))?;
+        Ok(receivers)
+    }
+
+    /// Sends a query string and read column blocks over a stream.
+    /// You probably want [`Client::query()`]
+    pub async fn query_raw(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<impl Stream<Item = Result<Block>>> {
+        self.query_raw_with_options(query, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `query_raw`, with per-query [`QueryOptions`].
+    pub async fn query_raw_with_options(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
+    ) -> Result<impl Stream<Item = Result<Block>>> {
+        let (_, blocks) = self.query_raw_with_id(query, options).await?;
+        Ok(blocks)
+    }
+
+    /// Same as `query_raw_with_options`, but also returns the ID the query was sent with, which is [`QueryOptions::query_id`] if set.
+    pub async fn query_raw_with_id(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
+    ) -> Result<(Uuid, impl Stream<Item = Result<Block>>)> {
+        let (id, receiver) = self.send_query(query.try_into()?, options).await?;
+
+        Ok((id, ReceiverStream::new(receiver)))
     }
 
     async fn send_data(&self, block: Block) -> Result<()> {
@@ -349,123 +1074,366 @@ impl Client {
         Ok(())
     }
 
-    /// Sends a query string with streaming associated data (i.e. insert) over native protocol.
-    /// Once all outgoing blocks are written (EOF of `blocks` stream), then any response blocks from Clickhouse are read.
-    /// You probably want [`Client::insert_native`].
-    pub async fn insert_native_raw(
-        &self,
-        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
-        mut blocks: impl Stream<Item = Block> + Send + Sync + Unpin + 'static,
-    ) -> Result<impl Stream<Item = Result<Block>>> {
+    #[cfg(feature = "compression")]
+    async fn send_compressed_data(&self, frame: Vec<u8>) -> Result<()> {
         let (sender, receiver) = oneshot::channel();
         self.sender
             .send(ClientRequest {
-                data: ClientRequestData::Query {
-                    query: query.try_into()?.0,
+                data: ClientRequestData::SendCompressedData {
+                    frame,
                     response: sender,
                 },
             })
             .await
-            .map_err(|e| KlickhouseError::ProtocolError(format!("failed to send query: {e}")))?;
-        let receiver = receiver.await.map_err(|e| {
+            .map_err(|e| KlickhouseError::ProtocolError(format!("failed to send block: {e}")))?;
+        receiver.await.map_err(|e| {
             KlickhouseError::ProtocolError(format!("failed to receive blocks from upstream: {e}"))
         })?;
 
+        Ok(())
+    }
+
+    /// Sends all outgoing blocks of an insert, followed by the terminating empty block.
+    /// Blocks are compressed on `compression_threads` worker threads when configured.
+    async fn send_blocks(
+        &self,
+        mut blocks: impl Stream<Item = Result<Block>> + Send + Unpin,
+    ) -> Result<()> {
+        #[cfg(feature = "compression")]
+        if self.compression_threads > 1 && self.compression != CompressionMethod::None {
+            let revision = self.revision;
+            let compression = self.compression;
+            let mut frames = blocks
+                .map(|block| async move {
+                    let block = block?;
+                    tokio::task::spawn_blocking(move || {
+                        crate::compression::compress_frame(compression.byte(), block, revision)
+                    })
+                    .await
+                    .map_err(|e| {
+                        KlickhouseError::ProtocolError(format!("compression task failed: {e}"))
+                    })?
+                })
+                .buffered(self.compression_threads);
+            while let Some(frame) = frames.next().await {
+                self.send_compressed_data(frame?).await?;
+            }
+            return self.send_data(Block::default()).await;
+        }
+
         while let Some(block) = blocks.next().await {
-            self.send_data(block).await?;
+            self.send_data(block?).await?;
+        }
+        self.send_data(Block::default()).await
+    }
+
+    /// Sends an insert query and waits for the server's header block, returning the insert and the types of its columns.
+    async fn start_insert(
+        &self,
+        mut query: ParsedQuery,
+        options: QueryOptions,
+    ) -> Result<(StartedInsert, IndexMap<String, Type>)> {
+        query.sql = query.sql.trim().to_string();
+        let progress_receiver = self.progress.subscribe();
+        let wait = options.waits_for_insert();
+        let (id, mut receiver) = self.send_query(query, options).await?;
+        let first_block = receiver.recv().await.ok_or_else(|| {
+            KlickhouseError::ProtocolError("missing header block from server".to_string())
+        })??;
+        let insert = StartedInsert {
+            id,
+            receiver,
+            progress_receiver,
+            wait,
+        };
+        Ok((insert, first_block.column_types))
+    }
+
+    /// Sends the blocks of a started insert, then waits for the server to finish it, counting what was sent and written.
+    async fn complete_insert(
+        &self,
+        insert: StartedInsert,
+        blocks: impl Stream<Item = Result<Block>> + Send + Unpin,
+    ) -> Result<InsertResult> {
+        let StartedInsert {
+            id,
+            receiver,
+            mut progress_receiver,
+            wait,
+        } = insert;
+        let mut result = InsertResult::default();
+        let mut progress = Progress::default();
+        let sent = self
+            .send_blocks(blocks.inspect(|block| {
+                if let Ok(block) = block {
+                    result.rows += block.rows;
+                    result.blocks += 1;
+                }
+                collect_progress(&mut progress_receiver, id, &mut progress);
+            }))
+            .await;
+        let mut responses = ReceiverStream::new(receiver);
+        if let Err(e) = sent {
+            // the server is still reading the insert, end it before the connection runs other queries
+            self.cancel_query(id).await?;
+            while responses.next().await.is_some() {}
+            return Err(e);
+        }
+        if !wait {
+            return Ok(result);
         }
-        self.send_data(Block {
-            info: BlockInfo::default(),
-            rows: 0,
-            column_types: IndexMap::new(),
-            column_data: IndexMap::new(),
-        })
-        .await?;
+
+        while let Some(block) = responses.next().await {
+            block?;
+            collect_progress(&mut progress_receiver, id, &mut progress);
+        }
+        collect_progress(&mut progress_receiver, id, &mut progress);
+        result.bytes = progress.new_written_bytes.unwrap_or_default();
+        Ok(result)
+    }
+
+    /// Sends a query string with streaming associated data (i.e. insert) over native protocol.
+    /// Once all outgoing blocks are written (EOF of `blocks` stream), then any response blocks from Clickhouse are read.
+    /// You probably want [`Client::insert_native`].
+    pub async fn insert_native_raw(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        blocks: impl Stream<Item = Block> + Send + Unpin + 'static,
+    ) -> Result<impl Stream<Item = Result<Block>>> {
+        self.insert_native_raw_with_options(query, blocks, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `insert_native_raw`, with per-query [`QueryOptions`].
+    pub async fn insert_native_raw_with_options(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        blocks: impl Stream<Item = Block> + Send + Unpin + 'static,
+        options: QueryOptions,
+    ) -> Result<impl Stream<Item = Result<Block>>> {
+        let (_, receiver) = self.send_query(query.try_into()?, options).await?;
+
+        self.send_blocks(blocks.map(Ok)).await?;
 
         Ok(ReceiverStream::new(receiver))
     }
 
     /// Sends a query string with streaming associated data (i.e. insert) over native protocol.
-    /// Once all outgoing blocks are written (EOF of `blocks` stream), then any response blocks from Clickhouse are read and DISCARDED.
+    /// Once all outgoing blocks are written (EOF of `blocks` stream), then any response blocks from Clickhouse are read and DISCARDED,
+    /// until the server finished the insert.
     /// Make sure any query you send native data with has a `format native` suffix.
-    pub async fn insert_native<T: Row + Send + Sync + 'static>(
+    pub async fn insert_native<T: Row + Send + 'static>(
         &self,
         query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
-        mut blocks: impl Stream<Item = Vec<T>> + Send + Sync + Unpin + 'static,
-    ) -> Result<()> {
-        let (sender, receiver) = oneshot::channel();
-        self.sender
-            .send(ClientRequest {
-                data: ClientRequestData::Query {
-                    query: query.try_into()?.0.trim().to_string(),
-                    response: sender,
-                },
-            })
+        blocks: impl Stream<Item = Vec<T>> + Send + Unpin + 'static,
+    ) -> Result<InsertResult> {
+        self.insert_native_with_options(query, blocks, QueryOptions::default())
             .await
-            .map_err(|e| KlickhouseError::ProtocolError(format!("failed to send query: {e}")))?;
-        let mut receiver = receiver.await.map_err(|e| {
-            KlickhouseError::ProtocolError(format!("failed to receive blocks from upstream: {e}"))
-        })?;
-        let first_block = receiver.recv().await.ok_or_else(|| {
-            KlickhouseError::ProtocolError("missing header block from server".to_string())
-        })??;
-        while let Some(rows) = blocks.next().await {
-            if rows.is_empty() {
-                continue;
-            }
-            let mut block = Block {
-                info: BlockInfo::default(),
-                rows: rows.len() as u64,
-                column_types: first_block.column_types.clone(),
-                column_data: IndexMap::new(),
-            };
-            rows.into_iter()
-                .map(|x| x.serialize_row(&first_block.column_types))
-                .filter_map(|x| match x {
-                    Err(e) => {
-                        error!("serialization error during insert (SKIPPED ROWS!): {:?}", e);
-                        None
-                    }
-                    Ok(x) => Some(x),
-                })
-                .try_for_each(|x| -> Result<()> {
-                    for (key, value) in x {
-                        let type_ = first_block.column_types.get(&*key).ok_or_else(|| {
-                            KlickhouseError::ProtocolError(format!(
-                                "missing type for data, column: {key}"
-                            ))
-                        })?;
-                        type_.validate_value(&value)?;
-                        if let Some(column) = block.column_data.get_mut(&*key) {
-                            column.push(value);
-                        } else {
-                            block.column_data.insert(key.into_owned(), vec![value]);
+    }
+
+    /// Same as `insert_native`, with per-query [`QueryOptions`].
+    pub async fn insert_native_with_options<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        blocks: impl Stream<Item = Vec<T>> + Send + Unpin + 'static,
+        options: QueryOptions,
+    ) -> Result<InsertResult> {
+        let (insert, column_types) = self.start_insert(query.try_into()?, options).await?;
+        let fixed_string_overflow = self.fixed_string_overflow;
+        let blocks = blocks
+            .filter(|rows| std::future::ready(!rows.is_empty()))
+            .map(move |rows| Self::build_block(rows, &column_types, fixed_string_overflow));
+        self.complete_insert(insert, blocks).await
+    }
+
+    /// Same as `insert_native`, but takes single rows rather than batches, i.e. from a row-at-a-time producer.
+    /// Rows are sent in blocks of up to `batch_size` (at least 1) rows, or of the rows that arrived within `max_delay` of the first row of the block,
+    /// whichever is fewer. The insert ends with the stream. Shorthand for [`Client::insert_rows_stream`] without a limit on the size of blocks.
+    pub async fn insert_native_rows<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        rows: impl Stream<Item = T> + Send + 'static,
+        batch_size: usize,
+        max_delay: Duration,
+    ) -> Result<InsertResult> {
+        self.insert_native_rows_with_options(
+            query,
+            rows,
+            batch_size,
+            max_delay,
+            QueryOptions::default(),
+        )
+        .await
+    }
+
+    /// Same as `insert_native_rows`, with per-query [`QueryOptions`].
+    pub async fn insert_native_rows_with_options<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        rows: impl Stream<Item = T> + Send + 'static,
+        batch_size: usize,
+        max_delay: Duration,
+        options: QueryOptions,
+    ) -> Result<InsertResult> {
+        let batching = InsertBatching {
+            max_rows: batch_size,
+            max_bytes: usize::MAX,
+            max_delay,
+        };
+        self.insert_rows_stream_with_options(query, rows, batching, options)
+            .await
+    }
+
+    /// Same as `insert_native_rows`, but also limits the approximate size of blocks, see [`InsertBatching`].
+    /// Rows are only taken from `rows` while a block is being filled, and no faster than blocks are written to the connection,
+    /// so an unbounded source (i.e. an event stream) is slowed down to the insert rate rather than buffered in memory.
+    pub async fn insert_rows_stream<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        rows: impl Stream<Item = T> + Send + 'static,
+        batching: InsertBatching,
+    ) -> Result<InsertResult> {
+        self.insert_rows_stream_with_options(query, rows, batching, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `insert_rows_stream`, with per-query [`QueryOptions`].
+    pub async fn insert_rows_stream_with_options<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        rows: impl Stream<Item = T> + Send + 'static,
+        batching: InsertBatching,
+        options: QueryOptions,
+    ) -> Result<InsertResult> {
+        let (insert, column_types) = self.start_insert(query.try_into()?, options).await?;
+        let fixed_string_overflow = self.fixed_string_overflow;
+        let blocks = stream::unfold(Box::pin(rows), move |mut rows| {
+            let column_types = column_types.clone();
+            let batching = batching.clone();
+            async move {
+                let first = rows.next().await?;
+                let deadline = Instant::now() + batching.max_delay;
+                let mut columns = ColumnWriters::new(&column_types, 0)
+                    .truncate_fixed_strings(fixed_string_overflow == FixedStringOverflow::Truncate);
+                if let Err(e) = Self::serialize_row(&mut columns, first) {
+                    return Some((Err(e), rows));
+                }
+                while columns.rows() < batching.max_rows.max(1)
+                    && columns.bytes() < batching.max_bytes
+                {
+                    match tokio::time::timeout_at(deadline, rows.next()).await {
+                        Ok(Some(row)) => {
+                            if let Err(e) = Self::serialize_row(&mut columns, row) {
+                                return Some((Err(e), rows));
+                            }
                         }
+                        Ok(None) | Err(_) => break,
                     }
-                    Ok(())
-                })?;
-            self.send_data(block).await?;
-        }
-        self.send_data(Block {
-            info: BlockInfo::default(),
-            rows: 0,
-            column_types: IndexMap::new(),
-            column_data: IndexMap::new(),
+                }
+                Some((Ok(columns.into_block()), rows))
+            }
         })
-        .await?;
+        // rows that all failed to convert leave nothing to send
+        .filter(|block| std::future::ready(!matches!(block, Ok(block) if block.rows == 0)));
+        self.complete_insert(insert, Box::pin(blocks)).await
+    }
+
+    fn build_block<T: Row>(
+        rows: Vec<T>,
+        column_types: &IndexMap<String, Type>,
+        fixed_string_overflow: FixedStringOverflow,
+    ) -> Result<Block> {
+        let mut columns = ColumnWriters::new(column_types, rows.len())
+            .truncate_fixed_strings(fixed_string_overflow == FixedStringOverflow::Truncate);
+        for row in rows {
+            Self::serialize_row(&mut columns, row)?;
+        }
+        Ok(columns.into_block())
+    }
+
+    fn serialize_row<T: Row>(columns: &mut ColumnWriters, row: T) -> Result<()> {
+        match row.serialize_into(columns) {
+            Ok(()) => columns.end_row(),
+            Err(e) => {
+                // invalid values fail the block, only rows that fail to convert are skipped
+                columns.discard_row()?;
+                error!("serialization error during insert (SKIPPED ROWS!): {:?}", e);
+            }
+        }
         Ok(())
     }
 
     /// Wrapper over [`Client::insert_native`] to send a single block.
     /// Make sure any query you send native data with has a `format native` suffix.
-    pub async fn insert_native_block<T: Row + Send + Sync + 'static>(
+    pub async fn insert_native_block<T: Row + Send + 'static>(
         &self,
         query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
         blocks: Vec<T>,
-    ) -> Result<()> {
+    ) -> Result<InsertResult> {
+        self.insert_native_block_with_options(query, blocks, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `insert_native_block`, with per-query [`QueryOptions`].
+    pub async fn insert_native_block_with_options<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        blocks: Vec<T>,
+        options: QueryOptions,
+    ) -> Result<InsertResult> {
         let blocks = Box::pin(async move { blocks });
         let stream = futures_util::stream::once(blocks);
-        self.insert_native(query, stream).await
+        self.insert_native_with_options(query, stream, options)
+            .await
+    }
+
+    /// Inserts `rows` as a server-side asynchronous insert, which the server buffers with those of other inserts into the same table,
+    /// i.e. for many small inserts from many clients. See [`AsyncInsertWait`] for when this returns, and the delivery guarantees of each choice.
+    /// Make sure the query has a `format native` suffix.
+    pub async fn insert_async<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        rows: Vec<T>,
+        wait: AsyncInsertWait,
+    ) -> Result<InsertResult> {
+        self.insert_async_with_options(query, rows, wait, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `insert_async`, with per-query [`QueryOptions`].
+    pub async fn insert_async_with_options<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        rows: Vec<T>,
+        wait: AsyncInsertWait,
+        options: QueryOptions,
+    ) -> Result<InsertResult> {
+        self.insert_native_block_with_options(query, rows, options.async_insert(wait))
+            .await
+    }
+
+    /// Inserts `rows` into `table` with an `INSERT INTO table (columns...) FORMAT Native` statement listing the columns of `T`,
+    /// so fields are matched to columns by name rather than by their order in the table.
+    /// Columns of the table not in `T` are filled with their defaults. Fails if `T` doesn't provide [`Row::column_names`].
+    pub async fn insert<T: Row + Send + 'static>(
+        &self,
+        table: impl Into<QualifiedTable>,
+        rows: Vec<T>,
+    ) -> Result<InsertResult> {
+        self.insert_with_options(table, rows, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `insert`, with per-query [`QueryOptions`].
+    pub async fn insert_with_options<T: Row + Send + 'static>(
+        &self,
+        table: impl Into<QualifiedTable>,
+        rows: Vec<T>,
+        options: QueryOptions,
+    ) -> Result<InsertResult> {
+        let query = insert_query::<T>(&table.into())?;
+        self.insert_native_block_with_options(query, rows, options)
+            .await
     }
 
     /// Runs a query against Clickhouse, returning a stream of deserialized rows.
@@ -474,38 +1442,178 @@ impl Client {
         &self,
         query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
     ) -> Result<impl Stream<Item = Result<T>>> {
-        let raw = self.query_raw(query).await?;
-        Ok(raw.flat_map(|block| match block {
-            Ok(mut block) => stream::iter(
-                block
-                    .take_iter_rows()
-                    .filter(|x| !x.is_empty())
-                    .map(|m| T::deserialize_row(m))
-                    .collect::<Vec<_>>(),
-            ),
-            Err(e) => stream::iter(vec![Err(e)]),
-        }))
+        self.query_with_options(query, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `query`, with per-query [`QueryOptions`].
+    pub async fn query_with_options<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
+    ) -> Result<impl Stream<Item = Result<T>>> {
+        let (_, rows) = self.query_with_id(query, options).await?;
+        Ok(rows)
+    }
+
+    /// Same as `query_with_options`, but also returns the ID the query was sent with, which is [`QueryOptions::query_id`] if set.
+    /// Use it to match [`Client::subscribe_progress`] events, to [cancel](Client::cancel_query) the query, or to look it up in `system.query_log`.
+    pub async fn query_with_id<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
+    ) -> Result<(Uuid, impl Stream<Item = Result<T>>)> {
+        let keep_empty_rows = options.keep_empty_rows;
+        let utf8 = options.utf8;
+        let (id, raw) = self.query_raw_with_id(query, options).await?;
+        Ok((
+            id,
+            raw.flat_map(move |block| match block {
+                Ok(block) => stream::iter(deserialize_block(block, keep_empty_rows, utf8)),
+                Err(e) => stream::iter(vec![Err(e)]),
+            }),
+        ))
+    }
+
+    /// Same as `query`, but returns only the rows of the first block that has any, and cancels the rest of the query on the server.
+    /// For queries with tiny results (i.e. a point lookup), this avoids waiting for the end of stream behind slowly streamed trailing packets.
+    /// Rows the server sends in later blocks are discarded, so only use it for results that fit in one block.
+    pub async fn query_first_block<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<Vec<T>> {
+        self.query_first_block_with_options(query, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `query_first_block`, with per-query [`QueryOptions`].
+    pub async fn query_first_block_with_options<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
+    ) -> Result<Vec<T>> {
+        let utf8 = options.utf8;
+        let (id, mut receiver) = self.send_query(query.try_into()?, options).await?;
+        while let Some(block) = receiver.recv().await {
+            let block = block?;
+            if block.rows == 0 {
+                continue;
+            }
+            drop(receiver);
+            self.cancel_query(id).await?;
+            return deserialize_block(block, false, utf8).into_iter().collect();
+        }
+        Ok(vec![])
+    }
+
+    /// Asks the server to stop the query with this ID, if it is executing on this connection. Does nothing otherwise, i.e. if it already ended.
+    /// The query's stream then ends early.
+    pub async fn cancel_query(&self, id: Uuid) -> Result<()> {
+        self.sender
+            .send(ClientRequest {
+                data: ClientRequestData::Cancel { id },
+            })
+            .await
+            .map_err(|e| KlickhouseError::ProtocolError(format!("failed to cancel query: {e}")))
+    }
+
+    /// Same as `query`, but deserializes rows with `serde` rather than [`Row`]. See [`crate::SerdeRow`].
+    #[cfg(feature = "serde_row")]
+    pub async fn query_serde<T: serde::Serialize + serde::de::DeserializeOwned>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<impl Stream<Item = Result<T>>> {
+        Ok(self
+            .query::<crate::SerdeRow<T>>(query)
+            .await?
+            .map(|row| row.map(|row| row.0)))
+    }
+
+    /// Same as `insert_native`, but serializes rows with `serde` rather than [`Row`]. See [`crate::SerdeRow`].
+    /// Make sure any query you send native data with has a `format native` suffix.
+    #[cfg(feature = "serde_row")]
+    pub async fn insert_native_serde<
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    >(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        blocks: impl Stream<Item = Vec<T>> + Send + Unpin + 'static,
+    ) -> Result<InsertResult> {
+        self.insert_native(
+            query,
+            blocks.map(|rows| {
+                rows.into_iter()
+                    .map(crate::SerdeRow::new)
+                    .collect::<Vec<_>>()
+            }),
+        )
+        .await
     }
 
     /// Same as `query`, but collects all rows into a `Vec`
     pub async fn query_collect<T: Row>(
         &self,
         query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<Vec<T>> {
+        self.query_collect_with_options(query, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `query_collect`, with per-query [`QueryOptions`].
+    pub async fn query_collect_with_options<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
     ) -> Result<Vec<T>> {
         let mut out = vec![];
-        let mut stream = self.query::<T>(query).await?;
+        let mut stream = self.query_with_options::<T>(query, options).await?;
         while let Some(next) = stream.next().await {
             out.push(next?);
         }
         Ok(out)
     }
 
+    /// Same as `query`, but yields rows in chunks of `chunk_size` (at least 1), regardless of how the server splits them into blocks.
+    /// Only the last chunk may be shorter. A chunk containing a row that failed to deserialize is an error.
+    pub async fn query_chunked<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        chunk_size: usize,
+    ) -> Result<impl Stream<Item = Result<Vec<T>>>> {
+        self.query_chunked_with_options(query, chunk_size, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `query_chunked`, with per-query [`QueryOptions`].
+    pub async fn query_chunked_with_options<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        chunk_size: usize,
+        options: QueryOptions,
+    ) -> Result<impl Stream<Item = Result<Vec<T>>>> {
+        Ok(self
+            .query_with_options::<T>(query, options)
+            .await?
+            .chunks(chunk_size.max(1))
+            .map(|rows| rows.into_iter().collect()))
+    }
+
     /// Same as `query`, but returns the first row and discards the rest.
     pub async fn query_one<T: Row>(
         &self,
         query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
     ) -> Result<T> {
-        self.query::<T>(query)
+        self.query_one_with_options(query, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `query_one`, with per-query [`QueryOptions`].
+    pub async fn query_one_with_options<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
+    ) -> Result<T> {
+        self.query_with_options::<T>(query, options)
             .await?
             .next()
             .await
@@ -517,7 +1625,21 @@ impl Client {
         &self,
         query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
     ) -> Result<Option<T>> {
-        self.query::<T>(query).await?.next().await.transpose()
+        self.query_opt_with_options(query, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `query_opt`, with per-query [`QueryOptions`].
+    pub async fn query_opt_with_options<T: Row>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
+    ) -> Result<Option<T>> {
+        self.query_with_options::<T>(query, options)
+            .await?
+            .next()
+            .await
+            .transpose()
     }
 
     /// Same as `query`, but discards all returns blocks. Waits until the first block returns from the server to check for errors.
@@ -526,13 +1648,42 @@ impl Client {
         &self,
         query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
     ) -> Result<()> {
-        let mut stream = self.query::<RawRow>(query).await?;
+        self.execute_with_options(query, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `execute`, with per-query [`QueryOptions`].
+    pub async fn execute_with_options(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        options: QueryOptions,
+    ) -> Result<()> {
+        let mut stream = self.query_with_options::<RawRow>(query, options).await?;
         while let Some(next) = stream.next().await {
             next?;
         }
         Ok(())
     }
 
+    /// Same as `execute`, but also returns the sum of the progress reported for the query (i.e. written rows for an `INSERT ... SELECT`).
+    /// Progress is best-effort: it may be incomplete if more than a hundred progress packets arrive faster than they are collected.
+    pub async fn execute_with_progress(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<Progress> {
+        let query = query.try_into()?;
+        let mut progress_receiver = self.progress.subscribe();
+        let (id, receiver) = self.send_query(query, QueryOptions::default()).await?;
+        let mut blocks = ReceiverStream::new(receiver);
+        let mut progress = Progress::default();
+        while let Some(block) = blocks.next().await {
+            block?;
+            collect_progress(&mut progress_receiver, id, &mut progress);
+        }
+        collect_progress(&mut progress_receiver, id, &mut progress);
+        Ok(progress)
+    }
+
     /// Same as `execute`, but doesn't wait for a server response. The query could get aborted if the connection is closed quickly.
     pub async fn execute_now(
         &self,
@@ -542,18 +1693,227 @@ impl Client {
         Ok(())
     }
 
+    /// Queues a query without waiting for the connection task or the server, discarding its results.
+    /// Unlike spawning `execute`, the query is ordered before any query sent afterwards: it skips `max_concurrent_queries` and is queued with [`QueryPriority::High`].
+    /// Fails if the request queue is full or closed.
+    pub(crate) fn execute_detached(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<()> {
+        let (sender, _) = oneshot::channel();
+        self.sender
+            .try_send(ClientRequest {
+                data: ClientRequestData::Query {
+                    query: query.try_into()?,
+                    options: QueryOptions::default().queue_priority(QueryPriority::High),
+                    response: sender,
+                    permit: None,
+                },
+            })
+            .map_err(|e| KlickhouseError::ProtocolError(format!("failed to send query: {e}")))
+    }
+
+    /// Sends a protocol-level ping and waits for the server to answer, i.e. to check that an idle connection is still alive.
+    /// Unlike a `SELECT 1`, this doesn't show up in the query log. If a query is executing, the ping is sent once it ends.
+    pub async fn ping(&self) -> Result<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.sender
+            .send(ClientRequest {
+                data: ClientRequestData::Ping { response: sender },
+            })
+            .await
+            .map_err(|e| KlickhouseError::ProtocolError(format!("failed to send ping: {e}")))?;
+        receiver.await.map_err(|e| {
+            KlickhouseError::ProtocolError(format!("connection closed before pong: {e}"))
+        })
+    }
+
     /// true if the Client is closed
     pub fn is_closed(&self) -> bool {
         self.sender.is_closed()
     }
 
-    /// Receive progress on the queries as they execute.
-    ///
-    /// TODO: There is currently no way to retrieve the ID of a query launched
-    ///       with `query` or `execute.`
-    ///       The signature of these functions should be modified to also return
-    ///       an ID (and possibly directly the streaming broadcast).
+    /// Whether `other` is a clone of this client, using the same connection.
+    pub(crate) fn same_connection(&self, other: &Client) -> bool {
+        self.sender.same_channel(&other.sender)
+    }
+
+    /// Receive progress on the queries as they execute, along with their IDs as returned by [`Client::query_with_id`].
+    /// Subscribe before sending the query so no progress is missed, and set [`QueryOptions::query_id`] to know its ID upfront.
     pub fn subscribe_progress(&self) -> broadcast::Receiver<(Uuid, Progress)> {
         self.progress.subscribe()
     }
+
+    /// Receive lifecycle events of this connection as they happen, i.e. to report connection health.
+    /// `Connected` is sent before the `Client` is returned, so it is only observable through [`ClientOptions::event_sender`].
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ClientEvent> {
+        self.events.subscribe()
+    }
+
+    /// Number of queries submitted but not yet sent to the server, i.e. to export the queue depth as a metric.
+    /// Includes those waiting for [`ClientOptions::max_concurrent_queries`].
+    pub fn queued_queries(&self) -> usize {
+        self.admission.queued()
+    }
+}
+
+/// The `INSERT` statement of [`Client::insert`], listing the columns of `T`.
+fn insert_query<T: Row>(table: &QualifiedTable) -> Result<String> {
+    let columns = T::column_names().ok_or_else(|| {
+        KlickhouseError::QueryArgumentError(format!(
+            "{} doesn't provide its column names, use insert_native with a column list",
+            std::any::type_name::<T>()
+        ))
+    })?;
+    let columns = columns
+        .into_iter()
+        .map(|x| Identifier::new(x).to_string())
+        .collect::<Vec<_>>();
+    Ok(format!(
+        "INSERT INTO {table} ({}) FORMAT Native",
+        columns.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    fn assert_send_sync_value<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn client_is_send_sync() {
+        assert_send_sync::<Client>();
+        assert_send_sync::<Arc<Client>>();
+        assert_send_sync::<ClientOptions>();
+        assert_send_sync::<KlickhouseError>();
+    }
+
+    #[test]
+    fn test_scrub_sql() {
+        let query = crate::QueryBuilder::new("SELECT * FROM users WHERE password = $1")
+            .arg("hunter2")
+            .redact(true)
+            .finalize()
+            .unwrap();
+        let error = KlickhouseError::ServerException {
+            code: 62,
+            name: "DB::Exception".to_string(),
+            message: format!("Syntax error in query: {}", query.as_sql()),
+            stack_trace: String::new(),
+            query_id: None,
+            nested: None,
+        };
+        let KlickhouseError::ServerException { message, .. } = scrub_sql(error, &query) else {
+            panic!("unexpected error variant");
+        };
+        assert!(!message.contains("hunter2"));
+        assert!(message.contains("password = $1"));
+
+        // values quoted on their own, i.e. by parse errors, are scrubbed too
+        let query = crate::QueryBuilder::new("SELECT toDate($1), $2")
+            .arg("it's 2024")
+            .arg(123456u32)
+            .redact(true)
+            .finalize()
+            .unwrap();
+        let error = KlickhouseError::ServerException {
+            code: 38,
+            name: "DB::Exception".to_string(),
+            message: "Cannot parse date: value it's 2024 (123456) in query".to_string(),
+            stack_trace: String::new(),
+            query_id: None,
+            nested: Some(Box::new(KlickhouseError::ServerException {
+                code: 38,
+                name: "DB::Exception".to_string(),
+                message: format!("While executing {}", query.as_sql()),
+                stack_trace: String::new(),
+                query_id: None,
+                nested: None,
+            })),
+        };
+        let KlickhouseError::ServerException {
+            message, nested, ..
+        } = scrub_sql(error, &query)
+        else {
+            panic!("unexpected error variant");
+        };
+        assert_eq!(
+            message,
+            "Cannot parse date: value <redacted> (<redacted>) in query"
+        );
+        let Some(KlickhouseError::ServerException { message, .. }) = nested.map(|x| *x) else {
+            panic!("unexpected error variant");
+        };
+        assert_eq!(message, format!("While executing {}", query.loggable()));
+    }
+
+    #[test]
+    fn test_insert_query() {
+        struct Event;
+
+        impl Row for Event {
+            const COLUMN_COUNT: Option<usize> = Some(2);
+
+            fn column_names() -> Option<Vec<std::borrow::Cow<'static, str>>> {
+                Some(vec!["id".into(), "user name".into()])
+            }
+
+            fn deserialize_row(_map: Vec<(&str, &Type, crate::Value)>) -> Result<Self> {
+                Ok(Event)
+            }
+
+            fn serialize_row(
+                self,
+                _type_hints: &IndexMap<String, Type>,
+            ) -> Result<Vec<(std::borrow::Cow<'static, str>, crate::Value)>> {
+                Ok(vec![])
+            }
+        }
+
+        assert_eq!(
+            insert_query::<Event>(&QualifiedTable::with_database("db", "events")).unwrap(),
+            "INSERT INTO `db`.`events` (`id`, `user name`) FORMAT Native"
+        );
+        assert!(insert_query::<RawRow>(&"events".into()).is_err());
+    }
+
+    /// Never executed, only type checked.
+    #[allow(dead_code)]
+    async fn futures_are_send(client: &Client) -> Result<()> {
+        assert_send(&client.query_raw("SELECT 1"));
+        assert_send(&client.query::<RawRow>("SELECT 1"));
+        assert_send(&client.query_collect::<RawRow>("SELECT 1"));
+        assert_send(&client.query_one::<RawRow>("SELECT 1"));
+        assert_send(&client.query_chunked::<RawRow>("SELECT 1", 10));
+        assert_send(&client.execute("SELECT 1"));
+        assert_send(&client.insert_native::<RawRow>(
+            "INSERT INTO t FORMAT native",
+            futures_util::stream::empty().boxed(),
+        ));
+        assert_send(&client.insert_native_raw(
+            "INSERT INTO t FORMAT native",
+            futures_util::stream::empty().boxed(),
+        ));
+
+        assert_send(&client.insert::<RawRow>("t", vec![]));
+
+        let raw = client.query_raw("SELECT 1").await?;
+        assert_send_sync_value(&raw);
+        let rows = client.query::<RawRow>("SELECT 1").await?;
+        assert_send_sync_value(&rows);
+        let chunks = client.query_chunked::<RawRow>("SELECT 1", 10).await?;
+        assert_send_sync_value(&chunks);
+        assert_send(&tokio::spawn({
+            let client = client.clone();
+            async move { client.execute("SELECT 1").await }
+        }));
+        Ok(())
+    }
 }
@@ -13,9 +13,9 @@ use crate::io::ClickhouseRead;
 use crate::protocol::CompressionMethod;
 use crate::{KlickhouseError, Result};
 
-pub async fn compress_block(block: Block, revision: u64) -> Result<(Vec<u8>, usize)> {
+pub fn compress_block(block: Block, revision: u64) -> Result<(Vec<u8>, usize)> {
     let mut raw = vec![];
-    block.write(&mut raw, revision).await?;
+    block.write_buffer(&mut raw, revision)?;
     let raw_len = raw.len();
     let mut compressed = Vec::<u8>::with_capacity(raw.len() + (raw.len() / 255) + 16 + 1);
     let out_len = unsafe {
@@ -39,6 +39,23 @@ pub async fn compress_block(block: Block, revision: u64) -> Result<(Vec<u8>, usi
     Ok((compressed, raw_len))
 }
 
+/// Compresses a block into a complete checksummed frame, ready to follow a data packet header.
+/// This is CPU-bound and never awaits, so it can run on a blocking thread.
+pub fn compress_frame(byte: u8, block: Block, revision: u64) -> Result<Vec<u8>> {
+    let (out, decompressed_size) = compress_block(block, revision)?;
+    let mut frame = Vec::with_capacity(out.len() + 25);
+    frame.extend_from_slice(&[0u8; 16]);
+    frame.push(byte);
+    frame.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
+    frame.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
+    frame.extend(out);
+
+    let hash = cityhash_rs::cityhash_102_128(&frame[16..]);
+    frame[..8].copy_from_slice(&((hash >> 64) as u64).to_le_bytes()[..]);
+    frame[8..16].copy_from_slice(&(hash as u64).to_le_bytes()[..]);
+    Ok(frame)
+}
+
 pub fn decompress_block(data: &[u8], decompressed_size: u32) -> Result<Vec<u8>> {
     let mut output = Vec::with_capacity(decompressed_size as usize + 1);
 
@@ -197,3 +214,38 @@ impl<'a, R: ClickhouseRead + 'static> AsyncRead for DecompressionReader<'a, R> {
         Poll::Ready(Ok(()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{protocol::DBMS_TCP_PROTOCOL_VERSION, Type, Value};
+
+    #[tokio::test]
+    async fn test_compress_frame_roundtrip() {
+        let mut block = Block::default();
+        block
+            .insert_column(
+                "s",
+                Type::String,
+                (0..1000)
+                    .map(|i| Value::string(format!("row {i}")))
+                    .collect(),
+            )
+            .unwrap();
+        let frame = compress_frame(
+            CompressionMethod::LZ4.byte(),
+            block.clone(),
+            DBMS_TCP_PROTOCOL_VERSION,
+        )
+        .unwrap();
+
+        let raw = read_compressed_blob(&mut &frame[..], CompressionMethod::LZ4)
+            .await
+            .unwrap();
+        let decoded = Block::read(&mut &raw[..], DBMS_TCP_PROTOCOL_VERSION)
+            .await
+            .unwrap();
+        assert_eq!(decoded.rows, block.rows);
+        assert_eq!(decoded.column_data, block.column_data);
+    }
+}
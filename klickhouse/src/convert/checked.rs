@@ -0,0 +1,75 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{FromSql, KlickhouseError, Result, ToSql, Type, Value};
+
+/// Opt-in lossy numeric conversion: reads any numeric column into `T` as long as the value fits,
+/// i.e. an `Int64` column into a `Checked<i32>` or a `Float64` column into a `Checked<f32>`.
+/// Out of range values (or fractional values read into an integer) are an error rather than being truncated.
+///
+/// Conversions follow [`Value::coerce_to`]. When serializing, the value is coerced to the column type in the same way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct Checked<T>(pub T);
+
+macro_rules! checked_impl {
+    ($($t:ty: $variant:ident),+ $(,)?) => {
+        $(
+            impl FromSql for Checked<$t> {
+                fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+                    let value = value.coerce_to(&Type::$variant).map_err(|_| {
+                        KlickhouseError::DeserializeError(format!(
+                            "value of type {} out of range for {}",
+                            type_,
+                            stringify!($t)
+                        ))
+                    })?;
+                    <$t>::from_sql(&Type::$variant, value).map(Checked)
+                }
+            }
+        )+
+    };
+}
+
+checked_impl! {
+    u8: UInt8,
+    u16: UInt16,
+    u32: UInt32,
+    u64: UInt64,
+    u128: UInt128,
+    i8: Int8,
+    i16: Int16,
+    i32: Int32,
+    i64: Int64,
+    i128: Int128,
+    f32: Float32,
+    f64: Float64,
+}
+
+impl<T: ToSql> ToSql for Checked<T> {
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        let value = self.0.to_sql(type_hint)?;
+        match type_hint {
+            Some(type_) => value.coerce_to(type_),
+            None => Ok(value),
+        }
+    }
+}
+
+impl<T> Deref for Checked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Checked<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for Checked<T> {
+    fn from(value: T) -> Self {
+        Checked(value)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::Row;
+
+/// A row of a `CollapsingMergeTree` or `VersionedCollapsingMergeTree` table, derived for structs with a `#[klickhouse(sign)]` field.
+///
+/// A row is changed by inserting a cancel row (sign `-1`) identical to its current state, followed by a state row (sign `1`) with the new values.
+/// Rows are collapsed in pairs when parts are merged, or at read time with `FINAL`.
+pub trait CollapsingRow: Row {
+    /// Sets the sign column.
+    fn set_sign(&mut self, sign: i8);
+
+    /// This row as a state row.
+    fn state(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_sign(1);
+        self
+    }
+
+    /// The row cancelling the state row `self`.
+    fn cancel(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_sign(-1);
+        self
+    }
+
+    /// Rows changing the state from `old` to `new`. `None` for `old` inserts `new`, and `None` for `new` deletes `old`.
+    fn change(old: Option<Self>, new: Option<Self>) -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        old.map(Self::cancel)
+            .into_iter()
+            .chain(new.map(Self::state))
+            .collect()
+    }
+}
+
+/// Rows applying each of `changes`, as pairs of old and new state, in order. See [`CollapsingRow::change`].
+pub fn collapsing_changes<T: CollapsingRow>(
+    changes: impl IntoIterator<Item = (Option<T>, Option<T>)>,
+) -> Vec<T> {
+    changes
+        .into_iter()
+        .flat_map(|(old, new)| T::change(old, new))
+        .collect()
+}
@@ -0,0 +1,176 @@
+use indexmap::IndexMap;
+
+use crate::{
+    block::{Block, BlockInfo},
+    KlickhouseError, Result, Type, Value,
+};
+
+/// The columns of an insert block, which [`Row::serialize_into`](crate::Row::serialize_into) appends a row to.
+///
+/// Values are validated against their column's type as they are pushed, and stored column by column,
+/// so rows don't need to be collected and re-pivoted into columns.
+pub struct ColumnWriters<'a> {
+    type_hints: &'a IndexMap<String, Type>,
+    /// Values of each column in `type_hints`, by index. Columns that are never pushed to are left out of the block.
+    columns: Vec<Option<Vec<Value>>>,
+    capacity: usize,
+    truncate_fixed_strings: bool,
+    rows: usize,
+    /// Approximate encoded size of the complete rows, and of the values pushed since
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    bytes: usize,
+    pending_bytes: usize,
+    /// Error of a pushed value, which fails the whole block rather than just its row
+    error: Option<KlickhouseError>,
+}
+
+impl<'a> ColumnWriters<'a> {
+    /// Starts an empty block with the columns in `type_hints`, reserving space for `capacity` rows.
+    pub fn new(type_hints: &'a IndexMap<String, Type>, capacity: usize) -> Self {
+        Self {
+            type_hints,
+            columns: (0..type_hints.len()).map(|_| None).collect(),
+            capacity,
+            truncate_fixed_strings: false,
+            rows: 0,
+            bytes: 0,
+            pending_bytes: 0,
+            error: None,
+        }
+    }
+
+    /// If `true`, values longer than their `FixedString(N)` column are truncated rather than rejected.
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn truncate_fixed_strings(mut self, truncate: bool) -> Self {
+        self.truncate_fixed_strings = truncate;
+        self
+    }
+
+    /// Types of the block's columns by name.
+    pub fn type_hints(&self) -> &'a IndexMap<String, Type> {
+        self.type_hints
+    }
+
+    /// Type of the column `name`, if the block has it.
+    pub fn type_hint(&self, name: &str) -> Option<&'a Type> {
+        self.type_hints.get(name)
+    }
+
+    /// Number of complete rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Approximate size of the complete rows in a native block, see [`Value::encoded_size_hint`].
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Appends `value` to the column `name`. Fails if the block has no such column, or if `value` doesn't fit its type.
+    pub fn push(&mut self, name: &str, mut value: Value) -> Result<()> {
+        let Some((index, _, type_)) = self.type_hints.get_full(name) else {
+            return Err(self.fail(KlickhouseError::ProtocolError(format!(
+                "missing type for data, column: {name}"
+            ))));
+        };
+        if self.truncate_fixed_strings {
+            type_.truncate_fixed_strings(&mut value);
+        }
+        if let Err(e) = type_.validate_value(&value) {
+            return Err(self.fail(e));
+        }
+        let capacity = self.capacity;
+        self.pending_bytes += value.encoded_size_hint();
+        self.columns[index]
+            .get_or_insert_with(|| Vec::with_capacity(capacity))
+            .push(value);
+        Ok(())
+    }
+
+    fn fail(&mut self, error: KlickhouseError) -> KlickhouseError {
+        self.error.get_or_insert_with(|| error.clone());
+        error
+    }
+
+    /// Completes the row pushed since the last call.
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn end_row(&mut self) {
+        self.rows += 1;
+        self.bytes += std::mem::take(&mut self.pending_bytes);
+    }
+
+    /// Drops the values pushed since the last completed row. Returns the error of a pushed value, if any, which should fail the block.
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn discard_row(&mut self) -> Result<()> {
+        for column in self.columns.iter_mut().flatten() {
+            column.truncate(self.rows);
+        }
+        self.pending_bytes = 0;
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Builds the block of all complete rows.
+    pub fn into_block(self) -> Block {
+        let mut column_data = IndexMap::with_capacity(self.columns.len());
+        for ((name, _), values) in self.type_hints.iter().zip(self.columns) {
+            if let Some(values) = values {
+                column_data.insert(name.clone(), values);
+            }
+        }
+        Block {
+            info: BlockInfo::default(),
+            rows: self.rows as u64,
+            column_types: self.type_hints.clone(),
+            column_data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_writers() {
+        let types = IndexMap::from([
+            ("a".to_string(), Type::UInt32),
+            ("b".to_string(), Type::FixedString(2)),
+            ("c".to_string(), Type::String),
+        ]);
+        let mut writers = ColumnWriters::new(&types, 2);
+        assert_eq!(writers.type_hint("b"), Some(&Type::FixedString(2)));
+        writers.push("b", Value::String(b"xy".to_vec())).unwrap();
+        writers.push("a", Value::UInt32(1)).unwrap();
+        writers.end_row();
+
+        // a failed row is dropped without failing the block
+        writers.push("b", Value::String(b"zz".to_vec())).unwrap();
+        writers.discard_row().unwrap();
+        assert_eq!(writers.rows(), 1);
+        assert_eq!(writers.bytes(), 8);
+
+        writers.push("a", Value::UInt32(2)).unwrap();
+        assert!(writers.push("b", Value::String(b"xyz".to_vec())).is_err());
+        assert!(writers.discard_row().is_err());
+        assert!(writers.push("d", Value::UInt32(1)).is_err());
+        assert!(writers.discard_row().is_err());
+
+        let block = writers.into_block();
+        assert_eq!(block.rows, 1);
+        assert_eq!(block.column_types, types);
+        assert_eq!(block.column_data.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(block.column_data["a"], vec![Value::UInt32(1)]);
+
+        let mut writers = ColumnWriters::new(&types, 1).truncate_fixed_strings(true);
+        writers.push("b", Value::String(b"xyz".to_vec())).unwrap();
+        writers.end_row();
+        assert_eq!(
+            writers.into_block().column_data["b"],
+            vec![Value::String(b"xy".to_vec())]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::{borrow::Cow, fmt};
+
+use chrono_tz::UTC;
+
+use crate::{FromSql, KlickhouseError, Type};
+
+/// Types tried by [`ExpectedColumn::default_type`], widest first so no field is narrowed,
+/// and signed before unsigned integers so unsigned fields don't accept them.
+const DEFAULT_TYPES: &[Type] = &[
+    Type::Float64,
+    Type::Float32,
+    Type::Int64,
+    Type::UInt64,
+    Type::Int32,
+    Type::UInt32,
+    Type::Int16,
+    Type::UInt16,
+    Type::Int8,
+    Type::UInt8,
+    Type::Uuid,
+    Type::Ipv4,
+    Type::Ipv6,
+    Type::Date,
+    Type::DateTime64(6, UTC),
+    Type::DateTime(UTC),
+    Type::String,
+];
+
+/// A column of a [`Row`](crate::Row) as given by [`Row::column_types`](crate::Row::column_types): its name, and which Clickhouse types it can be read from.
+#[derive(Clone)]
+pub struct ExpectedColumn {
+    pub name: Cow<'static, str>,
+    /// Name of the Rust type the column is read into, for diagnostics.
+    pub rust_type: &'static str,
+    accepts: Option<fn(&Type) -> bool>,
+    /// Levels of `Nested` structures the column is in, each wrapping its type in an `Array`.
+    nesting: usize,
+}
+
+impl ExpectedColumn {
+    /// A column read with [`FromSql`] into `T`.
+    pub fn of<T: FromSql>(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            rust_type: std::any::type_name::<T>(),
+            accepts: Some(accepts::<T>),
+            nesting: 0,
+        }
+    }
+
+    /// A column read into `T` some other way, i.e. by a `deserialize_with` function, whose type isn't checked.
+    pub fn any<T: ?Sized>(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            rust_type: std::any::type_name::<T>(),
+            accepts: None,
+            nesting: 0,
+        }
+    }
+
+    /// This column as a subcolumn of a `Nested` structure, named `name` (i.e. `parent.column`), that holds an array of the column's type.
+    pub fn nested(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = name.into();
+        self.nesting += 1;
+        self
+    }
+
+    /// Whether the column can be read from a column of type `type_`.
+    pub fn accepts(&self, type_: &Type) -> bool {
+        let Some(accepts) = self.accepts else {
+            return true;
+        };
+        let mut type_ = type_;
+        for _ in 0..self.nesting {
+            match type_ {
+                Type::Array(inner) => type_ = inner,
+                _ => return false,
+            }
+        }
+        // as the client does for the columns of query results
+        accepts(type_.strip_low_cardinality())
+    }
+
+    /// A type to create the column with when there are no values to guess it from, i.e. `Nullable(Int64)` for an `Option<i64>`:
+    /// the first of the common numeric, UUID, IP, date and string types the column accepts, preferring `Nullable` ones.
+    /// `None` for other types, such as decimals, and for columns read without [`FromSql`].
+    pub fn default_type(&self) -> Option<Type> {
+        self.accepts?;
+        let type_ = DEFAULT_TYPES
+            .iter()
+            .flat_map(|type_| [Type::Nullable(Box::new(type_.clone())), type_.clone()])
+            .map(|type_| (0..self.nesting).fold(type_, |type_, _| Type::Array(Box::new(type_))))
+            .find(|type_| self.accepts(type_))?;
+        Some(type_)
+    }
+}
+
+impl fmt::Debug for ExpectedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpectedColumn")
+            .field("name", &self.name)
+            .field("rust_type", &self.rust_type)
+            .field("checked", &self.accepts.is_some())
+            .field("nesting", &self.nesting)
+            .finish()
+    }
+}
+
+/// Reads a sample value of `type_` into `T`, and only rejects the type for errors about the type or shape of the value
+/// ([`unexpected_type`](crate::unexpected_type) and [`unexpected_value`](crate::unexpected_value)), rather than its contents.
+fn accepts<T: FromSql>(type_: &Type) -> bool {
+    !matches!(
+        T::from_sql(type_, type_.sample_value()),
+        Err(KlickhouseError::UnexpectedType(_)
+            | KlickhouseError::UnexpectedTypeWithColumn(..)
+            | KlickhouseError::UnexpectedValue { .. })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts() {
+        let column = ExpectedColumn::of::<u32>("a");
+        assert!(column.accepts(&Type::UInt32));
+        assert!(column.accepts(&Type::UInt8));
+        assert!(!column.accepts(&Type::Int64));
+        assert!(!column.accepts(&Type::Nullable(Box::new(Type::UInt32))));
+        assert!(!column.accepts(&Type::String));
+
+        let column = ExpectedColumn::of::<Option<String>>("a");
+        assert!(column.accepts(&Type::Nullable(Box::new(Type::String))));
+        assert!(column.accepts(&Type::String));
+        assert!(!column.accepts(&Type::Nullable(Box::new(Type::UInt32))));
+
+        let column = ExpectedColumn::of::<String>("a");
+        assert!(column.accepts(&"Enum8('a' = 1)".parse().unwrap()));
+        assert!(column.accepts(&Type::LowCardinality(Box::new(Type::String))));
+
+        let column = ExpectedColumn::of::<Vec<u8>>("a");
+        assert!(column.accepts(&Type::Array(Box::new(Type::UInt8))));
+        assert!(!column.accepts(&Type::Array(Box::new(Type::Float64))));
+
+        let column = ExpectedColumn::of::<(u8, String)>("a");
+        assert!(column.accepts(&"Tuple(UInt8, String)".parse().unwrap()));
+        assert!(!column.accepts(&"Tuple(UInt8, UInt8)".parse().unwrap()));
+
+        let column = ExpectedColumn::of::<u16>("b").nested("n.b");
+        assert_eq!(column.name, "n.b");
+        assert!(column.accepts(&Type::Array(Box::new(Type::UInt16))));
+        assert!(!column.accepts(&Type::UInt16));
+
+        assert!(ExpectedColumn::any::<u32>("a").accepts(&Type::String));
+    }
+
+    #[test]
+    fn test_default_type() {
+        let default_type = |column: ExpectedColumn| column.default_type();
+        assert_eq!(
+            default_type(ExpectedColumn::of::<u32>("a")),
+            Some(Type::UInt32)
+        );
+        assert_eq!(
+            default_type(ExpectedColumn::of::<i64>("a")),
+            Some(Type::Int64)
+        );
+        assert_eq!(
+            default_type(ExpectedColumn::of::<u8>("a")),
+            Some(Type::UInt8)
+        );
+        assert_eq!(
+            default_type(ExpectedColumn::of::<Option<u64>>("a")),
+            Some(Type::Nullable(Box::new(Type::UInt64)))
+        );
+        assert_eq!(
+            default_type(ExpectedColumn::of::<String>("a")),
+            Some(Type::String)
+        );
+        assert_eq!(
+            default_type(ExpectedColumn::of::<Option<crate::Uuid>>("a")),
+            Some(Type::Nullable(Box::new(Type::Uuid)))
+        );
+        assert_eq!(
+            default_type(ExpectedColumn::of::<chrono::DateTime<chrono::Utc>>("a")),
+            Some(Type::DateTime64(6, UTC))
+        );
+        assert_eq!(
+            default_type(ExpectedColumn::of::<crate::DateTime>("a")),
+            Some(Type::DateTime(UTC))
+        );
+        assert_eq!(
+            default_type(ExpectedColumn::of::<u16>("b").nested("n.b")),
+            Some(Type::Array(Box::new(Type::UInt16)))
+        );
+        assert_eq!(default_type(ExpectedColumn::any::<u32>("a")), None);
+    }
+}
@@ -25,3 +25,19 @@ impl<T: DeserializeOwned> FromSql for Json<T> {
         })?))
     }
 }
+
+/// Written as JSON text, for `JSON` and `String` columns.
+impl ToSql for serde_json::Value {
+    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
+        Ok(Value::String(self.to_string().into_bytes()))
+    }
+}
+
+/// Parsed from `JSON` columns, or JSON text in `String` columns.
+impl FromSql for serde_json::Value {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        let raw: String = FromSql::from_sql(type_, value)?;
+
+        serde_json::from_str(&raw).map_err(|e| KlickhouseError::DeserializeError(e.to_string()))
+    }
+}
@@ -2,10 +2,24 @@ use std::borrow::Cow;
 
 use crate::{types::Type, KlickhouseError, Result, Value};
 
+mod checked;
+pub use checked::*;
+mod collapsing;
+pub use collapsing::{collapsing_changes, CollapsingRow};
+mod column_writers;
+pub use column_writers::ColumnWriters;
+mod expected_column;
+pub use expected_column::ExpectedColumn;
 mod raw_row;
+mod rename_rule;
+pub use rename_rule::*;
+mod row_accessor;
 mod std_deserialize;
 mod std_serialize;
 pub use raw_row::*;
+#[cfg(feature = "client")]
+pub(crate) use row_accessor::deserialize_rows;
+pub use row_accessor::{ColumnLookup, FieldLookup, RowAccessor};
 mod unit_value;
 pub use unit_value::*;
 mod vec_tuple;
@@ -14,6 +28,10 @@ pub use vec_tuple::*;
 mod json;
 #[cfg(feature = "serde")]
 pub use json::*;
+#[cfg(feature = "serde_row")]
+mod serde_row;
+#[cfg(feature = "serde_row")]
+pub use serde_row::*;
 
 /// A type that can be converted to a raw Clickhouse SQL value.
 pub trait ToSql {
@@ -26,8 +44,17 @@ impl ToSql for Value {
     }
 }
 
+/// Error for a [`Type`] that can't be read into the requested Rust type.
 pub fn unexpected_type(type_: &Type) -> KlickhouseError {
-    KlickhouseError::DeserializeError(format!("unexpected type: {}", type_))
+    KlickhouseError::UnexpectedType(type_.clone())
+}
+
+/// Error for a [`Value`] whose shape doesn't match what its [`Type`] (or the caller) expects.
+pub fn unexpected_value(value: &Value, expected: impl std::fmt::Display) -> KlickhouseError {
+    KlickhouseError::UnexpectedValue {
+        expected: expected.to_string(),
+        received: value.guess_type(),
+    }
 }
 
 /// A type that can be converted from a raw Clickhouse SQL value.
@@ -51,10 +78,38 @@ pub trait Row: Sized {
     /// If `Some`, `serialize_row` and `deserialize_row` MUST have these names
     fn column_names() -> Option<Vec<Cow<'static, str>>>;
 
+    /// If `Some`, the columns of `column_names` with the types they can be read from. Used by [`Client::verify_schema`](crate::Client::verify_schema).
+    fn column_types() -> Option<Vec<ExpectedColumn>> {
+        None
+    }
+
     fn deserialize_row(map: Vec<(&str, &Type, Value)>) -> Result<Self>;
 
+    /// Deserializes a row of a query result. The client calls this rather than `deserialize_row`.
+    /// The default collects the row for `deserialize_row`. Override it to read the columns without that allocation,
+    /// as `#[derive(klickhouse::Row)]` does, matching columns to fields once per block through the accessor's [`ColumnLookup`].
+    fn deserialize_accessor(row: RowAccessor<'_>) -> Result<Self> {
+        Self::deserialize_row(row.into_vec())
+    }
+
+    /// Deserializes a row of a block with a single column.
+    /// Called instead of `deserialize_row` if `COLUMN_COUNT` is `Some(1)`, so implementations can skip building the row.
+    fn deserialize_unit(name: &str, type_: &Type, value: Value) -> Result<Self> {
+        Self::deserialize_row(vec![(name, type_, value)])
+    }
+
     fn serialize_row(
         self,
         type_hints: &indexmap::IndexMap<String, Type>,
     ) -> Result<Vec<(Cow<'static, str>, Value)>>;
+
+    /// Appends the row to the columns of an insert block. The client calls this rather than `serialize_row`.
+    /// The default pushes the columns returned by `serialize_row`. Override it to push each value as it is converted,
+    /// as `#[derive(klickhouse::Row)]` does for structs without `nested` fields.
+    fn serialize_into(self, columns: &mut ColumnWriters<'_>) -> Result<()> {
+        for (name, value) in self.serialize_row(columns.type_hints())? {
+            columns.push(&name, value)?;
+        }
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use crate::{FromSql, KlickhouseError, Result, Row, ToSql, Type, Value};
+use crate::{FromSql, KlickhouseError, Result, Row, RowAccessor, ToSql, Type, Value};
 
 /// A row of raw data returned from the database by a query.
 /// Or an unstructured runtime-defined row to upload to the server.
@@ -22,6 +22,14 @@ impl Row for RawRow {
         ))
     }
 
+    fn deserialize_accessor(mut row: RowAccessor<'_>) -> Result<Self> {
+        Ok(Self(
+            row.drain()
+                .map(|(name, type_, value)| Some((name.to_string(), type_.clone(), value)))
+                .collect(),
+        ))
+    }
+
     fn serialize_row(
         self,
         _type_hints: &indexmap::IndexMap<String, Type>,
@@ -35,6 +43,18 @@ impl Row for RawRow {
     }
 }
 
+/// How [`RawRow::dedup_columns`] treats columns sharing a name, i.e. from `SELECT a.id, b.id FROM a JOIN b ...`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateColumnPolicy {
+    /// Fail with [`KlickhouseError::DuplicateColumn`].
+    #[default]
+    Error,
+    /// Keep the first column with a name.
+    First,
+    /// Keep the last column with a name.
+    Last,
+}
+
 pub trait RowIndex {
     fn get<'a, I: IntoIterator<Item = &'a str>>(&self, columns: I) -> Option<usize>;
 }
@@ -56,7 +76,7 @@ impl RowIndex for str {
     }
 }
 
-impl<'b, T: RowIndex + ?Sized> RowIndex for &'b T {
+impl<T: RowIndex + ?Sized> RowIndex for &T {
     fn get<'a, I: IntoIterator<Item = &'a str>>(&self, columns: I) -> Option<usize> {
         (*self).get(columns)
     }
@@ -73,6 +93,39 @@ impl RawRow {
         self.0.len()
     }
 
+    /// Removes columns whose name was already seen according to `policy`. Already fetched columns are ignored.
+    /// By default, duplicates stay in the row and fetching by name returns them in order.
+    pub fn dedup_columns(&mut self, policy: DuplicateColumnPolicy) -> Result<()> {
+        let mut seen = std::collections::HashMap::<&str, usize>::new();
+        let mut remove = vec![];
+        for (index, column) in self.0.iter().enumerate() {
+            let Some((name, _, _)) = column else {
+                continue;
+            };
+            let Some(previous) = seen.insert(name, index) else {
+                continue;
+            };
+            match policy {
+                DuplicateColumnPolicy::Error => {
+                    return Err(KlickhouseError::DuplicateColumn {
+                        name: Cow::Owned(name.clone()),
+                        index,
+                    })
+                }
+                DuplicateColumnPolicy::First => {
+                    seen.insert(name, previous);
+                    remove.push(index);
+                }
+                DuplicateColumnPolicy::Last => remove.push(previous),
+            }
+        }
+        remove.sort_unstable();
+        for index in remove.into_iter().rev() {
+            self.0.remove(index);
+        }
+        Ok(())
+    }
+
     /// Like RawRow::get, but returns a Result rather than panicking.
     pub fn try_get<I: RowIndex, T: FromSql>(&mut self, index: I) -> Result<T> {
         let index = index
@@ -141,3 +194,38 @@ impl RawRow {
             .expect("failed to convert column");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row() -> RawRow {
+        RawRow::deserialize_row(vec![
+            ("a", &Type::UInt32, Value::UInt32(1)),
+            ("b", &Type::UInt32, Value::UInt32(2)),
+            ("a", &Type::UInt32, Value::UInt32(3)),
+            ("a", &Type::UInt32, Value::UInt32(4)),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dedup_columns() {
+        assert!(matches!(
+            row().dedup_columns(DuplicateColumnPolicy::Error),
+            Err(KlickhouseError::DuplicateColumn { index: 2, .. })
+        ));
+
+        let mut first = row();
+        first.dedup_columns(DuplicateColumnPolicy::First).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first.get::<_, u32>("a"), 1);
+        assert_eq!(first.get::<_, u32>("b"), 2);
+
+        let mut last = row();
+        last.dedup_columns(DuplicateColumnPolicy::Last).unwrap();
+        assert_eq!(last.len(), 2);
+        assert_eq!(last.get::<_, u32>("b"), 2);
+        assert_eq!(last.get::<_, u32>("a"), 4);
+    }
+}
@@ -0,0 +1,98 @@
+/// Case conversion of the columns of a `nested` field, as named by its `nested_rename_all` attribute.
+/// `klickhouse_derive` parses the attribute and passes the rule, as the inner column names are only known at runtime.
+#[doc(hidden)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenameRule {
+    /// Keep names as they are.
+    None,
+    /// "lowercase"
+    LowerCase,
+    /// "UPPERCASE"
+    UpperCase,
+    /// "PascalCase"
+    PascalCase,
+    /// "camelCase"
+    CamelCase,
+    /// "snake_case"
+    SnakeCase,
+    /// "SCREAMING_SNAKE_CASE"
+    ScreamingSnakeCase,
+    /// "kebab-case"
+    KebabCase,
+    /// "SCREAMING-KEBAB-CASE"
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    /// Applies the rule to a snake_case column name.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::None | RenameRule::LowerCase | RenameRule::SnakeCase => field.to_owned(),
+            RenameRule::UpperCase | RenameRule::ScreamingSnakeCase => field.to_ascii_uppercase(),
+            RenameRule::PascalCase => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            RenameRule::CamelCase => {
+                let pascal = RenameRule::PascalCase.apply_to_field(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => pascal,
+                }
+            }
+            RenameRule::KebabCase => field.replace('_', "-"),
+            RenameRule::ScreamingKebabCase => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// Column name for the column `name` of a struct in the `nested` field `parent`, joined with `separator` after applying
+/// `rename_all` to `name`. Used by `klickhouse_derive`.
+#[doc(hidden)]
+pub fn nested_column_name(
+    parent: &str,
+    separator: &str,
+    rename_all: RenameRule,
+    name: &str,
+) -> String {
+    format!("{parent}{separator}{}", rename_all.apply_to_field(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_column_name() {
+        let cases = [
+            (RenameRule::None, "p.nest_u64"),
+            (RenameRule::LowerCase, "p.nest_u64"),
+            (RenameRule::UpperCase, "p.NEST_U64"),
+            (RenameRule::PascalCase, "p.NestU64"),
+            (RenameRule::CamelCase, "p.nestU64"),
+            (RenameRule::SnakeCase, "p.nest_u64"),
+            (RenameRule::ScreamingSnakeCase, "p.NEST_U64"),
+            (RenameRule::KebabCase, "p.nest-u64"),
+            (RenameRule::ScreamingKebabCase, "p.NEST-U64"),
+        ];
+        for (rule, expected) in cases {
+            assert_eq!(nested_column_name("p", ".", rule, "nest_u64"), expected);
+        }
+        assert_eq!(
+            nested_column_name("p", "_", RenameRule::CamelCase, ""),
+            "p_"
+        );
+    }
+}
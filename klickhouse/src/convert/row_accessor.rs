@@ -0,0 +1,280 @@
+use std::cell::OnceCell;
+
+use crate::{block::Block, KlickhouseError, Result, Row, Type, Value};
+
+/// The columns of one row of a block, passed to [`Row::deserialize_accessor`].
+///
+/// Column names and types are shared by all rows of a block, and the values are held in a buffer that is reused from row to row,
+/// so reading a row doesn't allocate. Values that are neither taken nor drained are dropped when the buffer is refilled.
+pub struct RowAccessor<'a> {
+    columns: &'a [(&'a str, &'a Type)],
+    values: &'a mut Vec<Value>,
+    /// For a `flatten` field, the columns of the parent row that make up this row
+    view: Option<&'a [usize]>,
+    lookup: &'a ColumnLookup,
+}
+
+/// How a [`Row`] type's fields map to the columns of a block, computed by its [`Row::deserialize_accessor`] for the first row
+/// and reused for the others.
+#[derive(Default)]
+pub struct ColumnLookup(OnceCell<(FieldLookup, Vec<ColumnLookup>)>);
+
+/// Field lookup built by `#[derive(klickhouse::Row)]`.
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct FieldLookup {
+    /// For each column, the slot of the field it is read into, as numbered by the derive.
+    pub slots: Vec<usize>,
+    /// For each `flatten` field, its columns in the order of its `column_names`, or `None` if any are missing.
+    pub flattened: Vec<Option<Vec<usize>>>,
+}
+
+impl<'a> RowAccessor<'a> {
+    /// `values` holds one value for each of `columns`, in order. `lookup` must be shared by the rows of a block and used for a single [`Row`] type.
+    pub fn new(
+        columns: &'a [(&'a str, &'a Type)],
+        values: &'a mut Vec<Value>,
+        lookup: &'a ColumnLookup,
+    ) -> Self {
+        Self {
+            columns,
+            values,
+            view: None,
+            lookup,
+        }
+    }
+
+    fn column(&self, index: usize) -> Option<usize> {
+        match self.view {
+            Some(view) => view.get(index).copied(),
+            None => (index < self.values.len()).then_some(index),
+        }
+    }
+
+    /// Number of columns in the row.
+    pub fn len(&self) -> usize {
+        match self.view {
+            Some(view) => view.len(),
+            None => self.values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Name of the column at `index`.
+    pub fn name(&self, index: usize) -> Option<&'a str> {
+        let columns = self.columns;
+        columns.get(self.column(index)?).map(|(name, _)| *name)
+    }
+
+    /// Type of the column at `index`, without any `LowCardinality` wrapper.
+    pub fn type_(&self, index: usize) -> Option<&'a Type> {
+        let columns = self.columns;
+        columns.get(self.column(index)?).map(|(_, type_)| *type_)
+    }
+
+    /// Index of the first column named `name`.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        (0..self.len()).find(|&i| self.name(i) == Some(name))
+    }
+
+    /// Takes the value of the column at `index`, leaving `Null` in its place.
+    pub fn take(&mut self, index: usize) -> Option<Value> {
+        let column = self.column(index)?;
+        self.values
+            .get_mut(column)
+            .map(|x| std::mem::replace(x, Value::Null))
+    }
+
+    /// Takes the type and value of the first column named `name`, leaving `Null` in its place.
+    pub fn take_by_name(&mut self, name: &str) -> Option<(&'a Type, Value)> {
+        let index = self.index_of(name)?;
+        Some((self.type_(index)?, self.take(index)?))
+    }
+
+    /// Yields the name, type and value of every column in order, leaving `Null` in their place.
+    pub fn drain(&mut self) -> impl Iterator<Item = (&'a str, &'a Type, Value)> + '_ {
+        (0..self.len()).map(move |i| {
+            let column = self.column(i).expect("column in range");
+            let (name, type_) = self.columns[column];
+            (
+                name,
+                type_,
+                std::mem::replace(&mut self.values[column], Value::Null),
+            )
+        })
+    }
+
+    /// Collects the row as passed to [`Row::deserialize_row`].
+    pub fn into_vec(mut self) -> Vec<(&'a str, &'a Type, Value)> {
+        self.drain().collect()
+    }
+
+    /// The field lookup of the block, built by `build` for its first row. Used by `#[derive(klickhouse::Row)]`.
+    #[doc(hidden)]
+    pub fn field_lookup(
+        &self,
+        build: impl FnOnce(&Self) -> Result<FieldLookup>,
+    ) -> Result<&'a FieldLookup> {
+        let lookup = self.lookup;
+        if let Some((fields, _)) = lookup.0.get() {
+            return Ok(fields);
+        }
+        let mut fields = build(self)?;
+        // flattened columns are kept as indices into the block's columns, rather than into this row's
+        for columns in fields.flattened.iter_mut().flatten() {
+            for column in columns.iter_mut() {
+                *column = self.column(*column).ok_or_else(|| {
+                    KlickhouseError::DeserializeError("flattened column out of range".to_string())
+                })?;
+            }
+        }
+        let children = fields
+            .flattened
+            .iter()
+            .map(|_| ColumnLookup::default())
+            .collect();
+        let (fields, _) = lookup.0.get_or_init(|| (fields, children));
+        Ok(fields)
+    }
+
+    /// The columns of the `index`th `flatten` field, or `None` if any are missing. Requires [`RowAccessor::field_lookup`].
+    #[doc(hidden)]
+    pub fn flattened(&mut self, index: usize) -> Option<RowAccessor<'_>> {
+        let lookup = self.lookup;
+        let (fields, children) = lookup.0.get()?;
+        Some(RowAccessor {
+            columns: self.columns,
+            values: &mut *self.values,
+            view: Some(fields.flattened.get(index)?.as_deref()?),
+            lookup: children.get(index)?,
+        })
+    }
+}
+
+/// Deserializes every row of `block` through [`Row::deserialize_accessor`].
+/// A block with rows but no columns yields that many empty rows, which are skipped and counted in the returned number unless `keep_empty_rows` is set.
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) fn deserialize_rows<T: Row>(
+    block: Block,
+    keep_empty_rows: bool,
+) -> (Vec<Result<T>>, usize) {
+    let Block {
+        rows,
+        column_types,
+        column_data,
+        ..
+    } = block;
+    let mut columns = Vec::with_capacity(column_data.len());
+    let mut data = Vec::with_capacity(column_data.len());
+    for (name, values) in column_data {
+        let Some((name, type_)) = column_types.get_key_value(&name) else {
+            let error = KlickhouseError::ProtocolError(format!("missing type for column {name}"));
+            return (vec![Err(error)], 0);
+        };
+        columns.push((name.as_str(), type_.strip_low_cardinality()));
+        data.push(values.into_iter());
+    }
+
+    let mut values = Vec::with_capacity(columns.len());
+    let lookup = ColumnLookup::default();
+    if columns.is_empty() {
+        if !keep_empty_rows {
+            return (vec![], rows as usize);
+        }
+        let out = (0..rows)
+            .map(|_| T::deserialize_accessor(RowAccessor::new(&columns, &mut values, &lookup)))
+            .collect();
+        return (out, 0);
+    }
+
+    let mut out = Vec::with_capacity(rows as usize);
+    'rows: loop {
+        values.clear();
+        for column in data.iter_mut() {
+            match column.next() {
+                Some(value) => values.push(value),
+                None => break 'rows,
+            }
+        }
+        out.push(T::deserialize_accessor(RowAccessor::new(
+            &columns,
+            &mut values,
+            &lookup,
+        )));
+    }
+    (out, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RawRow, UnitValue};
+
+    #[test]
+    fn test_row_accessor() {
+        let columns = [("a", &Type::UInt32), ("b", &Type::String)];
+        let mut values = vec![Value::UInt32(1), Value::String(b"x".to_vec())];
+        let lookup = ColumnLookup::default();
+        let mut row = RowAccessor::new(&columns, &mut values, &lookup);
+        assert_eq!(row.len(), 2);
+        assert_eq!(row.name(1), Some("b"));
+        assert_eq!(row.type_(0), Some(&Type::UInt32));
+        assert_eq!(row.index_of("b"), Some(1));
+        assert_eq!(row.index_of("c"), None);
+        assert_eq!(
+            row.take_by_name("b"),
+            Some((&Type::String, Value::String(b"x".to_vec())))
+        );
+        assert_eq!(row.take(1), Some(Value::Null));
+        assert_eq!(row.take(2), None);
+        assert_eq!(
+            row.into_vec(),
+            vec![
+                ("a", &Type::UInt32, Value::UInt32(1)),
+                ("b", &Type::String, Value::Null)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_rows() {
+        let mut block = Block::default();
+        block
+            .insert_column(
+                "a",
+                Type::LowCardinality(Box::new(Type::String)),
+                vec![Value::String(b"x".to_vec()), Value::String(b"y".to_vec())],
+            )
+            .unwrap();
+        block
+            .insert_column("b", Type::UInt8, vec![Value::UInt8(1), Value::UInt8(2)])
+            .unwrap();
+        let (rows, skipped) = deserialize_rows::<RawRow>(block, false);
+        assert_eq!(skipped, 0);
+        assert_eq!(rows.len(), 2);
+        let mut row = rows.into_iter().nth(1).unwrap().unwrap();
+        assert_eq!(row.get::<_, String>("a"), "y");
+        assert_eq!(row.get::<_, u8>("b"), 2);
+
+        let block = Block {
+            rows: 3,
+            ..Default::default()
+        };
+        let (rows, skipped) = deserialize_rows::<RawRow>(block.clone(), false);
+        assert!(rows.is_empty());
+        assert_eq!(skipped, 3);
+        let (rows, skipped) = deserialize_rows::<RawRow>(block, true);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(skipped, 0);
+
+        let mut block = Block::default();
+        block
+            .insert_column("a", Type::UInt8, vec![Value::UInt8(1)])
+            .unwrap();
+        let (rows, _) = deserialize_rows::<UnitValue<String>>(block, false);
+        assert!(rows[0].is_err());
+    }
+}
@@ -0,0 +1,1351 @@
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+use indexmap::IndexMap;
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor},
+    forward_to_deserialize_any,
+    ser::{self, Impossible},
+    Deserialize, Serialize,
+};
+
+use crate::{
+    values::write_fixed_point, Date, DateTime, DynDateTime64, FromSql, Ipv4, Ipv6, KlickhouseError,
+    Result, Row, Type, Value,
+};
+
+/// A wrapper to use a [`Serialize`]/[`Deserialize`] type as a [`Row`], as an alternative to `#[derive(klickhouse::Row)]`.
+/// The wrapped type must serialize as a struct or a map, where each field is a column.
+///
+/// Values are converted guided by the column types, i.e. integers are widened or narrowed to the column's integer width,
+/// strings are parsed into `UUID`, `IPv4`/`IPv6`, `Enum8`/`Enum16`, `Date` (`YYYY-MM-DD`) and `DateTime`/`DateTime64` (RFC 3339) columns,
+/// and nested structs map to tuples. On the way out, these types deserialize from their string representations.
+///
+/// The second field chooses how 128 and 256 bit integers are presented, i.e. `SerdeRow(row, StringWideIntegers)`
+/// to read them as strings into a [`serde_json::Value`], see [`WideIntegers`]. [`SerdeRow::new`] uses [`AutoWideIntegers`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SerdeRow<T, W = AutoWideIntegers>(pub T, #[serde(skip)] pub W);
+
+impl<T> SerdeRow<T> {
+    pub fn new(row: T) -> Self {
+        Self(row, AutoWideIntegers)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned, W: WideIntegerPolicy> Row for SerdeRow<T, W> {
+    const COLUMN_COUNT: Option<usize> = None;
+
+    fn column_names() -> Option<Vec<Cow<'static, str>>> {
+        None
+    }
+
+    fn deserialize_row(map: Vec<(&str, &Type, Value)>) -> Result<Self> {
+        Ok(SerdeRow(
+            T::deserialize(RowDeserializer {
+                columns: map,
+                wide_integers: W::WIDE_INTEGERS,
+            })?,
+            W::default(),
+        ))
+    }
+
+    fn serialize_row(
+        self,
+        type_hints: &IndexMap<String, Type>,
+    ) -> Result<Vec<(Cow<'static, str>, Value)>> {
+        self.0.serialize(RowSerializer { type_hints })
+    }
+}
+
+/// How 128 and 256 bit integers are presented to self-describing targets such as [`serde_json::Value`].
+/// Fields with a concrete integer type always receive the full value. `Int256` and `UInt256` are always decimal strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WideIntegers {
+    /// A number if the value fits in 64 bits, otherwise a decimal string.
+    #[default]
+    Auto,
+    /// Always a decimal string, i.e. for JSON consumers that parse numbers as doubles.
+    String,
+}
+
+/// A [`WideIntegers`] choice as a type, for the second field of [`SerdeRow`].
+pub trait WideIntegerPolicy: Default {
+    const WIDE_INTEGERS: WideIntegers;
+}
+
+/// [`WideIntegers::Auto`], the default of [`SerdeRow`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AutoWideIntegers;
+
+impl WideIntegerPolicy for AutoWideIntegers {
+    const WIDE_INTEGERS: WideIntegers = WideIntegers::Auto;
+}
+
+/// [`WideIntegers::String`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StringWideIntegers;
+
+impl WideIntegerPolicy for StringWideIntegers {
+    const WIDE_INTEGERS: WideIntegers = WideIntegers::String;
+}
+
+impl Value {
+    /// Renders this value of type `type_` as JSON, in the same way a [`SerdeRow<serde_json::Value>`] column is.
+    pub fn to_json(self, type_: &Type, wide_integers: WideIntegers) -> Result<serde_json::Value> {
+        serde_json::Value::deserialize(ValueDeserializer {
+            type_,
+            value: self,
+            wide_integers,
+        })
+    }
+}
+
+impl ser::Error for KlickhouseError {
+    fn custom<T: Display>(msg: T) -> Self {
+        KlickhouseError::SerializeError(msg.to_string())
+    }
+}
+
+impl de::Error for KlickhouseError {
+    fn custom<T: Display>(msg: T) -> Self {
+        KlickhouseError::DeserializeError(msg.to_string())
+    }
+}
+
+fn parse<T: FromStr>(type_: &Type, value: &str) -> Result<T>
+where
+    T::Err: Display,
+{
+    value.parse().map_err(|e| {
+        KlickhouseError::SerializeError(format!("invalid {type_} value '{value}': {e}"))
+    })
+}
+
+fn out_of_range(type_: &Type, value: impl Display) -> KlickhouseError {
+    KlickhouseError::SerializeError(format!("value {value} out of range for {type_}"))
+}
+
+/// Stores an already scaled decimal in the width of the decimal `type_`, failing if it doesn't fit.
+fn decimal(type_: &Type, unscaled: i128, value: impl Display) -> Result<Value> {
+    let range = |_| out_of_range(type_, &value);
+    Ok(match type_ {
+        Type::Decimal32(scale) => Value::Decimal32(*scale, unscaled.try_into().map_err(range)?),
+        Type::Decimal64(scale) => Value::Decimal64(*scale, unscaled.try_into().map_err(range)?),
+        Type::Decimal128(scale) => Value::Decimal128(*scale, unscaled),
+        Type::Decimal256(scale) => Value::Decimal256(
+            *scale,
+            (if unscaled < 0 { u128::MAX } else { 0 }, unscaled as u128).into(),
+        ),
+        _ => unreachable!("not a decimal type: {type_}"),
+    })
+}
+
+struct RowSerializer<'a> {
+    type_hints: &'a IndexMap<String, Type>,
+}
+
+struct RowFieldSerializer<'a> {
+    type_hints: &'a IndexMap<String, Type>,
+    columns: Vec<(Cow<'static, str>, Value)>,
+    key: Option<String>,
+}
+
+macro_rules! row_unsupported {
+    (@ret) => { Self::Ok };
+    (@ret $ret:ty) => { $ret };
+    ($($name:ident $(<$generic:ident>)?: $($arg:ty),* $(=> $ret:ty)?;)*) => {
+        $(
+            fn $name$(<$generic: ?Sized + Serialize>)?(
+                self,
+                $(_: $arg),*
+            ) -> Result<row_unsupported!(@ret $($ret)?)> {
+                Err(KlickhouseError::SerializeError(
+                    "rows must serialize as a struct or map".to_string(),
+                ))
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for RowSerializer<'a> {
+    type Ok = Vec<(Cow<'static, str>, Value)>;
+    type Error = KlickhouseError;
+    type SerializeSeq = Impossible<Self::Ok, KlickhouseError>;
+    type SerializeTuple = Impossible<Self::Ok, KlickhouseError>;
+    type SerializeTupleStruct = Impossible<Self::Ok, KlickhouseError>;
+    type SerializeTupleVariant = Impossible<Self::Ok, KlickhouseError>;
+    type SerializeMap = RowFieldSerializer<'a>;
+    type SerializeStruct = RowFieldSerializer<'a>;
+    type SerializeStructVariant = Impossible<Self::Ok, KlickhouseError>;
+
+    row_unsupported! {
+        serialize_bool: bool;
+        serialize_i8: i8;
+        serialize_i16: i16;
+        serialize_i32: i32;
+        serialize_i64: i64;
+        serialize_u8: u8;
+        serialize_u16: u16;
+        serialize_u32: u32;
+        serialize_u64: u64;
+        serialize_f32: f32;
+        serialize_f64: f64;
+        serialize_char: char;
+        serialize_str: &str;
+        serialize_bytes: &[u8];
+        serialize_none: ;
+        serialize_unit: ;
+        serialize_unit_struct: &'static str;
+        serialize_unit_variant: &'static str, u32, &'static str;
+        serialize_newtype_variant<T>: &'static str, u32, &'static str, &T;
+        serialize_seq: Option<usize> => Self::SerializeSeq;
+        serialize_tuple: usize => Self::SerializeTuple;
+        serialize_tuple_struct: &'static str, usize => Self::SerializeTupleStruct;
+        serialize_tuple_variant: &'static str, u32, &'static str, usize => Self::SerializeTupleVariant;
+        serialize_struct_variant: &'static str, u32, &'static str, usize => Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(RowFieldSerializer {
+            type_hints: self.type_hints,
+            columns: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+}
+
+impl<'a> ser::SerializeStruct for RowFieldSerializer<'a> {
+    type Ok = Vec<(Cow<'static, str>, Value)>;
+    type Error = KlickhouseError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let value = value.serialize(ValueSerializer::new(self.type_hints.get(key)))?;
+        self.columns.push((Cow::Borrowed(key), value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.columns)
+    }
+}
+
+impl<'a> ser::SerializeMap for RowFieldSerializer<'a> {
+    type Ok = Vec<(Cow<'static, str>, Value)>;
+    type Error = KlickhouseError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        match key.serialize(ValueSerializer::new(None))? {
+            Value::String(key) => {
+                self.key = Some(String::from_utf8(key)?);
+                Ok(())
+            }
+            _ => Err(KlickhouseError::SerializeError(
+                "column names must serialize as strings".to_string(),
+            )),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.key.take().ok_or_else(|| {
+            KlickhouseError::SerializeError("map value serialized before its key".to_string())
+        })?;
+        let value = value.serialize(ValueSerializer::new(self.type_hints.get(&key)))?;
+        self.columns.push((Cow::Owned(key), value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.columns)
+    }
+}
+
+/// Serializes a single value, converting it to the column type when one is known.
+struct ValueSerializer<'a> {
+    type_: Option<&'a Type>,
+}
+
+impl<'a> ValueSerializer<'a> {
+    fn new(type_: Option<&'a Type>) -> Self {
+        Self { type_ }
+    }
+
+    fn hint(&self) -> Option<&'a Type> {
+        self.type_.map(|x| x.strip_null().strip_low_cardinality())
+    }
+
+    fn signed(self, value: i128, natural: Value) -> Result<Value> {
+        let Some(type_) = self.hint() else {
+            return Ok(natural);
+        };
+        let range = |_| out_of_range(type_, value);
+        Ok(match type_ {
+            Type::Int8 => Value::Int8(value.try_into().map_err(range)?),
+            Type::Int16 => Value::Int16(value.try_into().map_err(range)?),
+            Type::Int32 => Value::Int32(value.try_into().map_err(range)?),
+            Type::Int64 => Value::Int64(value.try_into().map_err(range)?),
+            Type::Int128 => Value::Int128(value),
+            Type::Int256 => {
+                Value::Int256((if value < 0 { u128::MAX } else { 0 }, value as u128).into())
+            }
+            Type::UInt8 => Value::UInt8(value.try_into().map_err(range)?),
+            Type::UInt16 => Value::UInt16(value.try_into().map_err(range)?),
+            Type::UInt32 => Value::UInt32(value.try_into().map_err(range)?),
+            Type::UInt64 => Value::UInt64(value.try_into().map_err(range)?),
+            Type::UInt128 => Value::UInt128(value.try_into().map_err(range)?),
+            Type::UInt256 => {
+                let value: u128 = value.try_into().map_err(range)?;
+                Value::UInt256((0, value).into())
+            }
+            Type::Float32 => Value::Float32(value as f32),
+            Type::Float64 => Value::Float64(value as f64),
+            Type::Decimal32(scale)
+            | Type::Decimal64(scale)
+            | Type::Decimal128(scale)
+            | Type::Decimal256(scale) => {
+                let unscaled = 10i128
+                    .checked_pow(*scale as u32)
+                    .and_then(|x| value.checked_mul(x))
+                    .ok_or_else(|| out_of_range(type_, value))?;
+                decimal(type_, unscaled, value)?
+            }
+            Type::Enum8(_) => Value::Enum8(value.try_into().map_err(range)?),
+            Type::Enum16(_) => Value::Enum16(value.try_into().map_err(range)?),
+            Type::Date => Value::Date(Date(value.try_into().map_err(range)?)),
+            Type::DateTime(tz) => Value::DateTime(DateTime(*tz, value.try_into().map_err(range)?)),
+            _ => natural,
+        })
+    }
+
+    fn float(self, value: f64, natural: Value) -> Result<Value> {
+        let Some(type_) = self.hint() else {
+            return Ok(natural);
+        };
+        Ok(match type_ {
+            Type::Float32 => Value::Float32(value as f32),
+            Type::Float64 => Value::Float64(value),
+            Type::Decimal32(scale)
+            | Type::Decimal64(scale)
+            | Type::Decimal128(scale)
+            | Type::Decimal256(scale) => {
+                let scaled = (value * 10f64.powi(*scale as i32)).round();
+                // `i128::MAX as f64` rounds up to 2^127, the first value that doesn't fit; NaN fails both comparisons
+                if !(scaled >= i128::MIN as f64 && scaled < i128::MAX as f64) {
+                    return Err(out_of_range(type_, value));
+                }
+                decimal(type_, scaled as i128, value)?
+            }
+            _ => natural,
+        })
+    }
+
+    fn string(self, value: &str) -> Result<Value> {
+        let Some(type_) = self.hint() else {
+            return Ok(Value::String(value.as_bytes().to_vec()));
+        };
+        Ok(match type_ {
+            Type::Uuid => Value::Uuid(parse(type_, value)?),
+            Type::Ipv4 => Value::Ipv4(Ipv4(parse(type_, value)?)),
+            Type::Ipv6 => Value::Ipv6(Ipv6(parse(type_, value)?)),
+            Type::Enum8(variants) => Value::Enum8(
+                variants
+                    .iter()
+                    .find(|(name, _)| name == value)
+                    .map(|(_, index)| *index)
+                    .ok_or_else(|| out_of_range(type_, value))?,
+            ),
+            Type::Enum16(variants) => Value::Enum16(
+                variants
+                    .iter()
+                    .find(|(name, _)| name == value)
+                    .map(|(_, index)| *index)
+                    .ok_or_else(|| out_of_range(type_, value))?,
+            ),
+            Type::Date => Value::Date(parse::<NaiveDate>(type_, value)?.into()),
+            Type::DateTime(tz) => {
+                let date = parse::<chrono::DateTime<Utc>>(type_, value)?;
+                let mut date: DateTime = date.try_into().map_err(|_| out_of_range(type_, value))?;
+                date.0 = *tz;
+                Value::DateTime(date)
+            }
+            Type::DateTime64(precision, tz) => {
+                let date = parse::<chrono::DateTime<Utc>>(type_, value)?;
+                let mut date = DynDateTime64::try_from_utc(date, *precision)
+                    .map_err(|_| out_of_range(type_, value))?;
+                date.0 = *tz;
+                Value::DateTime64(date)
+            }
+            _ => Value::String(value.as_bytes().to_vec()),
+        })
+    }
+
+    fn element_type(&self, index: usize) -> Option<&'a Type> {
+        match self.hint()? {
+            Type::Array(inner) => Some(&**inner),
+            Type::Tuple(inner) => inner.get(index),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ser::Serializer for ValueSerializer<'a> {
+    type Ok = Value;
+    type Error = KlickhouseError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = Impossible<Value, KlickhouseError>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = SeqSerializer<'a>;
+    type SerializeStructVariant = Impossible<Value, KlickhouseError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        self.signed(v as i128, Value::UInt8(v as u8))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value> {
+        self.signed(v as i128, Value::Int8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value> {
+        self.signed(v as i128, Value::Int16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value> {
+        self.signed(v as i128, Value::Int32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value> {
+        self.signed(v as i128, Value::Int64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        self.signed(v, Value::Int128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value> {
+        self.signed(v as i128, Value::UInt8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value> {
+        self.signed(v as i128, Value::UInt16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value> {
+        self.signed(v as i128, Value::UInt32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        self.signed(v as i128, Value::UInt64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        match self.hint() {
+            Some(Type::UInt128) | None => Ok(Value::UInt128(v)),
+            Some(Type::UInt256) => Ok(Value::UInt256((0, v).into())),
+            Some(type_) => match i128::try_from(v) {
+                Ok(signed) => self.signed(signed, Value::UInt128(v)),
+                Err(_) => Err(out_of_range(type_, v)),
+            },
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value> {
+        self.float(v as f64, Value::Float32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value> {
+        self.float(v, Value::Float64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value> {
+        self.string(v.encode_utf8(&mut [0u8; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        self.string(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(Value::String(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value> {
+        value.serialize(ValueSerializer::new(self.type_.map(|x| x.strip_null())))
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Tuple(vec![]))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        self.string(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _value: &T,
+    ) -> Result<Value> {
+        Err(KlickhouseError::SerializeError(format!(
+            "cannot serialize enum variant with data {name}::{variant}"
+        )))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer<'a>> {
+        Ok(SeqSerializer {
+            parent: self,
+            values: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(KlickhouseError::SerializeError(format!(
+            "cannot serialize enum variant with data {name}::{variant}"
+        )))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer<'a>> {
+        let (key_type, value_type) = match self.hint() {
+            Some(Type::Map(key, value)) => (Some(&**key), Some(&**value)),
+            _ => (None, None),
+        };
+        Ok(MapSerializer {
+            key_type,
+            value_type,
+            keys: Vec::with_capacity(len.unwrap_or_default()),
+            values: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(KlickhouseError::SerializeError(format!(
+            "cannot serialize enum variant with data {name}::{variant}"
+        )))
+    }
+}
+
+/// Serializes sequences, tuples and nested structs. Becomes a `Tuple` if the column is one, otherwise an `Array`.
+struct SeqSerializer<'a> {
+    parent: ValueSerializer<'a>,
+    values: Vec<Value>,
+}
+
+impl<'a> SeqSerializer<'a> {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let type_ = self.parent.element_type(self.values.len());
+        self.values
+            .push(value.serialize(ValueSerializer::new(type_))?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value> {
+        Ok(match self.parent.hint() {
+            Some(Type::Tuple(_)) => Value::Tuple(self.values),
+            Some(Type::String) | Some(Type::FixedString(_)) => Value::String(
+                self.values
+                    .into_iter()
+                    .map(|x| match x {
+                        Value::UInt8(x) => Ok(x),
+                        _ => Err(KlickhouseError::SerializeError(
+                            "only byte sequences can be serialized into a string column"
+                                .to_string(),
+                        )),
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            _ => Value::Array(self.values),
+        })
+    }
+}
+
+impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+    type Ok = Value;
+    type Error = KlickhouseError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
+    type Ok = Value;
+    type Error = KlickhouseError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for SeqSerializer<'a> {
+    type Ok = Value;
+    type Error = KlickhouseError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStruct for SeqSerializer<'a> {
+    type Ok = Value;
+    type Error = KlickhouseError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Tuple(self.values))
+    }
+}
+
+struct MapSerializer<'a> {
+    key_type: Option<&'a Type>,
+    value_type: Option<&'a Type>,
+    keys: Vec<Value>,
+    values: Vec<Value>,
+}
+
+impl<'a> ser::SerializeMap for MapSerializer<'a> {
+    type Ok = Value;
+    type Error = KlickhouseError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.keys
+            .push(key.serialize(ValueSerializer::new(self.key_type))?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.values
+            .push(value.serialize(ValueSerializer::new(self.value_type))?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Map(self.keys, self.values))
+    }
+}
+
+struct RowDeserializer<'a> {
+    columns: Vec<(&'a str, &'a Type, Value)>,
+    wide_integers: WideIntegers,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for RowDeserializer<'a> {
+    type Error = KlickhouseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(RowAccess {
+            columns: self.columns.into_iter(),
+            value: None,
+            wide_integers: self.wide_integers,
+        })
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(SeqAccess {
+            values: self
+                .columns
+                .into_iter()
+                .map(|(_, type_, value)| (type_.clone(), value))
+                .collect::<Vec<_>>()
+                .into_iter(),
+            wide_integers: self.wide_integers,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct
+        map struct enum identifier ignored_any
+    }
+}
+
+struct RowAccess<'a, I: Iterator<Item = (&'a str, &'a Type, Value)>> {
+    columns: I,
+    value: Option<(&'a Type, Value)>,
+    wide_integers: WideIntegers,
+}
+
+impl<'de, 'a, I: Iterator<Item = (&'a str, &'a Type, Value)>> de::MapAccess<'de>
+    for RowAccess<'a, I>
+{
+    type Error = KlickhouseError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.columns.next() {
+            Some((name, type_, value)) => {
+                self.value = Some((type_, value));
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let (type_, value) = self.value.take().ok_or_else(|| {
+            KlickhouseError::DeserializeError("column value read before its name".to_string())
+        })?;
+        seed.deserialize(ValueDeserializer {
+            type_,
+            value,
+            wide_integers: self.wide_integers,
+        })
+    }
+}
+
+/// Deserializes a single value. Types without a serde data model counterpart are presented as strings.
+struct ValueDeserializer<'a> {
+    type_: &'a Type,
+    value: Value,
+    wide_integers: WideIntegers,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn type_(&self) -> &'a Type {
+        self.type_.strip_null().strip_low_cardinality()
+    }
+
+    fn deserialize_integer<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            Value::Int128(x) => match i64::try_from(x) {
+                Ok(x) => visitor.visit_i64(x),
+                Err(_) => visitor.visit_i128(x),
+            },
+            Value::UInt128(x) => match u64::try_from(x) {
+                Ok(x) => visitor.visit_u64(x),
+                Err(_) => visitor.visit_u128(x),
+            },
+            _ => de::Deserializer::deserialize_any(self, visitor),
+        }
+    }
+
+    fn decimal_string(&self) -> Option<String> {
+        let (negative, magnitude, scale) = match &self.value {
+            Value::Decimal32(scale, value) => {
+                (*value < 0, value.unsigned_abs().to_string(), *scale)
+            }
+            Value::Decimal64(scale, value) => {
+                (*value < 0, value.unsigned_abs().to_string(), *scale)
+            }
+            Value::Decimal128(scale, value) => {
+                (*value < 0, value.unsigned_abs().to_string(), *scale)
+            }
+            Value::Decimal256(scale, value) => {
+                let (negative, magnitude) = value.sign_magnitude_digits();
+                (negative, magnitude, *scale)
+            }
+            _ => return None,
+        };
+        let mut out = String::new();
+        write_fixed_point(&mut out, negative, &magnitude, scale).ok()?;
+        Some(out)
+    }
+}
+
+/// Integer fields go through [`ValueDeserializer::deserialize_integer`], so they are unaffected by [`WideIntegers`].
+macro_rules! forward_to_deserialize_integer {
+    ($($method:ident)*) => {
+        paste::paste! {
+            $(
+                fn [<deserialize_ $method>]<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                    self.deserialize_integer(visitor)
+                }
+            )*
+        }
+    };
+}
+
+fn geo_json<T: Serialize>(value: T) -> Result<serde_json::Value> {
+    serde_json::to_value(value).map_err(|e| KlickhouseError::DeserializeError(e.to_string()))
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = KlickhouseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if let Some(decimal) = self.decimal_string() {
+            return visitor.visit_string(decimal);
+        }
+        let type_ = self.type_();
+        let json = match self.value {
+            Value::Int8(x) => return visitor.visit_i8(x),
+            Value::Int16(x) => return visitor.visit_i16(x),
+            Value::Int32(x) => return visitor.visit_i32(x),
+            Value::Int64(x) => return visitor.visit_i64(x),
+            Value::Int128(x) => {
+                return match (self.wide_integers, i64::try_from(x)) {
+                    (WideIntegers::Auto, Ok(x)) => visitor.visit_i64(x),
+                    _ => visitor.visit_string(x.to_string()),
+                }
+            }
+            Value::Int256(x) => {
+                let (negative, magnitude) = x.sign_magnitude_digits();
+                return visitor.visit_string(if negative {
+                    format!("-{magnitude}")
+                } else {
+                    magnitude
+                });
+            }
+            Value::UInt8(x) => return visitor.visit_u8(x),
+            Value::UInt16(x) => return visitor.visit_u16(x),
+            Value::UInt32(x) => return visitor.visit_u32(x),
+            Value::UInt64(x) => return visitor.visit_u64(x),
+            Value::UInt128(x) => {
+                return match (self.wide_integers, u64::try_from(x)) {
+                    (WideIntegers::Auto, Ok(x)) => visitor.visit_u64(x),
+                    _ => visitor.visit_string(x.to_string()),
+                }
+            }
+            Value::UInt256(x) => return visitor.visit_string(x.decimal_digits()),
+            Value::Float32(x) => return visitor.visit_f32(x),
+            Value::Float64(x) => return visitor.visit_f64(x),
+            Value::Decimal32(..)
+            | Value::Decimal64(..)
+            | Value::Decimal128(..)
+            | Value::Decimal256(..) => unreachable!(),
+            Value::String(x) => {
+                return match String::from_utf8(x) {
+                    Ok(x) => visitor.visit_string(x),
+                    Err(e) => visitor.visit_byte_buf(e.into_bytes()),
+                }
+            }
+            Value::Uuid(x) => return visitor.visit_string(x.to_string()),
+            Value::Date(x) => return visitor.visit_string(NaiveDate::from(x).to_string()),
+            value @ (Value::DateTime(_) | Value::DateTime64(_)) => {
+                return visitor
+                    .visit_string(chrono::DateTime::<Tz>::from_sql(type_, value)?.to_rfc3339())
+            }
+            Value::Enum8(x) => {
+                return match type_ {
+                    Type::Enum8(variants) => match variants.iter().find(|(_, i)| *i == x) {
+                        Some((name, _)) => visitor.visit_str(name),
+                        None => visitor.visit_i8(x),
+                    },
+                    _ => visitor.visit_i8(x),
+                }
+            }
+            Value::Enum16(x) => {
+                return match type_ {
+                    Type::Enum16(variants) => match variants.iter().find(|(_, i)| *i == x) {
+                        Some((name, _)) => visitor.visit_str(name),
+                        None => visitor.visit_i16(x),
+                    },
+                    _ => visitor.visit_i16(x),
+                }
+            }
+            Value::Array(values) => {
+                let inner = type_.unarray().unwrap_or(type_);
+                return visitor.visit_seq(SeqAccess {
+                    values: values
+                        .into_iter()
+                        .map(|x| (inner.clone(), x))
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                    wide_integers: self.wide_integers,
+                });
+            }
+            Value::Tuple(values) => {
+                let types = type_.untuple().unwrap_or_default();
+                return visitor.visit_seq(SeqAccess {
+                    values: values
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, x)| (types.get(i).cloned().unwrap_or_else(|| x.guess_type()), x))
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                    wide_integers: self.wide_integers,
+                });
+            }
+            Value::Null => return visitor.visit_none(),
+            Value::Map(keys, values) => {
+                let (key_type, value_type) = type_.unmap().unwrap_or((type_, type_));
+                return visitor.visit_map(MapAccess {
+                    key_type,
+                    value_type,
+                    entries: keys.into_iter().zip(values),
+                    value: None,
+                    wide_integers: self.wide_integers,
+                });
+            }
+            Value::Ipv4(x) => return visitor.visit_string(x.to_string()),
+            Value::Ipv6(x) => return visitor.visit_string(x.to_string()),
+            Value::Point(x) => geo_json(x)?,
+            Value::Ring(x) => geo_json(x)?,
+            Value::Polygon(x) => geo_json(x)?,
+            Value::MultiPolygon(x) => geo_json(x)?,
+            Value::LineString(x) => geo_json(x)?,
+            Value::MultiLineString(x) => geo_json(x)?,
+            Value::Variant(value, discriminator) => {
+                return ValueDeserializer {
+                    type_: match type_ {
+                        Type::Variant(types) => types.get(discriminator as usize).unwrap_or(type_),
+                        _ => type_,
+                    },
+                    value: *value,
+                    wide_integers: self.wide_integers,
+                }
+                .deserialize_any(visitor)
+            }
+        };
+        json.deserialize_any(visitor)
+            .map_err(|e| KlickhouseError::DeserializeError(e.to_string()))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            Value::UInt8(x) => visitor.visit_bool(x != 0),
+            Value::Int8(x) => visitor.visit_bool(x != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.decimal_string() {
+            Some(decimal) => visitor.visit_f32(parse(self.type_, &decimal)?),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.decimal_string() {
+            Some(decimal) => visitor.visit_f64(parse(self.type_, &decimal)?),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(ValueDeserializer {
+                type_: self.type_.strip_null(),
+                value,
+                wide_integers: self.wide_integers,
+            }),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let type_ = self.type_();
+        let name = match (&self.value, type_) {
+            (Value::Enum8(x), Type::Enum8(variants)) => {
+                variants.iter().find(|(_, i)| i == x).map(|x| x.0.clone())
+            }
+            (Value::Enum16(x), Type::Enum16(variants)) => {
+                variants.iter().find(|(_, i)| i == x).map(|x| x.0.clone())
+            }
+            (Value::String(x), _) => Some(String::from_utf8(x.clone())?),
+            _ => None,
+        };
+        match name {
+            Some(name) => visitor.visit_enum(name.into_deserializer()),
+            None => Err(KlickhouseError::DeserializeError(format!(
+                "cannot deserialize enum from {type_}"
+            ))),
+        }
+    }
+
+    forward_to_deserialize_integer! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128
+    }
+
+    forward_to_deserialize_any! {
+        char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqAccess<I: Iterator<Item = (Type, Value)>> {
+    values: I,
+    wide_integers: WideIntegers,
+}
+
+impl<'de, I: Iterator<Item = (Type, Value)>> de::SeqAccess<'de> for SeqAccess<I> {
+    type Error = KlickhouseError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        match self.values.next() {
+            Some((type_, value)) => seed
+                .deserialize(ValueDeserializer {
+                    type_: &type_,
+                    value,
+                    wide_integers: self.wide_integers,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct MapAccess<'a, I: Iterator<Item = (Value, Value)>> {
+    key_type: &'a Type,
+    value_type: &'a Type,
+    entries: I,
+    value: Option<Value>,
+    wide_integers: WideIntegers,
+}
+
+impl<'de, 'a, I: Iterator<Item = (Value, Value)>> de::MapAccess<'de> for MapAccess<'a, I> {
+    type Error = KlickhouseError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(ValueDeserializer {
+                    type_: self.key_type,
+                    value: key,
+                    wide_integers: self.wide_integers,
+                })
+                .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self.value.take().ok_or_else(|| {
+            KlickhouseError::DeserializeError("map value read before its key".to_string())
+        })?;
+        seed.deserialize(ValueDeserializer {
+            type_: self.value_type,
+            value,
+            wide_integers: self.wide_integers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct Inner {
+        a: u8,
+        b: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct TestRow {
+        id: u32,
+        wide: i32,
+        name: String,
+        maybe: Option<String>,
+        uuid: uuid::Uuid,
+        color: Color,
+        flag: bool,
+        price: f64,
+        date: NaiveDate,
+        time: chrono::DateTime<Utc>,
+        tags: Vec<String>,
+        inner: Inner,
+        map: BTreeMap<String, u16>,
+        addr: std::net::Ipv4Addr,
+    }
+
+    fn types() -> IndexMap<String, Type> {
+        [
+            ("id", "UInt32"),
+            ("wide", "Int64"),
+            ("name", "LowCardinality(String)"),
+            ("maybe", "Nullable(String)"),
+            ("uuid", "UUID"),
+            ("color", "Int8"),
+            ("flag", "UInt8"),
+            ("price", "Decimal64(2)"),
+            ("date", "Date"),
+            ("time", "DateTime64(3, 'UTC')"),
+            ("tags", "Array(String)"),
+            ("inner", "Tuple(UInt8, String)"),
+            ("map", "Map(String, UInt16)"),
+            ("addr", "IPv4"),
+        ]
+        .into_iter()
+        .map(|(name, type_)| {
+            let type_ = match name {
+                "color" => Type::Enum8(vec![("Red".to_string(), 1), ("Green".to_string(), 2)]),
+                _ => type_.parse().unwrap(),
+            };
+            (name.to_string(), type_)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_serde_row_roundtrip() {
+        let row = TestRow {
+            id: 7,
+            wide: -3,
+            name: "seven".to_string(),
+            maybe: None,
+            uuid: uuid::Uuid::new_v4(),
+            color: Color::Green,
+            flag: true,
+            price: -12.5,
+            date: NaiveDate::from_ymd_opt(2023, 4, 5).unwrap(),
+            time: "2023-04-05T06:07:08.123Z".parse().unwrap(),
+            tags: vec!["a".to_string(), "b".to_string()],
+            inner: Inner {
+                a: 1,
+                b: "x".to_string(),
+            },
+            map: [("k".to_string(), 3)].into_iter().collect(),
+            addr: "10.0.0.1".parse().unwrap(),
+        };
+        let types = types();
+        let serialized = SerdeRow::new(row.clone()).serialize_row(&types).unwrap();
+        for (name, value) in &serialized {
+            types.get(&**name).unwrap().validate_value(value).unwrap();
+        }
+        assert_eq!(serialized[1].1, Value::Int64(-3));
+        assert_eq!(serialized[5].1, Value::Enum8(2));
+        assert_eq!(serialized[7].1, Value::Decimal64(2, -1250));
+
+        let columns = serialized
+            .into_iter()
+            .map(|(name, value)| {
+                let type_ = types.get(&*name).unwrap();
+                (
+                    types.get_key_value(&*name).unwrap().0.as_str(),
+                    type_,
+                    value,
+                )
+            })
+            .collect();
+        let deserialized = SerdeRow::<TestRow>::deserialize_row(columns).unwrap();
+        assert_eq!(deserialized.0, row);
+    }
+
+    #[test]
+    fn test_serde_row_errors() {
+        #[derive(Serialize, Deserialize)]
+        struct Narrow {
+            id: i64,
+        }
+        let types: IndexMap<String, Type> = [("id".to_string(), Type::UInt8)].into_iter().collect();
+        assert!(SerdeRow::new(Narrow { id: 300 })
+            .serialize_row(&types)
+            .is_err());
+        assert!(SerdeRow::new(5u32).serialize_row(&types).is_err());
+        assert!(SerdeRow::<Narrow>::deserialize_row(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_decimal_range() {
+        #[derive(Serialize, Deserialize)]
+        struct Single<T> {
+            value: T,
+        }
+        fn serialize<T: Serialize + DeserializeOwned>(value: T, type_: &str) -> Result<Value> {
+            let types: IndexMap<String, Type> = [("value".to_string(), type_.parse().unwrap())]
+                .into_iter()
+                .collect();
+            SerdeRow::new(Single { value })
+                .serialize_row(&types)
+                .map(|mut x| x.remove(0).1)
+        }
+        assert_eq!(
+            serialize(i64::MAX - 1, "Decimal64(0)").unwrap(),
+            Value::Decimal64(0, i64::MAX - 1)
+        );
+        assert_eq!(
+            serialize(-21474836i64, "Decimal32(2)").unwrap(),
+            Value::Decimal32(2, -2147483600)
+        );
+        assert_eq!(
+            serialize(u64::MAX, "Decimal128(1)").unwrap(),
+            Value::Decimal128(1, u64::MAX as i128 * 10)
+        );
+        assert!(serialize(21474837i64, "Decimal32(2)").is_err());
+        assert!(serialize(i64::MAX, "Decimal64(1)").is_err());
+
+        assert_eq!(
+            serialize(1.005f64, "Decimal32(1)").unwrap(),
+            Value::Decimal32(1, 10)
+        );
+        assert!(serialize(1e12f64, "Decimal32(2)").is_err());
+        assert!(serialize(1e19f64, "Decimal64(0)").is_err());
+        assert!(serialize(1e40f64, "Decimal128(0)").is_err());
+        assert!(serialize(f64::NAN, "Decimal64(2)").is_err());
+        assert!(serialize(f64::INFINITY, "Decimal64(2)").is_err());
+        assert!(serialize(f32::NEG_INFINITY, "Decimal128(2)").is_err());
+    }
+
+    #[test]
+    fn test_wide_integers() {
+        let big = i128::MAX;
+        let columns = vec![
+            ("small", &Type::Int128, Value::Int128(-5)),
+            ("big", &Type::Int128, Value::Int128(big)),
+            ("unsigned", &Type::UInt128, Value::UInt128(u128::MAX)),
+        ];
+        let row = SerdeRow::<serde_json::Value>::deserialize_row(columns.clone()).unwrap();
+        assert_eq!(
+            row.0,
+            serde_json::json!({
+                "small": -5,
+                "big": big.to_string(),
+                "unsigned": u128::MAX.to_string(),
+            })
+        );
+
+        #[derive(Serialize, Deserialize)]
+        struct Typed {
+            small: i64,
+            big: i128,
+            unsigned: u128,
+        }
+        let row =
+            SerdeRow::<serde_json::Value, StringWideIntegers>::deserialize_row(columns.clone())
+                .unwrap();
+        assert_eq!(
+            row.0,
+            serde_json::json!({
+                "small": "-5",
+                "big": big.to_string(),
+                "unsigned": u128::MAX.to_string(),
+            })
+        );
+
+        let typed = SerdeRow::<Typed, StringWideIntegers>::deserialize_row(columns)
+            .unwrap()
+            .0;
+        assert_eq!(
+            (typed.small, typed.big, typed.unsigned),
+            (-5, big, u128::MAX)
+        );
+
+        assert_eq!(
+            Value::UInt128(7)
+                .to_json(&Type::UInt128, WideIntegers::String)
+                .unwrap(),
+            serde_json::json!("7")
+        );
+        assert_eq!(
+            Value::Array(vec![Value::Int128(7)])
+                .to_json(&Type::Array(Box::new(Type::Int128)), WideIntegers::Auto)
+                .unwrap(),
+            serde_json::json!([7])
+        );
+    }
+}
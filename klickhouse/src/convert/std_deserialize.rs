@@ -15,163 +15,72 @@ impl FromSql for bool {
         }
         match value {
             Value::UInt8(x) => Ok(x != 0),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
 
-impl FromSql for u8 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt8) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt8(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for u16 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt16) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt16(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for u32 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt32) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt32(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for u64 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt64) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt64(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for u128 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt128) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt128(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for i8 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int8) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int8(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for i16 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int16) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int16(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for i32 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int32) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int32(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for i64 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int64) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int64(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
-
-impl FromSql for i128 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int128) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int128(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
+/// Implements [`FromSql`] for a number type from every column type it can represent losslessly,
+/// so i.e. a `UInt8` column can be read into a `u64` field.
+macro_rules! widening_impl {
+    ($($t:ty: $($variant:ident),+;)+) => {
+        $(
+            impl FromSql for $t {
+                fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+                    if !matches!(type_, $(Type::$variant)|+) {
+                        return Err(unexpected_type(type_));
+                    }
+                    match value {
+                        $(Value::$variant(x) => Ok(x.into()),)+
+                        x => Err(unexpected_value(&x, type_)),
+                    }
+                }
+            }
+        )+
+    };
 }
 
-impl FromSql for f32 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Float32) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Float32(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
+widening_impl! {
+    u8: UInt8;
+    u16: UInt8, UInt16;
+    u32: UInt8, UInt16, UInt32;
+    u64: UInt8, UInt16, UInt32, UInt64;
+    u128: UInt8, UInt16, UInt32, UInt64, UInt128;
+    i8: Int8;
+    i16: Int8, Int16, UInt8;
+    i32: Int8, Int16, Int32, UInt8, UInt16;
+    i64: Int8, Int16, Int32, Int64, UInt8, UInt16, UInt32;
+    i128: Int8, Int16, Int32, Int64, Int128, UInt8, UInt16, UInt32, UInt64;
+    f32: Float32, Int8, Int16, UInt8, UInt16;
+    f64: Float32, Float64, Int8, Int16, Int32, UInt8, UInt16, UInt32;
 }
 
-impl FromSql for f64 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Float64) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Float64(x) => Ok(x),
-            _ => unimplemented!(),
-        }
+fn enum_name<T: PartialEq>(type_: &Type, entries: &[(String, T)], index: T) -> Result<String> {
+    match entries.iter().find(|(_, x)| *x == index) {
+        Some((name, _)) => Ok(name.clone()),
+        None => Err(KlickhouseError::DeserializeError(format!(
+            "value not in {type_}"
+        ))),
     }
 }
 
+/// Fails on invalid UTF-8, as `String` columns may hold arbitrary bytes.
+/// `Vec<u8>` and [`Bytes`](crate::Bytes) read any contents losslessly, [`MaybeUtf8`](crate::MaybeUtf8) keeps invalid contents as bytes,
+/// and [`helpers::utf8_lossy`](crate::helpers::utf8_lossy) replaces invalid sequences.
+/// `Enum8` and `Enum16` columns read as the name of their variant, `JSON` columns as JSON text.
 impl FromSql for String {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::String | Type::FixedString(_)) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::String(x) => Ok(String::from_utf8(x)?),
-            _ => unimplemented!(),
+        match (type_, value) {
+            (Type::String | Type::FixedString(_) | Type::Json, Value::String(x)) => {
+                Ok(String::from_utf8(x)?)
+            }
+            (Type::Enum8(entries), Value::Enum8(x)) => enum_name(type_, entries, x),
+            (Type::Enum16(entries), Value::Enum16(x)) => enum_name(type_, entries, x),
+            (
+                Type::String | Type::FixedString(_) | Type::Json | Type::Enum8(_) | Type::Enum16(_),
+                x,
+            ) => Err(unexpected_value(&x, type_)),
+            _ => Err(unexpected_type(type_)),
         }
     }
 }
@@ -179,10 +88,11 @@ impl FromSql for String {
 impl<T: FromSql + 'static> FromSql for Vec<T> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
         let subtype = match type_ {
-            Type::Array(x) => x,
+            Type::Array(x) => x.strip_low_cardinality(),
+            // raw contents of a string column, only for `Vec<u8>` and `Vec<i8>`
+            Type::String | Type::FixedString(_) => &Type::UInt8,
             x => return Err(unexpected_type(x)),
-        }
-        .strip_low_cardinality();
+        };
         match value {
             Value::String(x) if *subtype == Type::UInt8 || *subtype == Type::Int8 => {
                 let type_id = TypeId::of::<T>();
@@ -196,7 +106,7 @@ impl<T: FromSql + 'static> FromSql for Vec<T> {
                 .into_iter()
                 .map(|x| T::from_sql(subtype, x))
                 .collect::<Result<Vec<_>>>()?),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -213,12 +123,12 @@ impl<T: FromSql + Hash + Eq, Y: FromSql> FromSql for HashMap<T, Y> {
         match value {
             Value::Map(x, y) => {
                 let mut out = HashMap::new();
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?);
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -235,12 +145,12 @@ impl<T: FromSql + Ord, Y: FromSql> FromSql for BTreeMap<T, Y> {
         match value {
             Value::Map(x, y) => {
                 let mut out = BTreeMap::new();
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?);
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -257,12 +167,12 @@ impl<T: FromSql + Hash + Eq, Y: FromSql> FromSql for IndexMap<T, Y> {
         match value {
             Value::Map(x, y) => {
                 let mut out = IndexMap::new();
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?);
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -280,14 +190,24 @@ impl<T: FromSql> FromSql for Option<T> {
     }
 }
 
+/// Reads an `Array` of exactly `N` elements, or a `Tuple` of `N` elements that each read into `T`.
 impl<T: FromSql + Default + Copy, const N: usize> FromSql for [T; N] {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        let subtype = match type_ {
-            Type::Array(x) => x.strip_low_cardinality(),
+        let subtypes: Vec<&Type> = match type_ {
+            Type::Array(x) => vec![x.strip_low_cardinality(); N],
+            Type::Tuple(x) => {
+                if x.len() != N {
+                    return Err(KlickhouseError::DeserializeError(format!(
+                        "cannot read a Tuple of {} elements into an array of {N}",
+                        x.len()
+                    )));
+                }
+                x.iter().map(|x| x.strip_low_cardinality()).collect()
+            }
             x => return Err(unexpected_type(x)),
         };
         match value {
-            Value::Array(x) => {
+            Value::Array(x) | Value::Tuple(x) => {
                 if x.len() != N {
                     return Err(KlickhouseError::DeserializeError(format!(
                         "invalid length for array: {} expected {}",
@@ -296,12 +216,12 @@ impl<T: FromSql + Default + Copy, const N: usize> FromSql for [T; N] {
                     )));
                 }
                 let mut out = [T::default(); N];
-                for (i, value) in x.into_iter().enumerate() {
+                for (i, (value, subtype)) in x.into_iter().zip(subtypes).enumerate() {
                     out[i] = T::from_sql(subtype, value)?;
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -321,20 +241,20 @@ macro_rules! tuple_impls {
                         Type::Tuple(x) => &**x,
                         x => return Err(unexpected_type(x)),
                     };
+                    if subtype.len() != $len {
+                        return Err(KlickhouseError::DeserializeError(format!("cannot read a Tuple of {} elements into a tuple of {}", subtype.len(), $len)));
+                    }
                     let values = match value {
                         Value::Tuple(n) => n,
-                        _ => unimplemented!(),
+                        x => return Err(unexpected_value(&x, type_)),
                     };
                     if values.len() != subtype.len() {
-                        return Err(KlickhouseError::DeserializeError(format!("unexpected type: mismatch tuple length expected {}, got {}", subtype.len(), values.len())));
-                    }
-                    if values.len() != $len {
-                        return Err(KlickhouseError::DeserializeError(format!("unexpected type: mismatch tuple length expected {}, got {}", $len, values.len())));
+                        return Err(unexpected_value(&Value::Tuple(values), type_));
                     }
-                    let mut deque = ::std::collections::VecDeque::from(values);
+                    let mut values = values.into_iter();
                     Ok((
                         $(
-                            $name::from_sql(subtype[$n].strip_low_cardinality(), deque.pop_front().unwrap())?,
+                            $name::from_sql(subtype[$n].strip_low_cardinality(), values.next().unwrap_or(Value::Null))?,
                         )+
                     ))
                 }
@@ -85,15 +85,24 @@ impl ToSql for f64 {
     }
 }
 
+/// Writes a string, or the matching variant for an `Enum8`/`Enum16` type hint.
+fn string_to_sql(value: Vec<u8>, type_hint: Option<&Type>) -> Result<Value> {
+    let value = Value::String(value);
+    match type_hint {
+        Some(type_ @ (Type::Enum8(_) | Type::Enum16(_))) => value.coerce_to(type_),
+        _ => Ok(value),
+    }
+}
+
 impl ToSql for String {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::String(self.into_bytes()))
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        string_to_sql(self.into_bytes(), type_hint)
     }
 }
 
-impl<'a> ToSql for &'a str {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::String(self.as_bytes().to_vec()))
+impl ToSql for &str {
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        string_to_sql(self.as_bytes().to_vec(), type_hint)
     }
 }
 
@@ -167,8 +176,17 @@ impl<T: ToSql> ToSql for Option<T> {
     }
 }
 
+/// Writes an `Array`, or a `Tuple` if the type hint is one.
 impl<T: ToSql, const N: usize> ToSql for [T; N] {
     fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        if let Some(types) = type_hint.and_then(|x| x.untuple()) {
+            return Ok(Value::Tuple(
+                IntoIterator::into_iter(self)
+                    .enumerate()
+                    .map(|(i, x)| x.to_sql(types.get(i)))
+                    .collect::<Result<Vec<_>>>()?,
+            ));
+        }
         let type_hint = type_hint
             .and_then(|x| x.unarray())
             .map(|x| x.strip_low_cardinality());
@@ -180,13 +198,13 @@ impl<T: ToSql, const N: usize> ToSql for [T; N] {
     }
 }
 
-impl<'a, T: ToSql + Clone> ToSql for &'a T {
+impl<T: ToSql + Clone> ToSql for &T {
     fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
         self.clone().to_sql(type_hint)
     }
 }
 
-impl<'a, T: ToSql + Clone> ToSql for &'a mut T {
+impl<T: ToSql + Clone> ToSql for &mut T {
     fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
         self.clone().to_sql(type_hint)
     }
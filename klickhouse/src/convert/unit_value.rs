@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use crate::{FromSql, KlickhouseError, Result, Row, ToSql, Type, Value};
+use crate::{FromSql, KlickhouseError, Result, Row, RowAccessor, ToSql, Type, Value};
 
 /// A single column row
 #[derive(Clone, Debug, Default)]
@@ -14,20 +14,32 @@ impl<T: FromSql + ToSql> Row for UnitValue<T> {
     }
 
     fn deserialize_row(map: Vec<(&str, &Type, Value)>) -> Result<Self> {
-        if map.is_empty() {
+        let Some(item) = map.into_iter().next() else {
             return Err(KlickhouseError::MissingField("<unit>"));
-        }
-        let item = map.into_iter().next().unwrap();
+        };
         T::from_sql(item.1, item.2).map(UnitValue)
     }
 
+    fn deserialize_accessor(mut row: RowAccessor<'_>) -> Result<Self> {
+        let (Some(type_), Some(value)) = (row.type_(0), row.take(0)) else {
+            return Err(KlickhouseError::MissingField("<unit>"));
+        };
+        T::from_sql(type_, value).map(UnitValue)
+    }
+
+    fn deserialize_unit(_name: &str, type_: &Type, value: Value) -> Result<Self> {
+        T::from_sql(type_, value).map(UnitValue)
+    }
+
     fn serialize_row(
         self,
         type_hints: &indexmap::IndexMap<String, Type>,
     ) -> Result<Vec<(Cow<'static, str>, Value)>> {
-        Ok(vec![(
-            Cow::Borrowed("_"),
-            self.0.to_sql(type_hints.values().next())?,
-        )])
+        // named after the only column of the insert, if known
+        let (name, type_) = match type_hints.first() {
+            Some((name, type_)) => (Cow::Owned(name.clone()), Some(type_)),
+            None => (Cow::Borrowed("_"), None),
+        };
+        Ok(vec![(name, self.0.to_sql(type_)?)])
     }
 }
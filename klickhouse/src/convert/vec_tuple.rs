@@ -1,6 +1,6 @@
-use crate::{FromSql, KlickhouseError, Result, ToSql, Type, Value};
+use crate::{FromSql, Result, ToSql, Type, Value};
 
-use super::unexpected_type;
+use super::{unexpected_type, unexpected_value};
 
 /// A `Vec` wrapper that is encoded as a tuple in SQL as opposed to a Vec
 #[derive(Clone, Debug, Default)]
@@ -26,17 +26,13 @@ impl<T: FromSql> FromSql for VecTuple<T> {
         };
         let values = match value {
             Value::Tuple(n) => n,
-            _ => unimplemented!(),
+            x => return Err(unexpected_value(&x, type_)),
         };
         if values.len() != subtype.len() {
-            return Err(KlickhouseError::DeserializeError(format!(
-                "unexpected type: mismatch tuple length expected {}, got {}",
-                subtype.len(),
-                values.len()
-            )));
+            return Err(unexpected_value(&Value::Tuple(values), type_));
         }
         let mut out = Vec::with_capacity(values.len());
-        for (type_, value) in subtype.iter().zip(values.into_iter()) {
+        for (type_, value) in subtype.iter().zip(values) {
             out.push(T::from_sql(type_, value)?);
         }
         Ok(VecTuple(out))
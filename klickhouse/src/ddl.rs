@@ -0,0 +1,361 @@
+use std::fmt;
+
+#[cfg(feature = "client")]
+use futures_util::StreamExt;
+
+#[cfg(feature = "client")]
+use crate::{Client, QualifiedTable, RawRow};
+use crate::{Identifier, KlickhouseError, Result, Type};
+
+/// Default expression of a column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnDefault {
+    Default(String),
+    Materialized(String),
+    Alias(String),
+    Ephemeral(String),
+}
+
+impl ColumnDefault {
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    fn parse(kind: &str, expression: String) -> Result<Option<Self>> {
+        Ok(Some(match kind {
+            "" => return Ok(None),
+            "DEFAULT" => ColumnDefault::Default(expression),
+            "MATERIALIZED" => ColumnDefault::Materialized(expression),
+            "ALIAS" => ColumnDefault::Alias(expression),
+            "EPHEMERAL" => ColumnDefault::Ephemeral(expression),
+            x => {
+                return Err(KlickhouseError::DeserializeError(format!(
+                    "unknown column default kind: {x}"
+                )))
+            }
+        }))
+    }
+}
+
+impl fmt::Display for ColumnDefault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnDefault::Default(x) => write!(f, "DEFAULT {x}"),
+            ColumnDefault::Materialized(x) => write!(f, "MATERIALIZED {x}"),
+            ColumnDefault::Alias(x) => write!(f, "ALIAS {x}"),
+            ColumnDefault::Ephemeral(x) => write!(f, "EPHEMERAL {x}"),
+        }
+    }
+}
+
+/// A column of a [`CreateTableBuilder`], or as described by [`Client::describe_columns`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    pub type_: Type,
+    pub default: Option<ColumnDefault>,
+    pub comment: String,
+    /// Compression codecs applied in order, i.e. `["Delta(8)", "ZSTD(1)"]`
+    pub codecs: Vec<String>,
+    /// Expression after which values of the column are reset to their default, i.e. `ts + toIntervalDay(30)`
+    pub ttl: Option<String>,
+}
+
+impl ColumnDef {
+    pub fn new(name: impl Into<String>, type_: Type) -> Self {
+        Self {
+            name: name.into(),
+            type_,
+            default: None,
+            comment: String::new(),
+            codecs: vec![],
+            ttl: None,
+        }
+    }
+
+    pub fn default(mut self, default: ColumnDefault) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// Appends a compression codec, i.e. `Delta` or `ZSTD(3)`.
+    pub fn codec(mut self, codec: impl Into<String>) -> Self {
+        self.codecs.push(codec.into());
+        self
+    }
+
+    pub fn ttl(mut self, ttl: impl Into<String>) -> Self {
+        self.ttl = Some(ttl.into());
+        self
+    }
+}
+
+/// Formats the column as in a `CREATE TABLE` statement, i.e. `` `ts` DateTime CODEC(Delta(4), ZSTD(1)) TTL ts + toIntervalDay(30) ``.
+impl fmt::Display for ColumnDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", Identifier::new(&*self.name), self.type_)?;
+        if let Some(default) = &self.default {
+            write!(f, " {default}")?;
+        }
+        if !self.comment.is_empty() {
+            write!(f, " COMMENT {}", quote_string(&self.comment))?;
+        }
+        if !self.codecs.is_empty() {
+            write!(f, " CODEC({})", self.codecs.join(", "))?;
+        }
+        if let Some(ttl) = &self.ttl {
+            write!(f, " TTL {ttl}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a `CREATE TABLE` statement. Expressions (engine, keys, TTLs, settings) are interpolated as-is.
+/// ```
+/// # use klickhouse::{ColumnDef, CreateTableBuilder, Type};
+/// let ddl = CreateTableBuilder::new("events")
+///     .if_not_exists()
+///     .column(ColumnDef::new("ts", Type::DateTime(klickhouse::Tz::UTC)).codec("Delta").codec("ZSTD"))
+///     .column(ColumnDef::new("payload", Type::String).ttl("ts + INTERVAL 30 DAY"))
+///     .order_by("ts")
+///     .build();
+/// assert_eq!(
+///     ddl,
+///     "CREATE TABLE IF NOT EXISTS `events` (`ts` DateTime('UTC') CODEC(Delta, ZSTD), `payload` String TTL ts + INTERVAL 30 DAY) \
+///      ENGINE = MergeTree ORDER BY ts"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct CreateTableBuilder {
+    table: String,
+    if_not_exists: bool,
+    columns: Vec<ColumnDef>,
+    engine: String,
+    partition_by: Option<String>,
+    order_by: Option<String>,
+    ttl: Option<String>,
+    settings: Vec<(String, String)>,
+}
+
+impl CreateTableBuilder {
+    /// Starts a `MergeTree` table named `table`, which may be qualified with a database.
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            if_not_exists: false,
+            columns: vec![],
+            engine: "MergeTree".to_string(),
+            partition_by: None,
+            order_by: None,
+            ttl: None,
+            settings: vec![],
+        }
+    }
+
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    pub fn column(mut self, column: ColumnDef) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    pub fn columns(mut self, columns: impl IntoIterator<Item = ColumnDef>) -> Self {
+        self.columns.extend(columns);
+        self
+    }
+
+    /// Table engine with its arguments, i.e. `ReplacingMergeTree(version)`. Defaults to `MergeTree`.
+    pub fn engine(mut self, engine: impl Into<String>) -> Self {
+        self.engine = engine.into();
+        self
+    }
+
+    pub fn partition_by(mut self, expression: impl Into<String>) -> Self {
+        self.partition_by = Some(expression.into());
+        self
+    }
+
+    pub fn order_by(mut self, expression: impl Into<String>) -> Self {
+        self.order_by = Some(expression.into());
+        self
+    }
+
+    /// Table-level TTL, i.e. `ts + INTERVAL 1 YEAR DELETE`.
+    pub fn ttl(mut self, expression: impl Into<String>) -> Self {
+        self.ttl = Some(expression.into());
+        self
+    }
+
+    pub fn setting(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.settings.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn build(&self) -> String {
+        let mut out = format!(
+            "CREATE TABLE {}{} ({}) ENGINE = {}",
+            if self.if_not_exists {
+                "IF NOT EXISTS "
+            } else {
+                ""
+            },
+            self.table
+                .split('.')
+                .map(|x| Identifier::new(x).to_string())
+                .collect::<Vec<_>>()
+                .join("."),
+            self.columns
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.engine
+        );
+        if let Some(partition_by) = &self.partition_by {
+            out.push_str(&format!(" PARTITION BY {partition_by}"));
+        }
+        if let Some(order_by) = &self.order_by {
+            out.push_str(&format!(" ORDER BY {order_by}"));
+        }
+        if let Some(ttl) = &self.ttl {
+            out.push_str(&format!(" TTL {ttl}"));
+        }
+        if !self.settings.is_empty() {
+            let settings = self
+                .settings
+                .iter()
+                .map(|(name, value)| format!("{name} = {value}"))
+                .collect::<Vec<_>>();
+            out.push_str(&format!(" SETTINGS {}", settings.join(", ")));
+        }
+        out
+    }
+}
+
+fn quote_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Splits a `CODEC(...)` expression from `DESCRIBE TABLE` into its codecs.
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+fn parse_codecs(expression: &str) -> Vec<String> {
+    let Some(inner) = expression
+        .trim()
+        .strip_prefix("CODEC(")
+        .and_then(|x| x.strip_suffix(')'))
+    else {
+        return vec![];
+    };
+    let mut codecs = vec![];
+    let mut depth = 0usize;
+    let mut current = String::new();
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                codecs.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => (),
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        codecs.push(current.trim().to_string());
+    }
+    codecs
+}
+
+#[cfg(feature = "client")]
+impl Client {
+    /// Fetches all columns of `table` in order via `DESCRIBE TABLE`, including defaults, comments, codecs and TTLs.
+    /// `table` is quoted, use [`QualifiedTable::with_database`] for a table of another database.
+    pub async fn describe_columns(
+        &self,
+        table: impl Into<QualifiedTable>,
+    ) -> Result<Vec<ColumnDef>> {
+        let mut rows = self
+            .query::<RawRow>(format!("DESCRIBE TABLE {}", table.into()))
+            .await?;
+        let mut columns = vec![];
+        while let Some(row) = rows.next().await {
+            let mut row = row?;
+            let type_: String = row.try_get("type")?;
+            let default_type: String = row.try_get("default_type")?;
+            let codec: String = row.try_get("codec_expression")?;
+            let ttl: String = row.try_get("ttl_expression")?;
+            columns.push(ColumnDef {
+                name: row.try_get("name")?,
+                type_: type_.parse()?,
+                default: ColumnDefault::parse(&default_type, row.try_get("default_expression")?)?,
+                comment: row.try_get("comment")?,
+                codecs: parse_codecs(&codec),
+                ttl: (!ttl.is_empty()).then_some(ttl),
+            });
+        }
+        Ok(columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_def() {
+        let column = ColumnDef::new("value", Type::Float64)
+            .default(ColumnDefault::Default("0".to_string()))
+            .comment("it's a value")
+            .codec("Gorilla")
+            .codec("ZSTD(3)")
+            .ttl("ts + INTERVAL 1 DAY");
+        assert_eq!(
+            column.to_string(),
+            "`value` Float64 DEFAULT 0 COMMENT 'it\\'s a value' CODEC(Gorilla, ZSTD(3)) TTL ts + INTERVAL 1 DAY"
+        );
+        assert_eq!(
+            ColumnDef::new("a`b", Type::UInt8).to_string(),
+            "`a\\`b` UInt8"
+        );
+    }
+
+    #[test]
+    fn test_create_table() {
+        let ddl = CreateTableBuilder::new("db.t")
+            .column(ColumnDef::new("id", Type::UInt64))
+            .engine("ReplacingMergeTree")
+            .partition_by("id % 4")
+            .order_by("id")
+            .ttl("now() + INTERVAL 1 DAY")
+            .setting("index_granularity", "1024")
+            .build();
+        assert_eq!(
+            ddl,
+            "CREATE TABLE `db`.`t` (`id` UInt64) ENGINE = ReplacingMergeTree PARTITION BY id % 4 ORDER BY id \
+             TTL now() + INTERVAL 1 DAY SETTINGS index_granularity = 1024"
+        );
+    }
+
+    #[test]
+    fn test_parse_codecs() {
+        assert_eq!(
+            parse_codecs("CODEC(Delta(8), ZSTD(1))"),
+            vec!["Delta(8)".to_string(), "ZSTD(1)".to_string()]
+        );
+        assert_eq!(parse_codecs("CODEC(LZ4)"), vec!["LZ4".to_string()]);
+        assert!(parse_codecs("").is_empty());
+        assert_eq!(
+            ColumnDefault::parse("ALIAS", "a + 1".to_string()).unwrap(),
+            Some(ColumnDefault::Alias("a + 1".to_string()))
+        );
+        assert_eq!(ColumnDefault::parse("", String::new()).unwrap(), None);
+        assert!(ColumnDefault::parse("UNKNOWN", String::new()).is_err());
+    }
+}
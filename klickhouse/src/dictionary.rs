@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+
+use crate::{Client, QualifiedTable, QueryBuilder, RawRow, Result};
+
+/// Load state of an external dictionary, from `system.dictionaries`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DictionaryStatus {
+    NotLoaded,
+    Loaded,
+    Failed,
+    Loading,
+    FailedAndReloading,
+    LoadedAndReloading,
+    NotExist,
+    /// A status not known to this version of klickhouse
+    Other(String),
+}
+
+impl DictionaryStatus {
+    fn parse(status: String) -> Self {
+        match &*status {
+            "NOT_LOADED" => DictionaryStatus::NotLoaded,
+            "LOADED" => DictionaryStatus::Loaded,
+            "FAILED" => DictionaryStatus::Failed,
+            "LOADING" => DictionaryStatus::Loading,
+            "FAILED_AND_RELOADING" => DictionaryStatus::FailedAndReloading,
+            "LOADED_AND_RELOADING" => DictionaryStatus::LoadedAndReloading,
+            "NOT_EXIST" => DictionaryStatus::NotExist,
+            _ => DictionaryStatus::Other(status),
+        }
+    }
+
+    /// `true` if the dictionary can serve `dictGet` calls, possibly while reloading.
+    pub fn is_loaded(&self) -> bool {
+        matches!(
+            self,
+            DictionaryStatus::Loaded | DictionaryStatus::LoadedAndReloading
+        )
+    }
+
+    /// `true` if the last load attempt failed, see [`DictionaryInfo::last_exception`].
+    pub fn is_failed(&self) -> bool {
+        matches!(
+            self,
+            DictionaryStatus::Failed | DictionaryStatus::FailedAndReloading
+        )
+    }
+}
+
+/// An external dictionary, from `system.dictionaries`. See [`Client::list_dictionaries`].
+#[derive(Debug, Clone)]
+pub struct DictionaryInfo {
+    /// Empty for dictionaries defined in the server configuration
+    pub database: String,
+    pub name: String,
+    pub status: DictionaryStatus,
+    /// Path of the configuration file or DDL that defines the dictionary
+    pub origin: String,
+    /// Layout, i.e. `Flat` or `Hashed`
+    pub layout: String,
+    pub element_count: u64,
+    pub bytes_allocated: u64,
+    pub last_successful_update_time: Option<DateTime<Utc>>,
+    /// Error of the last failed load, if any
+    pub last_exception: Option<String>,
+}
+
+const DICTIONARY_COLUMNS: &str = "database, name, toString(status) AS status, origin, type, element_count, bytes_allocated, \
+    if(last_successful_update_time = 0, NULL, toDateTime(last_successful_update_time, 'UTC')) AS last_successful_update_time, last_exception";
+
+impl DictionaryInfo {
+    fn from_row(mut row: RawRow) -> Result<Self> {
+        let last_exception: String = row.try_get("last_exception")?;
+        Ok(Self {
+            database: row.try_get("database")?,
+            name: row.try_get("name")?,
+            status: DictionaryStatus::parse(row.try_get("status")?),
+            origin: row.try_get("origin")?,
+            layout: row.try_get("type")?,
+            element_count: row.try_get("element_count")?,
+            bytes_allocated: row.try_get("bytes_allocated")?,
+            last_successful_update_time: row.try_get("last_successful_update_time")?,
+            last_exception: (!last_exception.is_empty()).then_some(last_exception),
+        })
+    }
+}
+
+impl Client {
+    async fn query_dictionaries(&self, query: QueryBuilder<'_>) -> Result<Vec<DictionaryInfo>> {
+        let mut rows = self.query::<RawRow>(query).await?;
+        let mut out = vec![];
+        while let Some(row) = rows.next().await {
+            out.push(DictionaryInfo::from_row(row?)?);
+        }
+        Ok(out)
+    }
+
+    /// Lists all external dictionaries known to the server, with their load status.
+    pub async fn list_dictionaries(&self) -> Result<Vec<DictionaryInfo>> {
+        let query =
+            format!("SELECT {DICTIONARY_COLUMNS} FROM system.dictionaries ORDER BY database, name");
+        self.query_dictionaries(QueryBuilder::new(&query)).await
+    }
+
+    /// Fetches the status and last exception of the dictionary `name`, which may be qualified with a database.
+    /// Returns `None` if there is no such dictionary.
+    pub async fn dictionary_status(&self, name: &str) -> Result<Option<DictionaryInfo>> {
+        let query = format!(
+            "SELECT {DICTIONARY_COLUMNS} FROM system.dictionaries WHERE name = $1 OR concat(database, '.', name) = $1 LIMIT 1"
+        );
+        Ok(self
+            .query_dictionaries(QueryBuilder::new(&query).arg(name))
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Reloads the dictionary `name` via `SYSTEM RELOAD DICTIONARY`, waiting for the load to finish.
+    /// `name` is quoted, use [`QualifiedTable::with_database`] for a dictionary of another database.
+    pub async fn reload_dictionary(&self, name: impl Into<QualifiedTable>) -> Result<()> {
+        self.execute(format!("SYSTEM RELOAD DICTIONARY {}", name.into()))
+            .await
+    }
+
+    /// Reloads all dictionaries via `SYSTEM RELOAD DICTIONARIES`.
+    pub async fn reload_dictionaries(&self) -> Result<()> {
+        self.execute("SYSTEM RELOAD DICTIONARIES").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dictionary_status() {
+        assert_eq!(
+            DictionaryStatus::parse("LOADED_AND_RELOADING".to_string()),
+            DictionaryStatus::LoadedAndReloading
+        );
+        assert!(DictionaryStatus::parse("LOADED".to_string()).is_loaded());
+        assert!(DictionaryStatus::parse("FAILED_AND_RELOADING".to_string()).is_failed());
+        assert_eq!(
+            DictionaryStatus::parse("UNKNOWN".to_string()),
+            DictionaryStatus::Other("UNKNOWN".to_string())
+        );
+    }
+}
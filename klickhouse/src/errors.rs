@@ -1,4 +1,4 @@
-use std::{borrow::Cow, string::FromUtf8Error};
+use std::{borrow::Cow, fmt, string::FromUtf8Error};
 
 use thiserror::Error;
 
@@ -16,34 +16,163 @@ pub enum KlickhouseError {
     MissingField(&'static str),
     #[error("duplicate field {0} in struct")]
     DuplicateField(&'static str),
+    #[error("duplicate column {name} at index {index}, alias one of the columns to disambiguate (i.e. `SELECT a, a AS a2`)")]
+    DuplicateColumn {
+        name: Cow<'static, str>,
+        index: usize,
+    },
     #[error("protocol error: {0}")]
     ProtocolError(String),
     #[error("type parse error: {0}")]
-    TypeParseError(String),
+    TypeParseError(TypeParseError),
     #[error("deserialize error: {0}")]
     DeserializeError(String),
     #[error("serialize error: {0}")]
     SerializeError(String),
     #[error("deserialize error for column {0}: {1}")]
     DeserializeErrorWithColumn(&'static str, String),
-    #[error("server exception: {code} {name}: {message}\n{stack_trace}")]
+    #[error("server exception: {code} {name}: {message}{}\n{stack_trace}", query_id.as_ref().map(|x| format!(" (query id {x})")).unwrap_or_default())]
     ServerException {
         code: i32,
         name: String,
         message: String,
         stack_trace: String,
+        /// ID of the query that failed, if the exception was raised by one.
+        query_id: Option<String>,
+        /// The exception that caused this one, as reported by the server.
+        #[source]
+        nested: Option<Box<KlickhouseError>>,
     },
     #[error("unexpected type: {0}")]
     UnexpectedType(Type),
     #[error("unexpected type for column {0}: {1}")]
     UnexpectedTypeWithColumn(Cow<'static, str>, Type),
+    /// A value whose shape doesn't match its type or the Rust type it's read into, see [`unexpected_value`](crate::unexpected_value).
+    #[error("unexpected value: expected {expected}, got {received}")]
+    UnexpectedValue { expected: String, received: Type },
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("utf-8 conversion error: {0}")]
     Utf8(#[from] FromUtf8Error),
+    #[error("slow consumer: {0}")]
+    SlowConsumer(String),
+    #[error("query argument error: {0}")]
+    QueryArgumentError(String),
+    #[error("invalid connection url: {0}")]
+    InvalidUrl(String),
+    #[error("tls error: {message}")]
+    TlsError {
+        message: String,
+        /// The underlying error, i.e. a `rustls::Error`
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    /// A block's columns differ from those of earlier blocks of the same query, i.e. after a schema change during a `WATCH` or long-running query.
+    /// No further blocks of the query are delivered, restart the query to continue with the new schema.
+    #[error("schema changed during query: expected columns ({expected}), received ({received})")]
+    SchemaChanged { expected: String, received: String },
+}
+
+/// Where parsing a Clickhouse type name (i.e. from `DESCRIBE` output) failed, or why a type or value was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParseError {
+    /// The full type name being parsed.
+    pub input: String,
+    /// Byte offset into `input` where the part that couldn't be parsed starts. 0 if the whole type is invalid.
+    pub position: usize,
+    pub message: String,
+}
+
+impl TypeParseError {
+    /// An error about the whole type `input`, rather than a part of its name.
+    pub(crate) fn whole(input: impl ToString, message: impl Into<String>) -> Self {
+        Self {
+            input: input.to_string(),
+            position: 0,
+            message: message.into(),
+        }
+    }
+
+    /// The part of `input` from the failure on, i.e. `Foo(1))` in `Array(Foo(1))`.
+    pub fn remainder(&self) -> &str {
+        self.input.get(self.position..).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for TypeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.position == 0 {
+            write!(f, "{} in '{}'", self.message, self.input)
+        } else {
+            write!(
+                f,
+                "{} in '{}' at position {}: '{}'",
+                self.message,
+                self.input,
+                self.position,
+                self.remainder()
+            )
+        }
+    }
 }
 
+/// Clickhouse server error codes that indicate a transient condition, see `src/Common/ErrorCodes.cpp` in Clickhouse.
+const RETRYABLE_SERVER_CODES: &[i32] = &[
+    3,   // UNEXPECTED_END_OF_FILE
+    159, // TIMEOUT_EXCEEDED
+    202, // TOO_MANY_SIMULTANEOUS_QUERIES
+    203, // NO_FREE_CONNECTION
+    209, // SOCKET_TIMEOUT
+    210, // NETWORK_ERROR
+    236, // ABORTED
+    242, // TABLE_IS_READ_ONLY
+    252, // TOO_MANY_PARTS
+    285, // TOO_FEW_LIVE_REPLICAS
+    319, // UNKNOWN_STATUS_OF_INSERT
+    425, // SYSTEM_ERROR
+    999, // KEEPER_EXCEPTION
+];
+
 impl KlickhouseError {
+    /// Whether retrying the operation (on a new connection for IO errors) may succeed, i.e. for network failures or server overload.
+    pub fn is_retryable(&self) -> bool {
+        use std::io::ErrorKind;
+        match self {
+            KlickhouseError::Io(e) => matches!(
+                e.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::NotConnected
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::TimedOut
+                    | ErrorKind::Interrupted
+                    | ErrorKind::UnexpectedEof
+            ),
+            KlickhouseError::ServerException { code, .. } => RETRYABLE_SERVER_CODES.contains(code),
+            _ => false,
+        }
+    }
+
+    /// Attaches the ID of the failed query to a server exception.
+    pub fn with_query_id(mut self, id: impl ToString) -> Self {
+        if let KlickhouseError::ServerException { query_id, .. } = &mut self {
+            *query_id = Some(id.to_string());
+        }
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    pub(crate) fn tls(
+        message: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        KlickhouseError::TlsError {
+            message: message.into(),
+            source: Some(Box::new(source)),
+        }
+    }
+
     pub fn with_column_name(self, name: &'static str) -> Self {
         match self {
             KlickhouseError::DeserializeError(e) => {
@@ -52,6 +181,9 @@ impl KlickhouseError {
             KlickhouseError::UnexpectedType(e) => {
                 KlickhouseError::UnexpectedTypeWithColumn(Cow::Borrowed(name), e)
             }
+            e @ KlickhouseError::UnexpectedValue { .. } => {
+                KlickhouseError::DeserializeErrorWithColumn(name, e.to_string())
+            }
             x => x,
         }
     }
@@ -65,6 +197,10 @@ impl Clone for KlickhouseError {
             Self::OutOfBounds => Self::OutOfBounds,
             Self::MissingField(arg0) => Self::MissingField(arg0),
             Self::DuplicateField(arg0) => Self::DuplicateField(arg0),
+            Self::DuplicateColumn { name, index } => Self::DuplicateColumn {
+                name: name.clone(),
+                index: *index,
+            },
             Self::ProtocolError(arg0) => Self::ProtocolError(arg0.clone()),
             Self::TypeParseError(arg0) => Self::TypeParseError(arg0.clone()),
             Self::DeserializeError(arg0) => Self::DeserializeError(arg0.clone()),
@@ -77,20 +213,78 @@ impl Clone for KlickhouseError {
                 name,
                 message,
                 stack_trace,
+                query_id,
+                nested,
             } => Self::ServerException {
                 code: *code,
                 name: name.clone(),
                 message: message.clone(),
                 stack_trace: stack_trace.clone(),
+                query_id: query_id.clone(),
+                nested: nested.clone(),
             },
             Self::UnexpectedType(arg0) => Self::UnexpectedType(arg0.clone()),
             Self::UnexpectedTypeWithColumn(arg0, arg1) => {
                 Self::UnexpectedTypeWithColumn(arg0.clone(), arg1.clone())
             }
+            Self::UnexpectedValue { expected, received } => Self::UnexpectedValue {
+                expected: expected.clone(),
+                received: received.clone(),
+            },
             Self::Io(arg0) => Self::Io(std::io::Error::new(arg0.kind(), format!("{arg0}"))),
             Self::Utf8(arg0) => Self::Utf8(arg0.clone()),
+            Self::SlowConsumer(arg0) => Self::SlowConsumer(arg0.clone()),
+            Self::QueryArgumentError(arg0) => Self::QueryArgumentError(arg0.clone()),
+            Self::InvalidUrl(arg0) => Self::InvalidUrl(arg0.clone()),
+            // like `Io`, the clone only keeps the source's message
+            Self::TlsError { message, source } => Self::TlsError {
+                message: message.clone(),
+                source: source.as_ref().map(|x| x.to_string().into()),
+            },
+            Self::SchemaChanged { expected, received } => Self::SchemaChanged {
+                expected: expected.clone(),
+                received: received.clone(),
+            },
         }
     }
 }
 
 pub type Result<T, E = KlickhouseError> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn test_error_source() {
+        let nested = KlickhouseError::ServerException {
+            code: 210,
+            name: "DB::NetException".to_string(),
+            message: "connection reset".to_string(),
+            stack_trace: String::new(),
+            query_id: None,
+            nested: None,
+        };
+        let error = KlickhouseError::ServerException {
+            code: 1000,
+            name: "DB::Exception".to_string(),
+            message: "outer".to_string(),
+            stack_trace: String::new(),
+            query_id: None,
+            nested: Some(Box::new(nested)),
+        }
+        .with_query_id("abc");
+        assert!(error.to_string().contains("(query id abc)"));
+        assert!(!error.is_retryable());
+        let source = error.source().unwrap();
+        assert!(source.to_string().contains("connection reset"));
+        assert!(error.clone().source().is_some());
+
+        let io = KlickhouseError::from(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        assert!(io.is_retryable());
+        assert!(io.source().is_some());
+        assert!(!KlickhouseError::MissingRow.is_retryable());
+    }
+}
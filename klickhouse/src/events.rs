@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+/// Lifecycle events of a connection, see [`Client::subscribe_events`](crate::Client::subscribe_events).
+/// Several connections can report to one channel through [`ClientOptions::event_sender`](crate::ClientOptions::event_sender).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClientEvent {
+    /// The handshake with the server completed.
+    Connected {
+        server_name: String,
+        /// Server version, i.e. `24.3.2`
+        version: String,
+        revision: u64,
+    },
+    /// A query was submitted. `pending` is the number of queries waiting behind the executing one, including this one if it had to wait.
+    QueryQueued { pending: usize },
+    /// A query was sent to the server.
+    QueryStarted { id: Uuid },
+    /// A query completed, or failed with `error`.
+    QueryFinished {
+        id: Uuid,
+        elapsed: Duration,
+        error: Option<String>,
+    },
+    /// The columns of a block differed from those of earlier blocks of query `id`, which then fails with [`KlickhouseError::SchemaChanged`](crate::KlickhouseError::SchemaChanged).
+    SchemaChanged { id: Uuid },
+    /// The connection closed, either because every `Client` handle was dropped or with `error`.
+    Disconnected { error: Option<String> },
+    /// [`Pool::retry`](crate::Pool::retry) runs an operation again on another connection, for the `attempt`-th time, after it failed with `error`.
+    /// Only sent to [`ClientOptions::event_sender`](crate::ClientOptions::event_sender) of the pool, as the connection it used may be gone.
+    Reconnecting { attempt: usize, error: String },
+}
@@ -0,0 +1,387 @@
+use serde_json::Value as JsonValue;
+
+use crate::{Client, KlickhouseError, ParsedQuery, RawRow, Result};
+
+/// The kind of `EXPLAIN` run by [`Client::explain`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplainKind {
+    /// `EXPLAIN PLAN` with index analysis, see [`Client::explain_plan`].
+    Plan,
+    /// `EXPLAIN PIPELINE`, see [`Client::explain_pipeline`].
+    Pipeline,
+    /// `EXPLAIN ESTIMATE`, see [`Client::explain_estimate`].
+    Estimate,
+}
+
+/// Result of [`Client::explain`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Explain {
+    Plan(Vec<ExplainPlanNode>),
+    Pipeline(Vec<ExplainPipelineNode>),
+    Estimate(Vec<ExplainEstimate>),
+}
+
+/// A step of a query plan, from `EXPLAIN PLAN json = 1`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExplainPlanNode {
+    /// Step name, i.e. `Expression` or `ReadFromMergeTree`
+    pub node_type: String,
+    /// Step details, i.e. the table read by `ReadFromMergeTree`
+    pub description: Option<String>,
+    /// Indexes used to select parts and granules, for steps reading from MergeTree tables
+    pub indexes: Vec<ExplainIndex>,
+    /// Steps this step reads from
+    pub plans: Vec<ExplainPlanNode>,
+}
+
+impl ExplainPlanNode {
+    /// Iterates over this step and all steps below it, depth first.
+    pub fn walk(&self) -> impl Iterator<Item = &ExplainPlanNode> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.plans.iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Iterates over the indexes used anywhere in this part of the plan.
+    pub fn all_indexes(&self) -> impl Iterator<Item = &ExplainIndex> {
+        self.walk().flat_map(|x| x.indexes.iter())
+    }
+
+    fn parse(value: &JsonValue) -> Result<Self> {
+        let node_type = value
+            .get("Node Type")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| explain_error("plan step without a node type"))?;
+        Ok(Self {
+            node_type: node_type.to_string(),
+            description: json_string(value, "Description"),
+            indexes: json_array(value, "Indexes")
+                .iter()
+                .map(ExplainIndex::parse)
+                .collect(),
+            plans: json_array(value, "Plans")
+                .iter()
+                .map(Self::parse)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Use of an index by a step reading from a MergeTree table.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExplainIndex {
+    /// `MinMax`, `Partition`, `PrimaryKey` or `Skip`
+    pub type_: String,
+    /// Name of a skip index
+    pub name: Option<String>,
+    /// Columns of the index used by the condition
+    pub keys: Vec<String>,
+    pub condition: Option<String>,
+    pub initial_parts: Option<u64>,
+    pub selected_parts: Option<u64>,
+    pub initial_granules: Option<u64>,
+    pub selected_granules: Option<u64>,
+}
+
+impl ExplainIndex {
+    /// `true` if the index excluded any parts or granules.
+    pub fn is_selective(&self) -> bool {
+        self.selected_parts < self.initial_parts || self.selected_granules < self.initial_granules
+    }
+
+    fn parse(value: &JsonValue) -> Self {
+        let count = |name| value.get(name).and_then(JsonValue::as_u64);
+        Self {
+            type_: json_string(value, "Type").unwrap_or_default(),
+            name: json_string(value, "Name"),
+            keys: json_array(value, "Keys")
+                .iter()
+                .filter_map(|x| x.as_str().map(str::to_string))
+                .collect(),
+            condition: json_string(value, "Condition"),
+            initial_parts: count("Initial Parts"),
+            selected_parts: count("Selected Parts"),
+            initial_granules: count("Initial Granules"),
+            selected_granules: count("Selected Granules"),
+        }
+    }
+}
+
+fn json_string(value: &JsonValue, name: &str) -> Option<String> {
+    value.get(name)?.as_str().map(str::to_string)
+}
+
+fn json_array<'a>(value: &'a JsonValue, name: &str) -> &'a [JsonValue] {
+    value
+        .get(name)
+        .and_then(JsonValue::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn explain_error(message: impl std::fmt::Display) -> KlickhouseError {
+    KlickhouseError::DeserializeError(format!("invalid EXPLAIN output: {message}"))
+}
+
+/// Parses the output of `EXPLAIN PLAN json = 1`, a JSON array of `{"Plan": ...}` objects.
+fn parse_plan(json: &str) -> Result<Vec<ExplainPlanNode>> {
+    let value: JsonValue = serde_json::from_str(json).map_err(explain_error)?;
+    value
+        .as_array()
+        .ok_or_else(|| explain_error("expected an array"))?
+        .iter()
+        .map(|x| {
+            x.get("Plan")
+                .ok_or_else(|| explain_error("expected a plan"))
+                .and_then(ExplainPlanNode::parse)
+        })
+        .collect()
+}
+
+/// A line of `EXPLAIN PIPELINE` output: either a plan step, or a processor implementing the plan step above it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainPipelineNode {
+    /// Nesting level, from the indentation of the line
+    pub depth: usize,
+    /// Step or processor name, i.e. `ReadFromMergeTree` or `ExpressionTransform`
+    pub name: String,
+    /// `true` for plan steps, which are parenthesized in the text output
+    pub is_step: bool,
+    /// Number of parallel instances of a processor, from the ` × N` suffix
+    pub instances: usize,
+}
+
+fn parse_pipeline_line(line: &str) -> Option<ExplainPipelineNode> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let depth = (line.len() - trimmed.len()) / 2;
+    let trimmed = trimmed.trim_end();
+    if let Some(step) = trimmed.strip_prefix('(').and_then(|x| x.strip_suffix(')')) {
+        return Some(ExplainPipelineNode {
+            depth,
+            name: step.to_string(),
+            is_step: true,
+            instances: 1,
+        });
+    }
+    let (name, instances) = match trimmed.split_once(" × ") {
+        Some((name, rest)) => (
+            name,
+            rest.split_whitespace()
+                .next()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(1),
+        ),
+        None => {
+            // strip input and output port counts, i.e. `Resize 4 → 1`
+            let words = trimmed.split_whitespace().collect::<Vec<_>>();
+            match words.as_slice() {
+                [name @ .., inputs, "→", outputs]
+                    if !name.is_empty()
+                        && inputs.parse::<usize>().is_ok()
+                        && outputs.parse::<usize>().is_ok() =>
+                {
+                    return Some(ExplainPipelineNode {
+                        depth,
+                        name: name.join(" "),
+                        is_step: false,
+                        instances: 1,
+                    });
+                }
+                _ => (trimmed, 1),
+            }
+        }
+    };
+    Some(ExplainPipelineNode {
+        depth,
+        name: name.to_string(),
+        is_step: false,
+        instances,
+    })
+}
+
+/// Estimated amount of data read from a table, from `EXPLAIN ESTIMATE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainEstimate {
+    pub database: String,
+    pub table: String,
+    pub parts: u64,
+    pub rows: u64,
+    pub marks: u64,
+}
+
+impl Client {
+    async fn explain_lines(
+        &self,
+        prefix: &str,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<Vec<String>> {
+        let query = explain_query(prefix, query.try_into()?);
+        let rows = self.query_collect::<RawRow>(&query).await?;
+        rows.into_iter().map(|mut row| row.try_get(0)).collect()
+    }
+
+    /// Runs `EXPLAIN` of `kind` for `query`, which is not executed.
+    pub async fn explain(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        kind: ExplainKind,
+    ) -> Result<Explain> {
+        Ok(match kind {
+            ExplainKind::Plan => Explain::Plan(self.explain_plan(query).await?),
+            ExplainKind::Pipeline => Explain::Pipeline(self.explain_pipeline(query).await?),
+            ExplainKind::Estimate => Explain::Estimate(self.explain_estimate(query).await?),
+        })
+    }
+
+    /// Returns the plan of `query` with the indexes used to read each table, i.e. to check in tests that a query is served by the primary key.
+    pub async fn explain_plan(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<Vec<ExplainPlanNode>> {
+        let lines = self
+            .explain_lines("EXPLAIN PLAN json = 1, indexes = 1, description = 1", query)
+            .await?;
+        parse_plan(&lines.join("\n"))
+    }
+
+    /// Returns the processors that would execute `query`, in the order of the text output of `EXPLAIN PIPELINE`.
+    pub async fn explain_pipeline(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<Vec<ExplainPipelineNode>> {
+        let lines = self.explain_lines("EXPLAIN PIPELINE", query).await?;
+        Ok(lines
+            .iter()
+            .filter_map(|x| parse_pipeline_line(x))
+            .collect())
+    }
+
+    /// Returns the number of parts, rows and marks `query` would read from each MergeTree table.
+    pub async fn explain_estimate(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<Vec<ExplainEstimate>> {
+        let query = explain_query("EXPLAIN ESTIMATE", query.try_into()?);
+        let rows = self.query_collect::<RawRow>(&query).await?;
+        rows.into_iter()
+            .map(|mut row| {
+                Ok(ExplainEstimate {
+                    database: row.try_get("database")?,
+                    table: row.try_get("table")?,
+                    parts: row.try_get("parts")?,
+                    rows: row.try_get("rows")?,
+                    marks: row.try_get("marks")?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn explain_query(prefix: &str, query: ParsedQuery) -> ParsedQuery {
+    ParsedQuery {
+        sql: format!("{prefix} {}", query.sql),
+        redacted: query.redacted.map(|x| format!("{prefix} {x}")),
+        secrets: query.secrets,
+        params: query.params,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plan() {
+        let plan = parse_plan(
+            r#"[
+  {
+    "Plan": {
+      "Node Type": "Expression",
+      "Plans": [
+        {
+          "Node Type": "ReadFromMergeTree",
+          "Description": "default.test",
+          "Indexes": [
+            {
+              "Type": "PrimaryKey",
+              "Keys": ["id"],
+              "Condition": "(id in [5, 5])",
+              "Initial Parts": 2,
+              "Selected Parts": 1,
+              "Initial Granules": 10,
+              "Selected Granules": 1
+            },
+            {
+              "Type": "Skip",
+              "Name": "idx_value",
+              "Initial Parts": 1,
+              "Selected Parts": 1,
+              "Initial Granules": 1,
+              "Selected Granules": 1
+            }
+          ]
+        }
+      ]
+    }
+  }
+]"#,
+        )
+        .unwrap();
+        assert_eq!(plan.len(), 1);
+        let nodes = plan[0].walk().map(|x| &*x.node_type).collect::<Vec<_>>();
+        assert_eq!(nodes, vec!["Expression", "ReadFromMergeTree"]);
+        let indexes = plan[0].all_indexes().collect::<Vec<_>>();
+        assert_eq!(indexes.len(), 2);
+        assert_eq!(indexes[0].type_, "PrimaryKey");
+        assert_eq!(indexes[0].keys, vec!["id"]);
+        assert!(indexes[0].is_selective());
+        assert_eq!(indexes[1].name.as_deref(), Some("idx_value"));
+        assert!(!indexes[1].is_selective());
+
+        assert!(parse_plan("[{}]").is_err());
+        assert!(parse_plan("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_pipeline() {
+        let lines = [
+            "(Expression)",
+            "ExpressionTransform × 4",
+            "  (ReadFromMergeTree)",
+            "  MergeTreeSelect(pool: ReadPool, algorithm: Thread) × 4 0 → 1",
+            "    Resize 1 → 4",
+            "",
+        ];
+        let nodes = lines
+            .iter()
+            .filter_map(|x| parse_pipeline_line(x))
+            .collect::<Vec<_>>();
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(
+            nodes[0],
+            ExplainPipelineNode {
+                depth: 0,
+                name: "Expression".to_string(),
+                is_step: true,
+                instances: 1,
+            }
+        );
+        assert_eq!(nodes[1].name, "ExpressionTransform");
+        assert_eq!(nodes[1].instances, 4);
+        assert_eq!(nodes[2].depth, 1);
+        assert_eq!(
+            nodes[3].name,
+            "MergeTreeSelect(pool: ReadPool, algorithm: Thread)"
+        );
+        assert_eq!(nodes[3].instances, 4);
+        assert_eq!(nodes[4].depth, 2);
+        assert_eq!(nodes[4].name, "Resize");
+        assert_eq!(nodes[4].instances, 1);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{convert::unexpected_type, KlickhouseError, Result, Type, Value};
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+/// A duration type that can be stored as an integer count of some unit.
+/// [`std::time::Duration`] is written as `UInt64`, [`chrono::Duration`] as `Int64`.
+/// Any integer column up to 64 bits can be read.
+pub trait IntegerDuration: Sized {
+    #[doc(hidden)]
+    fn to_units(self, nanos_per_unit: i128) -> Result<Value>;
+
+    #[doc(hidden)]
+    fn from_units(units: i128, nanos_per_unit: i128) -> Result<Self>;
+}
+
+impl IntegerDuration for std::time::Duration {
+    fn to_units(self, nanos_per_unit: i128) -> Result<Value> {
+        let units = self.as_nanos() as i128 / nanos_per_unit;
+        Ok(Value::UInt64(units.try_into().map_err(|_| {
+            KlickhouseError::SerializeError(format!("duration {self:?} overflows UInt64"))
+        })?))
+    }
+
+    fn from_units(units: i128, nanos_per_unit: i128) -> Result<Self> {
+        if units < 0 {
+            return Err(KlickhouseError::DeserializeError(format!(
+                "negative duration {units} cannot be represented as std::time::Duration"
+            )));
+        }
+        let nanos = units * nanos_per_unit;
+        Ok(std::time::Duration::new(
+            (nanos / NANOS_PER_SEC) as u64,
+            (nanos % NANOS_PER_SEC) as u32,
+        ))
+    }
+}
+
+impl IntegerDuration for chrono::Duration {
+    fn to_units(self, nanos_per_unit: i128) -> Result<Value> {
+        let nanos = self.num_seconds() as i128 * NANOS_PER_SEC + self.subsec_nanos() as i128;
+        Ok(Value::Int64((nanos / nanos_per_unit).try_into().map_err(
+            |_| KlickhouseError::SerializeError(format!("duration {self} overflows Int64")),
+        )?))
+    }
+
+    fn from_units(units: i128, nanos_per_unit: i128) -> Result<Self> {
+        let nanos = units * nanos_per_unit;
+        let seconds = (nanos / NANOS_PER_SEC) as i64;
+        let subsec = (nanos % NANOS_PER_SEC) as i64;
+        chrono::Duration::try_seconds(seconds)
+            .map(|x| x + chrono::Duration::nanoseconds(subsec))
+            .ok_or_else(|| {
+                KlickhouseError::DeserializeError(format!(
+                    "duration of {seconds} seconds overflows chrono::Duration"
+                ))
+            })
+    }
+}
+
+fn integer_units(type_: &Type, value: Value) -> Result<i128> {
+    Ok(match value {
+        Value::Int8(x) => x as i128,
+        Value::Int16(x) => x as i128,
+        Value::Int32(x) => x as i128,
+        Value::Int64(x) => x as i128,
+        Value::UInt8(x) => x as i128,
+        Value::UInt16(x) => x as i128,
+        Value::UInt32(x) => x as i128,
+        Value::UInt64(x) => x as i128,
+        _ => return Err(unexpected_type(type_)),
+    })
+}
+
+macro_rules! duration_module {
+    ($name:ident, $unit:literal, $nanos_per_unit:expr) => {
+        #[doc = concat!("Maps a [`std::time::Duration`] or [`chrono::Duration`] to an integer column counting ", $unit, ".")]
+        ///
+        /// Sub-unit precision is truncated on write.
+        pub mod $name {
+            use super::*;
+
+            pub fn to_sql<D: IntegerDuration>(duration: D) -> Result<Value> {
+                duration.to_units($nanos_per_unit)
+            }
+
+            pub fn from_sql<D: IntegerDuration>(type_: &Type, value: Value) -> Result<D> {
+                D::from_units(integer_units(type_, value)?, $nanos_per_unit)
+            }
+        }
+    };
+}
+
+duration_module!(duration_secs, "seconds", NANOS_PER_SEC);
+duration_module!(duration_ms, "milliseconds", 1_000_000);
+duration_module!(duration_us, "microseconds", 1_000);
+duration_module!(duration_ns, "nanoseconds", 1);
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_std_duration() {
+        let duration = Duration::new(5, 123_456_789);
+        assert_eq!(duration_ms::to_sql(duration).unwrap(), Value::UInt64(5123));
+        assert_eq!(
+            duration_us::to_sql(duration).unwrap(),
+            Value::UInt64(5_123_456)
+        );
+        let out: Duration =
+            duration_ns::from_sql(&Type::UInt64, duration_ns::to_sql(duration).unwrap()).unwrap();
+        assert_eq!(out, duration);
+        let out: Duration = duration_ms::from_sql(&Type::Int32, Value::Int32(1500)).unwrap();
+        assert_eq!(out, Duration::from_millis(1500));
+        assert!(duration_ms::from_sql::<Duration>(&Type::Int64, Value::Int64(-1)).is_err());
+        assert!(duration_ms::from_sql::<Duration>(&Type::String, Value::string("1")).is_err());
+    }
+
+    #[test]
+    fn test_chrono_duration() {
+        let duration = -chrono::Duration::milliseconds(2500);
+        assert_eq!(duration_ms::to_sql(duration).unwrap(), Value::Int64(-2500));
+        assert_eq!(duration_secs::to_sql(duration).unwrap(), Value::Int64(-2));
+        let out: chrono::Duration =
+            duration_us::from_sql(&Type::Int64, duration_us::to_sql(duration).unwrap()).unwrap();
+        assert_eq!(out, duration);
+    }
+}
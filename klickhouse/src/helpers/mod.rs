@@ -0,0 +1,6 @@
+//! Modules for use with `#[klickhouse(with = "...")]` on [`Row`](crate::Row) fields.
+
+mod duration;
+pub use duration::*;
+pub mod parse;
+pub mod utf8_lossy;
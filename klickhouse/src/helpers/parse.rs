@@ -0,0 +1,48 @@
+//! Maps any [`FromStr`] + [`Display`] type to a `String` (or `FixedString`) column, parsing on read and formatting on write.
+//!
+//! Used by `#[klickhouse(parse)]`, i.e. for numbers stored as strings in a schema you don't control.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{convert::unexpected_type, KlickhouseError, Result, Type, Value};
+
+pub fn to_sql<T: Display>(value: T) -> Result<Value> {
+    Ok(Value::String(value.to_string().into_bytes()))
+}
+
+pub fn from_sql<T>(type_: &Type, value: Value) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if !matches!(type_, Type::String | Type::FixedString(_)) {
+        return Err(unexpected_type(type_));
+    }
+    let Value::String(bytes) = value else {
+        return Err(unexpected_type(type_));
+    };
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|e| KlickhouseError::DeserializeError(format!("failed to decode string: {e}")))?;
+    text.parse()
+        .map_err(|e| KlickhouseError::DeserializeError(format!("failed to parse {text:?}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(to_sql(42u64).unwrap(), Value::string("42"));
+        assert_eq!(
+            from_sql::<u64>(&Type::String, Value::string("42")).unwrap(),
+            42
+        );
+        assert_eq!(
+            from_sql::<f64>(&Type::FixedString(4), Value::string("1.5")).unwrap(),
+            1.5
+        );
+        assert!(from_sql::<u64>(&Type::String, Value::string("x")).is_err());
+        assert!(from_sql::<u64>(&Type::UInt64, Value::UInt64(1)).is_err());
+    }
+}
@@ -0,0 +1,23 @@
+//! Reads a `String` or `FixedString` column into a [`String`] field, replacing invalid UTF-8 sequences with `U+FFFD`
+//! instead of failing. Use as `#[klickhouse(with = "klickhouse::helpers::utf8_lossy")]`.
+//!
+//! This loses the original bytes, see [`MaybeUtf8`](crate::MaybeUtf8) to keep them.
+
+use crate::{convert::unexpected_type, unexpected_value, Result, Type, Value};
+
+pub fn to_sql(value: String) -> Result<Value> {
+    Ok(Value::String(value.into_bytes()))
+}
+
+pub fn from_sql(type_: &Type, value: Value) -> Result<String> {
+    if !matches!(type_, Type::String | Type::FixedString(_)) {
+        return Err(unexpected_type(type_));
+    }
+    match value {
+        Value::String(x) => Ok(match String::from_utf8(x) {
+            Ok(x) => x,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        }),
+        x => Err(unexpected_value(&x, type_)),
+    }
+}
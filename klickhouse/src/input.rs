@@ -0,0 +1,117 @@
+use futures_util::Stream;
+
+use crate::{
+    query_parser::fill_input_structure, Client, ColumnDef, Identifier, InsertResult,
+    KlickhouseError, ParsedQuery, QueryOptions, Result, Row,
+};
+
+impl Client {
+    /// Inserts rows through the `input` table function, so that the server transforms them with SQL before writing them,
+    /// i.e. to cast values, compute columns or filter rows during ingestion.
+    /// `query` calls `input()` once without arguments, which is filled in with the columns of `T` from [`Row::column_types`],
+    /// typed as by [`ExpectedColumn::default_type`](crate::ExpectedColumn::default_type), i.e. `UInt64` for a `u64` field.
+    /// `FORMAT Native` is appended unless the query ends with a `FORMAT` clause.
+    /// ```no_run
+    /// # async fn run(client: klickhouse::Client) -> klickhouse::Result<()> {
+    /// use klickhouse::Row;
+    ///
+    /// #[derive(Row)]
+    /// struct Visit {
+    ///     user_id: u64,
+    ///     url: String,
+    /// }
+    ///
+    /// let visits = vec![Visit { user_id: 1, url: "https://example.com/a?b".to_string() }];
+    /// client
+    ///     .insert_input(
+    ///         "INSERT INTO visits SELECT user_id, domain(url), now() FROM input() WHERE user_id != 0",
+    ///         futures_util::stream::iter([visits]),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    /// Fails for rows without [`Row::column_types`] and fields of other types, such as decimals. Use [`Client::insert_input_with_columns`] for those.
+    /// The returned row and block counts are those sent, which may differ from the rows written after filtering.
+    pub async fn insert_input<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        blocks: impl Stream<Item = Vec<T>> + Send + Unpin + 'static,
+    ) -> Result<InsertResult> {
+        let columns = T::column_types().ok_or_else(|| {
+            KlickhouseError::QueryArgumentError(
+                "insert_input requires a row type with column types, use insert_input_with_columns"
+                    .to_string(),
+            )
+        })?;
+        let columns = columns
+            .into_iter()
+            .map(|column| {
+                let type_ = column.default_type().ok_or_else(|| {
+                    KlickhouseError::QueryArgumentError(format!(
+                        "cannot infer the input type of column {} ({}), use insert_input_with_columns",
+                        column.name, column.rust_type
+                    ))
+                })?;
+                Ok(ColumnDef::new(column.name, type_))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.insert_input_with_options(query, columns, blocks, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `insert_input`, with the names and types of the input given by `columns`, i.e. to read a field as `LowCardinality(String)`.
+    /// Rows are serialized to `columns` like for [`Client::insert_native`], so the columns of `T` must all be among them.
+    /// ```no_run
+    /// # async fn run(client: klickhouse::Client, visits: Vec<klickhouse::RawRow>) -> klickhouse::Result<()> {
+    /// use klickhouse::{ColumnDef, Type};
+    ///
+    /// client
+    ///     .insert_input_with_columns(
+    ///         "INSERT INTO visits SELECT user_id, domain(url), now() FROM input()",
+    ///         vec![ColumnDef::new("user_id", Type::UInt64), ColumnDef::new("url", Type::String)],
+    ///         futures_util::stream::iter([visits]),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn insert_input_with_columns<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        columns: Vec<ColumnDef>,
+        blocks: impl Stream<Item = Vec<T>> + Send + Unpin + 'static,
+    ) -> Result<InsertResult> {
+        self.insert_input_with_options(query, columns, blocks, QueryOptions::default())
+            .await
+    }
+
+    /// Same as `insert_input_with_columns`, with per-query [`QueryOptions`].
+    pub async fn insert_input_with_options<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        columns: Vec<ColumnDef>,
+        blocks: impl Stream<Item = Vec<T>> + Send + Unpin + 'static,
+        options: QueryOptions,
+    ) -> Result<InsertResult> {
+        if let Some(names) = T::column_names() {
+            if let Some(missing) = names
+                .iter()
+                .find(|name| !columns.iter().any(|x| x.name == **name))
+            {
+                return Err(KlickhouseError::QueryArgumentError(format!(
+                    "input columns have no column {missing}"
+                )));
+            }
+        }
+        let structure = columns
+            .iter()
+            .map(|x| format!("{} {}", Identifier::new(&*x.name), x.type_))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut query = query.try_into()?;
+        query.sql = fill_input_structure(&query.sql, &structure)?;
+        self.insert_native_with_options(&query, blocks, options)
+            .await
+    }
+}
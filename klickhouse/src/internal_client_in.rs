@@ -23,31 +23,45 @@ pub(crate) const MAX_COMPRESSION_SIZE: u32 = 0x40000000;
 
 pub struct InternalClientIn<R: ClickhouseRead> {
     reader: R,
+    compression: CompressionMethod,
     pub server_hello: ServerHello,
 }
 
 impl<R: ClickhouseRead + 'static> InternalClientIn<R> {
-    pub fn new(reader: R) -> Self {
+    pub fn new(reader: R, compression: CompressionMethod) -> Self {
         InternalClientIn {
             reader,
+            compression,
             server_hello: ServerHello::default(),
         }
     }
 
+    /// Reads an exception followed by the chain of exceptions nested in it.
     async fn read_exception(&mut self) -> Result<ServerException> {
-        let code = self.reader.read_i32_le().await?;
-        let name = self.reader.read_utf8_string().await?;
-        let message = self.reader.read_utf8_string().await?;
-        let stack_trace = self.reader.read_utf8_string().await?;
-        let has_nested = self.reader.read_u8().await? != 0;
-
-        Ok(ServerException {
-            code,
-            name,
-            message,
-            stack_trace,
-            has_nested,
-        })
+        let mut chain = vec![];
+        loop {
+            let code = self.reader.read_i32_le().await?;
+            let name = self.reader.read_utf8_string().await?;
+            let message = self.reader.read_utf8_string().await?;
+            let stack_trace = self.reader.read_utf8_string().await?;
+            let has_nested = self.reader.read_u8().await? != 0;
+            chain.push(ServerException {
+                code,
+                name,
+                message,
+                stack_trace,
+                nested: None,
+            });
+            if !has_nested {
+                break;
+            }
+        }
+        let mut exception = chain.pop().unwrap();
+        while let Some(mut outer) = chain.pop() {
+            outer.nested = Some(Box::new(exception));
+            exception = outer;
+        }
+        Ok(exception)
     }
 
     #[cfg(feature = "compression")]
@@ -78,8 +92,9 @@ impl<R: ClickhouseRead + 'static> InternalClientIn<R> {
         Ok(ServerData { table_name, block })
     }
 
+    /// Logs and profile events are sent uncompressed, whatever the query's compression.
     async fn receive_log_data(&mut self) -> Result<ServerData> {
-        unimplemented!()
+        self.receive_data(CompressionMethod::None).await
     }
 
     pub async fn receive_packet(&mut self) -> Result<ServerPacket> {
@@ -117,7 +132,7 @@ impl<R: ClickhouseRead + 'static> InternalClientIn<R> {
                 }))
             }
             ServerPacketId::Data => Ok(ServerPacket::Data(
-                self.receive_data(CompressionMethod::default()).await?,
+                self.receive_data(self.compression).await?,
             )),
             ServerPacketId::Exception => Ok(ServerPacket::Exception(self.read_exception().await?)),
             ServerPacketId::Progress => {
@@ -165,10 +180,10 @@ impl<R: ClickhouseRead + 'static> InternalClientIn<R> {
                 }))
             }
             ServerPacketId::Totals => Ok(ServerPacket::Totals(
-                self.receive_data(CompressionMethod::default()).await?,
+                self.receive_data(self.compression).await?,
             )),
             ServerPacketId::Extremes => Ok(ServerPacket::Extremes(
-                self.receive_data(CompressionMethod::default()).await?,
+                self.receive_data(self.compression).await?,
             )),
             ServerPacketId::TablesStatusResponse => {
                 let mut response = TablesStatusResponse {
@@ -231,6 +246,9 @@ impl<R: ClickhouseRead + 'static> InternalClientIn<R> {
                 Ok(ServerPacket::PartUUIDs(out))
             }
             ServerPacketId::ReadTaskRequest => Ok(ServerPacket::ReadTaskRequest),
+            ServerPacketId::ProfileEvents => {
+                Ok(ServerPacket::ProfileEvents(self.receive_log_data().await?))
+            }
         };
         let packet = packet?;
 
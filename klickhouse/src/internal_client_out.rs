@@ -2,12 +2,16 @@ use crate::{
     block::Block,
     io::ClickhouseWrite,
     protocol::{
-        self, CompressionMethod, ServerHello, DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH,
-        DBMS_MIN_REVISION_WITH_CLIENT_INFO, DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET,
-        DBMS_MIN_REVISION_WITH_OPENTELEMETRY, DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO,
+        self, CompressionMethod, ServerHello, DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS, DBMS_MIN_REVISION_WITH_CLIENT_INFO,
+        DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET, DBMS_MIN_REVISION_WITH_OPENTELEMETRY,
+        DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS, DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO,
+        DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS,
         DBMS_MIN_REVISION_WITH_VERSION_PATCH,
     },
-    Result,
+    KlickhouseError, Result,
 };
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
@@ -21,6 +25,9 @@ pub struct ClientHello<'a> {
     pub default_database: &'a str,
     pub username: &'a str,
     pub password: &'a str,
+    pub client_name: &'a str,
+    /// Major, minor and patch version of the client, the patch is only sent with queries
+    pub client_version: (u64, u64, u64),
 }
 
 #[repr(u8)]
@@ -64,6 +71,10 @@ impl<'a> ClientInfo<'a> {
         to.write_string(self.initial_user).await?;
         to.write_string(self.initial_query_id).await?;
         to.write_string(self.initial_address).await?;
+        if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME {
+            // filled in by the server for initial queries
+            to.write_u64_le(0).await?;
+        }
         to.write_u8(1).await?;
         to.write_string(self.os_user).await?;
         to.write_string(self.client_hostname).await?;
@@ -91,6 +102,12 @@ impl<'a> ClientInfo<'a> {
                 to.write_u8(0u8).await?;
             }
         }
+        if revision >= DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS {
+            // collaborate_with_initiator, count_participating_replicas, number_of_current_replica
+            to.write_var_uint(0).await?;
+            to.write_var_uint(0).await?;
+            to.write_var_uint(0).await?;
+        }
 
         Ok(())
     }
@@ -113,14 +130,28 @@ pub enum QueryProcessingStage {
     WithMergableStateAfterAggregation,
 }
 
+/// A setting sent with a query, overriding the session's value for that query only.
+pub struct QuerySetting<'a> {
+    pub name: &'a str,
+    /// The value as it would be written in a `SETTINGS` clause, with strings unquoted
+    pub value: &'a str,
+    /// If `true`, the server fails the query if it doesn't know the setting, rather than ignoring it
+    pub important: bool,
+}
+
+const SETTING_FLAG_IMPORTANT: u64 = 0x01;
+const SETTING_FLAG_CUSTOM: u64 = 0x02;
+
 pub struct Query<'a> {
     pub id: &'a str,
     pub info: ClientInfo<'a>,
-    // pub settings: (), //TODO
+    pub settings: &'a [QuerySetting<'a>],
     //todo: interserver secret
     pub stage: QueryProcessingStage,
     pub compression: CompressionMethod,
     pub query: &'a str,
+    /// Names and values of the `{name:Type}` placeholders in `query`, see [`ParsedQuery`](crate::ParsedQuery).
+    pub parameters: &'a [(String, String)],
     //todo: data
 }
 
@@ -144,7 +175,27 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
                 .write(&mut self.writer, self.server_hello.revision_version)
                 .await?;
         }
-        //todo: settings
+        if !params.settings.is_empty() {
+            if self.server_hello.revision_version
+                < DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS
+            {
+                return Err(KlickhouseError::ProtocolError(
+                    "server is too old to receive settings with a query".to_string(),
+                ));
+            }
+            for setting in params.settings {
+                self.writer.write_string(setting.name).await?;
+                self.writer
+                    .write_var_uint(if setting.important {
+                        SETTING_FLAG_IMPORTANT
+                    } else {
+                        0
+                    })
+                    .await?;
+                self.writer.write_string(setting.value).await?;
+            }
+        }
+        // end of settings
         self.writer.write_string("").await?;
         if self.server_hello.revision_version >= DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET {
             //todo interserver secret
@@ -159,6 +210,32 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
             })
             .await?;
         self.writer.write_string(params.query).await?;
+        if !params.parameters.is_empty()
+            && self.server_hello.revision_version < DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS
+        {
+            return Err(KlickhouseError::ProtocolError(
+                "server is too old to receive parameters with a query".to_string(),
+            ));
+        }
+        if self.server_hello.revision_version >= DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS {
+            for (name, value) in params.parameters {
+                self.writer.write_string(name).await?;
+                self.writer.write_var_uint(SETTING_FLAG_CUSTOM).await?;
+                // custom settings are sent quoted, and the server unquotes them before parsing the parameter
+                let mut quoted = String::with_capacity(value.len() + 2);
+                quoted.push('\'');
+                for c in value.chars() {
+                    if c == '\\' || c == '\'' {
+                        quoted.push('\\');
+                    }
+                    quoted.push(c);
+                }
+                quoted.push('\'');
+                self.writer.write_string(&quoted).await?;
+            }
+            // end of parameters
+            self.writer.write_string("").await?;
+        }
 
         self.writer.flush().await?;
         Ok(())
@@ -166,25 +243,14 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
 
     #[cfg(feature = "compression")]
     async fn compress_data(&mut self, byte: u8, block: Block) -> Result<()> {
-        let (out, decompressed_size) =
-            crate::compression::compress_block(block, self.server_hello.revision_version).await?;
-        let mut new_out = Vec::with_capacity(out.len() + 5);
-        new_out.push(byte);
-        new_out.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
-        new_out.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
-        new_out.extend(out);
-
-        let hash = cityhash_rs::cityhash_102_128(&new_out[..]);
-        self.writer.write_u64_le((hash >> 64) as u64).await?;
-        self.writer.write_u64_le(hash as u64).await?;
-        // self.writer.write_u8(byte).await?;
-        // self.writer.write_u32_le(out.len() as u32).await?;
-        self.writer.write_all(&new_out[..]).await?;
+        let frame =
+            crate::compression::compress_frame(byte, block, self.server_hello.revision_version)?;
+        self.writer.write_all(&frame[..]).await?;
         Ok(())
     }
 
     #[cfg(not(feature = "compression"))]
-    async fn compress_data(&mut self, _byte: u8, _block: &Block) -> Result<()> {
+    async fn compress_data(&mut self, _byte: u8, _block: Block) -> Result<()> {
         panic!("attempted to use compression when not compiled with `compression` feature in klickhouse");
     }
 
@@ -222,19 +288,27 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
         Ok(())
     }
 
+    /// Sends a data packet whose block was already compressed by [`crate::compression::compress_frame`].
+    #[cfg(feature = "compression")]
+    pub async fn send_compressed_data(&mut self, frame: &[u8], name: &str) -> Result<()> {
+        self.writer
+            .write_var_uint(protocol::ClientPacketId::Data as u64)
+            .await?;
+        self.writer.write_string(name).await?;
+        self.writer.write_all(frame).await?;
+        self.writer.flush().await?;
+
+        Ok(())
+    }
+
     #[allow(clippy::needless_lifetimes)]
     pub async fn send_hello<'a>(&mut self, params: ClientHello<'a>) -> Result<()> {
         self.writer
             .write_var_uint(protocol::ClientPacketId::Hello as u64)
             .await?;
-        self.writer
-            .write_string(&format!(
-                "ClickHouse Rust-Klickhouse {}",
-                env!("CARGO_PKG_VERSION")
-            ))
-            .await?;
-        self.writer.write_var_uint(crate::VERSION_MAJOR).await?;
-        self.writer.write_var_uint(crate::VERSION_MINOR).await?;
+        self.writer.write_string(params.client_name).await?;
+        self.writer.write_var_uint(params.client_version.0).await?;
+        self.writer.write_var_uint(params.client_version.1).await?;
         self.writer
             .write_var_uint(protocol::DBMS_TCP_PROTOCOL_VERSION)
             .await?;
@@ -245,8 +319,132 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
         Ok(())
     }
 
-    // pub async fn send_ping(&mut self) -> Result<()> {
-    //     self.writer.write_var_uint(protocol::ClientPacketId::Ping as u64).await?;
-    //     Ok(())
-    // }
+    /// Answers a read task request, where an empty `task` means there are no more tasks to hand out.
+    /// Sent after the handshake to servers that negotiated [`DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM`].
+    pub async fn send_addendum(&mut self, quota_key: &str) -> Result<()> {
+        if self.server_hello.revision_version >= DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM {
+            self.writer.write_string(quota_key).await?;
+            self.writer.flush().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn send_read_task_response(&mut self, task: &str) -> Result<()> {
+        self.writer
+            .write_var_uint(protocol::ClientPacketId::ReadTaskResponse as u64)
+            .await?;
+        self.writer
+            .write_var_uint(protocol::DBMS_CLUSTER_PROCESSING_PROTOCOL_VERSION)
+            .await?;
+        self.writer.write_string(task).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Asks the server to stop executing the current query. The server still ends it with an end of stream or exception packet.
+    pub async fn send_cancel(&mut self) -> Result<()> {
+        self.writer
+            .write_var_uint(protocol::ClientPacketId::Cancel as u64)
+            .await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Asks the server for a pong, which it only answers between queries.
+    pub async fn send_ping(&mut self) -> Result<()> {
+        self.writer
+            .write_var_uint(protocol::ClientPacketId::Ping as u64)
+            .await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_task_response() {
+        let mut out = InternalClientOut::new(Vec::new());
+        out.send_read_task_response("").await.unwrap();
+        assert_eq!(out.writer, vec![9, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_query_settings() {
+        let query = |settings, parameters| Query {
+            id: "q",
+            info: ClientInfo {
+                kind: QueryKind::NoQuery,
+                initial_user: "",
+                initial_query_id: "",
+                initial_address: "",
+                os_user: "",
+                client_hostname: "",
+                client_name: "",
+                client_version_major: 0,
+                client_version_minor: 0,
+                client_tcp_protocol_version: 0,
+                quota_key: "",
+                distributed_depth: 0,
+                client_version_patch: 0,
+                open_telemetry: None,
+            },
+            settings,
+            stage: QueryProcessingStage::Complete,
+            compression: CompressionMethod::None,
+            query: "SELECT 1",
+            parameters,
+        };
+        let settings = [QuerySetting {
+            name: "final",
+            value: "1",
+            important: true,
+        }];
+
+        let mut out = InternalClientOut::new(Vec::new());
+        out.server_hello.revision_version = DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET;
+        out.send_query(query(&settings, &[])).await.unwrap();
+        let mut expected = vec![1, 1, b'q', 0, 5];
+        expected.extend_from_slice(b"final");
+        expected.extend_from_slice(&[1, 1, b'1', 0, 0, 2, 0, 8]);
+        expected.extend_from_slice(b"SELECT 1");
+        assert_eq!(out.writer, expected);
+
+        let mut out = InternalClientOut::new(Vec::new());
+        out.server_hello.revision_version =
+            DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS - 1;
+        assert!(out.send_query(query(&settings, &[])).await.is_err());
+        out.send_query(query(&[], &[])).await.unwrap();
+
+        let parameters = [("x".to_string(), "a'\\".to_string())];
+        let mut out = InternalClientOut::new(Vec::new());
+        out.server_hello.revision_version = DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS;
+        out.send_query(query(&[], &parameters)).await.unwrap();
+        let mut expected = vec![1, 1, b'q', 0, 0, 0, 2, 0, 8];
+        expected.extend_from_slice(b"SELECT 1");
+        expected.extend_from_slice(&[1, b'x', 2, 7]);
+        expected.extend_from_slice(b"'a\\'\\\\'");
+        expected.push(0);
+        assert_eq!(out.writer, expected);
+
+        let mut out = InternalClientOut::new(Vec::new());
+        out.server_hello.revision_version = DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS - 1;
+        assert!(out.send_query(query(&[], &parameters)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let mut out = InternalClientOut::new(Vec::new());
+        out.send_cancel().await.unwrap();
+        assert_eq!(out.writer, vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_ping() {
+        let mut out = InternalClientOut::new(Vec::new());
+        out.send_ping().await.unwrap();
+        assert_eq!(out.writer, vec![4]);
+    }
 }
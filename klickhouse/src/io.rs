@@ -1,3 +1,8 @@
+//! Wire-level IO traits used by the native protocol.
+//!
+//! Both traits are blanket-implemented for every compatible tokio [`AsyncRead`]/[`AsyncWrite`], so any transport (QUIC streams, in-memory pipes, instrumented wrappers)
+//! can be handed to `Client::connect_transport` or used to encode/decode protocol primitives directly.
+
 use std::future::Future;
 
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
@@ -6,11 +11,15 @@ use crate::{KlickhouseError, Result};
 
 use crate::protocol::MAX_STRING_SIZE;
 
+/// Reads Clickhouse protocol primitives. Implemented for every `AsyncRead + Unpin + Send + Sync`.
 pub trait ClickhouseRead: AsyncRead + Unpin + Send + Sync {
+    /// Reads a LEB128 variable-length unsigned integer.
     fn read_var_uint(&mut self) -> impl Future<Output = Result<u64>> + Send;
 
+    /// Reads a length-prefixed byte string.
     fn read_string(&mut self) -> impl Future<Output = Result<Vec<u8>>> + Send;
 
+    /// Reads a length-prefixed string, failing if it is not valid UTF-8.
     fn read_utf8_string(&mut self) -> impl Future<Output = Result<String>> + Send {
         async { Ok(String::from_utf8(self.read_string().await?)?) }
     }
@@ -41,19 +50,35 @@ impl<T: AsyncRead + Unpin + Send + Sync> ClickhouseRead for T {
         if len == 0 {
             return Ok(vec![]);
         }
-        let mut buf = Vec::with_capacity(len as usize);
+        read_bytes(self, len as usize).await
+    }
+}
 
-        let buf_mut = unsafe { std::slice::from_raw_parts_mut(buf.as_mut_ptr(), len as usize) };
-        self.read_exact(buf_mut).await?;
-        unsafe { buf.set_len(len as usize) };
+/// Bytes preallocated for a length read off the wire. Anything beyond is allocated as data actually arrives,
+/// so a corrupt length fails on a short read instead of allocating up front.
+const MAX_PREALLOCATION: usize = 1 << 16;
 
-        Ok(buf)
+/// Reads exactly `len` bytes.
+pub(crate) async fn read_bytes<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    len: usize,
+) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(len.min(MAX_PREALLOCATION));
+    reader.take(len as u64).read_to_end(&mut out).await?;
+    if out.len() != len {
+        return Err(KlickhouseError::Io(
+            std::io::ErrorKind::UnexpectedEof.into(),
+        ));
     }
+    Ok(out)
 }
 
+/// Writes Clickhouse protocol primitives. Implemented for every `AsyncWrite + Unpin + Send + Sync + 'static`.
 pub trait ClickhouseWrite: AsyncWrite + Unpin + Send + Sync + 'static {
+    /// Writes a LEB128 variable-length unsigned integer.
     fn write_var_uint(&mut self, value: u64) -> impl Future<Output = Result<()>> + Send;
 
+    /// Writes a length-prefixed byte string.
     fn write_string(
         &mut self,
         value: impl AsRef<[u8]> + Send,
@@ -83,3 +108,22 @@ impl<T: AsyncWrite + Unpin + Send + Sync + 'static> ClickhouseWrite for T {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_duplex_roundtrip() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        let writer = tokio::spawn(async move {
+            client.write_var_uint(300).await.unwrap();
+            client.write_string("klickhouse").await.unwrap();
+            client.write_var_uint(1 << 62).await.unwrap();
+        });
+        assert_eq!(server.read_var_uint().await.unwrap(), 300);
+        assert_eq!(server.read_utf8_string().await.unwrap(), "klickhouse");
+        assert_eq!(server.read_var_uint().await.unwrap(), 1 << 62);
+        writer.await.unwrap();
+    }
+}
@@ -1,31 +1,121 @@
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
 
-/// Clickhouse major version
+/// Clickhouse major version the client was modeled after. The version reported to the server is [`ClientOptions::client_version`].
 pub const VERSION_MAJOR: u64 = 22;
-/// Clickhouse minor version
+/// Clickhouse minor version the client was modeled after. The version reported to the server is [`ClientOptions::client_version`].
 pub const VERSION_MINOR: u64 = 9;
 
+#[cfg(feature = "client")]
+mod admission;
+#[cfg(feature = "client")]
+mod backfill;
+#[cfg(feature = "client")]
+pub use backfill::{Backfill, BackfillChunk};
+#[cfg(feature = "client")]
+mod blob;
+#[cfg(feature = "client")]
+pub use blob::{assemble_blobs, split_blob, BlobChunk, DEFAULT_BLOB_CHUNK_SIZE};
 pub mod block;
+#[cfg(feature = "client")]
 mod client;
 #[cfg(feature = "compression")]
 mod compression;
+#[cfg(feature = "client")]
+mod connection_url;
+#[cfg(feature = "client")]
+pub use connection_url::ConnectionUrl;
 mod convert;
+mod ddl;
+pub use ddl::{ColumnDef, ColumnDefault, CreateTableBuilder};
+#[cfg(feature = "client")]
+mod dictionary;
+#[cfg(feature = "client")]
+pub use dictionary::{DictionaryInfo, DictionaryStatus};
+#[cfg(all(feature = "client", feature = "serde"))]
+mod explain;
+#[cfg(all(feature = "client", feature = "serde"))]
+pub use explain::{
+    Explain, ExplainEstimate, ExplainIndex, ExplainKind, ExplainPipelineNode, ExplainPlanNode,
+};
 /// Error generator functions used by `klickhouse_derive`
 mod errors;
+pub mod helpers;
+#[cfg(feature = "client")]
+mod input;
+#[cfg(feature = "client")]
 mod internal_client_in;
+#[cfg(feature = "client")]
 mod internal_client_out;
-mod io;
+pub mod io;
+pub use io::{ClickhouseRead, ClickhouseWrite};
 #[cfg(feature = "bb8")]
 mod manager;
 #[cfg(feature = "refinery")]
 mod migrate;
 #[cfg(feature = "refinery")]
 pub use migrate::*;
+#[cfg(feature = "client")]
+mod pool;
+#[cfg(feature = "client")]
+pub use pool::{Pool, PoolOptions, PooledClient};
 mod progress;
 pub use progress::*;
 mod protocol;
+pub use protocol::{CompressionMethod, MAX_STRING_SIZE};
 mod query;
+#[cfg(feature = "client")]
+mod query_audit;
+#[cfg(feature = "client")]
+pub use query_audit::{AuditedQuery, QueryAuditHook};
+#[cfg(feature = "client")]
+mod query_options;
+#[cfg(feature = "client")]
+pub use query_options::{AsyncInsertWait, QueryOptions, QueryPriority, Utf8Policy};
+#[cfg(feature = "client")]
+mod query_sink;
+#[cfg(feature = "client")]
+pub use query_sink::SlowConsumerPolicy;
+#[cfg(feature = "client")]
+mod events;
+#[cfg(feature = "client")]
+pub use events::ClientEvent;
+#[cfg(feature = "client")]
+mod schema_cache;
+#[cfg(feature = "client")]
+mod script;
+#[cfg(feature = "client")]
+pub use schema_cache::{ColumnMismatch, SchemaDiff, TableSchema};
+#[cfg(feature = "client")]
+pub use script::{ResultStream, ScriptErrorPolicy, StatementResult};
+#[cfg(feature = "bb8")]
+mod sharded_insert;
+#[cfg(feature = "bb8")]
+pub use sharded_insert::ShardedInserter;
+#[cfg(feature = "client")]
+mod slow_query;
+#[cfg(feature = "transactions")]
+mod transaction;
+#[cfg(feature = "client")]
+pub use slow_query::{SlowQuery, SlowQueryHook};
+#[cfg(feature = "client")]
+mod temp_table;
+#[cfg(feature = "client")]
+pub use temp_table::TemporaryTable;
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(feature = "rustls-native-certs")]
+pub use tls::native_root_store;
+#[cfg(feature = "tls")]
+pub use tls::{root_store, tls_connector, TlsVerification};
+#[cfg(feature = "transactions")]
+pub use transaction::Transaction;
+#[cfg(feature = "client")]
+mod upsert;
+#[cfg(feature = "client")]
+pub use upsert::UpsertMerge;
 pub mod query_parser;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 mod types;
 mod values;
 pub use query::*;
@@ -58,19 +148,56 @@ pub use indexmap::IndexMap;
 /// - `skip_deserializing`, `skip_serializing`
 /// - `flatten`
 ///    - Index-based matching is disabled (the column names must match exactly).
-///    - Due to the current interface of the [Row] trait, performance might not be optimal, as a value map must be reconstitued for each flattened subfield.
+///    - Structs with flattened fields are slower to read: they collect each row for [Row::deserialize_row] and reconstitute a value map for each flattened subfield,
+///      while other structs read the columns in place through [Row::deserialize_accessor].
 ///
 /// ## Clickhouse-specific attributes
 /// - The `nested` attribute allows handling [Clickhouse nested data structures](https://clickhouse.com/docs/en/sql-reference/data-types/nested-data-structures/nested). See an example in the `tests` folder.
+///   Columns are named `field.column` by default. `nested_separator = "_"` changes the separator, and `nested_rename_all = "..."` applies a
+///   `rename_all` rule to the column names of the nested struct, i.e. for `nest_subField` columns of a table without `Nested` types.
+/// - The `sign` attribute marks the `Int8` sign column of a `CollapsingMergeTree` table, and derives [CollapsingRow] to generate cancel and state rows.
+///   The field can be of any type implementing `From<i8>`.
+/// - The `parse` attribute reads a `String` column into any [`FromStr`](std::str::FromStr) field type and writes it back with [`Display`](std::fmt::Display), see [`helpers::parse`].
+/// - The `datetime_overflow` attribute (`"error"`, `"clamp"` or `"null"`) sets the [`DateTimeOverflow`] policy for writing a `chrono::DateTime` field,
+///   or an `Option` of one, that may be out of range of its column.
 ///
 /// ## Known issues
 /// - For serialization, the ordering of fields in the struct declaration must match the order in the `INSERT` statement, respectively in the table declaration. See issue [#34](https://github.com/Protryon/klickhouse/issues/34).
+///   [`Client::insert`] lists the columns of the struct in its `INSERT` statement, so the order doesn't matter.
 pub use klickhouse_derive::Row;
 
+#[cfg(feature = "derive")]
+/// Derive macro implementing [FromSql] and [ToSql] for a Rust enum with unit variants, mapping it to an `Enum8` or `Enum16` column by variant name.
+///
+/// Variants are matched against the names of the column's enum entries, so their numeric values may differ from the Rust discriminants.
+/// Names are renamed with `#[klickhouse(rename = "...")]` on variants, or `#[klickhouse(rename_all = "...")]` on the enum.
+///
+/// With `#[klickhouse(enum_string)]` on the enum, `String`, `LowCardinality(String)` and `FixedString` columns are accepted too,
+/// holding the variant names as text. Unknown names fail to deserialize.
+/// ```
+/// # use klickhouse::{ClickhouseEnum, FromSql, ToSql, Type, Value};
+/// #[derive(ClickhouseEnum, Debug, PartialEq)]
+/// #[klickhouse(rename_all = "snake_case")]
+/// enum Status {
+///     Active,
+///     SoftDeleted,
+///     #[klickhouse(rename = "gone")]
+///     Purged,
+/// }
+///
+/// let type_: Type = "Enum8('active' = 1, 'soft_deleted' = 2, 'gone' = -1)".parse().unwrap();
+/// assert_eq!(Status::from_sql(&type_, Value::Enum8(2)).unwrap(), Status::SoftDeleted);
+/// assert_eq!(Status::Purged.to_sql(Some(&type_)).unwrap(), Value::Enum8(-1));
+/// ```
+pub use klickhouse_derive::ClickhouseEnum;
+
+#[cfg(feature = "client")]
 pub use client::*;
 pub use convert::*;
 pub use errors::*;
 pub use types::{Type, Tz};
 pub use values::*;
+#[cfg(feature = "client")]
 mod lock;
+#[cfg(feature = "client")]
 pub use lock::ClickhouseLock;
@@ -2,9 +2,13 @@ use std::time::Duration;
 
 use log::error;
 
-use crate::{Client, KlickhouseError};
+use crate::{Client, KlickhouseError, UnitValue};
 
 /// A hack implementation of a global lock for things like migrations
+///
+/// The lock has an exclusive (write) mode, taken with [`ClickhouseLock::lock`], and a shared (read) mode, taken with [`ClickhouseLock::read_lock`].
+/// Any number of readers can hold the lock at once, while a writer excludes readers and other writers.
+/// A waiting writer blocks new readers, so writers are not starved by overlapping readers.
 #[derive(Clone)]
 pub struct ClickhouseLock {
     name: String,
@@ -15,6 +19,8 @@ pub struct ClickhouseLock {
 /// A handle, that when dropped, attempts to unlock the owning lock.
 pub struct ClickhouseLockHandle<'a> {
     lock: Option<&'a ClickhouseLock>,
+    /// Table marking this holder
+    table: String,
 }
 
 impl ClickhouseLock {
@@ -32,75 +38,151 @@ impl ClickhouseLock {
         self
     }
 
-    /// Attempts to lock this table a single time.
-    pub async fn try_lock(&self) -> Result<Option<ClickhouseLockHandle<'_>>, KlickhouseError> {
+    fn writer_table(&self) -> String {
+        format!("_lock_{}", self.name)
+    }
+
+    fn reader_prefix(&self) -> String {
+        format!("_lock_{}_read_", self.name)
+    }
+
+    /// Creates a lock table, returning `false` if it already exists.
+    async fn create_table(&self, table: &str) -> Result<bool, KlickhouseError> {
         let query = format!(
-            "CREATE TABLE _lock_{}{} (i Int64)ENGINE=Null",
-            self.name, self.cluster_str
+            "CREATE TABLE {table}{} (i Int64)ENGINE=Null",
+            self.cluster_str
         );
 
         match self.client.execute(&query).await {
-            Ok(()) => (),
+            Ok(()) => Ok(true),
             Err(e) => {
                 let error = e.to_string();
                 if error.contains("already exists") {
-                    return Ok(None);
+                    Ok(false)
                 } else {
-                    return Err(e);
+                    Err(e)
                 }
             }
         }
+    }
 
-        Ok(Some(ClickhouseLockHandle { lock: Some(self) }))
+    async fn drop_table(&self, table: &str) -> Result<(), KlickhouseError> {
+        self.client
+            .execute(format!(
+                "DROP TABLE IF EXISTS {table}{} SYNC",
+                self.cluster_str
+            ))
+            .await
+    }
+
+    async fn writer_exists(&self) -> Result<bool, KlickhouseError> {
+        let count = self
+            .client
+            .query_one::<UnitValue<u64>>(format!(
+                "SELECT count() FROM system.tables WHERE database = currentDatabase() AND name = '{}'",
+                self.writer_table()
+            ))
+            .await?;
+        Ok(count.0 > 0)
+    }
+
+    async fn reader_tables(&self) -> Result<Vec<String>, KlickhouseError> {
+        Ok(self
+            .client
+            .query_collect::<UnitValue<String>>(format!(
+                "SELECT name FROM system.tables WHERE database = currentDatabase() AND startsWith(name, '{}')",
+                self.reader_prefix()
+            ))
+            .await?
+            .into_iter()
+            .map(|x| x.0)
+            .collect())
+    }
+
+    /// Attempts to lock this table a single time.
+    pub async fn try_lock(&self) -> Result<Option<ClickhouseLockHandle<'_>>, KlickhouseError> {
+        if !self.create_table(&self.writer_table()).await? {
+            return Ok(None);
+        }
+        let handle = ClickhouseLockHandle {
+            lock: Some(self),
+            table: self.writer_table(),
+        };
+        if !self.reader_tables().await?.is_empty() {
+            handle.unlock().await?;
+            return Ok(None);
+        }
+
+        Ok(Some(handle))
     }
 
     /// Attempts to lock this table.
     pub async fn lock(&self) -> Result<ClickhouseLockHandle<'_>, KlickhouseError> {
-        let query = format!(
-            "CREATE TABLE _lock_{}{} (i Int64)ENGINE=Null",
-            self.name, self.cluster_str
-        );
+        while !self.create_table(&self.writer_table()).await? {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        // dropping the handle releases the lock if this future is cancelled while waiting for readers
+        let handle = ClickhouseLockHandle {
+            lock: Some(self),
+            table: self.writer_table(),
+        };
+        while !self.reader_tables().await?.is_empty() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        Ok(handle)
+    }
+
+    /// Attempts to lock this table in shared mode a single time.
+    pub async fn try_read_lock(&self) -> Result<Option<ClickhouseLockHandle<'_>>, KlickhouseError> {
+        let table = format!("{}{}", self.reader_prefix(), uuid::Uuid::new_v4().simple());
+        self.create_table(&table).await?;
+        let handle = ClickhouseLockHandle {
+            lock: Some(self),
+            table,
+        };
+        // a writer creates its table before checking for readers, so one of us always sees the other
+        if self.writer_exists().await? {
+            handle.unlock().await?;
+            return Ok(None);
+        }
+
+        Ok(Some(handle))
+    }
 
+    /// Attempts to lock this table in shared mode.
+    pub async fn read_lock(&self) -> Result<ClickhouseLockHandle<'_>, KlickhouseError> {
         loop {
-            match self.client.execute(&query).await {
-                Ok(()) => break,
-                Err(e) => {
-                    let error = e.to_string();
-                    if error.contains("already exists") {
-                        tokio::time::sleep(Duration::from_millis(100)).await;
-                    } else {
-                        return Err(e);
-                    }
-                }
+            if let Some(handle) = self.try_read_lock().await? {
+                return Ok(handle);
             }
+            tokio::time::sleep(Duration::from_millis(100)).await;
         }
-
-        Ok(ClickhouseLockHandle { lock: Some(self) })
     }
 
-    /// Resets this lock, forcefully unlocking it
+    /// Resets this lock, forcefully unlocking it for the writer and all readers
     pub async fn reset(&self) -> Result<(), KlickhouseError> {
-        self.client
-            .execute(format!(
-                "DROP TABLE IF EXISTS _lock_{}{} SYNC",
-                self.name, self.cluster_str
-            ))
-            .await
+        for table in self.reader_tables().await? {
+            self.drop_table(&table).await?;
+        }
+        self.drop_table(&self.writer_table()).await
     }
 }
 
 impl<'a> ClickhouseLockHandle<'a> {
     /// Unlocks this handle (without spawning a tokio task)
     pub async fn unlock(mut self) -> Result<(), KlickhouseError> {
-        self.lock.take().unwrap().reset().await
+        let table = std::mem::take(&mut self.table);
+        self.lock.take().unwrap().drop_table(&table).await
     }
 }
 
 impl<'a> Drop for ClickhouseLockHandle<'a> {
     fn drop(&mut self) {
         if let Some(lock) = self.lock.take().cloned() {
+            let table = std::mem::take(&mut self.table);
             tokio::spawn(async move {
-                if let Err(e) = lock.reset().await {
+                if let Err(e) = lock.drop_table(&table).await {
                     error!("failed to reset lock: {}: {e:?}", lock.name);
                 }
             });
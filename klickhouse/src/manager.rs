@@ -1,31 +1,84 @@
-use std::net::SocketAddr;
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 use tokio::net::ToSocketAddrs;
 
 use crate::{convert::UnitValue, Client, ClientOptions, KlickhouseError};
 
+#[derive(Clone)]
+enum Destination {
+    /// Resolved once, when the manager was created.
+    Resolved(Vec<SocketAddr>),
+    /// Resolved again for every connection.
+    Hostname(String),
+}
+
 #[derive(Clone)]
 pub struct ConnectionManager {
-    destination: Vec<SocketAddr>,
+    destination: Destination,
     options: ClientOptions,
     prequel: Option<String>,
+    rotate: bool,
+    next_address: Arc<AtomicUsize>,
 }
 
 impl ConnectionManager {
+    /// Resolves `destination` once. Use [`ConnectionManager::with_hostname`] to pick up DNS changes.
     pub async fn new<A: ToSocketAddrs>(
         destination: A,
         options: ClientOptions,
     ) -> std::io::Result<Self> {
-        Ok(Self {
-            destination: tokio::net::lookup_host(destination).await?.collect(),
+        Ok(Self::with_destination(
+            Destination::Resolved(tokio::net::lookup_host(destination).await?.collect()),
+            options,
+        ))
+    }
+
+    /// Resolves `destination` (i.e. `clickhouse.default.svc.cluster.local:9000`) again for every new connection,
+    /// so DNS changes (i.e. Kubernetes services being rescheduled) are picked up without restarting the process.
+    pub fn with_hostname(destination: impl Into<String>, options: ClientOptions) -> Self {
+        Self::with_destination(Destination::Hostname(destination.into()), options)
+    }
+
+    fn with_destination(destination: Destination, options: ClientOptions) -> Self {
+        Self {
+            destination,
             options,
             prequel: None,
-        })
+            rotate: false,
+            next_address: Arc::new(AtomicUsize::new(0)),
+        }
     }
 
     pub fn with_prequel(mut self, prequel: impl Into<String>) -> Self {
         self.prequel = Some(prequel.into());
         self
     }
+
+    /// If `true`, each new connection starts at the next resolved address instead of the first, spreading connections
+    /// over all A/AAAA records. In both cases, the remaining addresses are tried in order if connecting fails.
+    pub fn with_rotation(mut self, rotate: bool) -> Self {
+        self.rotate = rotate;
+        self
+    }
+
+    async fn addresses(&self) -> std::io::Result<Vec<SocketAddr>> {
+        let mut addresses = match &self.destination {
+            Destination::Resolved(addresses) => addresses.clone(),
+            Destination::Hostname(hostname) => {
+                tokio::net::lookup_host(hostname.as_str()).await?.collect()
+            }
+        };
+        if self.rotate && !addresses.is_empty() {
+            let start = self.next_address.fetch_add(1, Ordering::Relaxed) % addresses.len();
+            addresses.rotate_left(start);
+        }
+        Ok(addresses)
+    }
 }
 
 #[async_trait::async_trait]
@@ -34,7 +87,8 @@ impl bb8::ManageConnection for ConnectionManager {
     type Error = KlickhouseError;
 
     async fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        let client = Client::connect(&self.destination[..], self.options.clone()).await?;
+        let addresses = self.addresses().await?;
+        let client = Client::connect(&addresses[..], self.options.clone()).await?;
         if let Some(prequel) = &self.prequel {
             client.execute(prequel).await?;
         }
@@ -50,3 +104,36 @@ impl bb8::ManageConnection for ConnectionManager {
         conn.is_closed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rotation() {
+        let addresses: Vec<SocketAddr> = vec![
+            "10.0.0.1:9000".parse().unwrap(),
+            "10.0.0.2:9000".parse().unwrap(),
+        ];
+        let manager = ConnectionManager::with_destination(
+            Destination::Resolved(addresses.clone()),
+            ClientOptions::default(),
+        );
+        assert_eq!(manager.addresses().await.unwrap(), addresses);
+        assert_eq!(manager.addresses().await.unwrap(), addresses);
+
+        let manager = manager.with_rotation(true);
+        assert_eq!(manager.addresses().await.unwrap(), addresses);
+        assert_eq!(
+            manager.addresses().await.unwrap(),
+            vec![addresses[1], addresses[0]]
+        );
+        assert_eq!(manager.addresses().await.unwrap(), addresses);
+
+        let manager = ConnectionManager::with_hostname("127.0.0.1:9000", ClientOptions::default());
+        assert_eq!(
+            manager.addresses().await.unwrap(),
+            vec!["127.0.0.1:9000".parse::<SocketAddr>().unwrap()]
+        );
+    }
+}
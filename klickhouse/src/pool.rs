@@ -0,0 +1,283 @@
+use std::{
+    ops::Deref,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use futures_util::{
+    future::{join_all, BoxFuture},
+    FutureExt,
+};
+use log::{debug, warn};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::{timeout, Instant},
+};
+
+use crate::{Client, ClientEvent, ClientOptions, KlickhouseError, ParsedQuery, Result, Row};
+
+/// Settings of a [`Pool`].
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    /// Maximum number of connections, idle or in use. Default 10.
+    pub max_size: usize,
+    /// Idle connections unused for this long are closed. Default 10 minutes, `None` keeps them open.
+    pub idle_timeout: Option<Duration>,
+    /// Idle connections are pinged this often, and closed if the server doesn't answer within `timeout`. Default 30 seconds, `None` disables pings.
+    pub ping_interval: Option<Duration>,
+    /// How long connecting or pinging may take before the connection counts as broken. Default 10 seconds.
+    pub timeout: Duration,
+    /// How often [`Pool::retry`] runs an operation again after the connection it used broke. Default 1.
+    pub retries: usize,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        PoolOptions {
+            max_size: 10,
+            idle_timeout: Some(Duration::from_secs(600)),
+            ping_interval: Some(Duration::from_secs(30)),
+            timeout: Duration::from_secs(10),
+            retries: 1,
+        }
+    }
+}
+
+struct IdleClient {
+    client: Client,
+    since: Instant,
+}
+
+struct PoolInner {
+    destination: String,
+    client_options: ClientOptions,
+    options: PoolOptions,
+    idle: Mutex<Vec<IdleClient>>,
+    permits: Arc<Semaphore>,
+}
+
+impl PoolInner {
+    fn is_expired(&self, idle: &IdleClient) -> bool {
+        idle.client.is_closed()
+            || self
+                .options
+                .idle_timeout
+                .is_some_and(|x| idle.since.elapsed() >= x)
+    }
+
+    /// Takes the most recently used idle connection that is still open.
+    fn take_idle(&self) -> Option<Client> {
+        let mut idle = self.idle.lock().unwrap();
+        while let Some(client) = idle.pop() {
+            if !self.is_expired(&client) {
+                return Some(client.client);
+            }
+        }
+        None
+    }
+
+    async fn connect(&self) -> Result<Client> {
+        timeout(
+            self.options.timeout,
+            Client::connect(self.destination.as_str(), self.client_options.clone()),
+        )
+        .await
+        .map_err(|_| {
+            KlickhouseError::Io(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("timed out connecting to {}", self.destination),
+            ))
+        })?
+    }
+
+    /// Pings all idle connections at once, closing those that don't answer or have been idle for too long.
+    /// They stay available to [`Pool::get`] meanwhile, so the pool never opens connections in their place.
+    async fn check_idle(&self) {
+        let clients = {
+            let mut idle = self.idle.lock().unwrap();
+            idle.retain(|x| !self.is_expired(x));
+            idle.iter().map(|x| x.client.clone()).collect::<Vec<_>>()
+        };
+        let broken = join_all(clients.into_iter().map(|client| async move {
+            match timeout(self.options.timeout, client.ping()).await {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => {
+                    debug!("closing pooled clickhouse connection: {e}");
+                    Some(client)
+                }
+                Err(_) => {
+                    debug!("closing pooled clickhouse connection: ping timed out");
+                    Some(client)
+                }
+            }
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        // connections taken meanwhile are discarded when returned if they are closed
+        self.idle.lock().unwrap().retain(|x| {
+            !broken
+                .iter()
+                .any(|client| client.same_connection(&x.client))
+        });
+    }
+}
+
+/// Pings idle connections every `interval`, until the pool is dropped.
+async fn keepalive(pool: Weak<PoolInner>, interval: Duration) {
+    let mut ticks = tokio::time::interval(interval);
+    ticks.tick().await;
+    loop {
+        ticks.tick().await;
+        let Some(pool) = pool.upgrade() else {
+            return;
+        };
+        pool.check_idle().await;
+    }
+}
+
+/// A pool of connections to one Clickhouse server, which are opened on demand and reused.
+///
+/// Connections that broke are discarded when they are returned, and idle connections are pinged in the background to find the ones that
+/// broke while unused. Use [`Pool::retry`] to transparently run idempotent queries again on a new connection.
+/// For a pool with more knobs, see [`ConnectionManager`](crate::ConnectionManager) for `bb8` (feature `bb8`).
+///
+/// ```no_run
+/// # async fn run() -> klickhouse::Result<()> {
+/// use klickhouse::{ClientOptions, Pool, PoolOptions};
+///
+/// let pool = Pool::new("127.0.0.1:9000", ClientOptions::default(), PoolOptions::default());
+/// let client = pool.get().await?;
+/// client.execute("SELECT 1").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<PoolInner>,
+}
+
+impl Pool {
+    /// Creates an empty pool for `destination` (i.e. `clickhouse:9000`), which is resolved again for every new connection.
+    /// Must be called within a Tokio runtime if `options.ping_interval` is set.
+    pub fn new(
+        destination: impl Into<String>,
+        client_options: ClientOptions,
+        options: PoolOptions,
+    ) -> Self {
+        let inner = Arc::new(PoolInner {
+            destination: destination.into(),
+            client_options,
+            permits: Arc::new(Semaphore::new(options.max_size.max(1))),
+            options,
+            idle: Mutex::new(vec![]),
+        });
+        if let Some(interval) = inner.options.ping_interval {
+            tokio::spawn(keepalive(Arc::downgrade(&inner), interval));
+        }
+        Self { inner }
+    }
+
+    /// Returns an idle connection, or opens a new one. Waits for a connection to be returned if `max_size` connections are in use.
+    pub async fn get(&self) -> Result<PooledClient> {
+        let permit = self
+            .inner
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| KlickhouseError::ProtocolError(format!("pool closed: {e}")))?;
+        let client = match self.inner.take_idle() {
+            Some(client) => client,
+            None => self.inner.connect().await?,
+        };
+        Ok(PooledClient {
+            client: Some(client),
+            pool: self.inner.clone(),
+            _permit: permit,
+        })
+    }
+
+    /// Runs `operation` with a pooled connection. If it fails because the connection broke (i.e. the server restarted),
+    /// or with a [retryable](KlickhouseError::is_retryable) error, it is run again, up to [`PoolOptions::retries`] times.
+    /// As the server may have executed the query before the connection broke, only use this for idempotent operations, i.e. `SELECT`s.
+    ///
+    /// The operation borrows the connection, so it returns a boxed future, i.e. `|client| async move { ... }.boxed()` with
+    /// [`FutureExt::boxed`](futures_util::FutureExt::boxed).
+    pub async fn retry<T, F>(&self, mut operation: F) -> Result<T>
+    where
+        F: for<'c> FnMut(&'c Client) -> BoxFuture<'c, Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let (result, broken) = match self.get().await {
+                Ok(client) => {
+                    let result = operation(&client).await;
+                    (result, client.is_closed())
+                }
+                Err(e) => (Err(e), false),
+            };
+            match result {
+                Err(e) if (broken || e.is_retryable()) && attempt < self.inner.options.retries => {
+                    attempt += 1;
+                    warn!("retrying clickhouse operation: {e}");
+                    if let Some(events) = &self.inner.client_options.event_sender {
+                        let _ = events.send(ClientEvent::Reconnecting {
+                            attempt,
+                            error: e.to_string(),
+                        });
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Same as [`Client::query_collect`], run with [`Pool::retry`].
+    pub async fn query_collect<T: Row + Send + 'static>(
+        &self,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+    ) -> Result<Vec<T>> {
+        let query = query.try_into()?;
+        self.retry(|client| {
+            let query = query.clone();
+            async move { client.query_collect::<T>(&query).await }.boxed()
+        })
+        .await
+    }
+
+    /// Number of open connections not in use.
+    pub fn idle_connections(&self) -> usize {
+        self.inner.idle.lock().unwrap().len()
+    }
+}
+
+/// A connection borrowed from a [`Pool`], which returns it to the pool when dropped, unless it broke.
+pub struct PooledClient {
+    client: Option<Client>,
+    pool: Arc<PoolInner>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            let mut idle = self.pool.idle.lock().unwrap();
+            if !client.is_closed() && idle.len() < self.pool.options.max_size.max(1) {
+                idle.push(IdleClient {
+                    client,
+                    since: Instant::now(),
+                });
+            }
+        }
+    }
+}
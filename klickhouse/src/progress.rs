@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 /// Query execution progress.
 /// Values are delta and must be summed.
 ///
@@ -35,3 +37,127 @@ impl std::ops::AddAssign for Progress {
         *self = *self + rhs;
     }
 }
+
+impl Progress {
+    /// Percentage (0-100) of rows read, for a `Progress` summed over a query. `None` while the total is unknown.
+    pub fn percent_complete(&self) -> Option<f64> {
+        if self.new_total_rows_to_read == 0 {
+            return None;
+        }
+        Some((self.read_rows as f64 / self.new_total_rows_to_read as f64 * 100.0).min(100.0))
+    }
+
+    /// Rows read per second over `elapsed`.
+    pub fn rows_per_second(&self, elapsed: Duration) -> f64 {
+        per_second(self.read_rows, elapsed)
+    }
+
+    /// Bytes read per second over `elapsed`.
+    pub fn bytes_per_second(&self, elapsed: Duration) -> f64 {
+        per_second(self.read_bytes, elapsed)
+    }
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds == 0.0 {
+        return 0.0;
+    }
+    count as f64 / seconds
+}
+
+/// Accumulates the [`Progress`] packets of a query, i.e. from [`Client::subscribe_progress`](crate::Client::subscribe_progress), into totals and rates.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressTracker {
+    totals: Progress,
+    started: Instant,
+}
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressTracker {
+    /// Starts tracking now.
+    pub fn new() -> Self {
+        Self::started_at(Instant::now())
+    }
+
+    pub fn started_at(started: Instant) -> Self {
+        Self {
+            totals: Progress::default(),
+            started,
+        }
+    }
+
+    /// Adds a progress packet.
+    pub fn update(&mut self, delta: Progress) {
+        self.totals += delta;
+    }
+
+    /// Sum of all progress packets so far.
+    pub fn totals(&self) -> Progress {
+        self.totals
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// See [`Progress::percent_complete`].
+    pub fn percent_complete(&self) -> Option<f64> {
+        self.totals.percent_complete()
+    }
+
+    pub fn rows_per_second(&self) -> f64 {
+        self.totals.rows_per_second(self.elapsed())
+    }
+
+    pub fn bytes_per_second(&self) -> f64 {
+        self.totals.bytes_per_second(self.elapsed())
+    }
+
+    /// Time left at the average read rate so far. `None` while the total rows or the rate are unknown.
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        let total = self.totals.new_total_rows_to_read;
+        let rate = self.rows_per_second();
+        if total == 0 || rate == 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            total.saturating_sub(self.totals.read_rows) as f64 / rate,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_totals() {
+        let delta = Progress {
+            read_rows: 250,
+            read_bytes: 1000,
+            new_total_rows_to_read: 0,
+            new_written_rows: None,
+            new_written_bytes: None,
+        };
+        let mut tracker = ProgressTracker::started_at(Instant::now() - Duration::from_secs(2));
+        tracker.update(Progress {
+            new_total_rows_to_read: 1000,
+            ..delta
+        });
+        tracker.update(delta);
+
+        assert_eq!(tracker.totals().read_rows, 500);
+        assert_eq!(tracker.percent_complete(), Some(50.0));
+        assert!(tracker.rows_per_second() <= 250.0);
+        assert!(tracker.rows_per_second() > 200.0);
+        assert!(tracker.estimated_remaining().unwrap() >= Duration::from_secs(2));
+        assert_eq!(delta.percent_complete(), None);
+        assert_eq!(delta.bytes_per_second(Duration::from_secs(4)), 250.0);
+    }
+}
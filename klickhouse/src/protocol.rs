@@ -1,3 +1,5 @@
+#![cfg_attr(not(feature = "client"), allow(dead_code))]
+
 use indexmap::IndexMap;
 use uuid::Uuid;
 
@@ -16,15 +18,28 @@ pub const DBMS_MIN_REVISION_WITH_VERSION_PATCH: u64 = 54401;
 // pub const DBMS_MIN_REVISION_WITH_COLUMN_DEFAULTS_METADATA: u64 = 54410;
 // pub const DBMS_MIN_REVISION_WITH_LOW_CARDINALITY_TYPE: u64 = 54405;
 pub const DBMS_MIN_REVISION_WITH_CLIENT_WRITE_INFO: u64 = 54420;
-// pub const DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS: u64 = 54429;
+pub const DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS: u64 = 54429;
 pub const DBMS_MIN_REVISION_WITH_OPENTELEMETRY: u64 = 54442;
 pub const DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET: u64 = 54441;
 // pub const DBMS_MIN_REVISION_WITH_X_FORWARDED_FOR_IN_CLIENT_INFO: u64 = 54443;
 // pub const DBMS_MIN_REVISION_WITH_REFERER_IN_CLIENT_INFO: u64 = 54447;
 pub const DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH: u64 = 54448;
-
-pub const DBMS_TCP_PROTOCOL_VERSION: u64 = 54448;
-
+pub const DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME: u64 = 54449;
+// pub const DBMS_MIN_PROTOCOL_VERSION_WITH_INCREMENTAL_PROFILE_EVENTS: u64 = 54451;
+pub const DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS: u64 = 54453;
+pub const DBMS_MIN_REVISION_WITH_CUSTOM_SERIALIZATION: u64 = 54454;
+// pub const DBMS_MIN_PROTOCOL_VERSION_WITH_PROFILE_EVENTS_IN_INSERT: u64 = 54456;
+// pub const DBMS_MIN_PROTOCOL_VERSION_WITH_VIEW_IF_PERMITTED: u64 = 54457;
+pub const DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM: u64 = 54458;
+pub const DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS: u64 = 54459;
+
+pub const DBMS_TCP_PROTOCOL_VERSION: u64 = 54459;
+
+/// Version of the task distribution protocol of cluster table functions (i.e. `s3Cluster`), sent with read task responses.
+pub const DBMS_CLUSTER_PROCESSING_PROTOCOL_VERSION: u64 = 1;
+
+/// Largest string value (1 GiB), and number of rows in a block, that is read or written. Servers don't read larger strings either by default.
+/// See [`split_blob`](crate::split_blob) to store larger values.
 pub const MAX_STRING_SIZE: usize = 1 << 30;
 
 #[repr(u64)]
@@ -60,6 +75,7 @@ pub enum ServerPacketId {
     TableColumns,
     PartUUIDs,
     ReadTaskRequest,
+    ProfileEvents,
 }
 
 impl ServerPacketId {
@@ -79,6 +95,7 @@ impl ServerPacketId {
             11 => ServerPacketId::TableColumns,
             12 => ServerPacketId::PartUUIDs,
             13 => ServerPacketId::ReadTaskRequest,
+            14 => ServerPacketId::ProfileEvents,
             x => {
                 return Err(KlickhouseError::ProtocolError(format!(
                     "invalid packet id from server: {}",
@@ -115,7 +132,7 @@ pub struct ServerException {
     pub name: String,
     pub message: String,
     pub stack_trace: String,
-    pub has_nested: bool,
+    pub nested: Option<Box<ServerException>>,
 }
 
 impl ServerException {
@@ -125,6 +142,8 @@ impl ServerException {
             name: self.name.clone(),
             message: self.message.clone(),
             stack_trace: self.stack_trace.clone(),
+            query_id: None,
+            nested: self.nested.as_ref().map(|x| Box::new(x.emit())),
         }
     }
 }
@@ -176,13 +195,16 @@ pub enum ServerPacket {
     TableColumns(TableColumns),
     PartUUIDs(Vec<Uuid>),
     ReadTaskRequest,
+    ProfileEvents(ServerData),
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-#[allow(unused)]
+/// Compression of the data blocks exchanged with the server, see [`ClientOptions::compression`](crate::ClientOptions::compression).
+/// Defaults to `LZ4` with the `compression` feature, and `None` otherwise.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum CompressionMethod {
     #[cfg_attr(not(feature = "compression"), default)]
     None,
+    /// LZ4 frames with CityHash checksums. Requires the `compression` feature.
     #[cfg_attr(feature = "compression", default)]
     LZ4,
 }
@@ -0,0 +1,132 @@
+use std::fmt;
+
+use crate::{KlickhouseError, ParsedQuery, Result};
+
+/// A column, table or database name, quoted with backticks when rendered into a query.
+/// Any string is safe to use, i.e. a column picked by the user of a report builder, as quotes and backslashes in it are escaped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Identifier(String);
+
+impl Identifier {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    /// The unquoted name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`")?;
+        for c in self.0.chars() {
+            match c {
+                '\\' => write!(f, "\\\\")?,
+                '`' => write!(f, "\\`")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        write!(f, "`")
+    }
+}
+
+impl From<&str> for Identifier {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Identifier {
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
+
+impl TryInto<ParsedQuery> for Identifier {
+    type Error = KlickhouseError;
+
+    fn try_into(self) -> Result<ParsedQuery> {
+        Ok(ParsedQuery::new(self.to_string()))
+    }
+}
+
+/// A table name, optionally qualified with its database, i.e. for [`SelectBuilder::new`](crate::SelectBuilder::new).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QualifiedTable {
+    pub database: Option<Identifier>,
+    pub table: Identifier,
+}
+
+impl QualifiedTable {
+    /// A table of the current database.
+    pub fn new(table: impl Into<Identifier>) -> Self {
+        Self {
+            database: None,
+            table: table.into(),
+        }
+    }
+
+    pub fn with_database(database: impl Into<Identifier>, table: impl Into<Identifier>) -> Self {
+        Self {
+            database: Some(database.into()),
+            table: table.into(),
+        }
+    }
+}
+
+/// A table of the current database. The name is not split at dots, use [`QualifiedTable::with_database`] for other databases.
+impl From<&str> for QualifiedTable {
+    fn from(table: &str) -> Self {
+        Self::new(table)
+    }
+}
+
+impl From<String> for QualifiedTable {
+    fn from(table: String) -> Self {
+        Self::new(table)
+    }
+}
+
+impl From<Identifier> for QualifiedTable {
+    fn from(table: Identifier) -> Self {
+        Self::new(table)
+    }
+}
+
+impl fmt::Display for QualifiedTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(database) = &self.database {
+            write!(f, "{database}.")?;
+        }
+        write!(f, "{}", self.table)
+    }
+}
+
+impl TryInto<ParsedQuery> for QualifiedTable {
+    type Error = KlickhouseError;
+
+    fn try_into(self) -> Result<ParsedQuery> {
+        Ok(ParsedQuery::new(self.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier() {
+        assert_eq!(Identifier::new("id").to_string(), "`id`");
+        assert_eq!(
+            Identifier::new("a` FROM x; --\\").to_string(),
+            "`a\\` FROM x; --\\\\`"
+        );
+        assert_eq!(QualifiedTable::new("t").to_string(), "`t`");
+        assert_eq!(
+            QualifiedTable::with_database("db", "t.x").to_string(),
+            "`db`.`t.x`"
+        );
+    }
+}
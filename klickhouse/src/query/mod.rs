@@ -1,16 +1,53 @@
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
-use crate::{KlickhouseError, Result, ToSql, Value};
+use crate::{KlickhouseError, LiteralOptions, Result, ToSql, Value};
 
+mod identifier;
+pub use identifier::*;
 mod select;
 pub use select::*;
 
 #[derive(Debug, Clone)]
-pub struct ParsedQuery(pub(crate) String);
+pub struct ParsedQuery {
+    pub(crate) sql: String,
+    /// Loggable form of `sql` without bound values, if redaction was requested.
+    pub(crate) redacted: Option<String>,
+    /// Bound values of a redacted query, as they may appear in server errors, longest first.
+    pub(crate) secrets: Vec<String>,
+    /// Parameters bound by [`QueryBuilder::server_param`], sent alongside `sql`: names and values in the text format the server parses them with.
+    pub(crate) params: Vec<(String, String)>,
+}
+
+impl ParsedQuery {
+    pub(crate) fn new(sql: String) -> Self {
+        Self {
+            sql,
+            redacted: None,
+            secrets: vec![],
+            params: vec![],
+        }
+    }
+
+    /// The SQL sent to Clickhouse, including any interpolated arguments.
+    pub fn as_sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// The text used for logs, slow query reports and error messages.
+    /// For queries built with [`QueryBuilder::redact`], this is the template plus the number and types of arguments.
+    pub fn loggable(&self) -> &str {
+        self.redacted.as_deref().unwrap_or(&self.sql)
+    }
+
+    pub fn is_redacted(&self) -> bool {
+        self.redacted.is_some()
+    }
+}
 
+/// Displays [`ParsedQuery::loggable`].
 impl fmt::Display for ParsedQuery {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.loggable())
     }
 }
 
@@ -18,51 +55,177 @@ impl TryInto<ParsedQuery> for String {
     type Error = KlickhouseError;
 
     fn try_into(self) -> Result<ParsedQuery> {
-        Ok(ParsedQuery(self))
+        Ok(ParsedQuery::new(self))
+    }
+}
+
+impl TryInto<ParsedQuery> for &str {
+    type Error = KlickhouseError;
+
+    fn try_into(self) -> Result<ParsedQuery> {
+        Ok(ParsedQuery::new(self.to_string()))
     }
 }
 
-impl<'a> TryInto<ParsedQuery> for &'a str {
+impl TryInto<ParsedQuery> for &String {
     type Error = KlickhouseError;
 
     fn try_into(self) -> Result<ParsedQuery> {
-        Ok(ParsedQuery(self.to_string()))
+        Ok(ParsedQuery::new(self.clone()))
     }
 }
 
-impl<'a> TryInto<ParsedQuery> for &'a String {
+/// Reuses a query, i.e. to run the same insert repeatedly.
+impl TryInto<ParsedQuery> for &ParsedQuery {
     type Error = KlickhouseError;
 
     fn try_into(self) -> Result<ParsedQuery> {
-        Ok(ParsedQuery(self.clone()))
+        Ok(self.clone())
     }
 }
 
 #[derive(Clone)]
 pub struct QueryBuilder<'a> {
-    base: &'a str,
-    arguments: Vec<Result<Value>>,
+    base: Cow<'a, str>,
+    /// `None` for absent optional arguments
+    arguments: Vec<Option<Result<Value>>>,
+    named: Vec<(String, Result<Value>)>,
+    server_named: Vec<(String, Result<Value>)>,
+    literal_options: LiteralOptions,
+    redact: bool,
+    strict: bool,
 }
 
 impl<'a> QueryBuilder<'a> {
     pub fn new(query: &'a str) -> Self {
         Self {
-            base: query,
+            base: Cow::Borrowed(query),
             arguments: vec![],
+            named: vec![],
+            server_named: vec![],
+            literal_options: LiteralOptions::default(),
+            redact: false,
+            strict: true,
         }
     }
 
+    /// If `true`, `Int64`/`UInt64` arguments are emitted as quoted strings with an explicit cast, i.e. `toUInt64('...')`.
+    /// This avoids precision loss when the query is proxied through JSON-based middleware.
+    pub fn quote_large_integers(mut self, quote: bool) -> Self {
+        self.literal_options.quote_64bit_integers = quote;
+        self
+    }
+
+    /// If `true`, logs, slow query reports and server error messages refer to this query by its template plus the number and types of its arguments,
+    /// rather than the SQL with values interpolated. Values quoted elsewhere in server errors are replaced with `<redacted>`.
+    /// See [`ClientOptions::raw_sql_in_errors`](crate::ClientOptions::raw_sql_in_errors).
+    pub fn redact(mut self, redact: bool) -> Self {
+        self.redact = redact;
+        self
+    }
+
+    /// If `true` (the default), finalizing fails on placeholders without a matching argument and on arguments that are never referenced.
+    /// If `false`, unbound placeholders are left as-is and extra arguments are ignored.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Binds the next positional argument, interpolated into the query as a typed SQL literal.
+    /// `Array`, `Map` and `Tuple` values are escaped element by element, so nested strings and binary data round-trip exactly.
     pub fn arg(mut self, arg: impl ToSql) -> Self {
-        self.arguments.push(arg.to_sql(None));
+        self.arguments.push(Some(arg.to_sql(None)));
         self
     }
 
     pub fn args<A: ToSql>(mut self, args: impl IntoIterator<Item = A>) -> Self {
         self.arguments
-            .extend(args.into_iter().map(|x| x.to_sql(None)));
+            .extend(args.into_iter().map(|x| Some(x.to_sql(None))));
+        self
+    }
+
+    /// Adds an argument that may be absent. `Some` binds like [`QueryBuilder::arg`].
+    /// `None` occupies the placeholder number without a value: it may go unused even in strict mode, but referencing it is an error in strict mode
+    /// (and leaves the placeholder as-is otherwise). Use [`QueryBuilder::fragment_if`] to only reference it when present.
+    /// To bind `NULL` instead, pass the `Option` to [`QueryBuilder::arg`].
+    pub fn arg_opt<T: ToSql>(mut self, arg: Option<T>) -> Self {
+        self.arguments.push(arg.map(|x| x.to_sql(None)));
         self
     }
 
+    /// Binds a named argument, interpolated like [`QueryBuilder::arg`] into `$name` and `{name:Type}` placeholders,
+    /// which can be mixed with positional ones. Binding a name again replaces its value.
+    /// A `{name:Identifier}` placeholder takes a string and renders it as a quoted identifier, any other type must accept the bound value
+    /// or finalizing fails, i.e.
+    /// ```
+    /// # use klickhouse::QueryBuilder;
+    /// let query = QueryBuilder::new("SELECT * FROM {table:Identifier} WHERE user_id = {user_id:UInt64} AND name != $name")
+    ///     .param("table", "users")
+    ///     .param("user_id", 42u64)
+    ///     .param("name", "root")
+    ///     .finalize()
+    ///     .unwrap();
+    /// assert_eq!(query.as_sql(), "SELECT * FROM `users` WHERE user_id = 42 AND name != 'root'");
+    /// ```
+    /// In strict mode, unbound `$name` placeholders and unused names are errors. Unbound `{name:Type}` placeholders are always left as-is
+    /// for server-side parameters.
+    pub fn param(mut self, name: impl Into<String>, value: impl ToSql) -> Self {
+        let name = name.into();
+        let value = value.to_sql(None);
+        match self.named.iter_mut().find(|(x, _)| *x == name) {
+            Some((_, old)) => *old = value,
+            None => self.named.push((name, value)),
+        }
+        self
+    }
+
+    /// Binds a server-side parameter: `{name:Type}` placeholders are left in the query, and the value is sent alongside it for the server to substitute,
+    /// which requires a server of version 22.8 or later. Binding a name again replaces its value.
+    /// Like with [`QueryBuilder::param`], the value must be valid for the placeholder's type, or be a string for `Identifier`.
+    /// Values, including the elements of `Array`, `Map` and `Tuple` values, are sent in the text format the server reads parameters in,
+    /// so strings and binary data round-trip exactly.
+    ///
+    /// In strict mode, a name without a placeholder is an error. A name bound with both `param` and `server_param` is interpolated.
+    pub fn server_param(mut self, name: impl Into<String>, value: impl ToSql) -> Self {
+        let name = name.into();
+        let value = value.to_sql(None);
+        match self.server_named.iter_mut().find(|(x, _)| *x == name) {
+            Some((_, old)) => *old = value,
+            None => self.server_named.push((name, value)),
+        }
+        self
+    }
+
+    /// If `condition` is true, appends `sql` (separated by a space) to the query along with its arguments.
+    /// Placeholders in `sql` are numbered from `$1` relative to the fragment, i.e.
+    /// ```
+    /// # use klickhouse::QueryBuilder;
+    /// let name: Option<&str> = Some("klickhouse");
+    /// let query = QueryBuilder::new("SELECT * FROM crates WHERE downloads > $1")
+    ///     .arg(100u64)
+    ///     .fragment_if(name.is_some(), "AND name = $1", name)
+    ///     .finalize()
+    ///     .unwrap();
+    /// assert_eq!(query.as_sql(), "SELECT * FROM crates WHERE downloads > 100 AND name = 'klickhouse'");
+    /// ```
+    pub fn fragment_if<A: ToSql>(
+        mut self,
+        condition: bool,
+        sql: &str,
+        args: impl IntoIterator<Item = A>,
+    ) -> Self {
+        if !condition {
+            return self;
+        }
+        let fragment = crate::query_parser::offset_query_arguments(sql, self.arguments.len());
+        let base = self.base.to_mut();
+        if !base.is_empty() && !fragment.is_empty() {
+            base.push(' ');
+        }
+        base.push_str(&fragment);
+        self.args(args)
+    }
+
     pub fn finalize(self) -> Result<ParsedQuery> {
         self.try_into()
     }
@@ -72,10 +235,227 @@ impl<'a> TryInto<ParsedQuery> for QueryBuilder<'a> {
     type Error = KlickhouseError;
 
     fn try_into(self) -> Result<ParsedQuery> {
-        let arguments = self.arguments.into_iter().collect::<Result<Vec<_>>>()?;
-        Ok(ParsedQuery(crate::query_parser::parse_query_arguments(
-            self.base,
-            &arguments[..],
-        )))
+        let arguments = self
+            .arguments
+            .into_iter()
+            .map(Option::transpose)
+            .collect::<Result<Vec<_>>>()?;
+        let named = self
+            .named
+            .into_iter()
+            .map(|(name, value)| Ok((name, value?)))
+            .collect::<Result<Vec<_>>>()?;
+        let server_named = self
+            .server_named
+            .into_iter()
+            .map(|(name, value)| Ok((name, value?)))
+            .collect::<Result<Vec<_>>>()?;
+        let redacted = self.redact.then(|| {
+            let types = arguments
+                .iter()
+                .map(|x| match x {
+                    Some(x) => x.guess_type().to_string(),
+                    None => "absent".to_string(),
+                })
+                .chain(
+                    named
+                        .iter()
+                        .map(|(name, x)| format!("{name}: {}", x.guess_type())),
+                )
+                .collect::<Vec<_>>();
+            format!(
+                "{} /* {} redacted argument(s): {} */",
+                self.base,
+                types.len(),
+                types.join(", ")
+            )
+        });
+        let secrets = if self.redact {
+            redacted_values(
+                arguments
+                    .iter()
+                    .flatten()
+                    .chain(named.iter().map(|(_, value)| value))
+                    .chain(server_named.iter().map(|(_, value)| value)),
+                self.literal_options,
+            )
+        } else {
+            vec![]
+        };
+        let arguments = arguments.iter().map(Option::as_ref).collect::<Vec<_>>();
+        let named = named
+            .iter()
+            .map(|(name, value)| (&**name, value))
+            .collect::<Vec<_>>();
+        let sql = crate::query_parser::substitute_arguments(
+            &self.base,
+            &arguments,
+            &named,
+            self.literal_options,
+            self.strict,
+        )?;
+        let placeholders = crate::query_parser::typed_placeholders(&sql);
+        let mut params = Vec::with_capacity(server_named.len());
+        for (name, value) in &server_named {
+            let mut types = placeholders
+                .iter()
+                .filter(|(x, _)| x == name)
+                .map(|(_, type_)| crate::query_parser::typed_param(name, type_, value));
+            match types.next() {
+                Some(text) => {
+                    // every placeholder must accept the value, the server uses the same text for all
+                    let text = text?;
+                    types.try_for_each(|x| x.map(drop))?;
+                    params.push((name.clone(), text));
+                }
+                None if self.strict => {
+                    return Err(KlickhouseError::QueryArgumentError(format!(
+                        "server parameter {name} has no placeholder"
+                    )))
+                }
+                None => (),
+            }
+        }
+        Ok(ParsedQuery {
+            sql,
+            redacted,
+            secrets,
+            params,
+        })
+    }
+}
+
+/// The forms in which `values` may appear in server errors: as literals, unquoted, and strings as-is.
+fn redacted_values<'a>(
+    values: impl Iterator<Item = &'a Value>,
+    options: LiteralOptions,
+) -> Vec<String> {
+    let mut secrets = vec![];
+    for value in values {
+        let literal = value.literal(options).to_string();
+        if let Some(unquoted) = literal
+            .strip_prefix('\'')
+            .and_then(|x| x.strip_suffix('\''))
+        {
+            secrets.push(unquoted.to_string());
+        }
+        if let Value::String(bytes) = value {
+            secrets.push(String::from_utf8_lossy(bytes).into_owned());
+        }
+        secrets.push(literal);
+    }
+    secrets.retain(|x| !x.is_empty());
+    sort_secrets(&mut secrets);
+    secrets
+}
+
+/// Orders [`ParsedQuery::secrets`] longest first, as longer ones may contain shorter ones, and removes duplicates.
+pub(crate) fn sort_secrets(secrets: &mut Vec<String>) {
+    secrets.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    secrets.dedup();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optional_arguments() {
+        let build = |name: Option<&str>, limit: Option<u64>| {
+            QueryBuilder::new("SELECT * FROM t WHERE id = $1")
+                .arg(1u32)
+                .fragment_if(name.is_some(), "AND name = $1", name)
+                .fragment_if(limit.is_some(), "LIMIT $1", limit)
+                .finalize()
+                .unwrap()
+        };
+        assert_eq!(
+            build(Some("x"), Some(5)).as_sql(),
+            "SELECT * FROM t WHERE id = 1 AND name = 'x' LIMIT 5"
+        );
+        assert_eq!(
+            build(None, Some(5)).as_sql(),
+            "SELECT * FROM t WHERE id = 1 LIMIT 5"
+        );
+        assert_eq!(build(None, None).as_sql(), "SELECT * FROM t WHERE id = 1");
+
+        let query = QueryBuilder::new("SELECT $1, $2")
+            .arg(1u32)
+            .arg_opt(None::<u32>)
+            .finalize();
+        assert!(matches!(query, Err(KlickhouseError::QueryArgumentError(_))));
+
+        let query = QueryBuilder::new("SELECT $1")
+            .arg(1u32)
+            .arg_opt(None::<u32>)
+            .finalize()
+            .unwrap();
+        assert_eq!(query.as_sql(), "SELECT 1");
+    }
+
+    #[test]
+    fn test_named_arguments() {
+        let query =
+            QueryBuilder::new("SELECT * FROM t WHERE id = $1 AND user_id = {user_id:UInt64}")
+                .arg(1u32)
+                .param("user_id", 5u64)
+                .fragment_if(true, "AND name = $name AND x = $1", [2u32])
+                .param("name", "a")
+                .param("user_id", 6u64)
+                .redact(true)
+                .finalize()
+                .unwrap();
+        assert_eq!(
+            query.as_sql(),
+            "SELECT * FROM t WHERE id = 1 AND user_id = 6 AND name = 'a' AND x = 2"
+        );
+        assert!(query.loggable().ends_with(
+            "/* 4 redacted argument(s): UInt32, UInt32, user_id: UInt64, name: String */"
+        ));
+
+        let query = QueryBuilder::new("SELECT $1")
+            .arg(1u32)
+            .param("unused", 2u32)
+            .finalize();
+        assert!(matches!(query, Err(KlickhouseError::QueryArgumentError(_))));
+    }
+
+    #[test]
+    fn test_server_params() {
+        let query = QueryBuilder::new(
+            "SELECT * FROM {table:Identifier} WHERE id = $1 AND name IN {names:Array(String)}",
+        )
+        .arg(1u32)
+        .server_param("table", "t")
+        .server_param("names", vec!["a'b", "c"])
+        .finalize()
+        .unwrap();
+        assert_eq!(
+            query.as_sql(),
+            "SELECT * FROM {table:Identifier} WHERE id = 1 AND name IN {names:Array(String)}"
+        );
+        assert_eq!(
+            query.params,
+            [
+                ("table".to_string(), "t".to_string()),
+                ("names".to_string(), "['a\\'b','c']".to_string())
+            ]
+        );
+
+        let query = QueryBuilder::new("SELECT {x:UInt8}")
+            .server_param("x", "a")
+            .finalize();
+        assert!(matches!(query, Err(KlickhouseError::QueryArgumentError(_))));
+
+        let query = QueryBuilder::new("SELECT 1")
+            .server_param("x", 1u8)
+            .finalize();
+        assert!(matches!(query, Err(KlickhouseError::QueryArgumentError(_))));
+        let query = QueryBuilder::new("SELECT 1")
+            .server_param("x", 1u8)
+            .strict(false)
+            .finalize()
+            .unwrap();
+        assert!(query.params.is_empty());
     }
 }
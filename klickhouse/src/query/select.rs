@@ -1,5 +1,16 @@
-use crate::{KlickhouseError, ParsedQuery, Result};
+use crate::{Identifier, KlickhouseError, ParsedQuery, Result, ToSql, Value};
+
+/// Direction of a column in [`SelectBuilder::order_by_columns`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
 
+/// Builds a `SELECT` query from clauses given as SQL, which is concatenated as-is.
+/// Clauses built from user input should use the variants taking an [`Identifier`] (or a [`QualifiedTable`](crate::QualifiedTable) for [`SelectBuilder::new`])
+/// and binding values, i.e. [`SelectBuilder::select_columns`] and [`SelectBuilder::where_eq`], which quote and escape everything they interpolate.
 #[derive(Clone)]
 pub struct SelectBuilder {
     withs: Vec<Result<ParsedQuery>>,
@@ -7,6 +18,8 @@ pub struct SelectBuilder {
     distinct_on: Vec<Result<ParsedQuery>>,
     exprs: Vec<Result<ParsedQuery>>,
     from: Result<ParsedQuery>,
+    final_: bool,
+    final_within_partitions: bool,
     sample: Option<Result<ParsedQuery>>,
     array_joins: Vec<Result<ParsedQuery>>,
     joins: Vec<Result<ParsedQuery>>,
@@ -25,6 +38,8 @@ impl SelectBuilder {
     pub fn new(from: impl TryInto<ParsedQuery, Error = KlickhouseError>) -> Self {
         Self {
             from: from.try_into(),
+            final_: Default::default(),
+            final_within_partitions: Default::default(),
             withs: Default::default(),
             distinct: Default::default(),
             distinct_on: Default::default(),
@@ -81,6 +96,33 @@ impl SelectBuilder {
         self
     }
 
+    /// Adds columns to the select clause by name.
+    pub fn select_columns<I: Into<Identifier>>(
+        mut self,
+        columns: impl IntoIterator<Item = I>,
+    ) -> Self {
+        for column in columns {
+            self.exprs.push(column.into().try_into());
+        }
+        self
+    }
+
+    /// If `true`, reads the table with `FROM ... FINAL`, so `ReplacingMergeTree`, `CollapsingMergeTree` and similar tables return rows as they will be after merging.
+    /// Use [`Client::check_final`](crate::Client::check_final) to check that the table supports it.
+    pub fn final_(mut self, final_: bool) -> Self {
+        self.final_ = final_;
+        self
+    }
+
+    /// If `true`, reads the table with `FINAL` like [`SelectBuilder::final_`], merging each partition on its own.
+    /// This is faster, but only correct if rows sharing a sorting key are always in the same partition.
+    /// Adds the `do_not_merge_across_partitions_select_final` setting to the SETTINGS clause.
+    pub fn final_within_partitions(mut self, within_partitions: bool) -> Self {
+        self.final_ |= within_partitions;
+        self.final_within_partitions = within_partitions;
+        self
+    }
+
     /// Sets the SAMPLE clause. Overwrites previous SAMPLE clauses.
     pub fn sample(mut self, item: impl TryInto<ParsedQuery, Error = KlickhouseError>) -> Self {
         self.sample = Some(item.try_into());
@@ -135,6 +177,38 @@ impl SelectBuilder {
         self
     }
 
+    /// Adds a `column = value` WHERE clause, or `column IS NULL` for a null `value`.
+    pub fn where_eq(mut self, column: impl Into<Identifier>, value: impl ToSql) -> Self {
+        let column = column.into();
+        self.where_
+            .push(value.to_sql(None).map(|value| match value {
+                Value::Null => ParsedQuery::new(format!("{column} IS NULL")),
+                value => ParsedQuery::new(format!("{column} = {value}")),
+            }));
+        self
+    }
+
+    /// Adds a `column IN (values...)` WHERE clause. No values match no rows.
+    pub fn where_in<A: ToSql>(
+        mut self,
+        column: impl Into<Identifier>,
+        values: impl IntoIterator<Item = A>,
+    ) -> Self {
+        let column = column.into();
+        let values = values
+            .into_iter()
+            .map(|x| x.to_sql(None).map(|x| x.to_string()))
+            .collect::<Result<Vec<_>>>();
+        self.where_.push(values.map(|values| {
+            if values.is_empty() {
+                ParsedQuery::new("0".to_string())
+            } else {
+                ParsedQuery::new(format!("{column} IN ({})", values.join(", ")))
+            }
+        }));
+        self
+    }
+
     /// Adds a column to the GROUP BY clause. No trailing commas. Can specify multiple in one call comma separated.
     pub fn group_by(mut self, item: impl TryInto<ParsedQuery, Error = KlickhouseError>) -> Self {
         self.group_by.push(item.try_into());
@@ -152,6 +226,17 @@ impl SelectBuilder {
         self
     }
 
+    /// Adds columns to the GROUP BY clause by name.
+    pub fn group_by_columns<I: Into<Identifier>>(
+        mut self,
+        columns: impl IntoIterator<Item = I>,
+    ) -> Self {
+        for column in columns {
+            self.group_by.push(column.into().try_into());
+        }
+        self
+    }
+
     /// Adds a HAVING clause. Concatenated automatically with AND operators.
     pub fn having(mut self, item: impl TryInto<ParsedQuery, Error = KlickhouseError>) -> Self {
         self.having.push(item.try_into());
@@ -175,12 +260,38 @@ impl SelectBuilder {
         self
     }
 
+    /// Sets the ORDER BY clause to columns by name. Overwrites previous ORDER BY clauses.
+    pub fn order_by_columns<I: Into<Identifier>>(
+        mut self,
+        columns: impl IntoIterator<Item = (I, SortOrder)>,
+    ) -> Self {
+        let columns = columns
+            .into_iter()
+            .map(|(column, order)| match order {
+                SortOrder::Asc => format!("{} ASC", column.into()),
+                SortOrder::Desc => format!("{} DESC", column.into()),
+            })
+            .collect::<Vec<_>>();
+        self.order_by = Some(Ok(ParsedQuery::new(columns.join(", "))));
+        self
+    }
+
     /// Sets the LIMIT clause. Overwrites previous LIMIT clauses.
     pub fn limit(mut self, item: impl TryInto<ParsedQuery, Error = KlickhouseError>) -> Self {
         self.limit = Some(item.try_into());
         self
     }
 
+    /// Sets the LIMIT clause to `limit` rows after skipping `offset` rows. Overwrites previous LIMIT clauses.
+    pub fn limit_rows(mut self, limit: u64, offset: Option<u64>) -> Self {
+        let sql = match offset {
+            Some(offset) => format!("{limit} OFFSET {offset}"),
+            None => limit.to_string(),
+        };
+        self.limit = Some(Ok(ParsedQuery::new(sql)));
+        self
+    }
+
     /// Sets the SETTINGS clause. Overwrites previous SETTINGS clauses.
     pub fn settings(mut self, item: impl TryInto<ParsedQuery, Error = KlickhouseError>) -> Self {
         self.settings = Some(item.try_into());
@@ -203,14 +314,14 @@ impl TryInto<ParsedQuery> for SelectBuilder {
     type Error = KlickhouseError;
 
     fn try_into(mut self) -> Result<ParsedQuery> {
-        let mut out = String::new();
+        let mut out = QueryText::default();
 
         if !self.withs.is_empty() {
             out.push_str("WITH ");
             self.withs.reverse();
             while let Some(last) = self.withs.pop() {
                 let last = last?;
-                out.push_str(&last.0);
+                out.push_query(&last);
                 if !self.withs.is_empty() {
                     out.push(',');
                 }
@@ -227,7 +338,7 @@ impl TryInto<ParsedQuery> for SelectBuilder {
             self.distinct_on.reverse();
             while let Some(last) = self.distinct_on.pop() {
                 let last = last?;
-                out.push_str(&last.0);
+                out.push_query(&last);
                 if !self.distinct_on.is_empty() {
                     out.push(',');
                 }
@@ -238,7 +349,7 @@ impl TryInto<ParsedQuery> for SelectBuilder {
         self.exprs.reverse();
         while let Some(last) = self.exprs.pop() {
             let last = last?;
-            out.push_str(&last.0);
+            out.push_query(&last);
             if !self.exprs.is_empty() {
                 out.push_str(",\n");
             } else {
@@ -247,11 +358,14 @@ impl TryInto<ParsedQuery> for SelectBuilder {
         }
 
         out.push_str("FROM ");
-        out.push_str(&self.from?.0);
+        out.push_query(&self.from?);
+        if self.final_ {
+            out.push_str(" FINAL");
+        }
         out.push('\n');
         if let Some(sample) = self.sample {
             out.push_str("SAMPLE ");
-            out.push_str(&sample?.0);
+            out.push_query(&sample?);
             out.push('\n');
         }
 
@@ -259,7 +373,7 @@ impl TryInto<ParsedQuery> for SelectBuilder {
             self.array_joins.reverse();
             while let Some(last) = self.array_joins.pop() {
                 let last = last?;
-                out.push_str(&last.0);
+                out.push_query(&last);
                 out.push('\n');
             }
         }
@@ -268,7 +382,7 @@ impl TryInto<ParsedQuery> for SelectBuilder {
             self.joins.reverse();
             while let Some(last) = self.joins.pop() {
                 let last = last?;
-                out.push_str(&last.0);
+                out.push_query(&last);
                 out.push('\n');
             }
         }
@@ -278,7 +392,7 @@ impl TryInto<ParsedQuery> for SelectBuilder {
             out.push_str("PREWHERE (");
             while let Some(last) = self.prewhere.pop() {
                 let last = last?;
-                out.push_str(&last.0);
+                out.push_query(&last);
                 if !self.prewhere.is_empty() {
                     out.push_str(") AND\n(");
                 } else {
@@ -292,7 +406,7 @@ impl TryInto<ParsedQuery> for SelectBuilder {
             out.push_str("WHERE (");
             while let Some(last) = self.where_.pop() {
                 let last = last?;
-                out.push_str(&last.0);
+                out.push_query(&last);
                 if !self.where_.is_empty() {
                     out.push_str(") AND\n(");
                 } else {
@@ -306,7 +420,7 @@ impl TryInto<ParsedQuery> for SelectBuilder {
             out.push_str("GROUP BY ");
             while let Some(last) = self.group_by.pop() {
                 let last = last?;
-                out.push_str(&last.0);
+                out.push_query(&last);
                 if !self.group_by.is_empty() {
                     out.push_str(",\n");
                 } else {
@@ -320,7 +434,7 @@ impl TryInto<ParsedQuery> for SelectBuilder {
             out.push_str("HAVING (");
             while let Some(last) = self.having.pop() {
                 let last = last?;
-                out.push_str(&last.0);
+                out.push_query(&last);
                 if !self.having.is_empty() {
                     out.push_str(") AND\n(");
                 } else {
@@ -331,29 +445,78 @@ impl TryInto<ParsedQuery> for SelectBuilder {
 
         if let Some(order_by) = self.order_by {
             out.push_str("ORDER BY ");
-            out.push_str(&order_by?.0);
+            out.push_query(&order_by?);
             out.push('\n');
         }
 
         if let Some(limit) = self.limit {
             out.push_str("LIMIT ");
-            out.push_str(&limit?.0);
+            out.push_query(&limit?);
             out.push('\n');
         }
 
-        if let Some(settings) = self.settings {
+        if self.settings.is_some() || self.final_within_partitions {
             out.push_str("SETTINGS ");
-            out.push_str(&settings?.0);
+            if let Some(settings) = self.settings {
+                out.push_query(&settings?);
+                if self.final_within_partitions {
+                    out.push_str(", ");
+                }
+            }
+            if self.final_within_partitions {
+                out.push_str("do_not_merge_across_partitions_select_final = 1");
+            }
             out.push('\n');
         }
 
         if let Some(union) = self.union {
             out.push_str("UNION ");
-            out.push_str(&union?.0);
+            out.push_query(&union?);
             out.push('\n');
         }
 
-        Ok(ParsedQuery(out))
+        Ok(out.finish())
+    }
+}
+
+/// Accumulates a query alongside its loggable form, so redaction of any part carries over to the whole.
+#[derive(Default)]
+struct QueryText {
+    sql: String,
+    loggable: String,
+    redacted: bool,
+    secrets: Vec<String>,
+    params: Vec<(String, String)>,
+}
+
+impl QueryText {
+    fn push(&mut self, c: char) {
+        self.sql.push(c);
+        self.loggable.push(c);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.sql.push_str(s);
+        self.loggable.push_str(s);
+    }
+
+    fn push_query(&mut self, query: &ParsedQuery) {
+        self.sql.push_str(&query.sql);
+        self.loggable.push_str(query.loggable());
+        self.redacted |= query.is_redacted();
+        self.secrets.extend(query.secrets.iter().cloned());
+        self.params.extend(query.params.iter().cloned());
+    }
+
+    fn finish(self) -> ParsedQuery {
+        let mut secrets = self.secrets;
+        super::sort_secrets(&mut secrets);
+        ParsedQuery {
+            sql: self.sql,
+            redacted: self.redacted.then_some(self.loggable),
+            secrets,
+            params: self.params,
+        }
     }
 }
 
@@ -376,4 +539,64 @@ mod tests {
         let query = builder.build().unwrap();
         println!("{query}");
     }
+
+    #[test]
+    fn test_select_builder_identifiers() {
+        let query = SelectBuilder::new(crate::QualifiedTable::with_database("db", "events"))
+            .select_columns(["id", "name`; DROP TABLE x; --"])
+            .where_eq("kind", "o'clock")
+            .where_eq("deleted_at", None::<u32>)
+            .where_in("id", [1u32, 2])
+            .where_in("id", Vec::<u32>::new())
+            .group_by_columns(["id"])
+            .order_by_columns([("id", SortOrder::Desc), ("kind", SortOrder::Asc)])
+            .limit_rows(10, Some(20))
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.as_sql(),
+            "SELECT\n`id`,\n`name\\`; DROP TABLE x; --`\nFROM `db`.`events`\n\
+            WHERE (`kind` = 'o\\'clock') AND\n(`deleted_at` IS NULL) AND\n(`id` IN (1, 2)) AND\n(0)\n\
+            GROUP BY `id`\nORDER BY `id` DESC, `kind` ASC\nLIMIT 10 OFFSET 20\n"
+        );
+    }
+
+    #[test]
+    fn test_select_builder_final() {
+        let query = SelectBuilder::new("events")
+            .select("id")
+            .final_(true)
+            .build()
+            .unwrap();
+        assert_eq!(query.as_sql(), "SELECT\nid\nFROM events FINAL\n");
+
+        let query = SelectBuilder::new("events")
+            .select("id")
+            .final_within_partitions(true)
+            .settings("max_threads = 2")
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.as_sql(),
+            "SELECT\nid\nFROM events FINAL\nSETTINGS max_threads = 2, do_not_merge_across_partitions_select_final = 1\n"
+        );
+    }
+
+    #[test]
+    fn test_select_builder_redacted() {
+        let query = SelectBuilder::new("users")
+            .select("id")
+            .where_(
+                QueryBuilder::new("password = $1")
+                    .arg("hunter2")
+                    .redact(true),
+            )
+            .build()
+            .unwrap();
+        assert!(query.as_sql().contains("'hunter2'"));
+        assert!(!query.to_string().contains("hunter2"));
+        assert!(query
+            .loggable()
+            .contains("password = $1 /* 1 redacted argument(s): String */"));
+    }
 }
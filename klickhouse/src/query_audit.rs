@@ -0,0 +1,32 @@
+use std::{fmt, sync::Arc};
+
+use uuid::Uuid;
+
+/// A query as sent to the server, see [`ClientOptions::query_audit_hook`](crate::ClientOptions::query_audit_hook).
+#[derive(Debug, Clone)]
+pub struct AuditedQuery {
+    pub id: Uuid,
+    /// The exact SQL sent, with arguments interpolated. Unlike logs and errors, this is never redacted.
+    pub sql: String,
+}
+
+/// Callback invoked (on the connection task, so it should not block) with every query right before it is sent, i.e. for audit logging.
+/// This includes the `USE` statements sent for [`QueryOptions::database`](crate::QueryOptions::database).
+#[derive(Clone)]
+pub struct QueryAuditHook(Arc<dyn Fn(&AuditedQuery) + Send + Sync>);
+
+impl QueryAuditHook {
+    pub fn new(hook: impl Fn(&AuditedQuery) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, query: &AuditedQuery) {
+        (self.0)(query)
+    }
+}
+
+impl fmt::Debug for QueryAuditHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QueryAuditHook")
+    }
+}
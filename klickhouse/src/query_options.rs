@@ -0,0 +1,359 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::{block::Block, KlickhouseError, Result, Value};
+
+/// Per-query overrides of [`ClientOptions`](crate::ClientOptions), accepted by the `_with_options` variant of each query and insert method of
+/// [`Client`](crate::Client), i.e. [`Client::query_with_options`](crate::Client::query_with_options).
+/// New knobs are added as builder methods, so constructing it with [`QueryOptions::new`] keeps compiling as they are.
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    pub(crate) database: Option<String>,
+    pub(crate) keep_empty_rows: bool,
+    pub(crate) final_: bool,
+    pub(crate) final_within_partitions: bool,
+    /// Name, value, and whether the server must know the setting
+    pub(crate) settings: Vec<(String, String, bool)>,
+    pub(crate) query_id: Option<Uuid>,
+    pub(crate) queue_priority: QueryPriority,
+    pub(crate) server_priority: Option<QueryPriority>,
+    pub(crate) async_insert: Option<AsyncInsertWait>,
+    pub(crate) json_as_string: bool,
+    pub(crate) utf8: Utf8Policy,
+}
+
+/// How urgent a query is compared to others submitted to the same [`Client`](crate::Client). Ordered from `Low` to `High`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QueryPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// What an insert with [`QueryOptions::async_insert`] waits for, i.e. with [`Client::insert_async`](crate::Client::insert_async).
+///
+/// With asynchronous inserts, the server collects the rows of many small inserts into the same table (with the same settings) in a buffer,
+/// and writes them to the table as one part once the buffer reaches `async_insert_max_data_size` bytes or is `async_insert_busy_timeout_ms` old.
+/// Each variant trades latency for how much is known about the rows when the insert returns:
+/// - Only `Flushed` reports every error, and returns rows as durable as with a regular insert.
+/// - Rows of an insert that returned with `Buffered` or `Sent` are lost if the server stops before flushing them.
+///   Errors of the flush are then only found in `system.asynchronous_insert_log`.
+///
+/// Buffered rows aren't deduplicated unless `async_insert_deduplicate` is set, so retrying an insert that failed after it was sent may duplicate its rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AsyncInsertWait {
+    /// Returns once the buffer holding the rows was written to the table (`wait_for_async_insert = 1`), taking up to the buffer timeout.
+    #[default]
+    Flushed,
+    /// Returns once the server accepted the rows into its buffer (`wait_for_async_insert = 0`), reporting errors found while parsing them.
+    Buffered,
+    /// Same settings as `Buffered`, but returns as soon as the rows are sent, without waiting for the server to end the insert,
+    /// so errors of the insert are not reported at all and [`InsertResult::bytes`](crate::InsertResult::bytes) is 0.
+    /// The connection still reads the server's response before it runs the next query.
+    Sent,
+}
+
+/// How a query's rows treat string values that aren't valid UTF-8, see [`QueryOptions::utf8`].
+/// `String` and `FixedString` columns hold arbitrary bytes, which only some fields can be read from:
+/// `Vec<u8>`, [`Bytes`](crate::Bytes) and [`MaybeUtf8`](crate::MaybeUtf8) fields read any value losslessly, `String` fields only read UTF-8.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Utf8Policy {
+    /// Values are passed to the row's fields unchanged, so byte fields read them exactly and `String` fields fail to read invalid ones.
+    #[default]
+    Bytes,
+    /// Rows with an invalid value fail to read, whatever the field the value is read into.
+    Error,
+    /// Invalid sequences are replaced with `U+FFFD`, so `String` fields read every value, and byte fields read the replaced value.
+    /// See [`helpers::utf8_lossy`](crate::helpers::utf8_lossy) to read only some fields this way.
+    Lossy,
+}
+
+impl Utf8Policy {
+    /// Applies the policy to every string value of `block`, including those nested in arrays, tuples, maps and variants.
+    pub(crate) fn apply(self, block: &mut Block) -> Result<()> {
+        if self == Utf8Policy::Bytes {
+            return Ok(());
+        }
+        for (name, values) in block.column_data.iter_mut() {
+            for value in values {
+                self.apply_value(value).map_err(|e| {
+                    KlickhouseError::DeserializeError(format!("column {name}: {e}"))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_value(self, value: &mut Value) -> Result<()> {
+        match value {
+            Value::String(bytes) => {
+                if let Err(e) = std::str::from_utf8(bytes) {
+                    if self == Utf8Policy::Error {
+                        return Err(KlickhouseError::DeserializeError(format!(
+                            "invalid UTF-8 in string value: {e}"
+                        )));
+                    }
+                    *bytes = String::from_utf8_lossy(bytes).into_owned().into_bytes();
+                }
+            }
+            Value::Array(items) | Value::Tuple(items) => {
+                for item in items {
+                    self.apply_value(item)?;
+                }
+            }
+            Value::Map(keys, values) => {
+                for item in keys.iter_mut().chain(values) {
+                    self.apply_value(item)?;
+                }
+            }
+            Value::Variant(inner, _) => self.apply_value(inner)?,
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+impl QueryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// If `true`, rows without any columns (from blocks that have rows but no columns, which a well-behaved server doesn't send)
+    /// are passed to [`Row::deserialize_row`](crate::Row::deserialize_row) rather than skipped with a warning.
+    /// Skipping is the default for compatibility, and may change in a future version.
+    ///
+    /// This only affects row streams: [`Client::query_raw`](crate::Client::query_raw) yields every block as-is, including the initial zero-row block
+    /// carrying the result's column names and types.
+    pub fn keep_empty_rows(mut self, keep: bool) -> Self {
+        self.keep_empty_rows = keep;
+        self
+    }
+
+    /// Runs the query with `database` as the current database instead of [`ClientOptions::default_database`](crate::ClientOptions::default_database),
+    /// i.e. for a database per tenant. The connection switches with a `USE` statement before the query, and back before the next query without an override.
+    /// If `default_database` is empty, the connection looks up the database selected at login with `SELECT currentDatabase()` before its first switch,
+    /// and switches back to that.
+    pub fn database(mut self, database: impl Into<String>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// If `true`, reads every table of the query as if with `FROM ... FINAL`, so `ReplacingMergeTree`, `CollapsingMergeTree` and similar tables
+    /// return rows as they will be after merging. This is sent as the `final` setting, which requires Clickhouse 23.2 or later.
+    /// To apply it to a single table, use [`SelectBuilder::final_`](crate::SelectBuilder::final_) instead.
+    pub fn final_(mut self, final_: bool) -> Self {
+        self.final_ = final_;
+        self
+    }
+
+    /// If `true`, `FINAL` reads merge each partition on its own, which is faster but only correct if rows sharing a sorting key
+    /// are always in the same partition. This is sent as the `do_not_merge_across_partitions_select_final` setting.
+    pub fn final_within_partitions(mut self, within_partitions: bool) -> Self {
+        self.final_within_partitions = within_partitions;
+        self
+    }
+
+    /// Sends the server setting `name` with the query, i.e. `.setting("max_execution_time", 30)` or `.setting("insert_deduplicate", false)`,
+    /// like `SETTINGS name = value` in SQL. Setting a name again replaces its value.
+    /// The query fails if the server doesn't know the setting, see [`QueryOptions::optional_setting`] otherwise.
+    pub fn setting(self, name: impl Into<String>, value: impl fmt::Display) -> Self {
+        self.push_setting(name.into(), value.to_string(), true)
+    }
+
+    /// Same as [`QueryOptions::setting`], but servers that don't know the setting (i.e. older versions) ignore it.
+    pub fn optional_setting(self, name: impl Into<String>, value: impl fmt::Display) -> Self {
+        self.push_setting(name.into(), value.to_string(), false)
+    }
+
+    /// Sends the query with `id` rather than a random one, i.e. to find it in `system.query_log` or `system.processes` by `query_id`,
+    /// or to match [`Client::subscribe_progress`](crate::Client::subscribe_progress) events before the query is dispatched.
+    /// The server rejects a query whose id is already used by a running query.
+    pub fn query_id(mut self, id: Uuid) -> Self {
+        self.query_id = Some(id);
+        self
+    }
+
+    /// Where the query is queued on the connection: ahead of all waiting queries of a lower priority, and behind those of the same or a higher one.
+    /// This applies both in the connection's queue and while waiting for [`ClientOptions::max_concurrent_queries`](crate::ClientOptions::max_concurrent_queries).
+    /// A steady stream of `High` queries can delay `Low` ones indefinitely.
+    pub fn queue_priority(mut self, priority: QueryPriority) -> Self {
+        self.queue_priority = priority;
+        self
+    }
+
+    /// Runs the query with a server-side priority, sent as the `priority` (1 for `High`, 2 for `Normal`, 3 for `Low`) and `os_thread_priority`
+    /// (-5, 0 and 10) settings. The server pauses queries of a lower priority while those of a higher one run, so give all queries of a mixed workload
+    /// a priority: queries without one are left out of that ordering. Raising the thread priority requires `CAP_SYS_NICE` on the server,
+    /// which ignores it otherwise, as do servers without `os_thread_priority`. Settings set explicitly take precedence.
+    /// To order queries on the client, see [`QueryOptions::queue_priority`].
+    pub fn priority(mut self, priority: QueryPriority) -> Self {
+        self.server_priority = Some(priority);
+        self
+    }
+
+    /// Runs an insert as a server-side asynchronous insert, sent as the `async_insert` and `wait_for_async_insert` settings.
+    /// See [`AsyncInsertWait`] for when the insert returns, and what is known about its rows by then. Settings set explicitly take precedence.
+    pub fn async_insert(mut self, wait: AsyncInsertWait) -> Self {
+        self.async_insert = Some(wait);
+        self
+    }
+
+    /// If `true`, `JSON` columns of the result are sent as JSON text, the only serialization of the type that can be read.
+    /// Set this for queries reading `JSON` columns, which fail to deserialize otherwise. Reading paths of a `JSON` column
+    /// (i.e. `SELECT data.a.b`) doesn't need it, as those have their own types.
+    /// This is sent as the `output_format_native_write_json_as_string` setting, which servers without the `JSON` type ignore.
+    /// The native serialization of `JSON` columns, which sends each dynamic path as a separate subcolumn, isn't supported.
+    pub fn json_as_string(mut self, json_as_string: bool) -> Self {
+        self.json_as_string = json_as_string;
+        self
+    }
+
+    /// How rows of the result treat string values that aren't valid UTF-8, see [`Utf8Policy`]. Defaults to [`Utf8Policy::Bytes`].
+    /// Like [`QueryOptions::keep_empty_rows`], this only affects row streams, not [`Client::query_raw`](crate::Client::query_raw).
+    pub fn utf8(mut self, policy: Utf8Policy) -> Self {
+        self.utf8 = policy;
+        self
+    }
+
+    /// Whether an insert reads the response of the server before returning.
+    pub(crate) fn waits_for_insert(&self) -> bool {
+        self.async_insert != Some(AsyncInsertWait::Sent)
+    }
+
+    fn push_setting(mut self, name: String, value: String, important: bool) -> Self {
+        self.settings.retain(|(x, _, _)| *x != name);
+        self.settings.push((name, value, important));
+        self
+    }
+
+    /// Settings sent with the query, as name, value and whether the server must know the setting.
+    /// Settings set explicitly take precedence over those implied by other options.
+    pub(crate) fn settings(&self) -> Vec<(&str, &str, bool)> {
+        let mut settings = vec![];
+        if self.final_ {
+            settings.push(("final", "1", true));
+        }
+        if self.final_within_partitions {
+            settings.push(("do_not_merge_across_partitions_select_final", "1", true));
+        }
+        if let Some(priority) = self.server_priority {
+            let (priority, os_thread_priority) = match priority {
+                QueryPriority::High => ("1", "-5"),
+                QueryPriority::Normal => ("2", "0"),
+                QueryPriority::Low => ("3", "10"),
+            };
+            settings.push(("priority", priority, true));
+            settings.push(("os_thread_priority", os_thread_priority, false));
+        }
+        if let Some(wait) = self.async_insert {
+            settings.push(("async_insert", "1", true));
+            settings.push((
+                "wait_for_async_insert",
+                match wait {
+                    AsyncInsertWait::Flushed => "1",
+                    AsyncInsertWait::Buffered | AsyncInsertWait::Sent => "0",
+                },
+                true,
+            ));
+        }
+        if self.json_as_string {
+            settings.push(("output_format_native_write_json_as_string", "1", false));
+        }
+        settings.retain(|(name, _, _)| !self.settings.iter().any(|(x, _, _)| x == name));
+        settings.extend(
+            self.settings
+                .iter()
+                .map(|(name, value, important)| (&**name, &**value, *important)),
+        );
+        settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings() {
+        let options = QueryOptions::new()
+            .final_(true)
+            .setting("max_execution_time", 30)
+            .optional_setting("insert_deduplicate", false)
+            .setting("max_execution_time", 60)
+            .setting("final", 0);
+        assert_eq!(
+            options.settings(),
+            vec![
+                ("insert_deduplicate", "false", false),
+                ("max_execution_time", "60", true),
+                ("final", "0", true),
+            ]
+        );
+
+        let options = QueryOptions::new()
+            .priority(QueryPriority::Low)
+            .setting("os_thread_priority", 19);
+        assert_eq!(
+            options.settings(),
+            vec![("priority", "3", true), ("os_thread_priority", "19", true)]
+        );
+
+        assert!(QueryOptions::new().settings().is_empty());
+        assert_eq!(
+            QueryOptions::new().json_as_string(true).settings(),
+            vec![("output_format_native_write_json_as_string", "1", false)]
+        );
+    }
+
+    #[test]
+    fn test_async_insert_settings() {
+        let options = QueryOptions::new().async_insert(AsyncInsertWait::Flushed);
+        assert!(options.waits_for_insert());
+        assert_eq!(
+            options.settings(),
+            vec![
+                ("async_insert", "1", true),
+                ("wait_for_async_insert", "1", true)
+            ]
+        );
+        let options = QueryOptions::new()
+            .async_insert(AsyncInsertWait::Sent)
+            .setting("async_insert_busy_timeout_ms", 100);
+        assert!(!options.waits_for_insert());
+        assert_eq!(options.settings()[1], ("wait_for_async_insert", "0", true));
+        assert!(QueryOptions::new().waits_for_insert());
+    }
+
+    #[test]
+    fn test_utf8_policy() {
+        let mut block = Block::default();
+        block
+            .insert_column(
+                "s",
+                crate::Type::Array(Box::new(crate::Type::String)),
+                vec![Value::Array(vec![
+                    Value::string("ok"),
+                    Value::String(vec![b'a', 0xFF]),
+                ])],
+            )
+            .unwrap();
+
+        let mut bytes = block.clone();
+        Utf8Policy::Bytes.apply(&mut bytes).unwrap();
+        assert_eq!(bytes.column_data, block.column_data);
+
+        let mut strict = block.clone();
+        assert!(Utf8Policy::Error.apply(&mut strict).is_err());
+
+        Utf8Policy::Lossy.apply(&mut block).unwrap();
+        assert_eq!(
+            block.column_data["s"],
+            vec![Value::Array(vec![
+                Value::string("ok"),
+                Value::string("a\u{FFFD}")
+            ])]
+        );
+    }
+}
@@ -1,18 +1,47 @@
-use crate::Value;
-use compiler_tools::util::parse_str;
+use crate::{Identifier, KlickhouseError, LiteralOptions, Result, Type, Value};
 use compiler_tools::TokenParse;
 use compiler_tools_derive::token_parse;
 use std::fmt::Write;
 
+/// Parses a heredoc, i.e. `$$text$$` or `$tag$text$tag$`, whose content is taken literally.
 fn parse_heredoc(input: &str) -> Option<(&str, &str)> {
-    let (tag, remaining) = parse_str::<'$'>(input)?;
-
-    let reoccur_index = remaining.find(tag)?;
+    let tag_len = input.strip_prefix('$')?.find('$')?;
+    let tag = &input[1..1 + tag_len];
+    let valid_tag = tag.bytes().all(|x| x.is_ascii_alphanumeric() || x == b'_')
+        && !tag.starts_with(|x: char| x.is_ascii_digit());
+    if !valid_tag {
+        return None;
+    }
+    let delimiter = &input[..tag_len + 2];
+    let end = input[delimiter.len()..].find(delimiter)? + delimiter.len() * 2;
+    Some((&input[..end], &input[end..]))
+}
 
-    Some((
-        &input[..tag.len() * 2 + reoccur_index],
-        &input[tag.len() * 2 + reoccur_index..],
-    ))
+/// Parses a block comment, which can span lines and nest like in Clickhouse.
+fn parse_block_comment(input: &str) -> Option<(&str, &str)> {
+    if !input.starts_with("/*") {
+        return None;
+    }
+    let bytes = input.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        match &bytes[i..i + 2] {
+            b"/*" => {
+                depth += 1;
+                i += 2;
+            }
+            b"*/" => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return Some((&input[..i], &input[i..]));
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    None
 }
 
 #[token_parse]
@@ -24,7 +53,7 @@ enum Token<'a> {
     Heredoc(&'a str),
     #[token(regex = "--[^\n]*")]
     CommentDash(&'a str),
-    #[token(regex = "/\\*.*\\*/")]
+    #[token(parse_fn = "parse_block_comment")]
     CommentBlock(&'a str),
 
     OpeningRoundBracket = "(",
@@ -66,7 +95,8 @@ enum Token<'a> {
     CommentHashbang(&'a str),
     #[token(regex = "#[^\n]*")]
     CommentHash(&'a str),
-    #[token(regex = "[a-zA-Z_][0-9a-zA-Z_]*")]
+    // like Clickhouse, identifiers may contain `$`, which isn't a placeholder then
+    #[token(regex = "[a-zA-Z_][0-9a-zA-Z_$]*")]
     BareWord(&'a str),
     #[token(
         regex_full = "(?i)0x[0-9a-f]+(\\.[0-9a-f]*)(p[+-]?[0-9]+)|0[0-7]+|[0-9]+(\\.[0-9]+|\\.)?(e[+-]?[0-9]+)?|\\.[0-9]+(e[+-]?[0-9]+)?|inf|infinity|nan"
@@ -84,15 +114,279 @@ enum Token<'a> {
 
 /// Parses a query and replaces arguments with values
 pub fn parse_query_arguments(query: &str, arguments: &[Value]) -> String {
+    parse_query_arguments_with(query, arguments, LiteralOptions::default())
+}
+
+/// Parses a query and replaces arguments with values, rendering them according to `options`
+pub fn parse_query_arguments_with(
+    query: &str,
+    arguments: &[Value],
+    options: LiteralOptions,
+) -> String {
+    let arguments = arguments.iter().map(Some).collect::<Vec<_>>();
+    substitute_arguments(query, &arguments, &[], options, false)
+        .expect("lenient argument substitution cannot fail")
+}
+
+/// Like [`parse_query_arguments_with`], but fails if a placeholder has no matching argument (i.e. `$3` with two arguments, or `$0`), or if an argument is never referenced.
+pub fn parse_query_arguments_strict(
+    query: &str,
+    arguments: &[Value],
+    options: LiteralOptions,
+) -> Result<String> {
+    let arguments = arguments.iter().map(Some).collect::<Vec<_>>();
+    substitute_arguments(query, &arguments, &[], options, true)
+}
+
+/// Parses a `{name:Type}` placeholder at the start of `tokens`, returning the name, the type and the number of tokens it spans.
+fn parse_typed_placeholder<'a>(tokens: &[Token<'a>]) -> Option<(&'a str, String, usize)> {
+    let mut rest = tokens
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, x)| !matches!(x, Token::Whitespace(_)));
+    let (_, Token::BareWord(name)) = rest.next()? else {
+        return None;
+    };
+    let (colon, Token::Colon) = rest.next()? else {
+        return None;
+    };
+    let (end, _) = rest.find(|(_, x)| matches!(x, Token::ClosingCurlyBrace))?;
+    let type_ = tokens[colon + 1..end]
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<String>();
+    let type_ = type_.trim();
+    if type_.is_empty() {
+        return None;
+    }
+    Some((name, type_.to_string(), end + 1))
+}
+
+/// The `{name:Type}` placeholders in `query`, as names and types.
+pub(crate) fn typed_placeholders(query: &str) -> Vec<(String, String)> {
     let mut tokenizer = Tokenizer::new(query);
-    let mut out = String::with_capacity(query.len() + 100);
+    let mut tokens = vec![];
     while let Some(token) = tokenizer.next() {
-        match token.token {
+        tokens.push(token.token);
+    }
+    let mut out = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        match parse_typed_placeholder(&tokens[i..]) {
+            Some((name, type_, len)) if tokens[i] == Token::OpeningCurlyBrace => {
+                out.push((name.to_string(), type_));
+                i += len;
+            }
+            _ => i += 1,
+        }
+    }
+    out
+}
+
+/// The declared type of a `{name:Type}` placeholder other than `Identifier`, which `value` must be valid for.
+fn placeholder_type(name: &str, type_: &str, value: &Value) -> Result<Type> {
+    let parsed = type_.parse::<Type>().map_err(|e| {
+        KlickhouseError::QueryArgumentError(format!(
+            "parameter {name} has an invalid type {type_}: {e}"
+        ))
+    })?;
+    parsed.validate_value(value).map_err(|e| {
+        KlickhouseError::QueryArgumentError(format!(
+            "parameter {name} does not match its type {type_}: {e}"
+        ))
+    })?;
+    Ok(parsed)
+}
+
+/// The name given by the argument of an `{name:Identifier}` placeholder.
+fn identifier_argument<'a>(name: &str, value: &'a Value) -> Result<&'a str> {
+    match value {
+        Value::String(bytes) => std::str::from_utf8(bytes).map_err(|_| {
+            KlickhouseError::QueryArgumentError(format!(
+                "parameter {name} is not a valid UTF-8 identifier"
+            ))
+        }),
+        _ => Err(KlickhouseError::QueryArgumentError(format!(
+            "parameter {name} of type Identifier must be a string"
+        ))),
+    }
+}
+
+/// Renders a named argument for a `{name:Type}` placeholder. `Identifier` arguments are quoted as names rather than string literals,
+/// like for Clickhouse's server-side parameters. Any other argument must be valid for the declared type, which the server would otherwise never see.
+fn typed_literal(
+    name: &str,
+    type_: &str,
+    value: &Value,
+    options: LiteralOptions,
+) -> Result<String> {
+    if type_ == "Identifier" {
+        return Ok(Identifier::new(identifier_argument(name, value)?).to_string());
+    }
+    placeholder_type(name, type_, value)?;
+    Ok(value.literal(options).to_string())
+}
+
+/// Renders a server-side parameter for a `{name:Type}` placeholder, in the text format the server parses it with.
+/// `Identifier` arguments are sent as the name itself.
+pub(crate) fn typed_param(name: &str, type_: &str, value: &Value) -> Result<String> {
+    if type_ == "Identifier" {
+        return Ok(identifier_argument(name, value)?.to_string());
+    }
+    value.param_text(&placeholder_type(name, type_, value)?)
+}
+
+/// Tracks whether tokens are within a `LIMIT`/`OFFSET` count or a `SETTINGS` clause.
+#[derive(Default)]
+struct LiteralClauses {
+    /// Bracket depth
+    depth: usize,
+    /// Bracket depth at the start of the current clause, and whether it is `SETTINGS`
+    current: Option<(usize, bool)>,
+}
+
+impl LiteralClauses {
+    fn visit(&mut self, token: Token<'_>) {
+        match token {
+            Token::OpeningRoundBracket => self.depth += 1,
+            Token::ClosingRoundBracket => {
+                self.depth = self.depth.saturating_sub(1);
+                if self.current.is_some_and(|(depth, _)| self.depth < depth) {
+                    self.current = None;
+                }
+            }
+            Token::Semicolon => self.current = None,
+            Token::BareWord(word) => {
+                let word = word.to_ascii_uppercase();
+                match (&*word, self.current) {
+                    ("LIMIT" | "OFFSET", _) => self.current = Some((self.depth, false)),
+                    ("SETTINGS", _) => self.current = Some((self.depth, true)),
+                    ("FORMAT" | "VALUES" | "SELECT", _) => self.current = None,
+                    // setting names are words too
+                    (_, Some((_, true))) => (),
+                    _ => self.current = None,
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Replaces placeholders with arguments. `None` arguments are absent: their placeholders are left as-is, or rejected if `strict`.
+/// In strict mode, absent arguments are allowed to be unused.
+///
+/// Named arguments replace `$name` and `{name:Type}` placeholders. In lenient mode, unknown `$name` placeholders are left as-is.
+/// Unknown `{name:Type}` placeholders are always left as-is, as they may be server-side parameters.
+pub(crate) fn substitute_arguments(
+    query: &str,
+    arguments: &[Option<&Value>],
+    named: &[(&str, &Value)],
+    options: LiteralOptions,
+    strict: bool,
+) -> Result<String> {
+    let mut tokenizer = Tokenizer::new(query);
+    let mut tokens = vec![];
+    while let Some(token) = tokenizer.next() {
+        tokens.push(token.token);
+    }
+    let mut out = String::with_capacity(query.len() + 100);
+    let mut used = arguments.iter().map(|x| x.is_none()).collect::<Vec<_>>();
+    let mut used_named = vec![false; named.len()];
+    let find_named = |name: &str| named.iter().position(|(x, _)| *x == name);
+    let mut clauses = LiteralClauses::default();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        i += 1;
+        clauses.visit(token);
+        // these clauses only take literals, so large integers can't be quoted with a cast
+        let options = match clauses.current {
+            Some(_) => LiteralOptions {
+                quote_64bit_integers: false,
+            },
+            None => options,
+        };
+        match token {
             Token::EscapedDollarSign => write!(&mut out, "{}", Token::DollarSign).unwrap(),
             Token::ClientArgument(argument) => match argument[1..].parse::<usize>() {
                 Ok(index) if index <= arguments.len() && index > 0 => {
-                    write!(&mut out, "{}", arguments[index - 1]).unwrap()
+                    used[index - 1] = true;
+                    match arguments[index - 1] {
+                        Some(value) => write!(&mut out, "{}", value.literal(options)).unwrap(),
+                        None if strict => {
+                            return Err(KlickhouseError::QueryArgumentError(format!(
+                                "placeholder {argument} refers to an absent optional argument"
+                            )))
+                        }
+                        None => write!(&mut out, "{}", token).unwrap(),
+                    }
+                }
+                _ if strict => {
+                    return Err(KlickhouseError::QueryArgumentError(format!(
+                        "placeholder {argument} is not bound, {} argument(s) given",
+                        arguments.len()
+                    )))
+                }
+                _ => write!(&mut out, "{}", token).unwrap(),
+            },
+            Token::DollarSign => match tokens.get(i) {
+                Some(Token::BareWord(name)) => match find_named(name) {
+                    Some(index) => {
+                        used_named[index] = true;
+                        write!(&mut out, "{}", named[index].1.literal(options)).unwrap();
+                        i += 1;
+                    }
+                    None if strict => {
+                        return Err(KlickhouseError::QueryArgumentError(format!(
+                            "placeholder ${name} is not bound"
+                        )))
+                    }
+                    None => write!(&mut out, "{}", token).unwrap(),
+                },
+                _ => write!(&mut out, "{}", token).unwrap(),
+            },
+            Token::OpeningCurlyBrace => {
+                let placeholder = parse_typed_placeholder(&tokens[i - 1..])
+                    .and_then(|(name, type_, len)| Some((find_named(name)?, type_, len)));
+                match placeholder {
+                    Some((index, type_, len)) => {
+                        used_named[index] = true;
+                        let (name, value) = named[index];
+                        out.push_str(&typed_literal(name, &type_, value, options)?);
+                        i += len - 1;
+                    }
+                    None => write!(&mut out, "{}", token).unwrap(),
                 }
+            }
+            t => write!(&mut out, "{t}").unwrap(),
+        }
+    }
+    if strict {
+        if let Some(unused) = used.iter().position(|x| !*x) {
+            return Err(KlickhouseError::QueryArgumentError(format!(
+                "argument ${} is never used",
+                unused + 1
+            )));
+        }
+        if let Some(unused) = used_named.iter().position(|x| !*x) {
+            return Err(KlickhouseError::QueryArgumentError(format!(
+                "parameter {} is never used",
+                named[unused].0
+            )));
+        }
+    }
+    Ok(out)
+}
+
+/// Shifts every placeholder `$n` (n > 0) in `query` to `$(n + offset)`
+pub(crate) fn offset_query_arguments(query: &str, offset: usize) -> String {
+    let mut tokenizer = Tokenizer::new(query);
+    let mut out = String::with_capacity(query.len());
+    while let Some(token) = tokenizer.next() {
+        match token.token {
+            Token::ClientArgument(argument) => match argument[1..].parse::<usize>() {
+                Ok(index) if index > 0 => write!(&mut out, "${}", index + offset).unwrap(),
                 _ => write!(&mut out, "{}", token.token).unwrap(),
             },
             t => write!(&mut out, "{t}").unwrap(),
@@ -123,6 +417,95 @@ pub fn split_query_statements(query: &str) -> Vec<String> {
         .collect()
 }
 
+/// Fills the one `input()` call of `query` with the table structure `structure`, i.e. `x UInt64, y String`,
+/// and appends `FORMAT Native` if the query has no `FORMAT` clause.
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) fn fill_input_structure(query: &str, structure: &str) -> Result<String> {
+    let mut tokenizer = Tokenizer::new(query);
+    let mut tokens = vec![];
+    while let Some(token) = tokenizer.next() {
+        if !matches!(
+            token.token,
+            Token::Whitespace(_) | Token::CommentDash(_) | Token::CommentBlock(_)
+        ) {
+            tokens.push(token.token);
+        }
+    }
+    let is_call = |i: usize| {
+        matches!(tokens[i], Token::BareWord(x) if x.eq_ignore_ascii_case("input"))
+            && tokens.get(i + 1) == Some(&Token::OpeningRoundBracket)
+    };
+    let calls = (0..tokens.len()).filter(|i| is_call(*i)).count();
+    // a `FORMAT <name>` clause ends the statement, anything else named `format` is i.e. a column or function
+    let statement = match tokens.iter().rposition(|x| *x != Token::Semicolon) {
+        Some(last) => &tokens[..=last],
+        None => &[][..],
+    };
+    let has_format = matches!(
+        statement,
+        [.., Token::BareWord(format), Token::BareWord(_)] if format.eq_ignore_ascii_case("format")
+    );
+
+    let mut tokenizer = Tokenizer::new(query);
+    let mut out = String::with_capacity(query.len() + structure.len() + 16);
+    let mut filled = false;
+    let mut call = false;
+    while let Some(token) = tokenizer.next() {
+        match token.token {
+            Token::BareWord(x) if x.eq_ignore_ascii_case("input") => call = true,
+            Token::Whitespace(_) => (),
+            Token::OpeningRoundBracket if call && calls == 1 => {
+                out.push('(');
+                let next = std::iter::from_fn(|| tokenizer.next())
+                    .find(|x| !matches!(x.token, Token::Whitespace(_)));
+                if next.map(|x| x.token) != Some(Token::ClosingRoundBracket) {
+                    break;
+                }
+                write!(
+                    &mut out,
+                    "'{}')",
+                    structure.replace('\\', "\\\\").replace('\'', "\\'")
+                )
+                .unwrap();
+                filled = true;
+                call = false;
+                continue;
+            }
+            _ => call = false,
+        }
+        write!(&mut out, "{}", token.token).unwrap();
+    }
+    if !filled {
+        return Err(KlickhouseError::QueryArgumentError(format!(
+            "expected a single `input()` call without arguments in query: {query}"
+        )));
+    }
+    if !has_format {
+        out.push_str(" FORMAT Native");
+    }
+    Ok(out)
+}
+
+/// Whether a statement only consists of comments, whitespace and semicolons
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) fn is_blank_statement(query: &str) -> bool {
+    let mut tokenizer = Tokenizer::new(query);
+    while let Some(token) = tokenizer.next() {
+        if !matches!(
+            token.token,
+            Token::Whitespace(_)
+                | Token::CommentDash(_)
+                | Token::CommentBlock(_)
+                | Token::CommentHash(_)
+                | Token::CommentHashbang(_)
+                | Token::Semicolon
+        ) {
+            return false;
+        }
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,6 +623,277 @@ mod tests {
         );
     }
 
+    #[test]
+    fn arg_quoted_integer_tests() {
+        let options = LiteralOptions {
+            quote_64bit_integers: true,
+        };
+        assert_eq!(
+            parse_query_arguments_with(
+                "SELECT $1, $2, $3, $4",
+                &[
+                    Value::UInt64(u64::MAX),
+                    Value::Int64(-5),
+                    Value::UInt32(7),
+                    Value::Array(vec![Value::UInt64(1), Value::UInt64(2)])
+                ],
+                options,
+            ),
+            "SELECT toUInt64('18446744073709551615'), toInt64('-5'), 7, [toUInt64('1'),toUInt64('2')]"
+        );
+    }
+
+    #[test]
+    fn arg_nested_tests() {
+        assert_eq!(
+            parse_query_arguments(
+                "SELECT $1, $2",
+                &[
+                    Value::Array(vec![
+                        Value::string("a',\\"),
+                        Value::String(vec![0, 0xFF, b']'])
+                    ]),
+                    Value::Map(
+                        vec![Value::string("k:}")],
+                        vec![Value::Tuple(vec![Value::Null, Value::string("(\n)")])]
+                    ),
+                ]
+            ),
+            "SELECT ['a\\',\\\\','\\0\\xFF]'], {'k:}':(NULL,'(\\n)')}"
+        );
+    }
+
+    #[test]
+    fn arg_quoted_context_tests() {
+        let args = [Value::UInt8(7), Value::UInt8(8)];
+        let substitute =
+            |query| parse_query_arguments_strict(query, &args, LiteralOptions::default()).unwrap();
+        assert_eq!(
+            substitute("SELECT `a$1`, \"b$2\", 'it''s $1', 'x\\'$2', $1, $2"),
+            "SELECT `a$1`, \"b$2\", 'it''s $1', 'x\\'$2', 7, 8"
+        );
+        assert_eq!(
+            substitute("SELECT $$it's $1$$, $tag$ $2 $ta$ $tag$, $$$1$$, $1, $2"),
+            "SELECT $$it's $1$$, $tag$ $2 $ta$ $tag$, $$$1$$, 7, 8"
+        );
+        assert_eq!(
+            substitute("SELECT /* $1 */ $1 /* $2\n /* nested $2 */ */ + $2 -- $1"),
+            "SELECT /* $1 */ 7 /* $2\n /* nested $2 */ */ + 8 -- $1"
+        );
+        // `$` is part of identifiers
+        assert_eq!(substitute("SELECT col$1, $1 + $2"), "SELECT col$1, 7 + 8");
+    }
+
+    #[test]
+    fn arg_literal_clause_tests() {
+        let options = LiteralOptions {
+            quote_64bit_integers: true,
+        };
+        let args = [
+            Value::UInt64(10),
+            Value::UInt64(20),
+            Value::UInt64(30),
+            Value::string("random"),
+        ];
+        assert_eq!(
+            parse_query_arguments_strict(
+                "SELECT * FROM t WHERE id = $1 LIMIT $2 OFFSET $3 SETTINGS max_threads = $1, load_balancing = $4",
+                &args,
+                options,
+            )
+            .unwrap(),
+            "SELECT * FROM t WHERE id = toUInt64('10') LIMIT 20 OFFSET 30 SETTINGS max_threads = 10, load_balancing = 'random'"
+        );
+        assert_eq!(
+            parse_query_arguments_strict(
+                "SELECT * FROM (SELECT * FROM t LIMIT $1) WHERE id = $2 LIMIT $3 BY id, $4",
+                &args,
+                options,
+            )
+            .unwrap(),
+            "SELECT * FROM (SELECT * FROM t LIMIT 10) WHERE id = toUInt64('20') LIMIT 30 BY id, 'random'"
+        );
+    }
+
+    #[test]
+    fn arg_strict_tests() {
+        let args = [Value::string("te'st"), Value::UInt32(3232)];
+        assert_eq!(
+            parse_query_arguments_strict(
+                "SELECT a FROM x WHERE x.y = $1 AND x.z = $2 AND x.w = '$3'",
+                &args,
+                LiteralOptions::default()
+            )
+            .unwrap(),
+            "SELECT a FROM x WHERE x.y = 'te\\'st' AND x.z = 3232 AND x.w = '$3'"
+        );
+        assert!(matches!(
+            parse_query_arguments_strict(
+                "SELECT a FROM x WHERE x.y = $1 AND x.z = $3",
+                &args,
+                LiteralOptions::default()
+            ),
+            Err(KlickhouseError::QueryArgumentError(_))
+        ));
+        assert!(matches!(
+            parse_query_arguments_strict("SELECT $0, $1, $2", &args, LiteralOptions::default()),
+            Err(KlickhouseError::QueryArgumentError(_))
+        ));
+        assert!(matches!(
+            parse_query_arguments_strict("SELECT $2", &args, LiteralOptions::default()),
+            Err(KlickhouseError::QueryArgumentError(_))
+        ));
+    }
+
+    #[test]
+    fn named_arg_tests() {
+        let id = Value::UInt64(7);
+        let name = Value::string("o'brien");
+        let table = Value::string("ev`ents");
+        let named = [("id", &id), ("name", &name), ("table", &table)];
+        assert_eq!(
+            substitute_arguments(
+                "SELECT '$id', `{id:UInt64}` FROM {table:Identifier} WHERE id = {id: UInt64} AND name = $name AND m = {'k':1}",
+                &[],
+                &named,
+                LiteralOptions::default(),
+                true,
+            )
+            .unwrap(),
+            "SELECT '$id', `{id:UInt64}` FROM `ev\\`ents` WHERE id = 7 AND name = 'o\\'brien' AND m = {'k':1}"
+        );
+        assert_eq!(
+            substitute_arguments(
+                "SELECT $id, {other:String}, $other",
+                &[],
+                &named[..1],
+                LiteralOptions::default(),
+                false,
+            )
+            .unwrap(),
+            "SELECT 7, {other:String}, $other"
+        );
+        assert!(matches!(
+            substitute_arguments(
+                "SELECT $other",
+                &[],
+                &named,
+                LiteralOptions::default(),
+                true
+            ),
+            Err(KlickhouseError::QueryArgumentError(_))
+        ));
+        assert!(matches!(
+            substitute_arguments("SELECT $id", &[], &named, LiteralOptions::default(), true),
+            Err(KlickhouseError::QueryArgumentError(_))
+        ));
+        assert!(matches!(
+            substitute_arguments(
+                "SELECT 1 FROM {id:Identifier}",
+                &[],
+                &named[..1],
+                LiteralOptions::default(),
+                true
+            ),
+            Err(KlickhouseError::QueryArgumentError(_))
+        ));
+        assert_eq!(
+            substitute_arguments(
+                "SELECT {name:Nullable(String)}, {id:LowCardinality(UInt64)}",
+                &[],
+                &named,
+                LiteralOptions::default(),
+                false
+            )
+            .unwrap(),
+            "SELECT 'o\\'brien', 7"
+        );
+        assert!(matches!(
+            substitute_arguments(
+                "SELECT {id:String}",
+                &[],
+                &named[..1],
+                LiteralOptions::default(),
+                true
+            ),
+            Err(KlickhouseError::QueryArgumentError(_))
+        ));
+        assert!(matches!(
+            substitute_arguments(
+                "SELECT {id:NotAType}",
+                &[],
+                &named[..1],
+                LiteralOptions::default(),
+                true
+            ),
+            Err(KlickhouseError::QueryArgumentError(_))
+        ));
+    }
+
+    #[test]
+    fn offset_tests() {
+        assert_eq!(
+            offset_query_arguments("AND x = $1 OR y = $2 OR z = '$1' OR w = $0", 3),
+            "AND x = $4 OR y = $5 OR z = '$1' OR w = $0"
+        );
+    }
+
+    #[test]
+    fn blank_statement_tests() {
+        assert!(is_blank_statement("-- nothing here\n;"));
+        assert!(is_blank_statement("/* a */ # b\n"));
+        assert!(!is_blank_statement("-- create\nCREATE TABLE x (a UInt8);"));
+        assert!(!is_blank_statement("SELECT '--';"));
+    }
+
+    #[test]
+    fn input_structure_tests() {
+        assert_eq!(
+            fill_input_structure(
+                "INSERT INTO t SELECT x * 2, upper(y) FROM input ( ) WHERE y != 'input()'",
+                "`x` UInt64, `y` String",
+            )
+            .unwrap(),
+            "INSERT INTO t SELECT x * 2, upper(y) FROM input ('`x` UInt64, `y` String') WHERE y != 'input()' FORMAT Native"
+        );
+        assert_eq!(
+            fill_input_structure(
+                "INSERT INTO t SELECT * FROM input() FORMAT Native",
+                "a Enum8('b' = 1)"
+            )
+            .unwrap(),
+            "INSERT INTO t SELECT * FROM input('a Enum8(\\'b\\' = 1)') FORMAT Native"
+        );
+        assert!(
+            fill_input_structure("INSERT INTO t SELECT * FROM input('x UInt8')", "x UInt8")
+                .is_err()
+        );
+        assert!(fill_input_structure(
+            "INSERT INTO t SELECT * FROM input() UNION ALL SELECT * FROM input()",
+            "x UInt8"
+        )
+        .is_err());
+        assert!(fill_input_structure("INSERT INTO t VALUES", "x UInt8").is_err());
+
+        // only a trailing `FORMAT <name>` counts as a format clause
+        assert_eq!(
+            fill_input_structure(
+                "INSERT INTO t SELECT format('{}-{}', a, b) AS format FROM input()",
+                "a String, b String"
+            )
+            .unwrap(),
+            "INSERT INTO t SELECT format('{}-{}', a, b) AS format FROM input('a String, b String') FORMAT Native"
+        );
+        assert_eq!(
+            fill_input_structure(
+                "INSERT INTO t SELECT * FROM input() format RowBinary; -- comment",
+                "a String"
+            )
+            .unwrap(),
+            "INSERT INTO t SELECT * FROM input('a String') format RowBinary; -- comment"
+        );
+    }
+
     #[test]
     fn split_tests() {
         assert_eq!(split_query_statements("X;B",), vec!["X;", "B"]);
@@ -0,0 +1,271 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use log::error;
+use tokio::{
+    fs::File,
+    io::{AsyncWriteExt, BufReader, BufWriter},
+    sync::mpsc::{self, error::TrySendError},
+};
+use uuid::Uuid;
+
+use crate::{block::Block, protocol::DBMS_TCP_PROTOCOL_VERSION, KlickhouseError, Result};
+
+/// What to do when the consumer of a query's results falls behind and its block buffer (see [`ClientOptions::query_buffer_size`](crate::ClientOptions::query_buffer_size)) is full.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SlowConsumerPolicy {
+    /// Wait for the consumer to catch up. This stalls the whole connection, including any queries queued behind this one.
+    #[default]
+    Wait,
+    /// Stop delivering the query's results. The consumer receives a [`KlickhouseError::SlowConsumer`] after the already buffered blocks.
+    Abort,
+    /// Spill excess blocks to a temporary file in the given directory. They are delivered in order once the consumer catches up.
+    Spill(PathBuf),
+}
+
+enum SpillItem {
+    Disk,
+    Memory(Result<Block>),
+}
+
+struct Spill {
+    writer: BufWriter<File>,
+    queue: mpsc::UnboundedSender<SpillItem>,
+    /// Number of items spilled but not yet handed to the consumer
+    pending: Arc<AtomicUsize>,
+}
+
+impl Spill {
+    async fn create(dir: &Path, sender: mpsc::Sender<Result<Block>>) -> Result<Self> {
+        let path = dir.join(format!("klickhouse-spill-{}.bin", Uuid::new_v4()));
+        let writer = File::create(&path).await?;
+        let reader = File::open(&path).await?;
+        let (queue, receiver) = mpsc::unbounded_channel();
+        let pending = Arc::new(AtomicUsize::new(0));
+        tokio::spawn(forward_spilled(
+            path,
+            reader,
+            receiver,
+            sender,
+            pending.clone(),
+        ));
+        Ok(Self {
+            writer: BufWriter::new(writer),
+            queue,
+            pending,
+        })
+    }
+
+    async fn push(&mut self, item: Result<Block>) -> Result<()> {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        match item {
+            Ok(block) => {
+                block
+                    .write(&mut self.writer, DBMS_TCP_PROTOCOL_VERSION)
+                    .await?;
+                self.writer.flush().await?;
+                self.queue.send(SpillItem::Disk).ok();
+            }
+            Err(e) => {
+                self.queue.send(SpillItem::Memory(Err(e))).ok();
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn forward_spilled(
+    path: PathBuf,
+    reader: File,
+    mut queue: mpsc::UnboundedReceiver<SpillItem>,
+    sender: mpsc::Sender<Result<Block>>,
+    pending: Arc<AtomicUsize>,
+) {
+    let mut reader = BufReader::new(reader);
+    while let Some(item) = queue.recv().await {
+        let item = match item {
+            SpillItem::Disk => Block::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION).await,
+            SpillItem::Memory(item) => item,
+        };
+        let failed = item.is_err();
+        if sender.send(item).await.is_err() || failed {
+            break;
+        }
+        pending.fetch_sub(1, Ordering::SeqCst);
+    }
+    drop(reader);
+    if let Err(e) = tokio::fs::remove_file(&path).await {
+        error!("failed to remove spill file {}: {:?}", path.display(), e);
+    }
+}
+
+/// Delivers the result blocks of the executing query to its consumer according to a [`SlowConsumerPolicy`].
+pub(crate) struct QuerySink {
+    sender: mpsc::Sender<Result<Block>>,
+    policy: SlowConsumerPolicy,
+    aborted: bool,
+    spill: Option<Spill>,
+}
+
+impl QuerySink {
+    pub fn new(sender: mpsc::Sender<Result<Block>>, policy: SlowConsumerPolicy) -> Self {
+        Self {
+            sender,
+            policy,
+            aborted: false,
+            spill: None,
+        }
+    }
+
+    pub async fn send(&mut self, item: Result<Block>) {
+        if self.aborted {
+            return;
+        }
+        match &self.policy {
+            SlowConsumerPolicy::Wait => {
+                self.sender.send(item).await.ok();
+            }
+            SlowConsumerPolicy::Abort => {
+                if let Err(TrySendError::Full(_)) = self.sender.try_send(item) {
+                    self.abort(format!(
+                        "result buffer of {} blocks is full",
+                        self.sender.max_capacity()
+                    ))
+                    .await;
+                }
+            }
+            SlowConsumerPolicy::Spill(dir) => {
+                let spilling = self
+                    .spill
+                    .as_ref()
+                    .map(|x| x.pending.load(Ordering::SeqCst) > 0)
+                    .unwrap_or_default();
+                // once spilling, everything goes through the spill file to preserve ordering
+                let item = if spilling {
+                    item
+                } else {
+                    match self.sender.try_send(item) {
+                        Ok(()) | Err(TrySendError::Closed(_)) => return,
+                        Err(TrySendError::Full(item)) => item,
+                    }
+                };
+                if self.spill.is_none() {
+                    match Spill::create(dir, self.sender.clone()).await {
+                        Ok(spill) => self.spill = Some(spill),
+                        Err(e) => {
+                            self.abort(format!("failed to create spill file: {e}"))
+                                .await;
+                            return;
+                        }
+                    }
+                }
+                if let Err(e) = self.spill.as_mut().unwrap().push(item).await {
+                    self.abort(format!("failed to spill block: {e}")).await;
+                }
+            }
+        }
+    }
+
+    /// Delivers `error` after any blocks sent so far, and drops everything sent afterwards.
+    pub async fn fail(&mut self, error: KlickhouseError) {
+        self.send(Err(error)).await;
+        self.aborted = true;
+    }
+
+    async fn abort(&mut self, message: String) {
+        self.aborted = true;
+        let error = KlickhouseError::SlowConsumer(message);
+        if let Some(spill) = &mut self.spill {
+            spill.queue.send(SpillItem::Memory(Err(error))).ok();
+        } else {
+            let sender = self.sender.clone();
+            tokio::spawn(async move { sender.send(Err(error)).await.ok() });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Type, Value};
+
+    fn block(i: u32) -> Block {
+        let mut block = Block::default();
+        block
+            .insert_column(
+                "x",
+                Type::LowCardinality(Box::new(Type::String)),
+                vec![Value::string(i.to_string()), Value::string("y")],
+            )
+            .unwrap();
+        block
+            .insert_column("i", Type::UInt32, vec![Value::UInt32(i); 2])
+            .unwrap();
+        block
+    }
+
+    fn index(block: &Block) -> u32 {
+        match block.column_data.get("i").unwrap()[0] {
+            Value::UInt32(x) => x,
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spill() {
+        let (sender, mut receiver) = mpsc::channel(2);
+        let mut sink = QuerySink::new(sender, SlowConsumerPolicy::Spill(std::env::temp_dir()));
+        for i in 0..10 {
+            sink.send(Ok(block(i))).await;
+        }
+        drop(sink);
+        for i in 0..10 {
+            let received = receiver.recv().await.unwrap().unwrap();
+            assert_eq!(index(&received), i);
+            assert_eq!(received.column_data, block(i).column_data);
+        }
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_abort() {
+        let (sender, mut receiver) = mpsc::channel(2);
+        let mut sink = QuerySink::new(sender, SlowConsumerPolicy::Abort);
+        for i in 0..5 {
+            sink.send(Ok(block(i))).await;
+        }
+        drop(sink);
+        assert_eq!(index(&receiver.recv().await.unwrap().unwrap()), 0);
+        assert_eq!(index(&receiver.recv().await.unwrap().unwrap()), 1);
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            Err(KlickhouseError::SlowConsumer(_))
+        ));
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fail() {
+        let (sender, mut receiver) = mpsc::channel(4);
+        let mut sink = QuerySink::new(sender, SlowConsumerPolicy::Wait);
+        sink.send(Ok(block(0))).await;
+        sink.fail(KlickhouseError::SchemaChanged {
+            expected: "i UInt32".to_string(),
+            received: "i String".to_string(),
+        })
+        .await;
+        sink.send(Ok(block(1))).await;
+        drop(sink);
+        assert_eq!(index(&receiver.recv().await.unwrap().unwrap()), 0);
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            Err(KlickhouseError::SchemaChanged { .. })
+        ));
+        assert!(receiver.recv().await.is_none());
+    }
+}
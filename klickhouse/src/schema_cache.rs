@@ -0,0 +1,344 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures_util::StreamExt;
+use indexmap::IndexMap;
+
+use crate::{
+    Client, ExpectedColumn, KlickhouseError, QualifiedTable, QueryBuilder, RawRow, Result, Row,
+    Type, UnitValue,
+};
+
+/// Insertable columns of a table in order, with their types. See [`Client::describe_table`].
+pub type TableSchema = Arc<IndexMap<String, Type>>;
+
+/// Entries by table name, with the time they were fetched.
+struct TtlMap<V>(Mutex<HashMap<String, (Instant, V)>>);
+
+impl<V> Default for TtlMap<V> {
+    fn default() -> Self {
+        Self(Mutex::default())
+    }
+}
+
+impl<V: Clone> TtlMap<V> {
+    fn get(&self, table: &str, ttl: Duration) -> Option<V> {
+        let entries = self.0.lock().unwrap();
+        let (fetched, value) = entries.get(table)?;
+        if fetched.elapsed() >= ttl {
+            return None;
+        }
+        Some(value.clone())
+    }
+
+    fn insert(&self, table: &str, value: V) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(table.to_string(), (Instant::now(), value));
+    }
+
+    fn invalidate(&self, table: &str) {
+        self.0.lock().unwrap().remove(table);
+    }
+
+    fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+/// Schemas fetched by [`Client::describe_table`] and engines fetched by [`Client::table_engine`], shared by all clones of a `Client`.
+#[derive(Default)]
+pub(crate) struct SchemaCache {
+    schemas: TtlMap<TableSchema>,
+    engines: TtlMap<String>,
+}
+
+impl SchemaCache {
+    fn get(&self, table: &str, ttl: Duration) -> Option<TableSchema> {
+        self.schemas.get(table, ttl)
+    }
+
+    fn insert(&self, table: &str, schema: TableSchema) {
+        self.schemas.insert(table, schema);
+    }
+
+    fn invalidate(&self, table: &str) {
+        self.schemas.invalidate(table);
+        self.engines.invalidate(table);
+    }
+
+    fn clear(&self) {
+        self.schemas.clear();
+        self.engines.clear();
+    }
+}
+
+/// Differences between the columns of a [`Row`] and a table, as returned by [`Client::verify_schema`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// Columns of the row that the table doesn't have.
+    pub missing: Vec<String>,
+    /// Columns of the table that the row doesn't have, with their types.
+    pub extra: Vec<(String, Type)>,
+    /// Columns of both whose type in the table can't be read into the row's field.
+    pub mismatched: Vec<ColumnMismatch>,
+}
+
+/// A column whose type in the table can't be read into the Rust type of the row's field. See [`SchemaDiff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnMismatch {
+    pub name: String,
+    pub rust_type: &'static str,
+    pub column_type: Type,
+}
+
+impl SchemaDiff {
+    /// Compares the columns of a row, in the form of [`Row::column_types`], to the columns of a table.
+    pub fn compare(columns: &[ExpectedColumn], schema: &IndexMap<String, Type>) -> Self {
+        let mut diff = SchemaDiff::default();
+        for column in columns {
+            match schema.get(&*column.name) {
+                None => diff.missing.push(column.name.to_string()),
+                Some(type_) if !column.accepts(type_) => diff.mismatched.push(ColumnMismatch {
+                    name: column.name.to_string(),
+                    rust_type: column.rust_type,
+                    column_type: type_.clone(),
+                }),
+                Some(_) => (),
+            }
+        }
+        for (name, type_) in schema {
+            if !columns.iter().any(|x| x.name == name.as_str()) {
+                diff.extra.push((name.clone(), type_.clone()));
+            }
+        }
+        diff
+    }
+
+    /// Whether the row and the table have the same columns, of compatible types.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "schemas match");
+        }
+        let mut parts = vec![];
+        if !self.missing.is_empty() {
+            parts.push(format!("missing columns: {}", self.missing.join(", ")));
+        }
+        if !self.extra.is_empty() {
+            let extra = self
+                .extra
+                .iter()
+                .map(|(name, type_)| format!("{name} {type_}"))
+                .collect::<Vec<_>>();
+            parts.push(format!("extra columns: {}", extra.join(", ")));
+        }
+        for mismatch in &self.mismatched {
+            parts.push(format!(
+                "column {} of type {} can't be read into {}",
+                mismatch.name, mismatch.column_type, mismatch.rust_type
+            ));
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// Whether `SELECT ... FINAL` is allowed on a table with `engine`: tables of the MergeTree family,
+/// and tables forwarding reads to other tables, whose engines aren't checked.
+fn supports_final(engine: &str) -> bool {
+    engine.ends_with("MergeTree")
+        || matches!(
+            engine,
+            "Distributed" | "Merge" | "Buffer" | "MaterializedView"
+        )
+}
+
+impl Client {
+    /// Fetches the insertable columns of `table` (that is, excluding `MATERIALIZED` and `ALIAS` columns) in order, with their types, via `DESCRIBE TABLE`.
+    /// `table` is interpolated as-is and may be qualified with a database.
+    ///
+    /// Results are cached for [`ClientOptions::schema_cache_ttl`](crate::ClientOptions::schema_cache_ttl) if set. After altering a table,
+    /// invalidate it with [`Client::invalidate_schema`].
+    pub async fn describe_table(&self, table: &str) -> Result<TableSchema> {
+        if let Some(ttl) = self.schema_cache_ttl {
+            if let Some(schema) = self.schema_cache.get(table, ttl) {
+                return Ok(schema);
+            }
+        }
+        let mut rows = self
+            .query::<RawRow>(format!("DESCRIBE TABLE {table}"))
+            .await?;
+        let mut schema = IndexMap::new();
+        while let Some(row) = rows.next().await {
+            let mut row = row?;
+            let default_type: String = row.try_get("default_type")?;
+            if default_type == "MATERIALIZED" || default_type == "ALIAS" {
+                continue;
+            }
+            let name: String = row.try_get("name")?;
+            let type_: String = row.try_get("type")?;
+            schema.insert(name, type_.parse::<Type>()?);
+        }
+        let schema = Arc::new(schema);
+        if self.schema_cache_ttl.is_some() {
+            self.schema_cache.insert(table, schema.clone());
+        }
+        Ok(schema)
+    }
+
+    /// Fetches the engine of `table` from `system.tables`, i.e. `ReplacingMergeTree`. Results are cached like those of [`Client::describe_table`],
+    /// by the name `table` is displayed with.
+    pub async fn table_engine(&self, table: impl Into<QualifiedTable>) -> Result<String> {
+        let table = table.into();
+        let key = table.to_string();
+        if let Some(ttl) = self.schema_cache_ttl {
+            if let Some(engine) = self.schema_cache.engines.get(&key, ttl) {
+                return Ok(engine);
+            }
+        }
+        let query = match &table.database {
+            Some(database) => QueryBuilder::new(
+                "SELECT engine FROM system.tables WHERE database = $1 AND name = $2",
+            )
+            .arg(database.as_str()),
+            None => QueryBuilder::new(
+                "SELECT engine FROM system.tables WHERE database = currentDatabase() AND name = $1",
+            ),
+        };
+        let engine = self
+            .query_opt::<UnitValue<String>>(query.arg(table.table.as_str()))
+            .await?
+            .ok_or_else(|| KlickhouseError::QueryArgumentError(format!("no table {table}")))?
+            .0;
+        if self.schema_cache_ttl.is_some() {
+            self.schema_cache.engines.insert(&key, engine.clone());
+        }
+        Ok(engine)
+    }
+
+    /// Fails if `SELECT ... FINAL` isn't supported by the engine of `table`, i.e. before reading it with
+    /// [`SelectBuilder::final_`](crate::SelectBuilder::final_) or [`QueryOptions::final_`](crate::QueryOptions::final_).
+    /// With [`ClientOptions::schema_cache_ttl`](crate::ClientOptions::schema_cache_ttl) set, this only queries the server once per table.
+    pub async fn check_final(&self, table: impl Into<QualifiedTable>) -> Result<()> {
+        let table = table.into();
+        let engine = self.table_engine(table.clone()).await?;
+        if !supports_final(&engine) {
+            return Err(KlickhouseError::QueryArgumentError(format!(
+                "{table} has engine {engine}, which doesn't support FINAL"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Compares the columns of `T` to those of `table` from [`Client::describe_table`], so mismatches surface before reading or inserting rows.
+    /// Fails if `T` doesn't provide [`Row::column_types`], as for rows that aren't derived from structs.
+    ///
+    /// Column types are checked by whether they can be read into the fields of `T`, and aren't checked for fields with a `deserialize_with` function.
+    /// `MATERIALIZED` and `ALIAS` columns aren't listed by `describe_table`, so fields for them are reported missing.
+    pub async fn verify_schema<T: Row>(&self, table: &str) -> Result<SchemaDiff> {
+        let columns = T::column_types().ok_or_else(|| {
+            KlickhouseError::QueryArgumentError(format!(
+                "{} doesn't provide its column types",
+                std::any::type_name::<T>()
+            ))
+        })?;
+        let schema = self.describe_table(table).await?;
+        Ok(SchemaDiff::compare(&columns, &schema))
+    }
+
+    /// Drops the cached schema of `table`, if any.
+    pub fn invalidate_schema(&self, table: &str) {
+        self.schema_cache.invalidate(table);
+    }
+
+    /// Drops all cached schemas.
+    pub fn invalidate_schemas(&self) {
+        self.schema_cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_cache() {
+        let cache = SchemaCache::default();
+        let schema: TableSchema = Arc::new([("a".to_string(), Type::UInt8)].into_iter().collect());
+        cache.insert("t", schema.clone());
+        assert_eq!(
+            cache.get("t", Duration::from_secs(60)),
+            Some(schema.clone())
+        );
+        assert_eq!(cache.get("t", Duration::ZERO), None);
+        assert_eq!(cache.get("u", Duration::from_secs(60)), None);
+
+        cache.invalidate("t");
+        assert_eq!(cache.get("t", Duration::from_secs(60)), None);
+
+        cache.insert("t", schema.clone());
+        cache.engines.insert("t", "Memory".to_string());
+        cache.clear();
+        assert_eq!(cache.get("t", Duration::from_secs(60)), None);
+        assert_eq!(cache.engines.get("t", Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn test_schema_diff() {
+        let columns = vec![
+            ExpectedColumn::of::<u32>("id"),
+            ExpectedColumn::of::<String>("name"),
+            ExpectedColumn::of::<u64>("count"),
+            ExpectedColumn::of::<u8>("gone"),
+        ];
+        let schema: IndexMap<String, Type> = [
+            ("id".to_string(), Type::UInt32),
+            (
+                "name".to_string(),
+                Type::LowCardinality(Box::new(Type::String)),
+            ),
+            ("count".to_string(), Type::Nullable(Box::new(Type::UInt64))),
+            ("added".to_string(), Type::Date),
+        ]
+        .into_iter()
+        .collect();
+        let diff = SchemaDiff::compare(&columns, &schema);
+        assert_eq!(diff.missing, vec!["gone".to_string()]);
+        assert_eq!(diff.extra, vec![("added".to_string(), Type::Date)]);
+        assert_eq!(
+            diff.mismatched,
+            vec![ColumnMismatch {
+                name: "count".to_string(),
+                rust_type: "u64",
+                column_type: Type::Nullable(Box::new(Type::UInt64)),
+            }]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "missing columns: gone; extra columns: added Date; column count of type Nullable(UInt64) can't be read into u64"
+        );
+
+        assert!(
+            SchemaDiff::compare(&columns[..2], &schema.into_iter().take(2).collect()).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_supports_final() {
+        assert!(supports_final("ReplacingMergeTree"));
+        assert!(supports_final("ReplicatedCollapsingMergeTree"));
+        assert!(supports_final("Distributed"));
+        assert!(!supports_final("Memory"));
+        assert!(!supports_final("View"));
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures_util::{
+    stream::{self, BoxStream},
+    Stream, StreamExt,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use uuid::Uuid;
+
+use crate::{
+    block::Block, client::deserialize_block, query_parser, Client, KlickhouseError, ParsedQuery,
+    Progress, QueryOptions, Result, Row, Utf8Policy,
+};
+
+/// What [`Client::execute_script`] does when a statement fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScriptErrorPolicy {
+    /// Don't run any statements after the failed one.
+    #[default]
+    Stop,
+    /// Run the remaining statements regardless.
+    Continue,
+}
+
+/// Outcome of one statement of a script run by [`Client::execute_script`].
+#[derive(Debug, Clone)]
+pub struct StatementResult {
+    /// Position of the statement within the script, starting at 0.
+    pub index: usize,
+    pub statement: String,
+    /// Sum of the progress reported for the statement, or its error.
+    pub result: Result<Progress>,
+    pub elapsed: Duration,
+}
+
+impl StatementResult {
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Rows written by the statement, if reported by the server (i.e. for `INSERT ... SELECT`).
+    pub fn written_rows(&self) -> Option<u64> {
+        self.result.as_ref().ok()?.new_written_rows
+    }
+}
+
+/// Result blocks of one statement sent by [`Client::execute_multi`], which start arriving once the statements before it have ended.
+/// Dropping it discards the statement's results, but doesn't stop it on the server.
+pub struct ResultStream {
+    query_id: Uuid,
+    blocks: BoxStream<'static, Result<Block>>,
+}
+
+impl ResultStream {
+    /// The ID the statement is sent with, to match [`Client::subscribe_progress`] events or to [cancel](Client::cancel_query) it.
+    pub fn query_id(&self) -> Uuid {
+        self.query_id
+    }
+
+    /// Deserializes the statement's rows, like [`Client::query`].
+    pub fn rows<T: Row>(self) -> impl Stream<Item = Result<T>> {
+        self.blocks.flat_map(|block| match block {
+            Ok(block) => stream::iter(deserialize_block(block, false, Utf8Policy::default())),
+            Err(e) => stream::iter(vec![Err(e)]),
+        })
+    }
+
+    /// Waits for the statement to end, discarding its results, like [`Client::execute`].
+    pub async fn finish(mut self) -> Result<()> {
+        while let Some(block) = self.blocks.next().await {
+            block?;
+        }
+        Ok(())
+    }
+}
+
+impl Stream for ResultStream {
+    type Item = Result<Block>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.blocks.poll_next_unpin(cx)
+    }
+}
+
+impl Client {
+    /// Queues all of `queries` on the connection at once and returns a stream of results for each, in the same order.
+    /// The server still runs them one after the other, but without a round trip through the caller in between,
+    /// which cuts the latency of many small statements, i.e. setup scripts at startup.
+    ///
+    /// No other query of this connection runs in between. Unlike [`Client::execute_script`], a failing statement doesn't stop the ones after it,
+    /// as they are already queued.
+    /// Each stream must be consumed or dropped for the statements after it to proceed, unless [`ClientOptions::slow_consumer_policy`](crate::ClientOptions::slow_consumer_policy) allows otherwise.
+    pub async fn execute_multi(
+        &self,
+        queries: impl IntoIterator<Item = ParsedQuery>,
+    ) -> Result<Vec<ResultStream>> {
+        let queries = queries
+            .into_iter()
+            .map(|query| (query, QueryOptions::new().query_id(Uuid::new_v4())))
+            .collect::<Vec<_>>();
+        let query_ids = queries
+            .iter()
+            .map(|(_, options)| options.query_id.unwrap())
+            .collect::<Vec<_>>();
+        let results = self
+            .send_queries(queries)
+            .await?
+            .into_iter()
+            .zip(query_ids)
+            .map(|(dispatched, query_id)| ResultStream {
+                query_id,
+                blocks: stream::once(dispatched)
+                    .flat_map(|dispatched| match dispatched {
+                        Ok((_, receiver)) => ReceiverStream::new(receiver).boxed(),
+                        Err(e) => stream::iter([Err(KlickhouseError::ProtocolError(format!(
+                            "failed to receive blocks from upstream: {e}"
+                        )))])
+                        .boxed(),
+                    })
+                    .boxed(),
+            })
+            .collect();
+        Ok(results)
+    }
+
+    /// Splits `script` into statements (`--`/`#`/`/* */` comments and heredocs are handled) and executes them one by one.
+    /// Clickhouse has no transactional DDL, so on failure the statements before it stay applied.
+    pub async fn execute_script(
+        &self,
+        script: &str,
+        on_error: ScriptErrorPolicy,
+    ) -> Vec<StatementResult> {
+        self.execute_script_with(script, on_error, |_| ()).await
+    }
+
+    /// Same as [`Client::execute_script`], calling `on_statement` as each statement completes.
+    pub async fn execute_script_with(
+        &self,
+        script: &str,
+        on_error: ScriptErrorPolicy,
+        mut on_statement: impl FnMut(&StatementResult) + Send,
+    ) -> Vec<StatementResult> {
+        let mut results = vec![];
+        for (index, statement) in query_parser::split_query_statements(script)
+            .into_iter()
+            .filter(|x| !query_parser::is_blank_statement(x))
+            .enumerate()
+        {
+            let start = Instant::now();
+            let result = self.execute_with_progress(&statement).await;
+            let result = StatementResult {
+                index,
+                statement,
+                result,
+                elapsed: start.elapsed(),
+            };
+            on_statement(&result);
+            let stop = !result.is_ok() && on_error == ScriptErrorPolicy::Stop;
+            results.push(result);
+            if stop {
+                break;
+            }
+        }
+        results
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use bb8::{Pool, RunError};
+use futures_util::StreamExt;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{ConnectionManager, KlickhouseError, ParsedQuery, Result, Row};
+
+/// Inserts rows over several pooled connections at once, to scale ingestion beyond the throughput of a single connection.
+///
+/// Rows are sharded by the hash of a key: all rows with the same key go to the same shard, which inserts them in the order they were sent.
+/// There is no ordering between rows of different shards. Each shard inserts whatever rows are buffered, up to `batch_size`, as one block,
+/// one insert at a time.
+pub struct ShardedInserter<T: Row, K> {
+    key: Box<dyn Fn(&T) -> K + Send + Sync>,
+    senders: Vec<mpsc::Sender<T>>,
+    tasks: Vec<JoinHandle<Result<()>>>,
+}
+
+impl<T: Row + Send + 'static, K: Hash> ShardedInserter<T, K> {
+    /// Starts `shards` (at least 1) insert tasks, each holding a connection from `pool` while it has rows to insert.
+    /// `query` must have a `format native` suffix, as for [`Client::insert_native`](crate::Client::insert_native).
+    pub fn new(
+        pool: Pool<ConnectionManager>,
+        query: impl TryInto<ParsedQuery, Error = KlickhouseError>,
+        shards: usize,
+        batch_size: usize,
+        key: impl Fn(&T) -> K + Send + Sync + 'static,
+    ) -> Result<Self> {
+        let query = query.try_into()?;
+        let (senders, tasks) = (0..shards.max(1))
+            .map(|_| {
+                let (sender, receiver) = mpsc::channel(batch_size.max(1));
+                let task = tokio::spawn(run_shard(
+                    pool.clone(),
+                    query.clone(),
+                    receiver,
+                    batch_size.max(1),
+                ));
+                (sender, task)
+            })
+            .unzip();
+        Ok(Self {
+            key: Box::new(key),
+            senders,
+            tasks,
+        })
+    }
+
+    /// Index of the shard that inserts rows with `key`.
+    fn shard(&self, key: &K) -> usize {
+        shard_index(key, self.senders.len())
+    }
+
+    /// Queues `row` on the shard for its key, waiting if that shard is behind by more than `batch_size` rows.
+    /// Fails if the shard stopped after an insert error, which [`ShardedInserter::finish`] returns.
+    pub async fn send(&self, row: T) -> Result<()> {
+        let shard = self.shard(&(self.key)(&row));
+        self.senders[shard].send(row).await.map_err(|_| {
+            KlickhouseError::ProtocolError(format!(
+                "shard {shard} stopped after an insert error, see `ShardedInserter::finish`"
+            ))
+        })
+    }
+
+    /// Inserts all queued rows and waits for every shard to finish, returning the first insert error, if any.
+    pub async fn finish(self) -> Result<()> {
+        drop(self.senders);
+        let mut result = Ok(());
+        for task in self.tasks {
+            let shard_result = task.await.map_err(|e| {
+                KlickhouseError::ProtocolError(format!("shard insert task failed: {e}"))
+            });
+            if result.is_ok() {
+                result = shard_result.and_then(|x| x);
+            }
+        }
+        result
+    }
+}
+
+/// Inserts the rows of one shard in batches, one insert at a time.
+async fn run_shard<T: Row + Send + 'static>(
+    pool: Pool<ConnectionManager>,
+    query: ParsedQuery,
+    receiver: mpsc::Receiver<T>,
+    batch_size: usize,
+) -> Result<()> {
+    let mut batches = ReceiverStream::new(receiver).ready_chunks(batch_size);
+    while let Some(rows) = batches.next().await {
+        let client = pool.get().await.map_err(|e| match e {
+            RunError::User(e) => e,
+            RunError::TimedOut => KlickhouseError::ProtocolError(
+                "timed out waiting for a pooled connection".to_string(),
+            ),
+        })?;
+        client.insert_native_block(&query, rows).await?;
+    }
+    Ok(())
+}
+
+/// Deterministic (within a build) shard for `key`.
+fn shard_index<K: Hash>(key: &K, shards: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % shards as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_index() {
+        for key in 0..100u64 {
+            let shard = shard_index(&key, 4);
+            assert!(shard < 4);
+            assert_eq!(shard, shard_index(&key, 4));
+        }
+        assert_eq!(shard_index(&"key", 1), 0);
+        let used = (0..100u64)
+            .map(|key| shard_index(&key, 4))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(used.len(), 4);
+    }
+}
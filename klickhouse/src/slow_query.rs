@@ -0,0 +1,52 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use log::warn;
+use uuid::Uuid;
+
+use crate::Progress;
+
+/// A query that has been executing for longer than [`ClientOptions::slow_query_threshold`](crate::ClientOptions::slow_query_threshold).
+#[derive(Debug, Clone)]
+pub struct SlowQuery {
+    pub id: Uuid,
+    pub query: String,
+    pub elapsed: Duration,
+    /// Sum of all progress received for the query so far.
+    pub progress: Progress,
+}
+
+/// Callback invoked (on the connection task, so it should not block) once per query that exceeds the slow query threshold.
+#[derive(Clone)]
+pub struct SlowQueryHook(Arc<dyn Fn(&SlowQuery) + Send + Sync>);
+
+impl SlowQueryHook {
+    pub fn new(hook: impl Fn(&SlowQuery) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, query: &SlowQuery) {
+        (self.0)(query)
+    }
+}
+
+impl fmt::Debug for SlowQueryHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SlowQueryHook")
+    }
+}
+
+/// Reports a slow query to `hook`, or as a `log` warning if there is none.
+pub(crate) fn report(hook: Option<&SlowQueryHook>, query: &SlowQuery) {
+    match hook {
+        Some(hook) => hook.call(query),
+        None => warn!(
+            "slow clickhouse query {} running for {:?} (read {} rows, {} bytes of {} rows): {}",
+            query.id,
+            query.elapsed,
+            query.progress.read_rows,
+            query.progress.read_bytes,
+            query.progress.new_total_rows_to_read,
+            query.query
+        ),
+    }
+}
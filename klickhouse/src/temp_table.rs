@@ -0,0 +1,301 @@
+use std::ops::Deref;
+
+use indexmap::IndexMap;
+use log::error;
+
+use crate::{
+    Client, ColumnDef, ExpectedColumn, Identifier, KlickhouseError, RawRow, Result, Row, Type,
+    Value,
+};
+
+/// A temporary table created by [`Client::create_temp_table`], i.e. to join queries against data held by the application.
+///
+/// Temporary tables only exist in the session of the connection that created them, so they are only visible to queries sent through this
+/// connection (including through clones of the [`Client`]). The guard derefs to the `Client` for convenience.
+///
+/// Dropping the guard queues a `DROP TEMPORARY TABLE` ahead of any later queries on the connection. The server also drops the table when the connection closes.
+pub struct TemporaryTable {
+    client: Client,
+    name: Identifier,
+    dropped: bool,
+}
+
+impl TemporaryTable {
+    /// The name of the table
+    pub fn name(&self) -> &Identifier {
+        &self.name
+    }
+
+    /// Drops the table now, waiting for the server to acknowledge it.
+    pub async fn drop_table(mut self) -> Result<()> {
+        self.dropped = true;
+        self.client.execute(drop_query(&self.name)).await
+    }
+}
+
+fn drop_query(name: &Identifier) -> String {
+    format!("DROP TEMPORARY TABLE IF EXISTS {name}")
+}
+
+impl Deref for TemporaryTable {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl Drop for TemporaryTable {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+        let query = drop_query(&self.name);
+        if self.client.execute_detached(&*query).is_ok() {
+            return;
+        }
+        // the request queue is full, fall back to dropping asynchronously
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            error!(
+                "TemporaryTable {} dropped outside of a tokio runtime",
+                self.name
+            );
+            return;
+        };
+        let client = self.client.clone();
+        handle.spawn(async move {
+            if let Err(e) = client.execute(&*query).await {
+                error!("failed to drop TemporaryTable: {:?}", e);
+            }
+        });
+    }
+}
+
+/// Guesses the column types of serialized rows from their values. A column holding `NULL` in any row is `Nullable`.
+/// With the `expected` columns of [`Row::column_types`], the columns are in their order, guessed types must be readable into the fields,
+/// and columns without any values (or all columns if there are no rows) get the widest type their field accepts.
+fn infer_columns(
+    rows: &[Vec<(String, Value)>],
+    expected: Option<Vec<ExpectedColumn>>,
+) -> Result<Vec<ColumnDef>> {
+    let mut columns = match (&expected, rows.first()) {
+        (Some(expected), _) => expected
+            .iter()
+            .map(|x| (x.name.to_string(), (None::<Type>, false)))
+            .collect::<IndexMap<_, _>>(),
+        (None, Some(first)) => first
+            .iter()
+            .map(|(name, _)| (name.clone(), (None::<Type>, false)))
+            .collect::<IndexMap<_, _>>(),
+        (None, None) => {
+            return Err(KlickhouseError::QueryArgumentError(
+                "cannot infer the columns of a temporary table without rows".to_string(),
+            ))
+        }
+    };
+    for row in rows {
+        if row.len() != columns.len() {
+            return Err(KlickhouseError::QueryArgumentError(
+                "rows of a temporary table must all have the same columns".to_string(),
+            ));
+        }
+        for (name, value) in row {
+            let Some((type_, nullable)) = columns.get_mut(name) else {
+                return Err(KlickhouseError::QueryArgumentError(format!(
+                    "rows of a temporary table must all have the same columns, found extra column {name}"
+                )));
+            };
+            match value {
+                Value::Null => *nullable = true,
+                value if type_.is_none() => *type_ = Some(value.guess_type()),
+                _ => (),
+            }
+        }
+    }
+    let mut expected = expected.map(Vec::into_iter);
+    columns
+        .into_iter()
+        .map(|(name, (type_, nullable))| {
+            let column = expected.as_mut().and_then(Iterator::next);
+            let type_ = match (type_, &column) {
+                (None, Some(column)) => {
+                    let type_ = column.default_type().ok_or_else(|| {
+                        KlickhouseError::QueryArgumentError(format!(
+                            "cannot infer the type of column {name} ({}) of a temporary table, use create_temp_table_with",
+                            column.rust_type
+                        ))
+                    })?;
+                    return Ok(ColumnDef::new(name, type_));
+                }
+                (type_, _) => type_.unwrap_or(Type::String),
+            };
+            let type_ = if nullable && !matches!(type_, Type::Nullable(_)) {
+                Type::Nullable(Box::new(type_))
+            } else {
+                type_
+            };
+            if let Some(column) = column.filter(|x| !x.accepts(&type_)) {
+                return Err(KlickhouseError::QueryArgumentError(format!(
+                    "guessed type {type_} of column {name} of a temporary table can't be read into {}, use create_temp_table_with",
+                    column.rust_type
+                )));
+            }
+            Ok(ColumnDef::new(name, type_))
+        })
+        .collect()
+}
+
+impl Client {
+    /// Creates a temporary `Memory` table holding `rows`, whose columns are those of `T` with types guessed from the values,
+    /// like [`Value::guess_type`]: a `u64` field becomes a `UInt64` column, a `String` field a `String` column, and an `Option` field is `Nullable`.
+    /// If `T` has [`Row::column_types`] (i.e. with `#[derive(Row)]`), the guessed types are checked against the fields, and columns without
+    /// values to guess from get the widest type their field accepts, such as `Nullable(Int64)` for an `Option<i64>` that is always `None`.
+    /// Otherwise, fails if `rows` is empty. Use [`Client::create_temp_table_with`] to choose the column types.
+    ///
+    /// Unlike external data sent along a query, the table can be used by any number of queries, and works with servers that don't support external data.
+    pub async fn create_temp_table<T: Row>(
+        &self,
+        name: impl Into<Identifier>,
+        rows: Vec<T>,
+    ) -> Result<TemporaryTable> {
+        let hints = IndexMap::new();
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                Ok(row
+                    .serialize_row(&hints)?
+                    .into_iter()
+                    .map(|(name, value)| (name.into_owned(), value))
+                    .collect::<Vec<_>>())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let columns = infer_columns(&rows, T::column_types())?;
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let mut raw = RawRow::default();
+                for (name, value) in row {
+                    raw.try_set(name, value)?;
+                }
+                Ok(raw)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.create_temp_table_with(name, columns, rows).await
+    }
+
+    /// Creates a temporary `Memory` table with `columns` and inserts `rows` into it. See [`Client::create_temp_table`].
+    pub async fn create_temp_table_with<T: Row + Send + 'static>(
+        &self,
+        name: impl Into<Identifier>,
+        columns: Vec<ColumnDef>,
+        rows: Vec<T>,
+    ) -> Result<TemporaryTable> {
+        let name = name.into();
+        let columns = columns
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.execute(format!(
+            "CREATE TEMPORARY TABLE {name} ({columns}) ENGINE = Memory"
+        ))
+        .await?;
+        let table = TemporaryTable {
+            client: self.clone(),
+            name,
+            dropped: false,
+        };
+        if !rows.is_empty() {
+            self.insert_native_block(format!("INSERT INTO {} FORMAT native", table.name), rows)
+                .await?;
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_columns() {
+        let rows = vec![
+            vec![
+                ("id".to_string(), Value::UInt64(1)),
+                ("name".to_string(), Value::Null),
+            ],
+            vec![
+                ("id".to_string(), Value::UInt64(2)),
+                ("name".to_string(), Value::string("b")),
+            ],
+        ];
+        assert_eq!(
+            infer_columns(&rows, None).unwrap(),
+            vec![
+                ColumnDef::new("id", Type::UInt64),
+                ColumnDef::new("name", Type::Nullable(Box::new(Type::String))),
+            ]
+        );
+        assert!(infer_columns(&[], None).is_err());
+        assert!(infer_columns(&[rows[0].clone(), rows[0][..1].to_vec()], None).is_err());
+    }
+
+    #[test]
+    fn test_infer_columns_expected() {
+        let expected = || {
+            Some(vec![
+                ExpectedColumn::of::<u32>("id"),
+                ExpectedColumn::of::<Option<i16>>("score"),
+                ExpectedColumn::of::<String>("name"),
+                ExpectedColumn::of::<Option<f64>>("weight"),
+            ])
+        };
+        let rows = vec![vec![
+            ("id".to_string(), Value::UInt32(1)),
+            ("score".to_string(), Value::Null),
+            ("name".to_string(), Value::string("a")),
+            ("weight".to_string(), Value::Float64(0.5)),
+        ]];
+        assert_eq!(
+            infer_columns(&rows, expected()).unwrap(),
+            vec![
+                ColumnDef::new("id", Type::UInt32),
+                ColumnDef::new("score", Type::Nullable(Box::new(Type::Int16))),
+                ColumnDef::new("name", Type::String),
+                ColumnDef::new("weight", Type::Float64),
+            ]
+        );
+        assert_eq!(
+            infer_columns(&[], expected()).unwrap(),
+            vec![
+                ColumnDef::new("id", Type::UInt32),
+                ColumnDef::new("score", Type::Nullable(Box::new(Type::Int16))),
+                ColumnDef::new("name", Type::String),
+                ColumnDef::new("weight", Type::Nullable(Box::new(Type::Float64))),
+            ]
+        );
+
+        let expected = vec![
+            ExpectedColumn::of::<Option<u64>>("a"),
+            ExpectedColumn::of::<i64>("b"),
+            ExpectedColumn::of::<u8>("c"),
+            ExpectedColumn::of::<Option<crate::Uuid>>("d"),
+        ];
+        assert_eq!(
+            infer_columns(&[], Some(expected))
+                .unwrap()
+                .into_iter()
+                .map(|x| x.type_)
+                .collect::<Vec<_>>(),
+            vec![
+                Type::Nullable(Box::new(Type::UInt64)),
+                Type::Int64,
+                Type::UInt8,
+                Type::Nullable(Box::new(Type::Uuid)),
+            ]
+        );
+
+        let rows = vec![vec![("id".to_string(), Value::string("x"))]];
+        assert!(infer_columns(&rows, Some(vec![ExpectedColumn::of::<u32>("id")])).is_err());
+    }
+}
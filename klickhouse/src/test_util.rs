@@ -0,0 +1,190 @@
+//! Helpers for writing integration tests against a live Clickhouse server.
+//!
+//! The connection is configured through the following environment variables:
+//! - `KLICKHOUSE_TEST_ADDR` (default `127.0.0.1:9000`)
+//! - `KLICKHOUSE_TEST_USER`
+//! - `KLICKHOUSE_TEST_PASSWORD`
+//! - `KLICKHOUSE_TEST_DATABASE`
+
+use log::error;
+
+use crate::{Client, ClientOptions, Result};
+
+/// The address of the test server, from `KLICKHOUSE_TEST_ADDR`.
+pub fn test_address() -> String {
+    std::env::var("KLICKHOUSE_TEST_ADDR").unwrap_or_else(|_| "127.0.0.1:9000".into())
+}
+
+/// Applies the credentials and database from the `KLICKHOUSE_TEST_*` environment variables to `options`.
+pub fn with_env_options(mut options: ClientOptions) -> ClientOptions {
+    if let Ok(user) = std::env::var("KLICKHOUSE_TEST_USER") {
+        options.username = user;
+    }
+
+    if let Ok(password) = std::env::var("KLICKHOUSE_TEST_PASSWORD") {
+        options.password = password;
+    }
+
+    if let Ok(database) = std::env::var("KLICKHOUSE_TEST_DATABASE") {
+        options.default_database = database;
+    }
+
+    options
+}
+
+/// Builds [`ClientOptions`] from the `KLICKHOUSE_TEST_*` environment variables.
+pub fn client_options() -> ClientOptions {
+    with_env_options(ClientOptions::default())
+}
+
+/// Connects to the test server described by the `KLICKHOUSE_TEST_*` environment variables.
+pub async fn get_client() -> Result<Client> {
+    get_client_with_options(ClientOptions::default()).await
+}
+
+/// Like [`get_client`], with `options` for anything the environment doesn't set.
+pub async fn get_client_with_options(options: ClientOptions) -> Result<Client> {
+    Client::connect(test_address(), with_env_options(options)).await
+}
+
+/// Drop the table if it exists, and create it with the given structure.
+/// Make sure to use distinct table names across tests to avoid conflicts between tests executing
+/// simultaneously.
+pub async fn prepare_table(table_name: &str, table_struct: &str, client: &Client) -> Result<()> {
+    client.execute(drop_table_sql(table_name)).await?;
+    client
+        .execute(create_table_sql(table_name, table_struct))
+        .await?;
+    Ok(())
+}
+
+fn drop_table_sql(table_name: &str) -> String {
+    format!("DROP TABLE IF EXISTS {}", table_name)
+}
+
+fn create_table_sql(table_name: &str, table_struct: &str) -> String {
+    format!(
+        "CREATE TABLE {} ({}) ENGINE = Memory;",
+        table_name, table_struct
+    )
+}
+
+/// A table created by [`TempTable::create`] that is dropped again when the guard is dropped.
+///
+/// Dropping spawns the `DROP TABLE` onto the current tokio runtime, so it may not complete if the runtime shuts down first.
+/// Use [`TempTable::drop_table`] to drop it deterministically.
+pub struct TempTable {
+    client: Client,
+    name: String,
+    dropped: bool,
+}
+
+impl TempTable {
+    /// Creates (or recreates) a `Memory` table with the given structure, see [`prepare_table`].
+    pub async fn create(
+        client: &Client,
+        table_name: impl Into<String>,
+        table_struct: &str,
+    ) -> Result<Self> {
+        let name = table_name.into();
+        prepare_table(&name, table_struct, client).await?;
+        Ok(Self {
+            client: client.clone(),
+            name,
+            dropped: false,
+        })
+    }
+
+    /// The name of the table
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Drops the table now, waiting for the server to acknowledge it.
+    pub async fn drop_table(mut self) -> Result<()> {
+        self.dropped = true;
+        self.client.execute(drop_table_sql(&self.name)).await
+    }
+}
+
+impl Drop for TempTable {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            error!("TempTable {} dropped outside of a tokio runtime", self.name);
+            return;
+        };
+        let client = self.client.clone();
+        let name = std::mem::take(&mut self.name);
+        handle.spawn(async move {
+            if let Err(e) = client.execute(drop_table_sql(&name)).await {
+                error!("failed to drop TempTable {}: {:?}", name, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_options() {
+        let vars = [
+            "KLICKHOUSE_TEST_ADDR",
+            "KLICKHOUSE_TEST_USER",
+            "KLICKHOUSE_TEST_PASSWORD",
+            "KLICKHOUSE_TEST_DATABASE",
+        ];
+        let saved = vars.map(|var| std::env::var(var).ok());
+        for var in vars {
+            std::env::remove_var(var);
+        }
+
+        assert_eq!(test_address(), "127.0.0.1:9000");
+        let options = with_env_options(ClientOptions {
+            username: "fallback".to_string(),
+            compression_threads: 4,
+            ..ClientOptions::default()
+        });
+        assert_eq!(options.username, "fallback");
+        assert_eq!(options.compression_threads, 4);
+
+        std::env::set_var("KLICKHOUSE_TEST_ADDR", "clickhouse:9440");
+        std::env::set_var("KLICKHOUSE_TEST_USER", "tester");
+        std::env::set_var("KLICKHOUSE_TEST_PASSWORD", "secret");
+        std::env::set_var("KLICKHOUSE_TEST_DATABASE", "scratch");
+        assert_eq!(test_address(), "clickhouse:9440");
+        let options = with_env_options(ClientOptions {
+            username: "fallback".to_string(),
+            compression_threads: 4,
+            ..ClientOptions::default()
+        });
+        assert_eq!(options.username, "tester");
+        assert_eq!(options.password, "secret");
+        assert_eq!(options.default_database, "scratch");
+        assert_eq!(options.compression_threads, 4);
+        assert_eq!(client_options().username, "tester");
+
+        for (var, value) in vars.iter().zip(saved) {
+            match value {
+                Some(value) => std::env::set_var(var, value),
+                None => std::env::remove_var(var),
+            }
+        }
+    }
+
+    #[test]
+    fn test_table_sql() {
+        assert_eq!(
+            create_table_sql("test_table", "id UInt64, name String"),
+            "CREATE TABLE test_table (id UInt64, name String) ENGINE = Memory;"
+        );
+        assert_eq!(
+            drop_table_sql("test_table"),
+            "DROP TABLE IF EXISTS test_table"
+        );
+    }
+}
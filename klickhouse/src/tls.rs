@@ -0,0 +1,184 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use rustls_pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::{
+    rustls::{
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+        ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+    },
+    TlsConnector,
+};
+
+use crate::{Client, ClientOptions, KlickhouseError, Result};
+
+/// How a connector built by [`tls_connector`] verifies the server's certificate.
+#[derive(Debug, Clone)]
+pub enum TlsVerification {
+    /// Against these trusted roots, i.e. a private CA loaded with [`root_store`].
+    Roots(RootCertStore),
+    /// Accept any certificate, i.e. a self-signed one in development. Handshake signatures are still checked, but the server is not authenticated.
+    Insecure,
+}
+
+/// Builds a root store trusting the given DER-encoded CA certificates.
+pub fn root_store(
+    certs: impl IntoIterator<Item = CertificateDer<'static>>,
+) -> Result<RootCertStore> {
+    let mut store = RootCertStore::empty();
+    for cert in certs {
+        store
+            .add(cert)
+            .map_err(|e| KlickhouseError::tls("invalid root certificate", e))?;
+    }
+    Ok(store)
+}
+
+/// Builds a root store trusting the platform's certificates, i.e. the system CA bundle (feature `rustls-native-certs`).
+/// Certificates that can't be parsed are skipped, but loading fails if none are usable.
+#[cfg(feature = "rustls-native-certs")]
+pub fn native_root_store() -> Result<RootCertStore> {
+    let certs = rustls_native_certs::load_native_certs()
+        .map_err(|e| KlickhouseError::tls("failed to load native root certificates", e))?;
+    let mut store = RootCertStore::empty();
+    let (_, ignored) = store.add_parsable_certificates(certs);
+    if store.is_empty() {
+        return Err(KlickhouseError::TlsError {
+            message: format!("no usable native root certificates, {ignored} ignored"),
+            source: None,
+        });
+    }
+    Ok(store)
+}
+
+/// Builds a connector for [`Client::connect_tls`] and friends, without client authentication.
+pub fn tls_connector(verification: TlsVerification) -> TlsConnector {
+    let config = match verification {
+        TlsVerification::Roots(roots) => ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+        TlsVerification::Insecure => {
+            let mut config = ClientConfig::builder()
+                .with_root_certificates(RootCertStore::empty())
+                .with_no_client_auth();
+            let verifier = NoCertificateVerification(config.crypto_provider().clone());
+            config
+                .dangerous()
+                .set_certificate_verifier(Arc::new(verifier));
+            config
+        }
+    };
+    TlsConnector::from(Arc::new(config))
+}
+
+#[derive(Debug)]
+struct NoCertificateVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+impl Client {
+    /// Connects over TLS to a server addressed by IP, whose certificate must then be issued for that IP rather than a DNS name.
+    /// No SNI is sent. Use [`TlsVerification::Insecure`] for servers with certificates for another name.
+    pub async fn connect_tls_ip(
+        destination: SocketAddr,
+        options: ClientOptions,
+        connector: &TlsConnector,
+    ) -> Result<Self> {
+        let name = ServerName::IpAddress(destination.ip().into());
+        Client::connect_tls(destination, options, name, connector).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    /// A self-signed CA certificate for `CN=klickhouse test CA`
+    const TEST_CA: &[u8] = include_bytes!("../tests/data/test_ca.der");
+
+    #[test]
+    fn test_root_store() {
+        assert!(root_store([]).unwrap().is_empty());
+
+        let store = root_store([CertificateDer::from(TEST_CA)]).unwrap();
+        assert_eq!(store.len(), 1);
+        // the trust anchor's subject is the DER encoded name, holding the common name as is
+        let subject: &[u8] = store.roots[0].subject.as_ref();
+        assert!(subject
+            .windows(b"klickhouse test CA".len())
+            .any(|x| x == b"klickhouse test CA"));
+        tls_connector(TlsVerification::Roots(store));
+
+        let error = root_store([
+            CertificateDer::from(TEST_CA),
+            CertificateDer::from(vec![1, 2, 3]),
+        ])
+        .unwrap_err();
+        assert!(matches!(error, KlickhouseError::TlsError { .. }));
+        assert!(error
+            .source()
+            .unwrap()
+            .downcast_ref::<tokio_rustls::rustls::Error>()
+            .is_some());
+        // clones keep the source's message
+        assert_eq!(
+            error.clone().source().unwrap().to_string(),
+            error.source().unwrap().to_string()
+        );
+    }
+
+    #[cfg(feature = "rustls-native-certs")]
+    #[test]
+    fn test_native_root_store() {
+        // depends on the platform's certificates, which may be missing in minimal environments
+        match native_root_store() {
+            Ok(store) => assert!(!store.is_empty()),
+            Err(e) => assert!(matches!(e, KlickhouseError::TlsError { .. })),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::ops::Deref;
+
+use log::error;
+
+use crate::{Client, Result};
+
+/// An open experimental Clickhouse transaction, started with [`Client::begin_transaction`]. Only `MergeTree` family tables support transactions,
+/// and the server must have `allow_experimental_transactions` enabled. See <https://clickhouse.com/docs/en/guides/developer/transactional>.
+///
+/// Transactions are bound to the connection's session, so every query sent over the connection (including through clones of the [`Client`]) is part of
+/// the transaction until it is committed or rolled back. Prefer a dedicated connection for transactional work. The guard derefs to the `Client` for convenience.
+///
+/// Dropping the guard without committing queues a `ROLLBACK` ahead of any later queries on the connection.
+pub struct Transaction {
+    client: Client,
+    finished: bool,
+}
+
+impl Client {
+    /// Starts a transaction on this connection with `BEGIN TRANSACTION`.
+    pub async fn begin_transaction(&self) -> Result<Transaction> {
+        self.execute("BEGIN TRANSACTION").await?;
+        Ok(Transaction {
+            client: self.clone(),
+            finished: false,
+        })
+    }
+}
+
+impl Transaction {
+    /// Commits the transaction, waiting for the server to acknowledge it.
+    pub async fn commit(mut self) -> Result<()> {
+        self.finished = true;
+        self.client.execute("COMMIT").await
+    }
+
+    /// Rolls back the transaction, waiting for the server to acknowledge it.
+    pub async fn rollback(mut self) -> Result<()> {
+        self.finished = true;
+        self.client.execute("ROLLBACK").await
+    }
+}
+
+impl Deref for Transaction {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if self.client.execute_detached("ROLLBACK").is_ok() {
+            return;
+        }
+        // the request queue is full, fall back to rolling back asynchronously
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            error!("Transaction dropped outside of a tokio runtime, it was not rolled back");
+            return;
+        };
+        let client = self.client.clone();
+        handle.spawn(async move {
+            if let Err(e) = client.execute("ROLLBACK").await {
+                error!("failed to roll back dropped Transaction: {:?}", e);
+            }
+        });
+    }
+}
@@ -1,14 +1,12 @@
-use tokio::io::AsyncReadExt;
+use crate::{io::ClickhouseRead, values::Value, KlickhouseError, Result};
 
-use crate::{io::ClickhouseRead, values::Value, Result};
-
-use super::{Deserializer, DeserializerState, Type};
+use super::{preallocate, read_offsets, Deserializer, DeserializerState, Type};
 
 /// Trait to allow reading `Item`s and packing them into a `Value::*`.
 pub trait ArrayDeserializerGeneric {
     type Item;
     /// The type of the items, e.g. [Value]
-    fn inner_type(type_: &Type) -> &Type;
+    fn inner_type(type_: &Type) -> Result<&Type>;
     /// Mapping from items to the return Value, e.g. simply `Vec<Value> -> Value::Array(items)`.
     fn inner_value(items: Vec<Self::Item>) -> Value;
     /// Conversion between the [Value] read and the items, e.g. simply the identity.
@@ -19,8 +17,8 @@ pub trait ArrayDeserializerGeneric {
 pub struct ArrayDeserializer;
 impl ArrayDeserializerGeneric for ArrayDeserializer {
     type Item = Value;
-    fn inner_type(type_: &Type) -> &Type {
-        type_.unwrap_array()
+    fn inner_type(type_: &Type) -> Result<&Type> {
+        type_.try_unwrap_array()
     }
     fn inner_value(items: Vec<Self::Item>) -> Value {
         Value::Array(items)
@@ -36,7 +34,7 @@ impl<T: ArrayDeserializerGeneric + 'static> Deserializer for T {
         reader: &mut R,
         state: &mut DeserializerState,
     ) -> Result<()> {
-        Self::inner_type(type_)
+        Self::inner_type(type_)?
             .deserialize_prefix(reader, state)
             .await
     }
@@ -50,16 +48,17 @@ impl<T: ArrayDeserializerGeneric + 'static> Deserializer for T {
         if rows == 0 {
             return Ok(vec![]);
         }
-        let mut offsets = vec![];
-        for _ in 0..rows {
-            offsets.push(reader.read_u64_le().await?);
-        }
-        let mut items = Self::inner_type(type_)
-            .deserialize_column(reader, offsets[offsets.len() - 1] as usize, state)
+        let offsets = read_offsets(reader, rows).await?;
+        let total_length = offsets.last().copied().unwrap_or_default();
+        let total_length = usize::try_from(total_length).map_err(|_| {
+            KlickhouseError::DeserializeError(format!("array length too large: {total_length}"))
+        })?;
+        let mut items = Self::inner_type(type_)?
+            .deserialize_column(reader, total_length, state)
             .await?
             .into_iter()
             .map(Self::item_mapping);
-        let mut out = Vec::with_capacity(rows);
+        let mut out = preallocate(rows);
         let mut read_offset = 0u64;
         for offset in offsets {
             let len = offset - read_offset;
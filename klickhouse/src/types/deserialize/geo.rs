@@ -1,6 +1,6 @@
 use crate::{io::ClickhouseRead, values::Value, Result};
 
-use super::{Deserializer, DeserializerState, Type};
+use super::{preallocate, Deserializer, DeserializerState, Type};
 
 use crate::values;
 
@@ -24,24 +24,18 @@ impl Deserializer for PointDeserializer {
         rows: usize,
         state: &mut DeserializerState,
     ) -> Result<Vec<Value>> {
-        let mut points = vec![Value::Point(Default::default()); rows];
-        for col in 0..2 {
-            for (row, value) in Type::Float64
-                .deserialize_column(reader, rows, state)
-                .await?
-                .into_iter()
-                .enumerate()
-            {
-                let Value::Float64(value) = value else {
-                    unreachable!()
-                };
-                match &mut points[row] {
-                    Value::Point(point) => point.0[col] = value,
-                    _ => {
-                        unreachable!()
-                    }
-                }
-            }
+        let xs = Type::Float64
+            .deserialize_column(reader, rows, state)
+            .await?;
+        let ys = Type::Float64
+            .deserialize_column(reader, rows, state)
+            .await?;
+        let mut points = preallocate(rows);
+        for (x, y) in xs.into_iter().zip(ys) {
+            let (Value::Float64(x), Value::Float64(y)) = (x, y) else {
+                unreachable!()
+            };
+            points.push(Value::Point(values::Point([x, y])));
         }
         Ok(points)
     }
@@ -52,8 +46,8 @@ macro_rules! array_deser {
             pub struct [<$name Deserializer>];
             impl super::array::ArrayDeserializerGeneric for [<$name Deserializer>] {
                 type Item = crate::values::$item;
-                fn inner_type(_type_: &Type) -> &Type {
-                    &Type::$item
+                fn inner_type(_type_: &Type) -> Result<&Type> {
+                    Ok(&Type::$item)
                 }
                 fn inner_value(items: Vec<Self::Item>) -> Value {
                     Value::$name(values::$name(items))
@@ -72,3 +66,37 @@ macro_rules! array_deser {
 array_deser!(Ring, Point);
 array_deser!(Polygon, Ring);
 array_deser!(MultiPolygon, Polygon);
+array_deser!(LineString, Point);
+array_deser!(MultiLineString, LineString);
+
+/// Reads `Geometry` columns as the `Variant` they are an alias of, returning values without their discriminator.
+pub struct GeometryDeserializer;
+
+impl Deserializer for GeometryDeserializer {
+    async fn read_prefix<R: ClickhouseRead>(
+        _type_: &Type,
+        reader: &mut R,
+        state: &mut DeserializerState,
+    ) -> Result<()> {
+        Type::geometry_variant()
+            .deserialize_prefix(reader, state)
+            .await
+    }
+
+    async fn read<R: ClickhouseRead>(
+        _type_: &Type,
+        reader: &mut R,
+        rows: usize,
+        state: &mut DeserializerState,
+    ) -> Result<Vec<Value>> {
+        Ok(Type::geometry_variant()
+            .deserialize_column(reader, rows, state)
+            .await?
+            .into_iter()
+            .map(|value| match value {
+                Value::Variant(value, _) => *value,
+                value => value,
+            })
+            .collect())
+    }
+}
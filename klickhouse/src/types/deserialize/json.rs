@@ -0,0 +1,33 @@
+use tokio::io::AsyncReadExt;
+
+use crate::{io::ClickhouseRead, values::Value, KlickhouseError, Result};
+
+use super::{Deserializer, DeserializerState, Type};
+use crate::types::json::JSON_STRING_SERIALIZATION_VERSION;
+
+pub struct JsonDeserializer;
+
+impl Deserializer for JsonDeserializer {
+    async fn read_prefix<R: ClickhouseRead>(
+        _type_: &Type,
+        reader: &mut R,
+        _state: &mut DeserializerState,
+    ) -> Result<()> {
+        let version = reader.read_u64_le().await?;
+        if version != JSON_STRING_SERIALIZATION_VERSION {
+            return Err(KlickhouseError::DeserializeError(format!(
+                "unsupported JSON serialization version {version}, only the string serialization can be read, see QueryOptions::json_as_string"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn read<R: ClickhouseRead>(
+        _type_: &Type,
+        reader: &mut R,
+        rows: usize,
+        state: &mut DeserializerState,
+    ) -> Result<Vec<Value>> {
+        Type::String.deserialize_column(reader, rows, state).await
+    }
+}
@@ -1,6 +1,6 @@
 use tokio::io::AsyncReadExt;
 
-use crate::{io::ClickhouseRead, values::Value, KlickhouseError, Result};
+use crate::{convert::unexpected_type, io::ClickhouseRead, values::Value, KlickhouseError, Result};
 
 use super::{Deserializer, DeserializerState, Type};
 
@@ -111,7 +111,7 @@ impl Deserializer for LowCardinalityDeserializer {
                             )
                         })?;
                         for entry in entries {
-                            let entry = entry.index_value();
+                            let entry = entry.index_value()?;
                             let value = if is_nullable && entry == 0 {
                                 Value::Null
                             } else {
@@ -131,17 +131,15 @@ impl Deserializer for LowCardinalityDeserializer {
                             )
                         })?;
                         for entry in entries {
-                            output.push(
-                                global_dictionary
-                                    .get(entry.index_value())
-                                    .cloned()
-                                    .ok_or_else(|| {
-                                        KlickhouseError::DeserializeError(format!(
-                                            "LowCardinality: illegal index {} in global_dictionary",
-                                            entry.index_value()
-                                        ))
-                                    })?,
-                            );
+                            let entry = entry.index_value()?;
+                            output.push(global_dictionary.get(entry).cloned().ok_or_else(
+                                || {
+                                    KlickhouseError::DeserializeError(format!(
+                                        "LowCardinality: illegal index {} in global_dictionary",
+                                        entry
+                                    ))
+                                },
+                            )?);
                         }
                     } else if needs_global_dictionary && has_additional_keys {
                         let additional_keys = additional_keys.as_ref().ok_or_else(|| {
@@ -155,7 +153,7 @@ impl Deserializer for LowCardinalityDeserializer {
                             )
                         })?;
                         for entry in entries {
-                            let entry = entry.index_value();
+                            let entry = entry.index_value()?;
                             let value = if is_nullable && entry == 0 {
                                 Value::Null
                             } else if entry < additional_keys.len() {
@@ -183,7 +181,7 @@ impl Deserializer for LowCardinalityDeserializer {
 
                 output
             }
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         })
     }
 }
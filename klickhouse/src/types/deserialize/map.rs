@@ -1,10 +1,9 @@
-use tokio::io::AsyncReadExt;
-
 use crate::{
-    io::ClickhouseRead, protocol::MAX_STRING_SIZE, values::Value, KlickhouseError, Result,
+    convert::unexpected_type, io::ClickhouseRead, protocol::MAX_STRING_SIZE, values::Value,
+    KlickhouseError, Result,
 };
 
-use super::{Deserializer, DeserializerState, Type};
+use super::{preallocate, read_offsets, Deserializer, DeserializerState, Type};
 
 pub struct MapDeserializer;
 
@@ -22,7 +21,7 @@ impl Deserializer for MapDeserializer {
                 ])));
                 nested.deserialize_prefix(reader, state).await?;
             }
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         }
         Ok(())
     }
@@ -45,35 +44,60 @@ impl Deserializer for MapDeserializer {
 
         let (key, value) = match type_ {
             Type::Map(key, value) => (key, value),
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         };
 
-        let mut offsets: Vec<u64> = Vec::with_capacity(rows);
-        for _ in 0..rows {
-            offsets.push(reader.read_u64_le().await?);
-        }
+        let offsets = read_offsets(reader, rows).await?;
 
-        let total_length = *offsets.last().unwrap();
+        let total_length = offsets.last().copied().unwrap_or_default();
+        if total_length > MAX_STRING_SIZE as u64 {
+            return Err(KlickhouseError::ProtocolError(format!(
+                "map length too large. {} > {}",
+                total_length, MAX_STRING_SIZE
+            )));
+        }
 
         let keys = key
             .deserialize_column(reader, total_length as usize, state)
             .await?;
-        assert_eq!(keys.len(), total_length as usize);
+        if keys.len() != total_length as usize {
+            return Err(KlickhouseError::DeserializeError(format!(
+                "Map: expected {} keys, got {}",
+                total_length,
+                keys.len()
+            )));
+        }
         let values = value
             .deserialize_column(reader, total_length as usize, state)
             .await?;
-        assert_eq!(values.len(), total_length as usize);
+        if values.len() != total_length as usize {
+            return Err(KlickhouseError::DeserializeError(format!(
+                "Map: expected {} values, got {}",
+                total_length,
+                values.len()
+            )));
+        }
 
         let mut keys = keys.into_iter();
         let mut values = values.into_iter();
-        let mut out = Vec::with_capacity(rows);
+        let mut out = preallocate(rows);
         let mut last_offset = 0u64;
         for offset in offsets {
             let mut key_out = vec![];
             let mut value_out = vec![];
             while last_offset < offset {
-                key_out.push(keys.next().unwrap());
-                value_out.push(values.next().unwrap());
+                match (keys.next(), values.next()) {
+                    (Some(key), Some(value)) => {
+                        key_out.push(key);
+                        value_out.push(value);
+                    }
+                    _ => {
+                        return Err(KlickhouseError::DeserializeError(format!(
+                            "Map: offset {} out of bounds for {} entries",
+                            offset, total_length
+                        )))
+                    }
+                }
                 last_offset += 1;
             }
             out.push(Value::Map(key_out, value_out));
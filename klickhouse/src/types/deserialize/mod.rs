@@ -1,10 +1,41 @@
 pub mod array;
 pub mod geo;
+pub mod json;
 pub mod low_cardinality;
 pub mod map;
 pub mod nullable;
 pub mod sized;
 pub mod string;
 pub mod tuple;
+pub mod variant;
+
+use tokio::io::AsyncReadExt;
 
 use super::*;
+use crate::io::read_bytes;
+
+/// Row counts come from the server, so preallocation is capped: a corrupt count then fails on a short read
+/// instead of attempting a huge allocation up front.
+const MAX_PREALLOCATED_ROWS: usize = 1 << 16;
+
+fn preallocate<T>(rows: usize) -> Vec<T> {
+    Vec::with_capacity(rows.min(MAX_PREALLOCATED_ROWS))
+}
+
+/// Reads `rows` array offsets, checking that they never decrease.
+async fn read_offsets<R: ClickhouseRead>(reader: &mut R, rows: usize) -> Result<Vec<u64>> {
+    let mut offsets = preallocate(rows);
+    let mut last = 0u64;
+    for _ in 0..rows {
+        let offset = reader.read_u64_le().await?;
+        if offset < last {
+            return Err(KlickhouseError::DeserializeError(format!(
+                "offsets must not decrease: {} after {}",
+                offset, last
+            )));
+        }
+        last = offset;
+        offsets.push(offset);
+    }
+    Ok(offsets)
+}
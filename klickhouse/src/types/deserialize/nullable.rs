@@ -1,8 +1,6 @@
-use tokio::io::AsyncReadExt;
+use crate::{convert::unexpected_type, io::ClickhouseRead, values::Value, Result};
 
-use crate::{io::ClickhouseRead, values::Value, Result};
-
-use super::{Deserializer, DeserializerState, Type};
+use super::{read_bytes, Deserializer, DeserializerState, Type};
 
 pub struct NullableDeserializer;
 
@@ -16,7 +14,7 @@ impl Deserializer for NullableDeserializer {
             Type::Nullable(inner) => {
                 inner.deserialize_prefix(reader, state).await?;
             }
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         }
         Ok(())
     }
@@ -28,8 +26,7 @@ impl Deserializer for NullableDeserializer {
         state: &mut DeserializerState,
     ) -> Result<Vec<Value>> {
         // if mask[i] == 0, item is present
-        let mut mask = vec![0u8; rows];
-        reader.read_exact(&mut mask).await?;
+        let mask = read_bytes(reader, rows).await?;
 
         let mut out = type_
             .strip_null()
@@ -3,9 +3,12 @@ use std::net::{Ipv4Addr, Ipv6Addr};
 use tokio::io::AsyncReadExt;
 use uuid::Uuid;
 
-use crate::{i256, io::ClickhouseRead, u256, values::Value, Date, DateTime, DynDateTime64, Result};
+use crate::{
+    convert::unexpected_type, i256, io::ClickhouseRead, u256, values::Value, Date, DateTime,
+    DynDateTime64, Result,
+};
 
-use super::{Deserializer, DeserializerState, Type};
+use super::{preallocate, Deserializer, DeserializerState, Type};
 
 pub struct SizedDeserializer;
 
@@ -16,7 +19,7 @@ impl Deserializer for SizedDeserializer {
         rows: usize,
         _state: &mut DeserializerState,
     ) -> Result<Vec<Value>> {
-        let mut out = Vec::with_capacity(rows);
+        let mut out = preallocate(rows);
         for _ in 0..rows {
             out.push(match type_ {
                 Type::Int8 => Value::Int8(reader.read_i8().await?),
@@ -71,7 +74,7 @@ impl Deserializer for SizedDeserializer {
                 }
                 Type::Enum8(_) => Value::Enum8(reader.read_i8().await?),
                 Type::Enum16(_) => Value::Enum16(reader.read_i16_le().await?),
-                _ => unimplemented!(),
+                x => return Err(unexpected_type(x)),
             });
         }
         Ok(out)
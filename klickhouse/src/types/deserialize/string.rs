@@ -1,12 +1,12 @@
-use tokio::io::AsyncReadExt;
+use crate::{
+    convert::unexpected_type, io::ClickhouseRead, protocol::MAX_STRING_SIZE, values::Value,
+    KlickhouseError, Result,
+};
 
-use crate::{io::ClickhouseRead, values::Value, Result};
-
-use super::{Deserializer, DeserializerState, Type};
+use super::{preallocate, read_bytes, Deserializer, DeserializerState, Type};
 
 pub struct StringDeserializer;
 
-#[allow(clippy::uninit_vec)]
 impl Deserializer for StringDeserializer {
     async fn read<R: ClickhouseRead>(
         type_: &Type,
@@ -16,25 +16,30 @@ impl Deserializer for StringDeserializer {
     ) -> Result<Vec<Value>> {
         match type_ {
             Type::String => {
-                let mut out = Vec::with_capacity(rows);
+                let mut out = preallocate(rows);
                 for _ in 0..rows {
                     out.push(Value::String(reader.read_string().await?));
                 }
                 Ok(out)
             }
             Type::FixedString(n) => {
-                let mut out = Vec::with_capacity(rows);
+                if *n > MAX_STRING_SIZE {
+                    return Err(KlickhouseError::ProtocolError(format!(
+                        "FixedString too large. {} > {}",
+                        n, MAX_STRING_SIZE
+                    )));
+                }
+                let mut out = preallocate(rows);
                 for _ in 0..rows {
-                    let mut buf = Vec::with_capacity(*n);
-                    unsafe { buf.set_len(*n) };
-                    reader.read_exact(&mut buf[..]).await?;
-                    let first_null = buf.iter().position(|x| *x == 0).unwrap_or(buf.len());
-                    buf.truncate(first_null);
+                    // trailing zeros are padding, see `FixedStringBytes` to keep them
+                    let mut buf = read_bytes(reader, *n).await?;
+                    let len = buf.iter().rposition(|x| *x != 0).map_or(0, |x| x + 1);
+                    buf.truncate(len);
                     out.push(Value::String(buf));
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            x => Err(unexpected_type(x)),
         }
     }
 }
@@ -1,6 +1,6 @@
-use crate::{io::ClickhouseRead, values::Value, Result};
+use crate::{convert::unexpected_type, io::ClickhouseRead, values::Value, KlickhouseError, Result};
 
-use super::{Deserializer, DeserializerState, Type};
+use super::{preallocate, Deserializer, DeserializerState, Type};
 
 pub struct TupleDeserializer;
 
@@ -16,7 +16,7 @@ impl Deserializer for TupleDeserializer {
                     item.deserialize_prefix(reader, state).await?;
                 }
             }
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         }
         Ok(())
     }
@@ -27,22 +27,25 @@ impl Deserializer for TupleDeserializer {
         rows: usize,
         state: &mut DeserializerState,
     ) -> Result<Vec<Value>> {
-        let types = type_.unwrap_tuple();
-        let mut tuples = vec![Value::Tuple(Vec::with_capacity(types.len())); rows];
+        let types = type_.try_unwrap_tuple()?;
+        let mut columns = Vec::with_capacity(types.len());
         for type_ in types {
-            for (i, value) in type_
-                .deserialize_column(reader, rows, state)
-                .await?
-                .into_iter()
-                .enumerate()
-            {
-                match &mut tuples[i] {
-                    Value::Tuple(values) => {
-                        values.push(value);
-                    }
-                    _ => unimplemented!(),
-                }
+            columns.push(
+                type_
+                    .deserialize_column(reader, rows, state)
+                    .await?
+                    .into_iter(),
+            );
+        }
+        let mut tuples = preallocate(rows);
+        for _ in 0..rows {
+            let mut values = Vec::with_capacity(columns.len());
+            for column in &mut columns {
+                values.push(column.next().ok_or_else(|| {
+                    KlickhouseError::DeserializeError("Tuple: short column".to_string())
+                })?);
             }
+            tuples.push(Value::Tuple(values));
         }
         Ok(tuples)
     }
@@ -0,0 +1,155 @@
+use tokio::io::AsyncReadExt;
+
+use crate::{convert::unexpected_type, io::ClickhouseRead, values::Value, KlickhouseError, Result};
+
+use super::{preallocate, read_bytes, Deserializer, DeserializerState, Type};
+use crate::types::variant::{
+    BASIC_DISCRIMINATORS_MODE, DYNAMIC_SERIALIZATION_V1, DYNAMIC_SERIALIZATION_V2,
+    NULL_DISCRIMINATOR, SHARED_VARIANT,
+};
+
+pub struct VariantDeserializer;
+
+impl Deserializer for VariantDeserializer {
+    async fn read_prefix<R: ClickhouseRead>(
+        type_: &Type,
+        reader: &mut R,
+        state: &mut DeserializerState,
+    ) -> Result<()> {
+        let Type::Variant(types) = type_ else {
+            return Err(unexpected_type(type_));
+        };
+        let mode = reader.read_u64_le().await?;
+        if mode != BASIC_DISCRIMINATORS_MODE {
+            return Err(KlickhouseError::DeserializeError(format!(
+                "unsupported Variant discriminators serialization mode {mode}"
+            )));
+        }
+        for type_ in types {
+            type_.deserialize_prefix(reader, state).await?;
+        }
+        Ok(())
+    }
+
+    async fn read<R: ClickhouseRead>(
+        type_: &Type,
+        reader: &mut R,
+        rows: usize,
+        state: &mut DeserializerState,
+    ) -> Result<Vec<Value>> {
+        let Type::Variant(types) = type_ else {
+            return Err(unexpected_type(type_));
+        };
+        let discriminators = read_bytes(reader, rows).await?;
+        let mut counts = vec![0usize; types.len()];
+        for discriminator in &discriminators {
+            if *discriminator == NULL_DISCRIMINATOR {
+                continue;
+            }
+            let Some(count) = counts.get_mut(*discriminator as usize) else {
+                return Err(KlickhouseError::DeserializeError(format!(
+                    "discriminator {discriminator} out of range for {type_}"
+                )));
+            };
+            *count += 1;
+        }
+        let mut columns = Vec::with_capacity(types.len());
+        for (type_, count) in types.iter().zip(counts) {
+            columns.push(
+                type_
+                    .deserialize_column(reader, count, state)
+                    .await?
+                    .into_iter(),
+            );
+        }
+        let mut out = preallocate(rows);
+        for discriminator in discriminators {
+            if discriminator == NULL_DISCRIMINATOR {
+                out.push(Value::Null);
+                continue;
+            }
+            let value = columns[discriminator as usize].next().ok_or_else(|| {
+                KlickhouseError::DeserializeError(format!("missing values of {type_}"))
+            })?;
+            out.push(Value::Variant(Box::new(value), discriminator));
+        }
+        Ok(out)
+    }
+}
+
+/// Reads `Dynamic` columns as the `Variant` of the types listed in their prefix, plus the shared variant.
+/// Values are returned as they are, without their discriminator.
+pub struct DynamicDeserializer;
+
+impl Deserializer for DynamicDeserializer {
+    async fn read_prefix<R: ClickhouseRead>(
+        _type_: &Type,
+        reader: &mut R,
+        state: &mut DeserializerState,
+    ) -> Result<()> {
+        match reader.read_u64_le().await? {
+            DYNAMIC_SERIALIZATION_V1 => {
+                // max_types
+                reader.read_var_uint().await?;
+            }
+            DYNAMIC_SERIALIZATION_V2 => (),
+            version => {
+                return Err(KlickhouseError::DeserializeError(format!(
+                    "unsupported Dynamic serialization version {version}"
+                )))
+            }
+        }
+        let count = reader.read_var_uint().await?;
+        if count >= NULL_DISCRIMINATOR as u64 {
+            return Err(KlickhouseError::DeserializeError(format!(
+                "too many Dynamic types: {count}"
+            )));
+        }
+        let mut names = vec![SHARED_VARIANT.to_string()];
+        for _ in 0..count {
+            names.push(reader.read_utf8_string().await?);
+        }
+        // variants are ordered by name, which gives their discriminators
+        names.sort();
+        let mut types = Vec::with_capacity(names.len());
+        let mut shared = 0;
+        for (i, name) in names.iter().enumerate() {
+            if name == SHARED_VARIANT {
+                shared = i as u8;
+                types.push(Type::String);
+            } else {
+                types.push(name.parse()?);
+            }
+        }
+        let variant = Type::Variant(types);
+        variant.deserialize_prefix(reader, state).await?;
+        state.dynamic_variants.push_back((variant, shared));
+        Ok(())
+    }
+
+    async fn read<R: ClickhouseRead>(
+        type_: &Type,
+        reader: &mut R,
+        rows: usize,
+        state: &mut DeserializerState,
+    ) -> Result<Vec<Value>> {
+        let (variant, shared) = state.dynamic_variants.pop_front().ok_or_else(|| {
+            KlickhouseError::DeserializeError(format!("missing prefix of {type_}"))
+        })?;
+        variant
+            .deserialize_column(reader, rows, state)
+            .await?
+            .into_iter()
+            .map(|value| match value {
+                Value::Variant(_, discriminator) if discriminator == shared => {
+                    Err(KlickhouseError::DeserializeError(
+                        "values of Dynamic types beyond its max_types are not supported"
+                            .to_string(),
+                    ))
+                }
+                Value::Variant(value, _) => Ok(*value),
+                value => Ok(value),
+            })
+            .collect()
+    }
+}
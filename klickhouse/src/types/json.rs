@@ -0,0 +1,2 @@
+/// `ObjectSerializationVersion::STRING` of the `JSON` type: each row is the object as JSON text, in a `String` column.
+pub const JSON_STRING_SERIALIZATION_VERSION: u64 = 1;
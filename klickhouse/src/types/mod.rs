@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::future::Future;
 use std::{fmt::Display, str::FromStr};
 
@@ -6,18 +7,21 @@ use futures_util::FutureExt;
 use uuid::Uuid;
 
 mod deserialize;
+mod json;
 mod low_cardinality;
 mod serialize;
 #[cfg(test)]
 mod tests;
+mod variant;
 
 use crate::{
+    convert::unexpected_type,
     i256,
     io::{ClickhouseRead, ClickhouseWrite},
     protocol::MAX_STRING_SIZE,
     u256,
     values::Value,
-    Date, DateTime, DynDateTime64, Ipv4, Ipv6, KlickhouseError, Result,
+    Date, DateTime, DynDateTime64, Ipv4, Ipv6, KlickhouseError, Result, TypeParseError,
 };
 
 /// A raw Clickhouse type.
@@ -64,6 +68,11 @@ pub enum Type {
     Ring,
     Polygon,
     MultiPolygon,
+    LineString,
+    MultiLineString,
+    /// Any geo value, as an alias of `Variant(LineString, MultiLineString, MultiPolygon, Point, Polygon, Ring)`.
+    /// Values are [`Value::Point`], [`Value::Ring`], ... or `Value::Null`, without a discriminator.
+    Geometry,
     /// Not supported
     Enum8(Vec<(String, i8)>),
     /// Not supported
@@ -80,13 +89,32 @@ pub enum Type {
     Nullable(Box<Type>),
 
     Map(Box<Type>, Box<Type>),
+
+    /// `JSON`, exchanged with the server as text: values are [`Value::String`]s holding a JSON object.
+    /// Type parameters (typed paths, `max_dynamic_paths`, `SKIP`) are accepted, but not kept.
+    /// Queries reading `JSON` columns must set [`QueryOptions::json_as_string`](crate::QueryOptions::json_as_string),
+    /// as the native serialization with a subcolumn per dynamic path isn't supported.
+    Json,
+
+    /// Values are [`Value::Variant`]s holding the index of their type, or `Value::Null`.
+    /// The server orders variants by type name, which gives their indices, so they must be listed in that order.
+    /// Parsed type names are ordered as the server would.
+    Variant(Vec<Type>),
+    /// Values are read as they are, and sent as a `Variant` of their [guessed types](Value::guess_type), which the server converts.
+    /// `max_types` is accepted, but not kept.
+    Dynamic,
 }
 
 impl Type {
+    /// Panics if the type is not an Array, see [`Type::try_unwrap_array`].
     pub fn unwrap_array(&self) -> &Type {
+        self.try_unwrap_array().unwrap()
+    }
+
+    pub fn try_unwrap_array(&self) -> Result<&Type> {
         match self {
-            Type::Array(x) => x,
-            _ => unimplemented!(),
+            Type::Array(x) => Ok(x),
+            x => Err(unexpected_type(x)),
         }
     }
 
@@ -97,10 +125,15 @@ impl Type {
         }
     }
 
+    /// Panics if the type is not a Map, see [`Type::try_unwrap_map`].
     pub fn unwrap_map(&self) -> (&Type, &Type) {
+        self.try_unwrap_map().unwrap()
+    }
+
+    pub fn try_unwrap_map(&self) -> Result<(&Type, &Type)> {
         match self {
-            Type::Map(key, value) => (&**key, &**value),
-            _ => unimplemented!(),
+            Type::Map(key, value) => Ok((&**key, &**value)),
+            x => Err(unexpected_type(x)),
         }
     }
 
@@ -111,10 +144,15 @@ impl Type {
         }
     }
 
+    /// Panics if the type is not a Tuple, see [`Type::try_unwrap_tuple`].
     pub fn unwrap_tuple(&self) -> &[Type] {
+        self.try_unwrap_tuple().unwrap()
+    }
+
+    pub fn try_unwrap_tuple(&self) -> Result<&[Type]> {
         match self {
-            Type::Tuple(x) => &x[..],
-            _ => unimplemented!(),
+            Type::Tuple(x) => Ok(&x[..]),
+            x => Err(unexpected_type(x)),
         }
     }
 
@@ -175,6 +213,8 @@ impl Type {
             Type::Ring => Value::Ring(Default::default()),
             Type::Polygon => Value::Polygon(Default::default()),
             Type::MultiPolygon => Value::MultiPolygon(Default::default()),
+            Type::LineString => Value::LineString(Default::default()),
+            Type::MultiLineString => Value::MultiLineString(Default::default()),
             Type::Enum8(_) => Value::Enum8(0),
             Type::Enum16(_) => Value::Enum16(0),
             Type::LowCardinality(x) => x.default_value(),
@@ -183,15 +223,120 @@ impl Type {
             Type::Tuple(types) => Value::Tuple(types.iter().map(|x| x.default_value()).collect()),
             Type::Nullable(_) => Value::Null,
             Type::Map(_, _) => Value::Map(vec![], vec![]),
+            Type::Json => Value::String(b"{}".to_vec()),
+            Type::Variant(_) | Type::Dynamic | Type::Geometry => Value::Null,
+        }
+    }
+
+    /// Like [`Type::default_value`], but not `Null` or empty wherever the type allows it, and naming the first entry of enums,
+    /// so reading it into a Rust type only fails if the type itself isn't accepted.
+    pub(crate) fn sample_value(&self) -> Value {
+        match self {
+            Type::Enum8(entries) => Value::Enum8(entries.first().map(|x| x.1).unwrap_or_default()),
+            Type::Enum16(entries) => {
+                Value::Enum16(entries.first().map(|x| x.1).unwrap_or_default())
+            }
+            Type::LowCardinality(x) | Type::Nullable(x) => x.sample_value(),
+            Type::Array(x) => Value::Array(vec![x.sample_value()]),
+            Type::Tuple(types) => Value::Tuple(types.iter().map(|x| x.sample_value()).collect()),
+            Type::Map(key, value) => {
+                Value::Map(vec![key.sample_value()], vec![value.sample_value()])
+            }
+            Type::Variant(types) => match types.first() {
+                Some(x) => Value::Variant(Box::new(x.sample_value()), 0),
+                None => Value::Null,
+            },
+            Type::Geometry => Type::Point.default_value(),
+            x => x.default_value(),
         }
     }
 
+    /// The `Variant` that `Geometry` is an alias of.
+    pub(crate) fn geometry_variant() -> Type {
+        Type::Variant(vec![
+            Type::LineString,
+            Type::MultiLineString,
+            Type::MultiPolygon,
+            Type::Point,
+            Type::Polygon,
+            Type::Ring,
+        ])
+    }
+
     pub fn strip_low_cardinality(&self) -> &Type {
         match self {
             Type::LowCardinality(x) => x,
             _ => self,
         }
     }
+
+    /// All scalar types that take no parameters, plus `DateTime` in UTC.
+    /// Types whose parameters are user chosen (decimals, `FixedString`, `DateTime64`, enums) are not included.
+    pub fn all_scalars() -> Vec<Type> {
+        vec![
+            Type::Int8,
+            Type::Int16,
+            Type::Int32,
+            Type::Int64,
+            Type::Int128,
+            Type::Int256,
+            Type::UInt8,
+            Type::UInt16,
+            Type::UInt32,
+            Type::UInt64,
+            Type::UInt128,
+            Type::UInt256,
+            Type::Float32,
+            Type::Float64,
+            Type::String,
+            Type::Uuid,
+            Type::Date,
+            Type::DateTime(Tz::UTC),
+            Type::Ipv4,
+            Type::Ipv6,
+        ]
+    }
+
+    /// True for integer, floating point and decimal types. `Nullable` and `LowCardinality` wrappers are looked through.
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self.strip_null().strip_low_cardinality().strip_null(),
+            Type::Int8
+                | Type::Int16
+                | Type::Int32
+                | Type::Int64
+                | Type::Int128
+                | Type::Int256
+                | Type::UInt8
+                | Type::UInt16
+                | Type::UInt32
+                | Type::UInt64
+                | Type::UInt128
+                | Type::UInt256
+                | Type::Float32
+                | Type::Float64
+                | Type::Decimal32(_)
+                | Type::Decimal64(_)
+                | Type::Decimal128(_)
+                | Type::Decimal256(_)
+        )
+    }
+
+    /// True for `Date`, `DateTime` and `DateTime64`. `Nullable` and `LowCardinality` wrappers are looked through.
+    pub fn is_temporal(&self) -> bool {
+        matches!(
+            self.strip_null().strip_low_cardinality().strip_null(),
+            Type::Date | Type::DateTime(_) | Type::DateTime64(_, _)
+        )
+    }
+
+    /// True for `String`, `FixedString` and enums, which are all read and written as text. `Nullable` and `LowCardinality` wrappers are looked through.
+    pub fn is_string_like(&self) -> bool {
+        matches!(
+            self.strip_null().strip_low_cardinality().strip_null(),
+            Type::String | Type::FixedString(_) | Type::Enum8(_) | Type::Enum16(_)
+        )
+    }
 }
 
 // we assume complete identifier normalization and type resolution from clickhouse
@@ -206,37 +351,89 @@ fn eat_identifier(input: &str) -> (&str, &str) {
     (input, "")
 }
 
-fn parse_args(input: &str) -> Result<Vec<&str>> {
+/// Bounds the recursion of [`Type::from_str`], since type names come from the server.
+const MAX_TYPE_DEPTH: usize = 128;
+
+/// A type parse failure at `at`, a slice of the type name being parsed, which locates it within the full name.
+struct ParseFailure<'a> {
+    at: &'a str,
+    message: String,
+}
+
+impl<'a> ParseFailure<'a> {
+    fn new(at: &'a str, message: impl Into<String>) -> Self {
+        Self {
+            at,
+            message: message.into(),
+        }
+    }
+
+    fn into_error(self, input: &str) -> KlickhouseError {
+        let position = (self.at.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|x| *x <= input.len())
+            .unwrap_or(0);
+        KlickhouseError::TypeParseError(TypeParseError {
+            input: input.to_string(),
+            position,
+            message: self.message,
+        })
+    }
+}
+
+type ParseResult<'a, T> = std::result::Result<T, ParseFailure<'a>>;
+
+fn parse_args(input: &str) -> ParseResult<'_, Vec<&str>> {
     if !input.starts_with('(') || !input.ends_with(')') {
-        return Err(KlickhouseError::TypeParseError(
-            "malformed arguments to type".to_string(),
-        ));
+        return Err(ParseFailure::new(input, "malformed arguments to type"));
     }
     let input = input[1..input.len() - 1].trim();
     let mut out = vec![];
     let mut in_parens = 0usize;
     let mut last_start = 0;
-    // todo: handle parens in enum strings?
+    // enum names are quoted strings, which may contain commas and parentheses
+    let mut in_quotes = None;
+    let mut escaped = false;
     for (i, c) in input.char_indices() {
+        if in_quotes.is_some() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '\'' => in_quotes = None,
+                _ => (),
+            }
+            continue;
+        }
         match c {
-            ',' => {
-                if in_parens == 0 {
-                    out.push(input[last_start..i].trim());
-                    last_start = i + 1;
-                }
+            '\'' => in_quotes = Some(i),
+            ',' if in_parens == 0 => {
+                out.push(input[last_start..i].trim());
+                last_start = i + 1;
             }
             '(' => {
                 in_parens += 1;
+                if in_parens > MAX_TYPE_DEPTH {
+                    return Err(ParseFailure::new(
+                        &input[i..],
+                        format!("type nested more than {MAX_TYPE_DEPTH} levels deep"),
+                    ));
+                }
             }
             ')' => {
-                in_parens -= 1;
+                in_parens = in_parens
+                    .checked_sub(1)
+                    .ok_or_else(|| ParseFailure::new(&input[i..], "mismatched parenthesis"))?;
             }
             _ => (),
         }
     }
+    if let Some(start) = in_quotes {
+        return Err(ParseFailure::new(&input[start..], "unterminated string"));
+    }
     if in_parens != 0 {
-        return Err(KlickhouseError::TypeParseError(
-            "mismatched parenthesis".to_string(),
+        return Err(ParseFailure::new(
+            &input[last_start..],
+            "mismatched parenthesis",
         ));
     }
     if last_start != input.len() {
@@ -245,247 +442,339 @@ fn parse_args(input: &str) -> Result<Vec<&str>> {
     Ok(out)
 }
 
-fn parse_scale(from: &str) -> Result<usize> {
+/// Parses a quoted enum name, returning it and the rest of the input.
+fn parse_enum_name(input: &str) -> ParseResult<'_, (String, &str)> {
+    let malformed = || ParseFailure::new(input, format!("malformed enum entry: '{input}'"));
+    let quoted = input.strip_prefix('\'').ok_or_else(malformed)?;
+    let mut name = vec![];
+    let mut buf = [0u8; 4];
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' => {
+                let name = String::from_utf8(name).map_err(|_| malformed())?;
+                return Ok((name, &quoted[i + 1..]));
+            }
+            '\\' => {
+                let (_, escaped) = chars.next().ok_or_else(malformed)?;
+                match escaped {
+                    'b' => name.push(0x08),
+                    'f' => name.push(0x0C),
+                    'r' => name.push(b'\r'),
+                    'n' => name.push(b'\n'),
+                    't' => name.push(b'\t'),
+                    '0' => name.push(b'\0'),
+                    'a' => name.push(0x07),
+                    'v' => name.push(0x0B),
+                    'x' => {
+                        let hex = quoted.get(i + 2..i + 4).ok_or_else(malformed)?;
+                        name.push(u8::from_str_radix(hex, 16).map_err(|_| malformed())?);
+                        chars.nth(1);
+                    }
+                    c => name.extend_from_slice(c.encode_utf8(&mut buf).as_bytes()),
+                }
+            }
+            c => name.extend_from_slice(c.encode_utf8(&mut buf).as_bytes()),
+        }
+    }
+    Err(malformed())
+}
+
+/// Parses the entries of an `Enum8` or `Enum16`, i.e. `'a' = 1, 'b' = 2`.
+/// Entries without a value are numbered after the previous one, starting at 1, as in Clickhouse DDL.
+fn parse_enum_entries<'a, T: TryFrom<i64>>(
+    input: &'a str,
+    kind: &str,
+    args: &[&'a str],
+) -> ParseResult<'a, Vec<(String, T)>> {
+    if args.is_empty() {
+        return Err(ParseFailure::new(
+            input,
+            format!("{kind} must have at least one entry"),
+        ));
+    }
+    let mut out = Vec::with_capacity(args.len());
+    let mut next = 1i64;
+    for arg in args {
+        let (name, rest) = parse_enum_name(arg)?;
+        let rest = rest.trim();
+        let value = if rest.is_empty() {
+            next
+        } else {
+            let value = rest.strip_prefix('=').ok_or_else(|| {
+                ParseFailure::new(rest, format!("malformed {kind} entry: '{arg}'"))
+            })?;
+            let value = value.trim();
+            value.parse::<i64>().map_err(|e| {
+                ParseFailure::new(value, format!("bad value for {kind} entry '{arg}': {e}"))
+            })?
+        };
+        next = value + 1;
+        let value = T::try_from(value).map_err(|_| {
+            ParseFailure::new(arg, format!("value {value} out of range for {kind}"))
+        })?;
+        out.push((name, value));
+    }
+    Ok(out)
+}
+
+fn parse_scale(from: &str) -> ParseResult<'_, usize> {
     from.parse()
-        .map_err(|_| KlickhouseError::TypeParseError("couldn't parse scale".to_string()))
+        .map_err(|_| ParseFailure::new(from, "couldn't parse scale"))
 }
 
-fn parse_precision(from: &str) -> Result<usize> {
+fn parse_precision(from: &str) -> ParseResult<'_, usize> {
     from.parse()
-        .map_err(|_| KlickhouseError::TypeParseError("couldn't parse precision".to_string()))
+        .map_err(|_| ParseFailure::new(from, "couldn't parse precision"))
 }
 
-impl FromStr for Type {
-    type Err = KlickhouseError;
+fn parse_decimal_scale(from: &str, max: usize) -> ParseResult<'_, usize> {
+    let scale = parse_scale(from)?;
+    if scale > max {
+        return Err(ParseFailure::new(
+            from,
+            format!("decimal scale {scale} exceeds {max}"),
+        ));
+    }
+    Ok(scale)
+}
 
-    fn from_str(s: &str) -> Result<Self> {
-        let (ident, following) = eat_identifier(s);
-        if ident.is_empty() {
-            return Err(KlickhouseError::TypeParseError(format!(
-                "invalid empty identifier for type: '{}'",
-                s
-            )));
-        }
-        let following = following.trim();
-        if !following.is_empty() {
-            let args = parse_args(following)?;
-            return Ok(match ident {
-                "Decimal" => {
-                    if args.len() != 2 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for Decimal, expected 2 and got {}",
-                            args.len()
-                        )));
-                    }
-                    let p: usize = parse_precision(args[0])?;
-                    let s: usize = parse_scale(args[1])?;
-                    if p <= 9 {
-                        Type::Decimal32(s)
-                    } else if p <= 18 {
-                        Type::Decimal64(s)
-                    } else if p <= 38 {
-                        Type::Decimal128(s)
-                    } else if p <= 76 {
-                        Type::Decimal256(s)
-                    } else {
-                        return Err(KlickhouseError::TypeParseError(
-                            "bad decimal spec, cannot exceed 76 precision".to_string(),
-                        ));
-                    }
-                }
-                "Decimal32" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for Decimal32, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::Decimal32(parse_scale(args[0])?)
-                }
-                "Decimal64" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for Decimal64, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::Decimal64(parse_scale(args[0])?)
-                }
-                "Decimal128" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for Decimal128, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::Decimal128(parse_scale(args[0])?)
-                }
-                "Decimal256" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for Decimal256, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::Decimal256(parse_scale(args[0])?)
-                }
-                "FixedString" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for FixedString, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::FixedString(parse_scale(args[0])?)
+fn parse_datetime64_precision(from: &str) -> ParseResult<'_, usize> {
+    let precision = parse_precision(from)?;
+    if precision > 9 {
+        return Err(ParseFailure::new(
+            from,
+            format!("DateTime64 precision cannot exceed 9, got {precision}"),
+        ));
+    }
+    Ok(precision)
+}
+
+/// Parses a quoted time zone name, i.e. `'Europe/Berlin'`.
+fn parse_timezone<'a>(kind: &str, from: &'a str) -> ParseResult<'a, Tz> {
+    if from.len() < 2 || !from.starts_with('\'') || !from.ends_with('\'') {
+        return Err(ParseFailure::new(
+            from,
+            format!("failed to parse timezone for {kind}: '{from}'"),
+        ));
+    }
+    from[1..from.len() - 1].parse().map_err(|e| {
+        ParseFailure::new(
+            from,
+            format!("failed to parse timezone for {kind}: '{from}': {e}"),
+        )
+    })
+}
+
+/// Fails unless `args` has `count` arguments.
+fn expect_args<'a>(input: &'a str, kind: &str, args: &[&str], count: usize) -> ParseResult<'a, ()> {
+    if args.len() != count {
+        return Err(ParseFailure::new(
+            input,
+            format!(
+                "bad arg count for {kind}, expected {count} and got {}",
+                args.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_type(s: &str) -> ParseResult<'_, Type> {
+    let (ident, following) = eat_identifier(s);
+    if ident.is_empty() {
+        return Err(ParseFailure::new(
+            s,
+            format!("invalid empty identifier for type: '{}'", s),
+        ));
+    }
+    let following = following.trim();
+    if !following.is_empty() {
+        let args = parse_args(following)?;
+        return Ok(match ident {
+            "Decimal" => {
+                expect_args(following, ident, &args, 2)?;
+                let p: usize = parse_precision(args[0])?;
+                let s: usize = parse_decimal_scale(args[1], p)?;
+                if p <= 9 {
+                    Type::Decimal32(s)
+                } else if p <= 18 {
+                    Type::Decimal64(s)
+                } else if p <= 38 {
+                    Type::Decimal128(s)
+                } else if p <= 76 {
+                    Type::Decimal256(s)
+                } else {
+                    return Err(ParseFailure::new(
+                        args[0],
+                        "bad decimal spec, cannot exceed 76 precision",
+                    ));
                 }
-                "DateTime" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for DateTime, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    if !args[0].starts_with('\'') || !args[0].ends_with('\'') {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "failed to parse timezone for DateTime: '{}'",
-                            args[0]
-                        )));
-                    }
-                    Type::DateTime(args[0][1..args[0].len() - 1].parse().map_err(|e| {
-                        KlickhouseError::TypeParseError(format!(
-                            "failed to parse timezone for DateTime: '{}': {}",
-                            args[0], e
-                        ))
-                    })?)
-                }
-                "DateTime64" => {
-                    if args.len() == 2 {
-                        if !args[1].starts_with('\'') || !args[1].ends_with('\'') {
-                            return Err(KlickhouseError::TypeParseError(format!(
-                                "failed to parse timezone for DateTime64: '{}'",
-                                args[0]
-                            )));
-                        }
-                        Type::DateTime64(
-                            parse_precision(args[0])?,
-                            args[1][1..args[1].len() - 1].parse().map_err(|e| {
-                                KlickhouseError::TypeParseError(format!(
-                                    "failed to parse timezone for DateTime64: '{}': {}",
-                                    args[0], e
-                                ))
-                            })?,
-                        )
-                    } else if args.len() == 1 {
-                        Type::DateTime64(parse_precision(args[0])?, chrono_tz::UTC)
-                    } else {
-                        return Err(KlickhouseError::TypeParseError(format!(
+            }
+            "Decimal32" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::Decimal32(parse_decimal_scale(args[0], 9)?)
+            }
+            "Decimal64" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::Decimal64(parse_decimal_scale(args[0], 18)?)
+            }
+            "Decimal128" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::Decimal128(parse_decimal_scale(args[0], 38)?)
+            }
+            "Decimal256" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::Decimal256(parse_decimal_scale(args[0], 76)?)
+            }
+            "FixedString" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::FixedString(parse_scale(args[0])?)
+            }
+            "DateTime" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::DateTime(parse_timezone(ident, args[0])?)
+            }
+            "DateTime64" => {
+                if args.len() == 2 {
+                    Type::DateTime64(
+                        parse_datetime64_precision(args[0])?,
+                        parse_timezone(ident, args[1])?,
+                    )
+                } else if args.len() == 1 {
+                    Type::DateTime64(parse_datetime64_precision(args[0])?, chrono_tz::UTC)
+                } else {
+                    return Err(ParseFailure::new(
+                        following,
+                        format!(
                             "bad arg count for DateTime64, expected 1 or 2 and got {}",
                             args.len()
-                        )));
-                    }
-                }
-                "Enum8" => {
-                    return Err(KlickhouseError::TypeParseError(
-                        "unsupported Enum8 type".to_string(),
+                        ),
                     ));
                 }
-                "Enum16" => {
-                    return Err(KlickhouseError::TypeParseError(
-                        "unsupported Enum16 type".to_string(),
-                    ));
-                }
-                "LowCardinality" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for LowCardinality, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::LowCardinality(Box::new(Type::from_str(args[0])?))
-                }
-                "Array" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for Array, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::Array(Box::new(Type::from_str(args[0])?))
-                }
-                "Nested" => {
-                    return Err(KlickhouseError::TypeParseError(
-                        "unsupported Nested type".to_string(),
-                    ));
-                }
-                "Tuple" => {
-                    let mut inner = vec![];
-                    for arg in args {
-                        inner.push(arg.trim().parse()?);
-                    }
-                    Type::Tuple(inner)
-                }
-                "Nullable" => {
-                    if args.len() != 1 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for Nullable, expected 1 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::Nullable(Box::new(Type::from_str(args[0])?))
-                }
-                "Map" => {
-                    if args.len() != 2 {
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "bad arg count for Map, expected 2 and got {}",
-                            args.len()
-                        )));
-                    }
-                    Type::Map(
-                        Box::new(Type::from_str(args[0])?),
-                        Box::new(Type::from_str(args[1])?),
-                    )
+            }
+            "Enum8" => Type::Enum8(parse_enum_entries(following, "Enum8", &args)?),
+            "Enum16" => Type::Enum16(parse_enum_entries(following, "Enum16", &args)?),
+            "LowCardinality" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::LowCardinality(Box::new(parse_type(args[0])?))
+            }
+            "Array" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::Array(Box::new(parse_type(args[0])?))
+            }
+            "Nested" => {
+                return Err(ParseFailure::new(s, "unsupported Nested type"));
+            }
+            "JSON" => Type::Json,
+            "Variant" => {
+                let mut inner = vec![];
+                for arg in args {
+                    inner.push(parse_type(arg.trim())?);
                 }
-                _ => {
-                    return Err(KlickhouseError::TypeParseError(format!(
-                        "invalid type with arguments: '{}'",
-                        ident
-                    )))
+                inner.sort_by_cached_key(|x| x.to_string());
+                Type::Variant(inner)
+            }
+            "Dynamic" => Type::Dynamic,
+            "Object" => {
+                return Err(ParseFailure::new(
+                    s,
+                    "unsupported deprecated Object type, use JSON",
+                ));
+            }
+            "Tuple" => {
+                let mut inner = vec![];
+                for arg in args {
+                    inner.push(parse_type(arg.trim())?);
                 }
-            });
-        }
-        Ok(match ident {
-            "Int8" => Type::Int8,
-            "Int16" => Type::Int16,
-            "Int32" => Type::Int32,
-            "Int64" => Type::Int64,
-            "Int128" => Type::Int128,
-            "Int256" => Type::Int256,
-            "Bool" | "UInt8" => Type::UInt8,
-            "UInt16" => Type::UInt16,
-            "UInt32" => Type::UInt32,
-            "UInt64" => Type::UInt64,
-            "UInt128" => Type::UInt128,
-            "UInt256" => Type::UInt256,
-            "Float32" => Type::Float32,
-            "Float64" => Type::Float64,
-            "String" => Type::String,
-            "UUID" => Type::Uuid,
-            "Date" => Type::Date,
-            "DateTime" => Type::DateTime(chrono_tz::UTC),
-            "IPv4" => Type::Ipv4,
-            "IPv6" => Type::Ipv6,
-            "Point" => Type::Point,
-            "Ring" => Type::Ring,
-            "Polygon" => Type::Polygon,
-            "MultiPolygon" => Type::MultiPolygon,
+                Type::Tuple(inner)
+            }
+            "Nullable" => {
+                expect_args(following, ident, &args, 1)?;
+                Type::Nullable(Box::new(parse_type(args[0])?))
+            }
+            "Map" => {
+                expect_args(following, ident, &args, 2)?;
+                Type::Map(
+                    Box::new(parse_type(args[0])?),
+                    Box::new(parse_type(args[1])?),
+                )
+            }
             _ => {
-                return Err(KlickhouseError::TypeParseError(format!(
-                    "invalid type name: '{}'",
-                    ident
-                )))
+                return Err(ParseFailure::new(
+                    s,
+                    format!("invalid type with arguments: '{}'", ident),
+                ))
             }
-        })
+        });
+    }
+    Ok(match ident {
+        "Int8" => Type::Int8,
+        "Int16" => Type::Int16,
+        "Int32" => Type::Int32,
+        "Int64" => Type::Int64,
+        "Int128" => Type::Int128,
+        "Int256" => Type::Int256,
+        "Bool" | "UInt8" => Type::UInt8,
+        "UInt16" => Type::UInt16,
+        "UInt32" => Type::UInt32,
+        "UInt64" => Type::UInt64,
+        "UInt128" => Type::UInt128,
+        "UInt256" => Type::UInt256,
+        "Float32" => Type::Float32,
+        "Float64" => Type::Float64,
+        "String" => Type::String,
+        "UUID" => Type::Uuid,
+        "Date" => Type::Date,
+        "DateTime" => Type::DateTime(chrono_tz::UTC),
+        "IPv4" => Type::Ipv4,
+        "IPv6" => Type::Ipv6,
+        "Point" => Type::Point,
+        "Ring" => Type::Ring,
+        "Polygon" => Type::Polygon,
+        "MultiPolygon" => Type::MultiPolygon,
+        "LineString" => Type::LineString,
+        "MultiLineString" => Type::MultiLineString,
+        "Geometry" => Type::Geometry,
+        "JSON" => Type::Json,
+        "Dynamic" => Type::Dynamic,
+        _ => {
+            return Err(ParseFailure::new(
+                s,
+                format!("invalid type name: '{}'", ident),
+            ))
+        }
+    })
+}
+
+/// Fails with a [`TypeParseError`] locating the part of `s` that couldn't be parsed.
+impl FromStr for Type {
+    type Err = KlickhouseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_type(s).map_err(|e| e.into_error(s))
+    }
+}
+
+fn write_enum_entries<T: Display>(
+    f: &mut std::fmt::Formatter<'_>,
+    items: &[(String, T)],
+) -> std::fmt::Result {
+    for (i, (name, value)) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "'")?;
+        for c in name.chars() {
+            match c {
+                '\\' => write!(f, "\\\\")?,
+                '\'' => write!(f, "\\'")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        write!(f, "' = {value}")?;
     }
+    Ok(())
 }
 
 impl Display for Type {
@@ -521,24 +810,19 @@ impl Display for Type {
             Type::Ring => write!(f, "Ring"),
             Type::Polygon => write!(f, "Polygon"),
             Type::MultiPolygon => write!(f, "MultiPolygon"),
-            Type::Enum8(items) => write!(
-                f,
-                "Enum8({})",
-                items
-                    .iter()
-                    .map(|(name, value)| format!("{}={}", name, value))
-                    .collect::<Vec<_>>()
-                    .join(",")
-            ),
-            Type::Enum16(items) => write!(
-                f,
-                "Enum16({})",
-                items
-                    .iter()
-                    .map(|(name, value)| format!("{}={}", name, value))
-                    .collect::<Vec<_>>()
-                    .join(",")
-            ),
+            Type::LineString => write!(f, "LineString"),
+            Type::MultiLineString => write!(f, "MultiLineString"),
+            Type::Geometry => write!(f, "Geometry"),
+            Type::Enum8(items) => {
+                write!(f, "Enum8(")?;
+                write_enum_entries(f, items)?;
+                write!(f, ")")
+            }
+            Type::Enum16(items) => {
+                write!(f, "Enum16(")?;
+                write_enum_entries(f, items)?;
+                write!(f, ")")
+            }
             Type::LowCardinality(inner) => write!(f, "LowCardinality({})", inner),
             Type::Array(inner) => write!(f, "Array({})", inner),
             // Type::Nested(items) => format!("Nested({})", items.iter().map(|(key, value)| format!("{} {}", key, value.to_string())).collect::<Vec<_>>().join(",")),
@@ -553,6 +837,17 @@ impl Display for Type {
             ),
             Type::Nullable(inner) => write!(f, "Nullable({})", inner),
             Type::Map(key, value) => write!(f, "Map({},{})", key, value),
+            Type::Json => write!(f, "JSON"),
+            Type::Variant(items) => write!(
+                f,
+                "Variant({})",
+                items
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            Type::Dynamic => write!(f, "Dynamic"),
         }
     }
 }
@@ -562,7 +857,7 @@ impl Type {
         &'a self,
         reader: &'a mut R,
         state: &'a mut DeserializerState,
-    ) -> impl Future<Output = Result<()>> + Send + '_ {
+    ) -> impl Future<Output = Result<()>> + Send + 'a {
         use deserialize::*;
 
         async move {
@@ -612,6 +907,15 @@ impl Type {
                 Type::MultiPolygon => {
                     geo::MultiPolygonDeserializer::read_prefix(self, reader, state).await?
                 }
+                Type::LineString => {
+                    geo::LineStringDeserializer::read_prefix(self, reader, state).await?
+                }
+                Type::MultiLineString => {
+                    geo::MultiLineStringDeserializer::read_prefix(self, reader, state).await?
+                }
+                Type::Geometry => {
+                    geo::GeometryDeserializer::read_prefix(self, reader, state).await?
+                }
                 Type::Nullable(_) => {
                     nullable::NullableDeserializer::read_prefix(self, reader, state).await?
                 }
@@ -620,6 +924,13 @@ impl Type {
                     low_cardinality::LowCardinalityDeserializer::read_prefix(self, reader, state)
                         .await?
                 }
+                Type::Json => json::JsonDeserializer::read_prefix(self, reader, state).await?,
+                Type::Variant(_) => {
+                    variant::VariantDeserializer::read_prefix(self, reader, state).await?
+                }
+                Type::Dynamic => {
+                    variant::DynamicDeserializer::read_prefix(self, reader, state).await?
+                }
             }
             Ok(())
         }
@@ -631,7 +942,7 @@ impl Type {
         reader: &'a mut R,
         rows: usize,
         state: &'a mut DeserializerState,
-    ) -> impl Future<Output = Result<Vec<Value>>> + Send + '_ {
+    ) -> impl Future<Output = Result<Vec<Value>>> + Send + 'a {
         use deserialize::*;
 
         async move {
@@ -682,6 +993,15 @@ impl Type {
                 Type::MultiPolygon => {
                     geo::MultiPolygonDeserializer::read(self, reader, rows, state).await?
                 }
+                Type::LineString => {
+                    geo::LineStringDeserializer::read(self, reader, rows, state).await?
+                }
+                Type::MultiLineString => {
+                    geo::MultiLineStringDeserializer::read(self, reader, rows, state).await?
+                }
+                Type::Geometry => {
+                    geo::GeometryDeserializer::read(self, reader, rows, state).await?
+                }
                 Type::Tuple(_) => tuple::TupleDeserializer::read(self, reader, rows, state).await?,
                 Type::Point => geo::PointDeserializer::read(self, reader, rows, state).await?,
                 Type::Nullable(_) => {
@@ -692,6 +1012,13 @@ impl Type {
                     low_cardinality::LowCardinalityDeserializer::read(self, reader, rows, state)
                         .await?
                 }
+                Type::Json => json::JsonDeserializer::read(self, reader, rows, state).await?,
+                Type::Variant(_) => {
+                    variant::VariantDeserializer::read(self, reader, rows, state).await?
+                }
+                Type::Dynamic => {
+                    variant::DynamicDeserializer::read(self, reader, rows, state).await?
+                }
             })
         }
         .boxed()
@@ -699,10 +1026,10 @@ impl Type {
 
     pub(crate) fn serialize_column<'a, W: ClickhouseWrite>(
         &'a self,
-        values: Vec<Value>,
+        values: Vec<&'a Value>,
         writer: &'a mut W,
         state: &'a mut SerializerState,
-    ) -> impl Future<Output = Result<()>> + Send + '_ {
+    ) -> impl Future<Output = Result<()>> + Send + 'a {
         use serialize::*;
 
         async move {
@@ -752,6 +1079,15 @@ impl Type {
                 Type::MultiPolygon => {
                     geo::MultiPolygonSerializer::write(self, values, writer, state).await?
                 }
+                Type::LineString => {
+                    geo::LineStringSerializer::write(self, values, writer, state).await?
+                }
+                Type::MultiLineString => {
+                    geo::MultiLineStringSerializer::write(self, values, writer, state).await?
+                }
+                Type::Geometry => {
+                    geo::GeometrySerializer::write(self, values, writer, state).await?
+                }
                 Type::Nullable(_) => {
                     nullable::NullableSerializer::write(self, values, writer, state).await?
                 }
@@ -760,6 +1096,13 @@ impl Type {
                     low_cardinality::LowCardinalitySerializer::write(self, values, writer, state)
                         .await?
                 }
+                Type::Json => json::JsonSerializer::write(self, values, writer, state).await?,
+                Type::Variant(_) => {
+                    variant::VariantSerializer::write(self, values, writer, state).await?
+                }
+                Type::Dynamic => {
+                    variant::DynamicSerializer::write(self, values, writer, state).await?
+                }
             }
             Ok(())
         }
@@ -770,7 +1113,7 @@ impl Type {
         &'a self,
         writer: &'a mut W,
         state: &'a mut SerializerState,
-    ) -> impl Future<Output = Result<()>> + Send + '_ {
+    ) -> impl Future<Output = Result<()>> + Send + 'a {
         use serialize::*;
 
         async move {
@@ -816,6 +1159,15 @@ impl Type {
                 Type::MultiPolygon => {
                     geo::MultiPolygonSerializer::write_prefix(self, writer, state).await?
                 }
+                Type::LineString => {
+                    geo::LineStringSerializer::write_prefix(self, writer, state).await?
+                }
+                Type::MultiLineString => {
+                    geo::MultiLineStringSerializer::write_prefix(self, writer, state).await?
+                }
+                Type::Geometry => {
+                    geo::GeometrySerializer::write_prefix(self, writer, state).await?
+                }
                 Type::Nullable(_) => {
                     nullable::NullableSerializer::write_prefix(self, writer, state).await?
                 }
@@ -824,42 +1176,143 @@ impl Type {
                     low_cardinality::LowCardinalitySerializer::write_prefix(self, writer, state)
                         .await?
                 }
+                Type::Json => json::JsonSerializer::write_prefix(self, writer, state).await?,
+                Type::Variant(_) => {
+                    variant::VariantSerializer::write_prefix(self, writer, state).await?
+                }
+                Type::Dynamic => {
+                    variant::DynamicSerializer::write_prefix(self, writer, state).await?
+                }
             }
             Ok(())
         }
         .boxed()
     }
 
+    pub(crate) fn contains_dynamic(&self) -> bool {
+        match self {
+            Type::Dynamic => true,
+            Type::LowCardinality(inner) | Type::Array(inner) | Type::Nullable(inner) => {
+                inner.contains_dynamic()
+            }
+            Type::Tuple(inner) | Type::Variant(inner) => inner.iter().any(Type::contains_dynamic),
+            Type::Map(key, value) => key.contains_dynamic() || value.contains_dynamic(),
+            _ => false,
+        }
+    }
+
+    /// Replaces `Dynamic` (including nested ones) with a `Variant` of the guessed types of its values, which are wrapped in [`Value::Variant`].
+    /// The server converts such a column to `Dynamic` on insert, which spares choosing a `Dynamic` structure before seeing the values.
+    pub(crate) fn resolve_dynamic(&self, values: Vec<&mut Value>) -> Result<Type> {
+        if !self.contains_dynamic() {
+            return Ok(self.clone());
+        }
+        Ok(match self {
+            Type::Dynamic => {
+                let mut types = vec![];
+                for value in &values {
+                    match &**value {
+                        Value::Null => (),
+                        Value::Variant(inner, _) => types.push(inner.guess_type()),
+                        value => types.push(value.guess_type()),
+                    }
+                }
+                types.sort_by_cached_key(|x| x.to_string());
+                types.dedup();
+                if types.is_empty() {
+                    types.push(Type::String);
+                }
+                for value in values {
+                    let inner = match std::mem::replace(value, Value::Null) {
+                        Value::Null => continue,
+                        Value::Variant(inner, _) => *inner,
+                        inner => inner,
+                    };
+                    let type_ = inner.guess_type();
+                    let discriminator = types.iter().position(|x| *x == type_).unwrap_or(0);
+                    *value = Value::Variant(Box::new(inner), discriminator as u8);
+                }
+                let type_ = Type::Variant(types);
+                type_.validate()?;
+                type_
+            }
+            Type::Array(inner) => {
+                let items = values
+                    .into_iter()
+                    .flat_map(|x| match x {
+                        Value::Array(items) => items.iter_mut().collect(),
+                        _ => vec![],
+                    })
+                    .collect();
+                Type::Array(Box::new(inner.resolve_dynamic(items)?))
+            }
+            Type::Tuple(inner) => {
+                let mut columns = inner.iter().map(|_| vec![]).collect::<Vec<_>>();
+                for value in values {
+                    if let Value::Tuple(items) = value {
+                        for (column, item) in columns.iter_mut().zip(items) {
+                            column.push(item);
+                        }
+                    }
+                }
+                inner
+                    .iter()
+                    .zip(columns)
+                    .map(|(type_, column)| type_.resolve_dynamic(column))
+                    .collect::<Result<_>>()
+                    .map(Type::Tuple)?
+            }
+            Type::Map(key, value) => {
+                let (mut keys, mut items) = (vec![], vec![]);
+                for value in values {
+                    if let Value::Map(k, v) = value {
+                        keys.extend(k.iter_mut());
+                        items.extend(v.iter_mut());
+                    }
+                }
+                Type::Map(
+                    Box::new(key.resolve_dynamic(keys)?),
+                    Box::new(value.resolve_dynamic(items)?),
+                )
+            }
+            // can't hold `Dynamic`, which `validate` rejects
+            other => other.clone(),
+        })
+    }
+
     pub(crate) fn validate(&self) -> Result<()> {
         match self {
-            Type::Decimal32(precision) => {
-                if *precision == 0 || *precision > 9 {
-                    return Err(KlickhouseError::TypeParseError(format!(
+            Type::Decimal32(precision) if *precision == 0 || *precision > 9 => {
+                return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                    self,
+                    format!(
                         "precision out of bounds for Decimal32({}) must be in range (1..=9)",
                         *precision
-                    )));
-                }
+                    ),
+                )));
             }
-            Type::DateTime64(precision, _) | Type::Decimal64(precision) => {
-                if *precision == 0 || *precision > 18 {
-                    return Err(KlickhouseError::TypeParseError(format!("precision out of bounds for Decimal64/DateTime64({}) must be in range (1..=18)", *precision)));
-                }
+            Type::DateTime64(precision, _) | Type::Decimal64(precision)
+                if *precision == 0 || *precision > 18 =>
+            {
+                return Err(KlickhouseError::TypeParseError(TypeParseError::whole(self, format!("precision out of bounds for Decimal64/DateTime64({}) must be in range (1..=18)", *precision))));
             }
-            Type::Decimal128(precision) => {
-                if *precision == 0 || *precision > 38 {
-                    return Err(KlickhouseError::TypeParseError(format!(
+            Type::Decimal128(precision) if *precision == 0 || *precision > 38 => {
+                return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                    self,
+                    format!(
                         "precision out of bounds for Decimal128({}) must be in range (1..=38)",
                         *precision
-                    )));
-                }
+                    ),
+                )));
             }
-            Type::Decimal256(precision) => {
-                if *precision == 0 || *precision > 76 {
-                    return Err(KlickhouseError::TypeParseError(format!(
+            Type::Decimal256(precision) if *precision == 0 || *precision > 76 => {
+                return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                    self,
+                    format!(
                         "precision out of bounds for Decimal256({}) must be in range (1..=76)",
                         *precision
-                    )));
-                }
+                    ),
+                )));
             }
             Type::LowCardinality(inner) => match inner.strip_null() {
                 Type::String
@@ -881,9 +1334,9 @@ impl Type {
                 | Type::UInt128
                 | Type::UInt256 => inner.validate()?,
                 _ => {
-                    return Err(KlickhouseError::TypeParseError(format!(
-                        "illegal type '{:?}' in LowCardinality, not allowed",
-                        inner
+                    return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                        self,
+                        format!("illegal type '{:?}' in LowCardinality, not allowed", inner),
                     )))
                 }
             },
@@ -896,17 +1349,47 @@ impl Type {
                     inner.validate()?;
                 }
             }
+            Type::Variant(inner) => {
+                if inner.len() >= variant::NULL_DISCRIMINATOR as usize {
+                    return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                        self,
+                        "too many types in Variant",
+                    )));
+                }
+                let names = inner.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                if names.windows(2).any(|x| x[0] >= x[1]) {
+                    return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                        self,
+                        "types in Variant must be distinct and ordered by name",
+                    )));
+                }
+                for inner in inner {
+                    if matches!(
+                        inner.strip_low_cardinality(),
+                        Type::Nullable(_) | Type::Variant(_) | Type::Dynamic | Type::Geometry
+                    ) {
+                        return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                            self,
+                            format!("illegal type '{:?}' in Variant, not allowed", inner),
+                        )));
+                    }
+                    inner.validate()?;
+                }
+            }
             Type::Nullable(inner) => {
                 match &**inner {
                     Type::Array(_)
                     | Type::Map(_, _)
                     | Type::LowCardinality(_)
                     | Type::Tuple(_)
+                    | Type::Variant(_)
+                    | Type::Dynamic
+                    | Type::Geometry
                     | Type::Nullable(_) => {
                         /*  | Type::Nested(_) */
-                        return Err(KlickhouseError::TypeParseError(format!(
-                            "nullable cannot contain composite type '{:?}'",
-                            inner
+                        return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                            self,
+                            format!("nullable cannot contain composite type '{:?}'", inner),
                         )));
                     }
                     _ => inner.validate()?,
@@ -937,7 +1420,7 @@ impl Type {
                         | Type::Enum8(_)
                         | Type::Enum16(_)
                 ) {
-                    return Err(KlickhouseError::TypeParseError("key in map must be String, Integer, LowCardinality, FixedString, UUID, Date, DateTime, Date32, Enum".to_string()));
+                    return Err(KlickhouseError::TypeParseError(TypeParseError::whole(self, "key in map must be String, Integer, LowCardinality, FixedString, UUID, Date, DateTime, Date32, Enum")));
                 }
                 key.validate()?;
                 value.validate()?;
@@ -947,17 +1430,91 @@ impl Type {
         Ok(())
     }
 
+    /// Cuts strings (including nested ones) down to the length of their `FixedString` type.
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn truncate_fixed_strings(&self, value: &mut Value) {
+        match (self, value) {
+            (Type::FixedString(length), Value::String(bytes)) => bytes.truncate(*length),
+            (Type::FixedString(length), Value::Array(items)) => items.truncate(*length),
+            (Type::LowCardinality(inner) | Type::Nullable(inner), value) => {
+                inner.truncate_fixed_strings(value)
+            }
+            (Type::Array(inner), Value::Array(items)) => {
+                for item in items {
+                    inner.truncate_fixed_strings(item);
+                }
+            }
+            (Type::Tuple(types), Value::Tuple(values)) => {
+                for (type_, value) in types.iter().zip(values) {
+                    type_.truncate_fixed_strings(value);
+                }
+            }
+            (Type::Map(key_type, value_type), Value::Map(keys, values)) => {
+                for key in keys {
+                    key_type.truncate_fixed_strings(key);
+                }
+                for value in values {
+                    value_type.truncate_fixed_strings(value);
+                }
+            }
+            _ => (),
+        }
+    }
+
     pub(crate) fn validate_value(&self, value: &Value) -> Result<()> {
         self.validate()?;
         if !self.inner_validate_value(value) {
-            return Err(KlickhouseError::TypeParseError(format!(
-                "could not assign value '{:?}' to type '{:?}'",
-                value, self
+            return Err(KlickhouseError::TypeParseError(TypeParseError::whole(
+                self,
+                self.describe_mismatch(value),
             )));
         }
         Ok(())
     }
 
+    /// Describes why `value` fails [`Type::inner_validate_value`], down to the first mismatching element of composite values.
+    fn describe_mismatch(&self, value: &Value) -> String {
+        let first_mismatch = |what: &str, types: &mut dyn Iterator<Item = (&Type, &Value)>| {
+            types
+                .enumerate()
+                .find(|(_, (type_, value))| !type_.inner_validate_value(value))
+                .map(|(i, (type_, value))| {
+                    format!("{what} {i}: {}", type_.describe_mismatch(value))
+                })
+        };
+        let mismatch = match (self, value) {
+            (Type::LowCardinality(inner), value) => return inner.describe_mismatch(value),
+            (Type::Nullable(inner), value) if value != &Value::Null => {
+                return inner.describe_mismatch(value)
+            }
+            (Type::Tuple(types), Value::Tuple(values)) if types.len() != values.len() => {
+                Some(format!(
+                    "tuple of {} values for type '{:?}' of {} elements",
+                    values.len(),
+                    self,
+                    types.len()
+                ))
+            }
+            (Type::Tuple(types), Value::Tuple(values)) => {
+                first_mismatch("tuple element", &mut types.iter().zip(values))
+            }
+            (Type::Array(inner), Value::Array(values)) => {
+                first_mismatch("array item", &mut values.iter().map(|x| (&**inner, x)))
+            }
+            (Type::Map(_, _), Value::Map(keys, values)) if keys.len() != values.len() => Some(
+                format!("map of {} keys and {} values", keys.len(), values.len()),
+            ),
+            (Type::Map(key, value), Value::Map(keys, values)) => {
+                first_mismatch("map key", &mut keys.iter().map(|x| (&**key, x))).or_else(|| {
+                    first_mismatch("map value", &mut values.iter().map(|x| (&**value, x)))
+                })
+            }
+            _ => None,
+        };
+        mismatch
+            .unwrap_or_else(|| format!("could not assign value '{:?}' to type '{:?}'", value, self))
+    }
+
     fn inner_validate_value(&self, value: &Value) -> bool {
         match (self, value) {
             (Type::Int8, Value::Int8(_))
@@ -988,11 +1545,12 @@ impl Type {
             (Type::Decimal256(precision1), Value::Decimal256(precision2, _)) => {
                 precision1 == precision2
             }
-            (Type::FixedString(_), Value::Array(items))
-            | (Type::String, Value::Array(items)) if items.iter().all(|item| matches!(item, Value::UInt8(_) | Value::Int8(_)))
+            (Type::FixedString(length), Value::Array(items)) if items.len() <= *length && items.iter().all(|item| matches!(item, Value::UInt8(_) | Value::Int8(_)))
+            => true,
+            (Type::String, Value::Array(items)) if items.iter().all(|item| matches!(item, Value::UInt8(_) | Value::Int8(_)))
             => true,
+            (Type::FixedString(length), Value::String(bytes)) => bytes.len() <= *length,
             (Type::String, Value::String(_))
-            | (Type::FixedString(_), Value::String(_))
             | (Type::Uuid, Value::Uuid(_))
             | (Type::Date, Value::Date(_)) => true,
             (Type::DateTime(tz1), Value::DateTime(date)) => tz1 == &date.0,
@@ -1000,22 +1558,33 @@ impl Type {
                 tz1 == &tz2.0 && precision1 == &tz2.2
             }
             (Type::Ipv4, Value::Ipv4(_)) | (Type::Ipv6, Value::Ipv6(_)) => true,
-            (Type::Point, Value::Point(_)) | (Type::Ring, Value::Ring(_)) | (Type::Polygon, Value::Polygon(_)) | (Type::MultiPolygon, Value::MultiPolygon(_)) => true,
+            (Type::Json, Value::String(_)) => true,
+            (Type::Variant(_) | Type::Dynamic, Value::Null) | (Type::Dynamic, _) => true,
+            (Type::Variant(types), Value::Variant(value, discriminator)) => types
+                .get(*discriminator as usize)
+                .is_some_and(|x| x.inner_validate_value(value)),
+            (Type::Variant(types), value) => types.iter().any(|x| x.inner_validate_value(value)),
+            (Type::Geometry, value) => Type::geometry_variant().inner_validate_value(value),
+            (Type::Point, Value::Point(_)) | (Type::Ring, Value::Ring(_)) | (Type::Polygon, Value::Polygon(_)) | (Type::MultiPolygon, Value::MultiPolygon(_)) | (Type::LineString, Value::LineString(_)) | (Type::MultiLineString, Value::MultiLineString(_)) => true,
             (Type::Enum8(entries), Value::Enum8(index)) => entries.iter().any(|x| x.1 == *index),
             (Type::Enum16(entries), Value::Enum16(index)) => entries.iter().any(|x| x.1 == *index),
             (Type::LowCardinality(x), value) => x.inner_validate_value(value),
             (Type::Array(inner_type), Value::Array(values)) => {
                 values.iter().all(|x| inner_type.inner_validate_value(x))
             }
-            (Type::Tuple(inner_types), Value::Tuple(values)) => inner_types
-                .iter()
-                .zip(values.iter())
-                .all(|(type_, value)| type_.inner_validate_value(value)),
+            (Type::Tuple(inner_types), Value::Tuple(values)) => {
+                inner_types.len() == values.len()
+                    && inner_types
+                        .iter()
+                        .zip(values.iter())
+                        .all(|(type_, value)| type_.inner_validate_value(value))
+            }
             (Type::Nullable(inner), value) => {
                 value == &Value::Null || inner.inner_validate_value(value)
             }
             (Type::Map(key, value), Value::Map(keys, values)) => {
-                keys.iter().all(|x| key.inner_validate_value(x))
+                keys.len() == values.len()
+                    && keys.iter().all(|x| key.inner_validate_value(x))
                     && values.iter().all(|x| value.inner_validate_value(x))
             }
             (_, _) => false,
@@ -1023,7 +1592,11 @@ impl Type {
     }
 }
 
-pub struct DeserializerState {}
+#[derive(Default)]
+pub struct DeserializerState {
+    /// Variants read from the prefixes of `Dynamic` columns, in order, taken when reading their data
+    dynamic_variants: VecDeque<(Type, u8)>,
+}
 
 pub struct SerializerState {}
 
@@ -1055,7 +1628,7 @@ pub trait Serializer {
 
     fn write<W: ClickhouseWrite>(
         type_: &Type,
-        values: Vec<Value>,
+        values: Vec<&Value>,
         writer: &mut W,
         state: &mut SerializerState,
     ) -> impl Future<Output = Result<()>>;
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use tokio::io::AsyncWriteExt;
 
 use crate::{io::ClickhouseWrite, values::Value, Result};
@@ -6,21 +8,21 @@ use super::{Serializer, SerializerState, Type};
 
 // Trait to allow serializing [Values] wrapping an array of items.
 pub trait ArraySerializerGeneric {
-    fn inner_type(type_: &Type) -> &Type;
-    fn value_len(value: &Value) -> usize;
-    fn values(value: Value) -> Vec<Value>;
+    fn inner_type(type_: &Type) -> Result<&Type>;
+    fn value_len(value: &Value) -> Result<usize>;
+    fn values(value: &Value) -> Result<Cow<'_, [Value]>>;
 }
 
 pub struct ArraySerializer;
 impl ArraySerializerGeneric for ArraySerializer {
-    fn value_len(value: &Value) -> usize {
-        value.unwrap_array_ref().len()
+    fn value_len(value: &Value) -> Result<usize> {
+        Ok(value.try_unwrap_array_ref()?.len())
     }
-    fn inner_type(type_: &Type) -> &Type {
-        type_.unwrap_array()
+    fn inner_type(type_: &Type) -> Result<&Type> {
+        type_.try_unwrap_array()
     }
-    fn values(value: Value) -> Vec<Value> {
-        value.unwrap_array()
+    fn values(value: &Value) -> Result<Cow<'_, [Value]>> {
+        value.try_unwrap_array_ref().map(Cow::Borrowed)
     }
 }
 
@@ -30,25 +32,26 @@ impl<T: ArraySerializerGeneric + 'static> Serializer for T {
         writer: &mut W,
         state: &mut SerializerState,
     ) -> Result<()> {
-        T::inner_type(type_).serialize_prefix(writer, state).await
+        T::inner_type(type_)?.serialize_prefix(writer, state).await
     }
 
     async fn write<W: ClickhouseWrite>(
         type_: &Type,
-        values: Vec<Value>,
+        values: Vec<&Value>,
         writer: &mut W,
         state: &mut SerializerState,
     ) -> Result<()> {
-        let type_ = T::inner_type(type_);
+        let type_ = T::inner_type(type_)?;
         let mut offset = 0usize;
         for value in &values {
-            offset += Self::value_len(value);
+            offset += Self::value_len(value)?;
             writer.write_u64_le(offset as u64).await?;
         }
-        let mut all_values: Vec<Value> = Vec::with_capacity(offset);
-        for value in values {
-            all_values.extend(Self::values(value));
-        }
+        let items = values
+            .into_iter()
+            .map(Self::values)
+            .collect::<Result<Vec<_>>>()?;
+        let all_values = items.iter().flat_map(|x| x.iter()).collect();
         type_.serialize_column(all_values, writer, state).await?;
         Ok(())
     }
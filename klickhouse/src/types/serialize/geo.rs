@@ -1,6 +1,8 @@
+use std::borrow::Cow;
+
 use crate::{io::ClickhouseWrite, values::Value, Result};
 
-use super::{Serializer, SerializerState, Type};
+use super::{unexpected_value, Serializer, SerializerState, Type};
 
 pub struct PointSerializer;
 
@@ -18,7 +20,7 @@ impl Serializer for PointSerializer {
 
     async fn write<W: ClickhouseWrite>(
         _type_: &Type,
-        values: Vec<Value>,
+        values: Vec<&Value>,
         writer: &mut W,
         state: &mut SerializerState,
     ) -> Result<()> {
@@ -28,15 +30,15 @@ impl Serializer for PointSerializer {
         ];
         for value in values {
             let Value::Point(point) = value else {
-                unreachable!()
+                return Err(unexpected_value(value, &Type::Point));
             };
             for (i, col) in columns.iter_mut().enumerate() {
                 col.push(Value::Float64(point.0[i]));
             }
         }
-        for column in columns {
+        for column in &columns {
             Type::Float64
-                .serialize_column(column, writer, state)
+                .serialize_column(column.iter().collect(), writer, state)
                 .await?;
         }
         Ok(())
@@ -48,22 +50,22 @@ macro_rules! array_ser {
         paste::paste! {
             pub struct [<$name Serializer>];
             impl super::array::ArraySerializerGeneric for [<$name Serializer>] {
-                fn inner_type(_type_: &Type) -> &Type {
-                    &Type::$item
+                fn inner_type(_type_: &Type) -> Result<&Type> {
+                    Ok(&Type::$item)
                 }
-                fn value_len(value: &Value) -> usize {
+                fn value_len(value: &Value) -> Result<usize> {
                     match value {
-                        Value::$name(array) => array.0.len(),
-                        _ => unreachable!()
+                        Value::$name(array) => Ok(array.0.len()),
+                        x => Err(unexpected_value(x, &Type::$name)),
                     }
                 }
-                fn values(value: Value) -> Vec<Value> {
+                fn values(value: &Value) -> Result<Cow<'_, [Value]>> {
                     match value {
-                        // The into_iter/collect is annoying, but unavoidable if we want
+                        // The iter/collect is annoying, but unavoidable if we want
                         // to give strong types to the user inside the containers rather than
                         // [Value]s.
-                        Value::$name(array) => array.0.into_iter().map(Value::$item).collect(),
-                        _ => unreachable!()
+                        Value::$name(array) => Ok(Cow::Owned(array.0.iter().cloned().map(Value::$item).collect())),
+                        x => Err(unexpected_value(x, &Type::$name)),
                     }
                 }
             }
@@ -74,3 +76,31 @@ macro_rules! array_ser {
 array_ser!(Ring, Point);
 array_ser!(Polygon, Ring);
 array_ser!(MultiPolygon, Polygon);
+array_ser!(LineString, Point);
+array_ser!(MultiLineString, LineString);
+
+/// `Geometry` columns are sent as the `Variant` they are an alias of, in which each geo value fits a single type.
+pub struct GeometrySerializer;
+
+impl Serializer for GeometrySerializer {
+    async fn write_prefix<W: ClickhouseWrite>(
+        _type_: &Type,
+        writer: &mut W,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        Type::geometry_variant()
+            .serialize_prefix(writer, state)
+            .await
+    }
+
+    async fn write<W: ClickhouseWrite>(
+        _type_: &Type,
+        values: Vec<&Value>,
+        writer: &mut W,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        Type::geometry_variant()
+            .serialize_column(values, writer, state)
+            .await
+    }
+}
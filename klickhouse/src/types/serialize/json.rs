@@ -0,0 +1,33 @@
+use tokio::io::AsyncWriteExt;
+
+use crate::{io::ClickhouseWrite, values::Value, Result};
+
+use super::{unexpected_value, Serializer, SerializerState, Type};
+use crate::types::json::JSON_STRING_SERIALIZATION_VERSION;
+
+pub struct JsonSerializer;
+
+impl Serializer for JsonSerializer {
+    async fn write_prefix<W: ClickhouseWrite>(
+        _type_: &Type,
+        writer: &mut W,
+        _state: &mut SerializerState,
+    ) -> Result<()> {
+        writer
+            .write_u64_le(JSON_STRING_SERIALIZATION_VERSION)
+            .await?;
+        Ok(())
+    }
+
+    async fn write<W: ClickhouseWrite>(
+        type_: &Type,
+        values: Vec<&Value>,
+        writer: &mut W,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        if let Some(value) = values.iter().find(|x| !matches!(x, Value::String(_))) {
+            return Err(unexpected_value(value, type_));
+        }
+        Type::String.serialize_column(values, writer, state).await
+    }
+}
@@ -1,7 +1,7 @@
 use indexmap::IndexSet;
 use tokio::io::AsyncWriteExt;
 
-use crate::{io::ClickhouseWrite, values::Value, Result};
+use crate::{convert::unexpected_type, io::ClickhouseWrite, values::Value, Result};
 
 use super::{Serializer, SerializerState, Type};
 
@@ -9,6 +9,15 @@ use crate::types::low_cardinality::*;
 
 pub struct LowCardinalitySerializer;
 
+/// Nulls in a non-nullable dictionary are folded into the default value rather than getting their own key.
+fn dictionary_key<'a>(value: &'a Value, is_nullable: bool, default_value: &'a Value) -> &'a Value {
+    if !is_nullable && matches!(value, Value::Null) {
+        default_value
+    } else {
+        value
+    }
+}
+
 impl Serializer for LowCardinalitySerializer {
     async fn write_prefix<W: ClickhouseWrite>(
         _type_: &Type,
@@ -21,13 +30,13 @@ impl Serializer for LowCardinalitySerializer {
 
     async fn write<W: ClickhouseWrite>(
         type_: &Type,
-        values: Vec<Value>,
+        values: Vec<&Value>,
         writer: &mut W,
         state: &mut SerializerState,
     ) -> Result<()> {
         let inner_type = match type_ {
             Type::LowCardinality(x) => &**x,
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         };
 
         if values.is_empty() {
@@ -37,13 +46,16 @@ impl Serializer for LowCardinalitySerializer {
         let is_nullable = inner_type.is_nullable();
         let inner_type = inner_type.strip_null();
 
+        // Without a global dictionary, the server always reads key 0 of a nullable dictionary as null,
+        // so it is reserved even if no nulls are present.
+        let default_value = inner_type.default_value();
         let mut keys: IndexSet<&Value> = IndexSet::new();
         let nulled = Value::Null;
         if is_nullable {
             keys.insert(&nulled);
         }
         for value in &values {
-            keys.insert(value);
+            keys.insert(dictionary_key(value, is_nullable, &default_value));
         }
 
         let mut flags = 0u64;
@@ -62,12 +74,23 @@ impl Serializer for LowCardinalitySerializer {
         writer.write_u64_le(keys.len() as u64).await?;
 
         inner_type
-            .serialize_column(keys.iter().copied().cloned().collect(), writer, state)
+            .serialize_column(
+                keys.iter()
+                    .map(|x| match x {
+                        Value::Null => &default_value,
+                        x => *x,
+                    })
+                    .collect(),
+                writer,
+                state,
+            )
             .await?;
 
         writer.write_u64_le(values.len() as u64).await?;
         for value in &values {
-            let index = keys.get_index_of(value).unwrap();
+            let index = keys
+                .get_index_of(dictionary_key(value, is_nullable, &default_value))
+                .unwrap();
             if keys.len() > u32::MAX as usize {
                 writer.write_u64_le(index as u64).await?;
             } else if keys.len() > u16::MAX as usize {
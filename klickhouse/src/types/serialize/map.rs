@@ -1,8 +1,10 @@
 use tokio::io::AsyncWriteExt;
 
-use crate::{io::ClickhouseWrite, values::Value, Result};
+use crate::{
+    convert::unexpected_type, io::ClickhouseWrite, values::Value, KlickhouseError, Result,
+};
 
-use super::{Serializer, SerializerState, Type};
+use super::{unexpected_value, Serializer, SerializerState, Type};
 
 pub struct MapSerializer;
 
@@ -20,20 +22,20 @@ impl Serializer for MapSerializer {
                 ])));
                 nested.serialize_prefix(writer, state).await?;
             }
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         }
         Ok(())
     }
 
     async fn write<W: ClickhouseWrite>(
         type_: &Type,
-        values: Vec<Value>,
+        values: Vec<&Value>,
         writer: &mut W,
         state: &mut SerializerState,
     ) -> Result<()> {
         let (key_type, value_type) = match type_ {
             Type::Map(key, value) => (key, value),
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         };
 
         let mut total_keys = vec![];
@@ -42,9 +44,15 @@ impl Serializer for MapSerializer {
         for value in values {
             let (keys, values) = match value {
                 Value::Map(keys, values) => (keys, values),
-                _ => unimplemented!(),
+                x => return Err(unexpected_value(x, type_)),
             };
-            assert_eq!(keys.len(), values.len());
+            if keys.len() != values.len() {
+                return Err(KlickhouseError::SerializeError(format!(
+                    "map has {} keys but {} values",
+                    keys.len(),
+                    values.len()
+                )));
+            }
             writer
                 .write_u64_le((total_keys.len() + keys.len()) as u64)
                 .await?;
@@ -1,10 +1,20 @@
 pub mod array;
 pub mod geo;
+pub mod json;
 pub mod low_cardinality;
 pub mod map;
 pub mod nullable;
 pub mod sized;
 pub mod string;
 pub mod tuple;
+pub mod variant;
 
 use super::*;
+
+fn unexpected_value(value: &Value, expected: impl std::fmt::Display) -> KlickhouseError {
+    KlickhouseError::SerializeError(format!(
+        "unexpected value for {}: {}",
+        expected,
+        value.guess_type()
+    ))
+}
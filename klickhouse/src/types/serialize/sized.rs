@@ -2,7 +2,7 @@ use tokio::io::AsyncWriteExt;
 
 use crate::{io::ClickhouseWrite, values::Value, Result};
 
-use super::{Serializer, SerializerState, Type};
+use super::{unexpected_value, Serializer, SerializerState, Type};
 
 pub struct SizedSerializer;
 
@@ -14,7 +14,7 @@ fn swap_endian_256(mut input: [u8; 32]) -> [u8; 32] {
 impl Serializer for SizedSerializer {
     async fn write<W: ClickhouseWrite>(
         type_: &Type,
-        values: Vec<Value>,
+        values: Vec<&Value>,
         writer: &mut W,
         _state: &mut SerializerState,
     ) -> Result<()> {
@@ -52,7 +52,7 @@ impl Serializer for SizedSerializer {
                 Value::Ipv6(x) => writer.write_all(&x.octets()[..]).await?,
                 Value::Enum8(x) => writer.write_i8(*x).await?,
                 Value::Enum16(x) => writer.write_i16_le(*x).await?,
-                _ => unimplemented!(),
+                x => return Err(unexpected_value(x, type_)),
             }
         }
         Ok(())
@@ -1,23 +1,34 @@
 use tokio::io::AsyncWriteExt;
 
-use crate::{io::ClickhouseWrite, values::Value, Result};
+use crate::{
+    io::ClickhouseWrite, protocol::MAX_STRING_SIZE, values::Value, KlickhouseError, Result,
+};
 
-use super::{Serializer, SerializerState, Type};
+use super::{unexpected_value, Serializer, SerializerState, Type};
 
 pub struct StringSerializer;
 
 async fn emit_bytes<W: ClickhouseWrite>(type_: &Type, bytes: &[u8], writer: &mut W) -> Result<()> {
     if let Type::FixedString(s) = type_ {
-        if bytes.len() >= *s {
-            writer.write_all(&bytes[..*s]).await?;
-        } else {
-            writer.write_all(bytes).await?;
-            let padding = *s - bytes.len();
-            for _ in 0..padding {
-                writer.write_u8(0).await?;
-            }
+        if bytes.len() > *s {
+            return Err(KlickhouseError::SerializeError(format!(
+                "value of {} bytes does not fit in {}",
+                bytes.len(),
+                type_
+            )));
+        }
+        writer.write_all(bytes).await?;
+        let padding = *s - bytes.len();
+        for _ in 0..padding {
+            writer.write_u8(0).await?;
         }
     } else {
+        if bytes.len() > MAX_STRING_SIZE {
+            return Err(KlickhouseError::SerializeError(format!(
+                "string of {} bytes exceeds the limit of {MAX_STRING_SIZE}",
+                bytes.len()
+            )));
+        }
         writer.write_string(bytes).await?;
     }
     Ok(())
@@ -26,7 +37,7 @@ async fn emit_bytes<W: ClickhouseWrite>(type_: &Type, bytes: &[u8], writer: &mut
 impl Serializer for StringSerializer {
     async fn write<W: ClickhouseWrite>(
         type_: &Type,
-        values: Vec<Value>,
+        values: Vec<&Value>,
         writer: &mut W,
         _state: &mut SerializerState,
     ) -> Result<()> {
@@ -40,14 +51,14 @@ impl Serializer for StringSerializer {
                     let bytes = items
                         .iter()
                         .map(|x| match x {
-                            Value::UInt8(x) => *x,
-                            Value::Int8(x) => *x as u8,
-                            _ => unimplemented!(),
+                            Value::UInt8(x) => Ok(*x),
+                            Value::Int8(x) => Ok(*x as u8),
+                            x => Err(unexpected_value(x, type_)),
                         })
-                        .collect::<Vec<u8>>();
+                        .collect::<Result<Vec<u8>>>()?;
                     emit_bytes(type_, &bytes, writer).await?;
                 }
-                _ => unimplemented!(),
+                x => return Err(unexpected_value(x, type_)),
             }
         }
         Ok(())
@@ -1,4 +1,6 @@
-use crate::{io::ClickhouseWrite, values::Value, Result};
+use crate::{
+    convert::unexpected_type, io::ClickhouseWrite, values::Value, KlickhouseError, Result,
+};
 
 use super::{Serializer, SerializerState, Type};
 
@@ -16,32 +18,40 @@ impl Serializer for TupleSerializer {
                     item.serialize_prefix(writer, state).await?;
                 }
             }
-            _ => unimplemented!(),
+            x => return Err(unexpected_type(x)),
         }
         Ok(())
     }
 
     async fn write<W: ClickhouseWrite>(
         type_: &Type,
-        values: Vec<Value>,
+        values: Vec<&Value>,
         writer: &mut W,
         state: &mut SerializerState,
     ) -> Result<()> {
         let inner_types = if let Type::Tuple(inner_types) = &type_ {
             inner_types
         } else {
-            unimplemented!();
+            return Err(unexpected_type(type_));
         };
 
         let mut columns = vec![Vec::with_capacity(values.len()); inner_types.len()];
 
         for value in values {
-            let tuple = value.unwrap_tuple();
-            for (i, value) in tuple.into_iter().enumerate() {
+            let tuple = value.try_unwrap_tuple_ref()?;
+            if tuple.len() != inner_types.len() {
+                return Err(KlickhouseError::SerializeError(format!(
+                    "expected tuple of length {} for {}, got {}",
+                    inner_types.len(),
+                    type_,
+                    tuple.len()
+                )));
+            }
+            for (i, value) in tuple.iter().enumerate() {
                 columns[i].push(value);
             }
         }
-        for (inner_type, column) in inner_types.iter().zip(columns.into_iter()) {
+        for (inner_type, column) in inner_types.iter().zip(columns) {
             inner_type.serialize_column(column, writer, state).await?;
         }
         Ok(())
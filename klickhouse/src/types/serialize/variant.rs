@@ -0,0 +1,82 @@
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    convert::unexpected_type, io::ClickhouseWrite, values::Value, KlickhouseError, Result,
+};
+
+use super::{unexpected_value, Serializer, SerializerState, Type};
+use crate::types::variant::{BASIC_DISCRIMINATORS_MODE, NULL_DISCRIMINATOR};
+
+pub struct VariantSerializer;
+
+impl Serializer for VariantSerializer {
+    async fn write_prefix<W: ClickhouseWrite>(
+        type_: &Type,
+        writer: &mut W,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        let Type::Variant(types) = type_ else {
+            return Err(unexpected_type(type_));
+        };
+        writer.write_u64_le(BASIC_DISCRIMINATORS_MODE).await?;
+        for type_ in types {
+            type_.serialize_prefix(writer, state).await?;
+        }
+        Ok(())
+    }
+
+    async fn write<W: ClickhouseWrite>(
+        type_: &Type,
+        values: Vec<&Value>,
+        writer: &mut W,
+        state: &mut SerializerState,
+    ) -> Result<()> {
+        let Type::Variant(types) = type_ else {
+            return Err(unexpected_type(type_));
+        };
+        let mut discriminators = Vec::with_capacity(values.len());
+        let mut columns = vec![vec![]; types.len()];
+        for value in values {
+            let (value, discriminator) = match value {
+                Value::Null => {
+                    discriminators.push(NULL_DISCRIMINATOR);
+                    continue;
+                }
+                Value::Variant(value, discriminator) => (&**value, *discriminator as usize),
+                // a plain value goes to the first variant it fits
+                value => match types.iter().position(|x| x.inner_validate_value(value)) {
+                    Some(discriminator) => (value, discriminator),
+                    None => return Err(unexpected_value(value, type_)),
+                },
+            };
+            let Some(column) = columns.get_mut(discriminator) else {
+                return Err(KlickhouseError::SerializeError(format!(
+                    "discriminator {discriminator} out of range for {type_}"
+                )));
+            };
+            column.push(value);
+            discriminators.push(discriminator as u8);
+        }
+        writer.write_all(&discriminators).await?;
+        for (type_, column) in types.iter().zip(columns) {
+            type_.serialize_column(column, writer, state).await?;
+        }
+        Ok(())
+    }
+}
+
+/// `Dynamic` columns are sent as a `Variant` of the types of their values, see [`Type::resolve_dynamic`].
+pub struct DynamicSerializer;
+
+impl Serializer for DynamicSerializer {
+    async fn write<W: ClickhouseWrite>(
+        type_: &Type,
+        _values: Vec<&Value>,
+        _writer: &mut W,
+        _state: &mut SerializerState,
+    ) -> Result<()> {
+        Err(KlickhouseError::SerializeError(format!(
+            "{type_} must be resolved to a Variant before it is serialized"
+        )))
+    }
+}
@@ -18,14 +18,14 @@ async fn roundtrip_values(type_: &Type, values: &[Value]) -> Result<Vec<Value>>
     let mut state = SerializerState {};
     type_.serialize_prefix(&mut output, &mut state).await?;
     type_
-        .serialize_column(values.to_vec(), &mut output, &mut state)
+        .serialize_column(values.iter().collect(), &mut output, &mut state)
         .await?;
     for x in &output {
         print!("{x:02X}");
     }
     println!();
     let mut input = Cursor::new(output);
-    let mut state = DeserializerState {};
+    let mut state = DeserializerState::default();
     type_.deserialize_prefix(&mut input, &mut state).await?;
     let deserialized = type_
         .deserialize_column(&mut input, values.len(), &mut state)
@@ -321,12 +321,32 @@ async fn roundtrip_string() {
             .await
             .unwrap()
     );
-    assert_ne!(
+    assert!(roundtrip_values(&Type::FixedString(3), &values[..])
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn roundtrip_fixed_string_padding() {
+    let values = &[
+        Value::string("abc"),
+        Value::String(vec![b'a', 0, b'b']),
+        Value::String(vec![]),
+    ];
+    assert_eq!(
         &values[..],
-        roundtrip_values(&Type::FixedString(3), &values[..])
+        roundtrip_values(&Type::FixedString(4), &values[..])
             .await
             .unwrap()
     );
+
+    let type_ = Type::FixedString(3);
+    assert!(type_.validate_value(&Value::string("abc")).is_ok());
+    assert!(type_.validate_value(&Value::string("abcd")).is_err());
+    let type_ = Type::Array(Box::new(Type::Nullable(Box::new(Type::FixedString(3)))));
+    let mut value = Value::Array(vec![Value::string("abcd"), Value::Null]);
+    type_.truncate_fixed_strings(&mut value);
+    assert_eq!(value, Value::Array(vec![Value::string("abc"), Value::Null]));
 }
 
 #[tokio::test]
@@ -448,6 +468,60 @@ async fn roundtrip_array2() {
     );
 }
 
+/// Builds a ragged array nested `depth` levels deep, with empty arrays at every level.
+fn ragged_array(depth: usize, counter: &mut usize, leaf: &impl Fn(usize) -> Value) -> Value {
+    *counter += 1;
+    if depth == 0 {
+        return leaf(*counter);
+    }
+    let len = *counter % 3;
+    Value::Array(
+        (0..len)
+            .map(|_| ragged_array(depth - 1, counter, leaf))
+            .collect(),
+    )
+}
+
+#[tokio::test]
+async fn roundtrip_deep_arrays() {
+    let leaves: [(Type, &dyn Fn(usize) -> Value); 3] = [
+        (Type::UInt32, &|i| Value::UInt32(i as u32)),
+        (Type::String, &|i| Value::string(format!("s{i}"))),
+        (
+            Type::LowCardinality(Box::new(Type::Nullable(Box::new(Type::String)))),
+            &|i| {
+                if i % 4 == 0 {
+                    Value::Null
+                } else {
+                    Value::string(format!("s{}", i % 5))
+                }
+            },
+        ),
+    ];
+    for depth in 3..=5 {
+        for (leaf_type, leaf) in &leaves {
+            let mut type_ = leaf_type.clone();
+            for _ in 0..depth {
+                type_ = Type::Array(Box::new(type_));
+            }
+            let mut counter = 0;
+            let values = (0..8)
+                .map(|_| ragged_array(depth, &mut counter, leaf))
+                .collect::<Vec<_>>();
+            type_.validate().unwrap();
+            for value in &values {
+                type_.validate_value(value).unwrap();
+            }
+            assert_eq!(
+                values,
+                roundtrip_values(&type_, &values).await.unwrap(),
+                "{type_}"
+            );
+            assert_eq!(type_.to_string().parse::<Type>().unwrap(), type_);
+        }
+    }
+}
+
 #[tokio::test]
 async fn roundtrip_tuple() {
     let values = &[
@@ -821,4 +895,500 @@ async fn roundtrip_geo() {
             .await
             .unwrap()
     );
+    // LineString
+    let linestring = |x| values::LineString(vec![point(x), point(2.0 * x), point(3.0 * x)]);
+    let values = &[
+        Value::LineString(linestring(1.0)),
+        Value::LineString(linestring(3.0)),
+    ];
+    assert_eq!(
+        &values[..],
+        roundtrip_values(&Type::LineString, &values[..])
+            .await
+            .unwrap()
+    );
+    // MultiLineString
+    let multilinestring = |x| values::MultiLineString(vec![linestring(x), linestring(2.0 * x)]);
+    let values = &[
+        Value::MultiLineString(multilinestring(1.0)),
+        Value::MultiLineString(multilinestring(3.0)),
+    ];
+    assert_eq!(
+        &values[..],
+        roundtrip_values(&Type::MultiLineString, &values[..])
+            .await
+            .unwrap()
+    );
+}
+
+fn low_cardinality_sequences(alphabet: &[Value], max_len: usize) -> Vec<Vec<Value>> {
+    let mut out = vec![vec![]];
+    let mut last = vec![vec![]];
+    for _ in 0..max_len {
+        let mut next = vec![];
+        for prefix in &last {
+            for value in alphabet {
+                let mut item: Vec<Value> = prefix.clone();
+                item.push(value.clone());
+                next.push(item);
+            }
+        }
+        out.extend(next.iter().cloned());
+        last = next;
+    }
+    out
+}
+
+#[tokio::test]
+async fn roundtrip_low_cardinality_nullable_exhaustive() {
+    let alphabet = &[
+        Value::Null,
+        Value::string(""),
+        Value::string("a"),
+        Value::string("b"),
+    ];
+    let type_ = Type::LowCardinality(Box::new(Type::Nullable(Box::new(Type::String))));
+    let array_type = Type::Array(Box::new(type_.clone()));
+    for values in low_cardinality_sequences(alphabet, 5) {
+        if !values.is_empty() {
+            assert_eq!(values, roundtrip_values(&type_, &values[..]).await.unwrap());
+        }
+        let arrays = vec![Value::Array(values.clone()), Value::Array(vec![])];
+        assert_eq!(
+            arrays,
+            roundtrip_values(&array_type, &arrays[..]).await.unwrap()
+        );
+    }
+}
+
+async fn serialize_values(type_: &Type, values: &[Value]) -> Vec<u8> {
+    let mut output = vec![];
+    let mut state = SerializerState {};
+    type_
+        .serialize_prefix(&mut output, &mut state)
+        .await
+        .unwrap();
+    type_
+        .serialize_column(values.iter().collect(), &mut output, &mut state)
+        .await
+        .unwrap();
+    output
+}
+
+#[tokio::test]
+async fn low_cardinality_null_keys_wire_format() {
+    let header = |keys: u8| {
+        let mut out = vec![];
+        // version
+        out.extend(1u64.to_le_bytes());
+        // flags: additional keys, UInt8 indices
+        out.extend((1u64 << 9).to_le_bytes());
+        out.extend((keys as u64).to_le_bytes());
+        out
+    };
+    let nullable = Type::LowCardinality(Box::new(Type::Nullable(Box::new(Type::String))));
+
+    // only nulls: a single default key at index 0
+    let mut expected = header(1);
+    expected.push(0);
+    expected.extend(2u64.to_le_bytes());
+    expected.extend([0, 0]);
+    assert_eq!(
+        expected,
+        serialize_values(&nullable, &[Value::Null, Value::Null]).await
+    );
+
+    // leading null, non-null, null, empty string
+    let mut expected = header(3);
+    expected.extend([0, 1, b'a', 0]);
+    expected.extend(4u64.to_le_bytes());
+    expected.extend([0, 1, 0, 2]);
+    assert_eq!(
+        expected,
+        serialize_values(
+            &nullable,
+            &[
+                Value::Null,
+                Value::string("a"),
+                Value::Null,
+                Value::string("")
+            ]
+        )
+        .await
+    );
+
+    // no nulls: index 0 is still reserved
+    let mut expected = header(2);
+    expected.extend([0, 1, b'a']);
+    expected.extend(1u64.to_le_bytes());
+    expected.extend([1]);
+    assert_eq!(
+        expected,
+        serialize_values(&nullable, &[Value::string("a")]).await
+    );
+
+    // non-nullable: nulls share the default key
+    let non_nullable = Type::LowCardinality(Box::new(Type::String));
+    let mut expected = header(1);
+    expected.push(0);
+    expected.extend(2u64.to_le_bytes());
+    expected.extend([0, 0]);
+    assert_eq!(
+        expected,
+        serialize_values(&non_nullable, &[Value::Null, Value::string("")]).await
+    );
+}
+
+#[tokio::test]
+async fn roundtrip_json() {
+    let values = &[
+        Value::string("{}"),
+        Value::string(r#"{"a":{"b":1},"c":["x",null]}"#),
+    ];
+    assert_eq!(
+        &values[..],
+        roundtrip_values(&Type::Json, &values[..]).await.unwrap()
+    );
+    let serialized = serialize_values(&Type::Json, &[Value::string("{}")]).await;
+    assert_eq!(serialized, [1, 0, 0, 0, 0, 0, 0, 0, 2, b'{', b'}']);
+    assert!(roundtrip_values(&Type::Json, &[Value::Int32(1)])
+        .await
+        .is_err());
+
+    let mut input = Cursor::new(vec![2, 0, 0, 0, 0, 0, 0, 0]);
+    assert!(Type::Json
+        .deserialize_prefix(&mut input, &mut DeserializerState::default())
+        .await
+        .is_err());
+}
+
+#[test]
+fn json_type_names() {
+    assert_eq!("JSON".parse::<Type>().unwrap(), Type::Json);
+    assert_eq!(
+        "JSON(max_dynamic_paths=16, a.b UInt32, SKIP c, SKIP REGEXP '^d')"
+            .parse::<Type>()
+            .unwrap(),
+        Type::Json
+    );
+    assert_eq!(Type::Json.to_string(), "JSON");
+    assert!("Object('json')".parse::<Type>().is_err());
+}
+
+#[tokio::test]
+async fn roundtrip_variant() {
+    let type_ = Type::Variant(vec![Type::Int64, Type::String]);
+    let values = &[
+        Value::Variant(Box::new(Value::string("a")), 1),
+        Value::Null,
+        Value::Variant(Box::new(Value::Int64(3)), 0),
+        Value::Variant(Box::new(Value::string("b")), 1),
+    ];
+    assert_eq!(
+        &values[..],
+        roundtrip_values(&type_, &values[..]).await.unwrap()
+    );
+    let serialized = serialize_values(&type_, &values[..2]).await;
+    assert_eq!(serialized, [0, 0, 0, 0, 0, 0, 0, 0, 1, 255, 1, b'a']);
+
+    // plain values take the first variant that fits
+    assert_eq!(
+        roundtrip_values(&type_, &[Value::string("c"), Value::Int64(1)])
+            .await
+            .unwrap(),
+        [
+            Value::Variant(Box::new(Value::string("c")), 1),
+            Value::Variant(Box::new(Value::Int64(1)), 0)
+        ]
+    );
+    assert!(roundtrip_values(&type_, &[Value::UInt8(1)]).await.is_err());
+    assert!(
+        roundtrip_values(&type_, &[Value::Variant(Box::new(Value::Int64(1)), 1)])
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn deserialize_dynamic() {
+    let mut input = vec![];
+    // version 1, max_types, types
+    input.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 32, 2]);
+    input.extend_from_slice(b"\x06String\x05Int64");
+    // variant of Int64, SharedVariant, String
+    input.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0]);
+    input.extend_from_slice(&[2, 255, 0]);
+    input.extend_from_slice(&7i64.to_le_bytes());
+    input.extend_from_slice(b"\x01x");
+
+    let mut input = Cursor::new(input);
+    let mut state = DeserializerState::default();
+    Type::Dynamic
+        .deserialize_prefix(&mut input, &mut state)
+        .await
+        .unwrap();
+    let values = Type::Dynamic
+        .deserialize_column(&mut input, 3, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(values, [Value::string("x"), Value::Null, Value::Int64(7)]);
+}
+
+#[tokio::test]
+async fn roundtrip_geometry() {
+    let values = &[
+        Value::Point(values::Point([1.0, 2.0])),
+        Value::Null,
+        Value::Ring(values::Ring(vec![values::Point([0.0, 0.0])])),
+        Value::LineString(values::LineString(vec![values::Point([0.0, 0.0])])),
+        Value::MultiPolygon(values::MultiPolygon(vec![])),
+    ];
+    assert_eq!(
+        &values[..],
+        roundtrip_values(&Type::Geometry, &values[..])
+            .await
+            .unwrap()
+    );
+    // discriminators follow the order of the variant types
+    let serialized = serialize_values(&Type::Geometry, &values[..2]).await;
+    assert_eq!(&serialized[8..10], [3, 255]);
+    assert!(roundtrip_values(&Type::Geometry, &[Value::Int32(1)])
+        .await
+        .is_err());
+    assert_eq!("Geometry".parse::<Type>().unwrap(), Type::Geometry);
+    assert!(Type::Nullable(Box::new(Type::Geometry)).validate().is_err());
+}
+
+#[test]
+fn resolve_dynamic() {
+    let type_ = Type::Array(Box::new(Type::Dynamic));
+    let mut values = [
+        Value::Array(vec![Value::string("a"), Value::Int32(1)]),
+        Value::Array(vec![Value::Null, Value::Int32(2)]),
+    ];
+    let resolved = type_.resolve_dynamic(values.iter_mut().collect()).unwrap();
+    assert_eq!(
+        resolved,
+        Type::Array(Box::new(Type::Variant(vec![Type::Int32, Type::String])))
+    );
+    assert_eq!(
+        values[0],
+        Value::Array(vec![
+            Value::Variant(Box::new(Value::string("a")), 1),
+            Value::Variant(Box::new(Value::Int32(1)), 0)
+        ])
+    );
+    assert_eq!(values[1].unwrap_array_ref()[0], Value::Null);
+}
+
+#[test]
+fn variant_type_names() {
+    let type_ = "Variant(String, Array(UInt8), Int64)"
+        .parse::<Type>()
+        .unwrap();
+    assert_eq!(
+        type_,
+        Type::Variant(vec![
+            Type::Array(Box::new(Type::UInt8)),
+            Type::Int64,
+            Type::String
+        ])
+    );
+    assert_eq!(type_.to_string(), "Variant(Array(UInt8),Int64,String)");
+    assert!("Variant(Nullable(String))"
+        .parse::<Type>()
+        .unwrap()
+        .validate()
+        .is_err());
+    assert!("Variant(String, String)"
+        .parse::<Type>()
+        .unwrap()
+        .validate()
+        .is_err());
+    assert!(Type::Variant(vec![Type::String, Type::Int64])
+        .validate()
+        .is_err());
+    assert!("Nullable(Dynamic)"
+        .parse::<Type>()
+        .unwrap()
+        .validate()
+        .is_err());
+    assert_eq!("Dynamic".parse::<Type>().unwrap(), Type::Dynamic);
+    assert_eq!(
+        "Dynamic(max_types=8)".parse::<Type>().unwrap(),
+        Type::Dynamic
+    );
+    assert_eq!(Type::Dynamic.to_string(), "Dynamic");
+}
+
+#[test]
+fn type_reflection() {
+    for type_ in Type::all_scalars() {
+        type_.validate().unwrap();
+        assert_eq!(type_.to_string().parse::<Type>().unwrap(), type_);
+    }
+    assert!(Type::Nullable(Box::new(Type::Decimal64(2))).is_numeric());
+    assert!(Type::LowCardinality(Box::new(Type::Nullable(Box::new(Type::UInt8)))).is_numeric());
+    assert!(!Type::String.is_numeric());
+    assert!(Type::DateTime64(3, chrono_tz::UTC).is_temporal());
+    assert!(!Type::Int64.is_temporal());
+    assert!(Type::LowCardinality(Box::new(Type::String)).is_string_like());
+    assert!(Type::Enum8(vec![]).is_string_like());
+    assert!(!Type::Uuid.is_string_like());
+}
+
+#[tokio::test]
+async fn mismatched_values_error() {
+    assert!(roundtrip_values(&Type::Int32, &[Value::string("1")])
+        .await
+        .is_err());
+    assert!(roundtrip_values(&Type::String, &[Value::Int32(1)])
+        .await
+        .is_err());
+    let array = Type::Array(Box::new(Type::Int32));
+    assert!(roundtrip_values(&array, &[Value::Int32(1)]).await.is_err());
+    let tuple = Type::Tuple(vec![Type::Int32, Type::String]);
+    assert!(
+        roundtrip_values(&tuple, &[Value::Tuple(vec![Value::Int32(1)])])
+            .await
+            .is_err()
+    );
+    let map = Type::Map(Box::new(Type::String), Box::new(Type::Int32));
+    assert!(
+        roundtrip_values(&map, &[Value::Map(vec![Value::string("a")], vec![])])
+            .await
+            .is_err()
+    );
+    assert!(Type::Int32.try_unwrap_array().is_err());
+    assert!(Type::Int32.try_unwrap_tuple().is_err());
+    assert!(Type::Int32.try_unwrap_map().is_err());
+}
+
+#[test]
+fn composite_value_validation() {
+    let error = |type_: &Type, value: Value| type_.validate_value(&value).unwrap_err().to_string();
+
+    let tuple = Type::Tuple(vec![Type::Int32, Type::String]);
+    let e = error(&tuple, Value::Tuple(vec![Value::Int32(1)]));
+    assert!(e.contains("tuple of 1 values"), "{e}");
+    assert!(tuple
+        .validate_value(&Value::Tuple(vec![
+            Value::Int32(1),
+            Value::string("a"),
+            Value::Int32(2)
+        ]))
+        .is_err());
+    let e = error(
+        &Type::Array(Box::new(tuple.clone())),
+        Value::Array(vec![
+            Value::Tuple(vec![Value::Int32(1), Value::string("a")]),
+            Value::Tuple(vec![Value::Int32(1), Value::Int32(2)]),
+        ]),
+    );
+    assert!(
+        e.contains("array item 1: tuple element 1: could not assign value '2' to type 'String'"),
+        "{e}"
+    );
+
+    let map = Type::Map(Box::new(Type::String), Box::new(Type::Int32));
+    let e = error(&map, Value::Map(vec![Value::string("a")], vec![]));
+    assert!(e.contains("map of 1 keys and 0 values"), "{e}");
+    let e = error(&Type::Nullable(Box::new(Type::Int32)), Value::string("a"));
+    assert!(e.contains("to type 'Int32'"), "{e}");
+    let e = error(
+        &map,
+        Value::Map(vec![Value::string("a")], vec![Value::Int64(1)]),
+    );
+    assert!(e.contains("map value 0:"), "{e}");
+    map.validate_value(&Value::Map(vec![Value::string("a")], vec![Value::Int32(1)]))
+        .unwrap();
+}
+
+#[test]
+fn malformed_type_names() {
+    let deep = format!("{}UInt8{}", "Array(".repeat(1000), ")".repeat(1000));
+    assert!(deep.parse::<Type>().is_err());
+    for name in [
+        "Array(UInt8))(",
+        "DateTime(')",
+        "DateTime64(3, ')",
+        "DateTime64(10)",
+        "Decimal(9, 10)",
+        "Decimal32(10)",
+    ] {
+        assert!(name.parse::<Type>().is_err(), "{name}");
+    }
+    assert_eq!("Decimal(9, 9)".parse::<Type>().unwrap(), Type::Decimal32(9));
+}
+
+#[test]
+fn type_parse_error_position() {
+    let error = |name: &str| match name.parse::<Type>() {
+        Err(crate::KlickhouseError::TypeParseError(e)) => e,
+        other => panic!("{name}: {other:?}"),
+    };
+
+    let e = error("Map(String, Array(Foo(1)))");
+    assert_eq!(e.input, "Map(String, Array(Foo(1)))");
+    assert_eq!(e.position, 18);
+    assert_eq!(e.remainder(), "Foo(1)))");
+    assert!(e.to_string().contains("at position 18: 'Foo(1)))'"));
+
+    let e = error("Tuple(UInt8, DateTime64(3, 'Mars/Olympus'))");
+    assert_eq!(e.remainder(), "'Mars/Olympus'))");
+    let e = error("Enum8('a' = 1, 'b' = 300)");
+    assert_eq!(e.remainder(), "'b' = 300)");
+    let e = error("Nullable(Decimal(9, x))");
+    assert_eq!(e.remainder(), "x))");
+    let e = error("Array(UInt8))(");
+    assert_eq!(e.position, 5);
+    assert_eq!(error("Foo").position, 0);
+
+    let e = Type::Nullable(Box::new(Type::Array(Box::new(Type::String))))
+        .validate()
+        .unwrap_err();
+    assert!(
+        matches!(e, crate::KlickhouseError::TypeParseError(e) if e.input == "Nullable(Array(String))")
+    );
+}
+
+#[test]
+fn enum_type_names() {
+    let type_: Type = "Enum8('a' = 1, 'b(, )' = -2, 'it\\'s \\\\' = 3, 'é' = 4)"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        type_,
+        Type::Enum8(vec![
+            ("a".to_string(), 1),
+            ("b(, )".to_string(), -2),
+            ("it's \\".to_string(), 3),
+            ("é".to_string(), 4),
+        ])
+    );
+    assert_eq!(type_.to_string().parse::<Type>().unwrap(), type_);
+
+    let type_: Type = "Nullable(Enum16('x\\x41' = 1000, 'y'))".parse().unwrap();
+    assert_eq!(
+        type_,
+        Type::Nullable(Box::new(Type::Enum16(vec![
+            ("xA".to_string(), 1000),
+            ("y".to_string(), 1001)
+        ])))
+    );
+    let type_: Type = "Tuple(Enum8('(' = 1), String)".parse().unwrap();
+    assert_eq!(
+        type_,
+        Type::Tuple(vec![Type::Enum8(vec![("(".to_string(), 1)]), Type::String])
+    );
+
+    for name in [
+        "Enum8('a' = 128)",
+        "Enum8('a = 1)",
+        "Enum8(a = 1)",
+        "Enum8('a' 1)",
+        "Enum16('a' = x)",
+    ] {
+        assert!(name.parse::<Type>().is_err(), "{name}");
+    }
 }
@@ -0,0 +1,11 @@
+/// Discriminator of `NULL` rows of a `Variant` column.
+pub const NULL_DISCRIMINATOR: u8 = 255;
+/// `DiscriminatorsSerializationMode::BASIC` of a `Variant`: one discriminator per row, followed by the values of each variant.
+pub const BASIC_DISCRIMINATORS_MODE: u64 = 0;
+
+/// `DynamicSerializationVersion::V1`, sent to clients of our protocol revision: the structure includes `max_types`.
+pub const DYNAMIC_SERIALIZATION_V1: u64 = 1;
+/// `DynamicSerializationVersion::V2`: the same as V1 without `max_types`.
+pub const DYNAMIC_SERIALIZATION_V2: u64 = 2;
+/// Variant of a `Dynamic` column holding values of types beyond its `max_types`, in a binary encoding along with their type.
+pub const SHARED_VARIANT: &str = "SharedVariant";
@@ -0,0 +1,147 @@
+use crate::{Client, KlickhouseError, QualifiedTable, QueryBuilder, RawRow, Result, Row, Value};
+
+/// How [`Client::upsert_replacing_with`] merges upserted rows with the versions they replace.
+#[derive(Debug, Clone, Default)]
+pub enum UpsertMerge {
+    /// Leave it to background merges. Until then, reads see both versions of a row unless they use `FINAL`.
+    #[default]
+    Background,
+    /// Run `OPTIMIZE TABLE ... FINAL`, rewriting every partition of the table.
+    Table,
+    /// Run `OPTIMIZE TABLE ... PARTITION <partition> FINAL`, only rewriting the partition holding the rows, i.e. `Value::Date(...)` for a table partitioned by day.
+    /// A partition key with multiple columns is given as a `Value::Tuple`.
+    Partition(Value),
+}
+
+impl UpsertMerge {
+    fn optimize_query(&self, table: &QualifiedTable) -> Option<String> {
+        match self {
+            UpsertMerge::Background => None,
+            UpsertMerge::Table => Some(format!("OPTIMIZE TABLE {table} FINAL")),
+            UpsertMerge::Partition(partition) => Some(format!(
+                "OPTIMIZE TABLE {table} PARTITION {partition} FINAL"
+            )),
+        }
+    }
+}
+
+/// Columns of a table that [`Client::upsert_replacing_with`] requires rows to have.
+struct ReplacingColumns {
+    sorting_key: Vec<String>,
+    has_version: bool,
+}
+
+impl Client {
+    async fn replacing_columns(
+        &self,
+        table: &QualifiedTable,
+        version_column: &str,
+    ) -> Result<ReplacingColumns> {
+        let engine = self.table_engine(table.clone()).await?;
+        if !engine.ends_with("ReplacingMergeTree") {
+            return Err(KlickhouseError::QueryArgumentError(format!(
+                "cannot upsert into {table}: engine {engine} is not a ReplacingMergeTree"
+            )));
+        }
+
+        let query = match &table.database {
+            Some(database) => QueryBuilder::new(
+                "SELECT name, is_in_sorting_key FROM system.columns WHERE database = $1 AND table = $2 ORDER BY position",
+            )
+            .arg(database.as_str()),
+            None => QueryBuilder::new(
+                "SELECT name, is_in_sorting_key FROM system.columns WHERE database = currentDatabase() AND table = $1 ORDER BY position",
+            ),
+        };
+        let columns = self
+            .query_collect::<RawRow>(query.arg(table.table.as_str()))
+            .await?;
+        let mut out = ReplacingColumns {
+            sorting_key: vec![],
+            has_version: false,
+        };
+        for mut column in columns {
+            let name: String = column.try_get(0)?;
+            let in_sorting_key: u8 = column.try_get(1)?;
+            out.has_version |= name == version_column;
+            if in_sorting_key != 0 {
+                out.sorting_key.push(name);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Same as `upsert_replacing_with`, leaving merges to the server.
+    pub async fn upsert_replacing<T: Row + Send + 'static>(
+        &self,
+        table: impl Into<QualifiedTable>,
+        rows: Vec<T>,
+        version_column: &str,
+    ) -> Result<()> {
+        self.upsert_replacing_with(table, rows, version_column, UpsertMerge::Background)
+            .await
+    }
+
+    /// Inserts `rows` into a `ReplacingMergeTree` table, where they replace the rows with the same sorting key once parts are merged,
+    /// and then merges them according to `merge`. Reads can also see only the latest versions before a merge by using `FINAL`.
+    ///
+    /// Fails without inserting if the table isn't a `ReplacingMergeTree`, has no column `version_column`,
+    /// or if `T` has a fixed set of columns which lacks `version_column` or any column of the table's sorting key.
+    /// A row missing one of those would be inserted with the column's default and replace the wrong row, or lose against older versions.
+    pub async fn upsert_replacing_with<T: Row + Send + 'static>(
+        &self,
+        table: impl Into<QualifiedTable>,
+        rows: Vec<T>,
+        version_column: &str,
+        merge: UpsertMerge,
+    ) -> Result<()> {
+        let table = table.into();
+        let columns = self.replacing_columns(&table, version_column).await?;
+        if !columns.has_version {
+            return Err(KlickhouseError::QueryArgumentError(format!(
+                "cannot upsert into {table}: no version column {version_column}"
+            )));
+        }
+        if let Some(names) = T::column_names() {
+            let missing = columns
+                .sorting_key
+                .iter()
+                .map(|x| &**x)
+                .chain([version_column])
+                .find(|x| !names.iter().any(|name| name == x));
+            if let Some(missing) = missing {
+                return Err(KlickhouseError::QueryArgumentError(format!(
+                    "cannot upsert into {table}: rows have no column {missing}"
+                )));
+            }
+        }
+
+        self.insert_native_block(format!("INSERT INTO {table} FORMAT native"), rows)
+            .await?;
+        if let Some(optimize) = merge.optimize_query(&table) {
+            self.execute(optimize).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optimize_query() {
+        let table = QualifiedTable::with_database("db", "events");
+        assert_eq!(UpsertMerge::Background.optimize_query(&table), None);
+        assert_eq!(
+            UpsertMerge::Table.optimize_query(&table).unwrap(),
+            "OPTIMIZE TABLE `db`.`events` FINAL"
+        );
+        assert_eq!(
+            UpsertMerge::Partition(Value::Tuple(vec![Value::UInt16(2024), Value::string("eu")]))
+                .optimize_query(&table)
+                .unwrap(),
+            "OPTIMIZE TABLE `db`.`events` PARTITION (2024,'eu') FINAL"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::{Deref, DerefMut};
 
-use crate::{unexpected_type, FromSql, Result, ToSql, Type, Value};
+use crate::{unexpected_type, unexpected_value, FromSql, Result, ToSql, Type, Value};
 
 /// Wrapper over Vec<u8> to allow more efficient serialization/deserialization of raw bytes
 /// The corresponding Clickhouse type here is String or FixedString, not Array(UInt8).
@@ -31,7 +31,7 @@ impl FromSql for Bytes {
         match type_ {
             Type::String | Type::FixedString(_) => match value {
                 Value::String(s) => Ok(Self(s)),
-                _ => unreachable!(),
+                x => Err(unexpected_value(&x, type_)),
             },
             Type::Array(x) if **x == Type::UInt8 || **x == Type::Int8 => match value {
                 Value::Array(values) => Ok(Self(
@@ -46,7 +46,7 @@ impl FromSql for Bytes {
                         })
                         .collect::<Result<Vec<u8>>>()?,
                 )),
-                _ => unreachable!(),
+                x => Err(unexpected_value(&x, type_)),
             },
             _ => Err(unexpected_type(type_)),
         }
@@ -78,3 +78,146 @@ impl From<Vec<u8>> for Bytes {
         Self(value)
     }
 }
+
+/// Raw contents of a `FixedString(N)` column, including the trailing zero padding that is trimmed when reading into [`String`] or [`Bytes`].
+/// Always `N` bytes long when read.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FixedStringBytes(pub Vec<u8>);
+
+impl ToSql for FixedStringBytes {
+    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
+        Ok(Value::String(self.0))
+    }
+}
+
+impl FromSql for FixedStringBytes {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        let Type::FixedString(length) = type_ else {
+            return Err(unexpected_type(type_));
+        };
+        match value {
+            Value::String(mut bytes) => {
+                bytes.resize(bytes.len().max(*length), 0);
+                Ok(Self(bytes))
+            }
+            x => Err(unexpected_value(&x, type_)),
+        }
+    }
+}
+
+impl Deref for FixedStringBytes {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for FixedStringBytes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<FixedStringBytes> for Vec<u8> {
+    fn from(value: FixedStringBytes) -> Self {
+        value.0
+    }
+}
+
+impl From<Vec<u8>> for FixedStringBytes {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+/// Contents of a `String` or `FixedString` column that may or may not be valid UTF-8.
+/// Valid text is read as [`MaybeUtf8::Text`], anything else is passed through unchanged as [`MaybeUtf8::Bytes`].
+///
+/// Reading into [`String`] fails on invalid UTF-8, and [`helpers::utf8_lossy`](crate::helpers::utf8_lossy) replaces invalid sequences.
+/// Use [`Bytes`] or `Vec<u8>` when the contents are never needed as text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MaybeUtf8 {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl MaybeUtf8 {
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            MaybeUtf8::Text(x) => x.as_bytes(),
+            MaybeUtf8::Bytes(x) => x,
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            MaybeUtf8::Text(x) => x.into_bytes(),
+            MaybeUtf8::Bytes(x) => x,
+        }
+    }
+
+    /// The text, if it was valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            MaybeUtf8::Text(x) => Some(x),
+            MaybeUtf8::Bytes(_) => None,
+        }
+    }
+
+    /// The text, with invalid UTF-8 sequences replaced by `U+FFFD`.
+    pub fn to_string_lossy(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            MaybeUtf8::Text(x) => std::borrow::Cow::Borrowed(x),
+            MaybeUtf8::Bytes(x) => String::from_utf8_lossy(x),
+        }
+    }
+}
+
+impl Default for MaybeUtf8 {
+    fn default() -> Self {
+        MaybeUtf8::Text(String::new())
+    }
+}
+
+impl ToSql for MaybeUtf8 {
+    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
+        Ok(Value::String(self.into_bytes()))
+    }
+}
+
+impl FromSql for MaybeUtf8 {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        if !matches!(type_, Type::String | Type::FixedString(_)) {
+            return Err(unexpected_type(type_));
+        }
+        match value {
+            Value::String(x) => Ok(match String::from_utf8(x) {
+                Ok(x) => MaybeUtf8::Text(x),
+                Err(e) => MaybeUtf8::Bytes(e.into_bytes()),
+            }),
+            x => Err(unexpected_value(&x, type_)),
+        }
+    }
+}
+
+impl From<String> for MaybeUtf8 {
+    fn from(value: String) -> Self {
+        MaybeUtf8::Text(value)
+    }
+}
+
+impl From<Vec<u8>> for MaybeUtf8 {
+    fn from(value: Vec<u8>) -> Self {
+        match String::from_utf8(value) {
+            Ok(x) => MaybeUtf8::Text(x),
+            Err(e) => MaybeUtf8::Bytes(e.into_bytes()),
+        }
+    }
+}
+
+impl From<MaybeUtf8> for Vec<u8> {
+    fn from(value: MaybeUtf8) -> Self {
+        value.into_bytes()
+    }
+}
@@ -1,25 +1,42 @@
 use crate::{
-    convert::{unexpected_type, FromSql},
+    convert::{unexpected_type, unexpected_value, FromSql},
     types::Type,
-    Result, Uuid,
+    KlickhouseError, Result, Uuid,
 };
 
 use crate::{convert::ToSql, Value};
 
 impl ToSql for Uuid {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::Uuid(self))
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        // legacy tables often store UUIDs as text, or as their 16 bytes
+        match type_hint.map(|x| x.strip_null().strip_low_cardinality()) {
+            Some(Type::String) | Some(Type::FixedString(36)) => {
+                Ok(Value::String(self.to_string().into_bytes()))
+            }
+            Some(Type::FixedString(16)) => Ok(Value::String(self.as_bytes().to_vec())),
+            _ => Ok(Value::Uuid(self)),
+        }
     }
 }
 
 impl FromSql for Uuid {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Uuid) {
+        if !matches!(type_, Type::Uuid | Type::String | Type::FixedString(_)) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Uuid(x) => Ok(x),
-            _ => unimplemented!(),
+            Value::String(x) if matches!(type_, Type::FixedString(16)) => Uuid::from_slice(&x)
+                .map_err(|e| KlickhouseError::DeserializeError(format!("invalid UUID bytes: {e}"))),
+            Value::String(x) => {
+                let text = String::from_utf8(x)?;
+                Uuid::parse_str(text.trim_end_matches('\0')).map_err(|e| {
+                    KlickhouseError::DeserializeError(format!(
+                        "failed to parse UUID from string '{text}': {e}"
+                    ))
+                })
+            }
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
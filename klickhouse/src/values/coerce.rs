@@ -0,0 +1,531 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use super::*;
+use crate::KlickhouseError;
+
+/// A numeric value lifted out of any numeric [`Value`] or parsed from a string.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Number {
+    Int(i128),
+    UInt(u128),
+    Float(f64),
+    /// Raw value and scale
+    Decimal(i128, usize),
+}
+
+impl Number {
+    pub(super) fn from_value(value: &Value) -> Option<Self> {
+        Some(match value {
+            Value::Int8(x) => Number::Int(*x as i128),
+            Value::Int16(x) => Number::Int(*x as i128),
+            Value::Int32(x) => Number::Int(*x as i128),
+            Value::Int64(x) => Number::Int(*x as i128),
+            Value::Int128(x) => Number::Int(*x),
+            Value::Int256(x) => Number::Int(i256_to_i128(*x)?),
+            Value::UInt8(x) => Number::Int(*x as i128),
+            Value::UInt16(x) => Number::Int(*x as i128),
+            Value::UInt32(x) => Number::Int(*x as i128),
+            Value::UInt64(x) => Number::Int(*x as i128),
+            Value::UInt128(x) => Number::UInt(*x),
+            Value::UInt256(x) => match <(u128, u128)>::from(*x) {
+                (0, x) => Number::UInt(x),
+                _ => return None,
+            },
+            Value::Float32(x) => Number::Float(*x as f64),
+            Value::Float64(x) => Number::Float(*x),
+            Value::Decimal32(scale, x) => Number::Decimal(*x as i128, *scale),
+            Value::Decimal64(scale, x) => Number::Decimal(*x as i128, *scale),
+            Value::Decimal128(scale, x) => Number::Decimal(*x, *scale),
+            Value::Decimal256(scale, x) => Number::Decimal(i256_to_i128(*x)?, *scale),
+            Value::String(x) => return Self::parse(std::str::from_utf8(x).ok()?),
+            _ => return None,
+        })
+    }
+
+    fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        match input {
+            "true" => return Some(Number::Int(1)),
+            "false" => return Some(Number::Int(0)),
+            _ => (),
+        }
+        if let Ok(x) = input.parse::<i128>() {
+            return Some(Number::Int(x));
+        }
+        if let Ok(x) = input.parse::<u128>() {
+            return Some(Number::UInt(x));
+        }
+        if let Some((whole, fraction)) = input.split_once('.') {
+            let digits = whole.trim_start_matches(['-', '+']);
+            if !digits.is_empty()
+                && !fraction.is_empty()
+                && digits.bytes().all(|x| x.is_ascii_digit())
+                && fraction.bytes().all(|x| x.is_ascii_digit())
+            {
+                if let Ok(raw) = format!("{whole}{fraction}").parse::<i128>() {
+                    return Some(Number::Decimal(raw, fraction.len()));
+                }
+            }
+        }
+        input.parse::<f64>().ok().map(Number::Float)
+    }
+
+    /// The exact integer value, if there is no fractional part.
+    pub(super) fn integer(self) -> Option<i128> {
+        match self {
+            Number::Int(x) => Some(x),
+            Number::UInt(x) => x.try_into().ok(),
+            Number::Float(x) if x.is_finite() && x.fract() == 0.0 => {
+                if x >= i128::MIN as f64 && x < i128::MAX as f64 {
+                    Some(x as i128)
+                } else {
+                    None
+                }
+            }
+            Number::Float(_) => None,
+            Number::Decimal(raw, scale) => {
+                let factor = 10i128.checked_pow(scale as u32)?;
+                (raw % factor == 0).then_some(raw / factor)
+            }
+        }
+    }
+
+    fn unsigned(self) -> Option<u128> {
+        match self {
+            Number::UInt(x) => Some(x),
+            x => x.integer()?.try_into().ok(),
+        }
+    }
+
+    pub(super) fn float(self) -> f64 {
+        match self {
+            Number::Int(x) => x as f64,
+            Number::UInt(x) => x as f64,
+            Number::Float(x) => x,
+            Number::Decimal(raw, scale) => raw as f64 / 10f64.powi(scale as i32),
+        }
+    }
+
+    /// The exact raw value at the given decimal scale.
+    pub(super) fn decimal(self, scale: usize) -> Option<i128> {
+        let factor = |scale: usize| 10i128.checked_pow(scale as u32);
+        match self {
+            Number::Float(x) => {
+                let x = (x * 10f64.powi(scale as i32)).round();
+                (x.is_finite() && x >= i128::MIN as f64 && x < i128::MAX as f64)
+                    .then_some(x as i128)
+            }
+            Number::Decimal(raw, from) if from > scale => {
+                let factor = factor(from - scale)?;
+                (raw % factor == 0).then_some(raw / factor)
+            }
+            Number::Decimal(raw, from) => raw.checked_mul(factor(scale - from)?),
+            x => x.integer()?.checked_mul(factor(scale)?),
+        }
+    }
+}
+
+pub(crate) fn i256_to_i128(value: i256) -> Option<i128> {
+    match <(u128, u128)>::from(value) {
+        (0, x) if x <= i128::MAX as u128 => Some(x as i128),
+        (u128::MAX, x) if x > i128::MAX as u128 => Some(x as i128),
+        _ => None,
+    }
+}
+
+fn i128_to_i256(value: i128) -> i256 {
+    (if value < 0 { u128::MAX } else { 0 }, value as u128).into()
+}
+
+fn parse_date_time(input: &str, tz: Tz) -> Option<chrono::DateTime<Tz>> {
+    let input = input.trim();
+    if let Ok(x) = chrono::DateTime::parse_from_rfc3339(input) {
+        return Some(x.with_timezone(&tz));
+    }
+    let naive = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+    DstPolicy::default().resolve(tz, naive).ok()
+}
+
+/// Any value with a point in time, as a timestamp in `tz`.
+pub(super) fn date_time(value: &Value, tz: Tz) -> Option<chrono::DateTime<Tz>> {
+    match value {
+        Value::Date(x) => DstPolicy::default()
+            .resolve(tz, NaiveDate::from(*x).and_hms_opt(0, 0, 0)?)
+            .ok(),
+        Value::DateTime(x) => Some(
+            chrono::DateTime::<Tz>::try_from(*x)
+                .ok()?
+                .with_timezone(&tz),
+        ),
+        Value::DateTime64(x) => Some(
+            chrono::DateTime::<Utc>::try_from(*x)
+                .ok()?
+                .with_timezone(&tz),
+        ),
+        Value::String(x) => parse_date_time(std::str::from_utf8(x).ok()?, tz),
+        value => {
+            let number = Number::from_value(value)?;
+            let seconds = number.float().floor();
+            let nanos = ((number.float() - seconds) * 1e9).round() as u32;
+            tz.timestamp_opt(seconds as i64, nanos).single()
+        }
+    }
+}
+
+/// Plain text form of a scalar value, without SQL quoting.
+pub(super) fn text(value: &Value, type_: Option<&Type>) -> Option<String> {
+    let type_ = type_.map(|x| x.strip_null().strip_low_cardinality());
+    Some(match value {
+        Value::Int8(x) => x.to_string(),
+        Value::Int16(x) => x.to_string(),
+        Value::Int32(x) => x.to_string(),
+        Value::Int64(x) => x.to_string(),
+        Value::Int128(x) => x.to_string(),
+        Value::Int256(x) => {
+            let (negative, magnitude) = x.sign_magnitude_digits();
+            if negative {
+                format!("-{magnitude}")
+            } else {
+                magnitude
+            }
+        }
+        Value::UInt8(x) => x.to_string(),
+        Value::UInt16(x) => x.to_string(),
+        Value::UInt32(x) => x.to_string(),
+        Value::UInt64(x) => x.to_string(),
+        Value::UInt128(x) => x.to_string(),
+        Value::UInt256(x) => x.decimal_digits(),
+        Value::Float32(x) => x.to_string(),
+        Value::Float64(x) => x.to_string(),
+        Value::Decimal32(scale, x) => {
+            let mut out = String::new();
+            write_fixed_point(&mut out, *x < 0, &x.unsigned_abs().to_string(), *scale).ok()?;
+            out
+        }
+        Value::Decimal64(scale, x) => {
+            let mut out = String::new();
+            write_fixed_point(&mut out, *x < 0, &x.unsigned_abs().to_string(), *scale).ok()?;
+            out
+        }
+        Value::Decimal128(scale, x) => {
+            let mut out = String::new();
+            write_fixed_point(&mut out, *x < 0, &x.unsigned_abs().to_string(), *scale).ok()?;
+            out
+        }
+        Value::Decimal256(scale, x) => {
+            let (negative, magnitude) = x.sign_magnitude_digits();
+            let mut out = String::new();
+            write_fixed_point(&mut out, negative, &magnitude, *scale).ok()?;
+            out
+        }
+        Value::String(x) => String::from_utf8(x.clone()).ok()?,
+        Value::Uuid(x) => x.to_string(),
+        Value::Date(x) => NaiveDate::from(*x).to_string(),
+        Value::DateTime(x) => chrono::DateTime::<Tz>::try_from(*x)
+            .ok()?
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        Value::DateTime64(x) => {
+            let date = chrono::DateTime::<Utc>::try_from(*x)
+                .ok()?
+                .with_timezone(&x.0);
+            let mut out = date.format("%Y-%m-%d %H:%M:%S").to_string();
+            if x.2 > 0 {
+                let units = x.1 % 10u64.pow(x.2 as u32);
+                out.push_str(&format!(".{units:0width$}", width = x.2));
+            }
+            out
+        }
+        Value::Enum8(x) => match type_ {
+            Some(Type::Enum8(variants)) => variants.iter().find(|(_, i)| i == x)?.0.clone(),
+            _ => return None,
+        },
+        Value::Enum16(x) => match type_ {
+            Some(Type::Enum16(variants)) => variants.iter().find(|(_, i)| i == x)?.0.clone(),
+            _ => return None,
+        },
+        Value::Ipv4(x) => x.to_string(),
+        Value::Ipv6(x) => x.to_string(),
+        _ => return None,
+    })
+}
+
+fn cannot_coerce(value: &Value, type_: &Type) -> KlickhouseError {
+    KlickhouseError::SerializeError(format!("cannot coerce {value:?} to {type_}"))
+}
+
+impl Value {
+    /// Best-effort conversion of this value into a value of `type_`, i.e. for binding user input in dynamic filters.
+    ///
+    /// Numbers convert between all numeric types as long as no precision or range is lost, and parse from strings.
+    /// Dates and timestamps convert between each other, from unix timestamps, and parse from `YYYY-MM-DD`, `YYYY-MM-DD hh:mm:ss[.fff]` or RFC 3339 strings.
+    /// Enums accept a variant name or index. UUIDs and IP addresses parse from strings. Any scalar converts to a string.
+    /// Arrays, tuples and maps are converted element-wise. `Null` is only accepted by `Nullable` types.
+    pub fn coerce_to(&self, type_: &Type) -> Result<Value> {
+        self.coerce_to_inner(type_, None)
+    }
+
+    fn coerce_to_inner(&self, type_: &Type, from: Option<&Type>) -> Result<Value> {
+        let error = || cannot_coerce(self, type_);
+        let number = || Number::from_value(self).ok_or_else(error);
+        let integer = || number()?.integer().ok_or_else(error);
+        macro_rules! ranged {
+            ($x:expr) => {
+                $x.try_into().map_err(|_| error())?
+            };
+        }
+        Ok(match type_ {
+            Type::Nullable(inner) => match self {
+                Value::Null => Value::Null,
+                _ => self.coerce_to_inner(inner, from)?,
+            },
+            Type::Variant(types) => {
+                let value = match self {
+                    Value::Null => return Ok(Value::Null),
+                    Value::Variant(value, _) => &**value,
+                    value => value,
+                };
+                // a value that fits a variant as it is keeps its type, otherwise it takes the first variant it converts to
+                let (value, discriminator) =
+                    match types.iter().position(|x| x.validate_value(value).is_ok()) {
+                        Some(discriminator) => (value.clone(), discriminator),
+                        None => types
+                            .iter()
+                            .enumerate()
+                            .find_map(|(i, x)| Some((value.coerce_to_inner(x, from).ok()?, i)))
+                            .ok_or_else(error)?,
+                    };
+                Value::Variant(Box::new(value), discriminator as u8)
+            }
+            Type::Dynamic => match self {
+                Value::Variant(value, _) => (**value).clone(),
+                value => value.clone(),
+            },
+            Type::Geometry => match self {
+                Value::Null => Value::Null,
+                Value::Variant(value, _) => value.coerce_to_inner(type_, from)?,
+                value => {
+                    type_.validate_value(value).map_err(|_| error())?;
+                    value.clone()
+                }
+            },
+            _ if matches!(self, Value::Null) => return Err(error()),
+            Type::LowCardinality(inner) => self.coerce_to_inner(inner, from)?,
+
+            Type::Int8 => Value::Int8(ranged!(integer()?)),
+            Type::Int16 => Value::Int16(ranged!(integer()?)),
+            Type::Int32 => Value::Int32(ranged!(integer()?)),
+            Type::Int64 => Value::Int64(ranged!(integer()?)),
+            Type::Int128 => Value::Int128(integer()?),
+            Type::Int256 => Value::Int256(i128_to_i256(integer()?)),
+            Type::UInt8 => Value::UInt8(ranged!(integer()?)),
+            Type::UInt16 => Value::UInt16(ranged!(integer()?)),
+            Type::UInt32 => Value::UInt32(ranged!(integer()?)),
+            Type::UInt64 => Value::UInt64(ranged!(integer()?)),
+            Type::UInt128 => Value::UInt128(number()?.unsigned().ok_or_else(error)?),
+            Type::UInt256 => Value::UInt256((0, number()?.unsigned().ok_or_else(error)?).into()),
+            Type::Float32 => Value::Float32(number()?.float() as f32),
+            Type::Float64 => Value::Float64(number()?.float()),
+            Type::Decimal32(scale) => Value::Decimal32(
+                *scale,
+                ranged!(number()?.decimal(*scale).ok_or_else(error)?),
+            ),
+            Type::Decimal64(scale) => Value::Decimal64(
+                *scale,
+                ranged!(number()?.decimal(*scale).ok_or_else(error)?),
+            ),
+            Type::Decimal128(scale) => {
+                Value::Decimal128(*scale, number()?.decimal(*scale).ok_or_else(error)?)
+            }
+            Type::Decimal256(scale) => Value::Decimal256(
+                *scale,
+                i128_to_i256(number()?.decimal(*scale).ok_or_else(error)?),
+            ),
+
+            Type::String => match self {
+                Value::String(x) => Value::String(x.clone()),
+                _ => Value::String(text(self, from).ok_or_else(error)?.into_bytes()),
+            },
+            Type::FixedString(length) => {
+                let bytes = match self {
+                    Value::String(x) => x.clone(),
+                    _ => text(self, from).ok_or_else(error)?.into_bytes(),
+                };
+                if bytes.len() > *length {
+                    return Err(error());
+                }
+                Value::String(bytes)
+            }
+            // only JSON text, which the server parses on insert
+            Type::Json => match self {
+                Value::String(x) => Value::String(x.clone()),
+                _ => return Err(error()),
+            },
+            Type::Uuid => match self {
+                Value::Uuid(x) => Value::Uuid(*x),
+                Value::String(_) => Value::Uuid(
+                    text(self, from)
+                        .ok_or_else(error)?
+                        .trim()
+                        .parse()
+                        .map_err(|_| error())?,
+                ),
+                _ => return Err(error()),
+            },
+            Type::Ipv4 => match self {
+                Value::Ipv4(x) => Value::Ipv4(*x),
+                Value::String(_) => Value::Ipv4(Ipv4(
+                    text(self, from)
+                        .ok_or_else(error)?
+                        .trim()
+                        .parse()
+                        .map_err(|_| error())?,
+                )),
+                _ => {
+                    let address: u32 = ranged!(integer()?);
+                    Value::Ipv4(Ipv4(Ipv4Addr::from(address)))
+                }
+            },
+            Type::Ipv6 => match self {
+                Value::Ipv6(x) => Value::Ipv6(*x),
+                Value::Ipv4(x) => Value::Ipv6(Ipv6(x.to_ipv6_mapped())),
+                Value::String(_) => Value::Ipv6(Ipv6(
+                    text(self, from)
+                        .ok_or_else(error)?
+                        .trim()
+                        .parse()
+                        .map_err(|_| error())?,
+                )),
+                _ => Value::Ipv6(Ipv6(Ipv6Addr::from(
+                    number()?.unsigned().ok_or_else(error)?,
+                ))),
+            },
+            Type::Date => match self {
+                Value::Date(x) => Value::Date(*x),
+                Value::DateTime(_) | Value::DateTime64(_) | Value::String(_) => {
+                    let tz = match self {
+                        Value::DateTime(x) => x.0,
+                        Value::DateTime64(x) => x.0,
+                        _ => chrono_tz::UTC,
+                    };
+                    let days = date_time(self, tz)
+                        .ok_or_else(error)?
+                        .date_naive()
+                        .signed_duration_since(NaiveDate::default())
+                        .num_days();
+                    Value::Date(Date(ranged!(days as i128)))
+                }
+                _ => Value::Date(Date(ranged!(integer()?))),
+            },
+            Type::DateTime(tz) => {
+                let date = date_time(self, *tz).ok_or_else(error)?;
+                Value::DateTime(DateTime(*tz, ranged!(date.timestamp() as i128)))
+            }
+            Type::DateTime64(precision, tz) => {
+                let date = date_time(self, *tz).ok_or_else(error)?;
+                Value::DateTime64(
+                    DynDateTime64::try_from_tz(date, *precision).map_err(|_| error())?,
+                )
+            }
+            Type::Enum8(variants) => {
+                let index = match self {
+                    Value::String(x) => {
+                        let name = std::str::from_utf8(x).map_err(|_| error())?;
+                        variants
+                            .iter()
+                            .find(|(x, _)| x == name)
+                            .map(|(_, i)| *i)
+                            .ok_or_else(error)?
+                    }
+                    Value::Enum8(x) => *x,
+                    Value::Enum16(x) => ranged!(*x as i128),
+                    _ => ranged!(integer()?),
+                };
+                if !variants.iter().any(|(_, i)| *i == index) {
+                    return Err(error());
+                }
+                Value::Enum8(index)
+            }
+            Type::Enum16(variants) => {
+                let index = match self {
+                    Value::String(x) => {
+                        let name = std::str::from_utf8(x).map_err(|_| error())?;
+                        variants
+                            .iter()
+                            .find(|(x, _)| x == name)
+                            .map(|(_, i)| *i)
+                            .ok_or_else(error)?
+                    }
+                    Value::Enum8(x) => *x as i16,
+                    Value::Enum16(x) => *x,
+                    _ => ranged!(integer()?),
+                };
+                if !variants.iter().any(|(_, i)| *i == index) {
+                    return Err(error());
+                }
+                Value::Enum16(index)
+            }
+            Type::Array(inner) => match self {
+                Value::Array(values) => Value::Array(
+                    values
+                        .iter()
+                        .map(|x| x.coerce_to_inner(inner, from.and_then(|x| x.unarray())))
+                        .collect::<Result<_>>()?,
+                ),
+                _ => return Err(error()),
+            },
+            Type::Tuple(types) => match self {
+                Value::Tuple(values) if values.len() == types.len() => Value::Tuple(
+                    values
+                        .iter()
+                        .zip(types)
+                        .enumerate()
+                        .map(|(i, (x, type_))| {
+                            x.coerce_to_inner(type_, from.and_then(|x| x.untuple()?.get(i)))
+                        })
+                        .collect::<Result<_>>()?,
+                ),
+                _ => return Err(error()),
+            },
+            Type::Map(key_type, value_type) => match self {
+                Value::Map(keys, values) => {
+                    let (from_key, from_value) = from.and_then(|x| x.unmap()).unzip();
+                    Value::Map(
+                        keys.iter()
+                            .map(|x| x.coerce_to_inner(key_type, from_key))
+                            .collect::<Result<_>>()?,
+                        values
+                            .iter()
+                            .map(|x| x.coerce_to_inner(value_type, from_value))
+                            .collect::<Result<_>>()?,
+                    )
+                }
+                _ => return Err(error()),
+            },
+            Type::Point
+            | Type::Ring
+            | Type::Polygon
+            | Type::MultiPolygon
+            | Type::LineString
+            | Type::MultiLineString => {
+                type_.validate_value(self).map_err(|_| error())?;
+                self.clone()
+            }
+        })
+    }
+
+    /// Same as [`Value::coerce_to`], but enum values are converted to strings by name using their source type `from`.
+    pub fn coerce_from_to(&self, from: &Type, type_: &Type) -> Result<Value> {
+        self.coerce_to_inner(type_, Some(from.strip_null().strip_low_cardinality()))
+    }
+}
@@ -0,0 +1,266 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use chrono_tz::Tz;
+
+use super::{
+    coerce::{date_time, text, Number},
+    *,
+};
+
+/// A comparison operator of a predicate, evaluated locally with [`Value::compare`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompareOp {
+    Equals,
+    NotEquals,
+    Less,
+    LessOrEquals,
+    Greater,
+    GreaterOrEquals,
+}
+
+impl CompareOp {
+    fn matches(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Equals => ordering == Ordering::Equal,
+            CompareOp::NotEquals => ordering != Ordering::Equal,
+            CompareOp::Less => ordering == Ordering::Less,
+            CompareOp::LessOrEquals => ordering != Ordering::Greater,
+            CompareOp::Greater => ordering == Ordering::Greater,
+            CompareOp::GreaterOrEquals => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Parses the SQL spelling of an operator, i.e. `<=` or `<>`.
+impl FromStr for CompareOp {
+    type Err = KlickhouseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim() {
+            "=" | "==" => CompareOp::Equals,
+            "!=" | "<>" => CompareOp::NotEquals,
+            "<" => CompareOp::Less,
+            "<=" => CompareOp::LessOrEquals,
+            ">" => CompareOp::Greater,
+            ">=" => CompareOp::GreaterOrEquals,
+            other => {
+                return Err(KlickhouseError::QueryArgumentError(format!(
+                    "unknown comparison operator: {other}"
+                )))
+            }
+        })
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CompareOp::Equals => "=",
+            CompareOp::NotEquals => "!=",
+            CompareOp::Less => "<",
+            CompareOp::LessOrEquals => "<=",
+            CompareOp::Greater => ">",
+            CompareOp::GreaterOrEquals => ">=",
+        })
+    }
+}
+
+/// Outcome of comparing two values the way Clickhouse does.
+enum SqlOrdering {
+    Ordered(Ordering),
+    /// Comparable types, but a `NaN` is involved, so only `!=` holds.
+    Unordered,
+    Null,
+    Incomparable,
+}
+
+/// Sign and magnitude of any integer value, which orders all of them exactly.
+fn wide_integer(value: &Value) -> Option<(bool, u256)> {
+    let signed = |x: i128| (x < 0, u256::from((0, x.unsigned_abs())));
+    let unsigned = |x: u128| (false, u256::from((0, x)));
+    Some(match value {
+        Value::Int8(x) => signed(*x as i128),
+        Value::Int16(x) => signed(*x as i128),
+        Value::Int32(x) => signed(*x as i128),
+        Value::Int64(x) => signed(*x as i128),
+        Value::Int128(x) => signed(*x),
+        Value::Int256(x) => x.sign_magnitude(),
+        Value::UInt8(x) => unsigned(*x as u128),
+        Value::UInt16(x) => unsigned(*x as u128),
+        Value::UInt32(x) => unsigned(*x as u128),
+        Value::UInt64(x) => unsigned(*x as u128),
+        Value::UInt128(x) => unsigned(*x),
+        Value::UInt256(x) => (false, *x),
+        _ => return None,
+    })
+}
+
+fn is_numeric(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Float32(_)
+            | Value::Float64(_)
+            | Value::Decimal32(..)
+            | Value::Decimal64(..)
+            | Value::Decimal128(..)
+            | Value::Decimal256(..)
+    ) || wide_integer(value).is_some()
+}
+
+/// Numeric value as a float, including 256-bit values outside the range of [`Number`].
+fn approximate(value: &Value) -> Option<f64> {
+    match Number::from_value(value) {
+        Some(x) => Some(x.float()),
+        None => text(value, None)?.parse().ok(),
+    }
+}
+
+fn compare_floats(left: f64, right: f64) -> SqlOrdering {
+    match left.partial_cmp(&right) {
+        Some(x) => SqlOrdering::Ordered(x),
+        None => SqlOrdering::Unordered,
+    }
+}
+
+fn compare_numbers(left: &Value, right: &Value) -> SqlOrdering {
+    if let (Some((l_negative, l)), Some((r_negative, r))) =
+        (wide_integer(left), wide_integer(right))
+    {
+        // -0 doesn't exist, so the sign alone orders values of different signs
+        return SqlOrdering::Ordered(match (l_negative, r_negative) {
+            (false, false) => l.cmp(&r),
+            (true, true) => r.cmp(&l),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+        });
+    }
+    let exact = |l: Number, r: Number, scale| match (l.decimal(scale), r.decimal(scale)) {
+        (Some(x), Some(y)) => SqlOrdering::Ordered(x.cmp(&y)),
+        _ => compare_floats(l.float(), r.float()),
+    };
+    match (Number::from_value(left), Number::from_value(right)) {
+        (Some(l @ Number::Float(_)), Some(r)) | (Some(l), Some(r @ Number::Float(_))) => {
+            compare_floats(l.float(), r.float())
+        }
+        (Some(l @ Number::Decimal(_, a)), Some(r @ Number::Decimal(_, b))) => exact(l, r, a.max(b)),
+        (Some(l @ Number::Decimal(_, scale)), Some(r))
+        | (Some(l), Some(r @ Number::Decimal(_, scale))) => exact(l, r, scale),
+        (Some(l), Some(r)) => compare_floats(l.float(), r.float()),
+        _ => match (approximate(left), approximate(right)) {
+            (Some(l), Some(r)) => compare_floats(l, r),
+            _ => SqlOrdering::Incomparable,
+        },
+    }
+}
+
+fn time_zone(value: &Value) -> Option<Tz> {
+    match value {
+        Value::DateTime(x) => Some(x.0),
+        Value::DateTime64(x) => Some(x.0),
+        _ => None,
+    }
+}
+
+fn compare_sequences(left: &[Value], right: &[Value]) -> SqlOrdering {
+    for (left, right) in left.iter().zip(right) {
+        match sql_ordering(left, right) {
+            SqlOrdering::Ordered(Ordering::Equal) => (),
+            other => return other,
+        }
+    }
+    SqlOrdering::Ordered(left.len().cmp(&right.len()))
+}
+
+fn sql_ordering(left: &Value, right: &Value) -> SqlOrdering {
+    use SqlOrdering::*;
+    match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => Null,
+        (left, right) if is_numeric(left) && is_numeric(right) => compare_numbers(left, right),
+        (Value::String(l), Value::String(r)) => Ordered(l.cmp(r)),
+        (Value::Uuid(l), Value::Uuid(r)) => Ordered(l.cmp(r)),
+        (Value::Date(l), Value::Date(r)) => Ordered(l.cmp(r)),
+        (
+            Value::Date(_) | Value::DateTime(_) | Value::DateTime64(_),
+            Value::Date(_) | Value::DateTime(_) | Value::DateTime64(_),
+        ) => {
+            // a date is midnight in the time zone of the other side, as on the server
+            let tz = time_zone(left)
+                .or_else(|| time_zone(right))
+                .unwrap_or(Tz::UTC);
+            match (date_time(left, tz), date_time(right, tz)) {
+                (Some(l), Some(r)) => Ordered(l.cmp(&r)),
+                _ => Incomparable,
+            }
+        }
+        (Value::Enum8(l), Value::Enum8(r)) => Ordered(l.cmp(r)),
+        (Value::Enum16(l), Value::Enum16(r)) => Ordered(l.cmp(r)),
+        (Value::Enum8(l), Value::Enum16(r)) => Ordered((*l as i16).cmp(r)),
+        (Value::Enum16(l), Value::Enum8(r)) => Ordered(l.cmp(&(*r as i16))),
+        (Value::Ipv4(l), Value::Ipv4(r)) => Ordered(l.cmp(r)),
+        (Value::Ipv6(l), Value::Ipv6(r)) => Ordered(l.cmp(r)),
+        (Value::Array(l), Value::Array(r)) => compare_sequences(l, r),
+        (Value::Tuple(l), Value::Tuple(r)) if l.len() == r.len() => compare_sequences(l, r),
+        _ => Incomparable,
+    }
+}
+
+impl Value {
+    /// Orders two values the way Clickhouse compares them: numbers by value across all numeric types (exactly, unless a float is involved),
+    /// strings by bytes, a `Date` against a `DateTime` as midnight in the time zone of the latter, and arrays and tuples element by element.
+    /// Returns `None` if either side is `NULL` or `NaN`, or the values can't be compared, i.e. a string with a number, or a `Map`.
+    pub fn sql_cmp(&self, other: &Value) -> Option<Ordering> {
+        match sql_ordering(self, other) {
+            SqlOrdering::Ordered(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Evaluates `self <op> other` like Clickhouse would, following [`Value::sql_cmp`].
+    /// Returns `Ok(None)` if either side is `NULL`, as the predicate is `NULL` too, and fails if the values can't be compared.
+    /// Like on the server, any comparison with `NaN` is false, except for `!=`.
+    pub fn compare(&self, op: CompareOp, other: &Value) -> Result<Option<bool>> {
+        match sql_ordering(self, other) {
+            SqlOrdering::Ordered(x) => Ok(Some(op.matches(x))),
+            SqlOrdering::Unordered => Ok(Some(op == CompareOp::NotEquals)),
+            SqlOrdering::Null => Ok(None),
+            SqlOrdering::Incomparable => Err(KlickhouseError::QueryArgumentError(format!(
+                "cannot compare {} with {}",
+                self.guess_type(),
+                other.guess_type()
+            ))),
+        }
+    }
+
+    /// Breaks ties between values that are equal for Clickhouse, but not structurally, i.e. `1::UInt8` and `1::Int32`.
+    fn tie_break(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Float32(l), Value::Float32(r)) => l.total_cmp(r),
+            (Value::Float64(l), Value::Float64(r)) => l.total_cmp(r),
+            (Value::Array(l), Value::Array(r)) | (Value::Tuple(l), Value::Tuple(r)) => l
+                .iter()
+                .zip(r)
+                .map(|(l, r)| l.partial_cmp(r).unwrap_or(Ordering::Equal))
+                .find(|x| x.is_ne())
+                .unwrap_or(Ordering::Equal),
+            _ => self
+                .guess_type()
+                .to_string()
+                .cmp(&other.guess_type().to_string()),
+        }
+    }
+}
+
+/// Orders values by [`Value::sql_cmp`], so `1::UInt8 < 2::Int64`. Values that Clickhouse considers equal but [`PartialEq`] doesn't,
+/// i.e. of different types, are ordered by type so that `partial_cmp` is `Some(Equal)` exactly when `==` holds.
+/// Incomparable values, including `NULL` against anything but `NULL`, return `None`.
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        match self.sql_cmp(other)? {
+            Ordering::Equal => Some(self.tie_break(other)),
+            x => Some(x),
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use std::num::TryFromIntError;
 
-use chrono::{Duration, FixedOffset, NaiveDate, ParseError, TimeZone, Utc};
+use chrono::{
+    Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc,
+};
 use chrono_tz::{Tz, UTC};
 
 use crate::{
-    convert::{unexpected_type, FromSql, ToSql},
+    convert::{unexpected_type, unexpected_value, FromSql, ToSql},
     types::Type,
     KlickhouseError, Result, Value,
 };
@@ -13,6 +15,76 @@ use crate::{
 #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd, Debug, Default)]
 pub struct Date(pub u16);
 
+impl Date {
+    /// The date `days` later (or earlier, if negative), or `None` if it's outside the range of `Date` (1970-01-01 to 2149-06-06).
+    pub fn add_days(self, days: i64) -> Option<Self> {
+        (self.0 as i64).checked_add(days)?.try_into().ok().map(Self)
+    }
+
+    /// The next day, or `None` after 2149-06-06.
+    pub fn succ(self) -> Option<Self> {
+        self.add_days(1)
+    }
+
+    /// The previous day, or `None` before 1970-01-02.
+    pub fn pred(self) -> Option<Self> {
+        self.add_days(-1)
+    }
+}
+
+/// Iterates over the days from `start` up to, but excluding, `end`. See [`DateRange::inclusive`] to include `end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DateRange {
+    start: u32,
+    end: u32,
+}
+
+impl DateRange {
+    pub fn new(start: Date, end: Date) -> Self {
+        Self {
+            start: start.0 as u32,
+            end: (end.0 as u32).max(start.0 as u32),
+        }
+    }
+
+    /// Iterates over the days from `start` up to and including `end`.
+    pub fn inclusive(start: Date, end: Date) -> Self {
+        Self {
+            start: start.0 as u32,
+            end: (end.0 as u32 + 1).max(start.0 as u32),
+        }
+    }
+}
+
+impl Iterator for DateRange {
+    type Item = Date;
+
+    fn next(&mut self) -> Option<Date> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        Some(Date((self.start - 1) as u16))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end - self.start) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for DateRange {
+    fn next_back(&mut self) -> Option<Date> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(Date(self.end as u16))
+    }
+}
+
+impl ExactSizeIterator for DateRange {}
+
 #[cfg(feature = "serde")]
 impl serde::Serialize for Date {
     fn serialize<S: serde::Serializer>(
@@ -47,7 +119,7 @@ impl FromSql for Date {
         }
         match value {
             Value::Date(x) => Ok(x),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -93,6 +165,17 @@ impl From<chrono::NaiveDate> for Date {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct DateTime(pub Tz, pub u32);
 
+impl DateTime {
+    /// The time `seconds` later (or earlier, if negative) in the same timezone, or `None` if it's outside the range of `DateTime` (1970 to 2106).
+    pub fn add_seconds(self, seconds: i64) -> Option<Self> {
+        (self.1 as i64)
+            .checked_add(seconds)?
+            .try_into()
+            .ok()
+            .map(|x| Self(self.0, x))
+    }
+}
+
 #[cfg(feature = "serde")]
 impl serde::Serialize for DateTime {
     fn serialize<S: serde::Serializer>(
@@ -114,7 +197,7 @@ impl<'de> serde::Deserialize<'de> for DateTime {
         let raw: String = String::deserialize(deserializer)?;
         let date: chrono::DateTime<FixedOffset> =
             chrono::DateTime::<FixedOffset>::parse_from_rfc3339(&raw)
-                .map_err(|e: ParseError| serde::de::Error::custom(e.to_string()))?;
+                .map_err(|e: chrono::ParseError| serde::de::Error::custom(e.to_string()))?;
 
         date.try_into()
             .map_err(|e: TryFromIntError| serde::de::Error::custom(e.to_string()))
@@ -134,7 +217,7 @@ impl FromSql for DateTime {
         }
         match value {
             Value::DateTime(x) => Ok(x),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -211,6 +294,21 @@ pub struct DateTime64<const PRECISION: usize>(pub Tz, pub u64);
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct DynDateTime64(pub Tz, pub u64, pub usize);
 
+impl DynDateTime64 {
+    /// Drops the digits finer than `precision` (i.e. 3 for milliseconds), returning a value of that precision.
+    /// Values with a precision of at most `precision` are returned as-is.
+    pub fn truncate_to(self, precision: usize) -> Self {
+        if precision >= self.2 {
+            return self;
+        }
+        Self(
+            self.0,
+            self.1 / 10u64.pow((self.2 - precision) as u32),
+            precision,
+        )
+    }
+}
+
 impl<const PRECISION: usize> From<DateTime64<PRECISION>> for DynDateTime64 {
     fn from(value: DateTime64<PRECISION>) -> Self {
         Self(value.0, value.1, PRECISION)
@@ -238,7 +336,7 @@ impl<'de> serde::Deserialize<'de> for DynDateTime64 {
         let raw: String = String::deserialize(deserializer)?;
         let date: chrono::DateTime<Utc> = Utc.from_utc_datetime(
             &chrono::DateTime::<FixedOffset>::parse_from_rfc3339(&raw)
-                .map_err(|e: ParseError| serde::de::Error::custom(e.to_string()))?
+                .map_err(|e: chrono::ParseError| serde::de::Error::custom(e.to_string()))?
                 .naive_utc(),
         );
 
@@ -268,7 +366,7 @@ impl<'de, const PRECISION: usize> serde::Deserialize<'de> for DateTime64<PRECISI
         let raw: String = String::deserialize(deserializer)?;
         let date: chrono::DateTime<Utc> = Utc.from_utc_datetime(
             &chrono::DateTime::<FixedOffset>::parse_from_rfc3339(&raw)
-                .map_err(|e: ParseError| serde::de::Error::custom(e.to_string()))?
+                .map_err(|e: chrono::ParseError| serde::de::Error::custom(e.to_string()))?
                 .naive_utc(),
         );
 
@@ -290,7 +388,7 @@ impl<const PRECISION: usize> FromSql for DateTime64<PRECISION> {
         }
         match value {
             Value::DateTime64(datetime) => Ok(Self(datetime.0, datetime.1)),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -301,15 +399,10 @@ impl<const PRECISION: usize> Default for DateTime64<PRECISION> {
     }
 }
 
+/// Writes a `DateTime64(6)`, or a value of the column type if known. Timestamps out of its range fail, see [`DateTimeWithOverflow`].
 impl ToSql for chrono::DateTime<Utc> {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::DateTime64(DynDateTime64(
-            chrono_tz::UTC,
-            self.timestamp_micros().try_into().map_err(|e| {
-                KlickhouseError::DeserializeError(format!("failed to convert DateTime64: {:?}", e))
-            })?,
-            6,
-        )))
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        DateTimeWithOverflow(self, DateTimeOverflow::default()).to_sql(type_hint)
     }
 }
 
@@ -335,13 +428,19 @@ impl FromSql for chrono::DateTime<Utc> {
                 Ok(datetime
                     .0
                     .timestamp_opt(seconds, units_ns)
-                    .unwrap()
+                    .single()
+                    .ok_or_else(|| {
+                        KlickhouseError::DeserializeError(format!(
+                            "DateTime64 out of range: {}",
+                            datetime.1
+                        ))
+                    })?
                     .with_timezone(&Utc))
             }
             Value::DateTime(date) => Ok(date.try_into().map_err(|e| {
                 KlickhouseError::DeserializeError(format!("failed to convert DateTime: {:?}", e))
             })?),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -376,15 +475,10 @@ impl TryFrom<DynDateTime64> for chrono::DateTime<Utc> {
     }
 }
 
+/// Writes a `DateTime64(6)`, or a value of the column type if known. Timestamps out of its range fail, see [`DateTimeWithOverflow`].
 impl ToSql for chrono::DateTime<Tz> {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::DateTime64(DynDateTime64(
-            self.timezone(),
-            self.timestamp_micros().try_into().map_err(|e| {
-                KlickhouseError::DeserializeError(format!("failed to convert DateTime64: {:?}", e))
-            })?,
-            6,
-        )))
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        DateTimeWithOverflow(self, DateTimeOverflow::default()).to_sql(type_hint)
     }
 }
 
@@ -407,12 +501,21 @@ impl FromSql for chrono::DateTime<Tz> {
                             e
                         ))
                     })?;
-                Ok(datetime.0.timestamp_opt(seconds, units_ns).unwrap())
+                datetime
+                    .0
+                    .timestamp_opt(seconds, units_ns)
+                    .single()
+                    .ok_or_else(|| {
+                        KlickhouseError::DeserializeError(format!(
+                            "DateTime64 out of range: {}",
+                            datetime.1
+                        ))
+                    })
             }
             Value::DateTime(date) => Ok(date.try_into().map_err(|e| {
                 KlickhouseError::DeserializeError(format!("failed to convert DateTime: {:?}", e))
             })?),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -525,6 +628,259 @@ impl TryFrom<DynDateTime64> for chrono::DateTime<FixedOffset> {
     }
 }
 
+/// Policy for resolving a naive wall-clock time that is ambiguous or nonexistent in a timezone, i.e. around DST transitions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum DstPolicy {
+    /// Ambiguous times resolve to the earlier instant. Nonexistent times are shifted backwards by the length of the gap.
+    #[default]
+    Earliest,
+    /// Ambiguous times resolve to the later instant. Nonexistent times are shifted forwards by the length of the gap.
+    Latest,
+    /// Ambiguous and nonexistent times are rejected with an error.
+    Reject,
+}
+
+impl DstPolicy {
+    /// Interprets `naive` as a wall-clock time in `tz`, resolving DST ambiguity according to this policy.
+    pub fn resolve(self, tz: Tz, naive: NaiveDateTime) -> Result<chrono::DateTime<Tz>> {
+        match (tz.from_local_datetime(&naive), self) {
+            (LocalResult::Single(x), _) => Ok(x),
+            (LocalResult::Ambiguous(earliest, _), DstPolicy::Earliest) => Ok(earliest),
+            (LocalResult::Ambiguous(_, latest), DstPolicy::Latest) => Ok(latest),
+            (LocalResult::None, DstPolicy::Earliest | DstPolicy::Latest) => {
+                // offsets in effect on either side of the gap
+                let before = tz.offset_from_local_datetime(&(naive - Duration::days(1)));
+                let after = tz.offset_from_local_datetime(&(naive + Duration::days(1)));
+                let offset = match self {
+                    DstPolicy::Earliest => after.latest(),
+                    _ => before.earliest(),
+                }
+                .ok_or_else(|| {
+                    KlickhouseError::SerializeError(format!(
+                        "cannot resolve nonexistent local time {naive} in {tz}"
+                    ))
+                })?;
+                Ok(tz.from_utc_datetime(&(naive - offset.fix())))
+            }
+            (LocalResult::Ambiguous(_, _), _) => Err(KlickhouseError::SerializeError(format!(
+                "local time {naive} is ambiguous in {tz}"
+            ))),
+            (LocalResult::None, _) => Err(KlickhouseError::SerializeError(format!(
+                "local time {naive} does not exist in {tz}"
+            ))),
+        }
+    }
+}
+
+/// A [`NaiveDateTime`] paired with an explicit [`DstPolicy`], used when writing wall-clock times into `DateTime`/`DateTime64` columns.
+/// A bare [`NaiveDateTime`] uses [`DstPolicy::default`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NaiveDateTimeWithPolicy(pub NaiveDateTime, pub DstPolicy);
+
+impl ToSql for NaiveDateTimeWithPolicy {
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        let map_err = |e: TryFromIntError| {
+            KlickhouseError::SerializeError(format!("failed to convert DateTime: {:?}", e))
+        };
+        match type_hint.map(|x| x.strip_null().strip_low_cardinality()) {
+            Some(Type::DateTime(tz)) => Ok(Value::DateTime(
+                self.1.resolve(*tz, self.0)?.try_into().map_err(map_err)?,
+            )),
+            Some(Type::DateTime64(precision, tz)) => Ok(Value::DateTime64(
+                DynDateTime64::try_from_tz(self.1.resolve(*tz, self.0)?, *precision)
+                    .map_err(map_err)?,
+            )),
+            // without a column timezone, the naive time is taken as UTC
+            _ => Ok(Value::DateTime64(
+                DynDateTime64::try_from_utc(self.0.and_utc(), 6).map_err(map_err)?,
+            )),
+        }
+    }
+}
+
+impl ToSql for NaiveDateTime {
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        NaiveDateTimeWithPolicy(self, DstPolicy::default()).to_sql(type_hint)
+    }
+}
+
+/// Reads the wall-clock time in the column timezone.
+impl FromSql for NaiveDateTime {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(chrono::DateTime::<Tz>::from_sql(type_, value)?.naive_local())
+    }
+}
+
+impl FromSql for NaiveDateTimeWithPolicy {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(Self(
+            NaiveDateTime::from_sql(type_, value)?,
+            DstPolicy::default(),
+        ))
+    }
+}
+
+/// Reads the wall-clock time of day in the column timezone.
+impl FromSql for NaiveTime {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(NaiveDateTime::from_sql(type_, value)?.time())
+    }
+}
+
+/// The latest time of `DateTime64` columns, 2299-12-31 23:59:59 UTC.
+const DATETIME64_MAX_SECONDS: i128 = 10_413_791_999;
+
+/// Policy for writing timestamps outside the range of their `DateTime`/`DateTime64` column. Timestamps before 1970 are out of range for both,
+/// as are those after 2106-02-07 06:28:15 for `DateTime`, and after 2299-12-31 for `DateTime64` (2262-04-11 at nanosecond precision).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum DateTimeOverflow {
+    /// Out of range timestamps are rejected with an error naming the timestamp and the range.
+    #[default]
+    Error,
+    /// Out of range timestamps are replaced with the nearest timestamp in range.
+    Clamp,
+    /// Out of range timestamps are written as `NULL`, or rejected like with `Error` if the column isn't `Nullable`.
+    Null,
+}
+
+impl DateTimeOverflow {
+    /// Converts `value` for a column of type `type_hint`, or to a `DateTime64(6)` in `tz` without one.
+    fn apply(
+        self,
+        value: chrono::DateTime<Utc>,
+        tz: Tz,
+        type_hint: Option<&Type>,
+    ) -> Result<Value> {
+        let nullable = type_hint.is_some_and(|x| x.strip_low_cardinality().is_nullable());
+        let (type_, tz) = match type_hint.map(|x| x.strip_null().strip_low_cardinality()) {
+            Some(Type::DateTime(tz)) => (Type::DateTime(*tz), *tz),
+            Some(Type::DateTime64(precision, tz)) => (Type::DateTime64(*precision, *tz), *tz),
+            _ => (Type::DateTime64(6, tz), tz),
+        };
+        let precision = match type_ {
+            Type::DateTime64(precision, _) => precision as u32,
+            _ => 0,
+        };
+        let scale = 10i128.pow(precision);
+        let max = match type_ {
+            Type::DateTime(_) => u32::MAX as i128,
+            _ => ((DATETIME64_MAX_SECONDS + 1) * scale - 1).min(i64::MAX as i128),
+        };
+        let mut units = value.timestamp() as i128 * scale
+            + value.timestamp_subsec_nanos() as i128 / 10i128.pow(9 - precision);
+        if !(0..=max).contains(&units) {
+            match self {
+                DateTimeOverflow::Clamp => units = units.clamp(0, max),
+                DateTimeOverflow::Null if nullable => return Ok(Value::Null),
+                _ => {
+                    let last = Utc
+                        .timestamp_opt(
+                            (max / scale) as i64,
+                            ((max % scale) * 10i128.pow(9 - precision)) as u32,
+                        )
+                        .unwrap();
+                    return Err(KlickhouseError::SerializeError(format!(
+                        "timestamp {value} out of range for {type_}, from 1970-01-01 00:00:00 UTC to {last}"
+                    )));
+                }
+            }
+        }
+        Ok(match type_ {
+            Type::DateTime(_) => Value::DateTime(DateTime(tz, units as u32)),
+            _ => Value::DateTime64(DynDateTime64(tz, units as u64, precision as usize)),
+        })
+    }
+}
+
+/// A timestamp paired with an explicit [`DateTimeOverflow`] policy, used when writing timestamps that may be out of range,
+/// i.e. outliers in a data pipeline. A bare timestamp uses [`DateTimeOverflow::default`].
+///
+/// Rows can set the policy of a field with `#[klickhouse(datetime_overflow = "clamp")]` (or `"null"`, `"error"`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DateTimeWithOverflow<T>(pub T, pub DateTimeOverflow);
+
+impl ToSql for DateTimeWithOverflow<chrono::DateTime<Utc>> {
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        self.1.apply(self.0, UTC, type_hint)
+    }
+}
+
+impl ToSql for DateTimeWithOverflow<chrono::DateTime<Tz>> {
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        self.1
+            .apply(self.0.with_timezone(&Utc), self.0.timezone(), type_hint)
+    }
+}
+
+impl<T> ToSql for DateTimeWithOverflow<Option<T>>
+where
+    DateTimeWithOverflow<T>: ToSql,
+{
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        match self.0 {
+            Some(x) => DateTimeWithOverflow(x, self.1).to_sql(type_hint),
+            None => Ok(Value::Null),
+        }
+    }
+}
+
+impl<T: FromSql> FromSql for DateTimeWithOverflow<T> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(Self(
+            T::from_sql(type_, value)?,
+            DateTimeOverflow::default(),
+        ))
+    }
+}
+
+/// Writes a `Date`, or midnight in the column timezone for `DateTime`/`DateTime64` columns.
+/// If a DST transition skips midnight, the first time of the day is used instead.
+impl ToSql for NaiveDate {
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        match type_hint.map(|x| x.strip_null().strip_low_cardinality()) {
+            Some(Type::DateTime(_) | Type::DateTime64(..)) => {
+                NaiveDateTimeWithPolicy(self.and_time(NaiveTime::MIN), DstPolicy::Latest)
+                    .to_sql(type_hint)
+            }
+            _ => {
+                let days = self.signed_duration_since(NaiveDate::default()).num_days();
+                Ok(Value::Date(Date(days.try_into().map_err(|_| {
+                    KlickhouseError::SerializeError(format!("date {self} out of range for Date"))
+                })?)))
+            }
+        }
+    }
+}
+
+/// Reads `Date` columns only. Use [`TruncatedDate`] to read the date of `DateTime`/`DateTime64` columns.
+impl FromSql for NaiveDate {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(Date::from_sql(type_, value)?.into())
+    }
+}
+
+/// A date read from either a `Date` column, or a `DateTime`/`DateTime64` column by dropping the time of day in the column timezone,
+/// i.e. to group rows of an event table into a daily rollup. Written like a [`NaiveDate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TruncatedDate(pub NaiveDate);
+
+impl ToSql for TruncatedDate {
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        self.0.to_sql(type_hint)
+    }
+}
+
+impl FromSql for TruncatedDate {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(Self(match type_ {
+            Type::DateTime(_) | Type::DateTime64(..) => {
+                NaiveDateTime::from_sql(type_, value)?.date()
+            }
+            _ => NaiveDate::from_sql(type_, value)?,
+        }))
+    }
+}
+
 #[cfg(test)]
 mod chrono_tests {
     use super::*;
@@ -542,6 +898,87 @@ mod chrono_tests {
         }
     }
 
+    #[test]
+    fn test_date_arithmetic() {
+        let date = Date::from(NaiveDate::from_ymd_opt(2024, 2, 28).unwrap());
+        assert_eq!(
+            NaiveDate::from(date.succ().unwrap().succ().unwrap()),
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+        );
+        assert_eq!(date.add_days(-2), date.pred().unwrap().pred());
+        assert_eq!(Date(0).pred(), None);
+        assert_eq!(Date(u16::MAX).succ(), None);
+        assert_eq!(Date(5).add_days(i64::MAX), None);
+
+        let days = DateRange::new(Date(10), Date(13)).collect::<Vec<_>>();
+        assert_eq!(days, vec![Date(10), Date(11), Date(12)]);
+        let days = DateRange::inclusive(Date(10), Date(12))
+            .rev()
+            .collect::<Vec<_>>();
+        assert_eq!(days, vec![Date(12), Date(11), Date(10)]);
+        assert_eq!(DateRange::new(Date(10), Date(5)).len(), 0);
+        assert_eq!(DateRange::inclusive(Date(0), Date(u16::MAX)).len(), 65536);
+        assert_eq!(
+            DateRange::inclusive(Date(u16::MAX - 1), Date(u16::MAX)).collect::<Vec<_>>(),
+            vec![Date(u16::MAX - 1), Date(u16::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_datetime_arithmetic() {
+        let time = DateTime(UTC, 100);
+        assert_eq!(time.add_seconds(-100), Some(DateTime(UTC, 0)));
+        assert_eq!(time.add_seconds(-101), None);
+        assert_eq!(time.add_seconds(u32::MAX as i64), None);
+
+        let time = DynDateTime64(UTC, 1_700_000_000_123_456_789, 9);
+        assert_eq!(
+            time.truncate_to(3),
+            DynDateTime64(UTC, 1_700_000_000_123, 3)
+        );
+        assert_eq!(time.truncate_to(0), DynDateTime64(UTC, 1_700_000_000, 0));
+        assert_eq!(time.truncate_to(9), time);
+        assert_eq!(time.truncate_to(3).truncate_to(6), time.truncate_to(3));
+    }
+
+    #[test]
+    fn test_naivedate_datetime() {
+        let tz = chrono_tz::America::Sao_Paulo;
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        assert_eq!(date.to_sql(None).unwrap(), Value::Date(date.into()));
+        assert_eq!(
+            date.to_sql(Some(&Type::DateTime(UTC))).unwrap(),
+            Value::DateTime(DateTime(UTC, 1_717_200_000))
+        );
+        assert_eq!(
+            date.to_sql(Some(&Type::DateTime64(3, tz))).unwrap(),
+            Value::DateTime64(DynDateTime64(tz, 1_717_210_800_000, 3))
+        );
+        // midnight didn't exist on the day DST started
+        let skipped = NaiveDate::from_ymd_opt(2018, 11, 4).unwrap();
+        let Value::DateTime(time) = skipped.to_sql(Some(&Type::DateTime(tz))).unwrap() else {
+            panic!("expected DateTime");
+        };
+        let time = chrono::DateTime::<Tz>::try_from(time).unwrap();
+        assert_eq!(time.naive_local(), skipped.and_hms_opt(1, 0, 0).unwrap());
+        assert!(NaiveDate::from_ymd_opt(1969, 12, 31)
+            .unwrap()
+            .to_sql(None)
+            .is_err());
+
+        // 23:30 in Sao Paulo is the next day in UTC
+        let value = Value::DateTime(DateTime(tz, 1_717_295_400));
+        assert!(NaiveDate::from_sql(&Type::DateTime(tz), value.clone()).is_err());
+        assert_eq!(
+            TruncatedDate::from_sql(&Type::DateTime(tz), value).unwrap(),
+            TruncatedDate(date)
+        );
+        assert_eq!(
+            TruncatedDate::from_sql(&Type::Date, Value::Date(date.into())).unwrap(),
+            TruncatedDate(date)
+        );
+    }
+
     #[test]
     fn test_naivedate() {
         for i in 0..30000u16 {
@@ -624,4 +1061,131 @@ mod chrono_tests {
 
         assert_eq!(new_chrono_date, chrono_date);
     }
+
+    #[test]
+    fn test_naive_datetime() {
+        let naive = NaiveDate::from_ymd_opt(2022, 4, 22)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap();
+        let type_ = Type::DateTime(chrono_tz::Europe::Berlin);
+        let value = naive.to_sql(Some(&type_)).unwrap();
+        // CEST is UTC+2
+        assert_eq!(
+            value,
+            Value::DateTime(DateTime(
+                chrono_tz::Europe::Berlin,
+                naive.and_utc().timestamp() as u32 - 7200
+            ))
+        );
+        assert_eq!(
+            naive,
+            NaiveDateTime::from_sql(&type_, value.clone()).unwrap()
+        );
+        assert_eq!(naive.time(), NaiveTime::from_sql(&type_, value).unwrap());
+
+        let type_ = Type::DateTime64(3, chrono_tz::Europe::Berlin);
+        let value = naive.to_sql(Some(&type_)).unwrap();
+        assert_eq!(naive, NaiveDateTime::from_sql(&type_, value).unwrap());
+    }
+
+    #[test]
+    fn test_naive_datetime_dst() {
+        let tz = chrono_tz::Europe::Berlin;
+        let type_ = Type::DateTime(tz);
+        let ambiguous = NaiveDate::from_ymd_opt(2022, 10, 30)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        let earliest = DstPolicy::Earliest.resolve(tz, ambiguous).unwrap();
+        let latest = DstPolicy::Latest.resolve(tz, ambiguous).unwrap();
+        assert_eq!(latest.timestamp() - earliest.timestamp(), 3600);
+        assert_eq!(earliest.naive_local(), ambiguous);
+        assert_eq!(latest.naive_local(), ambiguous);
+        assert!(NaiveDateTimeWithPolicy(ambiguous, DstPolicy::Reject)
+            .to_sql(Some(&type_))
+            .is_err());
+
+        let nonexistent = NaiveDate::from_ymd_opt(2022, 3, 27)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        assert_eq!(
+            DstPolicy::Earliest
+                .resolve(tz, nonexistent)
+                .unwrap()
+                .naive_local()
+                .time(),
+            NaiveTime::from_hms_opt(1, 30, 0).unwrap()
+        );
+        assert_eq!(
+            DstPolicy::Latest
+                .resolve(tz, nonexistent)
+                .unwrap()
+                .naive_local()
+                .time(),
+            NaiveTime::from_hms_opt(3, 30, 0).unwrap()
+        );
+        assert!(NaiveDateTimeWithPolicy(nonexistent, DstPolicy::Reject)
+            .to_sql(Some(&type_))
+            .is_err());
+    }
+
+    #[test]
+    fn test_datetime_overflow() {
+        let before = Utc.with_ymd_and_hms(1969, 12, 31, 23, 0, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2300, 1, 1, 0, 0, 0).unwrap();
+        let late = Utc.with_ymd_and_hms(2270, 1, 1, 0, 0, 0).unwrap();
+        let datetime = Type::DateTime(UTC);
+        let datetime64 = Type::DateTime64(9, UTC);
+
+        let e = before.to_sql(Some(&datetime)).unwrap_err().to_string();
+        assert!(e.contains("1969-12-31 23:00:00 UTC"), "{e}");
+        assert!(e.contains("2106-02-07 06:28:15 UTC"), "{e}");
+        assert!(late.to_sql(Some(&datetime)).is_err());
+        assert_eq!(
+            late.to_sql(Some(&Type::DateTime64(3, UTC))).unwrap(),
+            Value::DateTime64(DynDateTime64(UTC, late.timestamp_millis() as u64, 3))
+        );
+        assert!(late.to_sql(Some(&datetime64)).is_err());
+        assert!(after.to_sql(Some(&Type::DateTime64(3, UTC))).is_err());
+        assert!(after.to_sql(None).is_err());
+
+        assert_eq!(
+            DateTimeWithOverflow(before, DateTimeOverflow::Clamp)
+                .to_sql(Some(&datetime))
+                .unwrap(),
+            Value::DateTime(DateTime(UTC, 0))
+        );
+        assert_eq!(
+            DateTimeWithOverflow(after, DateTimeOverflow::Clamp)
+                .to_sql(Some(&datetime))
+                .unwrap(),
+            Value::DateTime(DateTime(UTC, u32::MAX))
+        );
+        assert_eq!(
+            DateTimeWithOverflow(after, DateTimeOverflow::Clamp)
+                .to_sql(Some(&datetime64))
+                .unwrap(),
+            Value::DateTime64(DynDateTime64(UTC, i64::MAX as u64, 9))
+        );
+
+        let nullable = Type::Nullable(Box::new(datetime.clone()));
+        assert_eq!(
+            DateTimeWithOverflow(Some(after), DateTimeOverflow::Null)
+                .to_sql(Some(&nullable))
+                .unwrap(),
+            Value::Null
+        );
+        assert!(DateTimeWithOverflow(after, DateTimeOverflow::Null)
+            .to_sql(Some(&datetime))
+            .is_err());
+        let in_range = Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            DateTimeWithOverflow(in_range.with_timezone(&UTC), DateTimeOverflow::Null)
+                .to_sql(Some(&nullable))
+                .unwrap(),
+            Value::DateTime(DateTime(UTC, in_range.timestamp() as u32))
+        );
+    }
 }
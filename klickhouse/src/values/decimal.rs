@@ -1,6 +1,10 @@
 use rust_decimal::Decimal;
 
-use crate::{unexpected_type, FromSql, KlickhouseError, Result, ToSql, Type, Value};
+use crate::{
+    unexpected_type,
+    values::{coerce::i256_to_i128, fixed_point::rescale_to_hint},
+    FromSql, KlickhouseError, Result, ToSql, Type, Value,
+};
 
 impl FromSql for Decimal {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
@@ -36,50 +40,25 @@ impl FromSql for Decimal {
                 Decimal::try_from_i128_with_scale(value, precision as u32)
                     .map_err(|_| out_of_range("Decimal128"))
             }
+            Value::Decimal256(precision, value) => Decimal::try_from_i128_with_scale(
+                i256_to_i128(value).ok_or_else(|| out_of_range("Decimal256"))?,
+                precision as u32,
+            )
+            .map_err(|_| out_of_range("Decimal256")),
             _ => Err(unexpected_type(type_)),
         }
     }
 }
 
+/// Writes a `Decimal128` of the decimal's own scale, or rescales to the decimal type hint (which may be nullable), failing if that would lose digits.
 impl ToSql for Decimal {
     fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
-        fn out_of_range(name: &str) -> KlickhouseError {
-            KlickhouseError::SerializeError(format!("{name} out of bounds for rust_decimal"))
-        }
-
-        fn mantissa_to_scale(mantissa: i128, scale: u32, precision: u32) -> Result<i128> {
-            assert!(precision >= scale);
-            if precision == scale {
-                Ok(mantissa)
-            } else {
-                mantissa
-                    .checked_mul(10i128.pow(precision - scale))
-                    .ok_or_else(|| out_of_range("mantissa"))
-            }
-        }
-
-        let scale = self.scale();
-        let mantissa = self.mantissa();
-        match type_hint {
-            None => Ok(Value::Decimal128(scale as usize, mantissa)),
-            Some(Type::Decimal32(precision)) if *precision as u32 >= scale => Ok(Value::Decimal32(
-                *precision,
-                mantissa_to_scale(mantissa, scale, *precision as u32)?
-                    .try_into()
-                    .map_err(|_| out_of_range("Decimal32"))?,
-            )),
-            Some(Type::Decimal64(precision)) if *precision as u32 >= scale => Ok(Value::Decimal64(
-                *precision,
-                mantissa_to_scale(mantissa, scale, *precision as u32)?
-                    .try_into()
-                    .map_err(|_| out_of_range("Decimal64"))?,
-            )),
-            Some(Type::Decimal128(precision)) if *precision as u32 >= scale => {
-                Ok(Value::Decimal128(
-                    *precision,
-                    mantissa_to_scale(mantissa, scale, *precision as u32)?,
-                ))
-            }
+        let value = Value::Decimal128(self.scale() as usize, self.mantissa());
+        match type_hint.map(Type::strip_null) {
+            None => Ok(value),
+            Some(
+                Type::Decimal32(_) | Type::Decimal64(_) | Type::Decimal128(_) | Type::Decimal256(_),
+            ) => rescale_to_hint(value, type_hint),
             Some(x) => Err(KlickhouseError::SerializeError(format!(
                 "unexpected type: {}",
                 x
@@ -87,3 +66,54 @@ impl ToSql for Decimal {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QueryBuilder;
+
+    #[test]
+    fn test_decimal_argument() {
+        let query = QueryBuilder::new("SELECT * FROM x WHERE y = $1")
+            .arg(Decimal::new(12345, 4))
+            .finalize()
+            .unwrap();
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM x WHERE y = toDecimal128('1.2345', 4)"
+        );
+    }
+
+    #[test]
+    fn test_nullable_decimal() {
+        let type_ = Type::Nullable(Box::new(Type::Decimal64(4)));
+        let value = Some(Decimal::new(15, 1)).to_sql(Some(&type_)).unwrap();
+        assert_eq!(value, Value::Decimal64(4, 15000));
+        type_.validate_value(&value).unwrap();
+        assert_eq!(
+            Option::<Decimal>::from_sql(&type_, value).unwrap(),
+            Some(Decimal::new(15000, 4))
+        );
+        assert_eq!(None::<Decimal>.to_sql(Some(&type_)).unwrap(), Value::Null);
+        assert_eq!(
+            Option::<Decimal>::from_sql(&type_, Value::Null).unwrap(),
+            None
+        );
+
+        // non-optional values can be written to nullable columns too
+        assert_eq!(
+            Decimal::new(-25, 1).to_sql(Some(&type_)).unwrap(),
+            Value::Decimal64(4, -25000)
+        );
+        let value = Decimal::new(-25, 1)
+            .to_sql(Some(&Type::Decimal256(2)))
+            .unwrap();
+        assert_eq!(
+            Decimal::from_sql(&Type::Decimal256(2), value).unwrap(),
+            Decimal::new(-250, 2)
+        );
+        assert!(Decimal::new(12345, 4)
+            .to_sql(Some(&Type::Decimal64(2)))
+            .is_err());
+    }
+}
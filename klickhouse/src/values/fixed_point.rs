@@ -1,10 +1,74 @@
+use std::fmt::{self, Write};
+
 use crate::{
-    convert::{unexpected_type, FromSql, ToSql},
+    convert::{unexpected_type, unexpected_value, FromSql, ToSql},
     i256,
     types::Type,
-    Result, Value,
+    KlickhouseError, Result, Value,
 };
 
+/// Rescales a decimal `value` to the decimal type of `type_hint`, which may be nullable, i.e. to insert a `FixedPoint64<2>` into a `Decimal64(4)` column.
+/// Fails if that would lose digits. Other type hints leave `value` as-is.
+pub(crate) fn rescale_to_hint(value: Value, type_hint: Option<&Type>) -> Result<Value> {
+    match type_hint.map(Type::strip_null) {
+        Some(
+            type_ @ (Type::Decimal32(_)
+            | Type::Decimal64(_)
+            | Type::Decimal128(_)
+            | Type::Decimal256(_)),
+        ) if *type_ != value.guess_type() => value.coerce_to(type_),
+        _ => Ok(value),
+    }
+}
+
+/// Reads a value of a decimal column of any width and scale as a value of the decimal type `target`, failing if that would lose digits.
+pub(crate) fn decimal_from_sql(type_: &Type, value: Value, target: &Type) -> Result<Value> {
+    if !matches!(
+        type_,
+        Type::Decimal32(_) | Type::Decimal64(_) | Type::Decimal128(_) | Type::Decimal256(_)
+    ) {
+        return Err(unexpected_type(type_));
+    }
+    if type_ == target {
+        return Ok(value);
+    }
+    value.coerce_to(target).map_err(|_| {
+        KlickhouseError::DeserializeError(format!(
+            "cannot read {value:?} of {type_} as {target} without losing digits"
+        ))
+    })
+}
+
+/// Writes the decimal digits of `magnitude` with the decimal point shifted left by `scale`, i.e. `-0.00005` for `(true, "5", 5)`.
+pub(crate) fn write_fixed_point(
+    f: &mut impl Write,
+    negative: bool,
+    magnitude: &str,
+    scale: usize,
+) -> fmt::Result {
+    if negative && magnitude.bytes().any(|x| x != b'0') {
+        f.write_char('-')?;
+    }
+    if magnitude.len() <= scale {
+        f.write_char('0')?;
+        if scale > 0 {
+            f.write_char('.')?;
+            for _ in magnitude.len()..scale {
+                f.write_char('0')?;
+            }
+            f.write_str(magnitude)?;
+        }
+    } else {
+        let (integer, fraction) = magnitude.split_at(magnitude.len() - scale);
+        f.write_str(integer)?;
+        if scale > 0 {
+            f.write_char('.')?;
+            f.write_str(fraction)?;
+        }
+    }
+    Ok(())
+}
+
 /// Wrapper type for Clickhouse `FixedPoint32` type.
 #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd, Debug, Default)]
 pub struct FixedPoint32<const PRECISION: u64>(pub i32);
@@ -24,19 +88,16 @@ impl<const PRECISION: u64> FixedPoint32<PRECISION> {
 }
 
 impl<const PRECISION: u64> ToSql for FixedPoint32<PRECISION> {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::Decimal32(PRECISION as usize, self.0))
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        rescale_to_hint(Value::Decimal32(PRECISION as usize, self.0), type_hint)
     }
 }
 
 impl<const PRECISION: u64> FromSql for FixedPoint32<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Decimal32(x) if *x == PRECISION as usize) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
+        match decimal_from_sql(type_, value, &Type::Decimal32(PRECISION as usize))? {
             Value::Decimal32(_, x) => Ok(Self(x)),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -52,19 +113,16 @@ impl<const PRECISION: u64> From<FixedPoint32<PRECISION>> for f64 {
 pub struct FixedPoint64<const PRECISION: u64>(pub i64);
 
 impl<const PRECISION: u64> ToSql for FixedPoint64<PRECISION> {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::Decimal64(PRECISION as usize, self.0))
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        rescale_to_hint(Value::Decimal64(PRECISION as usize, self.0), type_hint)
     }
 }
 
 impl<const PRECISION: u64> FromSql for FixedPoint64<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Decimal64(x) if *x == PRECISION as usize) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
+        match decimal_from_sql(type_, value, &Type::Decimal64(PRECISION as usize))? {
             Value::Decimal64(_, x) => Ok(Self(x)),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -94,19 +152,16 @@ impl<const PRECISION: u64> From<FixedPoint64<PRECISION>> for f64 {
 pub struct FixedPoint128<const PRECISION: u64>(pub i128);
 
 impl<const PRECISION: u64> ToSql for FixedPoint128<PRECISION> {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::Decimal128(PRECISION as usize, self.0))
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        rescale_to_hint(Value::Decimal128(PRECISION as usize, self.0), type_hint)
     }
 }
 
 impl<const PRECISION: u64> FromSql for FixedPoint128<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Decimal128(x) if *x == PRECISION as usize) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
+        match decimal_from_sql(type_, value, &Type::Decimal128(PRECISION as usize))? {
             Value::Decimal128(_, x) => Ok(Self(x)),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -136,19 +191,56 @@ impl<const PRECISION: u64> From<FixedPoint128<PRECISION>> for f64 {
 pub struct FixedPoint256<const PRECISION: u64>(pub i256);
 
 impl<const PRECISION: u64> ToSql for FixedPoint256<PRECISION> {
-    fn to_sql(self, _type_hint: Option<&Type>) -> Result<Value> {
-        Ok(Value::Decimal256(PRECISION as usize, self.0))
+    fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+        rescale_to_hint(Value::Decimal256(PRECISION as usize, self.0), type_hint)
     }
 }
 
 impl<const PRECISION: u64> FromSql for FixedPoint256<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Decimal256(x) if *x == PRECISION as usize) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
+        match decimal_from_sql(type_, value, &Type::Decimal256(PRECISION as usize))? {
             Value::Decimal256(_, x) => Ok(Self(x)),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
+
+impl<const PRECISION: u64> fmt::Display for FixedPoint32<PRECISION> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fixed_point(
+            f,
+            self.0 < 0,
+            &self.0.unsigned_abs().to_string(),
+            PRECISION as usize,
+        )
+    }
+}
+
+impl<const PRECISION: u64> fmt::Display for FixedPoint64<PRECISION> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fixed_point(
+            f,
+            self.0 < 0,
+            &self.0.unsigned_abs().to_string(),
+            PRECISION as usize,
+        )
+    }
+}
+
+impl<const PRECISION: u64> fmt::Display for FixedPoint128<PRECISION> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_fixed_point(
+            f,
+            self.0 < 0,
+            &self.0.unsigned_abs().to_string(),
+            PRECISION as usize,
+        )
+    }
+}
+
+impl<const PRECISION: u64> fmt::Display for FixedPoint256<PRECISION> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (negative, magnitude) = self.0.sign_magnitude_digits();
+        write_fixed_point(f, negative, &magnitude, PRECISION as usize)
+    }
+}
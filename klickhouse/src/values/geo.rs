@@ -44,6 +44,18 @@ pub struct Polygon(pub Vec<Ring>);
 ///
 /// <https://clickhouse.com/docs/en/sql-reference/data-types/geo#multipolygon>
 pub struct MultiPolygon(pub Vec<Polygon>);
+#[derive(Clone, Hash, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Sequence of points forming a line, not closed unlike a [`Ring`].
+///
+/// <https://clickhouse.com/docs/en/sql-reference/data-types/geo#linestring>
+pub struct LineString(pub Vec<Point>);
+#[derive(Clone, Hash, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Union of line strings.
+///
+/// <https://clickhouse.com/docs/en/sql-reference/data-types/geo#multilinestring>
+pub struct MultiLineString(pub Vec<LineString>);
 
 macro_rules! to_from_sql {
     ($name:ident) => {
@@ -60,7 +72,7 @@ macro_rules! to_from_sql {
                 }
                 match value {
                     Value::$name(x) => Ok(x),
-                    _ => unimplemented!(),
+                    x => Err(unexpected_value(&x, type_)),
                 }
             }
         }
@@ -71,6 +83,8 @@ to_from_sql!(Point);
 to_from_sql!(Ring);
 to_from_sql!(Polygon);
 to_from_sql!(MultiPolygon);
+to_from_sql!(LineString);
+to_from_sql!(MultiLineString);
 #[cfg(feature = "geo-types")]
 mod nav_types_conversions {
     use super::*;
@@ -89,7 +103,7 @@ mod nav_types_conversions {
                     }
                     match value {
                         Value::$ch_t(x) => Ok(x.into()),
-                        _ => unimplemented!(),
+                        x => Err(unexpected_value(&x, type_)),
                     }
                 }
             }
@@ -124,9 +138,13 @@ mod nav_types_conversions {
     }
     to_from_sql!(geo_types::Point, Point);
     // Rings and Linestrings
+    // Rings of geo_types repeat their first point at the end, which ClickHouse rings may omit,
+    // so rings are closed when converted. Closed rings are kept as is the other way, as ClickHouse geo functions return them.
     impl From<Ring> for geo_types::LineString {
         fn from(source: Ring) -> Self {
-            Self(source.0.into_iter().map(geo_types::Coord::from).collect())
+            let mut line = Self(source.0.into_iter().map(geo_types::Coord::from).collect());
+            line.close();
+            line
         }
     }
     impl From<geo_types::LineString> for Ring {
@@ -134,7 +152,53 @@ mod nav_types_conversions {
             Self(source.0.into_iter().map(Point::from).collect())
         }
     }
-    to_from_sql!(geo_types::LineString, Ring);
+    impl From<LineString> for geo_types::LineString {
+        fn from(source: LineString) -> Self {
+            Self(source.0.into_iter().map(geo_types::Coord::from).collect())
+        }
+    }
+    impl From<geo_types::LineString> for LineString {
+        fn from(source: geo_types::LineString) -> Self {
+            Self(source.0.into_iter().map(Point::from).collect())
+        }
+    }
+    // A geo_types::LineString is stored as a `Ring` unless the column is a `LineString`.
+    impl ToSql for geo_types::LineString {
+        fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+            match type_hint.map(|x| x.strip_null().strip_low_cardinality()) {
+                Some(Type::LineString | Type::Geometry) => Ok(Value::LineString(self.into())),
+                _ => Ok(Value::Ring(self.into())),
+            }
+        }
+    }
+    impl FromSql for geo_types::LineString {
+        fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+            if !matches!(type_, Type::Ring | Type::LineString) {
+                return Err(unexpected_type(type_));
+            }
+            match value {
+                Value::Ring(x) => Ok(x.into()),
+                Value::LineString(x) => Ok(x.into()),
+                x => Err(unexpected_value(&x, type_)),
+            }
+        }
+    }
+    // Multi line strings
+    impl From<MultiLineString> for geo_types::MultiLineString {
+        fn from(source: MultiLineString) -> Self {
+            source
+                .0
+                .into_iter()
+                .map(geo_types::LineString::from)
+                .collect()
+        }
+    }
+    impl From<geo_types::MultiLineString> for MultiLineString {
+        fn from(source: geo_types::MultiLineString) -> Self {
+            Self(source.into_iter().map(LineString::from).collect())
+        }
+    }
+    to_from_sql!(geo_types::MultiLineString, MultiLineString);
     // Rings and polygons (with no holes)
     // A Polygon -> Ring conversion is not provided, as the polygon might have holes.
     impl From<Ring> for geo_types::Polygon {
@@ -187,6 +251,59 @@ mod nav_types_conversions {
         }
     }
     to_from_sql!(geo_types::MultiPolygon, MultiPolygon);
+    // Geometries, stored as the geo value of their kind. Lines, rectangles and triangles are stored as line strings and polygons.
+    impl ToSql for geo_types::Geometry {
+        fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+            Ok(match self {
+                geo_types::Geometry::Point(x) => Value::Point(x.into()),
+                geo_types::Geometry::Line(x) => {
+                    return geo_types::LineString::from(x).to_sql(type_hint)
+                }
+                geo_types::Geometry::LineString(x) => {
+                    match type_hint.map(|x| x.strip_null().strip_low_cardinality()) {
+                        Some(Type::Ring) => Value::Ring(x.into()),
+                        _ => Value::LineString(x.into()),
+                    }
+                }
+                geo_types::Geometry::Polygon(x) => Value::Polygon(x.into()),
+                geo_types::Geometry::MultiLineString(x) => Value::MultiLineString(x.into()),
+                geo_types::Geometry::MultiPolygon(x) => Value::MultiPolygon(x.into()),
+                geo_types::Geometry::Rect(x) => Value::Polygon(x.to_polygon().into()),
+                geo_types::Geometry::Triangle(x) => Value::Polygon(x.to_polygon().into()),
+                geo_types::Geometry::MultiPoint(_) | geo_types::Geometry::GeometryCollection(_) => {
+                    return Err(KlickhouseError::SerializeError(
+                        "MultiPoint and GeometryCollection have no ClickHouse geo type".to_string(),
+                    ))
+                }
+            })
+        }
+    }
+    /// Reads any geo column. A `Ring` is read as a polygon without holes.
+    impl FromSql for geo_types::Geometry {
+        fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+            if !matches!(
+                type_,
+                Type::Geometry
+                    | Type::Point
+                    | Type::Ring
+                    | Type::Polygon
+                    | Type::MultiPolygon
+                    | Type::LineString
+                    | Type::MultiLineString
+            ) {
+                return Err(unexpected_type(type_));
+            }
+            Ok(match value {
+                Value::Point(x) => geo_types::Geometry::Point(x.into()),
+                Value::Ring(x) => geo_types::Geometry::Polygon(x.into()),
+                Value::Polygon(x) => geo_types::Geometry::Polygon(x.into()),
+                Value::MultiPolygon(x) => geo_types::Geometry::MultiPolygon(x.into()),
+                Value::LineString(x) => geo_types::Geometry::LineString(x.into()),
+                Value::MultiLineString(x) => geo_types::Geometry::MultiLineString(x.into()),
+                x => return Err(unexpected_value(&x, type_)),
+            })
+        }
+    }
     #[cfg(test)]
     #[test]
     fn roundtrip() {
@@ -200,4 +317,56 @@ mod nav_types_conversions {
         let multipolygon_geo2 = geo_types::MultiPolygon::from(multipolygon);
         assert_eq!(multipolygon_geo, multipolygon_geo2);
     }
+    #[cfg(test)]
+    #[test]
+    fn ring_closing() {
+        let open = Ring(vec![
+            Point([0.0, 0.0]),
+            Point([1.0, 0.0]),
+            Point([1.0, 1.0]),
+        ]);
+        let line = geo_types::LineString::from(open.clone());
+        assert!(line.is_closed());
+        assert_eq!(line.0.len(), 4);
+        let closed = Ring::from(line.clone());
+        assert_eq!(closed.0.len(), 4);
+        assert_eq!(geo_types::LineString::from(closed), line);
+        assert!(geo_types::LineString::from(Ring(vec![])).0.is_empty());
+        // a LineString is left open
+        let line = geo_types::LineString::from(LineString(open.0));
+        assert!(!line.is_closed());
+    }
+    #[cfg(test)]
+    #[test]
+    fn geometry() {
+        let roundtrip = |geometry: geo_types::Geometry, type_: &Type| {
+            let value = geometry.to_sql(Some(type_)).unwrap();
+            type_.validate_value(&value).unwrap();
+            geo_types::Geometry::from_sql(type_, value).unwrap()
+        };
+        let polygon: geo_types::Geometry =
+            geo_types::wkt! { POLYGON ((0.0 0.0, 10.0 0.0, 10.0 10.0, 0.0 0.0)) }.into();
+        for geometry in [
+            geo_types::wkt! { POINT (1.0 2.0) }.into(),
+            geo_types::wkt! { LINESTRING (30.0 10.0, 10.0 30.0, 40.0 40.0) }.into(),
+            polygon.clone(),
+            geo_types::wkt! { MULTILINESTRING ((10.0 10.0, 20.0 20.0), (40.0 40.0, 30.0 30.0)) }
+                .into(),
+        ] {
+            assert_eq!(roundtrip(geometry.clone(), &Type::Geometry), geometry);
+        }
+        let ring = geo_types::wkt! { LINESTRING (0.0 0.0, 10.0 0.0, 10.0 10.0, 0.0 0.0) };
+        assert_eq!(roundtrip(ring.into(), &Type::Ring), polygon);
+        let rect = geo_types::Rect::new((0.0, 0.0), (1.0, 1.0));
+        assert_eq!(
+            roundtrip(rect.into(), &Type::Geometry),
+            rect.to_polygon().into()
+        );
+        assert!(
+            geo_types::Geometry::from(geo_types::wkt! { MULTIPOINT (1.0 2.0) })
+                .to_sql(None)
+                .is_err()
+        );
+        assert!(geo_types::Geometry::from_sql(&Type::Geometry, Value::Null).is_err());
+    }
 }
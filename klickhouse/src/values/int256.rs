@@ -1,7 +1,7 @@
 use std::fmt;
 
 use crate::{
-    convert::{unexpected_type, FromSql, ToSql},
+    convert::{unexpected_type, unexpected_value, FromSql, ToSql},
     types::Type,
     Result, Value,
 };
@@ -12,6 +12,52 @@ use crate::{
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct i256(pub [u8; 32]);
 
+impl i256 {
+    /// Returns whether this value is negative, and the decimal digits of its absolute value.
+    pub(crate) fn sign_magnitude_digits(&self) -> (bool, String) {
+        let (negative, magnitude) = self.sign_magnitude();
+        (negative, magnitude.decimal_digits())
+    }
+
+    /// Returns whether this value is negative, and its absolute value.
+    pub(crate) fn sign_magnitude(&self) -> (bool, u256) {
+        let negative = self.0[0] & 0x80 != 0;
+        let mut magnitude = self.0;
+        if negative {
+            // two's complement negation
+            let mut carry = true;
+            for byte in magnitude.iter_mut().rev() {
+                let (value, overflow) = (!*byte).overflowing_add(carry as u8);
+                *byte = value;
+                carry = overflow;
+            }
+        }
+        (negative, u256(magnitude))
+    }
+}
+
+impl u256 {
+    /// Returns the decimal digits of this value.
+    pub(crate) fn decimal_digits(&self) -> String {
+        let mut value = self.0;
+        let mut digits = vec![];
+        while value.iter().any(|x| *x != 0) {
+            let mut remainder = 0u16;
+            for byte in value.iter_mut() {
+                let current = (remainder << 8) | *byte as u16;
+                *byte = (current / 10) as u8;
+                remainder = current % 10;
+            }
+            digits.push(b'0' + remainder as u8);
+        }
+        if digits.is_empty() {
+            digits.push(b'0');
+        }
+        digits.reverse();
+        String::from_utf8(digits).unwrap()
+    }
+}
+
 impl From<i256> for u256 {
     fn from(i: i256) -> Self {
         u256(i.0)
@@ -31,7 +77,7 @@ impl FromSql for i256 {
         }
         match value {
             Value::Int256(x) => Ok(x),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -84,7 +130,7 @@ impl FromSql for u256 {
         }
         match value {
             Value::UInt256(x) => Ok(x),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
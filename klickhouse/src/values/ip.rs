@@ -92,7 +92,7 @@ impl FromSql for Ipv4 {
         }
         match value {
             Value::Ipv4(x) => Ok(x),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -110,7 +110,7 @@ impl FromSql for Ipv6 {
         }
         match value {
             Value::Ipv6(x) => Ok(x),
-            _ => unimplemented!(),
+            x => Err(unexpected_value(&x, type_)),
         }
     }
 }
@@ -0,0 +1,241 @@
+use super::*;
+use std::{
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+/// Defines a CIDR network type over an address type of `$bits` bits, represented as the integer type `$int`.
+macro_rules! ip_net {
+    ($(#[$doc:meta])* $name:ident, $addr:ty, $wrapper:ident, $bits:literal, $int:ty) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name {
+            addr: $addr,
+            prefix_len: u8,
+        }
+
+        impl $name {
+            /// The network of `addr` with a prefix of `prefix_len` bits, or `None` if the prefix is longer than the address.
+            /// Host bits of `addr` are kept, see [`Self::trunc`].
+            pub fn new(addr: $addr, prefix_len: u8) -> Option<Self> {
+                if prefix_len > $bits {
+                    return None;
+                }
+                Some(Self { addr, prefix_len })
+            }
+
+            /// The network spanning exactly the addresses from `first` to `last`,
+            /// i.e. a range returned by `IPv4CIDRToRange` or `IPv6CIDRToRange`. `None` if the range isn't a CIDR block.
+            pub fn from_range(first: $addr, last: $addr) -> Option<Self> {
+                let host_bits = (<$int>::from(first) ^ <$int>::from(last)).count_ones() as u8;
+                let net = Self::new(first, $bits - host_bits)?;
+                (net.network() == first && net.last() == last).then_some(net)
+            }
+
+            pub fn addr(&self) -> $addr {
+                self.addr
+            }
+
+            pub fn prefix_len(&self) -> u8 {
+                self.prefix_len
+            }
+
+            pub fn netmask(&self) -> $addr {
+                <$int>::MAX
+                    .checked_shl(($bits - self.prefix_len) as u32)
+                    .unwrap_or(0)
+                    .into()
+            }
+
+            /// The first address of the network.
+            pub fn network(&self) -> $addr {
+                (<$int>::from(self.addr) & <$int>::from(self.netmask())).into()
+            }
+
+            /// The last address of the network.
+            pub fn last(&self) -> $addr {
+                (<$int>::from(self.addr) | !<$int>::from(self.netmask())).into()
+            }
+
+            /// The first and last addresses of the network.
+            pub fn range(&self) -> ($addr, $addr) {
+                (self.network(), self.last())
+            }
+
+            /// This network with the host bits of its address cleared.
+            pub fn trunc(&self) -> Self {
+                Self {
+                    addr: self.network(),
+                    prefix_len: self.prefix_len,
+                }
+            }
+
+            pub fn contains(&self, addr: &$addr) -> bool {
+                <$int>::from(*addr) & <$int>::from(self.netmask()) == <$int>::from(self.network())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}/{}", self.addr, self.prefix_len)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = KlickhouseError;
+
+            fn from_str(s: &str) -> Result<Self> {
+                let invalid = || KlickhouseError::DeserializeError(format!("invalid CIDR network '{s}'"));
+                let (addr, prefix_len) = s.split_once('/').ok_or_else(invalid)?;
+                let addr = addr.parse().map_err(|_| invalid())?;
+                let prefix_len = prefix_len.parse().map_err(|_| invalid())?;
+                Self::new(addr, prefix_len).ok_or_else(invalid)
+            }
+        }
+
+        /// Sent as a tuple of its first and last addresses, like the result of `IPv4CIDRToRange` and `IPv6CIDRToRange`,
+        /// or as text in CIDR notation for `String` columns.
+        impl ToSql for $name {
+            fn to_sql(self, type_hint: Option<&Type>) -> Result<Value> {
+                match type_hint.map(|x| x.strip_null().strip_low_cardinality()) {
+                    None | Some(Type::Tuple(_)) => {
+                        let (first, last) = self.range();
+                        Ok(Value::Tuple(vec![
+                            Value::$wrapper($wrapper(first)),
+                            Value::$wrapper($wrapper(last)),
+                        ]))
+                    }
+                    Some(Type::String | Type::FixedString(_)) => Ok(Value::String(self.to_string().into_bytes())),
+                    Some(type_) => Err(KlickhouseError::SerializeError(format!(
+                        "cannot serialize {} to {type_}",
+                        stringify!($name),
+                    ))),
+                }
+            }
+        }
+
+        /// Reads a tuple of the first and last addresses of a CIDR block, or text in CIDR notation.
+        impl FromSql for $name {
+            fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+                match (type_, value) {
+                    (Type::Tuple(types), Value::Tuple(values))
+                        if matches!(&types[..], [Type::$wrapper, Type::$wrapper]) =>
+                    {
+                        match &values[..] {
+                            [Value::$wrapper(first), Value::$wrapper(last)] => {
+                                Self::from_range(first.0, last.0).ok_or_else(|| {
+                                    KlickhouseError::DeserializeError(format!(
+                                        "range from {first} to {last} is not a CIDR network"
+                                    ))
+                                })
+                            }
+                            _ => Err(unexpected_value(&Value::Tuple(values), type_)),
+                        }
+                    }
+                    (Type::String | Type::FixedString(_), Value::String(x)) => {
+                        String::from_utf8(x)?.trim_end_matches('\0').parse()
+                    }
+                    (Type::Tuple(_) | Type::String | Type::FixedString(_), x) => {
+                        Err(unexpected_value(&x, type_))
+                    }
+                    _ => Err(unexpected_type(type_)),
+                }
+            }
+        }
+    };
+}
+
+ip_net!(
+    /// An IPv4 network in CIDR notation, i.e. `10.0.0.0/8`.
+    Ipv4Net,
+    Ipv4Addr,
+    Ipv4,
+    32,
+    u32
+);
+
+ip_net!(
+    /// An IPv6 network in CIDR notation, i.e. `2001:db8::/32`.
+    Ipv6Net,
+    Ipv6Addr,
+    Ipv6,
+    128,
+    u128
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipv4_net() {
+        let net: Ipv4Net = "10.1.2.3/8".parse().unwrap();
+        assert_eq!(net.addr(), Ipv4Addr::new(10, 1, 2, 3));
+        assert_eq!(net.netmask(), Ipv4Addr::new(255, 0, 0, 0));
+        assert_eq!(
+            net.range(),
+            (Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(10, 255, 255, 255))
+        );
+        assert_eq!(net.trunc().to_string(), "10.0.0.0/8");
+        assert!(net.contains(&Ipv4Addr::new(10, 9, 9, 9)));
+        assert!(!net.contains(&Ipv4Addr::new(11, 0, 0, 0)));
+
+        let all = Ipv4Net::new(Ipv4Addr::UNSPECIFIED, 0).unwrap();
+        assert_eq!(all.last(), Ipv4Addr::BROADCAST);
+        assert!(Ipv4Net::new(Ipv4Addr::UNSPECIFIED, 33).is_none());
+        assert!("10.0.0.0".parse::<Ipv4Net>().is_err());
+        assert!("10.0.0.0/x".parse::<Ipv4Net>().is_err());
+
+        assert_eq!(
+            Ipv4Net::from_range(Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(10, 255, 255, 255)),
+            Some(net.trunc())
+        );
+        assert_eq!(
+            Ipv4Net::from_range(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 1)),
+            Some("10.0.0.1/32".parse().unwrap())
+        );
+        assert_eq!(
+            Ipv4Net::from_range(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ipv6_net() {
+        let net: Ipv6Net = "2001:db8::/32".parse().unwrap();
+        assert_eq!(
+            net.last(),
+            "2001:db8:ffff:ffff:ffff:ffff:ffff:ffff"
+                .parse::<Ipv6Addr>()
+                .unwrap()
+        );
+        assert!(net.contains(&"2001:db8::1".parse().unwrap()));
+        assert_eq!(Ipv6Net::from_range(net.network(), net.last()), Some(net));
+        assert!(Ipv6Net::new(Ipv6Addr::UNSPECIFIED, 129).is_none());
+    }
+
+    #[test]
+    fn test_ip_net_sql() {
+        let net: Ipv4Net = "192.168.0.0/16".parse().unwrap();
+        let range_type = Type::Tuple(vec![Type::Ipv4, Type::Ipv4]);
+        let value = net.to_sql(Some(&range_type)).unwrap();
+        assert_eq!(
+            value,
+            Value::Tuple(vec![
+                Value::Ipv4(Ipv4(Ipv4Addr::new(192, 168, 0, 0))),
+                Value::Ipv4(Ipv4(Ipv4Addr::new(192, 168, 255, 255))),
+            ])
+        );
+        assert_eq!(net.to_sql(None).unwrap(), value);
+        assert_eq!(Ipv4Net::from_sql(&range_type, value).unwrap(), net);
+
+        let text = net.to_sql(Some(&Type::String)).unwrap();
+        assert_eq!(text, Value::string("192.168.0.0/16"));
+        assert_eq!(Ipv4Net::from_sql(&Type::String, text).unwrap(), net);
+
+        assert!(net.to_sql(Some(&Type::UInt32)).is_err());
+        assert!(Ipv4Net::from_sql(&Type::UInt32, Value::UInt32(0)).is_err());
+        assert!(Ipv6Net::from_sql(&range_type, net.to_sql(None).unwrap()).is_err());
+    }
+}
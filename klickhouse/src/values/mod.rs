@@ -4,13 +4,15 @@ use chrono::{NaiveDate, SecondsFormat};
 use chrono_tz::Tz;
 
 use crate::{
-    convert::{unexpected_type, FromSql, ToSql},
+    convert::{unexpected_type, unexpected_value, FromSql, ToSql},
     types::Type,
-    Result,
+    KlickhouseError, Result,
 };
 
 mod bytes;
 mod clickhouse_uuid;
+mod coerce;
+mod compare;
 mod date;
 #[cfg(feature = "rust_decimal")]
 mod decimal;
@@ -18,13 +20,19 @@ mod fixed_point;
 mod geo;
 mod int256;
 mod ip;
+mod ip_net;
+mod query_param;
 
 pub use bytes::*;
+pub use compare::*;
 pub use date::*;
 pub use fixed_point::*;
 pub use geo::*;
 pub use int256::*;
 pub use ip::*;
+pub use ip_net::*;
+
+pub(crate) use fixed_point::write_fixed_point;
 
 #[cfg(test)]
 mod tests;
@@ -83,6 +91,11 @@ pub enum Value {
     Ring(Ring),
     Polygon(Polygon),
     MultiPolygon(MultiPolygon),
+    LineString(LineString),
+    MultiLineString(MultiLineString),
+
+    /// A value of a `Variant` column, with the index of its type in the `Variant`.
+    Variant(Box<Value>, u8),
 }
 
 impl PartialEq for Value {
@@ -122,6 +135,9 @@ impl PartialEq for Value {
             (Self::Ring(l0), Self::Ring(r0)) => l0 == r0,
             (Self::Polygon(l0), Self::Polygon(r0)) => l0 == r0,
             (Self::MultiPolygon(l0), Self::MultiPolygon(r0)) => l0 == r0,
+            (Self::LineString(l0), Self::LineString(r0)) => l0 == r0,
+            (Self::MultiLineString(l0), Self::MultiLineString(r0)) => l0 == r0,
+            (Self::Variant(l0, l1), Self::Variant(r0, r1)) => l0 == r0 && l1 == r1,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
@@ -183,6 +199,12 @@ impl Hash for Value {
             Value::Ring(x) => ::core::hash::Hash::hash(x, state),
             Value::Polygon(x) => ::core::hash::Hash::hash(x, state),
             Value::MultiPolygon(x) => ::core::hash::Hash::hash(x, state),
+            Value::LineString(x) => ::core::hash::Hash::hash(x, state),
+            Value::MultiLineString(x) => ::core::hash::Hash::hash(x, state),
+            Value::Variant(x, __self_1) => {
+                ::core::hash::Hash::hash(x, state);
+                ::core::hash::Hash::hash(__self_1, state)
+            }
 
             _ => {}
         }
@@ -196,34 +218,57 @@ impl Value {
         Value::String(value.into().into_bytes())
     }
 
-    pub(crate) fn index_value(&self) -> usize {
+    pub(crate) fn index_value(&self) -> Result<usize> {
         match self {
-            Value::UInt8(x) => *x as usize,
-            Value::UInt16(x) => *x as usize,
-            Value::UInt32(x) => *x as usize,
-            Value::UInt64(x) => *x as usize,
-            _ => unimplemented!(),
+            Value::UInt8(x) => Ok(*x as usize),
+            Value::UInt16(x) => Ok(*x as usize),
+            Value::UInt32(x) => Ok(*x as usize),
+            Value::UInt64(x) => usize::try_from(*x)
+                .map_err(|_| KlickhouseError::DeserializeError(format!("index out of range: {x}"))),
+            x => Err(unexpected_value(x, "an unsigned index")),
         }
     }
 
+    /// Panics if the value is not an array, see [`Value::try_unwrap_array_ref`].
     pub fn unwrap_array_ref(&self) -> &[Value] {
+        self.try_unwrap_array_ref().unwrap()
+    }
+
+    pub fn try_unwrap_array_ref(&self) -> Result<&[Value]> {
         match self {
-            Value::Array(a) => &a[..],
-            _ => unimplemented!(),
+            Value::Array(a) => Ok(&a[..]),
+            x => Err(unexpected_value(x, "Array")),
         }
     }
 
+    /// Panics if the value is not an array, see [`Value::try_unwrap_array`].
     pub fn unwrap_array(self) -> Vec<Value> {
+        self.try_unwrap_array().unwrap()
+    }
+
+    pub fn try_unwrap_array(self) -> Result<Vec<Value>> {
         match self {
-            Value::Array(a) => a,
-            _ => unimplemented!(),
+            Value::Array(a) => Ok(a),
+            x => Err(unexpected_value(&x, "Array")),
         }
     }
 
+    /// Panics if the value is not a tuple, see [`Value::try_unwrap_tuple`].
     pub fn unwrap_tuple(self) -> Vec<Value> {
+        self.try_unwrap_tuple().unwrap()
+    }
+
+    pub fn try_unwrap_tuple(self) -> Result<Vec<Value>> {
+        match self {
+            Value::Tuple(a) => Ok(a),
+            x => Err(unexpected_value(&x, "Tuple")),
+        }
+    }
+
+    pub(crate) fn try_unwrap_tuple_ref(&self) -> Result<&[Value]> {
         match self {
-            Value::Tuple(a) => a,
-            _ => unimplemented!(),
+            Value::Tuple(a) => Ok(&a[..]),
+            x => Err(unexpected_value(x, "Tuple")),
         }
     }
 
@@ -252,6 +297,57 @@ impl Value {
         value.to_sql(None)
     }
 
+    /// Approximate size of the value in a native block, i.e. to limit the size of insert blocks.
+    /// Offsets of arrays are counted, but null maps of `Nullable` columns are not.
+    pub(crate) fn encoded_size_hint(&self) -> usize {
+        let points = |x: &[Point]| 8 + x.len() * 16;
+        match self {
+            Value::Int8(_) | Value::UInt8(_) | Value::Enum8(_) | Value::Null => 1,
+            Value::Int16(_) | Value::UInt16(_) | Value::Enum16(_) | Value::Date(_) => 2,
+            Value::Int32(_)
+            | Value::UInt32(_)
+            | Value::Float32(_)
+            | Value::Decimal32(..)
+            | Value::DateTime(_)
+            | Value::Ipv4(_) => 4,
+            Value::Int64(_)
+            | Value::UInt64(_)
+            | Value::Float64(_)
+            | Value::Decimal64(..)
+            | Value::DateTime64(_) => 8,
+            Value::Int128(_)
+            | Value::UInt128(_)
+            | Value::Decimal128(..)
+            | Value::Uuid(_)
+            | Value::Ipv6(_)
+            | Value::Point(_) => 16,
+            Value::Int256(_) | Value::UInt256(_) | Value::Decimal256(..) => 32,
+            // length prefix of up to 2 bytes for most strings
+            Value::String(x) => x.len() + 2,
+            Value::Array(x) => 8 + x.iter().map(Value::encoded_size_hint).sum::<usize>(),
+            Value::Tuple(x) => x.iter().map(Value::encoded_size_hint).sum(),
+            Value::Variant(x, _) => 1 + x.encoded_size_hint(),
+            Value::Map(keys, values) => {
+                8 + keys
+                    .iter()
+                    .chain(values)
+                    .map(Value::encoded_size_hint)
+                    .sum::<usize>()
+            }
+            Value::Ring(x) => points(&x.0),
+            Value::LineString(x) => points(&x.0),
+            Value::Polygon(x) => 8 + x.0.iter().map(|x| points(&x.0)).sum::<usize>(),
+            Value::MultiLineString(x) => 8 + x.0.iter().map(|x| points(&x.0)).sum::<usize>(),
+            Value::MultiPolygon(x) => {
+                8 + x
+                    .0
+                    .iter()
+                    .map(|x| 8 + x.0.iter().map(|x| points(&x.0)).sum::<usize>())
+                    .sum::<usize>()
+            }
+        }
+    }
+
     /// Guesses a [`Type`] from the value, may not correspond to actual column type in Clickhouse
     pub fn guess_type(&self) -> Type {
         match self {
@@ -278,8 +374,9 @@ impl Value {
             Value::Date(_) => Type::Date,
             Value::DateTime(time) => Type::DateTime(time.0),
             Value::DateTime64(x) => Type::DateTime64(x.2, x.0),
-            Value::Enum8(_) => unimplemented!(),
-            Value::Enum16(_) => unimplemented!(),
+            // the enum names aren't known, but enums are accepted as their underlying integers
+            Value::Enum8(_) => Type::Int8,
+            Value::Enum16(_) => Type::Int16,
             Value::Array(x) => Type::Array(Box::new(
                 x.first().map(|x| x.guess_type()).unwrap_or(Type::String),
             )),
@@ -296,11 +393,14 @@ impl Value {
             Value::Ring(_) => Type::Ring,
             Value::Polygon(_) => Type::Polygon,
             Value::MultiPolygon(_) => Type::MultiPolygon,
+            Value::LineString(_) => Type::LineString,
+            Value::MultiLineString(_) => Type::MultiLineString,
+            Value::Variant(value, _) => value.guess_type(),
         }
     }
 }
 
-fn escape_string(f: &mut fmt::Formatter<'_>, from: impl AsRef<[u8]>) -> fmt::Result {
+fn escape_string(f: &mut impl fmt::Write, from: impl AsRef<[u8]>) -> fmt::Result {
     let from = from.as_ref();
     for byte in from.iter().copied() {
         if byte < 128 {
@@ -332,53 +432,79 @@ impl fmt::Debug for Value {
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_literal(f, &LiteralOptions::default())
+    }
+}
+
+/// Options controlling how a [`Value`] is rendered as a SQL literal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LiteralOptions {
+    /// Emit `Int64`/`UInt64` values as quoted strings with an explicit cast, i.e. `toUInt64('...')`.
+    /// Useful when queries pass through JSON-based middleware that would lose precision on large integers.
+    pub quote_64bit_integers: bool,
+}
+
+/// A [`Value`] rendered as a SQL literal with specific [`LiteralOptions`], see [`Value::literal`].
+pub struct ValueLiteral<'a> {
+    value: &'a Value,
+    options: LiteralOptions,
+}
+
+impl fmt::Display for ValueLiteral<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt_literal(f, &self.options)
+    }
+}
+
+impl Value {
+    /// Renders this value as a SQL literal with the given options. `Display` uses the default options.
+    pub fn literal(&self, options: LiteralOptions) -> ValueLiteral<'_> {
+        ValueLiteral {
+            value: self,
+            options,
+        }
+    }
+
+    fn fmt_literal(&self, f: &mut fmt::Formatter<'_>, options: &LiteralOptions) -> fmt::Result {
         match self {
             Value::Int8(x) => write!(f, "{x}"),
             Value::Int16(x) => write!(f, "{x}"),
             Value::Int32(x) => write!(f, "{x}"),
+            Value::Int64(x) if options.quote_64bit_integers => write!(f, "toInt64('{x}')"),
             Value::Int64(x) => write!(f, "{x}"),
             Value::Int128(x) => write!(f, "{x}::Int128"),
             Value::Int256(x) => write!(f, "{x}::Int256"),
             Value::UInt8(x) => write!(f, "{x}"),
             Value::UInt16(x) => write!(f, "{x}"),
             Value::UInt32(x) => write!(f, "{x}"),
+            Value::UInt64(x) if options.quote_64bit_integers => write!(f, "toUInt64('{x}')"),
             Value::UInt64(x) => write!(f, "{x}"),
             Value::UInt128(x) => write!(f, "{x}::UInt128"),
             Value::UInt256(x) => write!(f, "{x}::UInt256"),
+            Value::Float32(x) if !x.is_finite() => write_non_finite(f, *x as f64),
             Value::Float32(x) => write!(f, "{x}"),
+            Value::Float64(x) if !x.is_finite() => write_non_finite(f, *x),
             Value::Float64(x) => write!(f, "{x}"),
             Value::Decimal32(precision, value) => {
-                let raw_value = value.to_string();
-                if raw_value.len() < *precision {
-                    write!(f, "{raw_value}")
-                } else {
-                    let pre = &raw_value[..raw_value.len() - precision];
-                    let fraction = &raw_value[raw_value.len() - precision..];
-                    write!(f, "{pre}.{fraction}")
-                }
+                write!(f, "toDecimal32('")?;
+                write_fixed_point(f, *value < 0, &value.unsigned_abs().to_string(), *precision)?;
+                write!(f, "', {precision})")
             }
             Value::Decimal64(precision, value) => {
-                let raw_value = value.to_string();
-                if raw_value.len() < *precision {
-                    write!(f, "{raw_value}")
-                } else {
-                    let pre = &raw_value[..raw_value.len() - precision];
-                    let fraction = &raw_value[raw_value.len() - precision..];
-                    write!(f, "{pre}.{fraction}")
-                }
+                write!(f, "toDecimal64('")?;
+                write_fixed_point(f, *value < 0, &value.unsigned_abs().to_string(), *precision)?;
+                write!(f, "', {precision})")
             }
             Value::Decimal128(precision, value) => {
-                let raw_value = value.to_string();
-                if raw_value.len() < *precision {
-                    write!(f, "{raw_value}")
-                } else {
-                    let pre = &raw_value[..raw_value.len() - precision];
-                    let fraction = &raw_value[raw_value.len() - precision..];
-                    write!(f, "{pre}.{fraction}")
-                }
+                write!(f, "toDecimal128('")?;
+                write_fixed_point(f, *value < 0, &value.unsigned_abs().to_string(), *precision)?;
+                write!(f, "', {precision})")
             }
-            Value::Decimal256(..) => {
-                unimplemented!("Decimal256 display not implemented");
+            Value::Decimal256(precision, value) => {
+                let (negative, magnitude) = value.sign_magnitude_digits();
+                write!(f, "toDecimal256('")?;
+                write_fixed_point(f, negative, &magnitude, *precision)?;
+                write!(f, "', {precision})")
             }
             Value::String(string) => {
                 write!(f, "'")?;
@@ -390,7 +516,7 @@ impl fmt::Display for Value {
             }
             Value::Date(date) => {
                 let chrono_date: NaiveDate = (*date).into();
-                write!(f, "{}", chrono_date.format("makeDate(%Y,%m,%d)"))
+                write!(f, "{}", chrono_date.format("makeDate(%Y,%-m,%-d)"))
             }
             Value::DateTime(datetime) => {
                 let chrono_date: chrono::DateTime<Tz> =
@@ -414,20 +540,26 @@ impl fmt::Display for Value {
             Value::Array(array) => {
                 write!(f, "[")?;
                 if let Some(item) = array.first() {
-                    write!(f, "{}", item)?;
+                    item.fmt_literal(f, options)?;
                 }
                 for item in array.iter().skip(1) {
-                    write!(f, ",{}", item)?;
+                    write!(f, ",")?;
+                    item.fmt_literal(f, options)?;
                 }
                 write!(f, "]")
             }
             Value::Tuple(tuple) => {
+                // `(x)` is just `x` in parentheses
+                if tuple.len() == 1 {
+                    write!(f, "tuple")?;
+                }
                 write!(f, "(")?;
                 if let Some(item) = tuple.first() {
-                    write!(f, "{}", item)?;
+                    item.fmt_literal(f, options)?;
                 }
                 for item in tuple.iter().skip(1) {
-                    write!(f, ",{}", item)?;
+                    write!(f, ",")?;
+                    item.fmt_literal(f, options)?;
                 }
                 write!(f, ")")
             }
@@ -437,19 +569,56 @@ impl fmt::Display for Value {
                 write!(f, "{{")?;
                 let mut iter = keys.iter().zip(values.iter());
                 if let Some((key, value)) = iter.next() {
-                    write!(f, "{key}:{value}")?;
+                    key.fmt_literal(f, options)?;
+                    write!(f, ":")?;
+                    value.fmt_literal(f, options)?;
                 }
                 for (key, value) in iter {
-                    write!(f, ",{key}:{value}")?;
+                    write!(f, ",")?;
+                    key.fmt_literal(f, options)?;
+                    write!(f, ":")?;
+                    value.fmt_literal(f, options)?;
                 }
                 write!(f, "}}")
             }
             Value::Ipv4(ipv4) => write!(f, "'{ipv4}'"),
             Value::Ipv6(ipv6) => write!(f, "'{ipv6}'"),
-            Value::Point(x) => write!(f, "{:?}", x),
-            Value::Ring(x) => write!(f, "{:?}", x),
-            Value::Polygon(x) => write!(f, "{:?}", x),
-            Value::MultiPolygon(x) => write!(f, "{:?}", x),
+            // geo types are tuples of coordinates nested in arrays
+            Value::Point(_)
+            | Value::Ring(_)
+            | Value::Polygon(_)
+            | Value::MultiPolygon(_)
+            | Value::LineString(_)
+            | Value::MultiLineString(_) => geo_literal(self).fmt_literal(f, options),
+            // the server picks the variant of the value
+            Value::Variant(value, _) => value.fmt_literal(f, options),
         }
     }
 }
+
+/// Writes `nan`, `inf` or `-inf`, which Clickhouse reads as `Float64`.
+fn write_non_finite(f: &mut impl fmt::Write, x: f64) -> fmt::Result {
+    if x.is_nan() {
+        write!(f, "nan")
+    } else if x > 0.0 {
+        write!(f, "inf")
+    } else {
+        write!(f, "-inf")
+    }
+}
+
+/// A geo value as the tuples and arrays it is made of, i.e. a `Ring` as `Array(Tuple(Float64, Float64))`.
+fn geo_literal(value: &Value) -> Value {
+    let point = |x: &Point| Value::Tuple(vec![Value::Float64(x.0[0]), Value::Float64(x.0[1])]);
+    let points = |x: &[Point]| Value::Array(x.iter().map(point).collect());
+    let polygon = |x: &Polygon| Value::Array(x.0.iter().map(|x| points(&x.0)).collect());
+    match value {
+        Value::Point(x) => point(x),
+        Value::Ring(x) => points(&x.0),
+        Value::LineString(x) => points(&x.0),
+        Value::Polygon(x) => polygon(x),
+        Value::MultiLineString(x) => Value::Array(x.0.iter().map(|x| points(&x.0)).collect()),
+        Value::MultiPolygon(x) => Value::Array(x.0.iter().map(polygon).collect()),
+        x => x.clone(),
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt::Write;
+
+use chrono::NaiveDate;
+
+use super::{escape_string, geo_literal, write_fixed_point, write_non_finite};
+use crate::{KlickhouseError, Result, Type, Value};
+
+impl Value {
+    /// Renders this value of `type_` as a query parameter, in the text format the server parses parameters with:
+    /// escaped like a `TabSeparated` field, with the elements of arrays, tuples and maps quoted.
+    /// Dates with times are sent as Unix timestamps, so they don't depend on the server's time zone.
+    pub(crate) fn param_text(&self, type_: &Type) -> Result<String> {
+        let mut out = String::new();
+        self.write_param_text(&mut out, type_, false)?;
+        Ok(out)
+    }
+
+    fn write_param_text(&self, out: &mut String, type_: &Type, quoted: bool) -> Result<()> {
+        let text = |out: &mut String, text: &[u8]| {
+            if quoted {
+                out.push('\'');
+            }
+            escape_string(out, text).unwrap();
+            if quoted {
+                out.push('\'');
+            }
+        };
+        match (type_, self) {
+            (Type::LowCardinality(inner), _) => return self.write_param_text(out, inner, quoted),
+            (Type::Nullable(_) | Type::Variant(_) | Type::Geometry, Value::Null) => {
+                out.push_str(if quoted { "NULL" } else { "\\N" })
+            }
+            (Type::Nullable(inner), _) => return self.write_param_text(out, inner, quoted),
+            (Type::Geometry, _) => {
+                return self.write_param_text(out, &Type::geometry_variant(), quoted)
+            }
+            (Type::Variant(types), Value::Variant(value, index)) => {
+                let type_ = types.get(*index as usize).ok_or_else(|| {
+                    KlickhouseError::QueryArgumentError(format!(
+                        "variant index {index} out of range for {type_}"
+                    ))
+                })?;
+                return value.write_param_text(out, type_, quoted);
+            }
+            (Type::Variant(types), value) => {
+                let type_ = types
+                    .iter()
+                    .find(|x| x.validate_value(value).is_ok())
+                    .ok_or_else(|| {
+                        KlickhouseError::QueryArgumentError(format!(
+                            "value {value} is not any of {type_}"
+                        ))
+                    })?;
+                return value.write_param_text(out, type_, quoted);
+            }
+            (Type::Enum8(entries), Value::Enum8(index)) => {
+                match entries.iter().find(|x| x.1 == *index) {
+                    Some((name, _)) => text(out, name.as_bytes()),
+                    None => write!(out, "{index}").unwrap(),
+                }
+            }
+            (Type::Enum16(entries), Value::Enum16(index)) => {
+                match entries.iter().find(|x| x.1 == *index) {
+                    Some((name, _)) => text(out, name.as_bytes()),
+                    None => write!(out, "{index}").unwrap(),
+                }
+            }
+            (_, Value::Int8(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::Int16(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::Int32(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::Int64(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::Int128(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::Int256(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::UInt8(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::UInt16(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::UInt32(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::UInt64(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::UInt128(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::UInt256(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::Float32(x)) if !x.is_finite() => write_non_finite(out, *x as f64).unwrap(),
+            (_, Value::Float32(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::Float64(x)) if !x.is_finite() => write_non_finite(out, *x).unwrap(),
+            (_, Value::Float64(x)) => write!(out, "{x}").unwrap(),
+            (_, Value::Decimal32(scale, x)) => {
+                write_fixed_point(out, *x < 0, &x.unsigned_abs().to_string(), *scale).unwrap()
+            }
+            (_, Value::Decimal64(scale, x)) => {
+                write_fixed_point(out, *x < 0, &x.unsigned_abs().to_string(), *scale).unwrap()
+            }
+            (_, Value::Decimal128(scale, x)) => {
+                write_fixed_point(out, *x < 0, &x.unsigned_abs().to_string(), *scale).unwrap()
+            }
+            (_, Value::Decimal256(scale, x)) => {
+                let (negative, magnitude) = x.sign_magnitude_digits();
+                write_fixed_point(out, negative, &magnitude, *scale).unwrap()
+            }
+            (_, Value::String(bytes)) => text(out, bytes),
+            (Type::String | Type::FixedString(_), Value::Array(bytes)) => {
+                let bytes = bytes
+                    .iter()
+                    .map(|x| match x {
+                        Value::UInt8(x) => *x,
+                        Value::Int8(x) => *x as u8,
+                        _ => 0,
+                    })
+                    .collect::<Vec<_>>();
+                text(out, &bytes)
+            }
+            (_, Value::Uuid(x)) => text(out, x.to_string().as_bytes()),
+            (_, Value::Date(x)) => {
+                let date: NaiveDate = (*x).into();
+                text(out, date.format("%Y-%m-%d").to_string().as_bytes())
+            }
+            // the server only reads Unix timestamps of at least 5 digits, leading zeros are allowed
+            (_, Value::DateTime(x)) => text(out, format!("{:05}", x.1).as_bytes()),
+            (_, Value::DateTime64(x)) => {
+                let ticks = x.1 as i64;
+                let magnitude = format!("{:0width$}", ticks.unsigned_abs(), width = x.2 + 5);
+                let mut timestamp = String::new();
+                write_fixed_point(&mut timestamp, ticks < 0, &magnitude, x.2).unwrap();
+                text(out, timestamp.as_bytes())
+            }
+            (_, Value::Ipv4(x)) => text(out, x.to_string().as_bytes()),
+            (_, Value::Ipv6(x)) => text(out, x.to_string().as_bytes()),
+            (Type::Array(inner), Value::Array(items)) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    item.write_param_text(out, inner, true)?;
+                }
+                out.push(']');
+            }
+            (Type::Tuple(types), Value::Tuple(items)) if types.len() == items.len() => {
+                out.push('(');
+                for (i, (item, type_)) in items.iter().zip(types).enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    item.write_param_text(out, type_, true)?;
+                }
+                out.push(')');
+            }
+            (Type::Map(key_type, value_type), Value::Map(keys, values)) => {
+                out.push('{');
+                for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    key.write_param_text(out, key_type, true)?;
+                    out.push(':');
+                    value.write_param_text(out, value_type, true)?;
+                }
+                out.push('}');
+            }
+            (
+                Type::Point
+                | Type::Ring
+                | Type::LineString
+                | Type::Polygon
+                | Type::MultiLineString
+                | Type::MultiPolygon,
+                _,
+            ) => return geo_literal(self).write_param_text(out, &geo_type(type_), quoted),
+            _ => {
+                return Err(KlickhouseError::QueryArgumentError(format!(
+                    "value {self} of {type_} can't be sent as a query parameter"
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The arrays and tuples of `Float64` coordinates a geo type is an alias of.
+fn geo_type(type_: &Type) -> Type {
+    let point = Type::Tuple(vec![Type::Float64, Type::Float64]);
+    let array = |x| Type::Array(Box::new(x));
+    match type_ {
+        Type::Point => point,
+        Type::Ring | Type::LineString => array(point),
+        Type::Polygon | Type::MultiLineString => array(array(point)),
+        _ => array(array(array(point))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DateTime, DynDateTime64};
+
+    fn param(value: Value, type_: &str) -> String {
+        value.param_text(&type_.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_param_text() {
+        assert_eq!(param(Value::Int64(-5), "Int64"), "-5");
+        assert_eq!(param(Value::Float64(f64::NAN), "Float64"), "nan");
+        assert_eq!(param(Value::Decimal64(2, -1250), "Decimal64(2)"), "-12.50");
+        assert_eq!(
+            param(Value::string("a\tb\\c'\n"), "String"),
+            "a\\tb\\\\c\\'\\n"
+        );
+        assert_eq!(
+            param(Value::String(vec![0, 255]), "FixedString(2)"),
+            "\\0\\xFF"
+        );
+        assert_eq!(param(Value::Null, "Nullable(String)"), "\\N");
+        assert_eq!(
+            param(Value::Enum8(2), "Enum8('a' = 1, 'b\\'c' = 2)"),
+            "b\\'c"
+        );
+        assert_eq!(
+            param(Value::DateTime(DateTime(chrono_tz::UTC, 42)), "DateTime"),
+            "00042"
+        );
+        assert_eq!(
+            param(
+                Value::DateTime64(DynDateTime64(chrono_tz::UTC, -1500i64 as u64, 3)),
+                "DateTime64(3)"
+            ),
+            "-00001.500"
+        );
+        assert_eq!(
+            param(
+                Value::Array(vec![Value::string("x'"), Value::string("]")]),
+                "Array(String)"
+            ),
+            "['x\\'',']']"
+        );
+        assert_eq!(
+            param(
+                Value::Array(vec![Value::Null, Value::UInt8(1)]),
+                "Array(Nullable(UInt8))"
+            ),
+            "[NULL,1]"
+        );
+        assert_eq!(
+            param(
+                Value::Map(
+                    vec![Value::string("k")],
+                    vec![Value::Tuple(vec![
+                        Value::Date(crate::Date(1)),
+                        Value::Ipv4("10.0.0.1".parse::<std::net::Ipv4Addr>().unwrap().into()),
+                    ])]
+                ),
+                "Map(String, Tuple(Date, IPv4))"
+            ),
+            "{'k':('1970-01-02','10.0.0.1')}"
+        );
+        assert_eq!(
+            param(Value::Point(crate::Point([1.0, 2.5])), "Point"),
+            "(1,2.5)"
+        );
+        assert!(Value::Tuple(vec![])
+            .param_text(&"Array(UInt8)".parse().unwrap())
+            .is_err());
+    }
+}
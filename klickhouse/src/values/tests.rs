@@ -3,11 +3,12 @@ use indexmap::IndexMap;
 use uuid::Uuid;
 
 use crate::{
-    convert::{FromSql, ToSql},
+    convert::{Checked, FromSql, ToSql, UnitValue},
     i256,
     types::Type,
-    u256, Date, DateTime, DateTime64, FixedPoint128, FixedPoint256, FixedPoint32, FixedPoint64,
-    MultiPolygon, Point, Polygon, Ring,
+    u256, Date, DateTime, DateTime64, DynDateTime64, FixedPoint128, FixedPoint256, FixedPoint32,
+    FixedPoint64, FixedStringBytes, Ipv4, LineString, MaybeUtf8, MultiLineString, MultiPolygon,
+    Point, Polygon, Ring, Row,
 };
 
 use super::Value;
@@ -201,6 +202,48 @@ fn roundtrip_d256() {
     assert_eq!(fixed, roundtrip(fixed, &Type::Decimal256(3)));
 }
 
+#[test]
+fn test_decimal_display() {
+    assert_eq!(FixedPoint32::<5>(-5000).to_string(), "-0.05000");
+    assert_eq!(FixedPoint32::<5>(-5).to_string(), "-0.00005");
+    assert_eq!(FixedPoint32::<2>(12345).to_string(), "123.45");
+    assert_eq!(FixedPoint32::<2>(-12345).to_string(), "-123.45");
+    assert_eq!(FixedPoint32::<2>(0).to_string(), "0.00");
+    assert_eq!(FixedPoint32::<0>(-7).to_string(), "-7");
+    assert_eq!(FixedPoint64::<3>(1000).to_string(), "1.000");
+    assert_eq!(
+        FixedPoint64::<3>(i64::MIN).to_string(),
+        "-9223372036854775.808"
+    );
+    assert_eq!(FixedPoint128::<4>(5).to_string(), "0.0005");
+    assert_eq!(FixedPoint128::<4>(-99999).to_string(), "-9.9999");
+    assert_eq!(
+        FixedPoint256::<3>(i256::from((0u128, 12345u128))).to_string(),
+        "12.345"
+    );
+    assert_eq!(
+        FixedPoint256::<3>(i256::from((u128::MAX, u128::MAX - 4))).to_string(),
+        "-0.005"
+    );
+
+    assert_eq!(
+        Value::Decimal32(5, -5).to_string(),
+        "toDecimal32('-0.00005', 5)"
+    );
+    assert_eq!(
+        Value::Decimal64(2, 150).to_string(),
+        "toDecimal64('1.50', 2)"
+    );
+    assert_eq!(
+        Value::Decimal128(3, -1234).to_string(),
+        "toDecimal128('-1.234', 3)"
+    );
+    assert_eq!(
+        Value::Decimal256(2, i256::from((0u128, 7u128))).to_string(),
+        "toDecimal256('0.07', 2)"
+    );
+}
+
 #[test]
 fn roundtrip_string() {
     let fixed = "test".to_string();
@@ -216,8 +259,17 @@ fn roundtrip_fixed_string() {
     let fixed = "".to_string();
     assert_eq!(fixed, roundtrip(fixed.clone(), &Type::FixedString(32)));
     let fixed = "test".to_string();
-    // truncation happens at network layer serialization
+    // length is checked when inserting, not when converting
     assert_eq!(fixed, roundtrip(fixed.clone(), &Type::FixedString(3)));
+
+    let type_ = Type::FixedString(4);
+    let raw = FixedStringBytes::from_sql(&type_, Value::string("ab")).unwrap();
+    assert_eq!(raw.0, b"ab\0\0");
+    assert_eq!(
+        raw.to_sql(Some(&type_)).unwrap(),
+        Value::String(b"ab\0\0".to_vec())
+    );
+    assert!(FixedStringBytes::from_sql(&Type::String, Value::string("ab")).is_err());
 }
 
 #[test]
@@ -247,6 +299,32 @@ fn roundtrip_uuid() {
     assert_eq!(fixed, roundtrip(fixed, &Type::Uuid));
 }
 
+#[test]
+fn uuid_string_column() {
+    let fixed = Uuid::from_u128(0x1234_5678_9abc_def0_1234_5678_9abc_def0);
+    let value = fixed.to_sql(Some(&Type::String)).unwrap();
+    assert_eq!(value, Value::string("12345678-9abc-def0-1234-56789abcdef0"));
+    assert_eq!(fixed, value.to_value::<Uuid>(&Type::String).unwrap());
+    let value = fixed
+        .to_sql(Some(&Type::Nullable(Box::new(Type::FixedString(36)))))
+        .unwrap();
+    assert_eq!(
+        fixed,
+        value.to_value::<Uuid>(&Type::FixedString(36)).unwrap()
+    );
+    assert_eq!(Value::Uuid(fixed), fixed.to_sql(Some(&Type::Uuid)).unwrap());
+    // FixedString(16) holds the binary UUID, not its text
+    let value = fixed.to_sql(Some(&Type::FixedString(16))).unwrap();
+    assert_eq!(value, Value::String(fixed.as_bytes().to_vec()));
+    assert_eq!(
+        fixed,
+        value.to_value::<Uuid>(&Type::FixedString(16)).unwrap()
+    );
+    assert!(Value::string("not a uuid")
+        .to_value::<Uuid>(&Type::String)
+        .is_err());
+}
+
 #[test]
 fn roundtrip_date() {
     let fixed = Date(0);
@@ -496,6 +574,32 @@ fn test_escape() {
     );
 }
 
+#[test]
+fn test_literal_forms() {
+    assert_eq!(Value::Float64(f64::NAN).to_string(), "nan");
+    assert_eq!(Value::Float32(f32::INFINITY).to_string(), "inf");
+    assert_eq!(Value::Float64(f64::NEG_INFINITY).to_string(), "-inf");
+    assert_eq!(Value::Float64(-1.5).to_string(), "-1.5");
+    assert_eq!(Value::Date(Date(19944)).to_string(), "makeDate(2024,8,9)");
+    assert_eq!(Value::Tuple(vec![Value::UInt8(1)]).to_string(), "tuple(1)");
+    assert_eq!(
+        Value::Tuple(vec![Value::UInt8(1), Value::Null]).to_string(),
+        "(1,NULL)"
+    );
+    let point = Point([1.5, -2.0]);
+    assert_eq!(Value::Point(point.clone()).to_string(), "(1.5,-2)");
+    let ring = Ring(vec![point.clone(), Point([3.0, 4.0])]);
+    assert_eq!(Value::Ring(ring.clone()).to_string(), "[(1.5,-2),(3,4)]");
+    assert_eq!(
+        Value::MultiPolygon(MultiPolygon(vec![Polygon(vec![ring])])).to_string(),
+        "[[[(1.5,-2),(3,4)]]]"
+    );
+    assert_eq!(
+        Value::MultiLineString(MultiLineString(vec![LineString(vec![point])])).to_string(),
+        "[[(1.5,-2)]]"
+    );
+}
+
 #[tokio::test]
 async fn roundtrip_geo() {
     // Points
@@ -510,10 +614,442 @@ async fn roundtrip_geo() {
     // Multipolygon
     let multipolygon = MultiPolygon(vec![
         polygon.clone(),
-        Polygon(vec![ring.clone(), Ring(vec![point])]),
+        Polygon(vec![ring.clone(), Ring(vec![point.clone()])]),
     ]);
     assert_eq!(
         &multipolygon,
         &roundtrip(multipolygon.clone(), &Type::MultiPolygon)
     );
+    // LineString
+    let linestring = LineString(vec![point.clone(), Point([3.0, 4.0])]);
+    assert_eq!(
+        &linestring,
+        &roundtrip(linestring.clone(), &Type::LineString)
+    );
+    // MultiLineString
+    let multilinestring = MultiLineString(vec![linestring.clone(), LineString(vec![point])]);
+    assert_eq!(
+        &multilinestring,
+        &roundtrip(multilinestring.clone(), &Type::MultiLineString)
+    );
+}
+
+#[test]
+fn test_coerce_numbers() {
+    assert_eq!(
+        Value::Int8(5).coerce_to(&Type::Int64).unwrap(),
+        Value::Int64(5)
+    );
+    assert_eq!(
+        Value::string("42").coerce_to(&Type::UInt16).unwrap(),
+        Value::UInt16(42)
+    );
+    assert_eq!(
+        Value::string("-1.25")
+            .coerce_to(&Type::Decimal64(3))
+            .unwrap(),
+        Value::Decimal64(3, -1250)
+    );
+    assert_eq!(
+        Value::Decimal32(2, 300).coerce_to(&Type::Int32).unwrap(),
+        Value::Int32(3)
+    );
+    assert_eq!(
+        Value::Float64(2.5).coerce_to(&Type::Decimal32(1)).unwrap(),
+        Value::Decimal32(1, 25)
+    );
+    assert_eq!(
+        Value::string("true").coerce_to(&Type::UInt8).unwrap(),
+        Value::UInt8(1)
+    );
+    assert!(Value::Int32(300).coerce_to(&Type::UInt8).is_err());
+    assert!(Value::Float64(2.5).coerce_to(&Type::Int32).is_err());
+    assert!(Value::Decimal64(3, 1255)
+        .coerce_to(&Type::Decimal64(2))
+        .is_err());
+    assert!(Value::string("abc").coerce_to(&Type::Float64).is_err());
+}
+
+#[test]
+fn test_coerce_strings_and_temporal() {
+    assert_eq!(
+        Value::Decimal64(2, -5).coerce_to(&Type::String).unwrap(),
+        Value::string("-0.05")
+    );
+    assert!(Value::string("toolong")
+        .coerce_to(&Type::FixedString(3))
+        .is_err());
+    let uuid = Uuid::from_u128(0x1234_5678_9abc_def0_1234_5678_9abc_def0);
+    assert_eq!(
+        Value::string(uuid.to_string())
+            .coerce_to(&Type::Uuid)
+            .unwrap(),
+        Value::Uuid(uuid)
+    );
+    assert_eq!(
+        Value::string("10.0.0.1").coerce_to(&Type::Ipv4).unwrap(),
+        Value::Ipv4(Ipv4("10.0.0.1".parse().unwrap()))
+    );
+    let enum_type = Type::Enum8(vec![("a".to_string(), 1), ("b".to_string(), 2)]);
+    assert_eq!(
+        Value::string("b").coerce_to(&enum_type).unwrap(),
+        Value::Enum8(2)
+    );
+    assert!(Value::Int8(3).coerce_to(&enum_type).is_err());
+    assert_eq!(
+        Value::Enum8(1)
+            .coerce_from_to(&enum_type, &Type::String)
+            .unwrap(),
+        Value::string("a")
+    );
+
+    assert_eq!(
+        Value::string("1970-01-11").coerce_to(&Type::Date).unwrap(),
+        Value::Date(Date(10))
+    );
+    assert_eq!(
+        Value::string("1970-01-01 00:01:40")
+            .coerce_to(&Type::DateTime(chrono_tz::UTC))
+            .unwrap(),
+        Value::DateTime(DateTime(chrono_tz::UTC, 100))
+    );
+    assert_eq!(
+        Value::string("1970-01-01T01:00:01.5+01:00")
+            .coerce_to(&Type::DateTime64(3, chrono_tz::UTC))
+            .unwrap(),
+        Value::DateTime64(DynDateTime64(chrono_tz::UTC, 1500, 3))
+    );
+    assert_eq!(
+        Value::Date(Date(1))
+            .coerce_to(&Type::DateTime(chrono_tz::UTC))
+            .unwrap(),
+        Value::DateTime(DateTime(chrono_tz::UTC, 86400))
+    );
+    assert_eq!(
+        Value::DateTime64(DynDateTime64(chrono_tz::UTC, 86_400_123, 3))
+            .coerce_to(&Type::String)
+            .unwrap(),
+        Value::string("1970-01-02 00:00:00.123")
+    );
+}
+
+#[test]
+fn test_coerce_containers() {
+    assert_eq!(
+        Value::Array(vec![Value::string("1"), Value::Int8(2)])
+            .coerce_to(&Type::Array(Box::new(Type::Int64)))
+            .unwrap(),
+        Value::Array(vec![Value::Int64(1), Value::Int64(2)])
+    );
+    let nullable = Type::Nullable(Box::new(Type::Float32));
+    assert_eq!(Value::Null.coerce_to(&nullable).unwrap(), Value::Null);
+    assert_eq!(
+        Value::Int8(1).coerce_to(&nullable).unwrap(),
+        Value::Float32(1.0)
+    );
+    assert!(Value::Null.coerce_to(&Type::Float32).is_err());
+    assert!(Value::Tuple(vec![Value::Int8(1)])
+        .coerce_to(&Type::Tuple(vec![Type::Int8, Type::Int8]))
+        .is_err());
+    assert_eq!(
+        Value::Map(vec![Value::Int8(1)], vec![Value::string("2")])
+            .coerce_to(&Type::Map(Box::new(Type::String), Box::new(Type::UInt8)))
+            .unwrap(),
+        Value::Map(vec![Value::string("1")], vec![Value::UInt8(2)])
+    );
+}
+
+#[test]
+fn test_mismatched_from_sql() {
+    assert!(i32::from_sql(&Type::Int32, Value::string("1")).is_err());
+    assert!(String::from_sql(&Type::String, Value::Int32(1)).is_err());
+    assert!(Vec::<i32>::from_sql(&Type::Array(Box::new(Type::Int32)), Value::Int32(1)).is_err());
+    assert!(<(i32, i32)>::from_sql(
+        &Type::Tuple(vec![Type::Int32, Type::Int32]),
+        Value::Array(vec![])
+    )
+    .is_err());
+    assert!(Date::from_sql(&Type::Date, Value::UInt16(1)).is_err());
+    assert!(Value::Int32(1).try_unwrap_array().is_err());
+    assert!(Value::Int32(1).try_unwrap_tuple().is_err());
+    assert_eq!(
+        Value::Array(vec![Value::Int32(1)])
+            .try_unwrap_array()
+            .unwrap(),
+        vec![Value::Int32(1)]
+    );
+    assert!(Value::string("1").index_value().is_err());
+    assert_eq!(Value::UInt16(3).index_value().unwrap(), 3);
+}
+
+#[test]
+fn test_widening_from_sql() {
+    assert_eq!(u64::from_sql(&Type::UInt8, Value::UInt8(200)).unwrap(), 200);
+    assert_eq!(
+        i64::from_sql(&Type::UInt32, Value::UInt32(u32::MAX)).unwrap(),
+        u32::MAX as i64
+    );
+    assert_eq!(i16::from_sql(&Type::Int8, Value::Int8(-3)).unwrap(), -3);
+    assert_eq!(
+        f64::from_sql(&Type::Float32, Value::Float32(1.5)).unwrap(),
+        1.5
+    );
+    assert_eq!(f64::from_sql(&Type::Int32, Value::Int32(-7)).unwrap(), -7.0);
+    assert!(u64::from_sql(&Type::Int8, Value::Int8(1)).is_err());
+    assert!(i64::from_sql(&Type::UInt64, Value::UInt64(1)).is_err());
+    assert!(u8::from_sql(&Type::UInt16, Value::UInt16(1)).is_err());
+    assert!(f32::from_sql(&Type::Float64, Value::Float64(1.0)).is_err());
+    assert_eq!(
+        Option::<u32>::from_sql(&Type::Nullable(Box::new(Type::UInt16)), Value::UInt16(9)).unwrap(),
+        Some(9)
+    );
+}
+
+#[test]
+fn test_checked_from_sql() {
+    assert_eq!(
+        Checked::<u8>::from_sql(&Type::Int64, Value::Int64(255)).unwrap(),
+        Checked(255)
+    );
+    assert!(Checked::<u8>::from_sql(&Type::Int64, Value::Int64(256)).is_err());
+    assert!(Checked::<u32>::from_sql(&Type::Int32, Value::Int32(-1)).is_err());
+    assert_eq!(
+        Checked::<f32>::from_sql(&Type::Float64, Value::Float64(0.25)).unwrap(),
+        Checked(0.25)
+    );
+    assert!(Checked::<i32>::from_sql(&Type::Float64, Value::Float64(0.5)).is_err());
+    assert_eq!(
+        Checked(300i64).to_sql(Some(&Type::UInt16)).unwrap(),
+        Value::UInt16(300)
+    );
+    assert!(Checked(-1i64).to_sql(Some(&Type::UInt16)).is_err());
+}
+
+#[test]
+fn test_invalid_utf8() {
+    let invalid = vec![b'a', 0xff, b'b'];
+    assert!(String::from_sql(&Type::String, Value::String(invalid.clone())).is_err());
+    assert_eq!(
+        Vec::<u8>::from_sql(&Type::String, Value::String(invalid.clone())).unwrap(),
+        invalid
+    );
+    assert_eq!(
+        crate::helpers::utf8_lossy::from_sql(&Type::String, Value::String(invalid.clone()))
+            .unwrap(),
+        "a\u{fffd}b"
+    );
+    let passthrough = MaybeUtf8::from_sql(&Type::String, Value::String(invalid.clone())).unwrap();
+    assert_eq!(passthrough, MaybeUtf8::Bytes(invalid.clone()));
+    assert_eq!(passthrough.as_str(), None);
+    assert_eq!(passthrough.to_sql(None).unwrap(), Value::String(invalid));
+    assert_eq!(
+        MaybeUtf8::from_sql(&Type::FixedString(2), Value::string("ok")).unwrap(),
+        MaybeUtf8::Text("ok".to_string())
+    );
+}
+
+#[test]
+fn test_tuple_arity() {
+    let fixed = (
+        1u8, 2u8, 3u8, 4u8, 5u8, 6u8, 7u8, 8u8, 9u8, 10u8, 11u8, 12u8, 13u8, 14u8, 15u8, 16u16,
+    );
+    let mut types = vec![Type::UInt8; 15];
+    types.push(Type::UInt16);
+    let out = roundtrip(fixed, &Type::Tuple(types));
+    assert_eq!((out.0, out.7, out.15), (1, 8, 16));
+
+    let type_ = Type::Tuple(vec![Type::UInt32, Type::UInt32, Type::UInt32]);
+    assert_eq!([1u32, 2, 3], roundtrip([1u32, 2, 3], &type_));
+    assert_eq!(
+        <[u64; 2]>::from_sql(
+            &Type::Tuple(vec![Type::UInt8, Type::UInt32]),
+            Value::Tuple(vec![Value::UInt8(1), Value::UInt32(2)])
+        )
+        .unwrap(),
+        [1, 2]
+    );
+
+    let value = Value::Tuple(vec![Value::UInt32(1), Value::UInt32(2), Value::UInt32(3)]);
+    let error = <(u32, u32)>::from_sql(&type_, value.clone()).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Tuple of 3 elements into a tuple of 2"));
+    let error = <[u32; 4]>::from_sql(&type_, value).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Tuple of 3 elements into an array of 4"));
+}
+
+#[test]
+fn test_nullable_decimals() {
+    let type_ = Type::Nullable(Box::new(Type::Decimal64(4)));
+    let fixed = Some(FixedPoint64::<4>(12345));
+    assert_eq!(fixed, roundtrip(fixed, &type_));
+    assert_eq!(None, roundtrip(None::<FixedPoint64<4>>, &type_));
+
+    // rescaled to the column's scale on insert, and back on read
+    let value = Some(FixedPoint64::<2>(150)).to_sql(Some(&type_)).unwrap();
+    assert_eq!(value, Value::Decimal64(4, 15000));
+    type_.validate_value(&value).unwrap();
+    assert_eq!(
+        Option::<FixedPoint64<2>>::from_sql(&type_, value).unwrap(),
+        Some(FixedPoint64::<2>(150))
+    );
+    assert_eq!(
+        FixedPoint128::<6>::from_sql(&Type::Decimal32(2), Value::Decimal32(2, -5)).unwrap(),
+        FixedPoint128::<6>(-50000)
+    );
+    assert!(FixedPoint64::<1>::from_sql(&Type::Decimal64(2), Value::Decimal64(2, 15)).is_err());
+    assert!(FixedPoint64::<2>(1)
+        .to_sql(Some(&Type::Decimal64(1)))
+        .is_err());
+    assert!(FixedPoint64::<2>::from_sql(&Type::Int64, Value::Int64(1)).is_err());
+
+    let value = Value::from_value(fixed).unwrap();
+    assert_eq!(value.to_string(), "toDecimal64('1.2345', 4)");
+}
+
+#[test]
+fn test_unit_value_deserialize() {
+    let value = UnitValue::<u64>::deserialize_unit("x", &Type::UInt64, Value::UInt64(5)).unwrap();
+    assert_eq!(value.0, 5);
+    let value =
+        UnitValue::<u64>::deserialize_row(vec![("x", &Type::UInt64, Value::UInt64(5))]).unwrap();
+    assert_eq!(value.0, 5);
+    assert!(UnitValue::<u64>::deserialize_unit("x", &Type::String, Value::String(vec![])).is_err());
+}
+
+#[test]
+fn test_unit_value_serialize_into() {
+    let types = IndexMap::from([("id".to_string(), Type::UInt32)]);
+    let mut columns = crate::ColumnWriters::new(&types, 1);
+    UnitValue(5u32).serialize_into(&mut columns).unwrap();
+    assert_eq!(
+        columns.into_block().column_data["id"],
+        vec![Value::UInt32(5)]
+    );
+}
+
+#[test]
+fn test_value_sql_cmp() {
+    use std::cmp::Ordering;
+
+    use crate::CompareOp;
+
+    assert_eq!(
+        Value::UInt8(1).sql_cmp(&Value::Int64(-1)),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        Value::UInt64(u64::MAX).sql_cmp(&Value::Int8(-1)),
+        Some(Ordering::Greater)
+    );
+    let minus_one = Value::Int256(i256::from((u128::MAX, u128::MAX)));
+    let minus_two = Value::Int256(i256::from((u128::MAX, u128::MAX - 1)));
+    assert_eq!(minus_two.sql_cmp(&minus_one), Some(Ordering::Less));
+    assert_eq!(minus_one.sql_cmp(&Value::Int8(-1)), Some(Ordering::Equal));
+    assert_eq!(
+        Value::UInt256(u256::from((1, 0))).sql_cmp(&Value::UInt128(u128::MAX)),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        Value::Decimal64(2, 150).sql_cmp(&Value::Decimal32(1, 15)),
+        Some(Ordering::Equal)
+    );
+    assert_eq!(
+        Value::Decimal64(2, 150).sql_cmp(&Value::UInt8(2)),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        Value::Float64(1.5).sql_cmp(&Value::Decimal32(1, 15)),
+        Some(Ordering::Equal)
+    );
+    assert_eq!(Value::Float64(f64::NAN).sql_cmp(&Value::UInt8(1)), None);
+    assert_eq!(Value::string("1").sql_cmp(&Value::UInt8(1)), None);
+    assert_eq!(
+        Value::string("a").sql_cmp(&Value::string("b")),
+        Some(Ordering::Less)
+    );
+
+    let date = Value::Date(Date(19000));
+    let midnight = Value::DateTime(DateTime(UTC, 19000 * 86400));
+    let midnight_kyiv =
+        Value::DateTime(DateTime(chrono_tz::Europe::Kyiv, 19000 * 86400 - 2 * 3600));
+    assert_eq!(date.sql_cmp(&midnight), Some(Ordering::Equal));
+    assert_eq!(date.sql_cmp(&midnight_kyiv), Some(Ordering::Equal));
+    assert_eq!(midnight.sql_cmp(&midnight_kyiv), Some(Ordering::Greater));
+    assert_eq!(
+        Value::DateTime64(DynDateTime64(UTC, 19000 * 86400 * 1000 + 1, 3)).sql_cmp(&date),
+        Some(Ordering::Greater)
+    );
+
+    assert_eq!(
+        Value::Array(vec![Value::UInt8(1), Value::UInt8(2)])
+            .sql_cmp(&Value::Array(vec![Value::Int32(1)])),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        Value::Tuple(vec![Value::UInt8(1)])
+            .sql_cmp(&Value::Tuple(vec![Value::UInt8(1), Value::UInt8(2)])),
+        None
+    );
+    assert_eq!(Value::Null.sql_cmp(&Value::Null), None);
+
+    let one = Value::UInt8(1);
+    assert_eq!(
+        one.compare(CompareOp::LessOrEquals, &Value::Float32(1.0))
+            .unwrap(),
+        Some(true)
+    );
+    assert_eq!(one.compare(CompareOp::Equals, &Value::Null).unwrap(), None);
+    let nan = Value::Float64(f64::NAN);
+    assert_eq!(nan.compare(CompareOp::Equals, &nan).unwrap(), Some(false));
+    assert_eq!(nan.compare(CompareOp::NotEquals, &one).unwrap(), Some(true));
+    assert!(one.compare(CompareOp::Less, &Value::string("2")).is_err());
+    assert_eq!("<>".parse::<CompareOp>().unwrap(), CompareOp::NotEquals);
+    assert!("=>".parse::<CompareOp>().is_err());
+}
+
+#[test]
+fn test_value_partial_ord() {
+    use std::cmp::Ordering;
+
+    assert!(Value::UInt8(1) < Value::Int64(2));
+    // equal for Clickhouse, but not structurally, so ordered by type
+    assert_ne!(Value::UInt8(1), Value::Int32(1));
+    assert_eq!(
+        Value::UInt8(1).partial_cmp(&Value::Int32(1)),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        Value::Float64(-0.0).partial_cmp(&Value::Float64(0.0)),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        Value::Array(vec![Value::Float64(0.0)])
+            .partial_cmp(&Value::Array(vec![Value::Float64(-0.0)])),
+        Some(Ordering::Greater)
+    );
+    let nan = Value::Float64(f64::NAN);
+    assert_eq!(nan.partial_cmp(&nan), Some(Ordering::Equal));
+    assert_eq!(nan.partial_cmp(&Value::Float64(1.0)), None);
+    assert_eq!(Value::Null.partial_cmp(&Value::Null), Some(Ordering::Equal));
+    assert_eq!(Value::Null.partial_cmp(&Value::UInt8(1)), None);
+
+    let mut values = vec![
+        Value::Int64(3),
+        Value::Decimal32(1, 15),
+        Value::UInt8(1),
+        Value::Float32(-2.0),
+    ];
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(
+        values,
+        vec![
+            Value::Float32(-2.0),
+            Value::UInt8(1),
+            Value::Decimal32(1, 15),
+            Value::Int64(3)
+        ]
+    );
 }
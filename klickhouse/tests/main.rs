@@ -1,49 +1,79 @@
 pub mod test;
+pub mod test_async_insert;
+pub mod test_backfill;
+pub mod test_blob;
 pub mod test_bytes;
+pub mod test_client_identity;
+pub mod test_collapsing;
+pub mod test_column_writers;
+#[cfg(feature = "compression")]
+pub mod test_compression;
+pub mod test_container_default;
+pub mod test_datetime_overflow;
+pub mod test_ddl;
 pub mod test_decimal;
+pub mod test_deep_array;
+pub mod test_dictionary;
+pub mod test_duration;
+pub mod test_enum;
+pub mod test_events;
+#[cfg(feature = "serde")]
+pub mod test_explain;
+pub mod test_final;
 pub mod test_flatten;
 #[cfg(feature = "geo-types")]
 pub mod test_geo;
+pub mod test_input;
+pub mod test_insert;
+pub mod test_insert_rows;
+pub mod test_ip_net;
+#[cfg(feature = "serde")]
+pub mod test_json;
+pub mod test_literal_roundtrip;
 pub mod test_lock;
+pub mod test_naive_date;
 pub mod test_nested;
 pub mod test_ordering;
+pub mod test_parse;
+pub mod test_pool;
+pub mod test_query_audit;
+pub mod test_query_chunked;
+pub mod test_query_first_block;
+pub mod test_query_id;
+pub mod test_query_options;
+pub mod test_queue_priority;
 pub mod test_raw_string;
+pub mod test_row_accessor;
+pub mod test_schema_cache;
+pub mod test_script;
+#[cfg(feature = "serde_row")]
+pub mod test_serde_row;
 pub mod test_serialize;
+pub mod test_server_params;
+#[cfg(feature = "bb8")]
+pub mod test_sharded_insert;
+pub mod test_slow_query;
+pub mod test_temp_table;
+#[cfg(feature = "transactions")]
+pub mod test_transaction;
+pub mod test_unit_value;
+pub mod test_upsert;
+pub mod test_utf8_policy;
+pub mod test_variant;
+pub mod test_verify_schema;
 
-use klickhouse::{Client, ClientOptions};
+use klickhouse::{test_util, Client, ClientOptions};
 
 pub async fn get_client() -> Client {
-    let mut options = ClientOptions::default();
-
-    if let Ok(user) = std::env::var("KLICKHOUSE_TEST_USER") {
-        options.username = user;
-    }
-
-    if let Ok(password) = std::env::var("KLICKHOUSE_TEST_PASSWORD") {
-        options.password = password;
-    }
-
-    if let Ok(database) = std::env::var("KLICKHOUSE_TEST_DATABASE") {
-        options.default_database = database;
-    }
-
-    let address = std::env::var("KLICKHOUSE_TEST_ADDR").unwrap_or_else(|_| "127.0.0.1:9000".into());
+    test_util::get_client().await.unwrap()
+}
 
-    Client::connect(address, options).await.unwrap()
+pub async fn get_client_with_options(options: ClientOptions) -> Client {
+    test_util::get_client_with_options(options).await.unwrap()
 }
-/// Drop the table if it exists, and create it with the given structure.
-/// Make sure to use distinct table names across tests to avoid conflicts between tests executing
-/// simultaneously.
+
 pub async fn prepare_table(table_name: &str, table_struct: &str, client: &Client) {
-    client
-        .execute(format!("DROP TABLE IF EXISTS {}", table_name))
-        .await
-        .unwrap();
-    client
-        .execute(format!(
-            "CREATE TABLE {} ({}) ENGINE = Memory;",
-            table_name, table_struct
-        ))
+    test_util::prepare_table(table_name, table_struct, client)
         .await
         .unwrap();
 }
@@ -0,0 +1,62 @@
+use klickhouse::{AsyncInsertWait, UnitValue};
+
+use super::prepare_table;
+
+#[derive(klickhouse::Row, Debug, Clone, PartialEq)]
+struct Row {
+    id: u32,
+    name: String,
+}
+
+fn rows(ids: std::ops::Range<u32>) -> Vec<Row> {
+    ids.map(|id| Row {
+        id,
+        name: format!("row {id}"),
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn test_async_insert() {
+    let client = super::get_client().await;
+    prepare_table("test_async_insert", "id UInt32, name String", &client).await;
+
+    let result = client
+        .insert_async(
+            "INSERT INTO test_async_insert FORMAT Native",
+            rows(0..3),
+            AsyncInsertWait::Flushed,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows, 3);
+    // flushed rows are in the table once the insert returns
+    let count = client
+        .query_one::<UnitValue<u64>>("SELECT count() FROM test_async_insert")
+        .await
+        .unwrap();
+    assert_eq!(count.0, 3);
+
+    for (range, wait) in [
+        (3..5, AsyncInsertWait::Buffered),
+        (5..6, AsyncInsertWait::Sent),
+    ] {
+        client
+            .insert_async(
+                "INSERT INTO test_async_insert FORMAT Native",
+                rows(range),
+                wait,
+            )
+            .await
+            .unwrap();
+    }
+    client
+        .execute("SYSTEM FLUSH ASYNC INSERT QUEUE")
+        .await
+        .unwrap();
+    let received = client
+        .query_collect::<Row>("SELECT * FROM test_async_insert ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(received, rows(0..6));
+}
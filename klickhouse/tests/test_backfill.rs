@@ -0,0 +1,47 @@
+use klickhouse::{Backfill, UnitValue};
+
+#[tokio::test]
+async fn test_backfill() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+    super::prepare_table("test_backfill", "id UInt64", &client).await;
+
+    let backfill = Backfill::by_id(
+        "INSERT INTO test_backfill SELECT number FROM numbers(1000) WHERE number >= $1 AND number < $2",
+        0,
+        1000,
+        300,
+    );
+    assert_eq!(backfill.len(), 4);
+
+    // simulate a run interrupted after the first two chunks
+    let mut completed = vec![];
+    backfill
+        .clone()
+        .resume_from(2)
+        .run(&client, |chunk| completed.push((*chunk.start, *chunk.end)))
+        .await
+        .unwrap();
+    assert_eq!(completed, vec![(600, 900), (900, 1000)]);
+
+    let mut completed = vec![];
+    let total = Backfill::by_id(
+        "INSERT INTO test_backfill SELECT number FROM numbers(1000) WHERE number >= $1 AND number < $2",
+        0,
+        600,
+        300,
+    )
+    .run(&client, |chunk| completed.push(chunk.index))
+    .await
+    .unwrap();
+    assert_eq!(completed, vec![0, 1]);
+    assert_eq!(total.new_written_rows, Some(600));
+
+    let count = client
+        .query_one::<UnitValue<u64>>("SELECT uniqExact(id) FROM test_backfill")
+        .await
+        .unwrap();
+    assert_eq!(count.0, 1000);
+}
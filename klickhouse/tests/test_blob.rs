@@ -0,0 +1,65 @@
+use futures_util::StreamExt;
+use klickhouse::{assemble_blobs, split_blob, BlobChunk};
+
+#[tokio::test]
+async fn test_blob() {
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_blob",
+        "blob_id String, seq UInt32, last Bool, data String",
+        &client,
+    )
+    .await;
+
+    let data = (0..=255u8).cycle().take(100_000).collect::<Vec<_>>();
+    let result = client
+        .insert_blob(
+            "INSERT INTO test_blob FORMAT Native",
+            "big",
+            std::io::Cursor::new(data.clone()),
+            16 * 1024,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows, 7);
+    assert_eq!(result.blocks, 7);
+    client
+        .insert_blob(
+            "INSERT INTO test_blob FORMAT Native",
+            "empty",
+            std::io::Cursor::new(vec![]),
+            16 * 1024,
+        )
+        .await
+        .unwrap();
+
+    let chunks = client
+        .query::<BlobChunk>("SELECT * FROM test_blob ORDER BY blob_id, seq")
+        .await
+        .unwrap();
+    let blobs = assemble_blobs(chunks)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<klickhouse::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        blobs,
+        [("big".to_string(), data), ("empty".to_string(), vec![])]
+    );
+
+    // an incomplete blob fails to assemble
+    let mut partial = split_blob("partial", b"abcdef", 2).unwrap();
+    partial.pop();
+    client
+        .insert_native_block("INSERT INTO test_blob FORMAT Native", partial)
+        .await
+        .unwrap();
+    let chunks = client
+        .query::<BlobChunk>("SELECT * FROM test_blob WHERE blob_id = 'partial' ORDER BY seq")
+        .await
+        .unwrap();
+    let blobs = assemble_blobs(chunks).collect::<Vec<_>>().await;
+    assert!(blobs[0].is_err());
+}
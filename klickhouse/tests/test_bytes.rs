@@ -1,4 +1,4 @@
-use klickhouse::{Bytes, RawRow};
+use klickhouse::{Bytes, ClientOptions, FixedStringBytes, FixedStringOverflow, RawRow};
 
 #[derive(klickhouse::Row, Debug, Default, PartialEq, Clone)]
 pub struct Row {
@@ -18,7 +18,12 @@ async fn test_client() {
     let _ = env_logger::builder()
         .filter_level(log::LevelFilter::Info)
         .try_init();
-    let client = super::get_client().await;
+    // raw_bytes2 doesn't fit in raw_bytes_fixed2
+    let client = super::get_client_with_options(ClientOptions {
+        fixed_string_overflow: FixedStringOverflow::Truncate,
+        ..Default::default()
+    })
+    .await;
 
     super::prepare_table(
         "test_bytes",
@@ -74,4 +79,30 @@ async fn test_client() {
         .await
         .unwrap();
     println!("{:?}", items2);
+
+    let fixed = client
+        .query_one::<klickhouse::UnitValue<FixedStringBytes>>(
+            "SELECT raw_bytes_fixed FROM test_bytes LIMIT 1",
+        )
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(fixed.0, [&raw_bytes[..], &[0, 0]].concat());
+
+    let strict = super::get_client().await;
+    assert!(strict
+        .insert_native_block("INSERT INTO test_bytes FORMAT Native", items)
+        .await
+        .is_err());
+
+    // the failed insert is cancelled, so the connection is free for the next query
+    let count = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        strict.query_one::<klickhouse::UnitValue<u64>>("SELECT count() FROM test_bytes"),
+    )
+    .await
+    .expect("query after failed insert timed out")
+    .unwrap()
+    .0;
+    assert_eq!(count, 2);
 }
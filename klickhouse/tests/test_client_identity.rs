@@ -0,0 +1,31 @@
+use klickhouse::{ClientOptions, Row};
+
+#[derive(Row, Debug)]
+struct Identity {
+    client_name: String,
+    client_version_major: u64,
+    client_version_minor: u64,
+}
+
+#[tokio::test]
+async fn test_client_identity() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client_with_options(ClientOptions {
+        client_name: "ingest-worker".to_string(),
+        client_version: (3, 14, 0),
+        ..Default::default()
+    })
+    .await;
+
+    let identity = client
+        .query_one::<Identity>(
+            "SELECT client_name, client_version_major, client_version_minor FROM system.processes WHERE query_id = queryID()",
+        )
+        .await
+        .unwrap();
+    assert_eq!(identity.client_name, "ingest-worker");
+    assert_eq!(identity.client_version_major, 3);
+    assert_eq!(identity.client_version_minor, 14);
+}
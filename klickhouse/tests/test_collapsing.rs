@@ -0,0 +1,76 @@
+use klickhouse::{collapsing_changes, CollapsingRow, QueryOptions, Row};
+
+#[derive(Row, Debug, Clone, PartialEq)]
+struct PageViews {
+    page: String,
+    views: u64,
+    #[klickhouse(sign)]
+    sign: i8,
+}
+
+fn page_views(page: &str, views: u64) -> PageViews {
+    PageViews {
+        page: page.to_string(),
+        views,
+        sign: 0,
+    }
+}
+
+#[test]
+fn test_collapsing_changes() {
+    let rows = collapsing_changes([
+        (None, Some(page_views("a", 1))),
+        (Some(page_views("b", 2)), Some(page_views("b", 3))),
+        (Some(page_views("c", 4)), None),
+    ]);
+    assert_eq!(
+        rows.iter()
+            .map(|x| (&*x.page, x.views, x.sign))
+            .collect::<Vec<_>>(),
+        vec![("a", 1, 1), ("b", 2, -1), ("b", 3, 1), ("c", 4, -1)]
+    );
+}
+
+#[tokio::test]
+async fn test_collapsing() {
+    let client = super::get_client().await;
+
+    client
+        .execute("DROP TABLE IF EXISTS test_collapsing")
+        .await
+        .unwrap();
+    client
+        .execute(
+            "CREATE TABLE test_collapsing (page String, views UInt64, sign Int8) \
+            ENGINE = CollapsingMergeTree(sign) ORDER BY page",
+        )
+        .await
+        .unwrap();
+
+    client
+        .insert_native_block(
+            "INSERT INTO test_collapsing FORMAT native",
+            vec![page_views("a", 1).state(), page_views("b", 1).state()],
+        )
+        .await
+        .unwrap();
+    client
+        .insert_native_block(
+            "INSERT INTO test_collapsing FORMAT native",
+            PageViews::change(Some(page_views("a", 1)), Some(page_views("a", 2))),
+        )
+        .await
+        .unwrap();
+
+    let rows = client
+        .query_collect_with_options::<PageViews>(
+            "SELECT * FROM test_collapsing ORDER BY page",
+            QueryOptions::new().final_(true),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![page_views("a", 2).state(), page_views("b", 1).state()]
+    );
+}
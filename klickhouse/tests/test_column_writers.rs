@@ -0,0 +1,76 @@
+use klickhouse::{ColumnWriters, IndexMap, Row, Type, Value};
+
+#[derive(Row, Debug, Clone)]
+struct Inner {
+    b: u8,
+}
+
+#[derive(Row, Debug, Clone)]
+struct Outer {
+    #[klickhouse(rename = "id")]
+    key: u32,
+    #[klickhouse(skip)]
+    _ignored: u8,
+    #[klickhouse(flatten)]
+    inner: Inner,
+    name: Option<String>,
+}
+
+#[test]
+fn test_serialize_into_derive() {
+    let types = IndexMap::from([
+        ("id".to_string(), Type::UInt32),
+        ("b".to_string(), Type::UInt8),
+        ("name".to_string(), Type::Nullable(Box::new(Type::String))),
+    ]);
+    let rows = vec![
+        Outer {
+            key: 1,
+            _ignored: 0,
+            inner: Inner { b: 2 },
+            name: None,
+        },
+        Outer {
+            key: 3,
+            _ignored: 0,
+            inner: Inner { b: 4 },
+            name: Some("x".to_string()),
+        },
+    ];
+    let mut columns = ColumnWriters::new(&types, rows.len());
+    for row in rows.clone() {
+        row.serialize_into(&mut columns).unwrap();
+    }
+    let block = columns.into_block();
+    assert_eq!(
+        block.column_data["id"],
+        vec![Value::UInt32(1), Value::UInt32(3)]
+    );
+    assert_eq!(
+        block.column_data["b"],
+        vec![Value::UInt8(2), Value::UInt8(4)]
+    );
+    assert_eq!(
+        block.column_data["name"],
+        vec![Value::Null, Value::String(b"x".to_vec())]
+    );
+
+    // same values as the row-major path
+    let row = rows[1].clone().serialize_row(&types).unwrap();
+    assert_eq!(
+        row.into_iter().map(|(_, value)| value).collect::<Vec<_>>(),
+        vec![
+            Value::UInt32(3),
+            Value::UInt8(4),
+            Value::String(b"x".to_vec())
+        ]
+    );
+
+    // values that don't fit their column are rejected as they are pushed
+    let types = IndexMap::from([("b".to_string(), Type::UInt8)]);
+    let mut columns = ColumnWriters::new(&types, 1);
+    assert!(columns.push("b", Value::UInt32(1)).is_err());
+    assert!(Inner { b: 1 }
+        .serialize_into(&mut ColumnWriters::new(&IndexMap::new(), 1))
+        .is_err());
+}
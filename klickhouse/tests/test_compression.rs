@@ -0,0 +1,44 @@
+use klickhouse::{ClientOptions, CompressionMethod, Row};
+
+#[derive(Row, Debug, Clone, PartialEq)]
+pub struct Payload {
+    id: u64,
+    text: String,
+}
+
+#[tokio::test]
+async fn test_compression() {
+    let rows = (0..10_000u64)
+        .map(|id| Payload {
+            id,
+            text: "compressible ".repeat(id as usize % 16),
+        })
+        .collect::<Vec<_>>();
+
+    for (compression, compression_threads) in [
+        (CompressionMethod::None, 1),
+        (CompressionMethod::LZ4, 1),
+        (CompressionMethod::LZ4, 4),
+    ] {
+        let client = super::get_client_with_options(ClientOptions {
+            compression,
+            compression_threads,
+            ..Default::default()
+        })
+        .await;
+        super::prepare_table("test_compression", "id UInt64, text String", &client).await;
+
+        client
+            .insert_native_block("INSERT INTO test_compression FORMAT native", rows.clone())
+            .await
+            .unwrap();
+        let received = client
+            .query_collect::<Payload>("SELECT * FROM test_compression ORDER BY id")
+            .await
+            .unwrap();
+        assert_eq!(
+            received, rows,
+            "{compression:?} on {compression_threads} threads"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use klickhouse::{KlickhouseError, Row, Type, Value};
+
+#[derive(Row, Debug, PartialEq)]
+#[klickhouse(default)]
+pub struct DefaultRow {
+    id: u32,
+    name: String,
+    #[klickhouse(flatten)]
+    sub: SubRow,
+    #[klickhouse(nested)]
+    items: Vec<Item>,
+}
+
+impl Default for DefaultRow {
+    fn default() -> Self {
+        Self {
+            id: 7,
+            name: "unnamed".to_string(),
+            sub: SubRow { a: 1, b: 2 },
+            items: vec![Item { x: 3 }],
+        }
+    }
+}
+
+#[derive(Row, Debug, Default, PartialEq)]
+pub struct SubRow {
+    a: u32,
+    b: u32,
+}
+
+#[derive(Row, Debug, Default, PartialEq)]
+pub struct Item {
+    x: u32,
+}
+
+#[derive(Row, Debug, PartialEq)]
+pub struct NoDefaultRow {
+    id: u32,
+    #[klickhouse(nested)]
+    items: Vec<Item>,
+}
+
+#[test]
+fn test_container_default() {
+    let row = DefaultRow::deserialize_row(vec![("id", &Type::UInt32, Value::UInt32(1))]).unwrap();
+    assert_eq!(
+        row,
+        DefaultRow {
+            id: 1,
+            ..Default::default()
+        }
+    );
+
+    let row = DefaultRow::deserialize_row(vec![
+        ("a", &Type::UInt32, Value::UInt32(10)),
+        ("b", &Type::UInt32, Value::UInt32(20)),
+        ("name", &Type::String, Value::string("x")),
+    ])
+    .unwrap();
+    assert_eq!(row.id, 7);
+    assert_eq!(row.name, "x");
+    assert_eq!(row.sub, SubRow { a: 10, b: 20 });
+    assert_eq!(row.items, vec![Item { x: 3 }]);
+
+    // partially present flattened fields fall back to the default as a whole
+    let row = DefaultRow::deserialize_row(vec![("a", &Type::UInt32, Value::UInt32(10))]).unwrap();
+    assert_eq!(row.sub, SubRow { a: 1, b: 2 });
+
+    let array = Type::Array(Box::new(Type::UInt32));
+    let row = DefaultRow::deserialize_row(vec![(
+        "items.x",
+        &array,
+        Value::Array(vec![Value::UInt32(5), Value::UInt32(6)]),
+    )])
+    .unwrap();
+    assert_eq!(row.items, vec![Item { x: 5 }, Item { x: 6 }]);
+}
+
+#[test]
+fn test_missing_nested_without_default() {
+    assert!(matches!(
+        NoDefaultRow::deserialize_row(vec![("id", &Type::UInt32, Value::UInt32(1))]),
+        Err(KlickhouseError::MissingField("items"))
+    ));
+}
+
+#[test]
+fn test_duplicate_column() {
+    assert!(matches!(
+        NoDefaultRow::deserialize_row(vec![
+            ("id", &Type::UInt32, Value::UInt32(1)),
+            ("id", &Type::UInt32, Value::UInt32(2)),
+        ]),
+        Err(KlickhouseError::DuplicateColumn { index: 1, .. })
+    ));
+}
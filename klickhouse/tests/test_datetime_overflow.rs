@@ -0,0 +1,78 @@
+use chrono::{TimeZone, Utc};
+use klickhouse::{DateTimeOverflow, DateTimeWithOverflow};
+
+use super::prepare_table;
+
+#[derive(klickhouse::Row, Debug, Clone, PartialEq)]
+struct Row {
+    id: u32,
+    #[klickhouse(datetime_overflow = "clamp")]
+    clamped: chrono::DateTime<Utc>,
+    #[klickhouse(datetime_overflow = "null")]
+    nulled: Option<chrono::DateTime<Utc>>,
+    precise: DateTimeWithOverflow<chrono::DateTime<Utc>>,
+}
+
+#[tokio::test]
+async fn test_datetime_overflow() {
+    let client = super::get_client().await;
+
+    prepare_table(
+        "test_datetime_overflow",
+        "id UInt32, clamped DateTime('UTC'), nulled Nullable(DateTime64(3, 'UTC')), precise DateTime64(9, 'UTC')",
+        &client,
+    )
+    .await;
+
+    let before = Utc.with_ymd_and_hms(1950, 1, 1, 0, 0, 0).unwrap();
+    let after = Utc.with_ymd_and_hms(2500, 1, 1, 0, 0, 0).unwrap();
+    let rows = vec![
+        Row {
+            id: 1,
+            clamped: before,
+            nulled: Some(after),
+            precise: DateTimeWithOverflow(after, DateTimeOverflow::Clamp),
+        },
+        Row {
+            id: 2,
+            clamped: after,
+            nulled: Some(before),
+            precise: DateTimeWithOverflow(before, DateTimeOverflow::Clamp),
+        },
+    ];
+    client
+        .insert_native_block("INSERT INTO test_datetime_overflow FORMAT Native", rows)
+        .await
+        .unwrap();
+
+    let received = client
+        .query_collect::<Row>("SELECT * FROM test_datetime_overflow ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(received[0].clamped.timestamp(), 0);
+    assert_eq!(received[1].clamped.timestamp(), u32::MAX as i64);
+    assert!(received.iter().all(|x| x.nulled.is_none()));
+    assert_eq!(
+        received[0].precise.0,
+        Utc.with_ymd_and_hms(2262, 4, 11, 23, 47, 16).unwrap()
+            + chrono::Duration::nanoseconds(854_775_807)
+    );
+    assert_eq!(received[1].precise.0.timestamp(), 0);
+
+    #[derive(klickhouse::Row)]
+    struct Strict {
+        id: u32,
+        clamped: chrono::DateTime<Utc>,
+    }
+    let e = client
+        .insert_native_block(
+            "INSERT INTO test_datetime_overflow (id, clamped) FORMAT Native",
+            vec![Strict {
+                id: 3,
+                clamped: before,
+            }],
+        )
+        .await
+        .unwrap_err();
+    assert!(e.to_string().contains("1950-01-01 00:00:00 UTC"), "{e}");
+}
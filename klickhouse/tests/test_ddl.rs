@@ -0,0 +1,46 @@
+use klickhouse::{ColumnDef, ColumnDefault, CreateTableBuilder, Type, Tz};
+
+#[tokio::test]
+async fn test_ddl() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    let columns = vec![
+        ColumnDef::new("ts", Type::DateTime(Tz::UTC))
+            .codec("Delta(4)")
+            .codec("ZSTD(1)"),
+        ColumnDef::new("value", Type::Float64)
+            .default(ColumnDefault::Default("0.".to_string()))
+            .comment("measured value")
+            .codec("Gorilla")
+            .ttl("ts + toIntervalDay(30)"),
+        ColumnDef::new("doubled", Type::Float64)
+            .default(ColumnDefault::Materialized("value * 2".to_string())),
+    ];
+    client
+        .execute("DROP TABLE IF EXISTS test_ddl")
+        .await
+        .unwrap();
+    client
+        .execute(
+            CreateTableBuilder::new("test_ddl")
+                .columns(columns.clone())
+                .order_by("ts")
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    let described = client.describe_columns("test_ddl").await.unwrap();
+    assert_eq!(described.len(), 3);
+    assert_eq!(described[0].codecs, columns[0].codecs);
+    assert_eq!(described[1].codecs, columns[1].codecs);
+    assert_eq!(described[1].comment, "measured value");
+    assert_eq!(described[1].ttl.as_deref(), Some("ts + toIntervalDay(30)"));
+    assert!(matches!(
+        described[2].default,
+        Some(ColumnDefault::Materialized(_))
+    ));
+}
@@ -0,0 +1,101 @@
+use klickhouse::Row;
+
+type Array5<T> = Vec<Vec<Vec<Vec<Vec<T>>>>>;
+
+#[derive(Row, Debug, Default, PartialEq)]
+struct DeepArrays {
+    id: u32,
+    n3: Vec<Vec<Vec<u32>>>,
+    n4: Vec<Vec<Vec<Vec<i64>>>>,
+    n5: Array5<u8>,
+    s3: Vec<Vec<Vec<String>>>,
+    s4: Vec<Vec<Vec<Vec<String>>>>,
+    s5: Array5<Option<String>>,
+}
+
+#[tokio::test]
+async fn test_deep_array() {
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_deep_array",
+        "id UInt32, \
+        n3 Array(Array(Array(UInt32))), \
+        n4 Array(Array(Array(Array(Int64)))), \
+        n5 Array(Array(Array(Array(Array(UInt8))))), \
+        s3 Array(Array(Array(String))), \
+        s4 Array(Array(Array(Array(String)))), \
+        s5 Array(Array(Array(Array(Array(Nullable(String))))))",
+        &client,
+    )
+    .await;
+
+    let rows = vec![
+        DeepArrays {
+            id: 1,
+            ..Default::default()
+        },
+        DeepArrays {
+            id: 2,
+            n3: vec![vec![vec![1, 2], vec![]], vec![], vec![vec![3]]],
+            n4: vec![vec![vec![vec![-1], vec![]], vec![vec![2, 3]]], vec![]],
+            n5: vec![vec![vec![vec![vec![1, 2, 3]], vec![]]], vec![vec![]]],
+            s3: vec![vec![vec!["a".to_string(), String::new()]], vec![vec![]]],
+            s4: vec![vec![
+                vec![vec!["b".to_string()]],
+                vec![vec![], vec!["c".to_string()]],
+            ]],
+            s5: vec![vec![vec![vec![vec![Some("d".to_string()), None]]]], vec![]],
+        },
+        DeepArrays {
+            id: 3,
+            n3: vec![vec![vec![4, 5, 6]]],
+            n4: vec![vec![vec![vec![i64::MAX, i64::MIN]]]],
+            n5: vec![vec![vec![vec![vec![], vec![255]]]]],
+            s3: vec![vec![vec!["it's".to_string()], vec!["[x]".to_string()]]],
+            s4: vec![],
+            s5: vec![vec![vec![vec![vec![None]]]]],
+        },
+    ];
+    client
+        .insert_native_block("INSERT INTO test_deep_array FORMAT native", rows)
+        .await
+        .unwrap();
+
+    let selected = client
+        .query_collect::<DeepArrays>("SELECT * FROM test_deep_array ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(selected.len(), 3);
+    assert_eq!(
+        selected[0],
+        DeepArrays {
+            id: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        selected[1].n3,
+        vec![vec![vec![1, 2], vec![]], vec![], vec![vec![3]]]
+    );
+    assert_eq!(
+        selected[1].s5,
+        vec![vec![vec![vec![vec![Some("d".to_string()), None]]]], vec![]]
+    );
+    assert_eq!(selected[2].n4, vec![vec![vec![vec![i64::MAX, i64::MIN]]]]);
+    assert_eq!(
+        selected[2].s3,
+        vec![vec![vec!["it's".to_string()], vec!["[x]".to_string()]]]
+    );
+
+    // the server's own deep arrays read back the same
+    let same = client
+        .query_collect::<DeepArrays>(
+            "SELECT * FROM test_deep_array WHERE n3 = [[[1, 2], []], [], [[3]]] \
+            AND s4 = [[[['b']], [[], ['c']]]] AND n5 = [[[[[1, 2, 3]], []]], [[]]]",
+        )
+        .await
+        .unwrap();
+    assert_eq!(same.len(), 1);
+    assert_eq!(same[0], selected[1]);
+}
@@ -0,0 +1,61 @@
+use klickhouse::DictionaryStatus;
+
+#[tokio::test]
+async fn test_dictionary() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_dictionary_source",
+        r"
+        id UInt64,
+        value String
+    ",
+        &client,
+    )
+    .await;
+    client
+        .execute("INSERT INTO test_dictionary_source VALUES (1, 'one')")
+        .await
+        .unwrap();
+    client
+        .execute("DROP DICTIONARY IF EXISTS test_dictionary")
+        .await
+        .unwrap();
+    client
+        .execute(
+            "CREATE DICTIONARY test_dictionary (id UInt64, value String) PRIMARY KEY id \
+            SOURCE(CLICKHOUSE(TABLE 'test_dictionary_source')) LIFETIME(0) LAYOUT(FLAT())",
+        )
+        .await
+        .unwrap();
+
+    client.reload_dictionary("test_dictionary").await.unwrap();
+    let info = client
+        .dictionary_status("test_dictionary")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.status, DictionaryStatus::Loaded);
+    assert_eq!(info.element_count, 1);
+    assert!(info.last_exception.is_none());
+
+    assert!(client
+        .list_dictionaries()
+        .await
+        .unwrap()
+        .iter()
+        .any(|x| x.name == "test_dictionary"));
+    assert!(client
+        .dictionary_status("test_dictionary_missing")
+        .await
+        .unwrap()
+        .is_none());
+
+    client
+        .execute("DROP DICTIONARY test_dictionary")
+        .await
+        .unwrap();
+}
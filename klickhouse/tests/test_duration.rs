@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq, Clone)]
+pub struct Row {
+    #[klickhouse(with = "klickhouse::helpers::duration_ms")]
+    elapsed_ms: Duration,
+    #[klickhouse(with = "klickhouse::helpers::duration_us")]
+    elapsed_us: Duration,
+    #[klickhouse(with = "klickhouse::helpers::duration_ns")]
+    offset_ns: chrono::Duration,
+}
+
+#[tokio::test]
+async fn test_duration() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_duration",
+        r"
+        elapsed_ms UInt64,
+        elapsed_us UInt64,
+        offset_ns Int64
+    ",
+        &client,
+    )
+    .await;
+
+    let items = vec![
+        Row {
+            elapsed_ms: Duration::from_millis(1500),
+            elapsed_us: Duration::from_micros(42),
+            offset_ns: -chrono::Duration::nanoseconds(7),
+        },
+        Row::default(),
+    ];
+
+    client
+        .insert_native_block("INSERT INTO test_duration FORMAT Native", items.clone())
+        .await
+        .unwrap();
+
+    let items2 = client
+        .query_collect::<Row>("SELECT * FROM test_duration")
+        .await
+        .unwrap();
+    assert_eq!(items, items2);
+}
@@ -0,0 +1,135 @@
+use klickhouse::{ClickhouseEnum, FromSql, Row, ToSql, Type, Value};
+
+#[derive(ClickhouseEnum, Debug, Clone, Copy, PartialEq)]
+#[klickhouse(rename_all = "snake_case")]
+enum Status {
+    Active,
+    SoftDeleted,
+    #[klickhouse(rename = "gone (purged)")]
+    Purged,
+}
+
+#[derive(ClickhouseEnum, Debug, Clone, Copy, PartialEq)]
+#[klickhouse(enum_string, rename_all = "lowercase")]
+enum Plan {
+    Free,
+    Pro,
+    #[klickhouse(rename = "enterprise (annual)")]
+    Enterprise,
+}
+
+#[derive(Row, Debug, PartialEq)]
+struct Account {
+    id: u32,
+    status: Status,
+    previous: Option<Status>,
+    kind: String,
+    plan: Plan,
+    plans: Vec<Plan>,
+}
+
+#[test]
+fn test_enum_conversions() {
+    let type_: Type = "Enum16('active' = 1, 'soft_deleted' = 300, 'gone (purged)' = -1)"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        Status::from_sql(&type_, Value::Enum16(300)).unwrap(),
+        Status::SoftDeleted
+    );
+    assert_eq!(
+        Status::Purged.to_sql(Some(&type_)).unwrap(),
+        Value::Enum16(-1)
+    );
+    assert!(Status::from_sql(&type_, Value::Enum16(2)).is_err());
+    assert!(Status::from_sql(&Type::String, Value::string("active")).is_err());
+
+    let other: Type = "Enum8('active' = 1)".parse().unwrap();
+    assert!(Status::Purged.to_sql(Some(&other)).is_err());
+    assert_eq!(String::from_sql(&other, Value::Enum8(1)).unwrap(), "active");
+    assert_eq!("active".to_sql(Some(&other)).unwrap(), Value::Enum8(1));
+    assert_eq!("active".to_sql(None).unwrap(), Value::string("active"));
+
+    let low_cardinality: Type = "LowCardinality(String)".parse().unwrap();
+    assert_eq!(
+        Plan::from_sql(&low_cardinality, Value::string("pro")).unwrap(),
+        Plan::Pro
+    );
+    assert_eq!(
+        Plan::Enterprise.to_sql(Some(&low_cardinality)).unwrap(),
+        Value::string("enterprise (annual)")
+    );
+    assert!(Plan::from_sql(&Type::String, Value::string("Pro")).is_err());
+    assert_eq!(
+        Plan::from_sql(
+            &Type::FixedString(8),
+            Value::String(b"pro\0\0\0\0\0".to_vec())
+        )
+        .unwrap(),
+        Plan::Pro
+    );
+    assert_eq!(
+        Plan::from_sql(&Type::FixedString(3), Value::string("pro")).unwrap(),
+        Plan::Pro
+    );
+    assert!(Plan::from_sql(&Type::String, Value::String(b"pro\0".to_vec())).is_err());
+    assert!(Plan::from_sql(&Type::UInt8, Value::UInt8(1)).is_err());
+    let enum_type: Type = "Enum8('free' = 0, 'pro' = 1)".parse().unwrap();
+    assert_eq!(
+        Plan::from_sql(&enum_type, Value::Enum8(1)).unwrap(),
+        Plan::Pro
+    );
+}
+
+#[tokio::test]
+async fn test_enum() {
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_enum",
+        "id UInt32, \
+        status Enum8('active' = 1, 'soft_deleted' = 2, 'gone (purged)' = -1), \
+        previous Nullable(Enum16('active' = 1000, 'soft_deleted' = 1001, 'gone (purged)' = 1002)), \
+        kind Enum8('it''s, (odd)' = 5), \
+        plan LowCardinality(String), \
+        plans Array(LowCardinality(String))",
+        &client,
+    )
+    .await;
+
+    let rows = vec![
+        Account {
+            id: 1,
+            status: Status::Active,
+            previous: None,
+            kind: "it's, (odd)".to_string(),
+            plan: Plan::Free,
+            plans: vec![],
+        },
+        Account {
+            id: 2,
+            status: Status::Purged,
+            previous: Some(Status::SoftDeleted),
+            kind: "it's, (odd)".to_string(),
+            plan: Plan::Enterprise,
+            plans: vec![Plan::Free, Plan::Pro],
+        },
+    ];
+    client
+        .insert_native_block("INSERT INTO test_enum FORMAT native", rows)
+        .await
+        .unwrap();
+
+    let rows = client
+        .query_collect::<Account>("SELECT * FROM test_enum ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(rows[0].status, Status::Active);
+    assert_eq!(rows[0].previous, None);
+    assert_eq!(rows[1].status, Status::Purged);
+    assert_eq!(rows[1].previous, Some(Status::SoftDeleted));
+    assert_eq!(rows[1].kind, "it's, (odd)");
+    assert_eq!(rows[0].plan, Plan::Free);
+    assert_eq!(rows[1].plan, Plan::Enterprise);
+    assert_eq!(rows[1].plans, [Plan::Free, Plan::Pro]);
+}
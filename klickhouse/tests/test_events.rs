@@ -0,0 +1,55 @@
+use klickhouse::{ClientEvent, ClientOptions};
+use tokio::sync::broadcast;
+
+#[tokio::test]
+async fn test_client_events() {
+    let (event_sender, mut events) = broadcast::channel(100);
+    let client = super::get_client_with_options(ClientOptions {
+        event_sender: Some(event_sender),
+        ..Default::default()
+    })
+    .await;
+
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        ClientEvent::Connected { .. }
+    ));
+    // the connection setup query
+    assert_eq!(
+        events.recv().await.unwrap(),
+        ClientEvent::QueryQueued { pending: 0 }
+    );
+    let ClientEvent::QueryStarted { id } = events.recv().await.unwrap() else {
+        panic!("expected QueryStarted");
+    };
+    let ClientEvent::QueryFinished {
+        id: finished_id,
+        error: None,
+        ..
+    } = events.recv().await.unwrap()
+    else {
+        panic!("expected successful QueryFinished");
+    };
+    assert_eq!(id, finished_id);
+
+    let mut subscribed = client.subscribe_events();
+    assert!(client.execute("SELECT missing_column").await.is_err());
+    assert_eq!(
+        subscribed.recv().await.unwrap(),
+        ClientEvent::QueryQueued { pending: 0 }
+    );
+    assert!(matches!(
+        subscribed.recv().await.unwrap(),
+        ClientEvent::QueryStarted { .. }
+    ));
+    assert!(matches!(
+        subscribed.recv().await.unwrap(),
+        ClientEvent::QueryFinished { error: Some(_), .. }
+    ));
+
+    drop(client);
+    assert_eq!(
+        subscribed.recv().await.unwrap(),
+        ClientEvent::Disconnected { error: None }
+    );
+}
@@ -0,0 +1,47 @@
+use klickhouse::{Explain, ExplainKind};
+
+#[tokio::test]
+async fn test_explain() {
+    let client = super::get_client().await;
+
+    client
+        .execute("DROP TABLE IF EXISTS test_explain")
+        .await
+        .unwrap();
+    client
+        .execute("CREATE TABLE test_explain (id UInt64, value String) ENGINE = MergeTree ORDER BY id SETTINGS index_granularity = 8")
+        .await
+        .unwrap();
+    client
+        .execute("INSERT INTO test_explain SELECT number, toString(number) FROM numbers(1000)")
+        .await
+        .unwrap();
+
+    let query = "SELECT value FROM test_explain WHERE id = 5";
+    let plan = client.explain_plan(query).await.unwrap();
+    let read = plan
+        .iter()
+        .flat_map(|x| x.walk())
+        .find(|x| x.node_type == "ReadFromMergeTree")
+        .expect("missing ReadFromMergeTree");
+    let primary_key = read
+        .indexes
+        .iter()
+        .find(|x| x.type_ == "PrimaryKey")
+        .expect("missing primary key index");
+    assert_eq!(primary_key.keys, vec!["id"]);
+    assert!(primary_key.is_selective());
+
+    let pipeline = client.explain_pipeline(query).await.unwrap();
+    assert!(pipeline.iter().any(|x| x.is_step));
+    assert!(pipeline.iter().any(|x| !x.is_step));
+
+    match client.explain(query, ExplainKind::Estimate).await.unwrap() {
+        Explain::Estimate(estimates) => {
+            assert_eq!(estimates.len(), 1);
+            assert_eq!(estimates[0].table, "test_explain");
+            assert!(estimates[0].rows < 1000);
+        }
+        x => panic!("unexpected explain output {x:?}"),
+    }
+}
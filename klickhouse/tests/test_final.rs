@@ -0,0 +1,59 @@
+use klickhouse::{QueryOptions, SelectBuilder, UnitValue};
+
+#[tokio::test]
+async fn test_final() {
+    let client = super::get_client().await;
+
+    client
+        .execute("DROP TABLE IF EXISTS test_final")
+        .await
+        .unwrap();
+    client
+        .execute(
+            "CREATE TABLE test_final (id UInt64, value String, version UInt64) \
+            ENGINE = ReplacingMergeTree(version) ORDER BY id",
+        )
+        .await
+        .unwrap();
+    // separate inserts create separate parts, which stay unmerged for the duration of the test
+    client
+        .execute("INSERT INTO test_final VALUES (1, 'old', 1)")
+        .await
+        .unwrap();
+    client
+        .execute("INSERT INTO test_final VALUES (1, 'new', 2)")
+        .await
+        .unwrap();
+    client.check_final("test_final").await.unwrap();
+
+    let query = SelectBuilder::new("test_final")
+        .select("value")
+        .final_(true)
+        .build()
+        .unwrap();
+    let values = client
+        .query_collect::<UnitValue<String>>(&query)
+        .await
+        .unwrap();
+    assert_eq!(
+        values.into_iter().map(|x| x.0).collect::<Vec<_>>(),
+        vec!["new"]
+    );
+
+    let values = client
+        .query_collect_with_options::<UnitValue<String>>(
+            "SELECT value FROM test_final",
+            QueryOptions::new()
+                .final_(true)
+                .final_within_partitions(true),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        values.into_iter().map(|x| x.0).collect::<Vec<_>>(),
+        vec!["new"]
+    );
+
+    super::prepare_table("test_final_memory", "id UInt64", &client).await;
+    assert!(client.check_final("test_final_memory").await.is_err());
+}
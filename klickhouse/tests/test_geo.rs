@@ -99,3 +99,107 @@ async fn test_client_wkt() {
         .unwrap();
     assert_eq!(row2, row);
 }
+
+#[derive(Clone, PartialEq, Debug, klickhouse::Row)]
+struct RowLineString {
+    linestring: geo_types::LineString,
+    multilinestring: geo_types::MultiLineString,
+}
+
+#[cfg(feature = "geo-types")]
+#[tokio::test]
+async fn test_client_linestring() {
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_geo_linestring",
+        "linestring LineString, multilinestring MultiLineString",
+        &client,
+    )
+    .await;
+    let row = RowLineString {
+        linestring: geo_types::wkt! { LINESTRING (30.0 10.0, 10.0 30.0, 40.0 40.0) },
+        multilinestring: geo_types::wkt! {
+            MULTILINESTRING ((10.0 10.0, 20.0 20.0, 10.0 40.0),
+                             (40.0 40.0, 30.0 30.0, 40.0 20.0, 30.0 10.0))
+        },
+    };
+
+    client
+        .insert_native_block(
+            "INSERT INTO test_geo_linestring FORMAT Native",
+            vec![row.clone()],
+        )
+        .await
+        .unwrap();
+
+    let row2 = client
+        .query_one::<RowLineString>("SELECT * FROM test_geo_linestring")
+        .await
+        .unwrap();
+    assert_eq!(row2, row);
+}
+
+#[derive(Clone, PartialEq, Debug, klickhouse::Row)]
+struct RowGeometry {
+    id: u32,
+    geometry: Option<geo_types::Geometry>,
+}
+
+#[cfg(feature = "geo-types")]
+#[tokio::test]
+async fn test_client_geometry() {
+    let client = super::get_client().await;
+
+    client
+        .execute("DROP TABLE IF EXISTS test_geo_geometry")
+        .await
+        .unwrap();
+    client
+        .execute_with_options(
+            "CREATE TABLE test_geo_geometry (id UInt32, geometry Geometry) ENGINE = Memory",
+            // Geometry is a Variant, which needed enabling before it left its experimental stage
+            klickhouse::QueryOptions::new()
+                .optional_setting("allow_experimental_variant_type", 1)
+                .optional_setting("allow_suspicious_variant_types", 1),
+        )
+        .await
+        .unwrap();
+    let geometries: Vec<Option<geo_types::Geometry>> = vec![
+        Some(geo_types::wkt! { POINT (1.0 2.0) }.into()),
+        Some(geo_types::wkt! { LINESTRING (30.0 10.0, 10.0 30.0, 40.0 40.0) }.into()),
+        Some(geo_types::wkt! { POLYGON ((0.0 0.0, 10.0 0.0, 10.0 10.0, 0.0 0.0)) }.into()),
+        Some(
+            geo_types::wkt! { MULTILINESTRING ((10.0 10.0, 20.0 20.0), (40.0 40.0, 30.0 30.0)) }
+                .into(),
+        ),
+        None,
+    ];
+    let rows = geometries
+        .into_iter()
+        .enumerate()
+        .map(|(id, geometry)| RowGeometry {
+            id: id as u32,
+            geometry,
+        })
+        .collect::<Vec<_>>();
+
+    client
+        .insert_native_block("INSERT INTO test_geo_geometry FORMAT Native", rows.clone())
+        .await
+        .unwrap();
+
+    let rows2 = client
+        .query_collect::<RowGeometry>("SELECT * FROM test_geo_geometry ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(rows2, rows);
+
+    let kind = client
+        .query_one::<klickhouse::UnitValue<String>>(
+            "SELECT variantType(geometry) FROM test_geo_geometry WHERE id = 2",
+        )
+        .await
+        .unwrap();
+    assert_eq!(kind.0, "Polygon");
+}
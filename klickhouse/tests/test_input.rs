@@ -0,0 +1,82 @@
+use klickhouse::{ColumnDef, Row, Type};
+
+#[derive(Row, Debug, Clone)]
+pub struct RawVisit {
+    user_id: u64,
+    url: String,
+}
+
+#[derive(Row, Debug, PartialEq)]
+pub struct Visit {
+    user_id: u64,
+    domain: String,
+}
+
+#[tokio::test]
+async fn test_insert_input() {
+    let client = super::get_client().await;
+    super::prepare_table("test_input", "user_id UInt64, domain String", &client).await;
+
+    let visits = vec![
+        RawVisit {
+            user_id: 1,
+            url: "https://example.com/a?b".to_string(),
+        },
+        RawVisit {
+            user_id: 0,
+            url: "https://ignored.example.com/".to_string(),
+        },
+        RawVisit {
+            user_id: 2,
+            url: "http://clickhouse.com".to_string(),
+        },
+    ];
+    let result = client
+        .insert_input(
+            "INSERT INTO test_input SELECT user_id, domain(url) FROM input() WHERE user_id != 0",
+            futures_util::stream::iter([visits.clone()]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows, 3);
+
+    let rows = client
+        .query_collect::<Visit>("SELECT * FROM test_input ORDER BY user_id")
+        .await
+        .unwrap();
+    assert_eq!(
+        rows,
+        [
+            Visit {
+                user_id: 1,
+                domain: "example.com".to_string()
+            },
+            Visit {
+                user_id: 2,
+                domain: "clickhouse.com".to_string()
+            },
+        ]
+    );
+
+    let result = client
+        .insert_input_with_columns(
+            "INSERT INTO test_input SELECT user_id, domain(url) FROM input()",
+            vec![
+                ColumnDef::new("user_id", Type::UInt64),
+                ColumnDef::new("url", Type::LowCardinality(Box::new(Type::String))),
+            ],
+            futures_util::stream::iter([visits.clone()]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows, 3);
+
+    let missing = client
+        .insert_input_with_columns(
+            "INSERT INTO test_input SELECT user_id, '' FROM input()",
+            vec![ColumnDef::new("user_id", Type::UInt64)],
+            futures_util::stream::iter([visits]),
+        )
+        .await;
+    assert!(missing.is_err());
+}
@@ -0,0 +1,43 @@
+use super::prepare_table;
+
+/// Fields in a different order than the columns of the table.
+#[derive(klickhouse::Row, Debug, Clone, PartialEq)]
+struct Row {
+    name: String,
+    id: u32,
+}
+
+#[tokio::test]
+async fn test_insert() {
+    let client = super::get_client().await;
+    prepare_table(
+        "test_insert",
+        "id UInt32, extra UInt8 DEFAULT 7, name String",
+        &client,
+    )
+    .await;
+
+    let rows = vec![
+        Row {
+            name: "a".to_string(),
+            id: 1,
+        },
+        Row {
+            name: "b".to_string(),
+            id: 2,
+        },
+    ];
+    let result = client.insert("test_insert", rows.clone()).await.unwrap();
+    assert_eq!(result.rows, 2);
+
+    let received = client
+        .query_collect::<Row>("SELECT name, id FROM test_insert ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(received, rows);
+    let extra = client
+        .query_collect::<klickhouse::UnitValue<u8>>("SELECT extra FROM test_insert")
+        .await
+        .unwrap();
+    assert!(extra.iter().all(|x| x.0 == 7));
+}
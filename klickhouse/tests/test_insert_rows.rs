@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use klickhouse::{InsertBatching, InsertResult, Row, UnitValue};
+
+#[derive(Row, Debug, Default)]
+pub struct Reading {
+    sensor: u32,
+    value: f64,
+}
+
+#[tokio::test]
+async fn test_insert_rows() {
+    let client = super::get_client().await;
+    super::prepare_table("test_insert_rows", "sensor UInt32, value Float64", &client).await;
+
+    // a fast burst, then a slow trickle that is flushed by the delay rather than the batch size
+    let fast = futures_util::stream::iter(0..25u32);
+    let slow = futures_util::stream::iter(25..28u32).then(|sensor| async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        sensor
+    });
+    let rows = fast.chain(slow).map(|sensor| Reading {
+        sensor,
+        value: sensor as f64 / 2.0,
+    });
+    let result = client
+        .insert_native_rows(
+            "INSERT INTO test_insert_rows FORMAT native",
+            rows,
+            10,
+            Duration::from_millis(20),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows, 28);
+    assert!(result.blocks >= 4, "{result:?}");
+    assert!(result.bytes >= 28 * 12, "{result:?}");
+
+    let count = client
+        .query_one::<UnitValue<u64>>("SELECT count() FROM test_insert_rows")
+        .await
+        .unwrap();
+    assert_eq!(count.0, 28);
+
+    let result = client
+        .insert_native_rows(
+            "INSERT INTO test_insert_rows FORMAT native",
+            futures_util::stream::empty::<Reading>(),
+            10,
+            Duration::from_millis(20),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result, InsertResult::default());
+}
+
+#[tokio::test]
+async fn test_insert_rows_stream() {
+    let client = super::get_client().await;
+    super::prepare_table(
+        "test_insert_rows_stream",
+        "sensor UInt32, value Float64",
+        &client,
+    )
+    .await;
+
+    // blocks of 12 bytes per row are cut by size well before the row limit
+    let rows = futures_util::stream::iter(0..1000u32).map(|sensor| Reading {
+        sensor,
+        value: sensor as f64,
+    });
+    let result = client
+        .insert_rows_stream(
+            "INSERT INTO test_insert_rows_stream FORMAT native",
+            rows,
+            InsertBatching {
+                max_rows: 500,
+                max_bytes: 1200,
+                max_delay: Duration::from_secs(10),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.rows, 1000);
+    assert_eq!(result.blocks, 10);
+
+    let count = client
+        .query_one::<UnitValue<u64>>("SELECT count() FROM test_insert_rows_stream")
+        .await
+        .unwrap();
+    assert_eq!(count.0, 1000);
+}
@@ -0,0 +1,59 @@
+use klickhouse::{Ipv4Net, Ipv6Net, QueryBuilder, UnitValue};
+
+use super::prepare_table;
+
+#[derive(klickhouse::Row, Debug, Clone, PartialEq)]
+struct Row {
+    id: u32,
+    network: Ipv4Net,
+}
+
+#[tokio::test]
+async fn test_ip_net() {
+    let client = super::get_client().await;
+
+    let range = client
+        .query_one::<UnitValue<Ipv4Net>>("SELECT IPv4CIDRToRange(toIPv4('192.168.5.2'), 16)")
+        .await
+        .unwrap();
+    assert_eq!(range.0, "192.168.0.0/16".parse().unwrap());
+    let range = client
+        .query_one::<UnitValue<Ipv6Net>>("SELECT IPv6CIDRToRange(toIPv6('2001:db8::1'), 32)")
+        .await
+        .unwrap();
+    assert_eq!(range.0, "2001:db8::/32".parse().unwrap());
+
+    // filtering by a network sent as its range
+    let network: Ipv4Net = "10.0.0.0/8".parse().unwrap();
+    let matches = client
+        .query_one::<UnitValue<u8>>(
+            QueryBuilder::new(
+                "SELECT toIPv4('10.1.2.3') BETWEEN tupleElement($1, 1) AND tupleElement($1, 2)",
+            )
+            .arg(network),
+        )
+        .await
+        .unwrap();
+    assert_eq!(matches.0, 1);
+
+    prepare_table("test_ip_net", "id UInt32, network String", &client).await;
+    let rows = vec![
+        Row {
+            id: 1,
+            network: "10.0.0.0/8".parse().unwrap(),
+        },
+        Row {
+            id: 2,
+            network: "192.168.1.0/24".parse().unwrap(),
+        },
+    ];
+    client
+        .insert_native_block("INSERT INTO test_ip_net FORMAT Native", rows.clone())
+        .await
+        .unwrap();
+    let received = client
+        .query_collect::<Row>("SELECT * FROM test_ip_net ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(received, rows);
+}
@@ -0,0 +1,87 @@
+use klickhouse::{Json, QueryOptions, UnitValue};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct Payload {
+    name: String,
+    tags: Vec<String>,
+}
+
+#[derive(klickhouse::Row, Debug, Clone)]
+struct Row {
+    id: u32,
+    data: serde_json::Value,
+    payload: Json<Payload>,
+}
+
+#[tokio::test]
+async fn test_json() {
+    let client = super::get_client().await;
+
+    client
+        .execute("DROP TABLE IF EXISTS test_json")
+        .await
+        .unwrap();
+    client
+        .execute_with_options(
+            "CREATE TABLE test_json (id UInt32, data JSON, payload JSON(name String)) ENGINE = Memory",
+            // needed before the type left its experimental stage
+            QueryOptions::new().optional_setting("allow_experimental_json_type", 1),
+        )
+        .await
+        .unwrap();
+
+    let rows = vec![
+        Row {
+            id: 1,
+            data: json!({"a": {"b": 1}, "c": "x"}),
+            payload: Json(Payload {
+                name: "first".to_string(),
+                tags: vec!["t".to_string()],
+            }),
+        },
+        Row {
+            id: 2,
+            data: json!({}),
+            payload: Json(Payload {
+                name: "second".to_string(),
+                tags: vec![],
+            }),
+        },
+    ];
+    client
+        .insert_native_block("INSERT INTO test_json FORMAT Native", rows.clone())
+        .await
+        .unwrap();
+
+    let received = client
+        .query_collect_with_options::<Row>(
+            "SELECT * FROM test_json ORDER BY id",
+            QueryOptions::new().json_as_string(true),
+        )
+        .await
+        .unwrap();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].data, rows[0].data);
+    assert_eq!(received[1].data, rows[1].data);
+    assert_eq!(received[0].payload.0.name, "first");
+
+    let path = client
+        .query_one::<UnitValue<u32>>("SELECT data.a.b::UInt32 FROM test_json WHERE id = 1")
+        .await
+        .unwrap();
+    assert_eq!(path.0, 1);
+
+    let text = client
+        .query_one_with_options::<UnitValue<String>>(
+            "SELECT data FROM test_json WHERE id = 1",
+            QueryOptions::new().json_as_string(true),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&text.0).unwrap(),
+        rows[0].data
+    );
+}
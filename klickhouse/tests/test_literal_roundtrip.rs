@@ -0,0 +1,302 @@
+//! Property-based round trips of values through their SQL literals: each value is interpolated into `SELECT CAST(<literal>, '<type>')`,
+//! and the server must return the same value. Run with `cargo test -- --ignored test_literal_roundtrip`;
+//! `PROPTEST_CASES` sets the number of values (default 256), and failures are shrunk to a minimal type and value.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use klickhouse::{
+    i256, query_parser::parse_query_arguments, u256, Date, DateTime, DynDateTime64, Ipv4, Ipv6,
+    LineString, MultiLineString, MultiPolygon, Point, Polygon, Ring, Type, Tz, UnitValue, Value,
+};
+use proptest::{
+    collection::vec,
+    prelude::*,
+    sample::{select, subsequence},
+    test_runner::{TestCaseError, TestRunner},
+};
+use uuid::Uuid;
+
+const TIME_ZONES: &[Tz] = &[
+    Tz::UTC,
+    Tz::Europe__Kyiv,
+    Tz::America__New_York,
+    Tz::Asia__Kolkata,
+    Tz::Australia__Lord_Howe,
+];
+
+fn leaf_type() -> impl Strategy<Value = Type> {
+    let time_zone = || select(TIME_ZONES);
+    prop_oneof![
+        select(vec![
+            Type::Int8,
+            Type::Int16,
+            Type::Int32,
+            Type::Int64,
+            Type::Int128,
+            Type::Int256,
+            Type::UInt8,
+            Type::UInt16,
+            Type::UInt32,
+            Type::UInt64,
+            Type::UInt128,
+            Type::UInt256,
+            Type::Float32,
+            Type::Float64,
+            Type::String,
+            Type::Uuid,
+            Type::Date,
+            Type::Ipv4,
+            Type::Ipv6,
+            Type::Point,
+            Type::Ring,
+            Type::Polygon,
+            Type::MultiPolygon,
+            Type::LineString,
+            Type::MultiLineString,
+        ]),
+        prop_oneof![
+            (0..=9usize).prop_map(Type::Decimal32),
+            (0..=18usize).prop_map(Type::Decimal64),
+            (0..=38usize).prop_map(Type::Decimal128),
+            (0..=76usize).prop_map(Type::Decimal256),
+        ],
+        time_zone().prop_map(Type::DateTime),
+        (0..=9usize, time_zone()).prop_map(|(precision, tz)| Type::DateTime64(precision, tz)),
+        subsequence((i8::MIN..=i8::MAX).collect::<Vec<_>>(), 3)
+            .prop_shuffle()
+            .prop_map(|values| Type::Enum8(
+                ["a", "b'c", "d\\e"]
+                    .iter()
+                    .zip(values)
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+            )),
+    ]
+}
+
+fn arb_type() -> impl Strategy<Value = Type> {
+    leaf_type().prop_recursive(2, 16, 3, |inner| {
+        prop_oneof![
+            inner.clone().prop_map(|x| Type::Array(Box::new(x))),
+            vec(inner.clone(), 1..4).prop_map(Type::Tuple),
+            leaf_type().prop_map(|inner| match inner {
+                // geo types can't be inside Nullable
+                Type::Point
+                | Type::Ring
+                | Type::Polygon
+                | Type::MultiPolygon
+                | Type::LineString
+                | Type::MultiLineString => inner,
+                inner => Type::Nullable(Box::new(inner)),
+            }),
+            (select(vec![Type::String, Type::UInt64, Type::Int32]), inner)
+                .prop_map(|(key, value)| Type::Map(Box::new(key), Box::new(value))),
+            Just(Type::LowCardinality(Box::new(Type::String))),
+        ]
+    })
+}
+
+fn arb_decimal(precision: u32) -> impl Strategy<Value = i128> {
+    let max = 10i128.pow(precision.min(38)) - 1;
+    prop_oneof![Just(max), Just(-max), -max..=max]
+}
+
+fn arb_float() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        Just(f64::NAN),
+        Just(f64::INFINITY),
+        Just(f64::NEG_INFINITY),
+        Just(-0.0),
+        any::<u64>().prop_map(f64::from_bits),
+        -1e6..1e6,
+    ]
+}
+
+fn arb_point() -> impl Strategy<Value = Point> {
+    (-180.0..180.0, -90.0..90.0).prop_map(|(x, y)| Point([x, y]))
+}
+
+fn arb_points() -> impl Strategy<Value = Vec<Point>> {
+    vec(arb_point(), 0..4)
+}
+
+fn arb_polygon() -> impl Strategy<Value = Polygon> {
+    vec(arb_points().prop_map(Ring), 0..3).prop_map(Polygon)
+}
+
+fn arb_value(type_: &Type) -> BoxedStrategy<Value> {
+    match type_ {
+        Type::Int8 => any::<i8>().prop_map(Value::Int8).boxed(),
+        Type::Int16 => any::<i16>().prop_map(Value::Int16).boxed(),
+        Type::Int32 => any::<i32>().prop_map(Value::Int32).boxed(),
+        Type::Int64 => any::<i64>().prop_map(Value::Int64).boxed(),
+        Type::Int128 => any::<i128>().prop_map(Value::Int128).boxed(),
+        Type::Int256 => any::<(u128, u128)>()
+            .prop_map(|x| Value::Int256(i256::from(x)))
+            .boxed(),
+        Type::UInt8 => any::<u8>().prop_map(Value::UInt8).boxed(),
+        Type::UInt16 => any::<u16>().prop_map(Value::UInt16).boxed(),
+        Type::UInt32 => any::<u32>().prop_map(Value::UInt32).boxed(),
+        Type::UInt64 => any::<u64>().prop_map(Value::UInt64).boxed(),
+        Type::UInt128 => any::<u128>().prop_map(Value::UInt128).boxed(),
+        Type::UInt256 => any::<(u128, u128)>()
+            .prop_map(|x| Value::UInt256(u256::from(x)))
+            .boxed(),
+        Type::Float32 => arb_float().prop_map(|x| Value::Float32(x as f32)).boxed(),
+        Type::Float64 => arb_float().prop_map(Value::Float64).boxed(),
+        Type::Decimal32(scale) => {
+            let scale = *scale;
+            arb_decimal(9)
+                .prop_map(move |x| Value::Decimal32(scale, x as i32))
+                .boxed()
+        }
+        Type::Decimal64(scale) => {
+            let scale = *scale;
+            arb_decimal(18)
+                .prop_map(move |x| Value::Decimal64(scale, x as i64))
+                .boxed()
+        }
+        Type::Decimal128(scale) => {
+            let scale = *scale;
+            arb_decimal(38)
+                .prop_map(move |x| Value::Decimal128(scale, x))
+                .boxed()
+        }
+        Type::Decimal256(scale) => {
+            let scale = *scale;
+            prop_oneof![
+                arb_decimal(38)
+                    .prop_map(|x| i256::from((if x < 0 { u128::MAX } else { 0 }, x as u128))),
+                // up to 10^75, beyond 128 bits
+                (0..10u128.pow(37), any::<u128>()).prop_map(i256::from),
+            ]
+            .prop_map(move |x| Value::Decimal256(scale, x))
+            .boxed()
+        }
+        Type::String | Type::LowCardinality(_) => vec(
+            prop_oneof![
+                select(b"'\\\n\t\0{}$?`\"".to_vec()),
+                b' '..=b'~',
+                any::<u8>(),
+            ],
+            0..16,
+        )
+        .prop_map(Value::String)
+        .boxed(),
+        Type::Uuid => any::<u128>()
+            .prop_map(|x| Value::Uuid(Uuid::from_u128(x)))
+            .boxed(),
+        Type::Date => any::<u16>().prop_map(|x| Value::Date(Date(x))).boxed(),
+        Type::DateTime(tz) => {
+            let tz = *tz;
+            any::<u32>()
+                .prop_map(move |x| Value::DateTime(DateTime(tz, x)))
+                .boxed()
+        }
+        Type::DateTime64(precision, tz) => {
+            let (precision, tz) = (*precision, *tz);
+            let scale = 10u64.pow(precision as u32);
+            // up to 2262, the end of the range of nanosecond precision
+            (0..9_200_000_000u64, 0..scale)
+                .prop_map(move |(seconds, sub_second)| {
+                    Value::DateTime64(DynDateTime64(tz, seconds * scale + sub_second, precision))
+                })
+                .boxed()
+        }
+        Type::Enum8(entries) => select(entries.iter().map(|(_, x)| *x).collect::<Vec<_>>())
+            .prop_map(Value::Enum8)
+            .boxed(),
+        Type::Ipv4 => any::<u32>()
+            .prop_map(|x| Value::Ipv4(Ipv4(Ipv4Addr::from(x))))
+            .boxed(),
+        Type::Ipv6 => any::<u128>()
+            .prop_map(|x| Value::Ipv6(Ipv6(Ipv6Addr::from(x))))
+            .boxed(),
+        Type::Point => arb_point().prop_map(Value::Point).boxed(),
+        Type::Ring => arb_points().prop_map(|x| Value::Ring(Ring(x))).boxed(),
+        Type::LineString => arb_points()
+            .prop_map(|x| Value::LineString(LineString(x)))
+            .boxed(),
+        Type::Polygon => arb_polygon().prop_map(Value::Polygon).boxed(),
+        Type::MultiLineString => vec(arb_points().prop_map(LineString), 0..3)
+            .prop_map(|x| Value::MultiLineString(MultiLineString(x)))
+            .boxed(),
+        Type::MultiPolygon => vec(arb_polygon(), 0..3)
+            .prop_map(|x| Value::MultiPolygon(MultiPolygon(x)))
+            .boxed(),
+        Type::Array(inner) => vec(arb_value(inner), 0..4).prop_map(Value::Array).boxed(),
+        Type::Tuple(inner) => inner
+            .iter()
+            .map(arb_value)
+            .collect::<Vec<_>>()
+            .prop_map(Value::Tuple)
+            .boxed(),
+        Type::Nullable(inner) => prop_oneof![3 => Just(Value::Null), 7 => arb_value(inner)].boxed(),
+        Type::Map(key, value) => {
+            let value = arb_value(value);
+            vec(arb_value(key), 0..4)
+                .prop_map(|keys| {
+                    let mut unique = vec![];
+                    for key in keys {
+                        if !unique.contains(&key) {
+                            unique.push(key);
+                        }
+                    }
+                    unique
+                })
+                .prop_flat_map(move |keys| {
+                    let values = vec(value.clone(), keys.len());
+                    (Just(keys), values)
+                })
+                .prop_map(|(keys, values)| Value::Map(keys, values))
+                .boxed()
+        }
+        other => panic!("no generator for {other}"),
+    }
+}
+
+/// Equality that also holds for `NaN`s at the same position.
+fn same(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Float32(l), Value::Float32(r)) => l == r || (l.is_nan() && r.is_nan()),
+        (Value::Float64(l), Value::Float64(r)) => l == r || (l.is_nan() && r.is_nan()),
+        (Value::Array(l), Value::Array(r)) | (Value::Tuple(l), Value::Tuple(r)) => {
+            l.len() == r.len() && l.iter().zip(r).all(|(l, r)| same(l, r))
+        }
+        (Value::Map(lk, lv), Value::Map(rk, rv)) => {
+            lk.len() == rk.len()
+                && lk.iter().zip(rk).all(|(l, r)| same(l, r))
+                && lv.iter().zip(rv).all(|(l, r)| same(l, r))
+        }
+        (l, r) => l == r,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "runs many queries, see module docs"]
+async fn test_literal_roundtrip() {
+    let client = super::get_client().await;
+    let runtime = tokio::runtime::Handle::current();
+    let cases = arb_type().prop_flat_map(|type_| {
+        let value = arb_value(&type_);
+        (Just(type_), value)
+    });
+    let mut runner = TestRunner::default();
+    let result = tokio::task::block_in_place(|| {
+        runner.run(&cases, |(type_, value)| {
+            let query = parse_query_arguments(
+                "SELECT CAST($1, $2)",
+                &[value.clone(), Value::string(type_.to_string())],
+            );
+            let received = runtime
+                .block_on(client.query_one::<UnitValue<Value>>(query.as_str()))
+                .map_err(|e| TestCaseError::fail(format!("{query}: {e}")))?
+                .0;
+            prop_assert!(same(&value, &received), "{}: received {}", query, received);
+            Ok(())
+        })
+    });
+    if let Err(e) = result {
+        panic!("{e}");
+    }
+}
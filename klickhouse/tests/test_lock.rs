@@ -28,3 +28,32 @@ async fn test_client() {
     println!("lock2 locked");
     handle2.unlock().await.unwrap();
 }
+
+#[tokio::test]
+async fn test_read_lock() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client1 = super::get_client().await;
+    let client2 = super::get_client().await;
+
+    let lock1 = ClickhouseLock::new(client1.clone(), "test_rw");
+    let lock2 = ClickhouseLock::new(client2.clone(), "test_rw");
+    lock1.reset().await.unwrap();
+
+    let reader1 = lock1.read_lock().await.unwrap();
+    let reader2 = lock2.read_lock().await.unwrap();
+    assert!(lock2.try_lock().await.unwrap().is_none());
+
+    reader1.unlock().await.unwrap();
+    assert!(tokio::time::timeout(Duration::from_secs(1), lock1.lock())
+        .await
+        .is_err());
+    reader2.unlock().await.unwrap();
+
+    let writer = lock1.lock().await.unwrap();
+    assert!(lock2.try_read_lock().await.unwrap().is_none());
+    writer.unlock().await.unwrap();
+
+    lock2.read_lock().await.unwrap().unlock().await.unwrap();
+}
@@ -0,0 +1,61 @@
+use chrono::NaiveDate;
+use klickhouse::TruncatedDate;
+
+#[derive(klickhouse::Row, Debug, PartialEq, Clone)]
+pub struct Event {
+    day: NaiveDate,
+    at: NaiveDate,
+}
+
+#[derive(klickhouse::Row, Debug, PartialEq)]
+pub struct Rollup {
+    day: NaiveDate,
+    at: TruncatedDate,
+}
+
+#[tokio::test]
+async fn test_naive_date() {
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_naive_date",
+        r"
+        day Date,
+        at DateTime('Asia/Tokyo')
+    ",
+        &client,
+    )
+    .await;
+
+    let day = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    client
+        .insert_native_block(
+            "INSERT INTO test_naive_date FORMAT Native",
+            vec![Event { day, at: day }],
+        )
+        .await
+        .unwrap();
+
+    let at = client
+        .query_one::<klickhouse::UnitValue<String>>("SELECT toString(at) FROM test_naive_date")
+        .await
+        .unwrap();
+    assert_eq!(at.0, "2024-03-10 00:00:00");
+
+    let rows = client
+        .query_collect::<Rollup>("SELECT day, at + INTERVAL 23 HOUR AS at FROM test_naive_date")
+        .await
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![Rollup {
+            day,
+            at: TruncatedDate(day)
+        }]
+    );
+
+    assert!(client
+        .query_collect::<Event>("SELECT * FROM test_naive_date")
+        .await
+        .is_err());
+}
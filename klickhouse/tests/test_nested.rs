@@ -75,3 +75,151 @@ async fn test_client() {
         .unwrap();
     assert_eq!(items, items2);
 }
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq, Clone)]
+pub struct TestSerializeNestedNaming {
+    #[klickhouse(nested, nested_separator = "_", nested_rename_all = "camelCase")]
+    nest: Vec<Nest>,
+}
+
+#[test]
+fn test_nested_naming() {
+    use klickhouse::{IndexMap, Row, Type, Value};
+
+    let names = [
+        "nest_nestString",
+        "nest_nestU64",
+        "nest_nestNullString",
+        "nest_nestI16",
+    ];
+    assert_eq!(
+        TestSerializeNestedNaming::column_names().unwrap(),
+        names.to_vec()
+    );
+    let column_types = TestSerializeNestedNaming::column_types().unwrap();
+    assert_eq!(
+        column_types.iter().map(|x| &*x.name).collect::<Vec<_>>(),
+        names.to_vec()
+    );
+    assert!(column_types[3].accepts(&Type::Array(Box::new(Type::Int16))));
+
+    let row = TestSerializeNestedNaming {
+        nest: vec![Nest {
+            nest_string: "nest1".to_string(),
+            nest_u64: Some(1),
+            nest_null_string: None,
+            nest_i16: 32,
+        }],
+    };
+    let columns = row.clone().serialize_row(&IndexMap::new()).unwrap();
+    assert_eq!(
+        columns.iter().map(|(name, _)| &**name).collect::<Vec<_>>(),
+        names.to_vec()
+    );
+
+    let types = [
+        Type::Array(Box::new(Type::String)),
+        Type::Array(Box::new(Type::Nullable(Box::new(Type::UInt64)))),
+        Type::Array(Box::new(Type::Nullable(Box::new(Type::String)))),
+        Type::Array(Box::new(Type::Int16)),
+    ];
+    // in reverse order, so columns are matched by name
+    let map: Vec<(&str, &Type, Value)> = columns
+        .iter()
+        .zip(&types)
+        .map(|((name, value), type_)| (&**name, type_, value.clone()))
+        .rev()
+        .collect();
+    assert_eq!(
+        TestSerializeNestedNaming::deserialize_row(map).unwrap(),
+        row
+    );
+}
+
+#[tokio::test]
+async fn test_client_nested_naming() {
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_nested_naming",
+        r"
+        nest_nestString Array(String),
+        nest_nestU64 Array(Nullable(UInt64)),
+        nest_nestNullString Array(Nullable(String)),
+        nest_nestI16 Array(Int16),
+    ",
+        &client,
+    )
+    .await;
+
+    let items = vec![TestSerializeNestedNaming {
+        nest: vec![Nest {
+            nest_string: "nest1".to_string(),
+            nest_u64: None,
+            nest_null_string: Some("x".to_string()),
+            nest_i16: -1,
+        }],
+    }];
+    client
+        .insert_native_block(
+            "INSERT INTO test_nested_naming FORMAT Native",
+            items.clone(),
+        )
+        .await
+        .unwrap();
+
+    let received = client
+        .query_collect::<TestSerializeNestedNaming>("SELECT * FROM test_nested_naming")
+        .await
+        .unwrap();
+    assert_eq!(received, items);
+}
+
+// `nested_rename_all` must name columns like `rename_all` names fields
+macro_rules! nested_rename_all_matches {
+    ($($rule:literal => $flat:ident, $nested:ident;)*) => {
+        $(
+            #[allow(unused)]
+            #[derive(klickhouse::Row)]
+            #[klickhouse(rename_all = $rule)]
+            struct $flat {
+                nest_string: String,
+                nest_u64: Option<u64>,
+                nest_null_string: Option<String>,
+                nest_i16: i16,
+            }
+
+            #[allow(unused)]
+            #[derive(klickhouse::Row)]
+            struct $nested {
+                #[klickhouse(nested, nested_rename_all = $rule)]
+                nest: Vec<Nest>,
+            }
+        )*
+
+        #[test]
+        fn test_nested_rename_all_matches_rename_all() {
+            use klickhouse::Row;
+
+            $(
+                let flat = $flat::column_names()
+                    .unwrap()
+                    .into_iter()
+                    .map(|x| format!("nest.{x}"))
+                    .collect::<Vec<_>>();
+                assert_eq!($nested::column_names().unwrap(), flat, $rule);
+            )*
+        }
+    };
+}
+
+nested_rename_all_matches! {
+    "lowercase" => FlatLowerCase, NestedLowerCase;
+    "UPPERCASE" => FlatUpperCase, NestedUpperCase;
+    "PascalCase" => FlatPascalCase, NestedPascalCase;
+    "camelCase" => FlatCamelCase, NestedCamelCase;
+    "snake_case" => FlatSnakeCase, NestedSnakeCase;
+    "SCREAMING_SNAKE_CASE" => FlatScreamingSnakeCase, NestedScreamingSnakeCase;
+    "kebab-case" => FlatKebabCase, NestedKebabCase;
+    "SCREAMING-KEBAB-CASE" => FlatScreamingKebabCase, NestedScreamingKebabCase;
+}
@@ -0,0 +1,63 @@
+use std::net::Ipv4Addr;
+
+#[derive(klickhouse::Row, Debug, PartialEq, Clone)]
+pub struct Row {
+    #[klickhouse(parse)]
+    amount: u64,
+    #[klickhouse(parse)]
+    ratio: f64,
+    #[klickhouse(parse)]
+    address: Ipv4Addr,
+}
+
+#[tokio::test]
+async fn test_parse() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_parse",
+        r"
+        amount String,
+        ratio String,
+        address LowCardinality(String)
+    ",
+        &client,
+    )
+    .await;
+
+    let items = vec![
+        Row {
+            amount: 12345678901,
+            ratio: 0.25,
+            address: Ipv4Addr::new(10, 0, 0, 1),
+        },
+        Row {
+            amount: 0,
+            ratio: -1.5,
+            address: Ipv4Addr::LOCALHOST,
+        },
+    ];
+
+    client
+        .insert_native_block("INSERT INTO test_parse FORMAT Native", items.clone())
+        .await
+        .unwrap();
+
+    let items2 = client
+        .query_collect::<Row>("SELECT * FROM test_parse ORDER BY amount DESC")
+        .await
+        .unwrap();
+    assert_eq!(items, items2);
+
+    client
+        .execute("INSERT INTO test_parse VALUES ('x', '1', '')")
+        .await
+        .unwrap();
+    assert!(client
+        .query_collect::<Row>("SELECT * FROM test_parse")
+        .await
+        .is_err());
+}
@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use futures_util::FutureExt;
+use klickhouse::{ClientEvent, ClientOptions, KlickhouseError, Pool, PoolOptions, UnitValue};
+
+fn pool(options: PoolOptions) -> Pool {
+    Pool::new(
+        klickhouse::test_util::test_address(),
+        klickhouse::test_util::with_env_options(ClientOptions::default()),
+        options,
+    )
+}
+
+#[tokio::test]
+async fn test_pool_reuse() {
+    let pool = pool(PoolOptions {
+        max_size: 2,
+        ..Default::default()
+    });
+    assert_eq!(pool.idle_connections(), 0);
+    let first = pool.get().await.unwrap();
+    let second = pool.get().await.unwrap();
+    first.ping().await.unwrap();
+    // a third connection waits for one to be returned
+    assert!(tokio::time::timeout(Duration::from_millis(100), pool.get())
+        .await
+        .is_err());
+    drop(first);
+    drop(second);
+    assert_eq!(pool.idle_connections(), 2);
+
+    let client = pool.get().await.unwrap();
+    assert_eq!(pool.idle_connections(), 1);
+    let value = client.query_one::<UnitValue<u8>>("SELECT 1").await.unwrap();
+    assert_eq!(value.0, 1);
+}
+
+#[tokio::test]
+async fn test_pool_ping_during_query() {
+    let pool = pool(PoolOptions::default());
+    let client = pool.get().await.unwrap();
+    let query = client.query_collect::<UnitValue<u8>>("SELECT sleep(0.2)");
+    let (rows, ping) = tokio::join!(query, client.ping());
+    assert_eq!(rows.unwrap().len(), 1);
+    ping.unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_keepalive() {
+    let pool = pool(PoolOptions {
+        idle_timeout: Some(Duration::from_millis(200)),
+        ping_interval: Some(Duration::from_millis(50)),
+        ..Default::default()
+    });
+    drop(pool.get().await.unwrap());
+    assert_eq!(pool.idle_connections(), 1);
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(pool.idle_connections(), 0);
+}
+
+#[tokio::test]
+async fn test_pool_keepalive_max_size() {
+    let pool = pool(PoolOptions {
+        max_size: 2,
+        ping_interval: Some(Duration::from_millis(5)),
+        ..Default::default()
+    });
+    // connections stay idle while they are pinged, so none are opened in their place
+    for _ in 0..20 {
+        let (first, second) = tokio::join!(pool.get(), pool.get());
+        drop(first.unwrap());
+        drop(second.unwrap());
+        assert_eq!(pool.idle_connections(), 2);
+        tokio::time::sleep(Duration::from_millis(3)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_pool_retry() {
+    let pool = pool(PoolOptions::default());
+    let mut attempts = 0;
+    let value = pool
+        .retry(|client| {
+            attempts += 1;
+            let error = match attempts {
+                1 => Some(std::io::ErrorKind::ConnectionReset),
+                _ => None,
+            };
+            async move {
+                if let Some(error) = error {
+                    return Err(KlickhouseError::Io(error.into()));
+                }
+                client.query_one::<UnitValue<u8>>("SELECT 1").await
+            }
+            .boxed()
+        })
+        .await
+        .unwrap();
+    assert_eq!(value.0, 1);
+    assert_eq!(attempts, 2);
+
+    // the connection is intact, so other errors aren't retried
+    attempts = 0;
+    let result = pool
+        .retry(|_| {
+            attempts += 1;
+            async { Err::<(), _>(KlickhouseError::MissingRow) }.boxed()
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+
+    let rows = pool
+        .query_collect::<UnitValue<u64>>("SELECT number FROM system.numbers LIMIT 3")
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+}
+
+#[tokio::test]
+async fn test_pool_retry_events() {
+    let (sender, mut events) = tokio::sync::broadcast::channel(100);
+    let pool = Pool::new(
+        klickhouse::test_util::test_address(),
+        klickhouse::test_util::with_env_options(ClientOptions {
+            event_sender: Some(sender),
+            ..Default::default()
+        }),
+        PoolOptions::default(),
+    );
+    let mut attempts = 0;
+    pool.retry(|_| {
+        attempts += 1;
+        let failed = attempts == 1;
+        async move {
+            if failed {
+                return Err(KlickhouseError::Io(
+                    std::io::ErrorKind::ConnectionReset.into(),
+                ));
+            }
+            Ok(())
+        }
+        .boxed()
+    })
+    .await
+    .unwrap();
+
+    let mut reconnecting = vec![];
+    while let Ok(event) = events.try_recv() {
+        if let ClientEvent::Reconnecting { attempt, .. } = event {
+            reconnecting.push(attempt);
+        }
+    }
+    assert_eq!(reconnecting, [1]);
+}
@@ -0,0 +1,25 @@
+use std::sync::{Arc, Mutex};
+
+use klickhouse::{AuditedQuery, ClientOptions, QueryAuditHook, QueryBuilder};
+
+#[tokio::test]
+async fn test_query_audit_hook() {
+    let audited: Arc<Mutex<Vec<AuditedQuery>>> = Default::default();
+    let hook_audited = audited.clone();
+    let client = super::get_client_with_options(ClientOptions {
+        query_audit_hook: Some(QueryAuditHook::new(move |query| {
+            hook_audited.lock().unwrap().push(query.clone());
+        })),
+        ..Default::default()
+    })
+    .await;
+
+    client
+        .execute(QueryBuilder::new("SELECT $1").arg("secret").redact(true))
+        .await
+        .unwrap();
+    let audited = audited.lock().unwrap();
+    assert_eq!(audited.len(), 1);
+    assert!(!audited[0].id.is_nil());
+    assert_eq!(audited[0].sql, "SELECT 'secret'");
+}
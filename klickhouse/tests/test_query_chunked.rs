@@ -0,0 +1,27 @@
+use futures_util::StreamExt;
+use klickhouse::UnitValue;
+
+#[tokio::test]
+async fn test_query_chunked() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    // small blocks, so chunks span block boundaries
+    let chunks = client
+        .query_chunked::<UnitValue<u64>>(
+            "SELECT number FROM system.numbers LIMIT 25 SETTINGS max_block_size = 7",
+            10,
+        )
+        .await
+        .unwrap()
+        .map(|chunk| chunk.unwrap().into_iter().map(|x| x.0).collect::<Vec<_>>())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(
+        chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+        vec![10, 10, 5]
+    );
+    assert_eq!(chunks.concat(), (0..25).collect::<Vec<_>>());
+}
@@ -0,0 +1,39 @@
+use klickhouse::UnitValue;
+
+#[tokio::test]
+async fn query_first_block() {
+    let client = super::get_client().await;
+
+    let rows = client
+        .query_first_block::<UnitValue<u64>>("SELECT number FROM numbers(3)")
+        .await
+        .unwrap();
+    assert_eq!(
+        rows.into_iter().map(|x| x.0).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+
+    let rows = client
+        .query_first_block::<UnitValue<u64>>("SELECT number FROM numbers(3) WHERE number > 5")
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+
+    // never ends on its own, so the follow-up query only runs if the server honored the cancellation
+    let rows = client
+        .query_first_block::<UnitValue<u64>>(
+            "SELECT number FROM system.numbers SETTINGS max_block_size = 10",
+        )
+        .await
+        .unwrap();
+    assert!(!rows.is_empty() && rows.len() <= 10);
+    assert_eq!(rows[0].0, 0);
+
+    let one = client.query_one::<UnitValue<u8>>("SELECT 1").await.unwrap();
+    assert_eq!(one.0, 1);
+
+    assert!(client
+        .query_first_block::<UnitValue<u64>>("SELECT missing_column")
+        .await
+        .is_err());
+}
@@ -0,0 +1,67 @@
+use futures_util::StreamExt;
+use klickhouse::{QueryOptions, RawRow, UnitValue};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_query_id() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    let (id, rows) = client
+        .query_with_id::<UnitValue<u64>>("SELECT 1", QueryOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(rows.count().await, 1);
+
+    let custom = Uuid::new_v4();
+    assert_ne!(id, custom);
+    let mut progress = client.subscribe_progress();
+    let (id, rows) = client
+        .query_with_id::<RawRow>(
+            "SELECT number FROM system.numbers LIMIT 100000",
+            QueryOptions::new().query_id(custom),
+        )
+        .await
+        .unwrap();
+    assert_eq!(id, custom);
+    assert_eq!(rows.count().await, 100000);
+    let (progress_id, _) = progress.recv().await.unwrap();
+    assert_eq!(progress_id, custom);
+
+    client.execute("SYSTEM FLUSH LOGS").await.unwrap();
+    let logged = client
+        .query_one::<UnitValue<String>>(
+            klickhouse::QueryBuilder::new(
+                "SELECT query FROM system.query_log WHERE query_id = $1 AND type = 'QueryFinish'",
+            )
+            .arg(custom.to_string()),
+        )
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(logged, "SELECT number FROM system.numbers LIMIT 100000");
+}
+
+#[tokio::test]
+async fn test_cancel_query() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    let (id, mut rows) = client
+        .query_with_id::<UnitValue<u64>>("SELECT number FROM system.numbers", QueryOptions::new())
+        .await
+        .unwrap();
+    rows.next().await.unwrap().unwrap();
+    client.cancel_query(id).await.unwrap();
+    // the otherwise endless query stops
+    while let Some(row) = rows.next().await {
+        if row.is_err() {
+            break;
+        }
+    }
+    client.execute("SELECT 1").await.unwrap();
+}
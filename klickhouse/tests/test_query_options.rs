@@ -0,0 +1,169 @@
+use futures_util::StreamExt;
+use klickhouse::{QueryOptions, QueryPriority, UnitValue};
+
+#[tokio::test]
+async fn test_query_database() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    let default = client
+        .query_one::<UnitValue<String>>("SELECT currentDatabase()")
+        .await
+        .unwrap()
+        .0;
+
+    client
+        .execute("CREATE DATABASE IF NOT EXISTS test_query_database")
+        .await
+        .unwrap();
+
+    let options = QueryOptions::new().database("test_query_database");
+    let current = client
+        .query_with_options::<UnitValue<String>>("SELECT currentDatabase()", options.clone())
+        .await
+        .unwrap()
+        .next()
+        .await
+        .unwrap()
+        .unwrap()
+        .0;
+    assert_eq!(current, "test_query_database");
+
+    client
+        .execute_with_options(
+            "CREATE TABLE IF NOT EXISTS tenant (id UInt32) ENGINE = Memory",
+            options,
+        )
+        .await
+        .unwrap();
+    client
+        .execute("SELECT * FROM test_query_database.tenant")
+        .await
+        .unwrap();
+
+    // back to the default database for queries without an override
+    let current = client
+        .query_one::<UnitValue<String>>("SELECT currentDatabase()")
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(current, default);
+
+    assert!(client
+        .execute_with_options(
+            "SELECT 1",
+            QueryOptions::new().database("test_query_database_missing")
+        )
+        .await
+        .is_err());
+    client.execute("SELECT 1").await.unwrap();
+
+    client
+        .execute("DROP DATABASE test_query_database")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_query_options_variants() {
+    let client = super::get_client().await;
+
+    client
+        .execute("CREATE DATABASE IF NOT EXISTS test_query_options_variants")
+        .await
+        .unwrap();
+    let options = QueryOptions::new().database("test_query_options_variants");
+    client
+        .execute_with_options(
+            "CREATE OR REPLACE TABLE tenant (id UInt32) ENGINE = Memory",
+            options.clone(),
+        )
+        .await
+        .unwrap();
+
+    client
+        .insert_native_block_with_options(
+            "INSERT INTO tenant FORMAT native",
+            vec![UnitValue(1u32), UnitValue(2u32)],
+            options.clone(),
+        )
+        .await
+        .unwrap();
+
+    let ids = client
+        .query_collect_with_options::<UnitValue<u32>>(
+            "SELECT id FROM tenant ORDER BY id",
+            options.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(ids.into_iter().map(|x| x.0).collect::<Vec<_>>(), vec![1, 2]);
+
+    let count = client
+        .query_one_with_options::<UnitValue<u64>>("SELECT count() FROM tenant", options.clone())
+        .await
+        .unwrap();
+    assert_eq!(count.0, 2);
+
+    let missing = client
+        .query_opt_with_options::<UnitValue<u32>>(
+            "SELECT id FROM tenant WHERE id > 5",
+            options.clone(),
+        )
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+
+    let first = client
+        .query_first_block_with_options::<UnitValue<u32>>(
+            "SELECT id FROM tenant ORDER BY id",
+            options,
+        )
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 2);
+
+    client
+        .execute("DROP DATABASE test_query_options_variants")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_query_settings() {
+    let client = super::get_client().await;
+
+    let options = QueryOptions::new()
+        .setting("max_threads", 3)
+        .optional_setting("setting_unknown_to_any_server", 1);
+    let value = client
+        .query_collect_with_options::<UnitValue<String>>(
+            "SELECT getSetting('max_threads')::String",
+            options,
+        )
+        .await
+        .unwrap();
+    assert_eq!(value[0].0, "3");
+
+    let options = QueryOptions::new().setting("setting_unknown_to_any_server", 1);
+    assert!(client
+        .query_collect_with_options::<UnitValue<String>>("SELECT 1", options)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_query_priority() {
+    let client = super::get_client().await;
+
+    let priority = client
+        .query_one_with_options::<UnitValue<String>>(
+            "SELECT getSetting('priority')::String",
+            QueryOptions::new().priority(QueryPriority::High),
+        )
+        .await
+        .unwrap();
+    assert_eq!(priority.0, "1");
+}
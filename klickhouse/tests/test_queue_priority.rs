@@ -0,0 +1,95 @@
+use std::{sync::Arc, time::Duration};
+
+use klickhouse::{ClientEvent, ClientOptions, ParsedQuery, QueryOptions, QueryPriority, UnitValue};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_queue_priority() {
+    let client = super::get_client_with_options(ClientOptions {
+        max_concurrent_queries: Some(1),
+        ..Default::default()
+    })
+    .await;
+
+    let slow = {
+        let client = client.clone();
+        tokio::spawn(async move { client.execute("SELECT sleep(1)").await })
+    };
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let finished = Arc::new(Mutex::new(vec![]));
+    let mut queries = vec![];
+    for (name, priority) in [
+        ("low", QueryPriority::Low),
+        ("normal", QueryPriority::Normal),
+        ("high", QueryPriority::High),
+    ] {
+        let client = client.clone();
+        let finished = finished.clone();
+        queries.push(tokio::spawn(async move {
+            client
+                .query_one_with_options::<UnitValue<u8>>(
+                    "SELECT 1",
+                    QueryOptions::new().queue_priority(priority),
+                )
+                .await
+                .unwrap();
+            finished.lock().await.push(name);
+        }));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(client.queued_queries(), 3);
+
+    slow.await.unwrap().unwrap();
+    for query in queries {
+        query.await.unwrap();
+    }
+    assert_eq!(*finished.lock().await, ["high", "normal", "low"]);
+    assert_eq!(client.queued_queries(), 0);
+}
+
+#[tokio::test]
+async fn test_queue_priority_batch() {
+    let client = super::get_client().await;
+    let mut events = client.subscribe_events();
+
+    let queries = ["SELECT sleep(0.5)", "SELECT 1"]
+        .into_iter()
+        .map(|x| x.try_into())
+        .collect::<klickhouse::Result<Vec<ParsedQuery>>>()
+        .unwrap();
+    let results = client.execute_multi(queries).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // queued while the batch runs, but must not run in between its statements
+    let high_id = Uuid::new_v4();
+    let high = {
+        let client = client.clone();
+        tokio::spawn(async move {
+            client
+                .query_one_with_options::<UnitValue<u8>>(
+                    "SELECT 1",
+                    QueryOptions::new()
+                        .queue_priority(QueryPriority::High)
+                        .query_id(high_id),
+                )
+                .await
+                .unwrap();
+        })
+    };
+    let mut batch_ids = vec![];
+    for result in results {
+        batch_ids.push(result.query_id());
+        result.finish().await.unwrap();
+    }
+    high.await.unwrap();
+
+    let mut started = vec![];
+    while let Ok(event) = events.try_recv() {
+        if let ClientEvent::QueryStarted { id } = event {
+            started.push(id);
+        }
+    }
+    assert_eq!(started, [batch_ids[0], batch_ids[1], high_id]);
+}
@@ -0,0 +1,143 @@
+use klickhouse::{ColumnLookup, KlickhouseError, Row, RowAccessor, Type, Value};
+
+#[derive(Row, Debug, PartialEq)]
+struct Item {
+    x: u32,
+}
+
+#[derive(Row, Debug, PartialEq)]
+struct Plain {
+    id: u32,
+    #[klickhouse(rename = "label")]
+    name: String,
+    #[klickhouse(default)]
+    missing: Option<u8>,
+    #[klickhouse(nested)]
+    items: Vec<Item>,
+}
+
+#[derive(Row, Debug, PartialEq)]
+struct Single {
+    id: u32,
+}
+
+fn accessor<T: Row>(columns: &[(&str, &Type)], values: Vec<Value>) -> klickhouse::Result<T> {
+    let mut values = values;
+    T::deserialize_accessor(RowAccessor::new(
+        columns,
+        &mut values,
+        &ColumnLookup::default(),
+    ))
+}
+
+#[test]
+fn test_row_accessor_derive() {
+    let array = Type::Array(Box::new(Type::UInt32));
+    let columns = [
+        ("label", &Type::String),
+        ("id", &Type::UInt32),
+        ("items.x", &array),
+    ];
+    let values = vec![
+        Value::String(b"a".to_vec()),
+        Value::UInt32(4),
+        Value::Array(vec![Value::UInt32(1), Value::UInt32(2)]),
+    ];
+    let expected = Plain {
+        id: 4,
+        name: "a".to_string(),
+        missing: None,
+        items: vec![Item { x: 1 }, Item { x: 2 }],
+    };
+    assert_eq!(
+        accessor::<Plain>(&columns, values.clone()).unwrap(),
+        expected
+    );
+    let row = columns
+        .iter()
+        .zip(values)
+        .map(|((name, type_), value)| (*name, *type_, value))
+        .collect();
+    assert_eq!(Plain::deserialize_row(row).unwrap(), expected);
+
+    // unnamed columns are matched by position, as with `deserialize_row`
+    let columns = [("1", &Type::UInt32)];
+    assert_eq!(
+        accessor::<Single>(&columns, vec![Value::UInt32(9)]).unwrap(),
+        Single { id: 9 }
+    );
+
+    let columns = [("other", &Type::UInt32), ("id", &Type::UInt32)];
+    assert!(matches!(
+        accessor::<Single>(&columns, vec![Value::UInt32(1), Value::UInt32(2)]),
+        Err(KlickhouseError::DuplicateColumn { .. })
+    ));
+    assert!(matches!(
+        accessor::<Item>(&[], vec![]),
+        Err(KlickhouseError::MissingField("x"))
+    ));
+}
+
+#[derive(Row, Debug, PartialEq, Default)]
+struct Inner {
+    a: u32,
+    b: String,
+}
+
+#[derive(Row, Debug, PartialEq)]
+struct Outer {
+    id: u32,
+    #[klickhouse(flatten)]
+    inner: Inner,
+}
+
+#[derive(Row, Debug, PartialEq, Default)]
+#[klickhouse(default)]
+struct OuterDefault {
+    id: u32,
+    #[klickhouse(flatten)]
+    inner: Inner,
+}
+
+#[test]
+fn test_row_accessor_lookup() {
+    // the lookup built for the first row is reused by the following rows of the block
+    let columns = [
+        ("b", &Type::String),
+        ("id", &Type::UInt32),
+        ("a", &Type::UInt32),
+    ];
+    let lookup = ColumnLookup::default();
+    for i in 0..3u32 {
+        let mut values = vec![
+            Value::String(format!("row{i}").into_bytes()),
+            Value::UInt32(i),
+            Value::UInt32(i * 10),
+        ];
+        let row =
+            Outer::deserialize_accessor(RowAccessor::new(&columns, &mut values, &lookup)).unwrap();
+        assert_eq!(
+            row,
+            Outer {
+                id: i,
+                inner: Inner {
+                    a: i * 10,
+                    b: format!("row{i}"),
+                },
+            }
+        );
+    }
+
+    let columns = [("id", &Type::UInt32), ("a", &Type::UInt32)];
+    assert!(matches!(
+        accessor::<Outer>(&columns, vec![Value::UInt32(1), Value::UInt32(2)]),
+        Err(KlickhouseError::MissingField(_))
+    ));
+    assert_eq!(
+        accessor::<OuterDefault>(&columns, vec![Value::UInt32(1), Value::UInt32(2)]).unwrap(),
+        OuterDefault {
+            id: 1,
+            inner: Inner::default(),
+        }
+    );
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use klickhouse::{ClientOptions, Type};
+
+#[tokio::test]
+async fn test_describe_table() {
+    let client = super::get_client_with_options(ClientOptions {
+        schema_cache_ttl: Some(Duration::from_secs(60)),
+        ..Default::default()
+    })
+    .await;
+    super::prepare_table(
+        "test_schema_cache",
+        "a UInt32, b String, c UInt64 MATERIALIZED a",
+        &client,
+    )
+    .await;
+
+    let schema = client.describe_table("test_schema_cache").await.unwrap();
+    assert_eq!(
+        schema.iter().collect::<Vec<_>>(),
+        vec![
+            (&"a".to_string(), &Type::UInt32),
+            (&"b".to_string(), &Type::String)
+        ]
+    );
+
+    client
+        .execute("ALTER TABLE test_schema_cache ADD COLUMN d Int8")
+        .await
+        .unwrap();
+    // still cached
+    assert_eq!(
+        client
+            .describe_table("test_schema_cache")
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    client.invalidate_schema("test_schema_cache");
+    assert_eq!(
+        client
+            .describe_table("test_schema_cache")
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+}
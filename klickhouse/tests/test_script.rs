@@ -0,0 +1,66 @@
+use futures_util::StreamExt;
+use klickhouse::{ParsedQuery, ScriptErrorPolicy, UnitValue};
+
+#[tokio::test]
+async fn test_execute_script() {
+    let client = super::get_client().await;
+
+    let script = "
+        -- setup
+        DROP TABLE IF EXISTS test_script;
+        CREATE TABLE test_script (a UInt32) ENGINE = Memory;
+        INSERT INTO test_script SELECT number FROM system.numbers LIMIT 10;
+        SELECT * FROM missing_table_for_test_script;
+        SELECT 1;
+    ";
+
+    let mut seen = vec![];
+    let results = client
+        .execute_script_with(script, ScriptErrorPolicy::Stop, |x| seen.push(x.index))
+        .await;
+    assert_eq!(results.len(), 4);
+    assert_eq!(seen, vec![0, 1, 2, 3]);
+    assert!(results[..3].iter().all(|x| x.is_ok()));
+    assert_eq!(results[2].written_rows(), Some(10));
+    assert!(!results[3].is_ok());
+
+    let results = client
+        .execute_script(script, ScriptErrorPolicy::Continue)
+        .await;
+    assert_eq!(results.len(), 5);
+    assert!(results[4].is_ok());
+}
+
+#[tokio::test]
+async fn test_execute_multi() {
+    let client = super::get_client().await;
+
+    let queries = [
+        "DROP TABLE IF EXISTS test_execute_multi",
+        "CREATE TABLE test_execute_multi (a UInt32) ENGINE = Memory",
+        "SELECT * FROM missing_table_for_test_execute_multi",
+        "INSERT INTO test_execute_multi SELECT number FROM system.numbers LIMIT 10",
+        "SELECT count() FROM test_execute_multi",
+    ]
+    .into_iter()
+    .map(|x| x.try_into())
+    .collect::<klickhouse::Result<Vec<ParsedQuery>>>()
+    .unwrap();
+    let mut results = client.execute_multi(queries).await.unwrap();
+    assert_eq!(results.len(), 5);
+    assert_ne!(results[0].query_id(), results[1].query_id());
+
+    let count = results.pop().unwrap();
+    let mut results = results.into_iter();
+    results.next().unwrap().finish().await.unwrap();
+    results.next().unwrap().finish().await.unwrap();
+    assert!(results.next().unwrap().finish().await.is_err());
+    results.next().unwrap().finish().await.unwrap();
+    let count = count
+        .rows::<UnitValue<u64>>()
+        .next()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(count.0, 10);
+}
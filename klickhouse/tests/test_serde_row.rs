@@ -0,0 +1,63 @@
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Row {
+    id: u64,
+    name: String,
+    score: Option<f64>,
+    tags: Vec<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[tokio::test]
+async fn test_serde_row() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+
+    super::prepare_table(
+        "test_serde_row",
+        r"
+        id UInt64,
+        name LowCardinality(String),
+        score Nullable(Decimal64(3)),
+        tags Array(String),
+        created_at DateTime64(3, 'UTC')
+    ",
+        &client,
+    )
+    .await;
+
+    let items = (0..4)
+        .map(|i| Row {
+            id: i,
+            name: format!("row {i}"),
+            score: if i % 2 == 0 {
+                Some(i as f64 / 2.0)
+            } else {
+                None
+            },
+            tags: vec!["a".to_string(); i as usize],
+            created_at: "2024-01-02T03:04:05.678Z".parse().unwrap(),
+        })
+        .collect::<Vec<_>>();
+
+    client
+        .insert_native_serde(
+            "INSERT INTO test_serde_row FORMAT Native",
+            futures_util::stream::iter(vec![items.clone()]),
+        )
+        .await
+        .unwrap();
+
+    let items2 = client
+        .query_serde::<Row>("SELECT * FROM test_serde_row ORDER BY id")
+        .await
+        .unwrap()
+        .map(|x| x.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(items, items2);
+}
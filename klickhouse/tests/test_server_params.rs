@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use klickhouse::{Bytes, QueryBuilder, UnitValue};
+
+#[tokio::test]
+async fn test_server_params() {
+    let client = super::get_client().await;
+
+    let names = vec!["a',\\".to_string(), "(\n)".to_string()];
+    let echoed = client
+        .query_one::<UnitValue<Vec<String>>>(
+            QueryBuilder::new("SELECT {names:Array(String)}").server_param("names", names.clone()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(echoed.0, names);
+
+    let map = HashMap::from([(
+        "k:}".to_string(),
+        (Some(5u32), Bytes(vec![0u8, 0xFF, b']'])),
+    )]);
+    let echoed = client
+        .query_one::<UnitValue<HashMap<String, (Option<u32>, Bytes)>>>(
+            QueryBuilder::new("SELECT {map:Map(String, Tuple(Nullable(UInt32), String))}")
+                .server_param("map", map.clone()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(echoed.0, map);
+
+    let count = client
+        .query_one::<UnitValue<u64>>(
+            QueryBuilder::new("SELECT count() FROM {table:Identifier} WHERE number < {n:UInt64}")
+                .server_param("table", "system.numbers LIMIT 10")
+                .server_param("n", 3u64),
+        )
+        .await;
+    // identifiers are names, not SQL
+    assert!(count.is_err());
+
+    let count = client
+        .query_one::<UnitValue<u64>>(
+            QueryBuilder::new(
+                "SELECT count() FROM (SELECT * FROM {table:Identifier} LIMIT 10) WHERE number < {n:UInt64}",
+            )
+            .server_param("table", "system.numbers")
+            .server_param("n", 3u64),
+        )
+        .await
+        .unwrap();
+    assert_eq!(count.0, 3);
+}
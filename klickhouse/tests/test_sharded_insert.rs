@@ -0,0 +1,63 @@
+use klickhouse::{bb8, ClientOptions, ConnectionManager, Row, ShardedInserter};
+
+#[derive(Row, Debug, Default)]
+pub struct Event {
+    device: u32,
+    seq: u32,
+}
+
+#[tokio::test]
+async fn test_sharded_insert() {
+    let _ = env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .try_init();
+    let client = super::get_client().await;
+    super::prepare_table(
+        "test_sharded_insert",
+        "device UInt32, seq UInt32, inserted DateTime64(9) DEFAULT now64(9)",
+        &client,
+    )
+    .await;
+
+    let address = std::env::var("KLICKHOUSE_TEST_ADDR").unwrap_or_else(|_| "127.0.0.1:9000".into());
+    let mut options = ClientOptions::default();
+    if let Ok(user) = std::env::var("KLICKHOUSE_TEST_USER") {
+        options.username = user;
+    }
+    if let Ok(password) = std::env::var("KLICKHOUSE_TEST_PASSWORD") {
+        options.password = password;
+    }
+    if let Ok(database) = std::env::var("KLICKHOUSE_TEST_DATABASE") {
+        options.default_database = database;
+    }
+    let manager = ConnectionManager::new(address, options).await.unwrap();
+    let pool = bb8::Pool::builder()
+        .max_size(4)
+        .build(manager)
+        .await
+        .unwrap();
+
+    let inserter = ShardedInserter::new(
+        pool,
+        "INSERT INTO test_sharded_insert (device, seq) FORMAT native",
+        4,
+        16,
+        |event: &Event| event.device,
+    )
+    .unwrap();
+    for seq in 0..100 {
+        for device in 0..8 {
+            inserter.send(Event { device, seq }).await.unwrap();
+        }
+    }
+    inserter.finish().await.unwrap();
+
+    // rows of each device were inserted in order
+    let out_of_order = client
+        .query_one::<klickhouse::UnitValue<u64>>(
+            "SELECT count() FROM (SELECT device, groupArray(seq) AS seqs FROM (SELECT * FROM test_sharded_insert ORDER BY inserted) GROUP BY device) WHERE seqs != arraySort(seqs) OR length(seqs) != 100",
+        )
+        .await
+        .unwrap();
+    assert_eq!(out_of_order.0, 0);
+}
@@ -0,0 +1,29 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use klickhouse::{ClientOptions, SlowQuery, SlowQueryHook};
+
+#[tokio::test]
+async fn test_slow_query_hook() {
+    let reported: Arc<Mutex<Vec<SlowQuery>>> = Default::default();
+    let hook_reported = reported.clone();
+    let client = super::get_client_with_options(ClientOptions {
+        slow_query_threshold: Some(Duration::from_millis(200)),
+        slow_query_hook: Some(SlowQueryHook::new(move |query| {
+            hook_reported.lock().unwrap().push(query.clone());
+        })),
+        ..Default::default()
+    })
+    .await;
+
+    client.execute("SELECT 1").await.unwrap();
+    assert!(reported.lock().unwrap().is_empty());
+
+    client.execute("SELECT sleep(1)").await.unwrap();
+    let reported = reported.lock().unwrap();
+    assert_eq!(reported.len(), 1);
+    assert_eq!(reported[0].query, "SELECT sleep(1)");
+    assert!(reported[0].elapsed >= Duration::from_millis(200));
+}
@@ -0,0 +1,70 @@
+use klickhouse::{ColumnDef, Row, Type};
+
+#[derive(Row, Debug, Clone, PartialEq)]
+struct Selection {
+    id: u64,
+    label: Option<String>,
+}
+
+#[tokio::test]
+async fn test_temp_table() {
+    let client = super::get_client().await;
+
+    let table = client
+        .create_temp_table(
+            "test_temp_table",
+            vec![
+                Selection { id: 2, label: None },
+                Selection {
+                    id: 5,
+                    label: Some("five".to_string()),
+                },
+            ],
+        )
+        .await
+        .unwrap();
+    let rows = table
+        .query_collect::<Selection>(
+            "SELECT t.number AS id, s.label AS label FROM numbers(10) AS t \
+            INNER JOIN test_temp_table AS s ON t.number = s.id ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Selection { id: 2, label: None },
+            Selection {
+                id: 5,
+                label: Some("five".to_string()),
+            },
+        ]
+    );
+    table.drop_table().await.unwrap();
+    assert!(client
+        .execute("SELECT * FROM test_temp_table")
+        .await
+        .is_err());
+
+    let table = client
+        .create_temp_table_with(
+            "test_temp_table_with",
+            vec![
+                ColumnDef::new("id", Type::UInt64),
+                ColumnDef::new("label", Type::Nullable(Box::new(Type::String))),
+            ],
+            Vec::<Selection>::new(),
+        )
+        .await
+        .unwrap();
+    let rows = table
+        .query_collect::<Selection>("SELECT * FROM test_temp_table_with")
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+    drop(table);
+    assert!(client
+        .execute("SELECT * FROM test_temp_table_with")
+        .await
+        .is_err());
+}
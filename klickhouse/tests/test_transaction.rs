@@ -0,0 +1,60 @@
+use klickhouse::{Client, Row, UnitValue};
+
+#[derive(Row, Debug, Default)]
+struct TestRow {
+    id: u32,
+}
+
+async fn count(client: &Client) -> u64 {
+    client
+        .query_one::<UnitValue<u64>>("SELECT count() FROM test_transaction")
+        .await
+        .unwrap()
+        .0
+}
+
+#[tokio::test]
+async fn test_transaction() {
+    let client = super::get_client().await;
+    client
+        .execute("DROP TABLE IF EXISTS test_transaction")
+        .await
+        .unwrap();
+    client
+        .execute("CREATE TABLE test_transaction (id UInt32) ENGINE = MergeTree ORDER BY id")
+        .await
+        .unwrap();
+
+    let transaction = client.begin_transaction().await.unwrap();
+    transaction
+        .insert_native_block(
+            "INSERT INTO test_transaction FORMAT native",
+            vec![TestRow { id: 1 }],
+        )
+        .await
+        .unwrap();
+    transaction.rollback().await.unwrap();
+    assert_eq!(count(&client).await, 0);
+
+    let transaction = client.begin_transaction().await.unwrap();
+    transaction
+        .insert_native_block(
+            "INSERT INTO test_transaction FORMAT native",
+            vec![TestRow { id: 2 }],
+        )
+        .await
+        .unwrap();
+    drop(transaction);
+    assert_eq!(count(&client).await, 0);
+
+    let transaction = client.begin_transaction().await.unwrap();
+    transaction
+        .insert_native_block(
+            "INSERT INTO test_transaction FORMAT native",
+            vec![TestRow { id: 3 }],
+        )
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+    assert_eq!(count(&client).await, 1);
+}
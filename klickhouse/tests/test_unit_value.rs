@@ -0,0 +1,49 @@
+use klickhouse::UnitValue;
+
+#[derive(klickhouse::Row, Debug, PartialEq)]
+struct Single {
+    name: String,
+}
+
+#[tokio::test]
+async fn unit_value() {
+    let client = super::get_client().await;
+
+    let count = client
+        .query_one::<UnitValue<u64>>("SELECT count() FROM numbers(10)")
+        .await
+        .unwrap();
+    assert_eq!(count.0, 10);
+
+    let values = client
+        .query_collect::<UnitValue<String>>(
+            "SELECT toLowCardinality(toString(number % 3)) FROM numbers(5)",
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|x| x.0)
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec!["0", "1", "2", "0", "1"]);
+
+    let rows = client
+        .query_collect::<Single>("SELECT toString(number) AS name FROM numbers(2)")
+        .await
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Single {
+                name: "0".to_string()
+            },
+            Single {
+                name: "1".to_string()
+            }
+        ]
+    );
+
+    assert!(client
+        .query_one::<Single>("SELECT 'x' AS other")
+        .await
+        .is_err());
+}
@@ -0,0 +1,88 @@
+use klickhouse::{QualifiedTable, Row, UpsertMerge};
+
+#[derive(Row, Debug, Clone, PartialEq)]
+struct Account {
+    tenant: String,
+    id: u64,
+    balance: i64,
+    version: u64,
+}
+
+#[derive(Row, Debug, Clone)]
+struct AccountWithoutVersion {
+    tenant: String,
+    id: u64,
+    balance: i64,
+}
+
+#[tokio::test]
+async fn test_upsert_replacing() {
+    let client = super::get_client().await;
+
+    client
+        .execute("DROP TABLE IF EXISTS test_upsert")
+        .await
+        .unwrap();
+    client
+        .execute(
+            "CREATE TABLE test_upsert (tenant String, id UInt64, balance Int64, version UInt64) \
+            ENGINE = ReplacingMergeTree(version) ORDER BY (tenant, id)",
+        )
+        .await
+        .unwrap();
+
+    let account = |id, balance, version| Account {
+        tenant: "acme".to_string(),
+        id,
+        balance,
+        version,
+    };
+    client
+        .upsert_replacing(
+            "test_upsert",
+            vec![account(1, 10, 1), account(2, 20, 1)],
+            "version",
+        )
+        .await
+        .unwrap();
+    client
+        .upsert_replacing_with(
+            QualifiedTable::new("test_upsert"),
+            vec![account(1, 15, 2)],
+            "version",
+            UpsertMerge::Table,
+        )
+        .await
+        .unwrap();
+
+    let rows = client
+        .query_collect::<Account>("SELECT * FROM test_upsert ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![account(1, 15, 2), account(2, 20, 1)]);
+
+    assert!(client
+        .upsert_replacing(
+            "test_upsert",
+            vec![AccountWithoutVersion {
+                tenant: "acme".to_string(),
+                id: 1,
+                balance: 0,
+            }],
+            "version",
+        )
+        .await
+        .is_err());
+    assert!(client
+        .upsert_replacing("test_upsert", vec![account(1, 0, 3)], "revision")
+        .await
+        .is_err());
+    assert!(client
+        .upsert_replacing(
+            QualifiedTable::with_database("system", "one"),
+            vec![account(1, 0, 3)],
+            "version"
+        )
+        .await
+        .is_err());
+}
@@ -0,0 +1,31 @@
+use klickhouse::{QueryOptions, UnitValue, Utf8Policy};
+
+async fn read<T: klickhouse::Row>(policy: Utf8Policy) -> klickhouse::Result<T> {
+    let client = super::get_client().await;
+    client
+        .query_one_with_options(
+            "SELECT [unhex('41FF'), 'b']",
+            QueryOptions::new().utf8(policy),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn test_utf8_policy() {
+    let bytes = read::<UnitValue<Vec<Vec<u8>>>>(Utf8Policy::Bytes)
+        .await
+        .unwrap();
+    assert_eq!(bytes.0, vec![vec![0x41, 0xFF], b"b".to_vec()]);
+    assert!(read::<UnitValue<Vec<String>>>(Utf8Policy::Bytes)
+        .await
+        .is_err());
+
+    assert!(read::<UnitValue<Vec<Vec<u8>>>>(Utf8Policy::Error)
+        .await
+        .is_err());
+
+    let lossy = read::<UnitValue<Vec<String>>>(Utf8Policy::Lossy)
+        .await
+        .unwrap();
+    assert_eq!(lossy.0, vec!["A\u{FFFD}".to_string(), "b".to_string()]);
+}
@@ -0,0 +1,67 @@
+use klickhouse::{QueryOptions, Value};
+
+#[derive(klickhouse::Row, Debug, Clone, PartialEq)]
+struct Row {
+    id: u32,
+    variant: Value,
+    dynamic: Value,
+}
+
+#[tokio::test]
+async fn test_variant() {
+    let client = super::get_client().await;
+
+    client
+        .execute("DROP TABLE IF EXISTS test_variant")
+        .await
+        .unwrap();
+    client
+        .execute_with_options(
+            "CREATE TABLE test_variant (id UInt32, variant Variant(String, Int64), dynamic Dynamic) ENGINE = Memory",
+            // needed before the types left their experimental stage
+            QueryOptions::new()
+                .optional_setting("allow_experimental_variant_type", 1)
+                .optional_setting("allow_experimental_dynamic_type", 1),
+        )
+        .await
+        .unwrap();
+
+    let rows = vec![
+        Row {
+            id: 1,
+            variant: Value::Int64(5),
+            dynamic: Value::string("text"),
+        },
+        Row {
+            id: 2,
+            variant: Value::Variant(Box::new(Value::string("x")), 1),
+            dynamic: Value::Array(vec![Value::UInt8(1), Value::UInt8(2)]),
+        },
+        Row {
+            id: 3,
+            variant: Value::Null,
+            dynamic: Value::Null,
+        },
+    ];
+    client
+        .insert_native_block("INSERT INTO test_variant FORMAT Native", rows.clone())
+        .await
+        .unwrap();
+
+    let received = client
+        .query_collect::<Row>("SELECT * FROM test_variant ORDER BY id")
+        .await
+        .unwrap();
+    assert_eq!(
+        received,
+        [
+            Row {
+                id: 1,
+                variant: Value::Variant(Box::new(Value::Int64(5)), 0),
+                dynamic: Value::string("text"),
+            },
+            rows[1].clone(),
+            rows[2].clone(),
+        ]
+    );
+}
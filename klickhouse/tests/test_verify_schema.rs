@@ -0,0 +1,61 @@
+use klickhouse::{ColumnMismatch, Type};
+
+use super::prepare_table;
+
+#[derive(klickhouse::Row, Debug)]
+struct Row {
+    id: u32,
+    name: String,
+    count: u64,
+    #[klickhouse(nested)]
+    tags: Vec<Tag>,
+    #[klickhouse(parse)]
+    address: std::net::IpAddr,
+    gone: Option<String>,
+}
+
+#[derive(klickhouse::Row, Debug, Default)]
+struct Tag {
+    key: String,
+    value: u8,
+}
+
+#[tokio::test]
+async fn test_verify_schema() {
+    let client = super::get_client().await;
+    prepare_table(
+        "test_verify_schema",
+        "id UInt32, name LowCardinality(String), count Nullable(UInt64), tags Nested(key String, value Float32), address String, added Date",
+        &client,
+    )
+    .await;
+
+    let diff = client
+        .verify_schema::<Row>("test_verify_schema")
+        .await
+        .unwrap();
+    assert!(!diff.is_empty());
+    assert_eq!(diff.missing, vec!["gone".to_string()]);
+    assert_eq!(diff.extra, vec![("added".to_string(), Type::Date)]);
+    assert_eq!(
+        diff.mismatched,
+        vec![
+            ColumnMismatch {
+                name: "count".to_string(),
+                rust_type: "u64",
+                column_type: "Nullable(UInt64)".parse().unwrap(),
+            },
+            ColumnMismatch {
+                name: "tags.value".to_string(),
+                rust_type: "u8",
+                column_type: "Array(Float32)".parse().unwrap(),
+            },
+        ]
+    );
+
+    // rows that don't list their column types can't be verified
+    assert!(client
+        .verify_schema::<klickhouse::RawRow>("test_verify_schema")
+        .await
+        .is_err());
+}
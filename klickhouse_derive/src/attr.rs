@@ -119,6 +119,7 @@ pub struct Container {
     type_try_from: Option<syn::Type>,
     type_into: Option<syn::Type>,
     is_packed: bool,
+    enum_string: bool,
 }
 
 impl Container {
@@ -132,6 +133,7 @@ impl Container {
         let mut type_from = Attr::none(cx, FROM);
         let mut type_try_from = Attr::none(cx, TRY_FROM);
         let mut type_into = Attr::none(cx, INTO);
+        let mut enum_string = BoolAttr::none(cx, ENUM_STRING);
 
         for meta_item in item
             .attrs
@@ -172,6 +174,15 @@ impl Container {
                     deny_unknown_fields.set_true(word);
                 }
 
+                // Parse `#[klickhouse(enum_string)]`
+                Meta::Path(word) if word == ENUM_STRING => match &item.data {
+                    syn::Data::Enum(_) => enum_string.set_true(word),
+                    _ => cx.error_spanned_by(
+                        word,
+                        "#[klickhouse(enum_string)] can only be used on enums",
+                    ),
+                },
+
                 // Parse `#[klickhouse(default)]`
                 Meta::Path(word) if word == DEFAULT => match &item.data {
                     syn::Data::Struct(syn::DataStruct { fields, .. }) => match fields {
@@ -311,6 +322,7 @@ impl Container {
             type_try_from: type_try_from.get(),
             type_into: type_into.get(),
             is_packed,
+            enum_string: enum_string.get(),
         }
     }
 
@@ -345,6 +357,68 @@ impl Container {
     pub fn is_packed(&self) -> bool {
         self.is_packed
     }
+
+    pub fn enum_string(&self) -> bool {
+        self.enum_string
+    }
+}
+
+/// Represents variant attribute information
+pub struct Variant {
+    name: Name,
+}
+
+impl Variant {
+    /// Extract out the `#[klickhouse(...)]` attributes from an enum variant.
+    pub fn from_ast(cx: &Ctxt, variant: &syn::Variant) -> Self {
+        let mut rename = Attr::none(cx, RENAME);
+
+        for meta_item in variant
+            .attrs
+            .iter()
+            .flat_map(|attr| get_klickhouse_meta_items(cx, attr))
+            .flatten()
+        {
+            match &meta_item {
+                // Parse `#[klickhouse(rename = "foo")]`
+                Meta::NameValue(m) if m.path == RENAME => {
+                    let Expr::Lit(expr_lit) = &m.value else {
+                        continue;
+                    };
+
+                    if let Ok(s) = get_lit_str(cx, RENAME, &expr_lit.lit) {
+                        rename.set(&m.path, s.value());
+                    }
+                }
+
+                meta_item => {
+                    let path = meta_item
+                        .path()
+                        .into_token_stream()
+                        .to_string()
+                        .replace(' ', "");
+                    cx.error_spanned_by(
+                        meta_item.path(),
+                        format!("unknown klickhouse variant attribute `{}`", path),
+                    );
+                }
+            }
+        }
+
+        Variant {
+            name: Name::from_attrs(unraw(&variant.ident), rename),
+        }
+    }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn rename_by_rules(&mut self, rules: &RenameRule) {
+        if !self.name.renamed {
+            self.name.name = rules.apply_to_variant(&self.name.name);
+        }
+    }
 }
 
 /// Represents field attribute information
@@ -358,6 +432,10 @@ pub struct Field {
     bound: Option<Vec<syn::WherePredicate>>,
     nested: bool,
     flatten: bool,
+    sign: bool,
+    datetime_overflow: Option<Ident>,
+    nested_separator: Option<String>,
+    nested_rename_all: Option<RenameRule>,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -384,10 +462,14 @@ impl Field {
         let mut skip_serializing = BoolAttr::none(cx, SKIP_SERIALIZING);
         let mut skip_deserializing = BoolAttr::none(cx, SKIP_DESERIALIZING);
         let mut flatten = BoolAttr::none(cx, FLATTEN);
+        let mut sign = BoolAttr::none(cx, SIGN);
         let mut default = Attr::none(cx, DEFAULT);
         let mut serialize_with = Attr::none(cx, SERIALIZE_WITH);
         let mut deserialize_with = Attr::none(cx, DESERIALIZE_WITH);
         let mut bound = Attr::none(cx, BOUND);
+        let mut datetime_overflow = Attr::none(cx, DATETIME_OVERFLOW);
+        let mut nested_separator = Attr::none(cx, NESTED_SEPARATOR);
+        let mut nested_rename_all = Attr::none(cx, NESTED_RENAME_ALL);
 
         let ident = match &field.ident {
             Some(ident) => unraw(ident),
@@ -443,6 +525,11 @@ impl Field {
                     flatten.set_true(word);
                 }
 
+                // Parse `#[klickhouse(sign)]`
+                Meta::Path(word) if word == SIGN => {
+                    sign.set_true(word);
+                }
+
                 // Parse `#[klickhouse(skip_deserializing)]`
                 Meta::Path(word) if word == SKIP_DESERIALIZING => {
                     skip_deserializing.set_true(word);
@@ -499,6 +586,18 @@ impl Field {
                     }
                 }
 
+                // Parse `#[klickhouse(parse)]`
+                Meta::Path(word) if word == PARSE => {
+                    serialize_with.set(
+                        word,
+                        syn::parse_quote!(::klickhouse::helpers::parse::to_sql),
+                    );
+                    deserialize_with.set(
+                        word,
+                        syn::parse_quote!(::klickhouse::helpers::parse::from_sql),
+                    );
+                }
+
                 // Parse `#[klickhouse(bound = "T: SomeBound")]`
                 Meta::NameValue(m) if m.path == BOUND => {
                     let Expr::Lit(expr_lit) = &m.value else {
@@ -512,6 +611,54 @@ impl Field {
                     }
                 }
 
+                // Parse `#[klickhouse(datetime_overflow = "clamp")]`
+                Meta::NameValue(m) if m.path == DATETIME_OVERFLOW => {
+                    let Expr::Lit(expr_lit) = &m.value else {
+                        continue;
+                    };
+
+                    if let Ok(s) = get_lit_str(cx, DATETIME_OVERFLOW, &expr_lit.lit) {
+                        let variant = match &*s.value() {
+                            "error" => "Error",
+                            "clamp" => "Clamp",
+                            "null" => "Null",
+                            other => {
+                                cx.error_spanned_by(
+                                    s,
+                                    format!("unknown datetime_overflow policy `{}`, expected `error`, `clamp` or `null`", other),
+                                );
+                                continue;
+                            }
+                        };
+                        datetime_overflow.set(&m.path, Ident::new(variant, s.span()));
+                    }
+                }
+
+                // Parse `#[klickhouse(nested_separator = "_")]`
+                Meta::NameValue(m) if m.path == NESTED_SEPARATOR => {
+                    let Expr::Lit(expr_lit) = &m.value else {
+                        continue;
+                    };
+
+                    if let Ok(s) = get_lit_str(cx, NESTED_SEPARATOR, &expr_lit.lit) {
+                        nested_separator.set(&m.path, s.value());
+                    }
+                }
+
+                // Parse `#[klickhouse(nested_rename_all = "foo")]`
+                Meta::NameValue(m) if m.path == NESTED_RENAME_ALL => {
+                    let Expr::Lit(expr_lit) = &m.value else {
+                        continue;
+                    };
+
+                    if let Ok(s) = get_lit_str(cx, NESTED_RENAME_ALL, &expr_lit.lit) {
+                        match RenameRule::from_str(&s.value()) {
+                            Ok(rule) => nested_rename_all.set(&m.path, rule),
+                            Err(err) => cx.error_spanned_by(s, err),
+                        }
+                    }
+                }
+
                 meta_item => {
                     let path = meta_item
                         .path()
@@ -545,6 +692,10 @@ impl Field {
             bound: bound.get(),
             nested: nested.get(),
             flatten: flatten.get(),
+            sign: sign.get(),
+            datetime_overflow: datetime_overflow.get(),
+            nested_separator: nested_separator.get(),
+            nested_rename_all: nested_rename_all.get(),
         }
     }
 
@@ -566,6 +717,10 @@ impl Field {
         self.nested
     }
 
+    pub fn sign(&self) -> bool {
+        self.sign
+    }
+
     pub fn skip_serializing(&self) -> bool {
         self.skip_serializing
     }
@@ -589,6 +744,23 @@ impl Field {
     pub fn bound(&self) -> Option<&[syn::WherePredicate]> {
         self.bound.as_ref().map(|vec| &vec[..])
     }
+
+    /// The `DateTimeOverflow` variant written timestamps are wrapped with.
+    pub fn datetime_overflow(&self) -> Option<&Ident> {
+        self.datetime_overflow.as_ref()
+    }
+
+    /// The separator and rename rule joining the field's name with the column names of a nested struct,
+    /// if either differs from the default of `.` and unchanged names.
+    pub fn nested_naming(&self) -> Option<(&str, RenameRule)> {
+        if self.nested_separator.is_none() && self.nested_rename_all.is_none() {
+            return None;
+        }
+        Some((
+            self.nested_separator.as_deref().unwrap_or("."),
+            self.nested_rename_all.unwrap_or(RenameRule::None),
+        ))
+    }
 }
 
 pub fn get_klickhouse_meta_items(cx: &Ctxt, attr: &syn::Attribute) -> Result<Vec<syn::Meta>, ()> {
@@ -5,6 +5,8 @@
 #[allow(deprecated, unused_imports)]
 use std::ascii::AsciiExt;
 
+use proc_macro2::TokenStream;
+use quote::quote;
 use std::fmt::{self, Debug, Display};
 
 use self::RenameRule::*;
@@ -47,7 +49,7 @@ static RENAME_RULES: &[(&str, RenameRule)] = &[
 ];
 
 impl RenameRule {
-    pub fn from_str(rename_all_str: &str) -> Result<Self, ParseError> {
+    pub fn from_str(rename_all_str: &str) -> Result<Self, ParseError<'_>> {
         for (name, rule) in RENAME_RULES {
             if rename_all_str == *name {
                 return Ok(*rule);
@@ -58,6 +60,47 @@ impl RenameRule {
         })
     }
 
+    /// The matching `klickhouse::RenameRule`, for rules applied at runtime.
+    pub fn runtime_rule(&self) -> TokenStream {
+        let variant = match *self {
+            None => quote!(None),
+            LowerCase => quote!(LowerCase),
+            UpperCase => quote!(UpperCase),
+            PascalCase => quote!(PascalCase),
+            CamelCase => quote!(CamelCase),
+            SnakeCase => quote!(SnakeCase),
+            ScreamingSnakeCase => quote!(ScreamingSnakeCase),
+            KebabCase => quote!(KebabCase),
+            ScreamingKebabCase => quote!(ScreamingKebabCase),
+        };
+        quote!(::klickhouse::RenameRule::#variant)
+    }
+
+    /// Apply a renaming rule to an enum variant, returning the version expected in the source.
+    pub fn apply_to_variant(&self, variant: &str) -> String {
+        match *self {
+            None | PascalCase => variant.to_owned(),
+            LowerCase => variant.to_ascii_lowercase(),
+            UpperCase => variant.to_ascii_uppercase(),
+            CamelCase => variant[..1].to_ascii_lowercase() + &variant[1..],
+            SnakeCase => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            ScreamingSnakeCase => SnakeCase.apply_to_variant(variant).to_ascii_uppercase(),
+            KebabCase => SnakeCase.apply_to_variant(variant).replace('_', "-"),
+            ScreamingKebabCase => ScreamingSnakeCase
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
     /// Apply a renaming rule to a struct field, returning the version expected in the source.
     pub fn apply_to_field(&self, field: &str) -> String {
         match *self {
@@ -5,6 +5,9 @@ use crate::ctxt::Ctxt;
 /// object. Simpler checks should happen when parsing and building the attrs.
 pub fn check(cx: &Ctxt, cont: &mut Container) {
     check_from_and_try_from(cx, cont);
+    check_sign(cx, cont);
+    check_datetime_overflow(cx, cont);
+    check_nested_naming(cx, cont);
 }
 
 fn check_from_and_try_from(cx: &Ctxt, cont: &mut Container) {
@@ -15,3 +18,47 @@ fn check_from_and_try_from(cx: &Ctxt, cont: &mut Container) {
         );
     }
 }
+
+fn check_sign(cx: &Ctxt, cont: &mut Container) {
+    let mut signs = cont.data.iter().filter(|field| field.attrs.sign());
+    if let Some(field) = signs.next() {
+        if field.attrs.flatten() || field.attrs.nested() || field.attrs.skip_serializing() {
+            cx.error_spanned_by(
+                field.original,
+                "#[klickhouse(sign)] cannot be used on flattened, nested or skipped fields",
+            );
+        }
+    }
+    if let Some(field) = signs.next() {
+        cx.error_spanned_by(
+            field.original,
+            "#[klickhouse(sign)] can only be used on one field",
+        );
+    }
+}
+
+fn check_datetime_overflow(cx: &Ctxt, cont: &mut Container) {
+    for field in cont.data.iter() {
+        if field.attrs.datetime_overflow().is_some()
+            && (field.attrs.serialize_with().is_some()
+                || field.attrs.flatten()
+                || field.attrs.nested())
+        {
+            cx.error_spanned_by(
+                field.original,
+                "#[klickhouse(datetime_overflow = \"...\")] cannot be used on flattened, nested or custom serialized fields",
+            );
+        }
+    }
+}
+
+fn check_nested_naming(cx: &Ctxt, cont: &mut Container) {
+    for field in cont.data.iter() {
+        if field.attrs.nested_naming().is_some() && !field.attrs.nested() {
+            cx.error_spanned_by(
+                field.original,
+                "#[klickhouse(nested_separator = \"...\")] and #[klickhouse(nested_rename_all = \"...\")] can only be used on nested fields",
+            );
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::attr;
+use crate::ctxt::Ctxt;
+use crate::dummy;
+use proc_macro2::TokenStream;
+
+pub fn expand_derive_enum(input: &syn::DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
+    let ctxt = Ctxt::new();
+    let container = attr::Container::from_ast(&ctxt, input);
+    let variants = match &input.data {
+        syn::Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                if !matches!(variant.fields, syn::Fields::Unit) {
+                    ctxt.error_spanned_by(
+                        variant,
+                        "Klickhouse ClickhouseEnum only supports unit variants",
+                    );
+                }
+                let mut attrs = attr::Variant::from_ast(&ctxt, variant);
+                attrs.rename_by_rules(container.rename_all_rule());
+                (&variant.ident, attrs.name().name())
+            })
+            .collect::<Vec<_>>(),
+        _ => {
+            ctxt.error_spanned_by(input, "Klickhouse ClickhouseEnum only supports enums");
+            vec![]
+        }
+    };
+    ctxt.check()?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let idents = variants.iter().map(|(ident, _)| ident).collect::<Vec<_>>();
+    let names = variants.iter().map(|(_, name)| name).collect::<Vec<_>>();
+    let accepted_types = if container.enum_string() {
+        quote! {
+            ::klickhouse::Type::String
+                | ::klickhouse::Type::FixedString(_)
+                | ::klickhouse::Type::Enum8(_)
+                | ::klickhouse::Type::Enum16(_)
+        }
+    } else {
+        quote! { ::klickhouse::Type::Enum8(_) | ::klickhouse::Type::Enum16(_) }
+    };
+
+    let impl_block = quote! {
+        #[automatically_derived]
+        impl #impl_generics ::klickhouse::FromSql for #ident #ty_generics #where_clause {
+            fn from_sql(type_: &::klickhouse::Type, value: ::klickhouse::Value) -> ::klickhouse::Result<Self> {
+                let type_ = type_.strip_low_cardinality();
+                if !matches!(type_, #accepted_types) {
+                    return Err(::klickhouse::unexpected_type(type_));
+                }
+                let name = <::std::string::String as ::klickhouse::FromSql>::from_sql(type_, value)?;
+                // FixedString values are padded with NUL bytes up to their length
+                let name = match type_ {
+                    ::klickhouse::Type::FixedString(_) => name.trim_end_matches('\0'),
+                    _ => &*name,
+                };
+                match name {
+                    #(#names => Ok(Self::#idents),)*
+                    name => Err(::klickhouse::KlickhouseError::DeserializeError(format!(
+                        "unknown variant '{}' for {}",
+                        name,
+                        stringify!(#ident),
+                    ))),
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::klickhouse::ToSql for #ident #ty_generics #where_clause {
+            fn to_sql(self, type_hint: Option<&::klickhouse::Type>) -> ::klickhouse::Result<::klickhouse::Value> {
+                let name: &str = match self {
+                    #(Self::#idents => #names,)*
+                };
+                <&str as ::klickhouse::ToSql>::to_sql(name, type_hint)
+            }
+        }
+    };
+
+    Ok(dummy::wrap_in_const(impl_block))
+}
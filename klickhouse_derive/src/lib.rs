@@ -10,6 +10,7 @@ mod case;
 mod check;
 mod ctxt;
 mod dummy;
+mod enums;
 mod fragment;
 mod internal;
 mod receiver;
@@ -32,3 +33,11 @@ pub fn derive_serialize(input: TokenStream) -> TokenStream {
         .unwrap_or_else(to_compile_errors)
         .into()
 }
+
+#[proc_macro_derive(ClickhouseEnum, attributes(klickhouse))]
+pub fn derive_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    enums::expand_derive_enum(&input)
+        .unwrap_or_else(to_compile_errors)
+        .into()
+}
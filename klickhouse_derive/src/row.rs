@@ -96,14 +96,31 @@ pub fn expand_derive_serialize(
     let params = Parameters::new(&cont);
     let (impl_generics, ty_generics, where_clause) = params.generics.split_for_impl();
     let deserialize_body = Stmts(deserialize_body(&cont, &params));
+    let deserialize_accessor_fn = deserialize_accessor_fn(&cont, &params);
     let column_names_body = Stmts(column_names_body(&cont, &params));
+    let column_types_body = Stmts(column_types_body(&cont, &params));
     let serialize_body = Stmts(serialize_body(&cont, &params));
+    let serialize_into_fn = serialize_into_fn(&cont, &params);
     let serialize_length_body = if flatten {
         Stmts(Fragment::Block(quote! { None }))
     } else {
         Stmts(serialize_length_body(&cont, &params))
     };
     let const_column_count_fn = format_ident!("__{ident}_column_count_klickhouse");
+    let collapsing_impl = match cont.data.iter().find(|field| field.attrs.sign()) {
+        Some(field) => {
+            let member = &field.member;
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics ::klickhouse::CollapsingRow for #ident #ty_generics #where_clause {
+                    fn set_sign(&mut self, sign: i8) {
+                        self.#member = ::core::convert::From::<i8>::from(sign);
+                    }
+                }
+            }
+        }
+        None => quote! {},
+    };
 
     let impl_block = quote! {
         #[doc(hidden)]
@@ -125,14 +142,24 @@ pub fn expand_derive_serialize(
                 #column_names_body
             }
 
+            fn column_types() -> Option<Vec<::klickhouse::ExpectedColumn>> {
+                #column_types_body
+            }
+
             fn deserialize_row(map: Vec<(&str, &::klickhouse::Type, ::klickhouse::Value)>) -> ::klickhouse::Result<Self> {
                 #deserialize_body
             }
 
+            #deserialize_accessor_fn
+
             fn serialize_row(self, type_hints: &::klickhouse::IndexMap<String, ::klickhouse::Type>) -> ::klickhouse::Result<Vec<(::std::borrow::Cow<'static, str>, ::klickhouse::Value)>> {
                 #serialize_body
             }
+
+            #serialize_into_fn
         }
+
+        #collapsing_impl
     };
 
     Ok(dummy::wrap_in_const(impl_block))
@@ -209,7 +236,8 @@ fn column_names_body(cont: &Container, _params: &Parameters) -> Fragment {
                 let ty = field.ty;
                 if field.attrs.nested() {
                     let field_ty = unwrap_vec_type(field.ty).expect("invalid non-Vec nested type");
-                    quote! { out.extend(<#field_ty as ::klickhouse::Row>::column_names()?.into_iter().map(|x| ::std::borrow::Cow::Owned(format!("{}.{}", #name, x)))); }
+                    let column_name = nested_column_name(field, quote! { &x });
+                    quote! { out.extend(<#field_ty as ::klickhouse::Row>::column_names()?.into_iter().map(|x| ::std::borrow::Cow::Owned(#column_name))); }
                 } else if field.attrs.flatten(){
                     quote! { out.extend(#ty::column_names()?); }
                 } else {
@@ -224,6 +252,49 @@ fn column_names_body(cont: &Container, _params: &Parameters) -> Fragment {
     }
 }
 
+/// Expression for the column name of a `nested` field's column with the inner name `inner`, a `&str` expression.
+fn nested_column_name(field: &Field, inner: TokenStream) -> TokenStream {
+    let name = field.attrs.name().name();
+    match field.attrs.nested_naming() {
+        Some((separator, rename_all)) => {
+            let rename_all = rename_all.runtime_rule();
+            quote! { ::klickhouse::nested_column_name(#name, #separator, #rename_all, #inner) }
+        }
+        None => quote! { format!("{}.{}", #name, #inner) },
+    }
+}
+
+fn column_types_body(cont: &Container, _params: &Parameters) -> Fragment {
+    if cont.attrs.type_into().is_some()
+        || cont.attrs.type_from().is_some()
+        || cont.attrs.type_try_from().is_some()
+    {
+        Fragment::Expr(quote! { None })
+    } else {
+        let type_sources = cont.data.iter().filter(|&field| !field.attrs.skip_serializing())
+            .map(|field| {
+                let name = field.attrs.name().name();
+                let ty = field.ty;
+                if field.attrs.nested() {
+                    let field_ty = unwrap_vec_type(field.ty).expect("invalid non-Vec nested type");
+                    let column_name = nested_column_name(field, quote! { &x.name });
+                    quote! { out.extend(<#field_ty as ::klickhouse::Row>::column_types()?.into_iter().map(|x| { let name = #column_name; x.nested(name) })); }
+                } else if field.attrs.flatten() {
+                    quote! { out.extend(<#ty as ::klickhouse::Row>::column_types()?); }
+                } else if field.attrs.skip_deserializing() || field.attrs.deserialize_with().is_some() {
+                    quote! { out.push(::klickhouse::ExpectedColumn::any::<#ty>(#name)); }
+                } else {
+                    quote! { out.push(::klickhouse::ExpectedColumn::of::<#ty>(#name)); }
+                }
+            }).collect::<Vec<_>>();
+        Fragment::Block(quote! {
+            let mut out = ::std::vec::Vec::new();
+            #(#type_sources)*
+            Some(out)
+        })
+    }
+}
+
 fn serialize_into(params: &Parameters, type_into: &syn::Type) -> Fragment {
     let self_var = &params.self_var;
     quote_block! {
@@ -274,6 +345,8 @@ fn serialize_struct_visitor(fields: &[Field], params: &Parameters) -> Vec<TokenS
                 None => {
                     if field.attrs.nested() {
                         let field_ty = unwrap_vec_type(field.ty).expect("invalid non-Vec nested type");
+                        let first_column_name = nested_column_name(field, quote! { &name });
+                        let empty_column_name = nested_column_name(field, quote! { column_names.as_ref().unwrap().get(i).expect("missing column_name for nested struct") });
                         quote! {
                             {
                                 let inner_length = <#field_ty as ::klickhouse::Row>::COLUMN_COUNT.expect("nested structure must have known length");
@@ -286,7 +359,7 @@ fn serialize_struct_visitor(fields: &[Field], params: &Parameters) -> Vec<TokenS
                                     assert_eq!(columns.len(), inner_length);
                                     for (i, (name, value)) in columns.into_iter().enumerate() {
                                         if outputs[i].0.is_none()  {
-                                            outputs[i].0 = Some(format!("{}.{}", #key_expr, name).into());
+                                            outputs[i].0 = Some(#first_column_name.into());
                                         }
                                         outputs[i].1.push(value);
                                     }
@@ -299,7 +372,7 @@ fn serialize_struct_visitor(fields: &[Field], params: &Parameters) -> Vec<TokenS
                                             if column_names.is_none() {
                                                 column_names = Some(<#field_ty as ::klickhouse::Row>::column_names().expect("column_names required for empty nested serialization"));
                                             }
-                                            format!("{}.{}", #key_expr, column_names.as_ref().unwrap().get(i).expect("missing column_name for nested struct")).into()
+                                            #empty_column_name.into()
                                         }
                                     };
                                     out.push((name, ::klickhouse::Value::Array(values)));
@@ -312,6 +385,11 @@ fn serialize_struct_visitor(fields: &[Field], params: &Parameters) -> Vec<TokenS
                             out.extend(#field_expr.serialize_row(&type_hints)?);
                         }
                     }
+                    else if let Some(policy) = field.attrs.datetime_overflow() {
+                        quote! {
+                            out.push((::std::borrow::Cow::Borrowed(#key_expr), ::klickhouse::ToSql::to_sql(::klickhouse::DateTimeWithOverflow(#field_expr, ::klickhouse::DateTimeOverflow::#policy), type_hints.get(#key_expr))?));
+                        }
+                    }
                     else {
                         quote! {
                             out.push((::std::borrow::Cow::Borrowed(#key_expr), <#field_ty as ::klickhouse::ToSql>::to_sql(#field_expr, type_hints.get(#key_expr))?));
@@ -323,6 +401,48 @@ fn serialize_struct_visitor(fields: &[Field], params: &Parameters) -> Vec<TokenS
         .collect()
 }
 
+/// Pushes each column straight into the insert block rather than through `serialize_row`.
+/// Nested fields are pivoted from rows into array columns, so those structs keep the default implementation.
+fn serialize_into_fn(cont: &Container, params: &Parameters) -> Option<TokenStream> {
+    if cont.attrs.type_into().is_some() || cont.data.iter().any(|field| field.attrs.nested()) {
+        return None;
+    }
+    let push_fields = cont
+        .data
+        .iter()
+        .filter(|&field| !field.attrs.skip_serializing())
+        .map(|field| {
+            let field_expr = get_member(params, &field.member);
+            let key_expr = field.attrs.name().name();
+            let field_ty = &field.ty;
+            if let Some(path) = field.attrs.serialize_with() {
+                quote! {
+                    columns.push(#key_expr, #path(#field_expr)?)?;
+                }
+            } else if field.attrs.flatten() {
+                quote! {
+                    ::klickhouse::Row::serialize_into(#field_expr, columns)?;
+                }
+            } else if let Some(policy) = field.attrs.datetime_overflow() {
+                quote! {
+                    let value = ::klickhouse::ToSql::to_sql(::klickhouse::DateTimeWithOverflow(#field_expr, ::klickhouse::DateTimeOverflow::#policy), columns.type_hint(#key_expr))?;
+                    columns.push(#key_expr, value)?;
+                }
+            } else {
+                quote! {
+                    let value = <#field_ty as ::klickhouse::ToSql>::to_sql(#field_expr, columns.type_hint(#key_expr))?;
+                    columns.push(#key_expr, value)?;
+                }
+            }
+        });
+    Some(quote! {
+        fn serialize_into(self, columns: &mut ::klickhouse::ColumnWriters<'_>) -> ::klickhouse::Result<()> {
+            #(#push_fields)*
+            ::klickhouse::Result::Ok(())
+        }
+    })
+}
+
 fn get_member(params: &Parameters, member: &Member) -> TokenStream {
     let self_var = &params.self_var;
     if params.is_packed {
@@ -338,7 +458,41 @@ fn deserialize_body(cont: &Container, params: &Parameters) -> Fragment {
     } else if let Some(type_try_from) = cont.attrs.type_try_from() {
         deserialize_try_from(type_try_from)
     } else {
-        deserialize_struct(params, &cont.data[..], &cont.attrs)
+        deserialize_struct(params, &cont.data[..], &cont.attrs, false)
+    }
+}
+
+/// Reads the row's columns in place rather than through `deserialize_row`, matching columns to fields once per block.
+fn deserialize_accessor_fn(cont: &Container, params: &Parameters) -> TokenStream {
+    let body = if let Some(type_from) = cont.attrs.type_from() {
+        quote_block! {
+            ::klickhouse::Result::map(
+                <#type_from as ::klickhouse::Row>::deserialize_accessor(row),
+                ::std::convert::From::from)
+        }
+    } else if let Some(type_try_from) = cont.attrs.type_try_from() {
+        quote_block! {
+            ::klickhouse::Result::and_then(
+                <#type_try_from as ::klickhouse::Row>::deserialize_accessor(row),
+                |v| ::std::convert::TryFrom::try_from(v).map_err(::std::convert::Into::into))
+        }
+    } else {
+        let body = Stmts(deserialize_struct(
+            params,
+            &cont.data[..],
+            &cont.attrs,
+            true,
+        ));
+        quote_block! {
+            let mut row = row;
+            #body
+        }
+    };
+    let body = Stmts(body);
+    quote! {
+        fn deserialize_accessor(row: ::klickhouse::RowAccessor<'_>) -> ::klickhouse::Result<Self> {
+            #body
+        }
     }
 }
 
@@ -358,7 +512,12 @@ fn deserialize_try_from(type_try_from: &syn::Type) -> Fragment {
     }
 }
 
-fn deserialize_struct(params: &Parameters, fields: &[Field], cattrs: &attr::Container) -> Fragment {
+fn deserialize_struct(
+    params: &Parameters,
+    fields: &[Field],
+    cattrs: &attr::Container,
+    accessor: bool,
+) -> Fragment {
     let this = &params.this;
     // let (de_impl_generics, de_ty_generics, ty_generics, where_clause) =
     //     split_with_de_lifetime(params);
@@ -367,7 +526,7 @@ fn deserialize_struct(params: &Parameters, fields: &[Field], cattrs: &attr::Cont
 
     let type_path = construct;
 
-    let visit_map = deserialize_map(&type_path, fields, cattrs);
+    let visit_map = deserialize_map(&type_path, fields, cattrs, accessor);
     let visit_map = Stmts(visit_map);
 
     quote_block! {
@@ -379,10 +538,12 @@ fn field_i(i: usize) -> Ident {
     Ident::new(&format!("__field{}", i), Span::call_site())
 }
 
+/// Deserializes a struct from the `map` of a row, or from the `row` accessor if `accessor` is set.
 fn deserialize_map(
     struct_path: &TokenStream,
     fields: &[Field],
     cattrs: &attr::Container,
+    accessor: bool,
 ) -> Fragment {
     // Create the field names for the fields.
     let fields_names: Vec<_> = fields
@@ -405,9 +566,12 @@ fn deserialize_map(
                 }
             });
 
-    // Match arms to extract a value for a field.
-    let mut name_match_arms = Vec::with_capacity(fields_names.len());
-    let mut index_match_arms = Vec::with_capacity(fields_names.len());
+    // Patterns and bodies of the match arms to extract a value for a field, by column name (binding `full_name` if the flag is set)
+    // or by column index.
+    let mut name_match_arms: Vec<(TokenStream, TokenStream, bool)> =
+        Vec::with_capacity(fields_names.len());
+    let mut index_match_arms: Vec<(TokenStream, TokenStream)> =
+        Vec::with_capacity(fields_names.len());
 
     let mut nested_temp_decls = vec![];
     let mut nested_rectify = vec![];
@@ -436,20 +600,42 @@ fn deserialize_map(
                     let mut #deser_name_ext_iter: Vec<::std::vec::IntoIter<::klickhouse::Value>> = Vec::with_capacity(#size_field);
                     let mut #deser_name_ext_len: usize = 0;
                 });
-                name_match_arms.push(quote_spanned! { span=>
-                    full_name if full_name.starts_with(#deser_name_dotted) => {
+                // With custom column names, the inner names are found by the column names they map to
+                let (matches_name, inner_name) = match field.attrs.nested_naming() {
+                    Some(_) => {
+                        let deser_name_names = format_ident!("__ext_{deser_name}_names");
+                        let column_name = nested_column_name(field, quote! { &x });
+                        nested_temp_decls.push(quote_spanned! { span=>
+                            let #deser_name_names: Vec<(String, ::std::borrow::Cow<'static, str>)> = <#field_ty as ::klickhouse::Row>::column_names()
+                                .expect("column_names required for nested structure with custom column names")
+                                .into_iter()
+                                .map(|x| (#column_name, x))
+                                .collect();
+                        });
+                        (
+                            quote! { #deser_name_names.iter().any(|(name, _)| name == full_name) },
+                            quote! { #deser_name_names.iter().find(|(name, _)| name == full_name).map(|(_, x)| &**x).unwrap() },
+                        )
+                    }
+                    None => (
+                        quote! { full_name.starts_with(#deser_name_dotted) },
+                        quote! { full_name.strip_prefix(#deser_name_dotted).unwrap() },
+                    ),
+                };
+                name_match_arms.push((quote! { full_name if #matches_name }, quote_spanned! { span=>
+                    {
                         let values = _value.unarray().ok_or_else(|| ::klickhouse::KlickhouseError::UnexpectedTypeWithColumn(::std::borrow::Cow::Owned(full_name.to_string()), _type_.clone()))?;
                         if #deser_name_ext.is_empty() {
                             #deser_name_ext_len = values.len();
                         } else if #deser_name_ext_len != values.len() {
                             return ::klickhouse::Result::Err(::klickhouse::KlickhouseError::DeserializeError(format!("invalid length for nested columns, mismatches previous column {}: {} != {}", _name, #deser_name_ext_len, values.len())));
                         }
-                        #deser_name_ext.push((full_name.strip_prefix(#deser_name_dotted).unwrap(), _type_.unarray().map(|x| x.strip_low_cardinality()).ok_or_else(|| ::klickhouse::KlickhouseError::UnexpectedTypeWithColumn(::std::borrow::Cow::Owned(full_name.to_string()), _type_.clone()))?));
+                        #deser_name_ext.push((#inner_name, _type_.unarray().map(|x| x.strip_low_cardinality()).ok_or_else(|| ::klickhouse::KlickhouseError::UnexpectedTypeWithColumn(::std::borrow::Cow::Owned(full_name.to_string()), _type_.clone()))?));
                         #deser_name_ext_iter.push(values.into_iter());
                     }
-                });
-                index_match_arms.push(quote_spanned! { span=>
-                    x if x >= (#local_index) && x < (#current_index) => {
+                }, true));
+                index_match_arms.push((quote! { x if x >= (#local_index) && x < (#current_index) }, quote_spanned! { span=>
+                    {
                         let values = _value.unarray().ok_or_else(|| ::klickhouse::KlickhouseError::UnexpectedTypeWithColumn(::std::borrow::Cow::Owned(_name.to_string()), _type_.clone()))?;
                         if #deser_name_ext.is_empty() {
                             #deser_name_ext_len = values.len();
@@ -459,9 +645,10 @@ fn deserialize_map(
                         #deser_name_ext.push((_name, _type_.unarray().map(|x| x.strip_low_cardinality()).ok_or_else(|| ::klickhouse::KlickhouseError::UnexpectedTypeWithColumn(::std::borrow::Cow::Owned(_name.to_string()), _type_.clone()))?));
                         #deser_name_ext_iter.push(values.into_iter());
                     }
-                });
+                }));
+                // Without any of its columns, the nested field is missing rather than empty
                 nested_rectify.push(quote_spanned! { span=>
-                    {
+                    if !#deser_name_ext.is_empty() {
                         #name = ::std::option::Option::Some(::std::vec::Vec::with_capacity(#deser_name_ext_len));
                         'outer: loop {
                             let mut temp = ::std::vec::Vec::with_capacity(#size_field);
@@ -494,22 +681,16 @@ fn deserialize_map(
                     quote_spanned!(span=> #path(_type_.strip_low_cardinality(), _value)?)
                 }
             };
-            name_match_arms.push(quote_spanned! { span=>
-                #deser_name => {
+            let body = quote_spanned! { span=>
+                {
                     if ::std::option::Option::is_some(&#name) {
-                        return ::klickhouse::Result::Err(::klickhouse::KlickhouseError::DuplicateField(#deser_name));
-                    }
-                    #name = ::std::option::Option::Some(#visit);
-                }
-            });
-            index_match_arms.push(quote_spanned! { span=>
-                x if x == (#local_index) => {
-                        if ::std::option::Option::is_some(&#name) {
-                        return ::klickhouse::Result::Err(::klickhouse::KlickhouseError::DuplicateField(#deser_name));
+                        return ::klickhouse::Result::Err(::klickhouse::KlickhouseError::DuplicateColumn { name: ::std::borrow::Cow::Borrowed(#deser_name), index: _field_index });
                     }
                     #name = ::std::option::Option::Some(#visit);
                 }
-            });
+            };
+            name_match_arms.push((quote! { #deser_name }, body.clone(), false));
+            index_match_arms.push((quote! { x if x == (#local_index) }, body));
         });
 
     // Visit ignored values to consume them
@@ -525,13 +706,20 @@ fn deserialize_map(
         }
     };
 
-    let index_match_arm = if fields.iter().any(|f| f.attrs.flatten()) {
+    let has_flatten = fields.iter().any(|f| f.attrs.flatten());
+    let name_arms = name_match_arms
+        .iter()
+        .map(|(pattern, body, _)| quote! { #pattern => #body });
+    let index_arms = index_match_arms
+        .iter()
+        .map(|(pattern, body)| quote! { #pattern => #body });
+    let index_match_arm = if has_flatten {
         // Disable index-based matching with flattening
         quote! { {} }
     } else {
         quote! {
             match _field_index {
-                #(#index_match_arms)*
+                #(#index_arms)*
                 #ignored_arm
             }
         }
@@ -542,6 +730,9 @@ fn deserialize_map(
         let mut map = map;
         let mut map_flattened_fields = std::collections::HashMap::<&str, (&::klickhouse::Type, ::klickhouse::Value)>::default();
     }];
+    // With an accessor, the columns of flattened fields are found once per block, and their values are read in place.
+    let mut claim_flatten = vec![];
+    let mut accessor_flatten = vec![];
     for (f, _) in fields_names.iter() {
         if !f.attrs.flatten() {
             continue;
@@ -553,23 +744,142 @@ fn deserialize_map(
         // TODO: To give the actual field, we would need to change the type of
         //       KlickhouseError::MissingField from &'static str to Cow.
         let missing_col_error = format!("Flattened field {} has missing column", name);
+        // With a container default, a flattened field with missing columns is taken from the default instead.
+        let (declare_missing, on_missing) = match cattrs.default() {
+            attr::Default::None => (
+                None,
+                quote!(return ::klickhouse::Result::Err(::klickhouse::KlickhouseError::MissingField(#missing_col_error))),
+            ),
+            attr::Default::Default | attr::Default::Path(_) => {
+                let missing_flag = format_ident!("__flatten_missing_{}", name);
+                (
+                    Some(quote!(let mut #missing_flag = false;)),
+                    quote!(#missing_flag = true),
+                )
+            }
+        };
         pull_flatten.push(quote! {
+            #declare_missing
             for c in #ty::column_names()
                     .ok_or_else(|| ::klickhouse::KlickhouseError::DeserializeError(#missing_names_error.into()))? {
-                let idx = map.iter().enumerate().find(|(_, (c2,_,_))| c2 == &c)
-                                    .ok_or(::klickhouse::KlickhouseError::MissingField(#missing_col_error))?.0;
-                let (col, ty, val) = map.swap_remove(idx);
-                map_flattened_fields.insert(col, (ty, val));
+                match map.iter().enumerate().find(|(_, (c2,_,_))| c2 == &c) {
+                    ::std::option::Option::Some((idx, _)) => {
+                        let (col, ty, val) = map.swap_remove(idx);
+                        map_flattened_fields.insert(col, (ty, val));
+                    }
+                    ::std::option::Option::None => { #on_missing; }
+                }
+            }
+        });
+        claim_flatten.push(quote! {
+            {
+                let names = <#ty as ::klickhouse::Row>::column_names()
+                    .ok_or_else(|| ::klickhouse::KlickhouseError::DeserializeError(#missing_names_error.into()))?;
+                let mut columns = ::std::option::Option::Some(::std::vec::Vec::with_capacity(names.len()));
+                for c in names.iter() {
+                    match (0..row.len()).find(|&i| !__claimed[i] && row.name(i) == ::std::option::Option::Some(&**c)) {
+                        ::std::option::Option::Some(i) => {
+                            __claimed[i] = true;
+                            if let ::std::option::Option::Some(columns) = columns.as_mut() {
+                                columns.push(i);
+                            }
+                        }
+                        ::std::option::Option::None => columns = ::std::option::Option::None,
+                    }
+                }
+                __flattened.push(columns);
             }
         });
+        let flatten_index = accessor_flatten.len();
+        let flatten_value = format_ident!("__flatten_{}", name);
+        // Without a container default, the value is the field itself, otherwise `None` takes it from the default.
+        let (wrap, on_missing) = match cattrs.default() {
+            attr::Default::None => (None, on_missing),
+            attr::Default::Default | attr::Default::Path(_) => (
+                Some(quote!(::std::option::Option::Some)),
+                quote!(::std::option::Option::None),
+            ),
+        };
+        accessor_flatten.push(quote! {
+            let #flatten_value = match row.flattened(#flatten_index) {
+                ::std::option::Option::Some(inner) => #wrap(<#ty as ::klickhouse::Row>::deserialize_accessor(inner)?),
+                ::std::option::Option::None => #on_missing,
+            };
+        });
     }
 
-    let match_keys = quote! {
-        #[allow(unused_comparisons)]
-        for (_field_index, (_name, _type_, _value)) in map.into_iter().enumerate() {
-            match _name {
-                #(#name_match_arms)*
-                _ => #index_match_arm,
+    let match_keys = if accessor {
+        // Each column is matched to a field once per block, by the same rules as with `map`, and rows dispatch on the result.
+        let slot_names = name_match_arms
+            .iter()
+            .enumerate()
+            .map(|(slot, (pattern, _, _))| quote! { #pattern => #slot });
+        let name_slots = name_match_arms.len();
+        let slot_index = if has_flatten {
+            quote! { ::std::primitive::usize::MAX - 1 }
+        } else {
+            let slot_indices = index_match_arms
+                .iter()
+                .enumerate()
+                .map(|(slot, (pattern, _))| {
+                    let slot = name_slots + slot;
+                    quote! { #pattern => #slot }
+                });
+            quote! {
+                match _field_index {
+                    #(#slot_indices,)*
+                    _ => ::std::primitive::usize::MAX - 1,
+                }
+            }
+        };
+        let slot_bodies = name_match_arms
+            .iter()
+            .map(|(_, body, binds_name)| {
+                let bind = binds_name.then(|| quote! { let full_name = _name; });
+                quote! { { #bind #body } }
+            })
+            .chain(index_match_arms.iter().map(|(_, body)| body.clone()))
+            .enumerate()
+            .map(|(slot, body)| quote! { #slot => #body });
+        quote! {
+            let __lookup = row.field_lookup(|row| {
+                let mut __flattened = ::std::vec::Vec::new();
+                let mut __claimed = ::std::vec![false; row.len()];
+                #(#claim_flatten)*
+                let slots = (0..row.len())
+                    .map(|_field_index| {
+                        if __claimed[_field_index] {
+                            return ::std::primitive::usize::MAX;
+                        }
+                        #[allow(unused_comparisons)]
+                        match row.name(_field_index).unwrap_or_default() {
+                            #(#slot_names,)*
+                            _ => #slot_index,
+                        }
+                    })
+                    .collect();
+                ::klickhouse::Result::Ok(::klickhouse::FieldLookup { slots, flattened: __flattened })
+            })?;
+            #(#accessor_flatten)*
+            for (_field_index, (_name, _type_, _value)) in row.drain().enumerate() {
+                match __lookup.slots[_field_index] {
+                    #(#slot_bodies)*
+                    // taken by a flattened field
+                    ::std::primitive::usize::MAX => {}
+                    #ignored_arm
+                }
+            }
+        }
+    } else {
+        quote! {
+            #(#pull_flatten)*
+
+            #[allow(unused_comparisons)]
+            for (_field_index, (_name, _type_, _value)) in map.into_iter().enumerate() {
+                match _name {
+                    #(#name_arms)*
+                    _ => #index_match_arm,
+                }
             }
         }
     };
@@ -594,10 +904,32 @@ fn deserialize_map(
         if field.attrs.skip_deserializing() {
             let value = Expr(expr_is_missing(field, cattrs));
             quote!(#member: #value)
+        } else if field.attrs.flatten() && accessor {
+            let flatten_value =
+                format_ident!("__flatten_{}", field.original.ident.as_ref().unwrap());
+            match cattrs.default() {
+                attr::Default::None => quote!(#member: #flatten_value),
+                attr::Default::Default | attr::Default::Path(_) => quote! {
+                    #member: match #flatten_value {
+                        ::std::option::Option::Some(x) => x,
+                        ::std::option::Option::None => __default.#member,
+                    }
+                },
+            }
         } else if field.attrs.flatten() {
             let ty = field.ty;
+            let from_default = match cattrs.default() {
+                attr::Default::None => None,
+                attr::Default::Default | attr::Default::Path(_) => {
+                    let missing_flag = format_ident!(
+                        "__flatten_missing_{}",
+                        field.original.ident.as_ref().unwrap()
+                    );
+                    Some(quote!(if #missing_flag { __default.#member } else))
+                }
+            };
             quote! {
-                #member: {
+                #member: #from_default {
                 // Recreate map based on the subfield column names and recursive to deserialize it.
                 // The unwraps would have produced an error earlier.
                 // The map is guaranteed to contain values for all fields.
@@ -635,8 +967,6 @@ fn deserialize_map(
         #(#let_values)*
         #(#nested_temp_decls)*
 
-        #(#pull_flatten)*
-
         #match_keys
 
         #(#nested_rectify)*
@@ -5,17 +5,23 @@ use syn::{Ident, Path};
 pub struct Symbol(&'static str);
 
 pub const BOUND: Symbol = Symbol("bound");
+pub const DATETIME_OVERFLOW: Symbol = Symbol("datetime_overflow");
 pub const DEFAULT: Symbol = Symbol("default");
 pub const DENY_UNKNOWN_FIELDS: Symbol = Symbol("deny_unknown_fields");
+pub const ENUM_STRING: Symbol = Symbol("enum_string");
 pub const NESTED: Symbol = Symbol("nested");
+pub const NESTED_RENAME_ALL: Symbol = Symbol("nested_rename_all");
+pub const NESTED_SEPARATOR: Symbol = Symbol("nested_separator");
 pub const FLATTEN: Symbol = Symbol("flatten");
 pub const DESERIALIZE_WITH: Symbol = Symbol("deserialize_with");
 pub const FROM: Symbol = Symbol("from");
 pub const INTO: Symbol = Symbol("into");
+pub const PARSE: Symbol = Symbol("parse");
 pub const RENAME: Symbol = Symbol("rename");
 pub const RENAME_ALL: Symbol = Symbol("rename_all");
 pub const KLICKHOUSE: Symbol = Symbol("klickhouse");
 pub const SERIALIZE_WITH: Symbol = Symbol("serialize_with");
+pub const SIGN: Symbol = Symbol("sign");
 pub const SKIP: Symbol = Symbol("skip");
 pub const SKIP_DESERIALIZING: Symbol = Symbol("skip_deserializing");
 pub const SKIP_SERIALIZING: Symbol = Symbol("skip_serializing");
@@ -28,7 +34,7 @@ impl PartialEq<Symbol> for Ident {
     }
 }
 
-impl<'a> PartialEq<Symbol> for &'a Ident {
+impl PartialEq<Symbol> for &Ident {
     fn eq(&self, word: &Symbol) -> bool {
         *self == word.0
     }
@@ -40,7 +46,7 @@ impl PartialEq<Symbol> for Path {
     }
 }
 
-impl<'a> PartialEq<Symbol> for &'a Path {
+impl PartialEq<Symbol> for &Path {
     fn eq(&self, word: &Symbol) -> bool {
         self.is_ident(word.0)
     }